@@ -4,8 +4,7 @@ fn main() {
     println!("Testing crontab import...\n");
 
     // Remove existing file to test import
-    let home = dirs::home_dir().unwrap();
-    let cron_file = home.join(".cron-manager-crontab");
+    let cron_file = cron_manager::paths::storage_file(None).unwrap();
     if cron_file.exists() {
         std::fs::remove_file(&cron_file).ok();
         println!("Removed existing {}", cron_file.display());
@@ -6,7 +6,7 @@ fn main() {
 
     // 1. Load current entries
     println!("1. Loading current system scheduler...");
-    let mut storage = Storage::with_system_scheduler();
+    let storage = Storage::with_system_scheduler();
     let mut entries = storage.load().expect("Failed to load scheduler");
     println!("   Current entries: {}\n", entries.len());
 
@@ -18,7 +18,8 @@ fn main() {
         "/usr/bin/test_command.sh".to_string(),
     );
     entries.push(test_entry);
-    storage.save(&entries).expect("Failed to save");
+    let report = storage.save(&entries).expect("Failed to save");
+    assert!(report.is_complete(), "Entries not registered: {:?}", report.failed);
     println!("   Added: 'Test Entry from App'\n");
 
     // 3. Verify it was saved to system
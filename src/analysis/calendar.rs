@@ -0,0 +1,169 @@
+use super::simulate;
+use crate::cron_entry::CronEntry;
+use chrono::{DateTime, Datelike, Duration, Local, Months, NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc};
+use std::collections::BTreeMap;
+
+/// How much time the calendar shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalendarSpan {
+    /// Monday to Sunday, hour by hour
+    Week,
+    /// The whole month, day by day
+    Month,
+}
+
+/// Upcoming runs of one day, in local time
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarDay {
+    pub date: NaiveDate,
+    /// Runs starting in each hour of the day
+    pub hours: [usize; 24],
+    /// The most runs starting in one minute of each hour
+    pub hour_peaks: [usize; 24],
+}
+
+impl CalendarDay {
+    pub fn runs(&self) -> usize {
+        self.hours.iter().sum()
+    }
+
+    /// The most runs starting in one minute of the day
+    pub fn peak(&self) -> usize {
+        self.hour_peaks.iter().copied().max().unwrap_or(0)
+    }
+}
+
+/// The upcoming runs of the enabled entries over a week or a month
+#[derive(Debug, Clone, PartialEq)]
+pub struct Calendar {
+    pub span: CalendarSpan,
+    /// Every day shown, in order
+    pub days: Vec<CalendarDay>,
+    /// The minute the most entries start in, and those entries
+    pub busiest: Option<(NaiveDateTime, Vec<usize>)>,
+}
+
+/// The first day the span around `date` shows: the Monday of its week, or
+/// the first of its month
+pub fn span_start(span: CalendarSpan, date: NaiveDate) -> NaiveDate {
+    match span {
+        CalendarSpan::Week => date - Duration::days(date.weekday().num_days_from_monday().into()),
+        CalendarSpan::Month => date.with_day(1).unwrap_or(date),
+    }
+}
+
+/// The span before (`-1`) or after (`1`) the one around `date`
+pub fn shift(span: CalendarSpan, date: NaiveDate, by: i32) -> NaiveDate {
+    let start = span_start(span, date);
+    match span {
+        CalendarSpan::Week => start + Duration::weeks(by.into()),
+        CalendarSpan::Month if by < 0 => start - Months::new(by.unsigned_abs()),
+        CalendarSpan::Month => start + Months::new(by.unsigned_abs()),
+    }
+}
+
+/// Plot the runs of the enabled entries in the span around `date` that are
+/// still to come at `now`
+pub fn calendar(
+    entries: &[CronEntry],
+    configured_tz: Option<&str>,
+    span: CalendarSpan,
+    date: NaiveDate,
+    now: DateTime<Utc>,
+) -> Calendar {
+    let start = span_start(span, date);
+    let end = match span {
+        CalendarSpan::Week => start + Duration::weeks(1),
+        CalendarSpan::Month => start + Months::new(1),
+    };
+    let mut days: Vec<CalendarDay> = start
+        .iter_days()
+        .take_while(|day| *day < end)
+        .map(|date| CalendarDay { date, hours: [0; 24], hour_peaks: [0; 24] })
+        .collect();
+
+    let midnight = |date: NaiveDate| {
+        Local.from_local_datetime(&date.and_time(Default::default())).earliest().map(|time| time.with_timezone(&Utc))
+    };
+    let (Some(from), Some(until)) = (midnight(start), midnight(end)) else {
+        return Calendar { span, days, busiest: None };
+    };
+    let from = from.max(now);
+    if from >= until {
+        return Calendar { span, days, busiest: None };
+    }
+    let hours = (until - from).num_hours() as u32 + 1;
+    let simulation = simulate::simulate(entries, configured_tz, from, hours);
+
+    let mut minutes: BTreeMap<NaiveDateTime, Vec<usize>> = BTreeMap::new();
+    for run in &simulation.runs {
+        let time = run.time.with_timezone(&Local).naive_local();
+        if time.date() >= end {
+            break;
+        }
+        minutes.entry(time.with_second(0).unwrap_or(time)).or_default().push(run.entry);
+    }
+    for (minute, started) in &minutes {
+        let Some(day) = days.iter_mut().find(|day| day.date == minute.date()) else {
+            continue;
+        };
+        let hour = minute.hour() as usize;
+        day.hours[hour] += started.len();
+        day.hour_peaks[hour] = day.hour_peaks[hour].max(started.len());
+    }
+    // The earliest of the busiest minutes
+    let busiest = minutes
+        .into_iter()
+        .filter(|(_, started)| started.len() > 1)
+        .fold(None, |busiest: Option<(NaiveDateTime, Vec<usize>)>, (minute, started)| match busiest {
+            Some(best) if best.1.len() >= started.len() => Some(best),
+            _ => Some((minute, started)),
+        });
+    Calendar { span, days, busiest }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::entry;
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_spans() {
+        // A Wednesday
+        let day = date("2024-05-29");
+        assert_eq!(span_start(CalendarSpan::Week, day), date("2024-05-27"));
+        assert_eq!(span_start(CalendarSpan::Month, day), date("2024-05-01"));
+        assert_eq!(shift(CalendarSpan::Week, day, 1), date("2024-06-03"));
+        assert_eq!(shift(CalendarSpan::Month, day, -1), date("2024-04-01"));
+        assert_eq!(shift(CalendarSpan::Month, date("2024-01-31"), 1), date("2024-02-01"));
+    }
+
+    #[test]
+    fn test_calendar_counts_upcoming_runs() {
+        let entries = vec![entry("Backup", "0 2 * * *"), entry("Report", "0 2 * * 2"), entry("Sync", "30 * * * *")];
+        let monday = date("2024-05-27");
+        // Noon on Monday, local time: the Monday 02:00 runs are over
+        let now = Local.from_local_datetime(&monday.and_hms_opt(12, 0, 0).unwrap()).unwrap().with_timezone(&Utc);
+        let week = calendar(&entries, None, CalendarSpan::Week, monday, now);
+
+        assert_eq!(week.days.len(), 7);
+        assert_eq!(week.days[0].runs(), 12);
+        assert_eq!(week.days[0].hours[2], 0);
+        assert_eq!(week.days[1].runs(), 26);
+        assert_eq!(week.days[1].hours[2], 3);
+        assert_eq!(week.days[1].hour_peaks[2], 2);
+        assert_eq!(week.days[2].peak(), 1);
+
+        let month = calendar(&entries, None, CalendarSpan::Month, monday, now);
+        assert_eq!(month.days.len(), 31);
+        assert_eq!(month.days[0].runs(), 0);
+        // Tuesday's backup and report start together
+        let (minute, started) = month.busiest.unwrap();
+        assert_eq!(minute, date("2024-05-28").and_hms_opt(2, 0, 0).unwrap());
+        assert_eq!(started, vec![0, 1]);
+    }
+}
@@ -0,0 +1,275 @@
+use crate::config::CollisionConfig;
+use crate::cron_entry::CronEntry;
+use crate::schedule::CronSchedule;
+use chrono::{Duration, NaiveDateTime, Timelike};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Two entries that repeatedly fire within the collision window of each other
+#[derive(Debug, Clone, PartialEq)]
+pub struct Collision {
+    /// Indices into the analyzed entry slice, lower index first
+    pub entries: (usize, usize),
+    /// How many times they coincide within the analysis horizon
+    pub occurrences: usize,
+    /// The first coinciding run
+    pub first: NaiveDateTime,
+}
+
+/// A window in which at least `cluster_size` distinct entries fire
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cluster {
+    pub start: NaiveDateTime,
+    pub entries: Vec<usize>,
+}
+
+/// The same set of entries starting together, however often it happens
+#[derive(Debug, Clone, PartialEq)]
+pub struct BusyMinute {
+    pub entries: Vec<usize>,
+    /// The first time they start together
+    pub first: NaiveDateTime,
+    /// How many times they start together within the horizon
+    pub occurrences: usize,
+}
+
+/// Where the runs pile up over the horizon
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Busiest {
+    /// Sets of two or more entries starting in the same minute, most entries first
+    pub minutes: Vec<BusyMinute>,
+    /// Runs starting in each hour of the day (local time), summed over the horizon
+    pub hours: [usize; 24],
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CollisionReport {
+    pub collisions: Vec<Collision>,
+    pub clusters: Vec<Cluster>,
+}
+
+impl CollisionReport {
+    /// Indices of entries involved in any collision or cluster
+    pub fn flagged(&self) -> BTreeSet<usize> {
+        let mut flagged = BTreeSet::new();
+        for collision in &self.collisions {
+            flagged.insert(collision.entries.0);
+            flagged.insert(collision.entries.1);
+        }
+        for cluster in &self.clusters {
+            flagged.extend(cluster.entries.iter().copied());
+        }
+        flagged
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.collisions.is_empty() && self.clusters.is_empty()
+    }
+}
+
+/// Find enabled entries that fire at (nearly) the same time, and windows
+/// where many jobs pile up, over `settings.horizon_hours` starting at `from`
+pub fn analyze(entries: &[CronEntry], from: NaiveDateTime, settings: &CollisionConfig) -> CollisionReport {
+    let runs = runs(entries, from, settings.horizon_hours);
+    CollisionReport {
+        collisions: find_collisions(&runs, settings.window_minutes.max(1)),
+        clusters: find_clusters(&runs, settings.cluster_window_minutes.max(1), settings.cluster_size),
+    }
+}
+
+/// Every firing minute of the enabled entries within the horizon, mapped to
+/// the entries firing then
+fn runs(entries: &[CronEntry], from: NaiveDateTime, horizon_hours: u32) -> BTreeMap<NaiveDateTime, Vec<usize>> {
+    let until = from + Duration::hours(horizon_hours as i64);
+    let mut runs: BTreeMap<NaiveDateTime, Vec<usize>> = BTreeMap::new();
+    for (index, entry) in entries.iter().enumerate() {
+        if !entry.enabled {
+            continue;
+        }
+        let Ok(schedule) = CronSchedule::parse(&entry.schedule) else {
+            continue;
+        };
+        for time in schedule.upcoming(from).take_while(|t| *t < until) {
+            runs.entry(time).or_default().push(index);
+        }
+    }
+    runs
+}
+
+/// The first minute within the horizon in which the entry at `index` starts
+/// along with more than `settings.max_per_minute` jobs, and all of them
+pub fn crowded_minute(
+    entries: &[CronEntry],
+    index: usize,
+    from: NaiveDateTime,
+    settings: &CollisionConfig,
+) -> Option<(NaiveDateTime, Vec<usize>)> {
+    runs(entries, from, settings.horizon_hours)
+        .into_iter()
+        .find(|(_, started)| started.len() > settings.max_per_minute && started.contains(&index))
+}
+
+/// The minutes and hours of the day in which the most jobs start
+pub fn busiest(entries: &[CronEntry], from: NaiveDateTime, settings: &CollisionConfig) -> Busiest {
+    let mut busiest = Busiest::default();
+    let mut minutes: HashMap<Vec<usize>, BusyMinute> = HashMap::new();
+    for (time, started) in runs(entries, from, settings.horizon_hours) {
+        busiest.hours[time.hour() as usize] += started.len();
+        if started.len() > 1 {
+            minutes
+                .entry(started.clone())
+                .and_modify(|minute| minute.occurrences += 1)
+                .or_insert(BusyMinute { entries: started, first: time, occurrences: 1 });
+        }
+    }
+    busiest.minutes = minutes.into_values().collect();
+    busiest.minutes.sort_by(|a, b| {
+        b.entries.len().cmp(&a.entries.len()).then(b.occurrences.cmp(&a.occurrences)).then(a.first.cmp(&b.first))
+    });
+    busiest
+}
+
+fn find_collisions(runs: &BTreeMap<NaiveDateTime, Vec<usize>>, window: u32) -> Vec<Collision> {
+    let mut pairs: HashMap<(usize, usize), Collision> = HashMap::new();
+    let mut record = |a: usize, b: usize, time: NaiveDateTime| {
+        let key = (a.min(b), a.max(b));
+        pairs
+            .entry(key)
+            .and_modify(|c| c.occurrences += 1)
+            .or_insert(Collision { entries: key, occurrences: 1, first: time });
+    };
+
+    for (time, indices) in runs {
+        for (i, a) in indices.iter().enumerate() {
+            // Same minute
+            for b in &indices[i + 1..] {
+                record(*a, *b, *time);
+            }
+            // Later minutes still inside the window
+            let end = *time + Duration::minutes(window as i64);
+            for (_, later) in runs.range(*time + Duration::minutes(1)..end) {
+                for b in later.iter().filter(|b| *b != a) {
+                    record(*a, *b, *time);
+                }
+            }
+        }
+    }
+
+    let mut collisions: Vec<Collision> = pairs.into_values().collect();
+    collisions.sort_by(|a, b| b.occurrences.cmp(&a.occurrences).then(a.first.cmp(&b.first)));
+    collisions
+}
+
+fn find_clusters(
+    runs: &BTreeMap<NaiveDateTime, Vec<usize>>,
+    window: u32,
+    cluster_size: usize,
+) -> Vec<Cluster> {
+    let mut clusters = Vec::new();
+    if cluster_size < 2 {
+        return clusters;
+    }
+
+    let mut skip_until: Option<NaiveDateTime> = None;
+    for start in runs.keys() {
+        if skip_until.is_some_and(|t| *start < t) {
+            continue;
+        }
+        let end = *start + Duration::minutes(window as i64);
+        let entries: BTreeSet<usize> = runs
+            .range(*start..end)
+            .flat_map(|(_, indices)| indices.iter().copied())
+            .collect();
+
+        if entries.len() >= cluster_size {
+            clusters.push(Cluster {
+                start: *start,
+                entries: entries.into_iter().collect(),
+            });
+            // Report each pile-up once rather than once per minute inside it
+            skip_until = Some(end);
+        }
+    }
+
+    clusters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::entry;
+
+    fn start() -> NaiveDateTime {
+        NaiveDateTime::parse_from_str("2024-01-01 00:00", "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_same_minute_collision() {
+        let entries = vec![
+            entry("backup", "0 2 * * *"),
+            entry("cleanup", "0 2 * * *"),
+            entry("report", "0 9 * * *"),
+        ];
+        let settings = CollisionConfig { horizon_hours: 24, ..Default::default() };
+
+        let report = analyze(&entries, start(), &settings);
+        assert_eq!(report.collisions.len(), 1);
+        assert_eq!(report.collisions[0].entries, (0, 1));
+        assert_eq!(report.collisions[0].occurrences, 1);
+        assert!(!report.flagged().contains(&2));
+    }
+
+    #[test]
+    fn test_collision_window() {
+        let entries = vec![entry("a", "0 2 * * *"), entry("b", "2 2 * * *")];
+        let narrow = CollisionConfig { horizon_hours: 24, window_minutes: 1, ..Default::default() };
+        let wide = CollisionConfig { horizon_hours: 24, window_minutes: 5, ..Default::default() };
+
+        assert!(analyze(&entries, start(), &narrow).collisions.is_empty());
+        assert_eq!(analyze(&entries, start(), &wide).collisions.len(), 1);
+    }
+
+    #[test]
+    fn test_clusters() {
+        let entries = vec![
+            entry("a", "0 2 * * *"),
+            entry("b", "1 2 * * *"),
+            entry("c", "3 2 * * *"),
+            entry("d", "30 2 * * *"),
+        ];
+        let settings = CollisionConfig {
+            horizon_hours: 24,
+            cluster_size: 3,
+            cluster_window_minutes: 5,
+            ..Default::default()
+        };
+
+        let report = analyze(&entries, start(), &settings);
+        assert_eq!(report.clusters.len(), 1);
+        assert_eq!(report.clusters[0].entries, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_crowded_minute_and_busiest() {
+        let entries = vec![
+            entry("backup", "0 2 * * *"),
+            entry("cleanup", "0 2 * * *"),
+            entry("report", "0 2 * * 1"),
+            entry("sync", "0 * * * *"),
+        ];
+        // 2024-01-01 is a Monday
+        let settings = CollisionConfig { horizon_hours: 48, max_per_minute: 3, ..Default::default() };
+
+        let (minute, started) = crowded_minute(&entries, 2, start(), &settings).unwrap();
+        assert_eq!(minute, start() + Duration::hours(2));
+        assert_eq!(started, vec![0, 1, 2, 3]);
+        assert_eq!(crowded_minute(&entries, 1, start() + Duration::days(1), &settings), None);
+
+        let busiest = busiest(&entries, start(), &settings);
+        assert_eq!(busiest.hours[2], 7);
+        assert_eq!(busiest.hours[3], 2);
+        assert_eq!(busiest.minutes.len(), 2);
+        assert_eq!(busiest.minutes[0].entries, vec![0, 1, 2, 3]);
+        assert_eq!(busiest.minutes[1].entries, vec![0, 1, 3]);
+        assert_eq!(busiest.minutes[1].occurrences, 1);
+    }
+}
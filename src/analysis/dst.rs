@@ -0,0 +1,150 @@
+use crate::schedule::compress_values;
+use crate::schedule::timezone::{ScheduleZone, WallClock};
+use crate::schedule::CronSchedule;
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use std::collections::BTreeSet;
+
+/// How many days ahead to look for DST transitions
+const HORIZON_DAYS: i64 = 366;
+
+/// Firing times on one DST transition day that are skipped or run twice
+#[derive(Debug, Clone, PartialEq)]
+pub struct DstIssue {
+    pub date: NaiveDate,
+    pub kind: WallClock,
+    pub times: Vec<NaiveTime>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DstReport {
+    pub issues: Vec<DstIssue>,
+    /// Hours of the day untouched by any transition in the horizon
+    pub safe_hours: Vec<u32>,
+}
+
+impl DstReport {
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// A one-line description of each issue with the safe hours suggestion
+    pub fn messages(&self, zone: &ScheduleZone) -> Vec<String> {
+        let safe = compress_values(&self.safe_hours);
+        self.issues
+            .iter()
+            .map(|issue| {
+                let times: Vec<String> = issue.times.iter().map(|t| t.format("%H:%M").to_string()).collect();
+                let what = match issue.kind {
+                    WallClock::Skipped => "is skipped when clocks jump forward",
+                    _ => "occurs twice when clocks fall back",
+                };
+                format!(
+                    "Run at {} on {} {} ({}); DST-safe hours: {}",
+                    times.join(", "),
+                    issue.date,
+                    what,
+                    zone,
+                    safe
+                )
+            })
+            .collect()
+    }
+}
+
+/// Find runs of `schedule` that fall into DST gaps or overlaps in `zone`
+/// during the year after `from`
+pub fn check(schedule: &CronSchedule, zone: &ScheduleZone, from: NaiveDate) -> DstReport {
+    let mut report = DstReport::default();
+    let mut affected_hours = BTreeSet::new();
+
+    for date in transition_days(zone, from) {
+        let mut skipped = Vec::new();
+        let mut repeated = Vec::new();
+
+        for minute in 0..24 * 60 {
+            let time = date.and_time(NaiveTime::MIN) + Duration::minutes(minute);
+            let kind = zone.classify(time);
+            if kind == WallClock::Unique {
+                continue;
+            }
+            affected_hours.insert(time.hour());
+            if schedule.matches(&time) {
+                match kind {
+                    WallClock::Skipped => skipped.push(time.time()),
+                    _ => repeated.push(time.time()),
+                }
+            }
+        }
+
+        for (kind, times) in [(WallClock::Skipped, skipped), (WallClock::Repeated, repeated)] {
+            if !times.is_empty() {
+                report.issues.push(DstIssue { date, kind, times });
+            }
+        }
+    }
+
+    report.safe_hours = (0..24).filter(|h| !affected_hours.contains(h)).collect();
+    report
+}
+
+/// Days on which the zone's UTC offset changes
+fn transition_days(zone: &ScheduleZone, from: NaiveDate) -> Vec<NaiveDate> {
+    let noon = |date: NaiveDate| -> NaiveDateTime { date.and_hms_opt(12, 0, 0).unwrap() };
+    let mut days = Vec::new();
+
+    for offset in 0..HORIZON_DAYS {
+        let date = from + Duration::days(offset);
+        let next = date + Duration::days(1);
+        if zone.offset_at(noon(date)) != zone.offset_at(noon(next)) {
+            // The change happened between the two noons; check both days
+            days.push(date);
+            days.push(next);
+        }
+    }
+
+    days
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_york() -> ScheduleZone {
+        ScheduleZone::resolve(Some("America/New_York"), None).unwrap()
+    }
+
+    fn jan_1() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()
+    }
+
+    #[test]
+    fn test_spring_forward_skip() {
+        let schedule = CronSchedule::parse("30 2 * * *").unwrap();
+        let report = check(&schedule, &new_york(), jan_1());
+
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].kind, WallClock::Skipped);
+        assert_eq!(report.issues[0].date, NaiveDate::from_ymd_opt(2024, 3, 10).unwrap());
+        assert!(!report.safe_hours.contains(&2));
+        assert!(report.safe_hours.contains(&3));
+    }
+
+    #[test]
+    fn test_fall_back_repeat() {
+        let schedule = CronSchedule::parse("30 1 * * *").unwrap();
+        let report = check(&schedule, &new_york(), jan_1());
+
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].kind, WallClock::Repeated);
+    }
+
+    #[test]
+    fn test_safe_schedule() {
+        let schedule = CronSchedule::parse("0 4 * * *").unwrap();
+        assert!(check(&schedule, &new_york(), jan_1()).is_empty());
+
+        let tokyo = ScheduleZone::resolve(Some("Asia/Tokyo"), None).unwrap();
+        let risky = CronSchedule::parse("30 2 * * *").unwrap();
+        assert!(check(&risky, &tokyo, jan_1()).is_empty());
+    }
+}
@@ -0,0 +1,92 @@
+use crate::schedule::CronSchedule;
+use chrono::{Duration, NaiveDateTime};
+
+/// How busy a schedule is, for coloring it in the table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Density {
+    /// At most once a day (daily, weekly, monthly...)
+    Daily,
+    /// More than once a day, up to hourly
+    Hourly,
+    /// More often than hourly, up to every minute
+    Minutely,
+}
+
+/// How often a schedule fires, counted over the coming day and week
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Frequency {
+    /// Runs in the 24 hours after the starting point
+    pub next_day: usize,
+    /// Runs in the 7 days after the starting point
+    pub next_week: usize,
+    /// The most runs in any single 24-hour slice of that week
+    pub peak_per_day: usize,
+}
+
+impl Frequency {
+    pub fn estimate(schedule: &CronSchedule, from: NaiveDateTime) -> Self {
+        let mut per_day = [0usize; 7];
+
+        // `upcoming` is exclusive, so start a minute early to count `from` itself
+        let start = from - Duration::minutes(1);
+        for time in schedule.upcoming(start).take_while(|t| *t < from + Duration::days(7)) {
+            let day = ((time - from).num_minutes() / (24 * 60)) as usize;
+            per_day[day.min(6)] += 1;
+        }
+
+        Self {
+            next_day: per_day[0],
+            next_week: per_day.iter().sum(),
+            peak_per_day: per_day.iter().copied().max().unwrap_or(0),
+        }
+    }
+
+    /// Bucket by the busiest day, so a job that runs every minute only on
+    /// Sundays still counts as minutely
+    pub fn density(&self) -> Density {
+        match self.peak_per_day {
+            0..=1 => Density::Daily,
+            2..=24 => Density::Hourly,
+            _ => Density::Minutely,
+        }
+    }
+
+    /// A short human-readable summary, e.g. "24 runs in the next 24h, 168 in 7d"
+    pub fn summary(&self) -> String {
+        format!(
+            "{} run{} in the next 24h, {} in 7d",
+            self.next_day,
+            if self.next_day == 1 { "" } else { "s" },
+            self.next_week
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn estimate(expr: &str) -> Frequency {
+        let from = NaiveDateTime::parse_from_str("2024-01-01 00:00", "%Y-%m-%d %H:%M").unwrap();
+        Frequency::estimate(&CronSchedule::parse(expr).unwrap(), from)
+    }
+
+    #[test]
+    fn test_estimate() {
+        let every_minute = estimate("* * * * *");
+        assert_eq!(every_minute.next_day, 1440);
+        assert_eq!(every_minute.next_week, 1440 * 7);
+
+        let hourly = estimate("0 * * * *");
+        assert_eq!(hourly.next_day, 24);
+        assert_eq!(hourly.density(), Density::Hourly);
+        assert_eq!(every_minute.density(), Density::Minutely);
+        assert_eq!(estimate("0 0 1 * *").density(), Density::Daily);
+
+        // 2024-01-01 is a Monday
+        let sundays = estimate("*/10 * * * 0");
+        assert_eq!(sundays.next_day, 0);
+        assert_eq!(sundays.peak_per_day, 144);
+        assert_eq!(sundays.density(), Density::Minutely);
+    }
+}
@@ -0,0 +1,321 @@
+use crate::analysis::dst;
+use crate::analysis::frequency::Frequency;
+use crate::config::Config;
+use crate::cron_entry::CronEntry;
+use crate::schedule::timezone::ScheduleZone;
+use crate::schedule::CronSchedule;
+use crate::secrets;
+use crate::shell::{self, Shell};
+use crate::storage::Storage;
+use chrono::NaiveDateTime;
+use std::path::{Path, PathBuf};
+
+/// PATH that cron (and launchd) give jobs unless the crontab overrides it
+const SCHEDULER_PATH: &[&str] = &["/usr/bin", "/bin", "/usr/sbin", "/sbin"];
+
+/// Shell builtins and keywords that never resolve to an executable
+const SHELL_BUILTINS: &[&str] = &[
+    "cd", "echo", "test", "[", "export", "source", ".", "exec", "eval", "set", "true", "false",
+    "if", "for", "while", "case", "exit", "umask", "ulimit", "printf", "read", ":",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn label(self) -> &'static str {
+        match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+/// A single lint finding for one entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// Index into the linted entry slice
+    pub entry: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Lint all entries against the given storage backend
+pub fn lint(entries: &[CronEntry], storage: &Storage, config: &Config, now: NaiveDateTime) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    for (index, entry) in entries.iter().enumerate() {
+        let mut report = |severity, message: String| {
+            findings.push(Finding { entry: index, severity, message });
+        };
+
+        for (severity, message) in lint_schedule(entry, storage, config, now) {
+            report(severity, message);
+        }
+        // Check the command the scheduler will actually run
+        match storage.resolve_scripts(&entry.command) {
+            Ok(command) => {
+                for (severity, message) in lint_command(&command, storage.get_backend_name(), entry.shell) {
+                    report(severity, message);
+                }
+            }
+            Err(e) => report(Severity::Error, e.to_string()),
+        }
+    }
+
+    findings
+}
+
+fn lint_schedule(
+    entry: &CronEntry,
+    storage: &Storage,
+    config: &Config,
+    now: NaiveDateTime,
+) -> Vec<(Severity, String)> {
+    let mut findings = Vec::new();
+
+    match CronSchedule::parse(&entry.schedule) {
+        Err(e) => findings.push((Severity::Error, format!("Invalid schedule: {}", e))),
+        // Runs at startup only, so there's no time of day to check
+        Ok(schedule) if schedule.reboot => {}
+        Ok(schedule) => {
+            if schedule.next_after(now).is_none() {
+                findings.push((
+                    Severity::Error,
+                    "Schedule never fires (the day and month can never coincide)".to_string(),
+                ));
+            }
+            let frequency = Frequency::estimate(&schedule, now);
+            if frequency.peak_per_day > config.frequency.warn_runs_per_day {
+                findings.push((
+                    Severity::Warning,
+                    format!(
+                        "Runs {} times per day; check the minute/hour fields for a typo",
+                        frequency.peak_per_day
+                    ),
+                ));
+            }
+            match ScheduleZone::resolve(entry.timezone.as_deref(), config.timezone.as_deref()) {
+                // Jobs that run every hour just move to the next one; only
+                // fixed-hour schedules are hurt by DST gaps and overlaps
+                Ok(zone) if schedule.hours.len() < 24 => {
+                    let report = dst::check(&schedule, &zone, now.date());
+                    for message in report.messages(&zone) {
+                        findings.push((Severity::Warning, message));
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => findings.push((Severity::Error, e.to_string())),
+            }
+            if let Err(e) = storage.check_schedule(&entry.schedule) {
+                findings.push((
+                    Severity::Error,
+                    format!("Not supported by the {} backend: {}", storage.get_backend_name(), e),
+                ));
+            }
+        }
+    }
+
+    findings
+}
+
+fn lint_command(command: &str, backend: &str, shell: Option<Shell>) -> Vec<(Severity, String)> {
+    let mut findings = Vec::new();
+    let cron_syntax = !matches!(backend, "Launchd" | "Systemd");
+
+    if let Some(shell) = shell {
+        findings.extend(lint_program_for(shell, command));
+    } else if let Some(program) = program_name(command) {
+        if program.contains('/') && !program.starts_with('/') && !program.starts_with('~') {
+            findings.push((
+                Severity::Warning,
+                format!("Relative path '{}' depends on the working directory (jobs start in $HOME)", program),
+            ));
+        } else if program.contains('/') {
+            let path = expand_home(program);
+            if !path.exists() {
+                findings.push((Severity::Error, format!("Executable '{}' does not exist", program)));
+            } else if !is_executable(&path) {
+                findings.push((Severity::Warning, format!("'{}' is not executable", program)));
+            }
+        } else if !SHELL_BUILTINS.contains(&program) && find_in(SCHEDULER_PATH.iter().map(PathBuf::from), program).is_none() {
+            let user_path = std::env::var_os("PATH")
+                .map(|p| std::env::split_paths(&p).collect::<Vec<_>>())
+                .unwrap_or_default();
+            match find_in(user_path.into_iter(), program) {
+                Some(found) => findings.push((
+                    Severity::Warning,
+                    format!(
+                        "'{}' is not on the scheduler's default PATH; use the full path {}",
+                        program,
+                        found.display()
+                    ),
+                )),
+                None => findings.push((Severity::Error, format!("Command '{}' not found", program))),
+            }
+        }
+    }
+
+    if cron_syntax && !command.contains('>') && !pipes_output(command) {
+        findings.push((
+            Severity::Warning,
+            "Output is not redirected; cron will email it (redirect to a file or /dev/null)".to_string(),
+        ));
+    }
+
+    if cron_syntax && has_unescaped_percent(command) {
+        findings.push((
+            Severity::Warning,
+            "Unescaped '%' is treated as a newline by cron (write \\% instead)".to_string(),
+        ));
+    }
+
+    if secrets::contains_secret(command) {
+        findings.push((
+            Severity::Warning,
+            "Command appears to contain a plaintext secret (use !secret NAME instead)".to_string(),
+        ));
+    }
+
+    findings
+}
+
+/// Program checks for commands run by another interpreter, whose cmdlets,
+/// builtins and PATH can't be looked up from here
+fn lint_program_for(shell: Shell, command: &str) -> Vec<(Severity, String)> {
+    if let Some(path) = shell.unquoted_path_with_spaces(command) {
+        return vec![(
+            Severity::Error,
+            format!("'{}' contains spaces and must be quoted: {}", path, shell.quote_path(path)),
+        )];
+    }
+    let Some(program) = shell.program(command) else {
+        return Vec::new();
+    };
+    if shell::is_windows_absolute(program) {
+        // Only checkable where WSL mounts the drive
+        match shell::wsl_path(program) {
+            Some(path) if !path.exists() => vec![(Severity::Error, format!("Executable '{}' does not exist", program))],
+            _ => Vec::new(),
+        }
+    } else if program.contains(['\\', '/']) && !program.starts_with(['/', '~', '$']) {
+        vec![(Severity::Warning, format!("Relative path '{}' depends on the working directory", program))]
+    } else {
+        Vec::new()
+    }
+}
+
+/// The program the command runs, skipping leading `VAR=value` assignments
+pub(crate) fn program_name(command: &str) -> Option<&str> {
+    let is_assignment = |word: &&str| word.contains('=') && !word.starts_with('=');
+    let word = command.split_whitespace().find(|word| !is_assignment(word))?;
+    let program = match word.chars().next() {
+        // A quoted path may contain spaces, so read up to the closing quote
+        Some(quote @ ('"' | '\'')) => {
+            let start = word.as_ptr() as usize - command.as_ptr() as usize + 1;
+            let end = command[start..].find(quote).map_or(command.len(), |i| start + i);
+            &command[start..end]
+        }
+        _ => word,
+    };
+    // Subshells, groups and the like are beyond a simple lint
+    if program.is_empty() || program.starts_with(['(', '{', '$', '`']) {
+        return None;
+    }
+    Some(program)
+}
+
+/// Whether the output goes into a pipe (`cmd | logger`), which is as good
+/// as redirecting it; `||` is not a pipe
+fn pipes_output(command: &str) -> bool {
+    let chars: Vec<char> = command.chars().collect();
+    (0..chars.len()).any(|i| chars[i] == '|' && chars.get(i + 1) != Some(&'|') && (i == 0 || chars[i - 1] != '|'))
+}
+
+fn has_unescaped_percent(command: &str) -> bool {
+    let mut escaped = false;
+    for c in command.chars() {
+        match c {
+            '%' if !escaped => return true,
+            '\\' => escaped = !escaped,
+            _ => escaped = false,
+        }
+    }
+    false
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+fn find_in(dirs: impl Iterator<Item = PathBuf>, program: &str) -> Option<PathBuf> {
+    dirs.map(|dir| dir.join(program)).find(|path| is_executable(path))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(command: &str) -> Vec<String> {
+        lint_command(command, "Cron", None).into_iter().map(|(_, m)| m).collect()
+    }
+
+    #[test]
+    fn test_relative_and_missing_paths() {
+        assert!(messages("./backup.sh > /dev/null")[0].contains("Relative path"));
+        assert!(messages("/nonexistent/tool > /dev/null")[0].contains("does not exist"));
+        assert!(messages("definitely-not-a-command-xyz > /dev/null")[0].contains("not found"));
+        assert!(messages("/bin/sh -c true > /dev/null").is_empty());
+        assert!(messages("'/nonexistent/My Scripts/job.sh' -v > /dev/null")[0]
+            .contains("'/nonexistent/My Scripts/job.sh' does not exist"));
+    }
+
+    #[test]
+    fn test_cron_specific_checks() {
+        let found = messages("/bin/sh -c 'date +%F'");
+        assert!(found.iter().any(|m| m.contains("email")));
+        assert!(found.iter().any(|m| m.contains("'%'")));
+
+        assert!(messages("/bin/sh -c 'date +\\%F' > /dev/null").is_empty());
+        assert!(messages("/bin/sh -c true | /bin/cat").is_empty());
+        assert!(messages("/bin/sh -c true || /bin/sh -c false").iter().any(|m| m.contains("email")));
+        assert!(lint_command("/bin/sh -c 'date +%F'", "Launchd", None).is_empty());
+        assert!(messages("/bin/sh --password hunter2 > /dev/null")[0].contains("plaintext secret"));
+    }
+
+    #[test]
+    fn test_other_shells() {
+        let windows = |command: &str| lint_command(command, "Launchd", Some(Shell::Cmd));
+        assert!(windows(r"C:\Program Files\App\run.exe /all")[0].1.contains(r#"must be quoted: "C:\Program Files\App\run.exe""#));
+        assert!(windows(r#""C:\Program Files\App\run.exe" /all"#).is_empty());
+        assert!(windows(r"scripts\backup.bat")[0].1.contains("Relative path"));
+        // Builtins and cmdlets aren't looked up
+        assert!(windows("dir").is_empty());
+        assert!(lint_command("Get-ChildItem > $null", "Cron", Some(Shell::Pwsh)).is_empty());
+    }
+
+    #[test]
+    fn test_program_name() {
+        assert_eq!(program_name("FOO=1 BAR=2 /usr/bin/env"), Some("/usr/bin/env"));
+        assert_eq!(program_name("(cd /tmp && ls)"), None);
+    }
+}
@@ -0,0 +1,6 @@
+pub mod calendar;
+pub mod collisions;
+pub mod dst;
+pub mod frequency;
+pub mod lint;
+pub mod simulate;
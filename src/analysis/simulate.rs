@@ -0,0 +1,103 @@
+use crate::cron_entry::CronEntry;
+use crate::schedule::timezone::ScheduleZone;
+use crate::schedule::CronSchedule;
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+
+/// Longest window a simulation may cover, to keep every-minute jobs bounded
+pub const MAX_HOURS: u32 = 31 * 24;
+
+/// One execution the scheduler would start
+#[derive(Debug, Clone, PartialEq)]
+pub struct Run {
+    /// Wall-clock time in the entry's own timezone
+    pub time: DateTime<FixedOffset>,
+    /// Index into the simulated entry slice
+    pub entry: usize,
+}
+
+/// A dry run of every enabled entry's schedule over a time window
+#[derive(Debug, Clone, PartialEq)]
+pub struct Simulation {
+    pub from: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    /// All runs in the window, in the order they would start
+    pub runs: Vec<Run>,
+    /// Run counts per enabled entry, most frequent first
+    pub counts: Vec<(usize, usize)>,
+    /// Enabled entries left out because their schedule or timezone is invalid
+    pub skipped: Vec<(usize, String)>,
+}
+
+/// List every execution of the enabled entries from `from` (inclusive) to
+/// `from + hours` (exclusive), without running anything
+pub fn simulate(entries: &[CronEntry], configured_tz: Option<&str>, from: DateTime<Utc>, hours: u32) -> Simulation {
+    let until = from + Duration::hours(hours.min(MAX_HOURS) as i64);
+    let mut simulation = Simulation {
+        from,
+        until,
+        runs: Vec::new(),
+        counts: Vec::new(),
+        skipped: Vec::new(),
+    };
+
+    for (index, entry) in entries.iter().enumerate() {
+        if !entry.enabled {
+            continue;
+        }
+        let zone = ScheduleZone::resolve(entry.timezone.as_deref(), configured_tz);
+        let schedule = CronSchedule::parse(&entry.schedule);
+        let (schedule, zone) = match (schedule, zone) {
+            (Ok(schedule), Ok(zone)) => (schedule, zone),
+            (Err(e), _) | (_, Err(e)) => {
+                simulation.skipped.push((index, e.to_string()));
+                continue;
+            }
+        };
+
+        // `runs_between` starts strictly after its first argument
+        let runs = zone.runs_between(&schedule, from - Duration::seconds(1), until);
+        simulation.counts.push((index, runs.len()));
+        simulation.runs.extend(runs.into_iter().map(|time| Run { time, entry: index }));
+    }
+
+    simulation.runs.sort_by_key(|run| (run.time, run.entry));
+    simulation.counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    simulation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::entry;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_runs_in_order_with_counts() {
+        let mut disabled = entry("Disabled", "* * * * *");
+        disabled.enabled = false;
+        let mut tokyo = entry("Tokyo", "0 9 * * *");
+        tokyo.timezone = Some("Asia/Tokyo".to_string());
+        let entries = vec![entry("Six-hourly", "0 */6 * * *"), disabled, tokyo, entry("Bad", "61 * * * *")];
+
+        let simulation = simulate(&entries, Some("UTC"), utc("2023-12-31T23:30:00Z"), 24);
+
+        // Tokyo's 09:00 is 00:00 UTC, tied with the six-hourly run and ordered by entry
+        assert_eq!(simulation.counts, [(0, 4), (2, 1)]);
+        let order: Vec<(String, usize)> = simulation
+            .runs
+            .iter()
+            .map(|run| (run.time.with_timezone(&Utc).format("%H:%M").to_string(), run.entry))
+            .collect();
+        let expected = [("00:00", 0), ("00:00", 2), ("06:00", 0), ("12:00", 0), ("18:00", 0)];
+        assert_eq!(order, expected.map(|(time, entry)| (time.to_string(), entry)));
+        assert_eq!(simulation.skipped.len(), 1);
+        assert_eq!(simulation.skipped[0].0, 3);
+
+        // A run exactly at the start of the window is included
+        let simulation = simulate(&entries[..1], Some("UTC"), utc("2024-01-01T00:00:00Z"), 1);
+        assert_eq!(simulation.runs.len(), 1);
+    }
+}
@@ -1,6 +1,51 @@
-use crate::cron_entry::CronEntry;
+use crate::analysis::calendar::{self, Calendar, CalendarSpan};
+use crate::analysis::collisions::{self, CollisionReport};
+use crate::analysis::lint::{self, Finding};
+use crate::analysis::simulate::{self, Simulation};
+use crate::audit::{self, AuditRecord, ChangeKind};
+use crate::backup::{self, BackupStore, Snapshot};
+use crate::bulk_edit::{self, EditDiff};
+use crate::cache::{ScheduleCache, ScheduleInfo};
+use crate::clipboard;
+use crate::clock::Clock;
+use crate::config::Config;
+use crate::conflict::{self, Merge, Resolution};
+use crate::cron_entry::{self, CronEntry};
+use crate::cron_parser::CronParser;
+use crate::export::{self, Format, OnDuplicate};
+use crate::filter::{self, EntryFilter, ListRow};
+use crate::i18n::{fill, Language, Strings};
+use crate::history;
+use crate::holidays::ExclusionCalendar;
+use crate::log_search;
+use crate::log_tail::LogTail;
+use crate::macros::{self, CommandRun};
+use crate::paths;
+use crate::notify;
+use crate::ping;
+use crate::policy::Policy;
+use crate::retry::RetryPolicy;
+use crate::run_history::{self, RunRecord, RunStats};
+use crate::run_now::ManualRun;
+use crate::refresh_job::RefreshJob;
+use crate::save_job::{SaveJob, Written};
+use crate::scripts;
+use crate::shell::Shell;
+use crate::schedule::timezone::{self, ScheduleZone};
+use crate::schedule::split::{self, Conversion};
+use crate::schedule::suggest::{self, Suggestion};
+use crate::schedule::{self, natural, normalize, quartz::QuartzExpression, CronSchedule, FieldKind};
 use crate::storage::Storage;
-use anyhow::Result;
+use crate::sync::{Direction, Sync};
+use crate::theme::Theme;
+use crate::trash::TrashedEntry;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, FixedOffset, NaiveDateTime, Utc};
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, PartialEq)]
 pub enum InputMode {
@@ -11,136 +56,2304 @@ pub enum InputMode {
     EditingName,
     EditingSchedule,
     EditingCommand,
+    EditingTimezone,
+    Snoozing,
+    EditingTags,
+    EditingEnv,
+    EditingShell,
+    EditingSuccessHook,
+    EditingFailureHook,
+    EditingTimeout,
+    EditingRetries,
+    EditingPing,
+    BulkToggling,
+    FilteringTag,
+    ViewingLog,
+    SearchingLog,
+    ViewingSimulation,
+    ViewingCalendar,
+    ViewingRun,
+    ConfirmDelete,
+    ImportingFile,
+    ConfirmImport,
+    BrowsingRuns,
+    ViewingChanges,
+    ViewingDryRun,
+    BrowsingBackups,
+    BrowsingTrash,
+    ReviewingPush,
+    ReviewingSync,
+    BrowsingScripts,
+    AddingScript,
+    ReviewingEdit,
 }
 
+impl InputMode {
+    /// Whether keys type into the input line in this mode
+    pub fn takes_text(&self) -> bool {
+        !matches!(
+            self,
+            InputMode::Normal
+                | InputMode::ViewingLog
+                | InputMode::ViewingCalendar
+                | InputMode::ViewingSimulation
+                | InputMode::ConfirmDelete
+                | InputMode::ConfirmImport
+                | InputMode::ViewingRun
+                | InputMode::BrowsingRuns
+                | InputMode::ViewingChanges
+                | InputMode::ViewingDryRun
+                | InputMode::BrowsingScripts
+                | InputMode::ReviewingEdit
+                | InputMode::ReviewingPush
+                | InputMode::ReviewingSync
+                | InputMode::BrowsingTrash
+                | InputMode::BrowsingBackups
+        )
+    }
+}
+
+/// Where a key moves the cursor in the text being typed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorMotion {
+    Left,
+    Right,
+    /// To the start of the word before the cursor
+    WordLeft,
+    /// To the start of the word after the cursor
+    WordRight,
+    Home,
+    End,
+}
+
+/// Scripts listed in the script library view
+#[derive(Debug)]
+pub struct ScriptBrowser {
+    pub names: Vec<String>,
+    pub selected: usize,
+}
+
+/// Snapshots listed in the backup browser, and the one opened for picking
+/// entries to restore
+#[derive(Debug)]
+pub struct BackupBrowser {
+    pub snapshots: Vec<Snapshot>,
+    pub selected: usize,
+    /// Entries of the opened snapshot and the highlighted one
+    pub opened: Option<(Vec<CronEntry>, usize)>,
+    /// The highlighted snapshot compared with the current entries or the
+    /// snapshot before it, kept in step with the highlight
+    pub diff: Option<(BackupDiff, Vec<AuditRecord>)>,
+    /// Waiting for `y` to roll back to the highlighted snapshot
+    pub confirm_rollback: bool,
+}
+
+/// What a snapshot is compared with in the backup browser
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackupDiff {
+    /// What changed since: rolling back undoes these
+    Current,
+    /// What the save that took the snapshot changed
+    Previous,
+}
+
+/// Deleted entries listed in the trash view
+#[derive(Debug)]
+pub struct TrashBrowser {
+    pub items: Vec<TrashedEntry>,
+    pub selected: usize,
+}
+
+/// Recorded runs of one entry, newest first, listed in the run history view
+#[derive(Debug)]
+pub struct RunBrowser {
+    pub entry: String,
+    pub runs: Vec<RunRecord>,
+    pub selected: usize,
+}
+
+/// How a sandbox differs from its live backend, and the changes picked to push
+#[derive(Debug)]
+pub struct PushReview {
+    pub changes: Vec<AuditRecord>,
+    pub picked: Vec<bool>,
+    pub selected: usize,
+}
+
+/// The other side of `sync`: the system scheduler for a tab on the local
+/// file, and the local file for a tab on the system scheduler
+pub struct SyncPeer {
+    pub storage: Storage,
+    /// This tab is the local file
+    pub local: bool,
+}
+
+/// How the local file differs from the system scheduler, listed before
+/// pushing, pulling or merging
+#[derive(Debug)]
+pub struct SyncReview {
+    pub changes: Vec<AuditRecord>,
+    pub selected: usize,
+}
+
+/// Entries changed both here and in the backend since they were loaded,
+/// settled one at a time before the save goes through
+#[derive(Debug)]
+pub struct ConflictResolver {
+    pub merge: Merge,
+    /// The backend's entries, the base for the next save
+    pub theirs: Vec<CronEntry>,
+    pub selected: usize,
+}
+
+/// All entries edited as text in `$EDITOR`, and what applying them would do
+#[derive(Debug)]
+pub struct BulkEdit {
+    pub path: PathBuf,
+    pub entries: Vec<CronEntry>,
+    pub diff: EditDiff,
+    /// Problems that keep the edit from being applied
+    pub errors: Vec<String>,
+}
+
+/// Ways to run a schedule the backend can't represent, offered before the
+/// schedule is accepted
+#[derive(Debug)]
+pub struct ConversionWizard {
+    pub schedule: String,
+    pub options: Vec<Conversion>,
+    /// Whether keeping the schedule as written is offered after the options
+    pub can_keep: bool,
+    pub selected: usize,
+}
+
+/// Lines read from a log file at a time
+const LOG_PAGE_LINES: usize = 1000;
+
+/// History commands offered at once in the command prompt
+const HISTORY_SUGGESTIONS: usize = 5;
+
+/// Window covered by the "upcoming runs" view
+const SIMULATION_HOURS: u32 = 24;
+
+/// Seconds between checks for outside changes on remote hosts, at least,
+/// since each one is a round trip
+const REMOTE_RELOAD_SECS: u64 = 30;
+
 pub struct App {
     pub entries: Vec<CronEntry>,
     pub selected_index: usize,
+    /// First entry shown in the table, and how many rows fit; kept up to
+    /// date by the renderer
+    pub table_offset: usize,
+    pub table_height: usize,
     pub input_mode: InputMode,
     pub input_buffer: String,
-    pub storage: Storage,
+    /// Cursor position in `input_buffer`, in characters; past the end while
+    /// the buffer is shorter, which counts as the end
+    input_cursor: usize,
+    /// Shared with the thread of a save in progress
+    pub storage: Arc<Storage>,
+    pub config: Config,
+    /// Command policy enforced when commands are added or edited
+    pub policy: Option<Policy>,
+    /// UI strings in the configured language
+    pub text: &'static Strings,
+    /// Colors in use: the configured preset until another is picked
+    pub theme: Theme,
+    /// Whether the preview pane is shown beside the table
+    pub show_preview: bool,
+    /// Tag the table is narrowed to, if any
+    pub tag_filter: Option<String>,
+    /// Whether the table's rows are grouped under tag headings
+    pub group_by_tag: bool,
+    /// "Now" for next runs and analyses; real unless `--simulate-time` is given
+    pub clock: Clock,
     pub message: Option<String>,
     pub should_quit: bool,
+    pub collisions: CollisionReport,
+    pub lint_findings: Vec<Finding>,
+    /// Success rate and durations over the last 30 days, by entry name
+    pub run_stats: HashMap<String, RunStats>,
+    /// Pinging entries whose last run is followed by an overdue one, by entry
+    /// name, with when that run was due
+    pub overdue_pings: HashMap<String, DateTime<FixedOffset>>,
+    /// Entries the backend refused on the last save, by index, with the reason
+    pub save_failures: BTreeMap<usize, String>,
+    /// Log opened in the log viewer, and how many lines it is scrolled up
+    pub log: Option<LogTail>,
+    pub log_scroll: usize,
+    /// The search typed in the log viewer, as typed and compiled
+    pub log_search: Option<(String, Regex)>,
+    /// Log file to show in `$PAGER`; the event loop suspends the TUI for it
+    pub pager_request: Option<PathBuf>,
+    /// Script to open in `$EDITOR`, likewise
+    pub editor_request: Option<PathBuf>,
+    /// Dry run shown in the upcoming-runs view, and how many lines it is scrolled down
+    pub simulation: Option<Simulation>,
+    pub simulation_scroll: usize,
+    /// The week or month of upcoming runs, and the day it was opened around
+    pub calendar: Option<Calendar>,
+    calendar_date: chrono::NaiveDate,
+    /// First hour shown in the week view
+    pub calendar_scroll: usize,
+    /// Command started with run-now, and how many lines its output is scrolled up
+    pub manual_run: Option<ManualRun>,
+    /// The macro command running in the background
+    pub macro_run: Option<CommandRun>,
+    pub run_scroll: usize,
+    /// Changes from the audit log shown in the recently-changed view, and
+    /// how many lines it is scrolled down
+    pub recent_changes: Option<Vec<AuditRecord>>,
+    pub changes_scroll: usize,
+    /// What the last save would have written in a `--dry-run`, and how many
+    /// lines it is scrolled down
+    pub dry_run_view: Option<String>,
+    pub dry_run_scroll: usize,
+    pub backup_browser: Option<BackupBrowser>,
+    pub trash_browser: Option<TrashBrowser>,
+    pub run_browser: Option<RunBrowser>,
+    /// Entries read by an import, while asking what to do with taken names
+    pub pending_import: Option<Vec<CronEntry>>,
+    pub script_browser: Option<ScriptBrowser>,
+    pub bulk_edit: Option<BulkEdit>,
+    /// File the selected entry's comments are being edited in, and the entry's name
+    pub comment_edit: Option<(PathBuf, String)>,
+    /// Conflicts found on save, while they are being resolved
+    pub conflict_resolver: Option<ConflictResolver>,
+    /// Entries to clone into a sandbox; the event loop opens it in a tab
+    pub sandbox_request: Option<Vec<CronEntry>>,
+    /// Changes were pushed from this sandbox; the event loop reloads the live tab
+    pub origin_changed: bool,
+    pub push_review: Option<PushReview>,
+    pub sync_peer: Option<SyncPeer>,
+    pub sync_review: Option<SyncReview>,
+    // The entries as the backend last had them, to tell its changes from ours
+    loaded: Vec<CronEntry>,
+    // When the most recent unsaved change was made
+    pending_save: Option<Instant>,
+    // The save running in the background, once the debounce has passed
+    save_job: Option<SaveJob>,
+    // When the backend was last checked for outside changes
+    reload_checked: Instant,
+    // The backend's fingerprint when it was last loaded, if it has one
+    reload_fingerprint: Option<u64>,
+    schedule_cache: ScheduleCache,
+    // Wall-clock minute the analyses were last computed for
+    analyzed_at: NaiveDateTime,
+    // The analyses for a new minute, worked out in the background
+    refresh_job: Option<RefreshJob>,
     // Temporary state for adding new entries
     temp_name: String,
     temp_schedule: String,
+    // On-success hook entered before the on-failure prompt
+    temp_success_hook: String,
+    temp_timeout: Option<u64>,
+    // Input typed before Tab-cycling started, and the suggestion shown
+    completion: Option<(String, usize)>,
+    /// Highlighted row of the schedule preset popup, while it is open
+    pub preset_picker: Option<usize>,
+    /// Conversion offered for a schedule the backend can't represent
+    pub conversion: Option<ConversionWizard>,
+    // Schedules of the entries a new entry is split into
+    temp_split: Vec<String>,
+    // Accept the next schedule even though the backend can't represent it
+    keep_unsupported: bool,
+    // Shell history, read the first time a command prompt opens
+    history: Option<Vec<String>>,
 }
 
 impl App {
-    pub fn new(storage: Storage) -> Result<Self> {
+    pub fn new(storage: Storage, config: Config) -> Result<Self> {
+        Self::with_clock(storage, config, Clock::system())
+    }
+
+    pub fn with_clock(storage: Storage, config: Config, clock: Clock) -> Result<Self> {
         let entries = storage.load()?;
-        Ok(Self {
+        let mut app = Self {
+            loaded: entries.clone(),
             entries,
             selected_index: 0,
+            table_offset: 0,
+            table_height: 1,
             input_mode: InputMode::Normal,
             input_buffer: String::new(),
-            storage,
+            input_cursor: 0,
+            storage: Arc::new(storage),
+            text: Language::detect(config.language.as_deref()).strings(),
+            theme: Theme::new(&config.theme, config.theme.preset),
+            show_preview: config.preview_pane,
+            tag_filter: None,
+            group_by_tag: config.group_by_tag,
+            policy: Policy::load(config.policy_file.as_deref())?,
+            config,
+            clock,
             message: None,
             should_quit: false,
+            collisions: CollisionReport::default(),
+            lint_findings: Vec::new(),
+            run_stats: HashMap::new(),
+            overdue_pings: HashMap::new(),
+            save_failures: BTreeMap::new(),
+            log: None,
+            log_scroll: 0,
+            log_search: None,
+            pager_request: None,
+            editor_request: None,
+            simulation: None,
+            simulation_scroll: 0,
+            calendar: None,
+            calendar_date: chrono::NaiveDate::default(),
+            calendar_scroll: 0,
+            manual_run: None,
+            macro_run: None,
+            run_scroll: 0,
+            recent_changes: None,
+            changes_scroll: 0,
+            dry_run_view: None,
+            dry_run_scroll: 0,
+            backup_browser: None,
+            trash_browser: None,
+            run_browser: None,
+            pending_import: None,
+            script_browser: None,
+            bulk_edit: None,
+            comment_edit: None,
+            conflict_resolver: None,
+            sandbox_request: None,
+            origin_changed: false,
+            push_review: None,
+            sync_peer: None,
+            sync_review: None,
+            pending_save: None,
+            save_job: None,
+            reload_checked: Instant::now(),
+            reload_fingerprint: None,
+            schedule_cache: ScheduleCache::default(),
+            analyzed_at: NaiveDateTime::default(),
+            refresh_job: None,
             temp_name: String::new(),
             temp_schedule: String::new(),
-        })
+            temp_success_hook: String::new(),
+            temp_timeout: None,
+            completion: None,
+            preset_picker: None,
+            conversion: None,
+            temp_split: Vec::new(),
+            keep_unsupported: false,
+            history: None,
+        };
+        // Say so up front rather than when the first change fails to save
+        if let Err(e) = app.storage.preflight() {
+            app.message = Some(fill(app.text.save_blocked, &[&format!("{:#}", e)]));
+        }
+        app.offer_recovery();
+        app.wake_snoozed()?;
+        app.refresh_analysis();
+        app.refresh_run_stats();
+        Ok(app)
+    }
+
+    /// When the backend looks damaged, or emptied while the newest backup
+    /// still has entries, ask to roll back to that backup rather than show
+    /// a list that the next save would write over it
+    fn offer_recovery(&mut self) {
+        let damage = self.storage.damage();
+        if damage.is_none() && !self.entries.is_empty() {
+            return;
+        }
+        let snapshots = match self.storage.backups().map(BackupStore::list) {
+            Some(Ok(snapshots)) => snapshots,
+            _ => Vec::new(),
+        };
+        let backed_up = snapshots.first().and_then(|newest| newest.entries().ok()).filter(|entries| !entries.is_empty());
+        let reason = match (damage, &backed_up) {
+            (Some(reason), _) => reason,
+            (None, Some(entries)) => fill(self.text.backend_emptied, &[&entries.len()]),
+            (None, None) => return,
+        };
+        tracing::warn!(reason, "backend looks damaged");
+        if backed_up.is_none() {
+            self.message = Some(fill(self.text.damaged_no_backup, &[&reason]));
+            return;
+        }
+        let taken_at = snapshots[0].taken_at;
+        self.backup_browser = Some(BackupBrowser {
+            snapshots,
+            selected: 0,
+            opened: None,
+            diff: None,
+            confirm_rollback: true,
+        });
+        self.input_mode = InputMode::BrowsingBackups;
+        self.diff_backup(BackupDiff::Current);
+        self.message = Some(fill(
+            self.text.confirm_recovery,
+            &[&reason, &taken_at.format("%Y-%m-%d %H:%M:%S")],
+        ));
+    }
+
+    /// Recompute cross-entry analyses after the entry list changes
+    pub fn refresh_analysis(&mut self) {
+        let now = self.clock.current_minute();
+        self.analyzed_at = now;
+        self.collisions = collisions::analyze(&self.entries, now, &self.config.collisions);
+        self.lint_findings = lint::lint(&self.entries, &self.storage, &self.config, now);
+        self.schedule_cache.retain(&self.entries);
+    }
+
+    /// Periodic update from the event loop: analyses look ahead from "now",
+    /// so recompute them, in the background, when the minute rolls over
+    pub fn on_tick(&mut self) -> Result<()> {
+        self.poll_manual_run();
+        self.poll_macro_run();
+        self.reload_if_changed();
+        self.poll_refresh();
+        let now = self.clock.current_minute();
+        if now != self.analyzed_at && self.refresh_job.is_none() {
+            self.wake_snoozed()?;
+            self.analyzed_at = now;
+            let (storage, config) = (Arc::clone(&self.storage), self.config.clone());
+            self.refresh_job = Some(RefreshJob::start(storage, config, self.entries.clone(), now));
+        }
+        Ok(())
+    }
+
+    /// Take in the background analyses once they are done
+    fn poll_refresh(&mut self) {
+        let Some(refreshed) = self.refresh_job.as_ref().and_then(RefreshJob::poll) else {
+            return;
+        };
+        self.refresh_job = None;
+        match refreshed {
+            Ok(refreshed) => {
+                // An edit meanwhile has analysed the entries as they are now
+                if refreshed.entries == self.entries {
+                    self.collisions = refreshed.collisions;
+                    self.lint_findings = refreshed.lint_findings;
+                    self.schedule_cache.retain(&self.entries);
+                }
+                self.record_run_stats(refreshed.runs, refreshed.read_at);
+            }
+            Err(e) => tracing::warn!(error = %format!("{:#}", e), "failed to refresh the analyses"),
+        }
+    }
+
+    /// Re-read the run history recorded by `cron-manager exec`
+    fn refresh_run_stats(&mut self) {
+        let now = chrono::Utc::now();
+        let since = now - chrono::Duration::days(run_history::STATS_DAYS as i64);
+        let runs = self.storage.runs_since(since, self.config.data_dir.as_deref());
+        self.record_run_stats(runs, now);
+    }
+
+    fn record_run_stats(&mut self, runs: Result<Vec<RunRecord>>, now: DateTime<Utc>) {
+        match runs {
+            Ok(records) => {
+                self.run_stats = run_history::stats(&records, now);
+                self.overdue_pings = self.find_overdue_pings(&records, now);
+            }
+            Err(e) => tracing::warn!(error = %format!("{:#}", e), "failed to read run history"),
+        }
+    }
+
+    /// Enabled entries with a ping URL that haven't run, and so pinged, within
+    /// the grace period of a run due since their last recorded one
+    fn find_overdue_pings(&self, records: &[RunRecord], now: DateTime<Utc>) -> HashMap<String, DateTime<FixedOffset>> {
+        let grace = chrono::Duration::minutes(self.config.ping.grace_minutes.into());
+        let mut last_runs: HashMap<&str, DateTime<Utc>> = HashMap::new();
+        for record in records {
+            let last = last_runs.entry(record.entry.as_str()).or_insert(record.started_at);
+            *last = (*last).max(record.started_at);
+        }
+        self.entries
+            .iter()
+            .filter(|entry| entry.enabled && entry.ping_url.is_some())
+            .filter_map(|entry| {
+                let schedule = CronSchedule::parse(&entry.schedule).ok()?;
+                let zone = self.zone_for(entry).ok()?;
+                let due = ping::overdue(&schedule, zone, last_runs.get(entry.name.as_str()).copied(), now, grace)?;
+                Some((entry.name.clone(), due))
+            })
+            .collect()
+    }
+
+    /// Re-enable entries whose snooze has run out. Uses the real time even
+    /// when simulating, since waking changes what is installed.
+    fn wake_snoozed(&mut self) -> Result<()> {
+        let now = chrono::Local::now().naive_local();
+        let woken: Vec<String> = self
+            .entries
+            .iter_mut()
+            .filter_map(|entry| entry.wake_if_due(now).then(|| entry.name.clone()))
+            .collect();
+        if !woken.is_empty() {
+            tracing::info!(entries = ?woken, "snooze expired");
+            self.save()?;
+            self.message = Some(fill(self.text.snooze_expired, &[&woken.join(", ")]));
+        }
+        Ok(())
+    }
+
+    /// Lint findings for the currently selected entry
+    pub fn selected_findings(&self) -> impl Iterator<Item = &Finding> {
+        self.lint_findings
+            .iter()
+            .filter(move |f| f.entry == self.selected_index)
+    }
+
+    /// Rows of the entry table, after the tag filter and grouping
+    pub fn list_rows(&self) -> Vec<ListRow> {
+        filter::list_rows(&self.entries, self.tag_filter.as_deref(), self.group_by_tag)
+    }
+
+    /// Entries in the order the table shows them
+    fn shown_entries(&self) -> Vec<usize> {
+        self.list_rows()
+            .into_iter()
+            .filter_map(|row| match row {
+                ListRow::Entry(i) => Some(i),
+                ListRow::Group(..) => None,
+            })
+            .collect()
+    }
+
+    /// Select the first shown entry when the tag filter hides the selected one
+    pub fn keep_selection_shown(&mut self) {
+        let shown = self.shown_entries();
+        if !shown.contains(&self.selected_index) {
+            if let Some(first) = shown.first() {
+                self.selected_index = *first;
+            }
+        }
     }
 
     pub fn move_selection_up(&mut self) {
-        if self.selected_index > 0 {
-            self.selected_index -= 1;
+        self.move_selection(-1);
+    }
+
+    pub fn move_selection_down(&mut self) {
+        self.move_selection(1);
+    }
+
+    /// Move the selection a screenful up or down
+    pub fn page_selection(&mut self, down: bool) {
+        let page = self.table_height.max(1) as isize;
+        self.move_selection(if down { page } else { -page });
+    }
+
+    pub fn select_first(&mut self) {
+        self.move_selection(isize::MIN);
+    }
+
+    pub fn select_last(&mut self) {
+        self.move_selection(isize::MAX);
+    }
+
+    /// Move the selection by `by` shown entries, stopping at either end
+    fn move_selection(&mut self, by: isize) {
+        let shown = self.shown_entries();
+        if let Some(position) = shown.iter().position(|i| *i == self.selected_index) {
+            let position = position.saturating_add_signed(by).min(shown.len() - 1);
+            self.selected_index = shown[position];
+        }
+    }
+
+    pub fn start_add_entry(&mut self) {
+        self.input_mode = InputMode::AddingName;
+        self.input_buffer.clear();
+        self.message = Some(self.text.prompt_new_name.to_string());
+    }
+
+    pub fn start_edit_name(&mut self) {
+        if let Some(entry) = self.entries.get(self.selected_index) {
+            self.input_mode = InputMode::EditingName;
+            self.set_input(entry.name.clone());
+            self.message = Some(self.text.prompt_edit_name.to_string());
+        }
+    }
+
+    pub fn start_edit_schedule(&mut self) {
+        if let Some(entry) = self.entries.get(self.selected_index) {
+            self.input_mode = InputMode::EditingSchedule;
+            self.set_input(entry.schedule.clone());
+            self.message = Some(self.text.prompt_edit_schedule.to_string());
+        }
+    }
+
+    pub fn start_edit_command(&mut self) {
+        self.load_history();
+        if let Some(entry) = self.entries.get(self.selected_index) {
+            self.input_mode = InputMode::EditingCommand;
+            self.set_input(entry.command.clone());
+            self.message = Some(self.text.prompt_edit_command.to_string());
+        }
+    }
+
+    /// Edit the on-success hook, then the on-failure hook
+    pub fn start_edit_hooks(&mut self) {
+        if let Some(entry) = self.entries.get(self.selected_index) {
+            self.input_mode = InputMode::EditingSuccessHook;
+            self.set_input(entry.on_success.clone().unwrap_or_default());
+            self.message = Some(self.text.prompt_success_hook.to_string());
+        }
+    }
+
+    /// Edit the timeout, then the retries
+    pub fn start_edit_limits(&mut self) {
+        if let Some(entry) = self.entries.get(self.selected_index) {
+            self.input_mode = InputMode::EditingTimeout;
+            self.set_input(entry.timeout_secs.map(cron_entry::format_seconds).unwrap_or_default());
+            self.message = Some(self.text.prompt_edit_timeout.to_string());
+        }
+    }
+
+    pub fn start_edit_ping(&mut self) {
+        if let Some(entry) = self.entries.get(self.selected_index) {
+            self.input_mode = InputMode::EditingPing;
+            self.set_input(entry.ping_url.clone().unwrap_or_default());
+            self.message = Some(self.text.prompt_edit_ping.to_string());
+        }
+    }
+
+    pub fn start_edit_timezone(&mut self) {
+        if let Some(entry) = self.entries.get(self.selected_index) {
+            self.input_mode = InputMode::EditingTimezone;
+            self.set_input(entry.timezone.clone().unwrap_or_default());
+            self.message = Some(self.text.prompt_edit_timezone.to_string());
+        }
+    }
+
+    pub fn start_snooze(&mut self) {
+        if self.entries.get(self.selected_index).is_none() {
+            return;
+        }
+        if !self.storage.keeps_disabled_entries() {
+            self.message = Some(fill(self.text.snooze_unsupported, &[&self.storage.get_backend_name()]));
+            return;
+        }
+        self.input_mode = InputMode::Snoozing;
+        self.input_buffer.clear();
+        self.message = Some(self.text.prompt_snooze.to_string());
+    }
+
+    pub fn start_edit_tags(&mut self) {
+        if let Some(entry) = self.entries.get(self.selected_index) {
+            self.input_mode = InputMode::EditingTags;
+            self.set_input(entry.tags.join(", "));
+            self.message = Some(self.text.prompt_edit_tags.to_string());
+        }
+    }
+
+    /// Pick the env preset exported before the selected entry's command
+    pub fn start_edit_env(&mut self) {
+        if let Some(entry) = self.entries.get(self.selected_index) {
+            self.input_mode = InputMode::EditingEnv;
+            self.set_input(entry.env.clone().unwrap_or_default());
+            let names = self.storage.env_preset_names();
+            let names = if names.is_empty() { "-".to_string() } else { names.join(", ") };
+            self.message = Some(fill(self.text.prompt_edit_env, &[&names]));
+        }
+    }
+
+    pub fn start_edit_shell(&mut self) {
+        if let Some(entry) = self.entries.get(self.selected_index) {
+            self.input_mode = InputMode::EditingShell;
+            self.set_input(entry.shell.map(Shell::name).unwrap_or_default().to_string());
+            self.message = Some(self.text.prompt_edit_shell.to_string());
+        }
+    }
+
+    pub fn start_bulk_toggle(&mut self) {
+        self.input_mode = InputMode::BulkToggling;
+        self.input_buffer.clear();
+        self.message = Some(self.text.prompt_bulk_toggle.to_string());
+    }
+
+    pub fn start_tag_filter(&mut self) {
+        self.input_mode = InputMode::FilteringTag;
+        self.set_input(self.tag_filter.clone().unwrap_or_default());
+        self.message = Some(self.text.prompt_tag_filter.to_string());
+    }
+
+    pub fn toggle_group_by_tag(&mut self) {
+        self.group_by_tag = !self.group_by_tag;
+        self.message = Some(if self.group_by_tag { self.text.grouped_by_tag } else { self.text.ungrouped }.to_string());
+    }
+
+    /// The selected entry's log file, or why there is none to show
+    fn selected_log_path(&mut self) -> Option<PathBuf> {
+        let entry = self.entries.get(self.selected_index)?;
+        let path = self.storage.log_path(entry);
+        if path.is_none() {
+            self.message = Some(self.text.no_log_file.to_string());
+        }
+        path
+    }
+
+    /// Open the selected entry's log file in the log viewer
+    pub fn open_log(&mut self) {
+        let Some(path) = self.selected_log_path() else {
+            return;
+        };
+        match LogTail::open(&path, LOG_PAGE_LINES) {
+            Ok(tail) => {
+                self.log = Some(tail);
+                self.log_scroll = 0;
+                self.input_mode = InputMode::ViewingLog;
+                self.message = Some(fill(self.text.log_opened, &[&path.display()]));
+            }
+            Err(e) => self.message = Some(format!("{:#}", e)),
+        }
+    }
+
+    /// Ask the event loop to open the selected entry's log file in `$PAGER`
+    pub fn open_log_in_pager(&mut self) {
+        let Some(path) = self.selected_log_path() else {
+            return;
+        };
+        if path.exists() {
+            self.message = Some(fill(self.text.log_opened, &[&path.display()]));
+            self.pager_request = Some(path);
+        } else {
+            self.message = Some(fill(self.text.log_missing, &[&path.display()]));
+        }
+    }
+
+    /// Scroll the log viewer towards older lines, reading more of the file
+    /// when nearing the top of what has been loaded
+    pub fn scroll_log_up(&mut self, lines: usize) -> Result<()> {
+        if let Some(log) = &mut self.log {
+            if self.log_scroll + lines + LOG_PAGE_LINES / 4 >= log.lines.len() {
+                log.load_more(LOG_PAGE_LINES)?;
+            }
+            self.log_scroll = (self.log_scroll + lines).min(log.lines.len().saturating_sub(1));
+        }
+        Ok(())
+    }
+
+    pub fn scroll_log_down(&mut self, lines: usize) {
+        self.log_scroll = self.log_scroll.saturating_sub(lines);
+    }
+
+    /// Prompt for text (or `/regex/`) to find in the log viewer
+    pub fn start_log_search(&mut self) {
+        self.input_mode = InputMode::SearchingLog;
+        self.set_input(self.log_search.as_ref().map(|(query, _)| query.clone()).unwrap_or_default());
+        self.message = Some(self.text.prompt_log_search.to_string());
+    }
+
+    /// Scroll the log viewer to the next match, older or newer than the line
+    /// at the bottom of the view
+    pub fn next_log_match(&mut self, older: bool) -> Result<()> {
+        self.seek_log_match(older, false)
+    }
+
+    /// Searching older lines reads more of the file until a match turns up or
+    /// the start is reached. `inclusive` also checks the bottom line itself.
+    fn seek_log_match(&mut self, older: bool, inclusive: bool) -> Result<()> {
+        let (Some(log), Some((query, pattern))) = (&mut self.log, &self.log_search) else {
+            return Ok(());
+        };
+        let current = log.lines.len().saturating_sub(1 + self.log_scroll);
+        let found = if older {
+            let mut end = if inclusive { current + 1 } else { current };
+            loop {
+                if let Some(index) = log.lines[..end.min(log.lines.len())].iter().rposition(|l| pattern.is_match(l)) {
+                    break Some(index);
+                }
+                if log.at_start() {
+                    break None;
+                }
+                // Only the lines just read are left to search
+                end = log.load_more(LOG_PAGE_LINES)?;
+            }
+        } else {
+            let start = if inclusive { current } else { current + 1 };
+            log.lines.iter().skip(start).position(|l| pattern.is_match(l)).map(|i| i + start)
+        };
+        self.message = Some(match found {
+            Some(index) => {
+                self.log_scroll = log.lines.len() - 1 - index;
+                let total = log.lines.iter().filter(|l| pattern.is_match(l)).count();
+                let ordinal = log.lines[index..].iter().filter(|l| pattern.is_match(l)).count();
+                fill(self.text.log_match, &[&ordinal, &total])
+            }
+            None => fill(self.text.log_no_match, &[query]),
+        });
+        Ok(())
+    }
+
+    pub fn close_log(&mut self) {
+        self.log = None;
+        self.log_scroll = 0;
+        self.log_search = None;
+        self.input_mode = InputMode::Normal;
+        self.message = None;
+    }
+
+    /// Show every run of the enabled entries in the next 24 hours
+    pub fn open_simulation(&mut self) {
+        let simulation = simulate::simulate(
+            &self.entries,
+            self.config.timezone.as_deref(),
+            self.clock.now(),
+            SIMULATION_HOURS,
+        );
+        self.message = Some(fill(self.text.simulation_summary, &[&simulation.runs.len(), &SIMULATION_HOURS]));
+        self.simulation = Some(simulation);
+        self.simulation_scroll = 0;
+        self.input_mode = InputMode::ViewingSimulation;
+    }
+
+    pub fn scroll_simulation_up(&mut self, lines: usize) {
+        self.simulation_scroll = self.simulation_scroll.saturating_sub(lines);
+    }
+
+    pub fn scroll_simulation_down(&mut self, lines: usize) {
+        if let Some(simulation) = &self.simulation {
+            let last = simulation.runs.len().saturating_sub(1);
+            self.simulation_scroll = (self.simulation_scroll + lines).min(last);
+        }
+    }
+
+    /// Plot the upcoming runs of this week in a calendar
+    pub fn open_calendar(&mut self) {
+        self.calendar_date = self.clock.now().with_timezone(&chrono::Local).date_naive();
+        // Start the week view at the working day rather than midnight
+        self.calendar_scroll = 6;
+        self.show_calendar(CalendarSpan::Week, self.calendar_date);
+        self.input_mode = InputMode::ViewingCalendar;
+    }
+
+    fn show_calendar(&mut self, span: CalendarSpan, date: chrono::NaiveDate) {
+        let calendar = calendar::calendar(&self.entries, self.config.timezone.as_deref(), span, date, self.clock.now());
+        self.message = Some(match &calendar.busiest {
+            Some((minute, started)) => {
+                let names: Vec<&str> = started.iter().map(|index| self.entries[*index].name.as_str()).collect();
+                fill(self.text.calendar_busiest, &[&minute.format("%m-%d %H:%M"), &started.len(), &names.join(", ")])
+            }
+            None => self.text.calendar_no_clusters.to_string(),
+        });
+        self.calendar_date = date;
+        self.calendar = Some(calendar);
+    }
+
+    /// Show the week or month before (`-1`) or after (`1`)
+    pub fn shift_calendar(&mut self, by: i32) {
+        if let Some(span) = self.calendar.as_ref().map(|calendar| calendar.span) {
+            self.show_calendar(span, calendar::shift(span, self.calendar_date, by));
+        }
+    }
+
+    /// Switch between the week and the month around the day shown
+    pub fn set_calendar_span(&mut self, span: CalendarSpan) {
+        self.show_calendar(span, self.calendar_date);
+    }
+
+    /// Go back to the week or month of today
+    pub fn calendar_today(&mut self) {
+        if let Some(span) = self.calendar.as_ref().map(|calendar| calendar.span) {
+            self.show_calendar(span, self.clock.now().with_timezone(&chrono::Local).date_naive());
+        }
+    }
+
+    pub fn scroll_calendar_up(&mut self, hours: usize) {
+        self.calendar_scroll = self.calendar_scroll.saturating_sub(hours);
+    }
+
+    pub fn scroll_calendar_down(&mut self, hours: usize) {
+        self.calendar_scroll = (self.calendar_scroll + hours).min(23);
+    }
+
+    pub fn close_calendar(&mut self) {
+        self.calendar = None;
+        self.input_mode = InputMode::Normal;
+        self.message = None;
+    }
+
+    pub fn close_simulation(&mut self) {
+        self.simulation = None;
+        self.simulation_scroll = 0;
+        self.input_mode = InputMode::Normal;
+        self.message = None;
+    }
+
+    /// Run the selected entry's command now, showing its output as it arrives.
+    /// Commands of remote backends would run on the wrong machine, so only
+    /// local ones can be run.
+    pub fn start_manual_run(&mut self) {
+        let Some(entry) = self.entries.get(self.selected_index) else {
+            return;
+        };
+        if let Some(host) = self.storage.host().or_else(|| self.storage.origin().and_then(Storage::host)) {
+            self.message = Some(fill(self.text.run_remote, &[&host]));
+            return;
+        }
+        if let Some(reason) = self.command_refusal(&entry.command) {
+            self.message = Some(reason);
+            return;
+        }
+        let run = self
+            .storage
+            .manual_command(entry)
+            .and_then(|command| ManualRun::start(&entry.name, &command, entry.stdin.as_deref(), RetryPolicy::of(entry)));
+        match run {
+            Ok(run) => {
+                self.message = Some(fill(self.text.run_started, &[&entry.name]));
+                self.manual_run = Some(run);
+                self.run_scroll = 0;
+                self.input_mode = InputMode::ViewingRun;
+            }
+            Err(e) => self.message = Some(format!("{:#}", e)),
+        }
+    }
+
+    /// Pick up the output of the run-now command, and say when it's done;
+    /// a failure is posted to the webhook in the background
+    pub fn poll_manual_run(&mut self) {
+        let Some(run) = &mut self.manual_run else {
+            return;
+        };
+        if run.poll() {
+            let exit_code = run.finished().and_then(|(exit_code, _)| exit_code);
+            let seconds = format!("{:.1}", run.elapsed().as_secs_f64());
+            self.message = Some(match exit_code {
+                _ if run.timed_out() => fill(self.text.run_timed_out, &[&run.name, &seconds]),
+                Some(code) => fill(self.text.run_exited, &[&run.name, &code, &seconds]),
+                None => fill(self.text.run_killed, &[&run.name, &seconds]),
+            });
+            if exit_code != Some(0) && self.config.notify.webhook_url.is_some() {
+                let (notify, secrets, name) = (self.config.notify.clone(), self.config.secrets.clone(), run.name.clone());
+                let stderr: Vec<&str> = run.lines.iter().filter(|line| line.stderr).map(|line| line.text.as_str()).collect();
+                let stderr = stderr.join("\n");
+                std::thread::spawn(move || {
+                    if let Err(e) = notify::notify_failure(&notify, &secrets, &name, exit_code, &stderr) {
+                        tracing::warn!(entry = %name, error = %format!("{:#}", e), "failed to notify the webhook");
+                    }
+                });
+            }
+        }
+    }
+
+    pub fn scroll_run_up(&mut self, lines: usize) {
+        if let Some(run) = &self.manual_run {
+            self.run_scroll = (self.run_scroll + lines).min(run.lines.len().saturating_sub(1));
+        }
+    }
+
+    pub fn scroll_run_down(&mut self, lines: usize) {
+        self.run_scroll = self.run_scroll.saturating_sub(lines);
+    }
+
+    /// Close the run-now view, killing the command if it is still running
+    pub fn close_manual_run(&mut self) {
+        if let Some(mut run) = self.manual_run.take() {
+            if run.finished().is_none() {
+                run.stop();
+                self.message = Some(fill(self.text.run_stopped, &[&run.name]));
+            } else {
+                self.message = None;
+            }
+        }
+        self.run_scroll = 0;
+        self.input_mode = InputMode::Normal;
+    }
+
+    /// Show the changes saved in the last `recent_days` days, newest first
+    pub fn open_changes(&mut self) {
+        let Some(store) = self.storage.backups() else {
+            self.message = Some(self.text.backups_disabled.to_string());
+            return;
+        };
+        let days = self.config.backups.recent_days;
+        let since = chrono::Utc::now() - chrono::Duration::days(days as i64);
+        match store.changes_since(since) {
+            Ok(records) if records.is_empty() => self.message = Some(fill(self.text.no_recent_changes, &[&days])),
+            Ok(records) => {
+                self.message = Some(fill(self.text.changes_summary, &[&records.len(), &days]));
+                self.recent_changes = Some(records);
+                self.changes_scroll = 0;
+                self.input_mode = InputMode::ViewingChanges;
+            }
+            Err(e) => self.message = Some(format!("{:#}", e)),
+        }
+    }
+
+    pub fn scroll_changes_up(&mut self, lines: usize) {
+        self.changes_scroll = self.changes_scroll.saturating_sub(lines);
+    }
+
+    pub fn scroll_changes_down(&mut self, lines: usize) {
+        if let Some(records) = &self.recent_changes {
+            // A line per change and one per changed field
+            let total: usize = records.iter().map(|r| 1 + r.fields.len()).sum();
+            self.changes_scroll = (self.changes_scroll + lines).min(total.saturating_sub(1));
+        }
+    }
+
+    pub fn close_changes(&mut self) {
+        self.recent_changes = None;
+        self.changes_scroll = 0;
+        self.input_mode = InputMode::Normal;
+        self.message = None;
+    }
+
+    /// Show what the last save would have written, writing pending changes
+    /// first so the rendering is current
+    pub fn open_dry_run(&mut self) -> Result<()> {
+        if self.storage.dry_run_output().is_none() {
+            self.message = Some(self.text.dry_run_off.to_string());
+            return Ok(());
+        }
+        self.flush_save()?;
+        match self.storage.dry_run_output() {
+            Some(output) if !output.is_empty() => {
+                self.dry_run_view = Some(output);
+                self.dry_run_scroll = 0;
+                self.input_mode = InputMode::ViewingDryRun;
+                self.message = None;
+            }
+            _ => self.message = Some(self.text.dry_run_nothing_saved.to_string()),
+        }
+        Ok(())
+    }
+
+    pub fn scroll_dry_run_up(&mut self, lines: usize) {
+        self.dry_run_scroll = self.dry_run_scroll.saturating_sub(lines);
+    }
+
+    pub fn scroll_dry_run_down(&mut self, lines: usize) {
+        if let Some(view) = &self.dry_run_view {
+            self.dry_run_scroll = (self.dry_run_scroll + lines).min(view.lines().count().saturating_sub(1));
+        }
+    }
+
+    pub fn close_dry_run(&mut self) {
+        self.dry_run_view = None;
+        self.dry_run_scroll = 0;
+        self.input_mode = InputMode::Normal;
+    }
+
+    pub fn open_backups(&mut self) {
+        let Some(store) = self.storage.backups() else {
+            self.message = Some(self.text.backups_disabled.to_string());
+            return;
+        };
+        match store.list() {
+            Ok(snapshots) if snapshots.is_empty() => self.message = Some(self.text.no_backups.to_string()),
+            Ok(snapshots) => {
+                self.message = Some(fill(self.text.backups_summary, &[&snapshots.len()]));
+                self.backup_browser = Some(BackupBrowser {
+                    snapshots,
+                    selected: 0,
+                    opened: None,
+                    diff: None,
+                    confirm_rollback: false,
+                });
+                self.input_mode = InputMode::BrowsingBackups;
+            }
+            Err(e) => self.message = Some(format!("{:#}", e)),
+        }
+    }
+
+    pub fn move_backup_selection(&mut self, down: bool) {
+        let Some(browser) = &mut self.backup_browser else {
+            return;
+        };
+        let (index, len) = match &mut browser.opened {
+            Some((entries, index)) => (index, entries.len()),
+            None => (&mut browser.selected, browser.snapshots.len()),
+        };
+        if down {
+            *index = (*index + 1).min(len.saturating_sub(1));
+        } else {
+            *index = index.saturating_sub(1);
+        }
+        browser.confirm_rollback = false;
+        if let Some((against, _)) = browser.diff {
+            self.diff_backup(against);
+        }
+    }
+
+    /// Compare the highlighted snapshot with the current entries or the
+    /// snapshot before it
+    pub fn diff_backup(&mut self, against: BackupDiff) {
+        let Some(browser) = &mut self.backup_browser else {
+            return;
+        };
+        if browser.opened.is_some() {
+            return;
+        }
+        let snapshot = &browser.snapshots[browser.selected];
+        let records = snapshot.entries().and_then(|entries| match against {
+            BackupDiff::Current => Ok(audit::diff(&entries, &self.entries, Utc::now(), None)),
+            // The oldest snapshot kept is compared with nothing
+            BackupDiff::Previous => {
+                let before = match browser.snapshots.get(browser.selected + 1) {
+                    Some(previous) => previous.entries()?,
+                    None => Vec::new(),
+                };
+                Ok(audit::diff(&before, &entries, Utc::now(), None))
+            }
+        });
+        match records {
+            Ok(records) => browser.diff = Some((against, records)),
+            Err(e) => self.message = Some(format!("{:#}", e)),
+        }
+    }
+
+    /// Ask whether to replace every entry with the highlighted snapshot's
+    pub fn request_rollback(&mut self) {
+        let Some(browser) = &mut self.backup_browser else {
+            return;
+        };
+        if browser.opened.is_some() {
+            return;
+        }
+        let taken_at = browser.snapshots[browser.selected].taken_at;
+        browser.confirm_rollback = true;
+        self.message = Some(fill(self.text.confirm_rollback, &[&taken_at.format("%Y-%m-%d %H:%M:%S")]));
+    }
+
+    /// Answer the rollback confirmation. The current entries were
+    /// snapshotted when saved, so a rollback can itself be rolled back.
+    pub fn confirm_rollback(&mut self, rollback: bool) -> Result<()> {
+        let Some(browser) = &mut self.backup_browser else {
+            return Ok(());
+        };
+        browser.confirm_rollback = false;
+        if !rollback {
+            self.message = Some(self.text.cancelled.to_string());
+            return Ok(());
+        }
+        let snapshot = browser.snapshots[browser.selected].clone();
+        let entries = match snapshot.entries() {
+            Ok(entries) => entries,
+            Err(e) => {
+                self.message = Some(format!("{:#}", e));
+                return Ok(());
+            }
+        };
+        let changes = audit::diff(&self.entries, &entries, Utc::now(), None).len();
+        self.entries = entries;
+        self.selected_index = self.selected_index.min(self.entries.len().saturating_sub(1));
+        self.backup_browser = None;
+        self.input_mode = InputMode::Normal;
+        self.save()?;
+        self.message = Some(fill(
+            self.text.rolled_back,
+            &[&snapshot.taken_at.format("%Y-%m-%d %H:%M:%S"), &changes],
+        ));
+        Ok(())
+    }
+
+    /// Open the highlighted snapshot, or restore the highlighted entry of
+    /// the opened one into the current set
+    pub fn confirm_backup(&mut self) -> Result<()> {
+        let Some(browser) = &mut self.backup_browser else {
+            return Ok(());
+        };
+        let Some((entries, index)) = &browser.opened else {
+            match browser.snapshots[browser.selected].entries() {
+                Ok(entries) if entries.is_empty() => self.message = Some(self.text.backup_empty.to_string()),
+                Ok(entries) => {
+                    browser.opened = Some((entries, 0));
+                    browser.diff = None;
+                }
+                Err(e) => self.message = Some(format!("{:#}", e)),
+            }
+            return Ok(());
+        };
+
+        let Some(entry) = entries.get(*index).cloned() else {
+            return Ok(());
+        };
+        let taken_at = browser.snapshots[browser.selected].taken_at;
+        let name = entry.name.clone();
+        self.selected_index = backup::restore_entry(&mut self.entries, entry);
+        self.save()?;
+        self.message = Some(fill(self.text.entry_restored, &[&name, &taken_at.format("%Y-%m-%d %H:%M:%S")]));
+        Ok(())
+    }
+
+    /// Go back from an opened snapshot or a diff to the list, or close the
+    /// browser
+    pub fn back_from_backups(&mut self) {
+        if let Some(browser) = &mut self.backup_browser {
+            if browser.opened.take().is_some() || browser.diff.take().is_some() {
+                return;
+            }
+        }
+        self.backup_browser = None;
+        self.input_mode = InputMode::Normal;
+        self.message = None;
+    }
+
+    pub fn open_scripts(&mut self) {
+        let Some(library) = self.storage.scripts() else {
+            self.message = Some(self.text.scripts_unavailable.to_string());
+            return;
+        };
+        match library.list() {
+            Ok(names) => {
+                self.message = Some(fill(self.text.scripts_summary, &[&names.len()]));
+                self.script_browser = Some(ScriptBrowser { names, selected: 0 });
+                self.input_mode = InputMode::BrowsingScripts;
+            }
+            Err(e) => self.message = Some(format!("{:#}", e)),
+        }
+    }
+
+    pub fn move_script_selection(&mut self, down: bool) {
+        if let Some(browser) = &mut self.script_browser {
+            browser.selected = if down {
+                (browser.selected + 1).min(browser.names.len().saturating_sub(1))
+            } else {
+                browser.selected.saturating_sub(1)
+            };
+        }
+    }
+
+    fn selected_script(&self) -> Option<&str> {
+        let browser = self.script_browser.as_ref()?;
+        browser.names.get(browser.selected).map(String::as_str)
+    }
+
+    pub fn edit_script(&mut self) {
+        let Some(library) = self.storage.scripts() else {
+            return;
+        };
+        if let Some(name) = self.selected_script() {
+            self.editor_request = Some(library.path(name));
+        }
+    }
+
+    pub fn start_import(&mut self) {
+        self.input_mode = InputMode::ImportingFile;
+        self.input_buffer.clear();
+        self.message = Some(self.text.prompt_import_path.to_string());
+    }
+
+    /// Read the file named in the prompt: an export (by its extension) or a
+    /// crontab. Asks what to do when names are taken, otherwise adds them all.
+    fn read_import(&mut self) -> Result<()> {
+        let path = paths::expand_home(&PathBuf::from(self.input_buffer.trim()));
+        let imported = std::fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| export::parse(&content, Format::from_path(&path)));
+        let imported = match imported {
+            Ok(imported) => imported,
+            Err(e) => {
+                self.message = Some(fill(self.text.import_failed, &[&path.display(), &format!("{:#}", e)]));
+                return Ok(());
+            }
+        };
+        for entry in &imported {
+            if let Some(refusal) = self.command_refusal(&entry.command) {
+                self.message = Some(fill(self.text.import_refused, &[&entry.name, &refusal]));
+                return Ok(());
+            }
+        }
+        self.input_buffer.clear();
+        let taken = export::duplicates(&self.entries, &imported).len();
+        self.pending_import = Some(imported);
+        if taken == 0 {
+            return self.finish_import(Some(OnDuplicate::Skip));
+        }
+        self.input_mode = InputMode::ConfirmImport;
+        self.message = Some(fill(self.text.import_taken, &[&taken]));
+        Ok(())
+    }
+
+    /// Add the pending import, settling taken names as chosen; `None` drops it
+    pub fn finish_import(&mut self, on_duplicate: Option<OnDuplicate>) -> Result<()> {
+        self.input_mode = InputMode::Normal;
+        let (Some(imported), Some(on_duplicate)) = (self.pending_import.take(), on_duplicate) else {
+            self.message = Some(self.text.import_cancelled.to_string());
+            return Ok(());
+        };
+        let summary = export::merge(&mut self.entries, imported, on_duplicate);
+        self.save()?;
+        let imported = summary.added + summary.overwritten + summary.renamed;
+        self.message = Some(fill(self.text.import_done, &[&imported, &summary.skipped]));
+        Ok(())
+    }
+
+    pub fn start_new_script(&mut self) {
+        self.input_mode = InputMode::AddingScript;
+        self.input_buffer.clear();
+        self.message = Some(self.text.prompt_script_name.to_string());
+    }
+
+    /// Create the script named in the prompt and open it in the editor
+    fn create_script(&mut self) {
+        let Some(library) = self.storage.scripts() else {
+            return;
+        };
+        let name = self.input_buffer.trim().to_string();
+        match library.create(&name) {
+            Ok(path) => {
+                self.editor_request = Some(path);
+                self.input_mode = InputMode::BrowsingScripts;
+                self.input_buffer.clear();
+                if let (Some(browser), Ok(names)) = (&mut self.script_browser, library.list()) {
+                    browser.selected = names.iter().position(|n| *n == name).unwrap_or(0);
+                    browser.names = names;
+                }
+                self.message = Some(fill(self.text.script_created, &[&name]));
+            }
+            Err(e) => self.message = Some(format!("{:#}", e)),
+        }
+    }
+
+    /// Delete the highlighted script unless an entry still runs it
+    pub fn delete_script(&mut self) {
+        let (Some(library), Some(name)) = (self.storage.scripts(), self.selected_script()) else {
+            return;
+        };
+        let users: Vec<&str> = self
+            .entries
+            .iter()
+            .filter(|e| scripts::references(&e.command, name))
+            .map(|e| e.name.as_str())
+            .collect();
+        if !users.is_empty() {
+            self.message = Some(fill(self.text.script_in_use, &[&name, &users.join(", ")]));
+            return;
+        }
+        let name = name.to_string();
+        match library.delete(&name) {
+            Ok(()) => {
+                if let Some(browser) = &mut self.script_browser {
+                    browser.names.retain(|n| *n != name);
+                    browser.selected = browser.selected.min(browser.names.len().saturating_sub(1));
+                }
+                self.message = Some(fill(self.text.script_deleted, &[&name]));
+            }
+            Err(e) => self.message = Some(format!("{:#}", e)),
+        }
+    }
+
+    /// Make the selected entry run the highlighted script
+    pub fn use_script(&mut self) -> Result<()> {
+        let Some(name) = self.selected_script().map(str::to_string) else {
+            return Ok(());
+        };
+        let command = format!("{}{}", scripts::PLACEHOLDER, name);
+        if let Some(refusal) = self.command_refusal(&command) {
+            self.message = Some(refusal);
+            return Ok(());
+        }
+        let Some(entry) = self.entries.get_mut(self.selected_index) else {
+            return Ok(());
+        };
+        let message = fill(self.text.script_used, &[&entry.name, &name]);
+        entry.command = command;
+        self.save()?;
+        self.close_scripts();
+        self.message = Some(message);
+        Ok(())
+    }
+
+    pub fn close_scripts(&mut self) {
+        self.script_browser = None;
+        self.input_mode = InputMode::Normal;
+        self.message = None;
+    }
+
+    /// Parsed schedule, next run and frequency for the selected entry
+    pub fn selected_schedule_info(&mut self) -> Option<&ScheduleInfo> {
+        self.schedule_info(self.selected_index)
+    }
+
+    /// Parsed schedule, next run and frequency for the entry at `index`
+    pub fn schedule_info(&mut self, index: usize) -> Option<&ScheduleInfo> {
+        let entry = self.entries.get(index)?;
+        Some(self.schedule_cache.get(entry, self.config.timezone.as_deref(), self.clock.now()))
+    }
+
+    /// The timezone the given entry's schedule is evaluated in
+    pub fn zone_for(&self, entry: &CronEntry) -> Result<ScheduleZone> {
+        ScheduleZone::resolve(entry.timezone.as_deref(), self.config.timezone.as_deref())
+    }
+
+    /// Write every entry to a file and open it in `$EDITOR`; the edit is
+    /// reviewed when the editor closes
+    pub fn start_bulk_edit(&mut self) {
+        let name = format!("edit-{}.crontab", std::process::id());
+        let path = match self.write_for_editor(&name, &bulk_edit::serialize(&self.entries)) {
+            Ok(path) => path,
+            Err(e) => {
+                self.message = Some(fill(self.text.bulk_edit_failed, &[&format!("{:#}", e)]));
+                return;
+            }
+        };
+        self.bulk_edit = Some(BulkEdit { path: path.clone(), entries: Vec::new(), diff: EditDiff::default(), errors: Vec::new() });
+        self.editor_request = Some(path);
+    }
+
+    /// Open the selected entry's comments in `$EDITOR`, one line per comment line
+    pub fn edit_comments(&mut self) {
+        let Some(entry) = self.entries.get(self.selected_index) else {
+            return;
+        };
+        let content: String = entry.comments.iter().map(|line| format!("{}\n", line)).collect();
+        let name = entry.name.clone();
+        let path = match self.write_for_editor(&format!("comments-{}.txt", std::process::id()), &content) {
+            Ok(path) => path,
+            Err(e) => {
+                self.message = Some(format!("{:#}", e));
+                return;
+            }
+        };
+        self.comment_edit = Some((path.clone(), name));
+        self.editor_request = Some(path);
+    }
+
+    /// Write a file for `$EDITOR` where only this user can read it, since
+    /// commands may hold secrets
+    fn write_for_editor(&self, name: &str, content: &str) -> Result<PathBuf> {
+        let dir = paths::private_dir(self.config.data_dir.as_deref())
+            .ok_or_else(|| anyhow!("No data directory to write {} in", name))?;
+        paths::write_private(&dir, name, content)
+    }
+
+    /// Read back edited comments. Blank lines around them are dropped; lines
+    /// that would read back as an entry are refused.
+    fn finish_comment_edit(&mut self, path: PathBuf, name: String) -> Result<()> {
+        let content = std::fs::read_to_string(&path);
+        let _ = std::fs::remove_file(&path);
+        let content = match content {
+            Ok(content) => content,
+            Err(e) => {
+                self.message = Some(format!("{:#}", e));
+                return Ok(());
+            }
+        };
+        let mut lines: Vec<String> = content.lines().map(|line| line.trim_end().to_string()).collect();
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        let start = lines.iter().position(|line| !line.is_empty()).unwrap_or(lines.len());
+        lines.drain(..start);
+        if let Some(line) = lines.iter().find(|line| CronParser::is_ambiguous_comment(line)) {
+            self.message = Some(fill(self.text.comment_ambiguous, &[line]));
+            return Ok(());
+        }
+        let Some(entry) = self.entries.iter_mut().find(|e| e.name == name) else {
+            return Ok(());
+        };
+        if entry.comments == lines {
+            return Ok(());
+        }
+        entry.comments = lines;
+        self.save()?;
+        self.message = Some(fill(self.text.comments_updated, &[&name]));
+        Ok(())
+    }
+
+    /// Called when `$EDITOR` exits: read back comments, or a bulk edit and
+    /// show it for review
+    pub fn editor_closed(&mut self) -> Result<()> {
+        if let Some((path, name)) = self.comment_edit.take() {
+            return self.finish_comment_edit(path, name);
+        }
+        let Some(edit) = &mut self.bulk_edit else {
+            return Ok(());
+        };
+        let content = match std::fs::read_to_string(&edit.path) {
+            Ok(content) => content,
+            Err(e) => {
+                self.message = Some(fill(self.text.bulk_edit_failed, &[&e]));
+                self.bulk_edit = None;
+                return Ok(());
+            }
+        };
+        let (entries, mut errors) = bulk_edit::parse(&content);
+        let diff = bulk_edit::diff(&self.entries, &entries);
+        // New and changed commands go through the same checks as the prompts
+        for entry in entries.iter().filter(|e| diff.added.contains(&e.name) || diff.changed.contains(&e.name)) {
+            let hooks = [&entry.on_success, &entry.on_failure].into_iter().flatten();
+            for command in std::iter::once(&entry.command).chain(hooks) {
+                if let Some(refusal) = self.command_refusal(command) {
+                    errors.push(format!("{}: {}", entry.name, refusal));
+                }
+            }
+            if let Err(e) = self.check_strict(&entry.schedule) {
+                errors.push(format!("{}: {}", entry.name, e));
+            }
+        }
+        if let Some(edit) = &mut self.bulk_edit {
+            (edit.entries, edit.diff, edit.errors) = (entries, diff, errors);
+        }
+        self.input_mode = InputMode::ReviewingEdit;
+        self.message = None;
+        Ok(())
+    }
+
+    /// Reopen the edited file to fix it
+    pub fn reopen_bulk_edit(&mut self) {
+        if let Some(edit) = &self.bulk_edit {
+            self.editor_request = Some(edit.path.clone());
+        }
+    }
+
+    /// Replace the entries with the edited ones, unless the edit has errors
+    pub fn apply_bulk_edit(&mut self) -> Result<()> {
+        if self.bulk_edit.as_ref().is_some_and(|edit| !edit.errors.is_empty()) {
+            self.message = Some(self.text.bulk_edit_has_errors.to_string());
+            return Ok(());
+        }
+        let Some(edit) = self.bulk_edit.take() else {
+            return Ok(());
+        };
+        let _ = std::fs::remove_file(&edit.path);
+        self.input_mode = InputMode::Normal;
+        if edit.diff.is_empty() {
+            self.message = Some(self.text.bulk_edit_unchanged.to_string());
+            return Ok(());
+        }
+        self.entries = edit.entries;
+        self.selected_index = self.selected_index.min(self.entries.len().saturating_sub(1));
+        self.save()?;
+        let diff = edit.diff;
+        self.message = Some(fill(self.text.bulk_edit_applied, &[&diff.added.len(), &diff.removed.len(), &diff.changed.len()]));
+        Ok(())
+    }
+
+    pub fn discard_bulk_edit(&mut self) {
+        if let Some(edit) = self.bulk_edit.take() {
+            let _ = std::fs::remove_file(&edit.path);
+        }
+        self.input_mode = InputMode::Normal;
+        self.message = Some(self.text.cancelled.to_string());
+    }
+
+    /// Ask whether to delete the selected entry, unless `confirm_delete` is off
+    pub fn request_delete(&mut self) -> Result<()> {
+        if self.entries.get(self.selected_index).is_none() {
+            return Ok(());
+        }
+        if !self.config.confirm_delete {
+            return self.delete_entry();
+        }
+        self.input_mode = InputMode::ConfirmDelete;
+        Ok(())
+    }
+
+    /// Answer the delete confirmation
+    pub fn confirm_delete(&mut self, delete: bool) -> Result<()> {
+        self.input_mode = InputMode::Normal;
+        if delete {
+            self.delete_entry()
+        } else {
+            self.message = Some(self.text.delete_cancelled.to_string());
+            Ok(())
+        }
+    }
+
+    /// Delete the selected entry, keeping a copy in the trash unless it is turned off
+    pub fn delete_entry(&mut self) -> Result<()> {
+        let Some(entry) = self.entries.get(self.selected_index) else {
+            return Ok(());
+        };
+        let mut message = self.text.entry_deleted.to_string();
+        if let Some(trash) = self.storage.trash() {
+            // Without a copy in the trash, the entry stays
+            if let Err(e) = trash.add(entry.clone(), chrono::Utc::now()) {
+                self.message = Some(format!("{:#}", e));
+                return Ok(());
+            }
+            message = fill(self.text.entry_trashed, &[&entry.name, &trash.retention_days()]);
+        }
+        self.entries.remove(self.selected_index);
+        if self.selected_index > 0 && self.selected_index >= self.entries.len() {
+            self.selected_index -= 1;
+        }
+        self.save()?;
+        self.message = Some(message);
+        Ok(())
+    }
+
+    pub fn open_trash(&mut self) {
+        let Some(trash) = self.storage.trash() else {
+            self.message = Some(self.text.trash_disabled.to_string());
+            return;
+        };
+        match trash.list(chrono::Utc::now()) {
+            Ok(items) if items.is_empty() => self.message = Some(self.text.trash_empty.to_string()),
+            Ok(items) => {
+                self.message = Some(fill(self.text.trash_summary, &[&items.len(), &trash.retention_days()]));
+                self.trash_browser = Some(TrashBrowser { items, selected: 0 });
+                self.input_mode = InputMode::BrowsingTrash;
+            }
+            Err(e) => self.message = Some(format!("{:#}", e)),
+        }
+    }
+
+    pub fn move_trash_selection(&mut self, down: bool) {
+        if let Some(browser) = &mut self.trash_browser {
+            if down {
+                browser.selected = (browser.selected + 1).min(browser.items.len().saturating_sub(1));
+            } else {
+                browser.selected = browser.selected.saturating_sub(1);
+            }
+        }
+    }
+
+    /// Put the highlighted entry back at the end of the list. An entry with
+    /// the same name has to be renamed or deleted first.
+    pub fn restore_from_trash(&mut self) -> Result<()> {
+        let Some(item) = self.trash_browser.as_ref().and_then(|b| b.items.get(b.selected)).cloned() else {
+            return Ok(());
+        };
+        if self.entries.iter().any(|e| e.name == item.entry.name) {
+            self.message = Some(fill(self.text.trash_name_taken, &[&item.entry.name]));
+            return Ok(());
+        }
+        let Some(entry) = self.take_from_trash(&item) else {
+            return Ok(());
+        };
+        let name = entry.name.clone();
+        self.entries.push(entry);
+        self.selected_index = self.entries.len() - 1;
+        self.save()?;
+        self.message = Some(fill(self.text.entry_restored_from_trash, &[&name]));
+        Ok(())
+    }
+
+    /// Drop the highlighted entry from the trash for good
+    pub fn purge_from_trash(&mut self) {
+        let Some(item) = self.trash_browser.as_ref().and_then(|b| b.items.get(b.selected)).cloned() else {
+            return;
+        };
+        if self.take_from_trash(&item).is_some() {
+            self.message = Some(fill(self.text.trash_purged, &[&item.entry.name]));
+        }
+    }
+
+    /// Remove an item from the trash and the view, closing the view when it
+    /// runs empty
+    fn take_from_trash(&mut self, item: &TrashedEntry) -> Option<CronEntry> {
+        let trash = self.storage.trash()?;
+        let entry = match trash.remove(item, chrono::Utc::now()) {
+            Ok(entry) => entry,
+            Err(e) => {
+                self.message = Some(format!("{:#}", e));
+                return None;
+            }
+        };
+        if let Some(browser) = &mut self.trash_browser {
+            browser.items.retain(|i| i != item);
+            browser.selected = browser.selected.min(browser.items.len().saturating_sub(1));
+            if browser.items.is_empty() {
+                self.close_trash();
+            }
+        }
+        entry
+    }
+
+    pub fn close_trash(&mut self) {
+        self.trash_browser = None;
+        self.input_mode = InputMode::Normal;
+        self.message = None;
+    }
+
+    /// List the selected entry's runs from the last `STATS_DAYS` days with
+    /// the end of their output
+    pub fn open_runs(&mut self) {
+        let Some(entry) = self.entries.get(self.selected_index) else {
+            return;
+        };
+        let since = chrono::Utc::now() - chrono::Duration::days(run_history::STATS_DAYS as i64);
+        match self.storage.runs_since(since, self.config.data_dir.as_deref()) {
+            Ok(mut runs) => {
+                runs.retain(|run| run.entry == entry.name);
+                if runs.is_empty() {
+                    self.message = Some(fill(self.text.runs_none, &[&entry.name]));
+                    return;
+                }
+                runs.reverse();
+                self.message = Some(fill(self.text.runs_summary, &[&runs.len(), &run_history::STATS_DAYS]));
+                self.run_browser = Some(RunBrowser { entry: entry.name.clone(), runs, selected: 0 });
+                self.input_mode = InputMode::BrowsingRuns;
+            }
+            Err(e) => self.message = Some(format!("{:#}", e)),
+        }
+    }
+
+    pub fn move_runs_selection(&mut self, down: bool) {
+        if let Some(browser) = &mut self.run_browser {
+            if down {
+                browser.selected = (browser.selected + 1).min(browser.runs.len().saturating_sub(1));
+            } else {
+                browser.selected = browser.selected.saturating_sub(1);
+            }
+        }
+    }
+
+    pub fn close_runs(&mut self) {
+        self.run_browser = None;
+        self.input_mode = InputMode::Normal;
+        self.message = None;
+    }
+
+    /// Clone the entries into a sandbox to experiment on; in a sandbox,
+    /// review the changes to push back to the live backend instead
+    pub fn start_sandbox(&mut self) -> Result<()> {
+        if self.storage.origin().is_some() {
+            return self.open_push_review();
+        }
+        self.sandbox_request = Some(self.entries.clone());
+        Ok(())
+    }
+
+    /// List how the sandbox differs from the live backend as it is now
+    pub fn open_push_review(&mut self) -> Result<()> {
+        let Some(origin) = self.storage.origin() else {
+            return Ok(());
+        };
+        let (live, name) = (origin.load()?, origin.get_backend_name());
+        let changes = audit::diff(&live, &self.entries, chrono::Utc::now(), None);
+        if changes.is_empty() {
+            self.close_push_review();
+            self.message = Some(fill(self.text.sandbox_in_sync, &[&name]));
+            return Ok(());
+        }
+        self.message = Some(fill(self.text.push_summary, &[&changes.len(), &name]));
+        let selected = self.push_review.as_ref().map_or(0, |r| r.selected.min(changes.len() - 1));
+        self.push_review = Some(PushReview { picked: vec![false; changes.len()], changes, selected });
+        self.input_mode = InputMode::ReviewingPush;
+        Ok(())
+    }
+
+    pub fn move_push_selection(&mut self, down: bool) {
+        if let Some(review) = &mut self.push_review {
+            if down {
+                review.selected = (review.selected + 1).min(review.changes.len().saturating_sub(1));
+            } else {
+                review.selected = review.selected.saturating_sub(1);
+            }
+        }
+    }
+
+    pub fn toggle_push_pick(&mut self) {
+        if let Some(review) = &mut self.push_review {
+            if let Some(picked) = review.picked.get_mut(review.selected) {
+                *picked = !*picked;
+            }
+        }
+    }
+
+    /// Apply the picked changes (or the highlighted one, if none is picked)
+    /// to the live backend, leaving its other entries as they are
+    pub fn push_picked(&mut self) -> Result<()> {
+        let (Some(review), Some(origin)) = (&self.push_review, self.storage.origin()) else {
+            return Ok(());
+        };
+        let mut picked: Vec<&AuditRecord> =
+            review.changes.iter().zip(&review.picked).filter(|(_, picked)| **picked).map(|(change, _)| change).collect();
+        if picked.is_empty() {
+            picked.extend(review.changes.get(review.selected));
+        }
+        let mut live = origin.load()?;
+        for change in &picked {
+            let position = live.iter().position(|e| e.name == change.entry);
+            let entry = self.entries.iter().find(|e| e.name == change.entry).cloned();
+            match (change.kind, position, entry) {
+                (ChangeKind::Removed, Some(position), _) => {
+                    live.remove(position);
+                }
+                (_, Some(position), Some(entry)) => live[position] = entry,
+                (_, None, Some(entry)) => live.push(entry),
+                _ => {}
+            }
+        }
+        let report = origin.save(&live)?;
+        self.origin_changed = true;
+        let count = picked.len();
+        self.open_push_review()?;
+        self.message = Some(if report.is_complete() {
+            fill(self.text.pushed_changes, &[&count, &origin_name(&self.storage)])
+        } else {
+            fill(self.text.push_partial, &[&report.failed.len(), &origin_name(&self.storage)])
+        });
+        Ok(())
+    }
+
+    pub fn close_push_review(&mut self) {
+        self.push_review = None;
+        self.input_mode = InputMode::Normal;
+        self.message = None;
+    }
+
+    /// The local file and the system scheduler, when this tab can sync
+    fn sync_sides(&self) -> Option<Sync<'_>> {
+        let peer = self.sync_peer.as_ref()?;
+        let storage: &Storage = &self.storage;
+        let (local, system) = if peer.local { (storage, &peer.storage) } else { (&peer.storage, storage) };
+        Some(Sync::new(local, system, &self.config))
+    }
+
+    /// List how the local file differs from the system scheduler, after
+    /// writing pending changes
+    pub fn open_sync(&mut self) -> Result<()> {
+        let Some(sync) = self.sync_sides() else {
+            self.message = Some(self.text.sync_unavailable.to_string());
+            return Ok(());
+        };
+        let backend = sync.system.get_backend_name();
+        self.flush_save()?;
+        let Some(sync) = self.sync_sides() else {
+            return Ok(());
+        };
+        let changes = sync.changes()?;
+        if changes.is_empty() {
+            self.close_sync();
+            self.message = Some(fill(self.text.sync_in_sync, &[&backend]));
+            return Ok(());
+        }
+        self.message = Some(fill(self.text.sync_summary, &[&changes.len(), &backend]));
+        self.sync_review = Some(SyncReview { changes, selected: 0 });
+        self.input_mode = InputMode::ReviewingSync;
+        Ok(())
+    }
+
+    pub fn move_sync_selection(&mut self, down: bool) {
+        if let Some(review) = &mut self.sync_review {
+            if down {
+                review.selected = (review.selected + 1).min(review.changes.len().saturating_sub(1));
+            } else {
+                review.selected = review.selected.saturating_sub(1);
+            }
+        }
+    }
+
+    /// Make the local file and the system scheduler match, then read this
+    /// tab's entries again. A merge with entries changed on both sides is
+    /// refused; push or pull settles them.
+    pub fn sync(&mut self, direction: Direction) -> Result<()> {
+        let Some(sync) = self.sync_sides() else {
+            return Ok(());
+        };
+        let backend = sync.system.get_backend_name();
+        let entries = match sync.entries(direction) {
+            Ok(entries) => entries,
+            Err(e) if direction == Direction::Merge => {
+                self.message = Some(fill(self.text.sync_conflicts, &[&format!("{:#}", e)]));
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        };
+        let report = sync.apply(direction, &entries)?;
+        self.close_sync();
+        self.reload()?;
+        self.message = Some(if report.is_complete() {
+            let done = match direction {
+                Direction::Push => self.text.synced_push,
+                Direction::Pull => self.text.synced_pull,
+                Direction::Merge => self.text.synced_merge,
+            };
+            fill(done, &[&entries.len(), &backend])
+        } else {
+            fill(self.text.push_partial, &[&report.failed.len(), &backend])
+        });
+        Ok(())
+    }
+
+    pub fn close_sync(&mut self) {
+        self.sync_review = None;
+        self.input_mode = InputMode::Normal;
+        self.message = None;
+    }
+
+    /// Read the entries again from the backend, e.g. after a sandbox pushed
+    /// to it. Unsaved changes are kept; they are merged on save.
+    pub fn reload(&mut self) -> Result<()> {
+        if self.has_unsaved_changes() || self.conflict_resolver.is_some() {
+            return Ok(());
+        }
+        let entries = self.storage.load()?;
+        self.set_merged(entries.clone(), entries);
+        Ok(())
+    }
+
+    /// Every `reload_interval_secs`, take in changes made to the backend
+    /// outside cron-manager. Only while the list is just being looked at:
+    /// unsaved edits are merged with them on save instead.
+    fn reload_if_changed(&mut self) {
+        let mut interval = self.config.reload_interval_secs;
+        if interval == 0 {
+            return;
+        }
+        if self.storage.host().is_some() {
+            interval = interval.max(REMOTE_RELOAD_SECS);
+        }
+        if self.reload_checked.elapsed() < Duration::from_secs(interval)
+            || self.input_mode != InputMode::Normal
+            || self.has_unsaved_changes()
+            || self.conflict_resolver.is_some()
+        {
+            return;
+        }
+        self.reload_checked = Instant::now();
+        // Taken before loading, so a change made in between is seen next time
+        let fingerprint = self.storage.fingerprint();
+        if fingerprint.is_some() && fingerprint == self.reload_fingerprint {
+            return;
+        }
+        let theirs = match self.storage.load() {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::warn!(error = %format!("{:#}", e), "failed to check the backend for changes");
+                return;
+            }
+        };
+        self.reload_fingerprint = fingerprint;
+        if theirs == self.loaded {
+            return;
+        }
+        tracing::info!(entries = theirs.len(), "backend changed outside cron-manager; reloading");
+        let selected = self.entries.get(self.selected_index).map(|entry| entry.name.clone());
+        self.set_merged(theirs.clone(), theirs);
+        if let Some(index) = selected.and_then(|name| self.entries.iter().position(|entry| entry.name == name)) {
+            self.selected_index = index;
+        }
+        self.message = Some(self.text.reloaded_from_disk.to_string());
+    }
+
+    /// Move the selected entry past the one shown above or below it, which
+    /// is the order the crontab is written in; not past its tag group. With
+    /// a tag filter the two can be far apart in the file: the entry goes
+    /// right next to its neighbour, and the hidden ones between keep their
+    /// order.
+    pub fn move_entry(&mut self, down: bool) -> Result<()> {
+        let rows = self.list_rows();
+        let Some(row) = rows.iter().position(|row| *row == ListRow::Entry(self.selected_index)) else {
+            return Ok(());
+        };
+        let neighbor = if down { rows.get(row + 1) } else { row.checked_sub(1).and_then(|above| rows.get(above)) };
+        let Some(ListRow::Entry(other)) = neighbor.cloned() else {
+            return Ok(());
+        };
+        let (entry, neighbor) = (&self.entries[self.selected_index], &self.entries[other]);
+        if let Some(line) = self.storage.environment_between(entry, neighbor) {
+            self.message = Some(fill(self.text.move_crosses_environment, &[&entry.name, &line]));
+            return Ok(());
+        }
+        let entry = self.entries.remove(self.selected_index);
+        let other = if other > self.selected_index { other - 1 } else { other };
+        let at = if down { other + 1 } else { other };
+        self.entries.insert(at, entry);
+        self.selected_index = at;
+        self.save()?;
+        self.message = Some(fill(self.text.entry_moved, &[&self.entries[at].name]));
+        Ok(())
+    }
+
+    pub fn toggle_enabled(&mut self) -> Result<()> {
+        if let Some(entry) = self.entries.get_mut(self.selected_index) {
+            entry.enabled = !entry.enabled;
+            entry.snoozed_until = None;
+        }
+        self.save()?;
+        if let Some(entry) = self.entries.get(self.selected_index) {
+            let template = if entry.enabled { self.text.entry_enabled } else { self.text.entry_disabled };
+            self.message = Some(fill(template, &[&entry.name]));
+        }
+        Ok(())
+    }
+
+    /// Flip whether the selected entry skips the dates in the exclusion calendar
+    pub fn toggle_skip_excluded(&mut self) -> Result<()> {
+        let Some(entry) = self.entries.get_mut(self.selected_index) else {
+            return Ok(());
+        };
+        entry.skip_excluded = !entry.skip_excluded;
+        let (name, skip) = (entry.name.clone(), entry.skip_excluded);
+        self.save()?;
+        self.message = Some(if skip {
+            let excluded = ExclusionCalendar::default_path(self.config.data_dir.as_deref())
+                .and_then(|path| ExclusionCalendar::new(path).load().ok())
+                .map_or(0, |dates| dates.len());
+            fill(self.text.skips_excluded, &[&name, &excluded])
+        } else {
+            fill(self.text.runs_on_excluded, &[&name])
+        });
+        Ok(())
+    }
+
+    /// Flag the selected entry for a watchdog, which alerts when one of its
+    /// runs doesn't happen
+    pub fn toggle_watchdog(&mut self) -> Result<()> {
+        let Some(entry) = self.entries.get_mut(self.selected_index) else {
+            return Ok(());
+        };
+        entry.watchdog = !entry.watchdog;
+        let (name, watchdog) = (entry.name.clone(), entry.watchdog);
+        self.save()?;
+        let template = if watchdog { self.text.watchdog_on } else { self.text.watchdog_off };
+        self.message = Some(fill(template, &[&name]));
+        Ok(())
+    }
+
+    pub fn toggle_preview(&mut self) {
+        self.show_preview = !self.show_preview;
+        self.message = Some(if self.show_preview { self.text.preview_on } else { self.text.preview_off }.to_string());
+    }
+
+    /// Switch to the next built-in color preset, keeping the configured colors
+    pub fn cycle_theme(&mut self) {
+        self.theme = Theme::new(&self.config.theme, self.theme.preset.next());
+        self.message = Some(fill(self.text.theme_switched, &[&self.theme.preset.name()]));
+    }
+
+    /// Show the Quartz equivalent of the selected entry's schedule
+    pub fn show_quartz_expression(&mut self) {
+        if let Some(entry) = self.entries.get(self.selected_index) {
+            self.message = Some(match QuartzExpression::from_standard(&entry.schedule) {
+                Ok(quartz) => fill(self.text.quartz_expression, &[&quartz]),
+                Err(e) => fill(self.text.no_quartz_equivalent, &[&e]),
+            });
+        }
+    }
+
+    /// Open the preset popup in the schedule prompts
+    pub fn open_preset_picker(&mut self) {
+        if matches!(self.input_mode, InputMode::AddingSchedule | InputMode::EditingSchedule) {
+            let current = suggest::PRESETS.iter().position(|p| p.value == self.input_buffer.trim());
+            self.preset_picker = Some(current.unwrap_or(0));
+        }
+    }
+
+    pub fn move_preset_selection(&mut self, down: bool) {
+        if let Some(index) = &mut self.preset_picker {
+            let len = suggest::PRESETS.len();
+            *index = if down { (*index + 1) % len } else { (*index + len - 1) % len };
         }
     }
 
-    pub fn move_selection_down(&mut self) {
-        if !self.entries.is_empty() && self.selected_index < self.entries.len() - 1 {
-            self.selected_index += 1;
+    /// Put the highlighted preset in the schedule prompt, ready to confirm
+    pub fn apply_preset(&mut self) {
+        if let Some(index) = self.preset_picker.take() {
+            self.completion = None;
+            self.set_input(suggest::PRESETS[index].value.to_string());
         }
     }
 
-    pub fn start_add_entry(&mut self) {
-        self.input_mode = InputMode::AddingName;
-        self.input_buffer.clear();
-        self.message = Some("Enter name for new cron entry:".to_string());
+    pub fn close_preset_picker(&mut self) {
+        self.preset_picker = None;
     }
 
-    pub fn start_edit_name(&mut self) {
-        if let Some(entry) = self.entries.get(self.selected_index) {
-            self.input_mode = InputMode::EditingName;
-            self.input_buffer = entry.name.clone();
-            self.message = Some("Edit name:".to_string());
+    /// Replace the text being typed, with the cursor at its end
+    pub fn set_input(&mut self, text: impl Into<String>) {
+        self.input_buffer = text.into();
+        self.input_cursor = self.input_buffer.chars().count();
+    }
+
+    /// Cursor position in the text being typed, in characters
+    pub fn input_cursor(&self) -> usize {
+        self.input_cursor.min(self.input_buffer.chars().count())
+    }
+
+    /// Byte offset in `input_buffer` of the character at `cursor`
+    fn input_offset(&self, cursor: usize) -> usize {
+        self.input_buffer.char_indices().nth(cursor).map_or(self.input_buffer.len(), |(offset, _)| offset)
+    }
+
+    pub fn handle_input_char(&mut self, c: char) {
+        self.completion = None;
+        let cursor = self.input_cursor();
+        self.input_buffer.insert(self.input_offset(cursor), c);
+        self.input_cursor = cursor + 1;
+    }
+
+    /// Delete the character before the cursor
+    pub fn handle_input_backspace(&mut self) {
+        self.completion = None;
+        let cursor = self.input_cursor();
+        if cursor > 0 {
+            self.input_buffer.remove(self.input_offset(cursor - 1));
+            self.input_cursor = cursor - 1;
         }
     }
 
-    pub fn start_edit_schedule(&mut self) {
-        if let Some(entry) = self.entries.get(self.selected_index) {
-            self.input_mode = InputMode::EditingSchedule;
-            self.input_buffer = entry.schedule.clone();
-            self.message = Some("Edit schedule (cron format):".to_string());
+    /// Insert pasted text at the cursor, on one line
+    pub fn paste(&mut self, text: &str) {
+        if !self.input_mode.takes_text() || self.preset_picker.is_some() {
+            return;
         }
+        let command = matches!(
+            self.input_mode,
+            InputMode::AddingCommand | InputMode::EditingCommand | InputMode::EditingSuccessHook | InputMode::EditingFailureHook
+        );
+        self.completion = None;
+        let text = clipboard::sanitize(text, command);
+        let cursor = self.input_cursor();
+        self.input_buffer.insert_str(self.input_offset(cursor), &text);
+        self.input_cursor = cursor + text.chars().count();
     }
 
-    pub fn start_edit_command(&mut self) {
-        if let Some(entry) = self.entries.get(self.selected_index) {
-            self.input_mode = InputMode::EditingCommand;
-            self.input_buffer = entry.command.clone();
-            self.message = Some("Edit command:".to_string());
+    /// Paste the system clipboard (`Ctrl+V`), for terminals that don't
+    /// send pastes themselves
+    pub fn paste_clipboard(&mut self) {
+        match clipboard::read() {
+            Ok(text) => self.paste(&text),
+            Err(e) => self.message = Some(fill(self.text.paste_failed, &[&e])),
         }
     }
 
-    pub fn delete_entry(&mut self) -> Result<()> {
-        if !self.entries.is_empty() && self.selected_index < self.entries.len() {
-            self.entries.remove(self.selected_index);
-            if self.selected_index > 0 && self.selected_index >= self.entries.len() {
-                self.selected_index -= 1;
+    /// Delete the character under the cursor
+    pub fn handle_input_delete(&mut self) {
+        self.completion = None;
+        let cursor = self.input_cursor();
+        if cursor < self.input_buffer.chars().count() {
+            self.input_buffer.remove(self.input_offset(cursor));
+        }
+    }
+
+    pub fn move_input_cursor(&mut self, motion: CursorMotion) {
+        let chars: Vec<char> = self.input_buffer.chars().collect();
+        let cursor = self.input_cursor();
+        self.input_cursor = match motion {
+            CursorMotion::Left => cursor.saturating_sub(1),
+            CursorMotion::Right => (cursor + 1).min(chars.len()),
+            CursorMotion::WordLeft => {
+                let mut cursor = cursor;
+                while cursor > 0 && chars[cursor - 1].is_whitespace() {
+                    cursor -= 1;
+                }
+                while cursor > 0 && !chars[cursor - 1].is_whitespace() {
+                    cursor -= 1;
+                }
+                cursor
             }
-            self.save()?;
-            self.message = Some("Entry deleted".to_string());
+            CursorMotion::WordRight => {
+                let mut cursor = cursor;
+                while cursor < chars.len() && !chars[cursor].is_whitespace() {
+                    cursor += 1;
+                }
+                while cursor < chars.len() && chars[cursor].is_whitespace() {
+                    cursor += 1;
+                }
+                cursor
+            }
+            CursorMotion::Home => 0,
+            CursorMotion::End => chars.len(),
+        };
+    }
+
+    /// Suggestions for the schedule field being typed, and which one Tab last
+    /// inserted. Empty outside the schedule prompts and for plain-English input.
+    pub fn schedule_suggestions(&self) -> Option<(FieldKind, Vec<Suggestion>, Option<usize>)> {
+        if !matches!(self.input_mode, InputMode::AddingSchedule | InputMode::EditingSchedule) {
+            return None;
         }
-        Ok(())
+        let (typed, selected) = match &self.completion {
+            Some((base, index)) => (base.as_str(), Some(*index)),
+            None => (self.input_buffer.as_str(), None),
+        };
+        if natural::looks_like_natural(typed) {
+            return None;
+        }
+        let (kind, suggestions) = suggest::suggestions_for(typed)?;
+        Some((kind, suggestions, selected))
     }
 
-    pub fn toggle_enabled(&mut self) -> Result<()> {
-        if let Some(entry) = self.entries.get_mut(self.selected_index) {
-            entry.enabled = !entry.enabled;
+    fn load_history(&mut self) {
+        if self.history.is_none() && self.config.suggest_from_history {
+            self.history = Some(history::load_commands());
         }
-        self.save()?;
-        if let Some(entry) = self.entries.get(self.selected_index) {
-            self.message = Some(format!(
-                "Entry {} {}",
-                entry.name,
-                if entry.enabled { "enabled" } else { "disabled" }
-            ));
+    }
+
+    /// Shell history commands starting with the typed command, and which one
+    /// Tab last inserted. Empty outside the command prompts.
+    pub fn command_suggestions(&self) -> Option<(Vec<&str>, Option<usize>)> {
+        if !matches!(self.input_mode, InputMode::AddingCommand | InputMode::EditingCommand) {
+            return None;
         }
-        Ok(())
+        let (typed, selected) = match &self.completion {
+            Some((base, index)) => (base.as_str(), Some(*index)),
+            None => (self.input_buffer.as_str(), None),
+        };
+        let suggestions = history::suggestions(self.history.as_deref()?, typed, HISTORY_SUGGESTIONS);
+        (!suggestions.is_empty()).then_some((suggestions, selected))
     }
 
-    pub fn handle_input_char(&mut self, c: char) {
-        self.input_buffer.push(c);
+    /// Tab: complete the schedule field or the command being typed
+    pub fn complete_input(&mut self) {
+        if let Some((suggestions, selected)) = self.command_suggestions() {
+            let index = selected.map_or(0, |i| (i + 1) % suggestions.len());
+            let command = suggestions[index].to_string();
+            let base = match self.completion.take() {
+                Some((base, _)) => base,
+                None => self.input_buffer.clone(),
+            };
+            self.set_input(command);
+            self.completion = Some((base, index));
+        } else {
+            self.complete_schedule_field();
+        }
     }
 
-    pub fn handle_input_backspace(&mut self) {
-        self.input_buffer.pop();
+    /// Fill the current schedule field with the next suggestion (Tab)
+    fn complete_schedule_field(&mut self) {
+        let Some((_, suggestions, selected)) = self.schedule_suggestions() else {
+            return;
+        };
+        let index = selected.map_or(0, |i| (i + 1) % suggestions.len());
+        let base = match self.completion.take() {
+            Some((base, _)) => base,
+            None => self.input_buffer.clone(),
+        };
+        self.set_input(suggest::complete(&base, suggestions[index].value));
+        self.completion = Some((base, index));
     }
 
     pub fn confirm_input(&mut self) -> Result<()> {
+        self.completion = None;
+        if matches!(self.input_mode, InputMode::AddingSchedule | InputMode::EditingSchedule)
+            && natural::looks_like_natural(&self.input_buffer)
+        {
+            self.preview_natural_schedule();
+            return Ok(());
+        }
+
         match self.input_mode {
-            InputMode::AddingName => {
-                if !self.input_buffer.is_empty() {
-                    self.temp_name = self.input_buffer.clone();
-                    self.input_buffer.clear();
-                    self.input_mode = InputMode::AddingSchedule;
-                    self.message = Some(format!("Name: {} | Enter schedule (cron format):", self.temp_name));
-                }
+            InputMode::AddingName if !self.input_buffer.is_empty() => {
+                self.temp_name = self.input_buffer.clone();
+                self.input_buffer.clear();
+                self.input_mode = InputMode::AddingSchedule;
+                self.message = Some(fill(self.text.prompt_schedule, &[&self.temp_name]));
             }
-            InputMode::AddingSchedule => {
-                if !self.input_buffer.is_empty() {
-                    self.temp_schedule = self.input_buffer.clone();
-                    self.input_buffer.clear();
-                    self.input_mode = InputMode::AddingCommand;
-                    self.message = Some(format!("Name: {} | Schedule: {} | Enter command:", self.temp_name, self.temp_schedule));
+            InputMode::AddingSchedule if !self.input_buffer.is_empty() => {
+                let (schedule, note) = resolve_schedule(&self.input_buffer, self.text);
+                if self.offer_conversion(&schedule) {
+                    return Ok(());
                 }
+                if let Err(e) = self.check_strict(&schedule) {
+                    self.message = Some(fill(self.text.invalid_schedule_enter, &[&e]));
+                    return Ok(());
+                }
+                self.temp_schedule = schedule;
+                self.input_buffer.clear();
+                self.input_mode = InputMode::AddingCommand;
+                self.load_history();
+                let prompt = fill(self.text.prompt_command, &[&self.temp_name, &self.temp_schedule]);
+                self.message = Some(match note {
+                    Some(note) => format!("{} | {}", note, prompt),
+                    None => prompt,
+                });
             }
-            InputMode::AddingCommand => {
-                if !self.input_buffer.is_empty() {
-                    self.finish_add_entry()?;
+            InputMode::AddingScript => self.create_script(),
+            InputMode::ImportingFile if !self.input_buffer.trim().is_empty() => self.read_import()?,
+            InputMode::AddingCommand if !self.input_buffer.is_empty() => {
+                if let Some(refusal) = self.command_refusal(&self.input_buffer) {
+                    self.message = Some(refusal);
+                    return Ok(());
                 }
+                self.finish_add_entry()?;
             }
             InputMode::EditingName => {
                 if let Some(entry) = self.entries.get_mut(self.selected_index) {
@@ -148,28 +2361,253 @@ impl App {
                     self.save()?;
                     self.input_mode = InputMode::Normal;
                     self.input_buffer.clear();
-                    self.message = Some("Name updated".to_string());
+                    self.message = Some(self.text.name_updated.to_string());
                 }
             }
             InputMode::EditingSchedule => {
+                let (schedule, note) = resolve_schedule(&self.input_buffer, self.text);
+                if self.offer_conversion(&schedule) {
+                    return Ok(());
+                }
+                if let Err(e) = self.check_strict(&schedule) {
+                    self.message = Some(fill(self.text.invalid_schedule_edit, &[&e]));
+                    return Ok(());
+                }
                 if let Some(entry) = self.entries.get_mut(self.selected_index) {
-                    entry.schedule = self.input_buffer.clone();
-                    if !entry.validate_schedule() {
-                        self.message = Some("Warning: Invalid cron schedule format".to_string());
-                    }
+                    entry.schedule = schedule;
+                    let valid = entry.validate_schedule();
                     self.save()?;
                     self.input_mode = InputMode::Normal;
                     self.input_buffer.clear();
-                    self.message = Some("Schedule updated".to_string());
+                    self.message = Some(match (valid, note) {
+                        (false, _) => self.text.schedule_updated_invalid.to_string(),
+                        (true, Some(note)) => fill(self.text.schedule_updated_note, &[&note]),
+                        (true, None) => self.text.schedule_updated.to_string(),
+                    });
+                    self.warn_if_crowded(self.selected_index);
                 }
             }
             InputMode::EditingCommand => {
+                if let Some(refusal) = self.command_refusal(&self.input_buffer) {
+                    self.message = Some(refusal);
+                    return Ok(());
+                }
                 if let Some(entry) = self.entries.get_mut(self.selected_index) {
                     entry.command = self.input_buffer.clone();
                     self.save()?;
                     self.input_mode = InputMode::Normal;
                     self.input_buffer.clear();
-                    self.message = Some("Command updated".to_string());
+                    self.message = Some(self.text.command_updated.to_string());
+                }
+            }
+            InputMode::EditingTimezone => {
+                let timezone = self.input_buffer.trim().to_string();
+                if !timezone.is_empty() {
+                    if let Err(e) = timezone::parse_tz(&timezone) {
+                        self.message = Some(fill(self.text.invalid_timezone, &[&e]));
+                        return Ok(());
+                    }
+                }
+                if let Some(entry) = self.entries.get_mut(self.selected_index) {
+                    entry.timezone = (!timezone.is_empty()).then_some(timezone);
+                    self.save()?;
+                    self.input_mode = InputMode::Normal;
+                    self.input_buffer.clear();
+                    self.message = Some(self.text.timezone_updated.to_string());
+                }
+            }
+            InputMode::EditingTags => {
+                if let Some(entry) = self.entries.get_mut(self.selected_index) {
+                    entry.tags = cron_entry::parse_tags(&self.input_buffer);
+                    self.save()?;
+                    self.input_mode = InputMode::Normal;
+                    self.input_buffer.clear();
+                    self.message = Some(self.text.tags_updated.to_string());
+                }
+            }
+            InputMode::EditingEnv => {
+                let preset = self.input_buffer.trim().to_string();
+                if !preset.is_empty() {
+                    if let Err(e) = self.storage.check_env_preset(&preset) {
+                        self.message = Some(fill(self.text.invalid_env_preset, &[&e]));
+                        return Ok(());
+                    }
+                }
+                if let Some(entry) = self.entries.get_mut(self.selected_index) {
+                    entry.env = (!preset.is_empty()).then_some(preset);
+                    self.save()?;
+                    self.input_mode = InputMode::Normal;
+                    self.input_buffer.clear();
+                    self.message = Some(self.text.env_updated.to_string());
+                }
+            }
+            InputMode::EditingShell => {
+                let shell = match Shell::parse(&self.input_buffer) {
+                    Ok(shell) => shell,
+                    Err(e) => {
+                        self.message = Some(fill(self.text.invalid_shell, &[&e]));
+                        return Ok(());
+                    }
+                };
+                if let Some(entry) = self.entries.get_mut(self.selected_index) {
+                    entry.shell = shell;
+                    self.save()?;
+                    self.input_mode = InputMode::Normal;
+                    self.input_buffer.clear();
+                    self.message = Some(self.text.shell_updated.to_string());
+                }
+            }
+            InputMode::EditingSuccessHook | InputMode::EditingFailureHook => {
+                let hook = self.input_buffer.trim().to_string();
+                if let Some(refusal) = (!hook.is_empty()).then(|| self.command_refusal(&hook)).flatten() {
+                    self.message = Some(refusal);
+                    return Ok(());
+                }
+                if self.input_mode == InputMode::EditingSuccessHook {
+                    self.temp_success_hook = hook;
+                    let on_failure = self.entries.get(self.selected_index).and_then(|entry| entry.on_failure.clone());
+                    self.set_input(on_failure.unwrap_or_default());
+                    self.input_mode = InputMode::EditingFailureHook;
+                    self.message = Some(self.text.prompt_failure_hook.to_string());
+                    return Ok(());
+                }
+                let on_success = std::mem::take(&mut self.temp_success_hook);
+                if let Some(entry) = self.entries.get_mut(self.selected_index) {
+                    entry.on_success = (!on_success.is_empty()).then_some(on_success);
+                    entry.on_failure = (!hook.is_empty()).then_some(hook);
+                    self.save()?;
+                    self.input_mode = InputMode::Normal;
+                    self.input_buffer.clear();
+                    self.message = Some(self.text.hooks_updated.to_string());
+                }
+            }
+            InputMode::EditingTimeout => {
+                let timeout = match self.input_buffer.trim() {
+                    "" => None,
+                    input => match cron_entry::parse_seconds(input) {
+                        Ok(seconds) => Some(seconds),
+                        Err(e) => {
+                            self.message = Some(fill(self.text.invalid_timeout, &[&e]));
+                            return Ok(());
+                        }
+                    },
+                };
+                self.temp_timeout = timeout;
+                let retries = match self.entries.get(self.selected_index) {
+                    Some(entry) if entry.retry_delay_secs > 0 => {
+                        format!("{}, {}", entry.retries, cron_entry::format_seconds(entry.retry_delay_secs))
+                    }
+                    Some(entry) if entry.retries > 0 => entry.retries.to_string(),
+                    _ => String::new(),
+                };
+                self.set_input(retries);
+                self.input_mode = InputMode::EditingRetries;
+                self.message = Some(self.text.prompt_edit_retries.to_string());
+            }
+            InputMode::EditingRetries => {
+                let (retries, delay) = match cron_entry::parse_retries(&self.input_buffer) {
+                    Ok(retries) => retries,
+                    Err(e) => {
+                        self.message = Some(fill(self.text.invalid_retries, &[&e]));
+                        return Ok(());
+                    }
+                };
+                let timeout = self.temp_timeout.take();
+                if let Some(entry) = self.entries.get_mut(self.selected_index) {
+                    entry.timeout_secs = timeout;
+                    entry.retries = retries;
+                    entry.retry_delay_secs = delay;
+                    self.save()?;
+                    self.input_mode = InputMode::Normal;
+                    self.input_buffer.clear();
+                    self.message = Some(self.text.limits_updated.to_string());
+                }
+            }
+            InputMode::EditingPing => {
+                let url = self.input_buffer.trim().to_string();
+                if !url.is_empty() && !ping::valid_url(&url) {
+                    self.message = Some(self.text.invalid_ping_url.to_string());
+                    return Ok(());
+                }
+                if let Some(entry) = self.entries.get_mut(self.selected_index) {
+                    entry.ping_url = (!url.is_empty()).then_some(url);
+                    self.save()?;
+                    self.input_mode = InputMode::Normal;
+                    self.input_buffer.clear();
+                    self.message = Some(self.text.ping_updated.to_string());
+                }
+            }
+            InputMode::SearchingLog => {
+                if self.input_buffer.is_empty() {
+                    self.log_search = None;
+                    self.input_mode = InputMode::ViewingLog;
+                    self.message = None;
+                    return Ok(());
+                }
+                match log_search::compile(&self.input_buffer) {
+                    Ok(pattern) => {
+                        self.log_search = Some((std::mem::take(&mut self.input_buffer), pattern));
+                        self.input_mode = InputMode::ViewingLog;
+                        self.seek_log_match(true, true)?;
+                    }
+                    Err(e) => self.message = Some(format!("{:#}", e)),
+                }
+            }
+            InputMode::BulkToggling => {
+                let filter = match EntryFilter::parse(&self.input_buffer) {
+                    Ok(filter) => filter,
+                    Err(e) => {
+                        self.message = Some(fill(self.text.invalid_bulk_filter, &[&e]));
+                        return Ok(());
+                    }
+                };
+                // Disable the group if any of it is running, otherwise enable it
+                let matched: Vec<&CronEntry> = self.entries.iter().filter(|e| filter.matches(e)).collect();
+                if matched.is_empty() {
+                    self.message = Some(fill(self.text.invalid_bulk_filter, &[&fill(self.text.no_match, &[&filter])]));
+                    return Ok(());
+                }
+                let enable = !matched.iter().any(|e| e.enabled);
+                let change = filter::set_enabled(&mut self.entries, &filter, enable);
+                self.save()?;
+                self.input_mode = InputMode::Normal;
+                self.input_buffer.clear();
+                let template = if enable { self.text.bulk_enabled } else { self.text.bulk_disabled };
+                self.message = Some(fill(template, &[&change.changed.len(), &filter, &change.changed.join(", ")]));
+            }
+            InputMode::FilteringTag => {
+                let tag = self.input_buffer.trim().trim_start_matches('#').trim();
+                if tag.is_empty() {
+                    self.tag_filter = None;
+                    self.message = Some(self.text.tag_filter_cleared.to_string());
+                } else if filter::list_rows(&self.entries, Some(tag), false).is_empty() {
+                    self.message = Some(fill(self.text.no_tagged_entries, &[&tag]));
+                    return Ok(());
+                } else {
+                    self.message = Some(fill(self.text.tag_filter_set, &[&tag]));
+                    self.tag_filter = Some(tag.to_string());
+                    self.keep_selection_shown();
+                }
+                self.input_mode = InputMode::Normal;
+                self.input_buffer.clear();
+            }
+            InputMode::Snoozing => {
+                let length = match cron_entry::parse_duration(&self.input_buffer) {
+                    Ok(length) => length,
+                    Err(e) => {
+                        self.message = Some(fill(self.text.invalid_snooze, &[&e]));
+                        return Ok(());
+                    }
+                };
+                // Snoozes change what is installed, so they follow the real clock
+                let until = Clock::system().current_minute() + length;
+                if let Some(entry) = self.entries.get_mut(self.selected_index) {
+                    entry.snooze(until);
+                    let name = entry.name.clone();
+                    self.save()?;
+                    self.input_mode = InputMode::Normal;
+                    self.input_buffer.clear();
+                    self.message = Some(fill(self.text.entry_snoozed, &[&name, &until.format("%Y-%m-%d %H:%M")]));
                 }
             }
             _ => {}
@@ -177,8 +2615,188 @@ impl App {
         Ok(())
     }
 
+    /// Run the `[[macros]]` binding for a key, if there is one: apply its
+    /// steps to the selected entry and save, or pipe the entry to its
+    /// command, which runs in the background
+    pub fn run_macro(&mut self, key: char) -> Result<()> {
+        let Some(binding) = self.config.macros.iter().find(|m| m.key == key).cloned() else {
+            return Ok(());
+        };
+        let label = binding.label();
+        if let Some(command) = &binding.command {
+            let Some(entry) = self.entries.get(self.selected_index) else {
+                return Ok(());
+            };
+            if let Some(run) = &self.macro_run {
+                self.message = Some(fill(self.text.macro_running, &[&run.label]));
+                return Ok(());
+            }
+            self.message = Some(fill(self.text.macro_running, &[&label]));
+            self.macro_run = Some(CommandRun::start(label, command, entry));
+            return Ok(());
+        }
+
+        let mut entries = self.entries.clone();
+        let mut selected = self.selected_index;
+        if let Err(e) = macros::apply(&binding.steps, &mut entries, &mut selected) {
+            self.message = Some(fill(self.text.macro_failed, &[&label, &format!("{:#}", e)]));
+            return Ok(());
+        }
+        // The same checks as typing the values in
+        let entry = &entries[selected];
+        let refusal = match &entry.env {
+            Some(env) => self.storage.check_env_preset(env).err().map(|e| e.to_string()),
+            None => None,
+        };
+        if let Some(refusal) = refusal.or_else(|| self.command_refusal(&entry.command)) {
+            self.message = Some(fill(self.text.macro_failed, &[&label, &refusal]));
+            return Ok(());
+        }
+        self.entries = entries;
+        self.selected_index = selected;
+        self.save()?;
+        self.message = Some(fill(self.text.macro_applied, &[&label, &self.entries[selected].name]));
+        Ok(())
+    }
+
+    /// Show what the macro's command printed once it has finished
+    fn poll_macro_run(&mut self) {
+        let Some(output) = self.macro_run.as_ref().and_then(CommandRun::poll) else {
+            return;
+        };
+        let label = self.macro_run.take().map(|run| run.label).unwrap_or_default();
+        self.message = Some(match output {
+            Ok(output) => fill(self.text.macro_ran, &[&label, &output]),
+            Err(e) => fill(self.text.macro_failed, &[&label, &format!("{:#}", e)]),
+        });
+    }
+
+    /// Why the typed command can't be saved: a missing script or secret, or
+    /// policy violations (checked against the script paths it will run)
+    fn command_refusal(&self, command: &str) -> Option<String> {
+        if let Err(e) = self.storage.check_placeholders(command) {
+            return Some(e.to_string());
+        }
+        let command = self.storage.resolve_scripts(command).ok()?;
+        let violations = self.policy.as_ref()?.violations(&command);
+        (!violations.is_empty()).then(|| fill(self.text.policy_refused, &[&violations.join("; ")]))
+    }
+
+    /// In strict mode, reject schedules that are invalid or that the backend
+    /// can't represent; otherwise accept everything
+    fn check_strict(&self, schedule: &str) -> Result<()> {
+        if self.config.strict_validation {
+            CronSchedule::parse(schedule)?;
+            self.storage.check_schedule(schedule)?;
+        }
+        Ok(())
+    }
+
+    /// Replace a plain-English schedule in the input buffer with the cron
+    /// expression it translates to, so the next Enter confirms it
+    fn preview_natural_schedule(&mut self) {
+        match natural::parse(&self.input_buffer) {
+            Ok(expression) => {
+                self.message = Some(fill(self.text.natural_preview, &[&self.input_buffer.trim()]));
+                self.set_input(expression);
+            }
+            Err(e) => {
+                self.message = Some(fill(self.text.natural_error, &[&e]));
+            }
+        }
+    }
+
+    /// Open the conversion wizard if the backend can't represent the
+    /// schedule but an equivalent or approximate one exists
+    fn offer_conversion(&mut self, schedule: &str) -> bool {
+        if std::mem::take(&mut self.keep_unsupported) || self.storage.check_schedule(schedule).is_ok() {
+            return false;
+        }
+        let options = split::conversions(schedule, |e| self.storage.check_schedule(e).is_ok());
+        if options.is_empty() {
+            return false;
+        }
+        self.completion = None;
+        self.conversion = Some(ConversionWizard {
+            schedule: schedule.to_string(),
+            options,
+            can_keep: !self.config.strict_validation,
+            selected: 0,
+        });
+        self.message = Some(fill(self.text.conversion_prompt, &[&self.storage.get_backend_name()]));
+        true
+    }
+
+    pub fn move_conversion_selection(&mut self, down: bool) {
+        if let Some(wizard) = &mut self.conversion {
+            let len = wizard.options.len() + wizard.can_keep as usize;
+            wizard.selected = if down { (wizard.selected + 1) % len } else { (wizard.selected + len - 1) % len };
+        }
+    }
+
+    /// Continue with the highlighted conversion, or with the schedule as written
+    pub fn apply_conversion(&mut self) -> Result<()> {
+        let Some(wizard) = self.conversion.take() else {
+            return Ok(());
+        };
+        match wizard.options.get(wizard.selected) {
+            Some(Conversion::Split(schedules)) if self.input_mode == InputMode::EditingSchedule => {
+                return self.split_selected_entry(schedules);
+            }
+            Some(Conversion::Split(schedules)) => {
+                self.set_input(schedules[0].clone());
+                self.temp_split = schedules.clone();
+            }
+            Some(Conversion::Approximate(schedule)) => self.set_input(schedule.clone()),
+            None => {
+                self.set_input(wizard.schedule);
+                self.keep_unsupported = true;
+            }
+        }
+        self.confirm_input()
+    }
+
+    /// Close the wizard, leaving the schedule in the prompt for editing
+    pub fn close_conversion(&mut self) {
+        self.conversion = None;
+        self.message = None;
+    }
+
+    /// Replace the selected entry with one copy per schedule
+    fn split_selected_entry(&mut self, schedules: &[String]) -> Result<()> {
+        let Some(entry) = self.entries.get(self.selected_index).cloned() else {
+            return Ok(());
+        };
+        let parts = schedules.iter().enumerate().map(|(i, schedule)| CronEntry {
+            name: split_name(&entry.name, i, schedules.len()),
+            schedule: schedule.clone(),
+            ..entry.clone()
+        });
+        self.entries.splice(self.selected_index..=self.selected_index, parts);
+        self.save()?;
+        self.input_mode = InputMode::Normal;
+        self.input_buffer.clear();
+        self.message = Some(fill(self.text.entry_split, &[&schedules.len()]));
+        Ok(())
+    }
+
     fn finish_add_entry(&mut self) -> Result<()> {
         let command = self.input_buffer.clone();
+        let split = std::mem::take(&mut self.temp_split);
+        if !split.is_empty() {
+            for (i, schedule) in split.iter().enumerate() {
+                let name = split_name(&self.temp_name, i, split.len());
+                self.entries.push(CronEntry::new(name, schedule.clone(), command.clone()));
+            }
+            self.save()?;
+            self.input_mode = InputMode::Normal;
+            self.input_buffer.clear();
+            self.temp_name.clear();
+            self.temp_schedule.clear();
+            self.message = Some(fill(self.text.entries_added_split, &[&split.len()]));
+            return Ok(());
+        }
+
         let entry = CronEntry::new(
             self.temp_name.clone(),
             self.temp_schedule.clone(),
@@ -186,13 +2804,16 @@ impl App {
         );
 
         if !entry.validate_schedule() {
-            self.message = Some("Warning: Invalid cron schedule format. Entry still added.".to_string());
+            self.message = Some(self.text.entry_added_invalid.to_string());
+        } else if let Some(existing) = self.entries.iter().find(|e| e.is_duplicate_of(&entry)) {
+            self.message = Some(fill(self.text.entry_added_duplicate, &[&existing.name]));
         } else {
-            self.message = Some("Entry added successfully".to_string());
+            self.message = Some(self.text.entry_added.to_string());
         }
 
         self.entries.push(entry);
         self.save()?;
+        self.warn_if_crowded(self.entries.len() - 1);
         self.input_mode = InputMode::Normal;
         self.input_buffer.clear();
         self.temp_name.clear();
@@ -200,20 +2821,295 @@ impl App {
         Ok(())
     }
 
+    /// Add a warning to the message when the entry starts in a minute more
+    /// jobs than `[collisions] max_per_minute` start in
+    fn warn_if_crowded(&mut self, index: usize) {
+        let now = self.clock.current_minute();
+        let Some((minute, started)) = collisions::crowded_minute(&self.entries, index, now, &self.config.collisions) else {
+            return;
+        };
+        let names: Vec<&str> = started.iter().map(|i| self.entries[*i].name.as_str()).collect();
+        let warning = fill(self.text.crowded_minute, &[&started.len(), &minute.format("%m-%d %H:%M"), &names.join(", ")]);
+        self.message = Some(match self.message.take() {
+            Some(message) => format!("{} | {}", message, warning),
+            None => warning,
+        });
+    }
+
     pub fn cancel_input(&mut self) {
-        self.input_mode = InputMode::Normal;
+        self.completion = None;
+        self.preset_picker = None;
+        self.conversion = None;
+        self.temp_split.clear();
+        // Naming a new script returns to the script list, a log search to the log
+        self.input_mode = match self.input_mode {
+            InputMode::AddingScript => InputMode::BrowsingScripts,
+            InputMode::SearchingLog => InputMode::ViewingLog,
+            _ => InputMode::Normal,
+        };
         self.input_buffer.clear();
         self.temp_name.clear();
         self.temp_schedule.clear();
-        self.message = Some("Cancelled".to_string());
+        self.temp_success_hook.clear();
+        self.temp_timeout = None;
+        self.message = Some(self.text.cancelled.to_string());
     }
 
     pub fn save(&mut self) -> Result<()> {
-        self.storage.save(&self.entries)?;
+        if self.config.normalize_on_save {
+            for entry in &mut self.entries {
+                if let Ok(normalized) = normalize::normalize(&entry.schedule) {
+                    entry.schedule = normalized;
+                }
+            }
+        }
+        self.refresh_analysis();
+        // Indices may have shifted; the next write reports failures afresh
+        self.save_failures.clear();
+        if self.config.save_debounce_ms == 0 {
+            self.write()?;
+        } else {
+            self.pending_save = Some(Instant::now());
+        }
+        Ok(())
+    }
+
+    /// When the pending changes should be written, if there are any; not
+    /// before the save in progress has finished
+    pub fn save_deadline(&self) -> Option<Instant> {
+        if self.save_job.is_some() {
+            return None;
+        }
+        self.pending_save
+            .map(|changed| changed + Duration::from_millis(self.config.save_debounce_ms))
+    }
+
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.pending_save.is_some() || self.save_job.is_some()
+    }
+
+    /// The spinner frame to show while a save is running in the background
+    pub fn save_spinner(&self) -> Option<char> {
+        self.save_job.as_ref().map(SaveJob::spinner)
+    }
+
+    /// Start writing the pending changes on a background thread; the event
+    /// loop picks up the outcome with `poll_save`
+    pub fn start_save(&mut self) {
+        if self.save_job.is_some() || self.conflict_resolver.is_some() || self.pending_save.take().is_none() {
+            return;
+        }
+        tracing::debug!(entries = self.entries.len(), "saving entries in the background");
+        self.save_job = Some(SaveJob::start(Arc::clone(&self.storage), self.entries.clone(), self.loaded.clone()));
+    }
+
+    /// Write the pending changes now instead of waiting for the edits to
+    /// settle, in the background like any other save
+    pub fn write_now(&mut self) {
+        if !self.has_unsaved_changes() {
+            self.message = Some(self.text.nothing_to_write.to_string());
+            return;
+        }
+        self.start_save();
+        self.message = Some(self.text.writing_changes.to_string());
+    }
+
+    /// The outcome of the background save, once it has finished
+    pub fn poll_save(&mut self) -> Option<Result<()>> {
+        let written = self.save_job.as_ref()?.poll()?;
+        self.save_job = None;
+        Some(self.finish_save(written))
+    }
+
+    /// Take in how a background save ended. When the backend had changed,
+    /// its changes are merged and the save starts again, unless entries
+    /// changed on both sides now wait for the conflict resolver.
+    fn finish_save(&mut self, written: Result<Written>) -> Result<()> {
+        let written = written.inspect_err(|e| tracing::error!(error = %format!("{:#}", e), "save failed"))?;
+        match written {
+            Written::Saved(refused, loaded) => {
+                self.loaded = loaded;
+                // Entries may have moved or gone while the save ran
+                let failed = refused
+                    .into_iter()
+                    .filter_map(|(name, reason)| Some((self.entries.iter().position(|e| e.name == name)?, reason)))
+                    .collect();
+                self.record_save_failures(failed);
+            }
+            Written::Changed(theirs) => {
+                if self.take_in(theirs) {
+                    self.save_job = Some(SaveJob::start(Arc::clone(&self.storage), self.entries.clone(), self.loaded.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write pending changes to the backend now, after the save in progress
+    pub fn flush_save(&mut self) -> Result<()> {
+        while let Some(job) = self.save_job.take() {
+            self.finish_save(job.wait())?;
+        }
+        if self.pending_save.take().is_some() {
+            tracing::debug!(entries = self.entries.len(), "saving entries");
+            if let Err(e) = self.write() {
+                tracing::error!(error = %format!("{:#}", e), "save failed");
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the entries, first merging in what was changed in the backend
+    /// since they were loaded. Entries changed on both sides wait for the
+    /// conflict resolver.
+    fn write(&mut self) -> Result<()> {
+        if self.conflict_resolver.is_some() {
+            // Written once the conflicts are settled
+            self.pending_save = Some(Instant::now());
+            return Ok(());
+        }
+        let theirs = self.storage.load()?;
+        if self.take_in(theirs) {
+            self.write_merged()?;
+        }
+        Ok(())
+    }
+
+    /// Merge in the backend's entries when they changed since they were
+    /// loaded; false when entries changed on both sides now wait for the
+    /// conflict resolver
+    fn take_in(&mut self, theirs: Vec<CronEntry>) -> bool {
+        if theirs == self.loaded {
+            return true;
+        }
+        let merge = conflict::merge(&self.loaded, &self.entries, &theirs);
+        tracing::info!(conflicts = merge.conflicts.len(), "backend changed since it was loaded");
+        if !merge.conflicts.is_empty() {
+            self.message = Some(fill(self.text.conflicts_found, &[&merge.conflicts.len()]));
+            self.conflict_resolver = Some(ConflictResolver { merge, theirs, selected: 0 });
+            return false;
+        }
+        self.set_merged(merge.entries, theirs);
+        self.message = Some(self.text.changes_merged.to_string());
+        true
+    }
+
+    /// Settle every conflict by writing your entries as they are, undoing
+    /// whatever was changed elsewhere
+    pub fn overwrite_conflicts(&mut self) -> Result<()> {
+        let Some(ConflictResolver { theirs, .. }) = self.conflict_resolver.take() else {
+            return Ok(());
+        };
+        self.loaded = theirs;
+        self.write_merged()?;
+        self.message = Some(self.text.conflicts_overwritten.to_string());
+        Ok(())
+    }
+
+    /// Settle every conflict by dropping your unsaved changes for what the
+    /// backend has now
+    pub fn reload_conflicts(&mut self) {
+        let Some(ConflictResolver { theirs, .. }) = self.conflict_resolver.take() else {
+            return;
+        };
+        self.pending_save = None;
+        self.set_merged(theirs.clone(), theirs);
+        self.message = Some(self.text.conflicts_reloaded.to_string());
+    }
+
+    fn set_merged(&mut self, entries: Vec<CronEntry>, theirs: Vec<CronEntry>) {
+        self.entries = entries;
+        self.loaded = theirs;
+        self.selected_index = self.selected_index.min(self.entries.len().saturating_sub(1));
+        self.refresh_analysis();
+    }
+
+    fn write_merged(&mut self) -> Result<()> {
+        // `loaded` is what the backend has now, so a single edit can be saved alone
+        let report = self.storage.save_changes(&self.loaded, &self.entries)?;
+        // As read back, since some backends don't keep every field
+        self.loaded = self.storage.load()?;
+        self.record_save_failures(report.failed);
+        Ok(())
+    }
+
+    pub fn move_conflict_selection(&mut self, down: bool) {
+        if let Some(resolver) = &mut self.conflict_resolver {
+            if down {
+                resolver.selected = (resolver.selected + 1).min(resolver.merge.conflicts.len().saturating_sub(1));
+            } else {
+                resolver.selected = resolver.selected.saturating_sub(1);
+            }
+        }
+    }
+
+    /// Settle the highlighted conflict; once none are left, save the result
+    pub fn resolve_conflict(&mut self, resolution: Resolution) -> Result<()> {
+        let Some(resolver) = &mut self.conflict_resolver else {
+            return Ok(());
+        };
+        resolver.merge.resolve(resolver.selected, resolution);
+        resolver.selected = resolver.selected.min(resolver.merge.conflicts.len().saturating_sub(1));
+        if !resolver.merge.conflicts.is_empty() {
+            return Ok(());
+        }
+        let Some(ConflictResolver { merge, theirs, .. }) = self.conflict_resolver.take() else {
+            return Ok(());
+        };
+        self.set_merged(merge.entries, theirs);
+        self.write_merged()?;
+        self.message = Some(self.text.conflicts_resolved.to_string());
+        Ok(())
+    }
+
+    /// Close the resolver without saving; the next save finds the conflicts again
+    pub fn close_conflicts(&mut self) {
+        self.conflict_resolver = None;
+        self.pending_save = None;
+        self.message = Some(self.text.conflicts_not_saved.to_string());
+    }
+
+    /// Keep the entries the backend refused so the table can mark them
+    fn record_save_failures(&mut self, failed: Vec<(usize, String)>) {
+        self.save_failures = failed.into_iter().collect();
+        if let Some((index, reason)) = self.save_failures.iter().next() {
+            let name = self.entries.get(*index).map_or("", |e| e.name.as_str());
+            self.message = Some(fill(
+                self.text.save_partial,
+                &[&self.save_failures.len(), &name, reason],
+            ));
+        }
+    }
+
+    /// Quit, unless writing the pending changes turns up conflicts to resolve first
+    pub fn quit(&mut self) -> Result<()> {
+        self.flush_save()?;
+        self.should_quit = self.conflict_resolver.is_none();
         Ok(())
     }
+}
+
+/// The backend a sandbox pushes to, for messages
+fn origin_name(storage: &Storage) -> &'static str {
+    storage.origin().map_or("", Storage::get_backend_name)
+}
+
+/// Name of part `index` of an entry split into `count` entries
+fn split_name(name: &str, index: usize, count: usize) -> String {
+    format!("{} ({}/{})", name, index + 1, count)
+}
 
-    pub fn quit(&mut self) {
-        self.should_quit = true;
+/// Resolve schedule prompt input into the expression to store, plus a note
+/// describing any conversion from another notation (e.g. Quartz)
+fn resolve_schedule(input: &str, text: &Strings) -> (String, Option<String>) {
+    match schedule::resolve_input(input) {
+        Ok(resolved) => {
+            let note = resolved
+                .converted_from
+                .map(|from| fill(text.converted_expression, &[&from, &resolved.expression]));
+            (resolved.expression, note)
+        }
+        Err(e) => (input.to_string(), Some(fill(text.warning, &[&e]))),
     }
 }
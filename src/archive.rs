@@ -0,0 +1,109 @@
+use crate::cron_entry::CronEntry;
+use crate::mail;
+use crate::storage::Storage;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Version of the archive layout; `restore` refuses newer ones
+const VERSION: u32 = 1;
+
+/// Everything `backup` writes to a single file so `restore` can rebuild the
+/// schedule on another machine: every field of every entry, with `!script`
+/// and `!secret` placeholders left in, and the managed scripts they run.
+/// Backend files (crontab, plists, units) are generated again on restore,
+/// so script paths and launchd labels fit the new machine.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Archive {
+    pub version: u32,
+    pub created: DateTime<Utc>,
+    /// `user@host` the backup was taken on
+    pub origin: String,
+    /// The backend the entries were read from
+    pub backend: String,
+    pub entries: Vec<CronEntry>,
+    /// Managed scripts by name
+    #[serde(default)]
+    pub scripts: BTreeMap<String, String>,
+}
+
+impl Archive {
+    /// Collect the backend's entries and the scripts in the script library
+    pub fn collect(storage: &Storage) -> Result<Self> {
+        let mut scripts = BTreeMap::new();
+        if let Some(library) = storage.scripts() {
+            for name in library.list()? {
+                let content = library.read(&name)?;
+                scripts.insert(name, content);
+            }
+        }
+        Ok(Self {
+            version: VERSION,
+            created: Utc::now(),
+            origin: mail::origin(),
+            backend: storage.get_backend_name().to_string(),
+            entries: storage.load()?,
+            scripts,
+        })
+    }
+
+    pub fn to_string(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)? + "\n")
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let archive: Self = serde_json::from_str(content).context("Not a cron-manager backup")?;
+        if archive.version > VERSION {
+            bail!("The backup was written by a newer cron-manager (version {})", archive.version);
+        }
+        Ok(archive)
+    }
+
+    /// Names of the archived scripts whose content differs from the script
+    /// of the same name already in the library
+    pub fn changed_scripts(&self, storage: &Storage) -> Vec<&str> {
+        let Some(library) = storage.scripts() else {
+            return Vec::new();
+        };
+        self.scripts
+            .iter()
+            .filter(|(name, content)| library.path(name).exists() && library.read(name).ok().as_ref() != Some(*content))
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// Write the archived scripts to the script library
+    pub fn restore_scripts(&self, storage: &Storage) -> Result<()> {
+        if self.scripts.is_empty() {
+            return Ok(());
+        }
+        let library = storage.scripts().context("No script library to restore scripts to")?;
+        for (name, content) in &self.scripts {
+            library.write(name, content)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::mock::MockScheduler;
+
+    #[test]
+    fn test_round_trip() {
+        let mut entry = CronEntry::new("Backup".into(), "0 2 * * *".into(), "!script backup.sh --all".into());
+        entry.tags = vec!["nightly".into()];
+        let storage = Storage::with_scheduler(Box::new(MockScheduler::with_entries(vec![entry.clone()])));
+        let archive = Archive::collect(&storage).unwrap();
+        assert_eq!(archive.entries, vec![entry]);
+
+        let parsed = Archive::parse(&archive.to_string().unwrap()).unwrap();
+        assert_eq!(parsed, archive);
+
+        let newer = archive.to_string().unwrap().replacen("\"version\": 1", "\"version\": 2", 1);
+        assert!(Archive::parse(&newer).is_err());
+        assert!(Archive::parse("{\"entries\": []}").is_err());
+    }
+}
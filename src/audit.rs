@@ -0,0 +1,192 @@
+use crate::cron_entry::{self, CronEntry};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// What a save did to one entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// One field of a changed entry, before and after
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: String,
+    pub after: String,
+}
+
+/// A change to one entry, as recorded in the audit log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub at: DateTime<Utc>,
+    /// The user who saved, when known
+    pub user: Option<String>,
+    pub entry: String,
+    pub kind: ChangeKind,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldChange>,
+}
+
+/// Changes made by every save, one JSON record per line
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn append(&self, records: &[AuditRecord]) -> Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("Failed to create directory: {:?}", parent))?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open audit log: {:?}", self.path))?;
+        let mut lines = String::new();
+        for record in records {
+            lines.push_str(&serde_json::to_string(record)?);
+            lines.push('\n');
+        }
+        file.write_all(lines.as_bytes())
+            .with_context(|| format!("Failed to write audit log: {:?}", self.path))
+    }
+
+    /// Records since the given time, most recent first
+    pub fn load_since(&self, since: DateTime<Utc>) -> Result<Vec<AuditRecord>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read audit log: {:?}", self.path))?;
+        let mut records: Vec<AuditRecord> = content
+            .lines()
+            .filter_map(|line| serde_json::from_str::<AuditRecord>(line).ok())
+            .filter(|record| record.at >= since)
+            .collect();
+        records.reverse();
+        Ok(records)
+    }
+}
+
+/// The user running cron-manager, from `$USER` or `$LOGNAME`
+pub fn current_user() -> Option<String> {
+    std::env::var("USER").or_else(|_| std::env::var("LOGNAME")).ok()
+}
+
+/// The changes between two entry sets, matched by name
+pub fn diff(before: &[CronEntry], after: &[CronEntry], at: DateTime<Utc>, user: Option<&str>) -> Vec<AuditRecord> {
+    let record = |entry: &str, kind, fields| AuditRecord {
+        at,
+        user: user.map(str::to_string),
+        entry: entry.to_string(),
+        kind,
+        fields,
+    };
+    let old: HashMap<&str, &CronEntry> = before.iter().map(|e| (e.name.as_str(), e)).collect();
+    let mut records = Vec::new();
+    for entry in after {
+        match old.get(entry.name.as_str()) {
+            None => records.push(record(&entry.name, ChangeKind::Added, Vec::new())),
+            Some(previous) => {
+                let fields: Vec<FieldChange> = field_values(previous)
+                    .into_iter()
+                    .zip(field_values(entry))
+                    .filter(|((_, a), (_, b))| a != b)
+                    .map(|((field, before), (_, after))| FieldChange { field: field.to_string(), before, after })
+                    .collect();
+                if !fields.is_empty() {
+                    records.push(record(&entry.name, ChangeKind::Changed, fields));
+                }
+            }
+        }
+    }
+    for entry in before {
+        if !after.iter().any(|e| e.name == entry.name) {
+            records.push(record(&entry.name, ChangeKind::Removed, Vec::new()));
+        }
+    }
+    records
+}
+
+/// The fields compared between saves, as shown in the recently-changed view
+fn field_values(entry: &CronEntry) -> Vec<(&'static str, String)> {
+    let optional = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+    vec![
+        ("schedule", entry.schedule.clone()),
+        ("command", entry.command.clone()),
+        ("enabled", entry.enabled.to_string()),
+        ("timezone", optional(&entry.timezone)),
+        ("tags", entry.tags.join(", ")),
+        ("snooze", entry.snoozed_until.map_or_else(|| "-".to_string(), |until| until.to_string())),
+        ("skip_excluded_dates", entry.skip_excluded.to_string()),
+        ("on_success", optional(&entry.on_success)),
+        ("on_failure", optional(&entry.on_failure)),
+        ("watchdog", entry.watchdog.to_string()),
+        ("env", optional(&entry.env)),
+        ("vars", cron_entry::format_vars(&entry.env_vars)),
+        ("cwd", entry.cwd.as_ref().map_or_else(|| "-".to_string(), |cwd| cwd.display().to_string())),
+        ("shell", entry.shell.map_or("sh", |shell| shell.name()).to_string()),
+        ("stdin", optional(&entry.stdin)),
+        ("comments", entry.comments.join(" / ")),
+        ("timeout", entry.timeout_secs.map_or_else(|| "-".to_string(), cron_entry::format_seconds)),
+        ("retries", entry.retries.to_string()),
+        ("retry_delay", cron_entry::format_seconds(entry.retry_delay_secs)),
+        ("ping_url", optional(&entry.ping_url)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{entry, TempDir};
+
+    #[test]
+    fn test_diff_and_log() {
+        let now = Utc::now();
+        let before = vec![entry("Backup", "0 2 * * *"), entry("Legacy", "0 4 * * *")];
+        let mut changed = entry("Backup", "0 3 * * *");
+        changed.enabled = false;
+        let after = vec![changed, entry("Report", "0 9 * * 1-5")];
+
+        let records = diff(&before, &after, now, Some("alice"));
+        let summary: Vec<(&str, ChangeKind)> = records.iter().map(|r| (r.entry.as_str(), r.kind)).collect();
+        assert_eq!(
+            summary,
+            [("Backup", ChangeKind::Changed), ("Report", ChangeKind::Added), ("Legacy", ChangeKind::Removed)]
+        );
+        assert_eq!(
+            records[0].fields,
+            [
+                FieldChange { field: "schedule".to_string(), before: "0 2 * * *".to_string(), after: "0 3 * * *".to_string() },
+                FieldChange { field: "enabled".to_string(), before: "true".to_string(), after: "false".to_string() },
+            ]
+        );
+        assert!(diff(&after, &after, now, None).is_empty());
+
+        let dir = TempDir::new("audit");
+        let log = AuditLog::new(dir.join("audit.jsonl"));
+        log.append(&records).unwrap();
+        let loaded = log.load_since(now - chrono::Duration::days(1)).unwrap();
+        assert_eq!(loaded.len(), 3);
+        assert_eq!(loaded[0].entry, "Legacy");
+        assert_eq!(loaded[2].user.as_deref(), Some("alice"));
+        assert!(log.load_since(now + chrono::Duration::seconds(1)).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,162 @@
+use crate::audit::{self, AuditLog, AuditRecord};
+use crate::cron_entry::CronEntry;
+use crate::cron_parser::CronParser;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDateTime, Utc};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File name format of a snapshot, sortable by time
+const SNAPSHOT_FORMAT: &str = "%Y%m%d-%H%M%S%.3f";
+
+const SNAPSHOT_EXTENSION: &str = "crontab";
+
+/// Directory holding the snapshots, inside the data directory
+const BACKUP_DIR: &str = "backups";
+
+/// Audit log of the changes between snapshots, next to them
+const AUDIT_FILE: &str = "changes.jsonl";
+
+/// Copies of the entry set taken after every save, newest kept up to a limit
+#[derive(Debug, Clone)]
+pub struct BackupStore {
+    dir: PathBuf,
+    keep: usize,
+}
+
+/// One saved copy of the entry set
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub path: PathBuf,
+    pub taken_at: NaiveDateTime,
+}
+
+impl Snapshot {
+    /// The entries as they were when the snapshot was taken
+    pub fn entries(&self) -> Result<Vec<CronEntry>> {
+        let content = fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read backup: {:?}", self.path))?;
+        CronParser::parse(&content)
+    }
+}
+
+impl BackupStore {
+    pub fn new(dir: PathBuf, keep: usize) -> Self {
+        Self { dir, keep }
+    }
+
+    /// `backups` under the data directory
+    pub fn default_dir(data_dir: &Path) -> PathBuf {
+        data_dir.join(BACKUP_DIR)
+    }
+
+    /// Store a snapshot of the entries unless they match the newest one, log
+    /// what changed since that one, then drop the oldest snapshots beyond the limit
+    pub fn record(&self, entries: &[CronEntry]) -> Result<()> {
+        let content = CronParser::serialize(entries);
+        let snapshots = self.list()?;
+        if let Some(latest) = snapshots.first() {
+            if fs::read_to_string(&latest.path).is_ok_and(|previous| previous == content) {
+                return Ok(());
+            }
+            let changes = latest
+                .entries()
+                .map(|previous| audit::diff(&previous, entries, Utc::now(), audit::current_user().as_deref()))
+                .and_then(|changes| self.audit_log().append(&changes));
+            if let Err(e) = changes {
+                tracing::warn!(error = %format!("{:#}", e), "failed to write audit log");
+            }
+        }
+
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create backup directory: {:?}", self.dir))?;
+        let name = format!("{}.{}", Local::now().format(SNAPSHOT_FORMAT), SNAPSHOT_EXTENSION);
+        let path = self.dir.join(name);
+        fs::write(&path, content).with_context(|| format!("Failed to write backup: {:?}", path))?;
+
+        // The new snapshot counts towards the limit
+        for old in snapshots.iter().skip(self.keep.saturating_sub(1)) {
+            fs::remove_file(&old.path).with_context(|| format!("Failed to remove old backup: {:?}", old.path))?;
+        }
+        Ok(())
+    }
+
+    fn audit_log(&self) -> AuditLog {
+        AuditLog::new(self.dir.join(AUDIT_FILE))
+    }
+
+    /// Changes saved since the given time, most recent first
+    pub fn changes_since(&self, since: DateTime<Utc>) -> Result<Vec<AuditRecord>> {
+        self.audit_log().load_since(since)
+    }
+
+    /// All snapshots, newest first
+    pub fn list(&self) -> Result<Vec<Snapshot>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut snapshots: Vec<Snapshot> = fs::read_dir(&self.dir)
+            .with_context(|| format!("Failed to read backup directory: {:?}", self.dir))?
+            .filter_map(|item| {
+                let path = item.ok()?.path();
+                let stem = path.file_stem()?.to_str()?;
+                if path.extension()? != SNAPSHOT_EXTENSION {
+                    return None;
+                }
+                let taken_at = NaiveDateTime::parse_from_str(stem, SNAPSHOT_FORMAT).ok()?;
+                Some(Snapshot { path, taken_at })
+            })
+            .collect();
+        snapshots.sort_by_key(|snapshot| std::cmp::Reverse(snapshot.taken_at));
+        Ok(snapshots)
+    }
+}
+
+/// Put a snapshot's entry back into the current set: it replaces the entry
+/// with the same name, or is added at the end. Returns its index.
+pub fn restore_entry(entries: &mut Vec<CronEntry>, entry: CronEntry) -> usize {
+    match entries.iter().position(|e| e.name == entry.name) {
+        Some(index) => {
+            entries[index] = entry;
+            index
+        }
+        None => {
+            entries.push(entry);
+            entries.len() - 1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{entry, TempDir};
+
+    #[test]
+    fn test_record_skips_duplicates_and_prunes() {
+        let dir = TempDir::new("backups");
+        let store = BackupStore::new(dir.path().to_path_buf(), 2);
+
+        store.record(&[entry("A", "0 * * * *")]).unwrap();
+        store.record(&[entry("A", "0 * * * *")]).unwrap();
+        assert_eq!(store.list().unwrap().len(), 1);
+
+        for schedule in ["5 * * * *", "10 * * * *"] {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            store.record(&[entry("A", schedule)]).unwrap();
+        }
+        let snapshots = store.list().unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].entries().unwrap()[0].schedule, "10 * * * *");
+        assert_eq!(snapshots[1].entries().unwrap()[0].schedule, "5 * * * *");
+    }
+
+    #[test]
+    fn test_restore_entry() {
+        let mut entries = vec![entry("A", "0 * * * *"), entry("B", "0 1 * * *")];
+        assert_eq!(restore_entry(&mut entries, entry("A", "30 * * * *")), 0);
+        assert_eq!(entries[0].schedule, "30 * * * *");
+        assert_eq!(restore_entry(&mut entries, entry("C", "0 2 * * *")), 2);
+        assert_eq!(entries.len(), 3);
+    }
+}
@@ -0,0 +1,131 @@
+use crate::cron_entry::CronEntry;
+use crate::cron_parser::CronParser;
+use crate::schedule::CronSchedule;
+use std::collections::{HashMap, HashSet};
+
+/// Written above the entries in the file opened in `$EDITOR`
+const HEADER: &str = "\
+# Edit the entries below, then save and quit to review the changes.
+# Each entry is a `# NAME:` line, optional `# KEY: value` metadata
+# (TZ, SNOOZE, TAGS, SKIP, ON_SUCCESS, ON_FAILURE, WATCHDOG, ENV) and the job line;
+# comment the job line out (`# 0 2 * * * cmd`) to disable the entry. Comment
+# lines directly above `# NAME:` are kept with the entry. As in crontab, a
+# literal `%` is written `\\%`; text after a bare `%` is fed to the command's
+# stdin, with each further `%` read as a newline.
+";
+
+/// The entries as text for editing
+pub fn serialize(entries: &[CronEntry]) -> String {
+    let mut output = String::from(HEADER);
+    for entry in entries {
+        output.push('\n');
+        output.push_str(&entry.to_crontab_string());
+        output.push('\n');
+    }
+    output
+}
+
+/// Entries read back from the edited text, and the problems that keep them
+/// from being applied
+pub fn parse(content: &str) -> (Vec<CronEntry>, Vec<String>) {
+    let entries = CronParser::parse(content).unwrap_or_default();
+    let mut errors = Vec::new();
+
+    // The parser skips named entries whose job line it can't read
+    let parsed: HashSet<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+    for line in content.lines() {
+        if let Some(name) = line.trim().strip_prefix("# NAME:").map(str::trim) {
+            if !parsed.contains(name) {
+                errors.push(format!("{}: missing or malformed job line", name));
+            }
+        }
+    }
+
+    let mut seen = HashSet::new();
+    for entry in &entries {
+        if !seen.insert(entry.name.as_str()) {
+            errors.push(format!("{}: duplicate name", entry.name));
+        }
+        if let Err(e) = CronSchedule::parse(&entry.schedule) {
+            errors.push(format!("{}: {}", entry.name, e));
+        }
+    }
+    (entries, errors)
+}
+
+/// Entry names added, removed and changed by an edit
+#[derive(Debug, Default, PartialEq)]
+pub struct EditDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl EditDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compare entries by name
+pub fn diff(before: &[CronEntry], after: &[CronEntry]) -> EditDiff {
+    let old: HashMap<&str, &CronEntry> = before.iter().map(|e| (e.name.as_str(), e)).collect();
+    let new: HashSet<&str> = after.iter().map(|e| e.name.as_str()).collect();
+    let mut diff = EditDiff::default();
+
+    for entry in after {
+        match old.get(entry.name.as_str()) {
+            None => diff.added.push(entry.name.clone()),
+            Some(previous) if *previous != entry => diff.changed.push(entry.name.clone()),
+            Some(_) => {}
+        }
+    }
+    diff.removed = before
+        .iter()
+        .filter(|e| !new.contains(e.name.as_str()))
+        .map(|e| e.name.clone())
+        .collect();
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::command_entry;
+
+    #[test]
+    fn test_round_trip_and_diff() {
+        let before = vec![command_entry("Backup", "0 2 * * *", "/bin/backup"), command_entry("Sync", "0 2 * * *", "/bin/sync")];
+        let text = serialize(&before).replace("/bin/backup", "/usr/bin/backup").replace("# NAME: Sync\n0 2 * * * /bin/sync\n", "");
+        let text = format!("{}\n# NAME: Report\n# TAGS: daily\n30 8 * * 1-5 /bin/report\n", text);
+
+        let (after, errors) = parse(&text);
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(after.len(), 2);
+        assert_eq!(after[1].tags, ["daily"]);
+        assert_eq!(
+            diff(&before, &after),
+            EditDiff {
+                added: vec!["Report".to_string()],
+                removed: vec!["Sync".to_string()],
+                changed: vec!["Backup".to_string()],
+            }
+        );
+        assert!(diff(&before, &before).is_empty());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let text = "# NAME: Broken\n0 2 * *\n\n# NAME: Twice\n0 2 * * * a\n# NAME: Twice\n0 3 * * * b\n# NAME: Bad\n0 25 * * * c\n";
+        let (entries, errors) = parse(text);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(
+            errors,
+            [
+                "Broken: missing or malformed job line",
+                "Twice: duplicate name",
+                "Bad: Value 25 out of range in hour field (expected 0-23)",
+            ]
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use crate::analysis::frequency::Frequency;
+use crate::cron_entry::CronEntry;
+use crate::schedule::timezone::ScheduleZone;
+use crate::schedule::CronSchedule;
+use chrono::{DateTime, FixedOffset, Utc};
+use std::collections::HashMap;
+
+/// Everything the UI shows about an entry's schedule
+#[derive(Debug, Clone)]
+pub struct ScheduleInfo {
+    /// The parsed schedule, or why it failed to parse
+    pub schedule: Result<CronSchedule, String>,
+    /// The zone it is evaluated in, or why that zone is invalid
+    pub zone: Result<ScheduleZone, String>,
+    /// `None` when the schedule never fires (or didn't parse)
+    pub next_run: Option<DateTime<FixedOffset>>,
+    pub frequency: Frequency,
+}
+
+/// Parsed schedules and next-run times keyed by schedule text and timezone,
+/// so redraws don't re-parse and re-evaluate every expression. Editing an
+/// entry changes its key; a cached result is recomputed once its next run
+/// has passed.
+#[derive(Debug, Default)]
+pub struct ScheduleCache {
+    infos: HashMap<(String, Option<String>), ScheduleInfo>,
+}
+
+impl ScheduleCache {
+    pub fn get(&mut self, entry: &CronEntry, configured_tz: Option<&str>, now: DateTime<Utc>) -> &ScheduleInfo {
+        let timezone = entry.timezone.as_deref().or(configured_tz);
+        let key = (entry.schedule.clone(), timezone.map(str::to_string));
+
+        let info = self
+            .infos
+            .entry(key)
+            .or_insert_with(|| ScheduleInfo::compute(&entry.schedule, timezone, now));
+        if info.next_run.is_some_and(|next| next <= now) {
+            *info = ScheduleInfo::compute(&entry.schedule, timezone, now);
+        }
+        info
+    }
+
+    /// Drop results for schedules no entry uses any more
+    pub fn retain(&mut self, entries: &[CronEntry]) {
+        self.infos
+            .retain(|(schedule, _), _| entries.iter().any(|e| e.schedule == *schedule));
+    }
+}
+
+impl ScheduleInfo {
+    fn compute(expression: &str, timezone: Option<&str>, now: DateTime<Utc>) -> Self {
+        let schedule = CronSchedule::parse(expression).map_err(|e| e.to_string());
+        let zone = ScheduleZone::resolve(timezone, None).map_err(|e| e.to_string());
+
+        let (next_run, frequency) = match (&schedule, &zone) {
+            (Ok(schedule), Ok(zone)) => (
+                zone.next_runs(schedule, now, 1).first().copied(),
+                Frequency::estimate(schedule, now.with_timezone(&chrono::Local).naive_local()),
+            ),
+            _ => (None, Frequency::default()),
+        };
+
+        Self { schedule, zone, next_run, frequency }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_recomputes_after_next_run_passes() {
+        let entry = CronEntry::new("Hourly".to_string(), "0 * * * *".to_string(), "/bin/true".to_string());
+        let mut cache = ScheduleCache::default();
+
+        let first = cache.get(&entry, Some("UTC"), utc("2024-01-01T00:30:00Z")).next_run;
+        assert_eq!(first.unwrap().to_rfc3339(), "2024-01-01T01:00:00+00:00");
+
+        // Still in the future: the cached time is reused
+        let same = cache.get(&entry, Some("UTC"), utc("2024-01-01T00:59:00Z")).next_run;
+        assert_eq!(same, first);
+
+        let later = cache.get(&entry, Some("UTC"), utc("2024-01-01T01:00:00Z")).next_run;
+        assert_eq!(later.unwrap().to_rfc3339(), "2024-01-01T02:00:00+00:00");
+    }
+
+    #[test]
+    fn test_invalid_schedule_and_zone() {
+        let mut entry = CronEntry::new("Bad".to_string(), "61 * * * *".to_string(), "/bin/true".to_string());
+        let mut cache = ScheduleCache::default();
+        assert!(cache.get(&entry, None, Utc::now()).schedule.is_err());
+
+        entry.schedule = "0 * * * *".to_string();
+        entry.timezone = Some("Mars/Olympus".to_string());
+        let info = cache.get(&entry, None, Utc::now());
+        assert!(info.schedule.is_ok());
+        assert!(info.zone.is_err());
+        assert!(info.next_run.is_none());
+    }
+}
@@ -0,0 +1,1022 @@
+use crate::agent::{self, Agent};
+use crate::archive::Archive;
+use crate::analysis::collisions;
+use crate::analysis::lint::{self, Severity};
+use crate::analysis::simulate::{self, MAX_HOURS};
+use crate::clock::{self, Clock};
+use crate::audit::ChangeKind;
+use crate::config::{BackendKind, Config};
+use crate::conflict::{self, Resolution};
+use crate::cron_entry;
+use crate::export::{self, Format, OnDuplicate};
+use crate::filter::{self, EntryFilter};
+use crate::holidays::{self, ExclusionCalendar};
+use crate::hooks;
+use crate::jobfile::{self, Change, Jobfile};
+use crate::log_capture;
+use crate::log_search;
+use crate::mail;
+use crate::notify;
+use crate::ping::{self, Signal};
+use crate::policy::Policy;
+use crate::retry::RetryPolicy;
+use crate::run_history::{self, RunHistory, RunRecord};
+use crate::schedule::timezone::ScheduleZone;
+use crate::schedule::CronSchedule;
+use crate::secrets;
+use crate::scheduler::SaveReport;
+use crate::storage::Storage;
+use crate::sync::{self, Direction, Sync};
+use crate::watchdog;
+use anyhow::{bail, Context, Result};
+use chrono::{Duration, Local, Utc};
+use std::io::IsTerminal;
+use std::path::Path;
+use std::time::Instant;
+
+/// Options before the subcommand that take a value
+pub const GLOBAL_VALUE_OPTIONS: &[&str] = &["--simulate-time", "--host", "--agent"];
+
+/// Run a non-interactive subcommand (e.g. `cron-manager report`)
+pub fn run(command: &str, args: &[String], storage: &Storage, config: &Config, clock: Clock) -> Result<()> {
+    match command {
+        "report" => report(storage, config, clock),
+        "analyze" => analyze(args, storage, config, clock),
+        "lint" => lint(storage, config, clock),
+        "simulate" => simulate(args, storage, config, clock),
+        "validate" => validate(storage, config),
+        "enable" => set_enabled(args, storage, true),
+        "disable" => set_enabled(args, storage, false),
+        "exec" => exec(args, config),
+        "watchdog" => watchdog(args, config, clock),
+        "agent" => agent(args, storage, config),
+        "holidays" => holidays(args, config, clock),
+        "plan" => plan(args, storage, config, false),
+        "apply" => plan(args, storage, config, true),
+        "logs" => logs(args, storage, config),
+        "history" => history(args, storage, config),
+        "export" => export(args, storage),
+        "import" => import(args, storage, config),
+        "backup" => backup(args, storage),
+        "sync" => sync(args, storage, config),
+        "restore" => restore(args, storage, config),
+        _ => bail!("Unknown command: {}", command),
+    }
+}
+
+/// The value following `--name` in the arguments
+fn option_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == name)
+        .and_then(|i| args.get(i + 1))
+        .map(String::as_str)
+}
+
+/// Arguments after the subcommand that aren't options or option values
+fn positional<'a>(args: &'a [String], command: &str) -> Vec<&'a str> {
+    let start = args.iter().position(|a| a == command).map_or(args.len(), |i| i + 1);
+    args[start..]
+        .iter()
+        .enumerate()
+        .filter(|(i, a)| !a.starts_with("--") && (*i == 0 || !GLOBAL_VALUE_OPTIONS.contains(&args[start + i - 1].as_str())))
+        .map(|(_, a)| a.as_str())
+        .collect()
+}
+
+/// Manage the dates entries flagged with "skip on excluded dates" don't run on:
+/// `holidays [list]`, `add <date> [label]`, `remove <date>`, `import <file.ics>`
+/// and `check [date]`, which succeeds only on an excluded date (today by default)
+fn holidays(args: &[String], config: &Config, clock: Clock) -> Result<()> {
+    let path = ExclusionCalendar::default_path(config.data_dir.as_deref())
+        .context("No data directory for the exclusion calendar")?;
+    let calendar = ExclusionCalendar::new(path);
+    let words = positional(args, "holidays");
+
+    match words.as_slice() {
+        [] | ["list"] => {
+            let dates = calendar.load()?;
+            for (date, label) in &dates {
+                println!("{}  {}", date, label);
+            }
+            println!("{} excluded dates", dates.len());
+        }
+        ["add", date, label @ ..] => {
+            let date = holidays::parse_date(date)?;
+            calendar.add([(date, label.join(" "))])?;
+            println!("Excluded {}", date);
+        }
+        ["remove", date] => {
+            let date = holidays::parse_date(date)?;
+            if !calendar.remove(date)? {
+                bail!("{} is not excluded", date);
+            }
+            println!("Removed {}", date);
+        }
+        ["import", file] => {
+            let content = std::fs::read_to_string(file).with_context(|| format!("Failed to read {}", file))?;
+            let events = holidays::parse_ics(&content);
+            if events.is_empty() {
+                bail!("No events with a start date found in {}", file);
+            }
+            let total = events.len();
+            let added = calendar.add(events)?;
+            println!("Imported {} dates ({} new)", total, added);
+        }
+        ["check", rest @ ..] => {
+            let date = match rest {
+                [] => clock.now_local().date(),
+                [date] => holidays::parse_date(date)?,
+                _ => bail!("Usage: holidays check [YYYY-MM-DD]"),
+            };
+            // Called before every run of a guarded entry: answer with the exit status only
+            if !calendar.load()?.contains_key(&date) {
+                std::process::exit(1);
+            }
+        }
+        _ => bail!("Usage: holidays [list | add <YYYY-MM-DD> [label] | remove <YYYY-MM-DD> | import <file.ics> | check [YYYY-MM-DD]]"),
+    }
+    Ok(())
+}
+
+/// `plan -f <jobfile> [--prune] [--json]` prints what it would take to
+/// converge the entries onto the jobfile; `apply` with the same options then
+/// does it, after confirmation or with `--yes`. Entries missing from the
+/// jobfile are only deleted with `--prune`. Entries changed in the backend
+/// while the plan was reviewed are merged in; ones the plan changes too are
+/// settled by `--strategy yours|theirs|merged`, or stop the apply.
+fn plan(args: &[String], storage: &Storage, config: &Config, apply: bool) -> Result<()> {
+    let path = option_value(args, "-f")
+        .or_else(|| option_value(args, "--file"))
+        .context("Missing -f <jobfile>")?;
+    let jobfile = Jobfile::load(Path::new(path))?;
+    let strategy = option_value(args, "--strategy").map(Resolution::parse).transpose()?;
+    let loaded = storage.load()?;
+    let mut entries = loaded.clone();
+    let flag = |name: &str| args.iter().any(|a| a == name);
+    let plan = jobfile::plan(&jobfile, &entries, flag("--prune"));
+
+    if flag("--json") {
+        println!("{}", serde_json::to_string_pretty(&plan.to_json())?);
+    } else {
+        print_plan(&plan);
+    }
+    if !apply || plan.is_empty() {
+        return Ok(());
+    }
+    // Refuse the whole apply rather than converge halfway
+    storage.preflight()?;
+    let policy = Policy::load(config.policy_file.as_deref())?;
+    for change in &plan.changes {
+        if let Change::Create(entry) | Change::Update { after: entry, .. } = change {
+            if let Some(preset) = &entry.env {
+                storage.check_env_preset(preset).with_context(|| format!("Job '{}'", entry.name))?;
+            }
+            let hooks = [&entry.on_success, &entry.on_failure].into_iter().flatten();
+            for command in std::iter::once(&entry.command).chain(hooks) {
+                storage.check_placeholders(command).with_context(|| format!("Job '{}'", entry.name))?;
+                let violations = match &policy {
+                    Some(policy) => policy.violations(&storage.resolve_scripts(command)?),
+                    None => Vec::new(),
+                };
+                if !violations.is_empty() {
+                    bail!("Job '{}' violates the command policy: {}", entry.name, violations.join("; "));
+                }
+            }
+        }
+    }
+    if !flag("--yes") && !confirm("Apply these changes?")? {
+        bail!("Not applied");
+    }
+    plan.apply(&mut entries);
+    let current = storage.load()?;
+    if current != loaded {
+        let mut merge = conflict::merge(&loaded, &entries, &current);
+        if !merge.conflicts.is_empty() {
+            let Some(strategy) = strategy else {
+                let names: Vec<&str> = merge.conflicts.iter().map(|c| c.name.as_str()).collect();
+                bail!(
+                    "Changed in the backend since the plan was made: {}; plan again, or pass --strategy yours|theirs|merged",
+                    names.join(", ")
+                );
+            };
+            merge.resolve_all(strategy);
+        }
+        eprintln!("Merged changes made in the backend meanwhile");
+        entries = merge.entries;
+    }
+    let report = storage.save(&entries)?;
+    eprintln!("Applied {} changes", plan.changes.len());
+    if !report.is_complete() {
+        for (index, reason) in &report.failed {
+            eprintln!("Not registered: {}: {}", entries[*index].name, reason);
+        }
+        bail!("{} entries were not registered by the scheduler", report.failed.len());
+    }
+    Ok(())
+}
+
+fn print_plan(plan: &jobfile::Plan) {
+    let (create, update, delete) = plan.counts();
+    println!("{} to create, {} to update, {} to delete", create, update, delete);
+    for change in &plan.changes {
+        match change {
+            Change::Create(entry) => println!("  + {}  {}  {}", entry.name, entry.schedule, entry.command),
+            Change::Update { before, after, fields } => {
+                println!("  ~ {}", after.name);
+                for field in fields {
+                    match *field {
+                        "schedule" => println!("      schedule: {} -> {}", before.schedule, after.schedule),
+                        "command" => println!("      command: {} -> {}", before.command, after.command),
+                        field => println!("      {} changed", field),
+                    }
+                }
+            }
+            Change::Delete(entry) => println!("  - {}", entry.name),
+        }
+    }
+}
+
+/// Ask on the terminal; without one (e.g. in CI) the answer is no
+fn confirm(question: &str) -> Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        eprintln!("{} Not a terminal; pass --yes to apply", question);
+        return Ok(false);
+    }
+    eprint!("{} [y/N] ", question);
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+/// Check every entry's command against the command policy; fails on any violation
+fn validate(storage: &Storage, config: &Config) -> Result<()> {
+    let Some(policy) = Policy::load(config.policy_file.as_deref())? else {
+        let path = Policy::default_path().unwrap_or_default();
+        println!("No policy file ({}); every command is allowed", path.display());
+        return Ok(());
+    };
+
+    let entries = storage.load()?;
+    let mut refused = 0;
+    for entry in &entries {
+        let violations = match storage.resolve_scripts(&entry.command) {
+            Ok(command) => policy.violations(&command),
+            Err(e) => vec![e.to_string()],
+        };
+        if !violations.is_empty() {
+            refused += 1;
+        }
+        for violation in violations {
+            println!("{}: {}", entry.name, violation);
+        }
+    }
+
+    println!("{} entries checked, {} violate the policy", entries.len(), refused);
+    if refused > 0 {
+        bail!("Policy check failed for {} entries", refused);
+    }
+    Ok(())
+}
+
+/// Run `exec --name <entry> [--on-success <hook>] [--on-failure <hook>]
+/// [--timeout <secs>] [--retries <n> [--retry-delay <secs>]] [--ping <url>] -- <command>`:
+/// the command runs through `sh`, stopped at the timeout and tried again
+/// while it fails and retries are left; each try's start time, duration, exit
+/// code and the end of its output go to the run history, the monitoring
+/// check hears of the start and the end, a failure is posted to the
+/// configured webhook, the output is mailed, the matching hook runs after the
+/// last try, and we exit with its status
+fn exec(args: &[String], config: &Config) -> Result<()> {
+    let separator = args
+        .iter()
+        .position(|a| a == "--")
+        .context("Usage: exec --name <entry> -- <command>")?;
+    let (options, command) = (&args[..separator], &args[separator + 1..]);
+    let name = option_value(options, "--name").context("Missing --name <entry>")?;
+    if command.is_empty() {
+        bail!("Missing the command to run after --");
+    }
+
+    let seconds = |option: &str| -> Result<Option<u64>> {
+        option_value(options, option)
+            .map(|value| value.parse().with_context(|| format!("Invalid {} '{}' (seconds)", option, value)))
+            .transpose()
+    };
+    let policy = RetryPolicy {
+        timeout: seconds("--timeout")?.map(std::time::Duration::from_secs),
+        retries: seconds("--retries")?.unwrap_or(0).try_into().context("Too many --retries")?,
+        delay: std::time::Duration::from_secs(seconds("--retry-delay")?.unwrap_or(0)),
+    };
+
+    let ping = option_value(options, "--ping");
+    if let Some(url) = ping {
+        ping::send(url, Signal::Start, None);
+    }
+
+    let mut attempt = 0;
+    let run = loop {
+        let started_at = Utc::now();
+        let start = Instant::now();
+        let run = run_history::run_captured(&command.join(" "), policy.timeout)?;
+        let record = RunRecord {
+            entry: name.to_string(),
+            started_at,
+            duration_ms: start.elapsed().as_millis() as u64,
+            exit_code: run.exit_code,
+            output: run.output.clone(),
+        };
+
+        // The job's own result matters more than its bookkeeping
+        match RunHistory::default_path(config.data_dir.as_deref()) {
+            Some(path) => {
+                if let Err(e) = RunHistory::new(path).append(&record) {
+                    eprintln!("Failed to record run: {:#}", e);
+                }
+            }
+            None => eprintln!("No data directory to record the run in"),
+        }
+        if run.exit_code == Some(0) || attempt == policy.retries {
+            break run;
+        }
+        attempt += 1;
+        eprintln!("Retrying ({}/{}) in {}s", attempt, policy.retries, policy.delay.as_secs());
+        std::thread::sleep(policy.delay);
+    };
+    let exit_code = run.exit_code;
+    if let Some(url) = ping {
+        ping::send(url, Signal::Finish(exit_code), Some(&run.output));
+    }
+    if exit_code != Some(0) {
+        if let Err(e) = notify::notify_failure(&config.notify, &config.secrets, name, exit_code, &run.stderr) {
+            eprintln!("{:#}", e);
+        }
+    }
+    if let Err(e) = mail::mail_run(&config.mail, &config.secrets, name, exit_code, &run.output) {
+        eprintln!("{:#}", e);
+    }
+    let hook = if exit_code == Some(0) { "--on-success" } else { "--on-failure" };
+    if let Some(hook) = option_value(options, hook) {
+        hooks::run_hook(hook, name, exit_code);
+    }
+    std::process::exit(exit_code.unwrap_or(1));
+}
+
+/// Serve this host's backend, run history and logs to TUIs elsewhere:
+/// `agent [--listen <addr>]` over TCP, or `agent --socket <path>`
+fn agent(args: &[String], storage: &Storage, config: &Config) -> Result<()> {
+    let token = agent::token(config.agent.token.as_deref())?;
+    let agent = Agent::new(
+        storage,
+        token,
+        RunHistory::default_path(config.data_dir.as_deref()),
+        config.policy_file.as_deref(),
+    )?;
+    if let Some(path) = option_value(args, "--socket") {
+        #[cfg(unix)]
+        return agent.serve_unix(Path::new(path));
+        #[cfg(not(unix))]
+        bail!("Unix sockets aren't supported on this platform: {}", path);
+    }
+
+    let address = option_value(args, "--listen")
+        .or(config.agent.listen.as_deref())
+        .unwrap_or(agent::DEFAULT_LISTEN);
+    let loopback = address
+        .parse::<std::net::SocketAddr>()
+        .is_ok_and(|a| a.ip().is_loopback());
+    if !loopback {
+        // Requests, token included, travel in the clear
+        eprintln!("Warning: {} is reachable from other hosts without encryption; prefer an ssh tunnel", address);
+    }
+    agent.serve_tcp(address)
+}
+
+/// Run `watchdog --name <entry> --schedule <expr> [--tz <zone>] [--skip-excluded]`,
+/// the command of the entries generated for watched entries: print an alert,
+/// run the configured alert command and fail when a run due since the entry's
+/// last recorded one never happened
+fn watchdog(args: &[String], config: &Config, clock: Clock) -> Result<()> {
+    let name = option_value(args, "--name").context("Missing --name <entry>")?;
+    let schedule = option_value(args, "--schedule").context("Missing --schedule <expression>")?;
+    let schedule = CronSchedule::parse(schedule)?;
+    let zone = ScheduleZone::resolve(option_value(args, "--tz"), config.timezone.as_deref())?;
+    let path = RunHistory::default_path(config.data_dir.as_deref()).context("No data directory for the run history")?;
+
+    let now = clock.now();
+    let records = RunHistory::new(path).load_since(now - Duration::days(watchdog::LOOKBACK_DAYS))?;
+    // Nothing to go by until the entry's first recorded run
+    let Some(last_run) = records.iter().filter(|r| r.entry == name).map(|r| r.started_at).max() else {
+        return Ok(());
+    };
+    let excluded = match args.iter().any(|a| a == "--skip-excluded") {
+        true => ExclusionCalendar::default_path(config.data_dir.as_deref())
+            .map(|path| ExclusionCalendar::new(path).load())
+            .transpose()?
+            .unwrap_or_default(),
+        false => Default::default(),
+    };
+    let grace = Duration::minutes(config.watchdog.grace_minutes.into());
+    let skipped = |time: chrono::DateTime<Utc>| excluded.contains_key(&time.with_timezone(&Local).date_naive());
+    let Some(due_at) = watchdog::missed_run(&schedule, zone, last_run, now, grace, skipped) else {
+        return Ok(());
+    };
+
+    // Printed output is mailed by cron
+    println!(
+        "Missed run: '{}' was due at {} but last ran at {}",
+        name,
+        due_at.format("%Y-%m-%d %H:%M %:z"),
+        last_run.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+    );
+    if let Some(command) = &config.watchdog.alert_command {
+        watchdog::run_alert(command, name, due_at);
+    }
+    std::process::exit(1);
+}
+
+/// List every run in `--hours` (default 24) starting at `--from` (local
+/// `YYYY-MM-DD HH:MM`, default now), without executing anything
+fn simulate(args: &[String], storage: &Storage, config: &Config, clock: Clock) -> Result<()> {
+    let from = match option_value(args, "--from") {
+        Some(text) => clock::parse_local_time(text).context("Invalid --from")?,
+        None => clock.now(),
+    };
+    let hours = match option_value(args, "--hours") {
+        Some(text) => match text.parse::<u32>() {
+            Ok(hours) if (1..=MAX_HOURS).contains(&hours) => hours,
+            _ => bail!("--hours must be a number from 1 to {}", MAX_HOURS),
+        },
+        None => 24,
+    };
+
+    let entries = storage.load()?;
+    let simulation = simulate::simulate(&entries, config.timezone.as_deref(), from, hours);
+    let local = |time: chrono::DateTime<Utc>| time.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+
+    println!("Runs from {} to {} (local time)", local(simulation.from), local(simulation.until));
+    for run in &simulation.runs {
+        println!("  {}  {}", local(run.time.with_timezone(&Utc)), entries[run.entry].name);
+    }
+
+    println!("\nRuns per entry:");
+    for (index, count) in &simulation.counts {
+        println!("  {:>5}  {}", count, entries[*index].name);
+    }
+    if !simulation.skipped.is_empty() {
+        println!("\nSkipped (invalid schedule or timezone):");
+        for (index, error) in &simulation.skipped {
+            println!("  {}: {}", entries[*index].name, error);
+        }
+    }
+    println!("{} runs in total", simulation.runs.len());
+    Ok(())
+}
+
+/// Enable or disable every entry matching `--tag <tag>` or `--name <glob>`
+fn set_enabled(args: &[String], storage: &Storage, enabled: bool) -> Result<()> {
+    let filter = match (option_value(args, "--tag"), option_value(args, "--name")) {
+        (Some(tag), None) => EntryFilter::Tag(tag.to_string()),
+        (None, Some(pattern)) => EntryFilter::Name(pattern.to_string()),
+        _ => bail!("Specify exactly one of --tag <tag> or --name <pattern>"),
+    };
+
+    let mut entries = storage.load()?;
+    let change = filter::set_enabled(&mut entries, &filter, enabled);
+    if change.changed.is_empty() && change.unchanged == 0 {
+        bail!("No entries match {}", filter);
+    }
+    let report = if change.changed.is_empty() { SaveReport::default() } else { storage.save(&entries)? };
+
+    let action = if enabled { "Enabled" } else { "Disabled" };
+    println!("{} {} entries", action, change.changed.len());
+    for name in &change.changed {
+        println!("  {}", name);
+    }
+    if change.unchanged > 0 {
+        println!("{} matching entries were already {}", change.unchanged, action.to_lowercase());
+    }
+    if !report.is_complete() {
+        for (index, reason) in &report.failed {
+            eprintln!("Not registered: {}: {}", entries[*index].name, reason);
+        }
+        bail!("{} entries were not registered by the scheduler", report.failed.len());
+    }
+    Ok(())
+}
+
+/// `history export [--entry <name>] [--since 30d|YYYY-MM-DD] [--format json|csv]`:
+/// print recorded runs, oldest first, for other tools or incident reports
+fn history(args: &[String], storage: &Storage, config: &Config) -> Result<()> {
+    if positional(args, "history").first() != Some(&"export") {
+        bail!("Usage: history export [--entry <name>] [--since 30d|YYYY-MM-DD] [--format json|csv]");
+    }
+    let now = Utc::now();
+    let since = match option_value(args, "--since") {
+        None => now - Duration::days(run_history::STATS_DAYS as i64),
+        Some(since) => match chrono::NaiveDate::parse_from_str(since, "%Y-%m-%d") {
+            Ok(date) => date
+                .and_hms_opt(0, 0, 0)
+                .and_then(|time| time.and_local_timezone(Local).earliest())
+                .with_context(|| format!("Invalid date '{}'", since))?
+                .with_timezone(&Utc),
+            Err(_) => now - cron_entry::parse_duration(since)?,
+        },
+    };
+    let csv = match option_value(args, "--format").unwrap_or("json") {
+        "json" => false,
+        "csv" => true,
+        format => bail!("Unknown format '{}' (use json or csv)", format),
+    };
+
+    let mut records = storage.runs_since(since, config.data_dir.as_deref())?;
+    if let Some(entry) = option_value(args, "--entry") {
+        records.retain(|record| record.entry == entry);
+    }
+    records.sort_by_key(|record| record.started_at);
+    if csv {
+        print!("{}", run_history::to_csv(&records));
+    } else {
+        println!("{}", serde_json::to_string_pretty(&records)?);
+    }
+    Ok(())
+}
+
+/// `export [--format json|yaml|toml] [path]`: every field of every entry, to
+/// the file (format from its extension by default) or to standard output
+fn export(args: &[String], storage: &Storage) -> Result<()> {
+    let format = option_value(args, "--format");
+    let words: Vec<&str> = positional(args, "export").into_iter().filter(|word| Some(*word) != format).collect();
+    let path = match words.as_slice() {
+        [] => None,
+        [path] => Some(Path::new(*path)),
+        _ => bail!("Usage: export [--format json|yaml|toml] [path]"),
+    };
+    let format = match format {
+        Some(name) => Format::parse(name)?,
+        None => path.and_then(Format::from_path).unwrap_or(Format::Json),
+    };
+    let text = storage.export(format)?;
+    match path {
+        Some(path) => {
+            std::fs::write(path, text).with_context(|| format!("Failed to write {}", path.display()))?;
+            println!("Exported to {}", path.display());
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+/// `backup <file>`: write the entries and managed scripts to a single
+/// archive, to `restore` on another machine
+fn backup(args: &[String], storage: &Storage) -> Result<()> {
+    let [path] = positional(args, "backup")[..] else {
+        bail!("Usage: backup <file>");
+    };
+    let archive = Archive::collect(storage)?;
+    std::fs::write(path, archive.to_string()?).with_context(|| format!("Failed to write {}", path))?;
+    println!("Backed up {} entries and {} scripts to {}", archive.entries.len(), archive.scripts.len(), path);
+    Ok(())
+}
+
+/// `restore <file> [--yes]`: replace the entries with those of a `backup`
+/// archive and put its scripts back, generating the backend's files
+/// (crontab, plists, units) for this machine. Asks first when entries or
+/// scripts would be replaced.
+fn restore(args: &[String], storage: &Storage, config: &Config) -> Result<()> {
+    let [path] = positional(args, "restore")[..] else {
+        bail!("Usage: restore <file> [--yes]");
+    };
+    let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path))?;
+    let archive = Archive::parse(&content).with_context(|| format!("Failed to restore {}", path))?;
+    println!(
+        "{} entries and {} scripts from {} ({}), backed up {}",
+        archive.entries.len(),
+        archive.scripts.len(),
+        archive.origin,
+        archive.backend,
+        archive.created.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+    );
+
+    let current = storage.load()?;
+    let changed_scripts = archive.changed_scripts(storage);
+    let mut replaced = Vec::new();
+    if !current.is_empty() {
+        replaced.push(format!("the {} current entries", current.len()));
+    }
+    if !changed_scripts.is_empty() {
+        replaced.push(format!("the scripts {}", changed_scripts.join(", ")));
+    }
+    if !replaced.is_empty() && !args.iter().any(|a| a == "--yes") && !confirm(&format!("Replace {}?", replaced.join(" and ")))? {
+        bail!("Not restored");
+    }
+
+    // A dry run leaves the scripts alone too, so entries must find theirs already there
+    if storage.dry_run_output().is_none() {
+        archive.restore_scripts(storage)?;
+    }
+    let policy = Policy::load(config.policy_file.as_deref())?;
+    for entry in &archive.entries {
+        storage.check_placeholders(&entry.command).with_context(|| format!("Entry '{}'", entry.name))?;
+        let violations = match &policy {
+            Some(policy) => policy.violations(&storage.resolve_scripts(&entry.command)?),
+            None => Vec::new(),
+        };
+        if !violations.is_empty() {
+            bail!("Entry '{}' violates the command policy: {}", entry.name, violations.join("; "));
+        }
+    }
+    let report = storage.save(&archive.entries)?;
+    println!("Restored {} entries to {}", archive.entries.len(), storage.get_backend_name());
+    if !report.is_complete() {
+        for (index, reason) in &report.failed {
+            eprintln!("Not registered: {}: {}", archive.entries[*index].name, reason);
+        }
+        bail!("{} entries were not registered by the scheduler", report.failed.len());
+    }
+    Ok(())
+}
+
+/// `sync [--push|--pull|--merge [--strategy yours|theirs|merged]] [--yes]`:
+/// list how the local file differs from the system scheduler, and with a
+/// direction make them match. A merge takes the changes each side made
+/// since the last sync; entries changed on both need a strategy.
+fn sync(args: &[String], storage: &Storage, config: &Config) -> Result<()> {
+    if storage.host().is_some() {
+        bail!("sync works between the local file and this machine's system scheduler");
+    }
+    let flag = |name: &str| args.iter().any(|a| a == name);
+    let direction = match (flag("--push"), flag("--pull"), flag("--merge")) {
+        (false, false, false) => None,
+        (true, false, false) => Some(Direction::Push),
+        (false, true, false) => Some(Direction::Pull),
+        (false, false, true) => Some(Direction::Merge),
+        _ => bail!("Pass one of --push, --pull or --merge"),
+    };
+    let strategy = option_value(args, "--strategy").map(Resolution::parse).transpose()?;
+    let open = |kind| {
+        let storage = Storage::for_backend(kind, config)?;
+        anyhow::Ok(if config.dry_run { storage.dry_run(true, config) } else { storage })
+    };
+    let (local, system) = (open(BackendKind::File)?, open(sync::system_kind(config))?);
+    let sync = Sync::new(&local, &system, config);
+    let backend = system.get_backend_name();
+
+    let changes = sync.changes()?;
+    if changes.is_empty() {
+        println!("The local file and {} are in sync", backend);
+        return Ok(());
+    }
+    println!("{} differences between the local file and {} (+ only in the file, - only in {}, ~ {} -> file)", changes.len(), backend, backend, backend);
+    let show = |value: &str| if config.secrets.redact { secrets::redact(value) } else { value.to_string() };
+    for change in &changes {
+        let marker = match change.kind {
+            ChangeKind::Added => "+",
+            ChangeKind::Removed => "-",
+            ChangeKind::Changed => "~",
+        };
+        println!("  {} {}", marker, change.entry);
+        for field in &change.fields {
+            println!("      {}: {} -> {}", field.field, show(&field.before), show(&field.after));
+        }
+    }
+    let Some(direction) = direction else {
+        return Ok(());
+    };
+
+    let entries = match (direction, strategy) {
+        (Direction::Merge, Some(strategy)) => {
+            let mut merge = sync.merge()?;
+            merge.resolve_all(strategy);
+            merge.entries
+        }
+        (Direction::Merge, None) => sync
+            .entries(direction)
+            .context("Push or pull instead, or pass --strategy yours|theirs|merged (yours is the local file)")?,
+        _ => sync.entries(direction)?,
+    };
+    let question = match direction {
+        Direction::Push => format!("Write the local file's {} entries to {}?", entries.len(), backend),
+        Direction::Pull => format!("Replace the local file with the {} entries of {}?", entries.len(), backend),
+        Direction::Merge => format!("Write the {} merged entries to the local file and {}?", entries.len(), backend),
+    };
+    if !flag("--yes") && !confirm(&question)? {
+        bail!("Not synced");
+    }
+    let report = sync.apply(direction, &entries)?;
+    println!("Synced {} entries", entries.len());
+    if !report.is_complete() {
+        for (index, reason) in &report.failed {
+            eprintln!("Not registered: {}: {}", entries[*index].name, reason);
+        }
+        bail!("{} entries were not registered by the scheduler", report.failed.len());
+    }
+    Ok(())
+}
+
+/// `import <file> [--format json|yaml|toml|crontab|system] [--on-duplicate skip|overwrite|rename]`:
+/// add the entries of an export or a crontab (format from the extension by
+/// default; `system` for `/etc/crontab` with its user column); fails on
+/// taken names unless told how to settle them
+fn import(args: &[String], storage: &Storage, config: &Config) -> Result<()> {
+    if args.iter().any(|a| a == "--launch-agents") {
+        return import_launch_agents(args, storage);
+    }
+    let (format, on_duplicate) = (option_value(args, "--format"), option_value(args, "--on-duplicate"));
+    let words: Vec<&str> = positional(args, "import")
+        .into_iter()
+        .filter(|word| Some(*word) != format && Some(*word) != on_duplicate)
+        .collect();
+    let [path] = words.as_slice() else {
+        bail!("Usage: import <file> [--format json|yaml|toml|crontab|system] [--on-duplicate skip|overwrite|rename]");
+    };
+    let path = Path::new(*path);
+    let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let imported = match format {
+        Some("system") => export::parse_system(&content),
+        Some("crontab") => export::parse(&content, None),
+        Some(name) => export::parse(&content, Some(Format::parse(name)?)),
+        None => export::parse(&content, Format::from_path(path)),
+    }
+    .with_context(|| format!("Failed to import {}", path.display()))?;
+
+    let policy = Policy::load(config.policy_file.as_deref())?;
+    for entry in &imported {
+        storage.check_placeholders(&entry.command).with_context(|| format!("Entry '{}'", entry.name))?;
+        let violations = match &policy {
+            Some(policy) => policy.violations(&storage.resolve_scripts(&entry.command)?),
+            None => Vec::new(),
+        };
+        if !violations.is_empty() {
+            bail!("Entry '{}' violates the command policy: {}", entry.name, violations.join("; "));
+        }
+    }
+
+    let mut entries = storage.load()?;
+    let taken = export::duplicates(&entries, &imported);
+    let on_duplicate = match on_duplicate {
+        Some(choice) => OnDuplicate::parse(choice)?,
+        None if taken.is_empty() => OnDuplicate::Skip,
+        None => bail!("Names already in use: {}; pass --on-duplicate skip|overwrite|rename", taken.join(", ")),
+    };
+    let summary = export::merge(&mut entries, imported, on_duplicate);
+    let report = storage.save(&entries)?;
+    println!(
+        "Imported {} entries ({} overwritten, {} renamed), skipped {}",
+        summary.added + summary.overwritten + summary.renamed,
+        summary.overwritten,
+        summary.renamed,
+        summary.skipped
+    );
+    if !report.is_complete() {
+        for (index, reason) in &report.failed {
+            eprintln!("Not registered: {}: {}", entries[*index].name, reason);
+        }
+        bail!("{} entries were not registered by the scheduler", report.failed.len());
+    }
+    Ok(())
+}
+
+/// `import --launch-agents [--adopt [label...]]`: list the user's
+/// LaunchAgents that CronManager didn't write and whether each can become an
+/// entry; with `--adopt`, add those (or the labels given) as entries and
+/// retire the originals so they don't run twice
+#[cfg(target_os = "macos")]
+fn import_launch_agents(args: &[String], storage: &Storage) -> Result<()> {
+    use crate::scheduler::launchd::LaunchdScheduler;
+    use crate::scheduler::Scheduler;
+
+    let scheduler = LaunchdScheduler::new();
+    let labels = positional(args, "import");
+    let agents: Vec<_> = scheduler
+        .foreign_agents()?
+        .into_iter()
+        .filter(|agent| labels.is_empty() || labels.contains(&agent.label.as_str()))
+        .collect();
+    if let Some(missing) = labels.iter().find(|label| !agents.iter().any(|agent| agent.label == **label)) {
+        bail!("No LaunchAgent labelled '{}'", missing);
+    }
+
+    if !args.iter().any(|a| a == "--adopt") {
+        for agent in &agents {
+            match &agent.entry {
+                Ok(entry) => println!("{}  {}  {}", agent.label, entry.schedule, entry.command),
+                Err(reason) => println!("{}  (read-only: {})", agent.label, reason),
+            }
+        }
+        let adoptable = agents.iter().filter(|agent| agent.entry.is_ok()).count();
+        println!("{} LaunchAgents, {} can be adopted with --adopt", agents.len(), adoptable);
+        return Ok(());
+    }
+
+    // Retiring the originals only makes sense when launchd runs the entries
+    if storage.get_backend_name() != scheduler.backend_name() {
+        bail!("Adopting needs the launchd backend, not {}", storage.get_backend_name());
+    }
+    let mut entries = storage.load()?;
+    let mut adopted = Vec::new();
+    for agent in &agents {
+        match &agent.entry {
+            Ok(entry) if entries.iter().any(|existing| existing.name == entry.name) => {
+                eprintln!("Skipped {}: an entry of that name already exists", agent.label);
+            }
+            Ok(entry) => {
+                entries.push(entry.clone());
+                adopted.push((agent, entries.len() - 1));
+            }
+            Err(reason) => eprintln!("Skipped {}: {}", agent.label, reason),
+        }
+    }
+    if adopted.is_empty() {
+        bail!("No LaunchAgents to adopt");
+    }
+
+    let report = storage.save(&entries)?;
+    for (agent, index) in &adopted {
+        if let Some((_, reason)) = report.failed.iter().find(|(failed, _)| failed == index) {
+            eprintln!("Not registered: {}: {} (original left in place)", agent.label, reason);
+            continue;
+        }
+        let retired = scheduler.retire_agent(agent)?;
+        println!("Adopted {} (original moved to {})", agent.label, retired.display());
+    }
+    if !report.is_complete() {
+        bail!("{} entries were not registered by the scheduler", report.failed.len());
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn import_launch_agents(_args: &[String], _storage: &Storage) -> Result<()> {
+    bail!("--launch-agents is only available on macOS")
+}
+
+/// `logs grep <pattern> [--tag <tag> | --name <pattern>]`: print the lines of
+/// every entry's log that match, grep-style; fails when nothing matches.
+/// `logs rotate [<file>]`: rotate a captured log, or all of them.
+fn logs(args: &[String], storage: &Storage, config: &Config) -> Result<()> {
+    let words = positional(args, "logs");
+    let query = match words.as_slice() {
+        ["grep", query, ..] => *query,
+        ["rotate", files @ ..] => return rotate_logs(files, config),
+        _ => bail!("Usage: logs grep <text or /regex/> [--tag <tag> | --name <pattern>] | logs rotate [<file>]"),
+    };
+    let pattern = log_search::compile(query)?;
+    let filter = match (option_value(args, "--tag"), option_value(args, "--name")) {
+        (Some(tag), None) => Some(EntryFilter::Tag(tag.to_string())),
+        (None, Some(name)) => Some(EntryFilter::Name(name.to_string())),
+        (None, None) => None,
+        _ => bail!("Specify at most one of --tag <tag> or --name <pattern>"),
+    };
+
+    let mut searched = Vec::new();
+    let mut found = 0;
+    for entry in storage.load()? {
+        if filter.as_ref().is_some_and(|f| !f.matches(&entry)) {
+            continue;
+        }
+        // Entries often share a log file; search each once
+        let Some(path) = storage.log_path(&entry) else { continue };
+        if searched.contains(&path) || !path.exists() {
+            continue;
+        }
+        for found_line in log_search::search_file(&path, &pattern)? {
+            println!("{}  {}:{}: {}", entry.name, path.display(), found_line.line, found_line.text);
+            found += 1;
+        }
+        searched.push(path);
+    }
+    if found == 0 {
+        bail!("No matches in {} log files", searched.len());
+    }
+    Ok(())
+}
+
+/// Rotate the captured log `cron-manager` runs this for before each capture,
+/// creating its directory if need be, or every log in the capture directory
+fn rotate_logs(files: &[&str], config: &Config) -> Result<()> {
+    match files {
+        [file] => {
+            let path = Path::new(file);
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+            }
+            log_capture::rotate(path, &config.logs)?;
+        }
+        [] => {
+            let dir = crate::paths::data_dir(config.data_dir.as_deref()).context("No data directory")?;
+            let rotated = log_capture::rotate_all(&dir.join(log_capture::LOG_DIR), &config.logs)?;
+            println!("Rotated {} logs", rotated);
+        }
+        _ => bail!("Usage: logs rotate [<file>]"),
+    }
+    Ok(())
+}
+
+/// Print schedule collisions and clusters for the upcoming horizon
+fn report(storage: &Storage, config: &Config, clock: Clock) -> Result<()> {
+    let entries = storage.load()?;
+    let settings = &config.collisions;
+    let now = clock.now_local();
+    let report = collisions::analyze(&entries, now, settings);
+
+    println!("Collision report for the next {} hours", settings.horizon_hours);
+
+    if report.is_empty() {
+        println!("No collisions or clusters found.");
+        return Ok(());
+    }
+
+    if !report.collisions.is_empty() {
+        println!("\nRuns within {} minute(s) of each other:", settings.window_minutes);
+        for collision in &report.collisions {
+            let (a, b) = collision.entries;
+            println!(
+                "  '{}' and '{}': {} time(s), first at {}",
+                entries[a].name,
+                entries[b].name,
+                collision.occurrences,
+                collision.first.format("%Y-%m-%d %H:%M")
+            );
+        }
+    }
+
+    if !report.clusters.is_empty() {
+        println!(
+            "\nClusters of {}+ jobs within {} minutes:",
+            settings.cluster_size, settings.cluster_window_minutes
+        );
+        for cluster in &report.clusters {
+            let names: Vec<&str> = cluster.entries.iter().map(|i| entries[*i].name.as_str()).collect();
+            println!("  {}: {}", cluster.start.format("%Y-%m-%d %H:%M"), names.join(", "));
+        }
+    }
+
+    Ok(())
+}
+
+/// `analyze [--top <n>]` prints the minutes the most jobs start together in
+/// and the hours of the day the most runs start in, over the horizon
+fn analyze(args: &[String], storage: &Storage, config: &Config, clock: Clock) -> Result<()> {
+    let top = match option_value(args, "--top") {
+        Some(text) => text.parse::<usize>().ok().filter(|top| *top > 0).context("--top must be a positive number")?,
+        None => 10,
+    };
+    let entries = storage.load()?;
+    let settings = &config.collisions;
+    let busiest = collisions::busiest(&entries, clock.now_local(), settings);
+
+    println!("Busiest minutes in the next {} hours:", settings.horizon_hours);
+    if busiest.minutes.is_empty() {
+        println!("  No two jobs start in the same minute");
+    }
+    for minute in busiest.minutes.iter().take(top) {
+        let names: Vec<&str> = minute.entries.iter().map(|i| entries[*i].name.as_str()).collect();
+        let marker = if minute.entries.len() > settings.max_per_minute { "!" } else { " " };
+        println!(
+            "{} {} jobs at {} ({} time(s)): {}",
+            marker,
+            minute.entries.len(),
+            minute.first.format("%Y-%m-%d %H:%M"),
+            minute.occurrences,
+            names.join(", ")
+        );
+    }
+
+    let mut hours: Vec<(usize, usize)> = busiest.hours.iter().copied().enumerate().filter(|(_, runs)| *runs > 0).collect();
+    hours.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    println!("\nBusiest hours of the day:");
+    let days = f64::from(settings.horizon_hours) / 24.0;
+    for (hour, runs) in hours.into_iter().take(top) {
+        println!("  {:02}:00-{:02}:59  {:>6} runs ({:.1} a day)", hour, hour, runs, runs as f64 / days);
+    }
+    Ok(())
+}
+
+/// Print lint findings for every entry; fails when any finding is an error
+fn lint(storage: &Storage, config: &Config, clock: Clock) -> Result<()> {
+    let entries = storage.load()?;
+    let now = clock.now_local();
+    let findings = lint::lint(&entries, storage, config, now);
+
+    for finding in &findings {
+        println!(
+            "{:<7} {}: {}",
+            finding.severity.label(),
+            entries[finding.entry].name,
+            finding.message
+        );
+    }
+
+    let errors = findings.iter().filter(|f| f.severity == Severity::Error).count();
+    println!(
+        "{} entries checked, {} error(s), {} warning(s)",
+        entries.len(),
+        errors,
+        findings.len() - errors
+    );
+
+    if errors > 0 {
+        bail!("Lint failed with {} error(s)", errors);
+    }
+    Ok(())
+}
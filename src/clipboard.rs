@@ -0,0 +1,74 @@
+use anyhow::{bail, Result};
+use std::process::Command;
+
+/// Programs that print the clipboard, tried in order; the first one that
+/// runs wins
+const PASTE_COMMANDS: &[&[&str]] = &[
+    &["pbpaste"],
+    &["wl-paste", "--no-newline"],
+    &["xclip", "-selection", "clipboard", "-o"],
+    &["xsel", "--clipboard", "--output"],
+    // WSL
+    &["powershell.exe", "-NoProfile", "-Command", "Get-Clipboard"],
+];
+
+/// Words after which a pasted line break continues the command rather than
+/// ending it
+const CONTINUATIONS: &[&str] = &["&&", "||", "|", ";", "then", "do", "else", "{", "("];
+
+/// The text on the system clipboard
+pub fn read() -> Result<String> {
+    for command in PASTE_COMMANDS {
+        match Command::new(command[0]).args(&command[1..]).output() {
+            Ok(output) if output.status.success() => return Ok(String::from_utf8_lossy(&output.stdout).into_owned()),
+            Ok(output) => bail!("{} failed: {}", command[0], String::from_utf8_lossy(&output.stderr).trim()),
+            Err(_) => continue,
+        }
+    }
+    bail!("No clipboard program found (install wl-clipboard, xclip or xsel)")
+}
+
+/// Pasted text made fit for the one-line input: lines continued with `\`
+/// are joined, and other line breaks (`\n`, `\r\n`, or the `\r` many
+/// terminals paste) become `; ` between the lines of a command and spaces
+/// elsewhere. Tabs become spaces and other control characters are dropped.
+pub fn sanitize(text: &str, command: bool) -> String {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let mut joined = String::new();
+    for line in text.lines().map(str::trim_end).filter(|line| !line.trim().is_empty()) {
+        if !joined.is_empty() {
+            let continued = joined.ends_with('\\');
+            if continued {
+                joined.pop();
+            }
+            let last_word = joined.split_whitespace().last().unwrap_or("");
+            if command && !continued && !CONTINUATIONS.contains(&last_word) {
+                joined.push(';');
+            }
+            if !joined.ends_with(' ') {
+                joined.push(' ');
+            }
+            joined.push_str(line.trim_start());
+        } else {
+            // Leading spaces stay, to separate what's pasted from what's typed
+            joined.push_str(line);
+        }
+    }
+    joined.chars().filter_map(|c| if c == '\t' { Some(' ') } else { (!c.is_control()).then_some(c) }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize() {
+        assert_eq!(sanitize("/usr/bin/backup --all\n", true), "/usr/bin/backup --all");
+        assert_eq!(sanitize("cd /srv\r\nmake\r\n\r\n./deploy", true), "cd /srv; make; ./deploy");
+        assert_eq!(sanitize("rsync -a \\\n    src/ dst/", true), "rsync -a src/ dst/");
+        assert_eq!(sanitize("test -f x &&\n  rm x\nif true; then\n echo\nfi", true), "test -f x && rm x; if true; then echo; fi");
+        assert_eq!(sanitize(" && cd /srv\rmake\r", true), " && cd /srv; make");
+        assert_eq!(sanitize("Nightly\nbackup", false), "Nightly backup");
+        assert_eq!(sanitize("a\tb\u{1b}[0m", false), "a b[0m");
+    }
+}
@@ -0,0 +1,69 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, Local, NaiveDateTime, TimeZone, Timelike, Utc};
+
+/// Source of "now" for schedule computations. `--simulate-time` starts it at
+/// another moment, from which it keeps running at normal speed.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Clock {
+    /// How far the simulated time is ahead of (or behind) the real time
+    offset: Option<Duration>,
+}
+
+impl Clock {
+    /// The real time
+    pub fn system() -> Self {
+        Self::default()
+    }
+
+    /// A clock that reads `start` now and advances from there
+    pub fn starting_at(start: DateTime<Utc>) -> Self {
+        Self { offset: Some(start - Utc::now()) }
+    }
+
+    pub fn is_simulated(&self) -> bool {
+        self.offset.is_some()
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        Utc::now() + self.offset.unwrap_or_default()
+    }
+
+    /// Local wall-clock time
+    pub fn now_local(&self) -> NaiveDateTime {
+        self.now().with_timezone(&Local).naive_local()
+    }
+
+    /// Local wall-clock time truncated to the minute
+    pub fn current_minute(&self) -> NaiveDateTime {
+        let now = self.now_local();
+        now.with_second(0).and_then(|t| t.with_nanosecond(0)).unwrap_or(now)
+    }
+}
+
+/// Parse a local `YYYY-MM-DD HH:MM` (or `YYYY-MM-DDTHH:MM`) time
+pub fn parse_local_time(text: &str) -> Result<DateTime<Utc>> {
+    let naive = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M")
+        .or_else(|_| NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M"))
+        .with_context(|| format!("Invalid time '{}' (expected YYYY-MM-DD HH:MM)", text))?;
+    match Local.from_local_datetime(&naive).earliest() {
+        Some(time) => Ok(time.with_timezone(&Utc)),
+        None => bail!("{} does not exist in the local timezone", text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simulated_clock() {
+        let start = parse_local_time("2030-03-10 01:30").unwrap();
+        let clock = Clock::starting_at(start);
+        assert!(clock.is_simulated());
+        assert!((clock.now() - start).num_seconds().abs() < 5);
+        assert_eq!(clock.current_minute().format("%Y-%m-%d %H:%M").to_string(), "2030-03-10 01:30");
+
+        assert!(!Clock::system().is_simulated());
+        assert!(parse_local_time("next tuesday").is_err());
+    }
+}
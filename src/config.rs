@@ -0,0 +1,437 @@
+use crate::env_presets::{self, EnvPresets};
+use crate::keys::KeyMap;
+use crate::macros::{self, Macro};
+use crate::paths;
+use crate::theme::ThemeConfig;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::PathBuf;
+
+/// User settings loaded from `config.toml` in the cron-manager config directory.
+/// Every field is optional; a missing file means all defaults.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Rewrite schedules into their canonical form before saving
+    pub normalize_on_save: bool,
+    /// Refuse to confirm invalid or backend-incompatible schedules instead of
+    /// saving them with a warning (also enabled by `--strict`)
+    pub strict_validation: bool,
+    /// Saves only render what would be written (`--dry-run`); not read from
+    /// the config file
+    #[serde(skip)]
+    pub dry_run: bool,
+    /// Wait this long after the last edit before writing to the backend, so a
+    /// burst of changes becomes a single write; the write then runs in the
+    /// background (0 saves every change at once, blocking)
+    pub save_debounce_ms: u64,
+    /// Check the backend this often for changes made outside cron-manager
+    /// (another editor, `crontab -e`) and reload them; 0 never checks
+    pub reload_interval_secs: u64,
+    /// With `--local`, import the system scheduler's entries when the local
+    /// file doesn't exist yet. Disable for an instant first start.
+    pub import_from_system: bool,
+    /// Offer commands from the shell history (Tab) when entering a command
+    pub suggest_from_history: bool,
+    /// Ask before `d` deletes the selected entry
+    pub confirm_delete: bool,
+    /// Show the selected entry in full beside the table, on wide enough terminals
+    pub preview_pane: bool,
+    /// Group the entry table's rows under tag headings
+    pub group_by_tag: bool,
+    /// UI language: "en", "ja" or "auto" (from LANG)
+    pub language: Option<String>,
+    /// Directory for the local crontab and other data files
+    /// (defaults to `$XDG_DATA_HOME/cron-manager`)
+    pub data_dir: Option<PathBuf>,
+    /// Managed script library (defaults to `scripts` in the data directory)
+    pub scripts_dir: Option<PathBuf>,
+    /// IANA timezone schedules are evaluated in (defaults to the system timezone)
+    pub timezone: Option<String>,
+    /// Command policy file (defaults to `policy.toml` in the config directory)
+    pub policy_file: Option<PathBuf>,
+    /// Run every entry through `cron-manager exec` so each run, with the end
+    /// of its output, is recorded in the run history
+    pub record_runs: bool,
+    pub backend: BackendConfig,
+    pub backups: BackupConfig,
+    pub trash: TrashConfig,
+    pub collisions: CollisionConfig,
+    pub frequency: FrequencyConfig,
+    pub secrets: SecretsConfig,
+    pub watchdog: WatchdogConfig,
+    pub ping: PingConfig,
+    pub notify: NotifyConfig,
+    pub mail: MailConfig,
+    pub logs: LogsConfig,
+    pub agent: AgentConfig,
+    pub theme: ThemeConfig,
+    /// Named sets of `NAME=value` lines that entries can reference
+    pub env_presets: EnvPresets,
+    /// `[keys]`: the keys of the entry list's actions
+    pub keys: KeyMap,
+    /// `[[macros]]`: keys bound to steps or external commands
+    pub macros: Vec<Macro>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            normalize_on_save: false,
+            strict_validation: false,
+            dry_run: false,
+            save_debounce_ms: 500,
+            reload_interval_secs: 2,
+            import_from_system: true,
+            suggest_from_history: true,
+            confirm_delete: true,
+            preview_pane: true,
+            group_by_tag: false,
+            data_dir: None,
+            scripts_dir: None,
+            language: None,
+            timezone: None,
+            policy_file: None,
+            record_runs: false,
+            backend: BackendConfig::default(),
+            backups: BackupConfig::default(),
+            trash: TrashConfig::default(),
+            collisions: CollisionConfig::default(),
+            frequency: FrequencyConfig::default(),
+            secrets: SecretsConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            ping: PingConfig::default(),
+            notify: NotifyConfig::default(),
+            mail: MailConfig::default(),
+            logs: LogsConfig::default(),
+            agent: AgentConfig::default(),
+            theme: ThemeConfig::default(),
+            env_presets: EnvPresets::new(),
+            keys: KeyMap::default(),
+            macros: Vec::new(),
+        }
+    }
+}
+
+/// A scheduler backend that can be selected in the config file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackendKind {
+    /// The OS scheduler: cron on Linux, launchd on macOS (same as `--system`)
+    System,
+    Cron,
+    Launchd,
+    /// systemd user timers (Linux)
+    Systemd,
+    /// The local crontab file (same as `--local`)
+    File,
+    /// root's schedule: root's crontab, or LaunchDaemons on macOS (same as
+    /// `--root`)
+    Root,
+}
+
+/// `[backend]` section: the backend used when neither `--local` nor
+/// `--system` is given, per operating system
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct BackendConfig {
+    pub linux: BackendKind,
+    pub macos: BackendKind,
+    /// Any other OS
+    pub other: BackendKind,
+}
+
+impl Default for BackendConfig {
+    fn default() -> Self {
+        Self {
+            linux: BackendKind::System,
+            macos: BackendKind::System,
+            other: BackendKind::System,
+        }
+    }
+}
+
+impl BackendConfig {
+    /// The configured backend for the OS this binary was built for
+    pub fn for_current_os(&self) -> BackendKind {
+        match std::env::consts::OS {
+            "linux" => self.linux,
+            "macos" => self.macos,
+            _ => self.other,
+        }
+    }
+}
+
+/// `[collisions]` section: thresholds for collision and clustering warnings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CollisionConfig {
+    /// Runs less than this many minutes apart count as colliding (1 = same minute)
+    pub window_minutes: u32,
+    /// Flag a cluster when at least this many jobs fire within `cluster_window_minutes`
+    pub cluster_size: usize,
+    pub cluster_window_minutes: u32,
+    /// Warn when adding or editing a schedule makes more jobs than this start in one minute
+    pub max_per_minute: usize,
+    /// How far ahead to look for collisions
+    pub horizon_hours: u32,
+}
+
+impl Default for CollisionConfig {
+    fn default() -> Self {
+        Self {
+            window_minutes: 1,
+            cluster_size: 3,
+            cluster_window_minutes: 5,
+            max_per_minute: 2,
+            horizon_hours: 24 * 7,
+        }
+    }
+}
+
+/// `[frequency]` section: when to warn about schedules that fire very often
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct FrequencyConfig {
+    /// Warn when a schedule fires more than this many times in a day
+    pub warn_runs_per_day: usize,
+}
+
+impl Default for FrequencyConfig {
+    fn default() -> Self {
+        // Anything more often than every 5 minutes
+        Self { warn_runs_per_day: 288 }
+    }
+}
+
+/// `[backups]` section: snapshots of the entries taken after every save
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct BackupConfig {
+    pub enabled: bool,
+    /// Snapshots kept before the oldest are deleted
+    pub keep: usize,
+    /// Days covered by the recently-changed view
+    pub recent_days: u32,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self { enabled: true, keep: 50, recent_days: 14 }
+    }
+}
+
+/// `[trash]` section: deleted entries kept so they can be put back
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TrashConfig {
+    /// Days a deleted entry stays in the trash; 0 deletes entries for good
+    pub retention_days: u32,
+}
+
+impl Default for TrashConfig {
+    fn default() -> Self {
+        Self { retention_days: 30 }
+    }
+}
+
+/// `[secrets]` section: where `!secret NAME` placeholders are looked up
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SecretsConfig {
+    /// `NAME=value` file (defaults to `secrets.env` in the config directory)
+    pub env_file: Option<PathBuf>,
+    /// Fall back to the OS keychain (`security` on macOS, `secret-tool` elsewhere)
+    pub keychain: bool,
+    /// Mask values that look like credentials in the entry table
+    pub redact: bool,
+}
+
+impl Default for SecretsConfig {
+    fn default() -> Self {
+        Self { env_file: None, keychain: true, redact: true }
+    }
+}
+
+/// `[watchdog]` section: checks that entries flagged with a watchdog really run
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct WatchdogConfig {
+    /// When the generated watchdog entries run (a single minute of every hour
+    /// by default, which every backend can represent)
+    pub schedule: String,
+    /// How late a run may be before it counts as missed; should cover the
+    /// job's longest run, as runs are recorded when they finish
+    pub grace_minutes: u32,
+    /// Run through `sh` when a run was missed, with the entry's name in
+    /// `CRON_MANAGER_ENTRY` (the alert is printed either way, so cron mails it)
+    pub alert_command: Option<String>,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self { schedule: "7 * * * *".to_string(), grace_minutes: 15, alert_command: None }
+    }
+}
+
+/// `[ping]` section: entries that ping a monitoring service
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PingConfig {
+    /// How late a ping may be before the entry is marked as overdue in the
+    /// table; like the watchdog's, it should cover the job's longest run
+    pub grace_minutes: u32,
+}
+
+impl Default for PingConfig {
+    fn default() -> Self {
+        Self { grace_minutes: 15 }
+    }
+}
+
+/// `[notify]` section: a Slack- or Discord-compatible incoming webhook told
+/// about failed runs
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct NotifyConfig {
+    /// Where failures are posted (may be `!secret NAME`); when set, every
+    /// entry runs through `cron-manager exec` so its failures are seen
+    pub webhook_url: Option<String>,
+    /// The message, with `{name}`, `{exit_code}` and `{stderr}` (the end of
+    /// the run's standard error) filled in
+    pub message: String,
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        Self { webhook_url: None, message: "Cron job \"{name}\" failed with exit code {exit_code}\n{stderr}".to_string() }
+    }
+}
+
+/// When a run's output is mailed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MailWhen {
+    /// Like cron's `MAILTO`: runs that printed something, and failed ones
+    Output,
+    /// Failed runs only
+    Failure,
+}
+
+/// `[mail]` section: mailing the output of runs over SMTP, for hosts where
+/// cron has no mail transfer agent to send its own
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MailConfig {
+    /// Recipients; when there are any, every entry runs through
+    /// `cron-manager exec` so its output can be mailed
+    pub to: Vec<String>,
+    /// Sender address (defaults to `user@host`)
+    pub from: Option<String>,
+    /// The SMTP server: `smtps://` for TLS, `smtp://` for plain or STARTTLS
+    pub smtp_url: String,
+    pub username: Option<String>,
+    /// May be `!secret NAME`
+    pub password: Option<String>,
+    pub when: MailWhen,
+}
+
+impl Default for MailConfig {
+    fn default() -> Self {
+        Self {
+            to: Vec::new(),
+            from: None,
+            smtp_url: "smtp://localhost:25".to_string(),
+            username: None,
+            password: None,
+            when: MailWhen::Output,
+        }
+    }
+}
+
+/// `[logs]` section: capturing the output of entries that don't redirect it
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LogsConfig {
+    /// Append the output of entries that don't redirect it themselves to a
+    /// log file per entry in the data directory's `logs/`, for backends that
+    /// keep no log of their own (cron)
+    pub capture: bool,
+    /// Size a log grows to before it is rotated, checked before each run
+    pub max_size_kb: u64,
+    /// Rotated copies kept (`name.log.1` is the newest)
+    pub keep: usize,
+    /// Rotated copies older than this are deleted (0 keeps them)
+    pub max_age_days: u32,
+}
+
+impl Default for LogsConfig {
+    fn default() -> Self {
+        Self { capture: false, max_size_kb: 1024, keep: 5, max_age_days: 30 }
+    }
+}
+
+/// `[agent]` section: `cron-manager agent` and the TUI connecting to it
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AgentConfig {
+    /// Shared secret every request must carry (`CRON_MANAGER_AGENT_TOKEN` overrides it)
+    pub token: Option<String>,
+    /// Address the agent listens on (defaults to 127.0.0.1:7878)
+    pub listen: Option<String>,
+}
+
+impl Config {
+    /// Whether every entry runs through `cron-manager exec`, to record its
+    /// runs, post its failures to the webhook or mail its output
+    pub fn exec_every_entry(&self) -> bool {
+        self.record_runs || self.notify.webhook_url.is_some() || !self.mail.to.is_empty()
+    }
+
+    /// Location of the config file (e.g. `~/.config/cron-manager/config.toml`)
+    pub fn path() -> Option<PathBuf> {
+        paths::config_dir().map(|dir| dir.join("config.toml"))
+    }
+
+    /// Load the config file, falling back to defaults when it doesn't exist
+    pub fn load() -> Result<Self> {
+        match Self::path() {
+            Some(path) if path.exists() => {
+                let content = fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read config file: {:?}", path))?;
+                Self::parse(&content)
+                    .with_context(|| format!("Failed to parse config file: {:?}", path))
+            }
+            _ => Ok(Self::default()),
+        }
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let config: Self = toml::from_str(content)?;
+        env_presets::validate(&config.env_presets)?;
+        macros::validate(&config.macros, &config.keys)?;
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_backend_section() {
+        let config = Config::parse("[backend]\nmacos = \"file\"\n").unwrap();
+        assert_eq!(config.backend.macos, BackendKind::File);
+        assert_eq!(config.backend.linux, BackendKind::System);
+        assert!(Config::parse("[backend]\nlinux = \"anacron\"\n").is_err());
+    }
+
+    #[test]
+    fn test_macros_take_keys_the_key_map_frees() {
+        let binding = "[[macros]]\nkey = \"d\"\nsteps = [\"disable\"]\n";
+        assert!(Config::parse(binding).is_err());
+        assert!(Config::parse(&format!("[keys]\ndelete = \"D\"\n\n{}", binding)).is_ok());
+        assert!(Config::parse("[keys]\nadd = \"d\"\n").is_err());
+    }
+}
@@ -0,0 +1,208 @@
+use crate::cron_entry::CronEntry;
+use anyhow::{bail, Result};
+use serde_json::{Map, Value};
+
+/// How to settle an entry changed both here and in the backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Yours,
+    Theirs,
+    /// Your version, with the fields only they changed taken from theirs
+    Merged,
+}
+
+impl Resolution {
+    /// Parse the `--strategy` option of `apply`
+    pub fn parse(input: &str) -> Result<Self> {
+        Ok(match input {
+            "yours" => Self::Yours,
+            "theirs" => Self::Theirs,
+            "merged" => Self::Merged,
+            _ => bail!("Unknown strategy '{}' (use yours, theirs or merged)", input),
+        })
+    }
+}
+
+/// An entry changed on both sides since it was loaded. `None` is an entry
+/// deleted on that side, or not there in the first place.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub name: String,
+    pub base: Option<CronEntry>,
+    pub yours: Option<CronEntry>,
+    pub theirs: Option<CronEntry>,
+}
+
+impl Conflict {
+    /// The entry to keep, if any
+    pub fn resolve(&self, resolution: Resolution) -> Option<CronEntry> {
+        match resolution {
+            Resolution::Yours => self.yours.clone(),
+            Resolution::Theirs => self.theirs.clone(),
+            Resolution::Merged => self.merged(),
+        }
+    }
+
+    /// Your version with the fields only they changed taken from theirs. An
+    /// entry deleted on one side keeps the other side's version.
+    fn merged(&self) -> Option<CronEntry> {
+        let (yours, theirs) = match (&self.yours, &self.theirs) {
+            (Some(yours), Some(theirs)) => (yours, theirs),
+            (yours, theirs) => return yours.clone().or_else(|| theirs.clone()),
+        };
+        let Some(base) = &self.base else {
+            return Some(yours.clone());
+        };
+        let (base, their_fields) = (fields(base), fields(theirs));
+        let mut merged = fields(yours);
+        for (field, value) in merged.iter_mut() {
+            match their_fields.get(field) {
+                Some(theirs) if base.get(field) == Some(value) => *value = theirs.clone(),
+                _ => {}
+            }
+        }
+        Some(serde_json::from_value(Value::Object(merged)).unwrap_or_else(|_| yours.clone()))
+    }
+
+    /// Names of the fields that differ between your version and theirs
+    pub fn changed_fields(&self) -> Vec<String> {
+        let (Some(yours), Some(theirs)) = (&self.yours, &self.theirs) else {
+            return Vec::new();
+        };
+        let theirs = fields(theirs);
+        fields(yours)
+            .into_iter()
+            .filter(|(field, value)| theirs.get(field) != Some(value))
+            .map(|(field, _)| field)
+            .collect()
+    }
+}
+
+fn fields(entry: &CronEntry) -> Map<String, Value> {
+    match serde_json::to_value(entry) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    }
+}
+
+/// Your entries with the backend's changes merged in, and the entries
+/// both sides changed, which stay as yours until resolved
+#[derive(Debug, Clone, PartialEq)]
+pub struct Merge {
+    pub entries: Vec<CronEntry>,
+    pub conflicts: Vec<Conflict>,
+}
+
+impl Merge {
+    /// Settle a conflict, putting the chosen entry in place of yours
+    pub fn resolve(&mut self, index: usize, resolution: Resolution) {
+        if index >= self.conflicts.len() {
+            return;
+        }
+        let conflict = self.conflicts.remove(index);
+        let position = self.entries.iter().position(|e| e.name == conflict.name);
+        match (position, conflict.resolve(resolution)) {
+            (Some(position), Some(entry)) => self.entries[position] = entry,
+            (Some(position), None) => {
+                self.entries.remove(position);
+            }
+            (None, Some(entry)) => self.entries.push(entry),
+            (None, None) => {}
+        }
+    }
+
+    /// Settle every remaining conflict the same way
+    pub fn resolve_all(&mut self, resolution: Resolution) {
+        while !self.conflicts.is_empty() {
+            self.resolve(0, resolution);
+        }
+    }
+}
+
+/// Three-way merge, by entry name, of your entries and the backend's
+/// current ones (theirs), both changed from the entries loaded (base). An
+/// entry only one side changed takes that side's version; entries the
+/// backend added go at the end.
+pub fn merge(base: &[CronEntry], yours: &[CronEntry], theirs: &[CronEntry]) -> Merge {
+    let find = |entries: &[CronEntry], name: &str| entries.iter().find(|e| e.name == name).cloned();
+    let mut merge = Merge { entries: Vec::new(), conflicts: Vec::new() };
+    let settle = |name: &str, yours: Option<CronEntry>, merge: &mut Merge| {
+        let (base, theirs) = (find(base, name), find(theirs, name));
+        let keep = if theirs == base || theirs == yours {
+            yours
+        } else if yours == base {
+            theirs
+        } else {
+            merge.conflicts.push(Conflict { name: name.to_string(), base, yours: yours.clone(), theirs });
+            yours
+        };
+        merge.entries.extend(keep);
+    };
+
+    for entry in yours {
+        settle(&entry.name, Some(entry.clone()), &mut merge);
+    }
+    // Deleted here, or added there
+    let missing: Vec<&CronEntry> = base.iter().chain(theirs).filter(|e| !yours.iter().any(|y| y.name == e.name)).collect();
+    let mut seen = Vec::new();
+    for entry in missing {
+        if !seen.contains(&entry.name) {
+            seen.push(entry.name.clone());
+            settle(&entry.name, None, &mut merge);
+        }
+    }
+    merge
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::command_entry as entry;
+
+    #[test]
+    fn test_merge() {
+        let base = vec![
+            entry("Backup", "0 2 * * *", "/bin/backup"),
+            entry("Report", "0 9 * * *", "/bin/report"),
+            entry("Sync", "0 * * * *", "/bin/sync"),
+            entry("Legacy", "0 4 * * *", "/bin/legacy"),
+        ];
+        // Here: Backup's schedule and Report's command changed, Legacy deleted
+        let mut yours = base.clone();
+        yours[0].schedule = "0 3 * * *".to_string();
+        yours[1].command = "/bin/report --full".to_string();
+        yours.remove(3);
+        // There: Backup's command and Report's command changed, Sync disabled, Cleanup added
+        let mut theirs = base.clone();
+        theirs[0].command = "/bin/backup --fast".to_string();
+        theirs[1].command = "/bin/report --short".to_string();
+        theirs[2].enabled = false;
+        theirs.push(entry("Cleanup", "0 5 * * *", "/bin/cleanup"));
+
+        let mut merge = merge(&base, &yours, &theirs);
+        let names: Vec<&str> = merge.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["Backup", "Report", "Sync", "Cleanup"]);
+        assert!(!merge.entries[2].enabled);
+        let conflicts: Vec<&str> = merge.conflicts.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(conflicts, ["Backup", "Report"]);
+        assert_eq!(merge.conflicts[1].changed_fields(), ["command"]);
+
+        // Merged takes their command and keeps your schedule
+        merge.resolve(0, Resolution::Merged);
+        assert_eq!(merge.entries[0].schedule, "0 3 * * *");
+        assert_eq!(merge.entries[0].command, "/bin/backup --fast");
+        merge.resolve_all(Resolution::Theirs);
+        assert_eq!(merge.entries[1].command, "/bin/report --short");
+        assert!(merge.conflicts.is_empty());
+
+        // An entry deleted here but changed there is a conflict
+        let mut changed = base.clone();
+        changed[3].enabled = false;
+        let mut merge = super::merge(&base, &base[..3], &changed);
+        assert_eq!(merge.conflicts[0].name, "Legacy");
+        merge.resolve(0, Resolution::Merged);
+        assert_eq!(merge.entries.last().map(|e| e.name.as_str()), Some("Legacy"));
+
+        assert!(Resolution::parse("mine").is_err());
+    }
+}
@@ -1,4 +1,12 @@
+use crate::schedule::{normalize, CronSchedule};
+use crate::shell::Shell;
+use anyhow::{bail, Result};
+use chrono::{Duration, NaiveDateTime};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Format of the `# SNOOZE:` metadata timestamp (local time)
+const SNOOZE_FORMAT: &str = "%Y-%m-%d %H:%M";
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct CronEntry {
@@ -6,8 +14,51 @@ pub struct CronEntry {
     pub schedule: String,  // Cron expression (e.g., "0 2 * * *")
     pub command: String,   // Command to execute
     pub enabled: bool,     // Whether this entry is active
+    #[serde(default)]
+    pub timezone: Option<String>,  // IANA timezone the schedule is evaluated in
+    #[serde(default)]
+    pub snoozed_until: Option<NaiveDateTime>,  // Re-enable automatically after this local time
+    #[serde(default)]
+    pub tags: Vec<String>,  // Labels for grouping entries in bulk operations
+    #[serde(default)]
+    pub command_template: Option<String>,  // Command with `!secret` placeholders, when resolved for saving
+    #[serde(default)]
+    pub skip_excluded: bool,  // Don't run on the dates in the exclusion calendar
+    #[serde(default)]
+    pub on_success: Option<String>,  // Hook run after the command exits with 0
+    #[serde(default)]
+    pub on_failure: Option<String>,  // Hook run after the command fails
+    #[serde(default)]
+    pub watchdog: bool,  // Alert when a scheduled run doesn't happen
+    #[serde(default)]
+    pub watchdog_for: Option<String>,  // Set on the generated entry that watches the named one
+    #[serde(default)]
+    pub env: Option<String>,  // Env preset from the config exported before the command
+    #[serde(default)]
+    pub shell: Option<Shell>,  // Interpreter the command is written for (POSIX sh when unset)
+    #[serde(default)]
+    pub stdin: Option<String>,  // Text fed to the command's standard input (after `%` in a crontab)
+    #[serde(default)]
+    pub comments: Vec<String>,  // Free-form comment lines written above the entry
+    #[serde(default)]
+    pub env_vars: Vec<(String, String)>,  // Variables set for this entry alone, after its env preset
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,  // Directory the command runs in (the home directory when unset)
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,  // Stop the command after running this long
+    #[serde(default)]
+    pub retries: u32,  // Times a failed run is tried again
+    #[serde(default)]
+    pub retry_delay_secs: u64,  // Wait before each retry
+    #[serde(default)]
+    pub ping_url: Option<String>,  // Monitoring check pinged when a run starts and ends
+    #[serde(default)]
+    pub user: Option<String>,  // User the job runs as, in the system crontab format (/etc/crontab)
 }
 
+/// Keys of the `# KEY: value` metadata comments written after `# NAME:`
+pub const METADATA_KEYS: &[&str] = &["TZ", "SNOOZE", "TAGS", "SKIP", "ON_SUCCESS", "ON_FAILURE", "WATCHDOG", "WATCHDOG_FOR", "ENV", "SHELL", "VARS", "CWD", "TIMEOUT", "RETRIES", "RETRY_DELAY", "PING", "COMMAND", "USER"];
+
 impl CronEntry {
     pub fn new(name: String, schedule: String, command: String) -> Self {
         Self {
@@ -15,23 +66,336 @@ impl CronEntry {
             schedule,
             command,
             enabled: true,
+            timezone: None,
+            snoozed_until: None,
+            tags: Vec::new(),
+            command_template: None,
+            skip_excluded: false,
+            on_success: None,
+            on_failure: None,
+            watchdog: false,
+            watchdog_for: None,
+            env: None,
+            shell: None,
+            stdin: None,
+            comments: Vec::new(),
+            env_vars: Vec::new(),
+            cwd: None,
+            timeout_secs: None,
+            retries: 0,
+            retry_delay_secs: 0,
+            ping_url: None,
+            user: None,
+        }
+    }
+
+    /// Metadata stored as `# KEY: value` comments between `# NAME:` and the job line
+    pub fn metadata(&self) -> Vec<(&'static str, String)> {
+        let mut metadata = Vec::new();
+        if let Some(tz) = &self.timezone {
+            metadata.push(("TZ", tz.clone()));
+        }
+        if let Some(until) = &self.snoozed_until {
+            metadata.push(("SNOOZE", until.format(SNOOZE_FORMAT).to_string()));
+        }
+        if !self.tags.is_empty() {
+            metadata.push(("TAGS", self.tags.join(", ")));
+        }
+        if self.skip_excluded {
+            metadata.push(("SKIP", "excluded-dates".to_string()));
+        }
+        if let Some(hook) = &self.on_success {
+            metadata.push(("ON_SUCCESS", hook.clone()));
+        }
+        if let Some(hook) = &self.on_failure {
+            metadata.push(("ON_FAILURE", hook.clone()));
+        }
+        if self.watchdog {
+            metadata.push(("WATCHDOG", "yes".to_string()));
+        }
+        if let Some(name) = &self.watchdog_for {
+            metadata.push(("WATCHDOG_FOR", name.clone()));
+        }
+        if let Some(preset) = &self.env {
+            metadata.push(("ENV", preset.clone()));
+        }
+        if let Some(shell) = self.shell {
+            metadata.push(("SHELL", shell.name().to_string()));
+        }
+        if !self.env_vars.is_empty() {
+            metadata.push(("VARS", format_vars(&self.env_vars)));
+        }
+        if let Some(cwd) = &self.cwd {
+            metadata.push(("CWD", cwd.display().to_string()));
+        }
+        if let Some(timeout) = self.timeout_secs {
+            metadata.push(("TIMEOUT", format_seconds(timeout)));
+        }
+        if self.retries > 0 {
+            metadata.push(("RETRIES", self.retries.to_string()));
+        }
+        if self.retry_delay_secs > 0 {
+            metadata.push(("RETRY_DELAY", format_seconds(self.retry_delay_secs)));
+        }
+        if let Some(url) = &self.ping_url {
+            metadata.push(("PING", url.clone()));
+        }
+        if let Some(template) = &self.command_template {
+            metadata.push(("COMMAND", template.clone()));
+        }
+        // Also in the job line; kept here so the user format reads it back
+        if let Some(user) = &self.user {
+            metadata.push(("USER", user.clone()));
+        }
+        metadata
+    }
+
+    /// Apply one metadata comment read back from a crontab
+    pub fn set_metadata(&mut self, key: &str, value: &str) {
+        match key {
+            "TZ" => self.timezone = Some(value.to_string()),
+            "SNOOZE" => self.snoozed_until = NaiveDateTime::parse_from_str(value, SNOOZE_FORMAT).ok(),
+            "TAGS" => self.tags = parse_tags(value),
+            "SKIP" => self.skip_excluded = value == "excluded-dates",
+            "ON_SUCCESS" => self.on_success = Some(value.to_string()),
+            "ON_FAILURE" => self.on_failure = Some(value.to_string()),
+            "WATCHDOG" => self.watchdog = value == "yes",
+            "WATCHDOG_FOR" => self.watchdog_for = Some(value.to_string()),
+            "ENV" => self.env = Some(value.to_string()),
+            "SHELL" => self.shell = Shell::parse(value).ok().flatten(),
+            "VARS" => self.env_vars = parse_vars(value),
+            "CWD" => self.cwd = Some(PathBuf::from(value)),
+            "TIMEOUT" => self.timeout_secs = parse_seconds(value).ok(),
+            "RETRIES" => self.retries = value.trim().parse().unwrap_or(0),
+            "RETRY_DELAY" => self.retry_delay_secs = parse_seconds(value).unwrap_or(0),
+            "PING" => self.ping_url = Some(value.to_string()),
+            "COMMAND" => self.command_template = Some(value.to_string()),
+            "USER" => self.user = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    /// Disable the entry until `until`, when `wake_if_due` re-enables it
+    pub fn snooze(&mut self, until: NaiveDateTime) {
+        self.enabled = false;
+        self.snoozed_until = Some(until);
+    }
+
+    /// Re-enable a snoozed entry once its snooze has expired; returns whether it woke
+    pub fn wake_if_due(&mut self, now: NaiveDateTime) -> bool {
+        match self.snoozed_until {
+            Some(until) if until <= now => {
+                self.enabled = true;
+                self.snoozed_until = None;
+                true
+            }
+            _ => false,
         }
     }
 
     pub fn validate_schedule(&self) -> bool {
-        cron::Schedule::from_str(&self.schedule).is_ok()
+        CronSchedule::parse(&self.schedule).is_ok()
+    }
+
+    /// Whether `other` runs the same command on an equivalent schedule
+    pub fn is_duplicate_of(&self, other: &CronEntry) -> bool {
+        self.command.trim() == other.command.trim()
+            && normalize::equivalent(&self.schedule, &other.schedule)
+    }
+
+    /// The file the command's standard output is redirected to (`> file`,
+    /// `>> file`), if any. `/dev/null` and redirections to descriptors don't count.
+    pub fn output_path(&self) -> Option<PathBuf> {
+        let mut words = self.command.split_whitespace();
+        let mut target = None;
+        while let Some(word) = words.next() {
+            let rest = match word.strip_prefix("1>").or_else(|| word.strip_prefix('>')) {
+                Some(rest) => rest.trim_start_matches('>'),
+                None => continue,
+            };
+            let file = if rest.is_empty() { words.next() } else { Some(rest) };
+            match file {
+                Some(file) if !file.starts_with('&') && file != "/dev/null" => target = Some(file),
+                _ => {}
+            }
+        }
+
+        let target = target?.trim_matches(|c| c == '"' || c == '\'');
+        Some(match target.strip_prefix("~/") {
+            Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+            None => PathBuf::from(target),
+        })
     }
 
     pub fn to_crontab_string(&self) -> String {
+        let mut header: String = self.comments.iter().map(|line| format!("{}\n", comment_line(line))).collect();
+        header.push_str(&format!("# NAME: {}", self.name));
+        for (key, value) in self.metadata() {
+            header.push_str(&format!("\n# {}: {}", key, value));
+        }
+
+        // The system format's user column sits between schedule and command
+        let mut command = match &self.user {
+            Some(user) => format!("{} {}", user, escape_percent(&self.command)),
+            None => escape_percent(&self.command),
+        };
+        if let Some(input) = &self.stdin {
+            command.push('%');
+            command.push_str(&escape_percent(input).replace('\n', "%"));
+        }
         if self.enabled {
-            format!("# NAME: {}\n{} {}", self.name, self.schedule, self.command)
+            format!("{}\n{} {}", header, self.schedule, command)
         } else {
-            format!("# NAME: {}\n# {} {}", self.name, self.schedule, self.command)
+            format!("{}\n# {} {}", header, self.schedule, command)
+        }
+    }
+}
+
+/// Escape `%` as `\%`, which cron would otherwise read as a newline
+fn escape_percent(text: &str) -> String {
+    text.replace('%', r"\%")
+}
+
+/// Split the command field of a crontab line at the first unescaped `%`:
+/// cron runs what comes before it and feeds the rest to the command's
+/// standard input, with further `%` read as newlines
+pub fn split_command_field(field: &str) -> (String, Option<String>) {
+    let mut command = String::new();
+    let mut stdin: Option<String> = None;
+    let mut chars = field.chars().peekable();
+    while let Some(c) = chars.next() {
+        let c = match c {
+            '\\' if chars.peek() == Some(&'%') => {
+                chars.next();
+                '%'
+            }
+            '%' if stdin.is_none() => {
+                stdin = Some(String::new());
+                continue;
+            }
+            '%' => '\n',
+            c => c,
+        };
+        stdin.as_mut().unwrap_or(&mut command).push(c);
+    }
+    (command, stdin)
+}
+
+/// A comment as written to the crontab: `# text`, or `#` for a blank line
+fn comment_line(text: &str) -> String {
+    if text.is_empty() {
+        "#".to_string()
+    } else {
+        format!("# {}", text)
+    }
+}
+
+/// Split a comma-separated tag list, dropping empty and repeated tags
+pub fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in input.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.to_string());
         }
     }
+    tags
 }
 
-use std::str::FromStr;
+/// Variables on one line as `NAME=value; NAME=value`, with `\\`, `;` and
+/// line breaks in values escaped
+pub fn format_vars(vars: &[(String, String)]) -> String {
+    vars.iter()
+        .map(|(name, value)| {
+            let value = value.replace('\\', "\\\\").replace(';', "\\;").replace('\n', "\\n");
+            format!("{}={}", name, value)
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Read back variables written by `format_vars`; parts without `=` are skipped
+pub fn parse_vars(input: &str) -> Vec<(String, String)> {
+    let mut parts = vec![String::new()];
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => parts.last_mut().unwrap().push('\n'),
+                Some(escaped) => parts.last_mut().unwrap().push(escaped),
+                None => {}
+            },
+            ';' => parts.push(String::new()),
+            c => parts.last_mut().unwrap().push(c),
+        }
+    }
+    parts
+        .iter()
+        .filter_map(|part| part.trim_start().split_once('='))
+        .map(|(name, value)| (name.trim().to_string(), value.to_string()))
+        .collect()
+}
+
+/// Parse a length of time such as `30m`, `8h`, `2d` or `1w` (snoozes,
+/// `history export --since`)
+pub fn parse_duration(input: &str) -> Result<Duration> {
+    let input = input.trim();
+    let split = input.find(|c: char| !c.is_ascii_digit()).unwrap_or(input.len());
+    let (amount, unit) = input.split_at(split);
+    let amount: i64 = match amount.parse() {
+        Ok(amount) if amount > 0 => amount,
+        _ => bail!("Invalid length '{}' (e.g. 8h, 2d)", input),
+    };
+    Ok(match unit.trim() {
+        "m" | "min" | "minutes" => Duration::minutes(amount),
+        "h" | "" | "hours" => Duration::hours(amount),
+        "d" | "days" => Duration::days(amount),
+        "w" | "weeks" => Duration::weeks(amount),
+        _ => bail!("Unknown unit in '{}' (use m, h, d or w)", input),
+    })
+}
+
+/// Parse a length in seconds such as `90`, `90s`, `5m` or `2h` (timeouts,
+/// retry delays)
+pub fn parse_seconds(input: &str) -> Result<u64> {
+    let input = input.trim();
+    let split = input.find(|c: char| !c.is_ascii_digit()).unwrap_or(input.len());
+    let (amount, unit) = input.split_at(split);
+    let amount: u64 = match amount.parse() {
+        Ok(amount) if amount > 0 => amount,
+        _ => bail!("Invalid length '{}' (e.g. 90s, 5m)", input),
+    };
+    let unit = match unit.trim() {
+        "s" | "" | "sec" | "seconds" => 1,
+        "m" | "min" | "minutes" => 60,
+        "h" | "hours" => 3600,
+        _ => bail!("Unknown unit in '{}' (use s, m or h)", input),
+    };
+    Ok(amount * unit)
+}
+
+/// Parse retries after a failure and the wait before each, as `3` or `3, 30s`;
+/// empty for none
+pub fn parse_retries(input: &str) -> Result<(u32, u64)> {
+    let (count, delay) = input.split_once(',').unwrap_or((input, ""));
+    let retries = match count.trim() {
+        "" => 0,
+        count => count.parse().map_err(|_| anyhow::anyhow!("Invalid number of retries '{}'", count))?,
+    };
+    let delay = match delay.trim() {
+        "" => 0,
+        delay => parse_seconds(delay)?,
+    };
+    Ok((retries, delay))
+}
+
+/// A length in seconds in the largest unit that divides it: `90s`, `5m`, `2h`
+pub fn format_seconds(seconds: u64) -> String {
+    match seconds {
+        s if s > 0 && s % 3600 == 0 => format!("{}h", s / 3600),
+        s if s > 0 && s % 60 == 0 => format!("{}m", s / 60),
+        s => format!("{}s", s),
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -65,4 +429,113 @@ mod tests {
         );
         assert!(!invalid.validate_schedule());
     }
+
+    #[test]
+    fn test_output_path() {
+        let with_command = |command: &str| {
+            CronEntry::new("Job".to_string(), "0 2 * * *".to_string(), command.to_string()).output_path()
+        };
+        assert_eq!(with_command("/bin/job >> /var/log/job.log 2>&1"), Some(PathBuf::from("/var/log/job.log")));
+        assert_eq!(with_command("/bin/job >/tmp/out.log"), Some(PathBuf::from("/tmp/out.log")));
+        assert_eq!(with_command("/bin/job > /dev/null 2> /tmp/err.log"), None);
+        assert_eq!(with_command("/bin/job"), None);
+    }
+
+    #[test]
+    fn test_snooze_and_wake() {
+        let at = |s: &str| NaiveDateTime::parse_from_str(s, SNOOZE_FORMAT).unwrap();
+        let mut entry = CronEntry::new("Nightly".to_string(), "0 2 * * *".to_string(), "/bin/job".to_string());
+
+        entry.snooze(at("2024-01-02 09:00"));
+        assert!(!entry.enabled);
+        assert_eq!(entry.metadata(), vec![("SNOOZE", "2024-01-02 09:00".to_string())]);
+
+        assert!(!entry.wake_if_due(at("2024-01-02 08:59")));
+        assert!(entry.wake_if_due(at("2024-01-02 09:00")));
+        assert!(entry.enabled);
+        assert_eq!(entry.snoozed_until, None);
+
+        assert_eq!(parse_duration("8h").unwrap(), Duration::hours(8));
+        assert_eq!(parse_duration("2d").unwrap(), Duration::days(2));
+        assert!(parse_duration("0h").is_err());
+        assert!(parse_duration("3y").is_err());
+    }
+
+    #[test]
+    fn test_tags_metadata() {
+        let mut entry = CronEntry::new("Backup".to_string(), "0 2 * * *".to_string(), "/bin/backup".to_string());
+        entry.set_metadata("TAGS", "backups, nightly,,Backups ");
+        assert_eq!(entry.tags, ["backups", "nightly"]);
+        assert_eq!(entry.metadata(), vec![("TAGS", "backups, nightly".to_string())]);
+    }
+
+    #[test]
+    fn test_skip_metadata() {
+        let mut entry = CronEntry::new("Report".to_string(), "0 9 * * 1-5".to_string(), "/bin/report".to_string());
+        entry.set_metadata("SKIP", "excluded-dates");
+        assert!(entry.skip_excluded);
+        assert_eq!(entry.metadata(), vec![("SKIP", "excluded-dates".to_string())]);
+    }
+
+    #[test]
+    fn test_hook_metadata() {
+        let mut entry = CronEntry::new("Build".to_string(), "0 1 * * *".to_string(), "make".to_string());
+        entry.set_metadata("ON_SUCCESS", "curl -X POST https://deploy.example.com/hook");
+        entry.set_metadata("ON_FAILURE", "notify-send \"build failed: $CRON_MANAGER_EXIT_CODE\"");
+        assert_eq!(entry.metadata().len(), 2);
+        assert_eq!(entry.metadata()[0].0, "ON_SUCCESS");
+        assert_eq!(entry.on_failure.as_deref(), Some("notify-send \"build failed: $CRON_MANAGER_EXIT_CODE\""));
+    }
+
+    #[test]
+    fn test_vars_and_cwd_metadata() {
+        let mut entry = CronEntry::new("Deploy".to_string(), "0 3 * * *".to_string(), "./deploy.sh".to_string());
+        entry.env_vars = vec![
+            ("RAILS_ENV".to_string(), "production".to_string()),
+            ("GREETING".to_string(), "a;b \\ c\nd".to_string()),
+        ];
+        entry.cwd = Some(PathBuf::from("/srv/app"));
+        let metadata = entry.metadata();
+        assert_eq!(metadata[0], ("VARS", "RAILS_ENV=production; GREETING=a\\;b \\\\ c\\nd".to_string()));
+
+        let mut read = CronEntry::new("Deploy".to_string(), "0 3 * * *".to_string(), "./deploy.sh".to_string());
+        for (key, value) in &metadata {
+            read.set_metadata(key, value);
+        }
+        assert_eq!(read, entry);
+    }
+
+    #[test]
+    fn test_timeout_and_retry_metadata() {
+        let mut entry = CronEntry::new("Sync".to_string(), "*/10 * * * *".to_string(), "rsync -a src dst".to_string());
+        entry.timeout_secs = Some(300);
+        entry.retries = 3;
+        entry.retry_delay_secs = 90;
+        let metadata = entry.metadata();
+        assert_eq!(metadata, [("TIMEOUT", "5m".to_string()), ("RETRIES", "3".to_string()), ("RETRY_DELAY", "90s".to_string())]);
+
+        let mut read = CronEntry::new("Sync".to_string(), "*/10 * * * *".to_string(), "rsync -a src dst".to_string());
+        for (key, value) in &metadata {
+            read.set_metadata(key, value);
+        }
+        assert_eq!(read, entry);
+        assert_eq!(parse_seconds("2h").unwrap(), 7200);
+        assert!(parse_seconds("0").is_err());
+        assert!(parse_seconds("5d").is_err());
+    }
+
+    #[test]
+    fn test_is_duplicate_of() {
+        let entry = CronEntry::new(
+            "Weekly".to_string(),
+            "0 0 * * 7".to_string(),
+            "/bin/report".to_string(),
+        );
+        let same = CronEntry::new(
+            "Weekly report".to_string(),
+            "0 0 * * 0".to_string(),
+            "/bin/report".to_string(),
+        );
+        assert!(entry.is_duplicate_of(&same));
+    }
 }
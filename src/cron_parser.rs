@@ -1,30 +1,104 @@
-use crate::cron_entry::CronEntry;
+use crate::cron_entry::{self, CronEntry, METADATA_KEYS};
+use crate::schedule::CronSchedule;
 use anyhow::Result;
 
 pub struct CronParser;
 
+/// Lines around the entries cron-manager owns in a system crontab; the rest
+/// of the crontab belongs to whoever wrote it
+pub const BLOCK_BEGIN: &str = "# BEGIN cron-manager";
+pub const BLOCK_END: &str = "# END cron-manager";
+
+/// A line of a crontab as far as saving is concerned: either one kept as
+/// written (environment variables, blank lines, comments of no entry) or
+/// the place of an entry, by its index among the parsed entries
+#[derive(Debug, Clone, PartialEq)]
+enum Line<'a> {
+    Raw(&'a str),
+    Entry(usize),
+}
+
 impl CronParser {
     pub fn parse(content: &str) -> Result<Vec<CronEntry>> {
+        Ok(Self::parse_layout(content, false).0)
+    }
+
+    /// Parse the system crontab format (`/etc/crontab`, `/etc/cron.d/*`),
+    /// where a user column follows the schedule. Lines without one are kept
+    /// as written rather than read as jobs.
+    pub fn parse_system(content: &str) -> Result<Vec<CronEntry>> {
+        Ok(Self::parse_layout(content, true).0)
+    }
+
+    /// The entries inside the cron-manager block, or, in a crontab that
+    /// doesn't have one yet, those with a `# NAME:` line; jobs without one
+    /// belong to whoever wrote them
+    pub fn parse_block(content: &str) -> Result<Vec<CronEntry>> {
+        match Self::split_block(content) {
+            Some((_, block, _)) => Self::parse(&block.join("\n")),
+            None => {
+                let lines: Vec<&str> = content.lines().collect();
+                let (entries, _, spans) = Self::parse_layout(content, false);
+                Ok(entries
+                    .into_iter()
+                    .zip(spans)
+                    .filter(|(_, span)| Self::is_named(&lines, *span))
+                    .map(|(entry, _)| entry)
+                    .collect())
+            }
+        }
+    }
+
+    /// Whether the entry spanning these lines has a `# NAME:` line, i.e. was
+    /// written by cron-manager
+    fn is_named(lines: &[&str], (first, last): (usize, usize)) -> bool {
+        lines[first..=last].iter().any(|line| line.trim().starts_with("# NAME:"))
+    }
+
+    /// The lines before, inside and after the cron-manager block; a missing
+    /// end marker leaves the block open to the end
+    fn split_block(content: &str) -> Option<(Vec<&str>, Vec<&str>, Vec<&str>)> {
+        let lines: Vec<&str> = content.lines().collect();
+        let begin = lines.iter().position(|line| line.trim() == BLOCK_BEGIN)?;
+        let end = lines[begin..]
+            .iter()
+            .position(|line| line.trim() == BLOCK_END)
+            .map_or(lines.len(), |offset| begin + offset);
+        let after = lines.get(end + 1..).unwrap_or_default().to_vec();
+        Some((lines[..begin].to_vec(), lines[begin + 1..end].to_vec(), after))
+    }
+
+    /// The entries of a crontab, its lines with entries marked by place, and
+    /// the first and last line of each entry (its comments included)
+    fn parse_layout(content: &str, system: bool) -> (Vec<CronEntry>, Vec<Line<'_>>, Vec<(usize, usize)>) {
         let mut entries = Vec::new();
+        let mut layout = Vec::new();
+        let mut spans = Vec::new();
         let lines: Vec<&str> = content.lines().collect();
         let mut i = 0;
+        // Comment lines directly above the next entry, as written and as text
+        let mut comments: Vec<(&str, String)> = Vec::new();
 
         while i < lines.len() {
             let line = lines[i].trim();
+            let start = i;
 
-            // Skip empty lines
-            if line.is_empty() {
-                i += 1;
-                continue;
-            }
-
-            // Check if this is a NAME comment
-            if line.starts_with("# NAME:") {
+            let entry = if line.is_empty() {
+                // Comments above an empty line belong to no entry
+                None
+            } else if line.starts_with("# NAME:") {
                 let name = line.strip_prefix("# NAME:").unwrap().trim().to_string();
                 i += 1;
 
-                if i < lines.len() {
-                    let next_line = lines[i].trim();
+                // Collect `# KEY: value` metadata lines before the job line
+                let mut metadata = Vec::new();
+                while let Some(kv) = lines.get(i).and_then(|l| Self::parse_metadata_line(l.trim())) {
+                    metadata.push(kv);
+                    i += 1;
+                }
+
+                lines.get(i).and_then(|next_line| {
+                    let next_line = next_line.trim();
 
                     // Check if the entry is commented out (disabled)
                     let (enabled, cron_line) = if next_line.starts_with("# ") && !next_line.starts_with("# NAME:") {
@@ -34,37 +108,147 @@ impl CronParser {
                     };
 
                     // Parse the cron line
-                    if let Some((schedule, command)) = Self::parse_cron_line(cron_line) {
-                        let mut entry = CronEntry::new(name, schedule, command);
-                        entry.enabled = enabled;
-                        entries.push(entry);
+                    let (schedule, command) = Self::parse_cron_line(cron_line)?;
+                    let mut entry = Self::new_entry(name, schedule, &command, system)?;
+                    entry.enabled = enabled;
+                    for (key, value) in metadata {
+                        // The user column wins in the system format
+                        if !(system && key == "USER") {
+                            entry.set_metadata(key, value);
+                        }
                     }
+                    // Read in the user format, a system-format job line has
+                    // the user in front of the command
+                    if let (false, Some(user)) = (system, &entry.user) {
+                        if let Some(rest) = entry.command.strip_prefix(user.as_str()).filter(|rest| rest.starts_with(char::is_whitespace)) {
+                            entry.command = rest.trim_start().to_string();
+                        }
+                    }
+                    Some(entry)
+                })
+            } else if !line.starts_with('#') {
+                // Regular cron line without a name; anything else, such as
+                // `MAILTO=ops`, is kept as written
+                Self::parse_cron_line(line).and_then(|(schedule, command)| {
+                    Self::new_entry(format!("Unnamed ({})", entries.len() + 1), schedule, &command, system)
+                })
+            } else if let Some(mut entry) = Self::parse_commented_job(line).and_then(|(schedule, command)| {
+                Self::new_entry(format!("Unnamed ({})", entries.len() + 1), schedule, &command, system)
+            }) {
+                // Commented-out job without a name: keep it as a disabled entry
+                entry.enabled = false;
+                Some(entry)
+            } else {
+                let text = line.trim_start_matches('#');
+                comments.push((lines[i], text.strip_prefix(' ').unwrap_or(text).to_string()));
+                i += 1;
+                continue;
+            };
+
+            match entry {
+                Some(mut entry) => {
+                    spans.push((start - comments.len(), i));
+                    entry.comments = comments.drain(..).map(|(_, text)| text).collect();
+                    layout.push(Line::Entry(entries.len()));
+                    entries.push(entry);
                 }
-            } else if !line.starts_with("#") {
-                // Regular cron line without a name
-                if let Some((schedule, command)) = Self::parse_cron_line(line) {
-                    let name = format!("Unnamed ({})", entries.len() + 1);
-                    entries.push(CronEntry::new(name, schedule, command));
+                None => {
+                    // Comments above a broken entry don't move on to the next
+                    layout.extend(comments.drain(..).map(|(raw, _)| Line::Raw(raw)));
+                    layout.extend(lines[start..(i + 1).min(lines.len())].iter().map(|raw| Line::Raw(raw)));
                 }
             }
-
             i += 1;
         }
+        layout.extend(comments.drain(..).map(|(raw, _)| Line::Raw(raw)));
 
-        Ok(entries)
+        (entries, layout, spans)
     }
 
-    fn parse_cron_line(line: &str) -> Option<(String, String)> {
-        let parts: Vec<&str> = line.splitn(6, ' ').collect();
+    /// The environment line, like `MAILTO=""`, between the entries named
+    /// `first` and `second`: swapping them would run each with the other's
+    /// settings
+    pub fn environment_between(content: &str, first: &str, second: &str) -> Option<String> {
+        let (entries, layout, _) = Self::parse_layout(content, false);
+        let position = |name: &str| layout.iter().position(|line| matches!(line, Line::Entry(i) if entries[*i].name == name));
+        let (a, b) = (position(first)?, position(second)?);
+        layout[a.min(b)..a.max(b)].iter().find_map(|line| match line {
+            Line::Raw(raw) if Self::is_assignment(raw) => Some(raw.trim().to_string()),
+            _ => None,
+        })
+    }
+
+    /// Whether the line sets a variable, such as `SHELL=/bin/bash`
+    fn is_assignment(line: &str) -> bool {
+        line.split_once('=').is_some_and(|(name, _)| {
+            let name = name.trim();
+            !name.is_empty()
+                && !name.starts_with(|c: char| c.is_ascii_digit())
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+    }
+
+    /// Whether a comment line would read back as something else, i.e. a
+    /// `# NAME:` line, a commented-out job or a block marker
+    pub fn is_ambiguous_comment(text: &str) -> bool {
+        let line = format!("# {}", text);
+        line.starts_with("# NAME:")
+            || [BLOCK_BEGIN, BLOCK_END].contains(&line.trim())
+            || Self::parse_commented_job(&line).is_some()
+    }
+
+    fn parse_metadata_line(line: &str) -> Option<(&str, &str)> {
+        let (key, value) = line.strip_prefix("# ")?.split_once(':')?;
+        METADATA_KEYS
+            .contains(&key)
+            .then(|| (key, value.trim()))
+    }
+
+    /// A comment that holds a job line (`# 0 2 * * * cmd` or `#0 2 * * * cmd`).
+    /// Only comments whose first five fields form a valid schedule count, so
+    /// prose and header comments like `# m h dom mon dow command` are skipped.
+    fn parse_commented_job(line: &str) -> Option<(String, String)> {
+        let (schedule, command) = Self::parse_cron_line(line.strip_prefix('#')?.trim_start())?;
+        CronSchedule::parse(&schedule).ok()?;
+        Some((schedule, command))
+    }
 
-        if parts.len() >= 6 {
-            // Standard cron format: minute hour day month weekday command
-            let schedule = parts[0..5].join(" ");
-            let command = parts[5..].join(" ");
-            Some((schedule, command))
+    /// An entry from a job line, with `%` escapes and stdin text taken
+    /// apart; in the system format the field starts with the user, and a
+    /// line without a command after it isn't a job
+    fn new_entry(name: String, schedule: String, field: &str, system: bool) -> Option<CronEntry> {
+        let (user, field) = if system {
+            let (user, command) = field.trim_start().split_once(char::is_whitespace)?;
+            (Some(user.to_string()), command.trim_start())
         } else {
-            None
+            (None, field)
+        };
+        let (command, stdin) = cron_entry::split_command_field(field);
+        let mut entry = CronEntry::new(name, schedule, command);
+        entry.stdin = stdin;
+        entry.user = user;
+        Some(entry)
+    }
+
+    fn parse_cron_line(line: &str) -> Option<(String, String)> {
+        // Nickname format: @daily command
+        if line.starts_with('@') {
+            let (schedule, command) = line.split_once(char::is_whitespace)?;
+            CronSchedule::parse(schedule).ok()?;
+            return Some((schedule.to_string(), command.trim_start().to_string()));
         }
+
+        // Standard cron format: minute hour day month weekday command, the
+        // fields separated by spaces or tabs (as in /etc/crontab)
+        let mut rest = line;
+        let mut fields = Vec::with_capacity(5);
+        for _ in 0..5 {
+            let (field, after) = rest.trim_start().split_once(char::is_whitespace)?;
+            fields.push(field);
+            rest = after;
+        }
+        let command = rest.trim_start();
+        (!command.is_empty()).then(|| (fields.join(" "), command.to_string()))
     }
 
     pub fn serialize(entries: &[CronEntry]) -> String {
@@ -77,6 +261,139 @@ impl CronParser {
 
         output
     }
+
+    /// Serialize entries over an existing crontab, keeping its other lines
+    /// (`SHELL=`, `PATH=`, `MAILTO=`, blank lines, loose comments) exactly as
+    /// written. The entry lines stay where entries stood and are filled in
+    /// the order of `entries`, so entries moved in the list move in the file
+    /// too: an entry kept, or renamed with the same schedule and command,
+    /// takes a slot, and entries that weren't there before are added at the end.
+    pub fn serialize_into(existing: &str, entries: &[CronEntry]) -> String {
+        Self::serialize_layout(existing, entries, false)
+    }
+
+    /// `serialize_into` for a crontab in the system format, whose job lines
+    /// carry the user column (see `parse_system`)
+    pub fn serialize_system_into(existing: &str, entries: &[CronEntry]) -> String {
+        Self::serialize_layout(existing, entries, true)
+    }
+
+    /// `content` less what a damaged file can't be trusted with: entries
+    /// that don't read back, and lines that aren't text. The entries that
+    /// do read, and the environment and comment lines, stay where they were
+    /// so `serialize_into` can save over the file without losing them.
+    pub fn without_damage(content: &str) -> String {
+        let (_, layout, spans) = Self::parse_layout(content, false);
+        let lines: Vec<&str> = content.lines().collect();
+        let mut output = String::new();
+        for line in &layout {
+            let kept = match line {
+                Line::Entry(index) => &lines[spans[*index].0..=spans[*index].1],
+                Line::Raw(raw) if Self::is_readable(raw) => std::slice::from_ref(raw),
+                Line::Raw(_) => continue,
+            };
+            for line in kept {
+                output.push_str(line);
+                output.push('\n');
+            }
+        }
+        output
+    }
+
+    /// Whether a line that isn't part of an entry is a blank, environment
+    /// or comment line, rather than what is left of a broken entry
+    fn is_readable(raw: &str) -> bool {
+        let line = raw.trim();
+        let comment = line.starts_with('#') && !line.starts_with("# NAME:") && Self::parse_metadata_line(line).is_none();
+        !raw.contains(['\0', char::REPLACEMENT_CHARACTER]) && (line.is_empty() || comment || Self::is_assignment(line))
+    }
+
+    fn serialize_layout(existing: &str, entries: &[CronEntry], system: bool) -> String {
+        let (old, layout, _) = Self::parse_layout(existing, system);
+        let mut placed = vec![false; entries.len()];
+        let mut take = |matches: &dyn Fn(&CronEntry) -> bool| {
+            let index = (0..entries.len()).find(|&i| !placed[i] && matches(&entries[i]))?;
+            placed[index] = true;
+            Some(index)
+        };
+
+        let mut slots: Vec<Option<usize>> = layout
+            .iter()
+            .map(|line| match line {
+                Line::Entry(index) => take(&|entry: &CronEntry| entry.name == old[*index].name),
+                Line::Raw(_) => None,
+            })
+            .collect();
+        for (slot, line) in slots.iter_mut().zip(&layout) {
+            if let (None, Line::Entry(index)) = (&slot, line) {
+                let was = &old[*index];
+                *slot = take(&|entry: &CronEntry| entry.schedule == was.schedule && entry.command == was.command);
+            }
+        }
+        // Fill the slots in list order
+        let mut kept: Vec<usize> = slots.iter().flatten().copied().collect();
+        kept.sort_unstable();
+        for (slot, index) in slots.iter_mut().flatten().zip(kept) {
+            *slot = index;
+        }
+
+        let mut output = String::new();
+        for (slot, line) in slots.iter().zip(&layout) {
+            match (slot, line) {
+                (_, Line::Raw(raw)) => output.push_str(raw),
+                (Some(index), Line::Entry(_)) => output.push_str(&entries[*index].to_crontab_string()),
+                // Deleted entries leave no line behind
+                (None, Line::Entry(_)) => continue,
+            }
+            output.push('\n');
+        }
+        for (entry, _) in entries.iter().zip(&placed).filter(|(_, placed)| !**placed) {
+            output.push_str(&entry.to_crontab_string());
+            output.push('\n');
+        }
+
+        output
+    }
+
+    /// Serialize entries into the cron-manager block of a system crontab,
+    /// leaving every line outside it as another tool or the user wrote it.
+    /// A crontab without a block gets one around the lines from its first
+    /// `# NAME:` entry to its last, so they keep their order and environment
+    /// lines; jobs without a name stay outside, and those between named
+    /// entries follow the block.
+    pub fn serialize_block(existing: &str, entries: &[CronEntry]) -> String {
+        let (before, block, after) = match Self::split_block(existing) {
+            Some(parts) => parts,
+            None => {
+                let lines: Vec<&str> = existing.lines().collect();
+                let (_, _, spans) = Self::parse_layout(existing, false);
+                let (named, foreign): (Vec<_>, Vec<_>) = spans.into_iter().partition(|span| Self::is_named(&lines, *span));
+                match (named.first(), named.last()) {
+                    (Some(&(first, _)), Some(&(_, last))) => {
+                        let inside: Vec<(usize, usize)> =
+                            foreign.into_iter().filter(|(start, end)| *start > first && *end < last).collect();
+                        let moved = |i: &usize| inside.iter().any(|(start, end)| (start..=end).contains(&i));
+                        let block = (first..=last).filter(|i| !moved(i)).map(|i| lines[i]).collect();
+                        let after = (first..=last).filter(moved).chain(last + 1..lines.len()).map(|i| lines[i]).collect();
+                        (lines[..first].to_vec(), block, after)
+                    }
+                    // Nothing of cron-manager's yet, so nothing to change until there are entries
+                    _ if entries.is_empty() => return existing.to_string(),
+                    _ => (lines, Vec::new(), Vec::new()),
+                }
+            }
+        };
+
+        let block = if block.is_empty() { String::new() } else { format!("{}\n", block.join("\n")) };
+        let mut output: String = before.iter().map(|line| format!("{}\n", line)).collect();
+        output.push_str(BLOCK_BEGIN);
+        output.push('\n');
+        output.push_str(&Self::serialize_into(&block, entries));
+        output.push_str(BLOCK_END);
+        output.push('\n');
+        output.extend(after.iter().map(|line| format!("{}\n", line)));
+        output
+    }
 }
 
 #[cfg(test)]
@@ -111,6 +428,190 @@ mod tests {
         assert!(!entries[0].enabled);
     }
 
+    #[test]
+    fn test_parse_commented_unnamed_jobs() {
+        let content = r#"# Edit this file to introduce tasks to be run by cron.
+# m h  dom mon dow   command
+#30 4 * * 1 /bin/weekly.sh
+# 0 2 * * * /bin/nightly.sh
+0 * * * * /bin/hourly.sh
+"#;
+
+        let entries = CronParser::parse(content).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].name, "Unnamed (1)");
+        assert_eq!(entries[0].schedule, "30 4 * * 1");
+        assert_eq!(entries[0].comments, ["Edit this file to introduce tasks to be run by cron.", "m h  dom mon dow   command"]);
+        assert!(entries[1].comments.is_empty());
+        assert!(!entries[0].enabled);
+        assert_eq!(entries[1].command, "/bin/nightly.sh");
+        assert!(!entries[1].enabled);
+        assert!(entries[2].enabled);
+    }
+
+    #[test]
+    fn test_metadata_round_trip() {
+        let mut entry = CronEntry::new(
+            "Tokyo Report".to_string(),
+            "0 9 * * *".to_string(),
+            "/bin/report".to_string(),
+        );
+        entry.timezone = Some("Asia/Tokyo".to_string());
+        entry.enabled = false;
+
+        let output = CronParser::serialize(&[entry.clone()]);
+        assert!(output.contains("# TZ: Asia/Tokyo"));
+
+        let entries = CronParser::parse(&output).unwrap();
+        assert_eq!(entries, vec![entry]);
+    }
+
+    #[test]
+    fn test_comments_round_trip() {
+        let content = "# Ask ops before changing: the backup window is agreed with the DBAs\n#\n# See TICKET-42\n# NAME: Backup\n# TAGS: db\n0 2 * * * /bin/backup\n\n# Stray note\n\n# NAME: Sync\n0 * * * * /bin/sync\n";
+        let entries = CronParser::parse(content).unwrap();
+        assert_eq!(
+            entries[0].comments,
+            ["Ask ops before changing: the backup window is agreed with the DBAs", "", "See TICKET-42"]
+        );
+        // A comment separated by a blank line belongs to no entry
+        assert!(entries[1].comments.is_empty());
+
+        let output = CronParser::serialize(&entries);
+        assert!(output.starts_with("# Ask ops before changing"));
+        assert_eq!(CronParser::parse(&output).unwrap(), entries);
+
+        assert!(CronParser::is_ambiguous_comment("NAME: Other"));
+        assert!(CronParser::is_ambiguous_comment("0 2 * * * /bin/old"));
+        assert!(!CronParser::is_ambiguous_comment("Runs after the 2am backup"));
+    }
+
+    #[test]
+    fn test_serialize_keeps_other_lines() {
+        let content = "SHELL=/bin/bash\nPATH=/usr/local/bin:/usr/bin:/bin\n\n# Jobs below mail the DBAs\nMAILTO=dba@example.com\n# NAME: Backup\n0 2 * * * /bin/backup\n\n# Stray note\n\nMAILTO=\"\"\n# NAME: Sync\n0 * * * * /bin/sync\n";
+        let mut entries = CronParser::parse(content).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].comments.is_empty());
+        assert_eq!(CronParser::serialize_into(content, &entries), content);
+
+        // Renamed entries keep their place, deleted ones leave, new ones go last
+        entries[1].name = "Sync all".to_string();
+        entries.remove(0);
+        entries.push(CronEntry::new("New".to_string(), "5 * * * *".to_string(), "/bin/new".to_string()));
+        assert_eq!(
+            CronParser::serialize_into(content, &entries),
+            "SHELL=/bin/bash\nPATH=/usr/local/bin:/usr/bin:/bin\n\n# Jobs below mail the DBAs\nMAILTO=dba@example.com\n\n# Stray note\n\nMAILTO=\"\"\n# NAME: Sync all\n0 * * * * /bin/sync\n# NAME: New\n5 * * * * /bin/new\n"
+        );
+
+        // Entries with a variable set between them don't trade places
+        assert_eq!(CronParser::environment_between(content, "Sync", "Backup").as_deref(), Some("MAILTO=\"\""));
+        assert_eq!(CronParser::environment_between(content, "Backup", "New"), None);
+
+        // Moved entries swap lines; the lines between them stay
+        let content = "MAILTO=ops\n# NAME: Backup\n0 2 * * * /bin/backup\n\n# NAME: Sync\n0 * * * * /bin/sync\n";
+        assert_eq!(CronParser::environment_between(content, "Backup", "Sync"), None);
+        let mut entries = CronParser::parse(content).unwrap();
+        entries.swap(0, 1);
+        assert_eq!(
+            CronParser::serialize_into(content, &entries),
+            "MAILTO=ops\n# NAME: Sync\n0 * * * * /bin/sync\n\n# NAME: Backup\n0 2 * * * /bin/backup\n"
+        );
+    }
+
+    #[test]
+    fn test_block_leaves_other_lines_alone() {
+        let content = "MAILTO=ops@example.com\n# NAME: Backup\n0 2 * * * /bin/backup\nMAILTO=\"\"\n0 * * * * /usr/bin/certbot renew\n# added by puppet\n*/5 * * * * /opt/puppet/run\n";
+        let mut entries = CronParser::parse_block(content).unwrap();
+        assert_eq!(entries.len(), 1);
+
+        // The first save adopts only the named entries into a block; nothing moves
+        let saved = CronParser::serialize_block(content, &entries);
+        assert_eq!(
+            saved,
+            format!(
+                "MAILTO=ops@example.com\n{}\n# NAME: Backup\n0 2 * * * /bin/backup\n{}\nMAILTO=\"\"\n0 * * * * /usr/bin/certbot renew\n# added by puppet\n*/5 * * * * /opt/puppet/run\n",
+                BLOCK_BEGIN, BLOCK_END
+            )
+        );
+        assert_eq!(CronParser::parse_block(&saved).unwrap(), entries);
+
+        // Lines another tool adds outside the block are neither read nor rewritten
+        let edited = format!("{}# from ansible\n30 1 * * * /usr/local/bin/rotate\n", saved);
+        assert_eq!(CronParser::parse_block(&edited).unwrap().len(), 1);
+        entries[0].name = "Nightly backup".to_string();
+        entries.push(CronEntry::new("Sync".to_string(), "0 * * * *".to_string(), "/bin/sync".to_string()));
+        let resaved = CronParser::serialize_block(&edited, &entries);
+        assert!(resaved.starts_with(&format!(
+            "MAILTO=ops@example.com\n{}\n# NAME: Nightly backup\n0 2 * * * /bin/backup\n# NAME: Sync\n0 * * * * /bin/sync\n{}\nMAILTO=\"\"\n",
+            BLOCK_BEGIN, BLOCK_END
+        )));
+        assert!(resaved.ends_with("*/5 * * * * /opt/puppet/run\n# from ansible\n30 1 * * * /usr/local/bin/rotate\n"));
+
+        // Unnamed jobs between named entries follow the block
+        let mixed = "# NAME: A\n0 1 * * * /bin/a\n# from puppet\n0 * * * * /opt/puppet/run\n# NAME: B\n0 2 * * * /bin/b\n";
+        let entries = CronParser::parse_block(mixed).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            CronParser::serialize_block(mixed, &entries),
+            format!("{}\n# NAME: A\n0 1 * * * /bin/a\n# NAME: B\n0 2 * * * /bin/b\n{}\n# from puppet\n0 * * * * /opt/puppet/run\n", BLOCK_BEGIN, BLOCK_END)
+        );
+
+        // A crontab without entries of its own is left as it is until there are some
+        assert_eq!(CronParser::serialize_block("MAILTO=ops\n", &[]), "MAILTO=ops\n");
+        assert_eq!(CronParser::serialize_block("0 * * * * /usr/bin/certbot renew\n", &[]), "0 * * * * /usr/bin/certbot renew\n");
+    }
+
+    #[test]
+    fn test_nicknames() {
+        let content = "# NAME: Boot\n@reboot /bin/warm-cache\n# @daily /bin/old-report\n@weekly  /bin/rotate\n@someday /bin/never\n";
+        let entries = CronParser::parse(content).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!((entries[0].schedule.as_str(), entries[0].command.as_str()), ("@reboot", "/bin/warm-cache"));
+        assert!(!entries[1].enabled);
+        assert_eq!((entries[2].schedule.as_str(), entries[2].command.as_str()), ("@weekly", "/bin/rotate"));
+        assert!(CronParser::serialize(&entries).contains("\n@reboot /bin/warm-cache\n"));
+        assert_eq!(CronParser::parse(&CronParser::serialize(&entries)).unwrap(), entries);
+    }
+
+    #[test]
+    fn test_percent_and_stdin() {
+        let content = "# NAME: Dump\n0 2 * * * pg_dump > /backup/db-$(date +\\%F).sql\n0 9 * * 1 mail -s Report ops%Weekly report%sent at 9:00 (100\\% done)\n";
+        let entries = CronParser::parse(content).unwrap();
+        assert_eq!(entries[0].command, "pg_dump > /backup/db-$(date +%F).sql");
+        assert_eq!(entries[0].stdin, None);
+        assert_eq!(entries[1].command, "mail -s Report ops");
+        assert_eq!(entries[1].stdin.as_deref(), Some("Weekly report\nsent at 9:00 (100% done)"));
+
+        let output = CronParser::serialize(&entries);
+        assert!(output.contains("date +\\%F"));
+        assert!(output.contains("ops%Weekly report%sent at 9:00 (100\\% done)"));
+        assert_eq!(CronParser::parse(&output).unwrap(), entries);
+    }
+
+    #[test]
+    fn test_system_format() {
+        let content = "SHELL=/bin/sh\n# m h dom mon dow user\tcommand\n17 *\t* * *\troot    cd / && run-parts --report /etc/cron.hourly\n\
+                       # NAME: Reindex\n# 30 3 * * 0 www-data /srv/app/reindex\n@reboot postgres /usr/bin/warm%stdin\n5 4 * * *\n";
+        let entries = CronParser::parse_system(content).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].user.as_deref(), Some("root"));
+        assert_eq!(entries[0].command, "cd / && run-parts --report /etc/cron.hourly");
+        assert_eq!((entries[1].name.as_str(), entries[1].user.as_deref(), entries[1].enabled), ("Reindex", Some("www-data"), false));
+        assert_eq!((entries[2].schedule.as_str(), entries[2].user.as_deref()), ("@reboot", Some("postgres")));
+        assert_eq!(entries[2].stdin.as_deref(), Some("stdin"));
+
+        let output = CronParser::serialize_system_into(content, &entries);
+        assert!(output.starts_with("SHELL=/bin/sh\n# m h dom mon dow user\tcommand\n"));
+        assert!(output.contains("\n# 30 3 * * 0 www-data /srv/app/reindex\n"));
+        assert!(output.contains("\n@reboot postgres /usr/bin/warm%stdin\n"));
+        assert_eq!(CronParser::parse_system(&output).unwrap(), entries);
+        // Unnamed jobs read in the user format have the user in the command;
+        // named ones keep it in `# USER:`
+        assert_eq!(CronParser::parse(content).unwrap()[0].user, None);
+        assert!(output.contains("# NAME: Reindex\n# USER: www-data\n"));
+        assert_eq!(CronParser::parse(&CronParser::serialize(&entries)).unwrap(), entries);
+    }
+
     #[test]
     fn test_serialize() {
         let entries = vec![
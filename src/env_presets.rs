@@ -0,0 +1,106 @@
+use crate::secrets;
+use anyhow::{bail, Context, Result};
+use std::collections::BTreeMap;
+
+/// Named sets of `NAME=value` lines from `[env_presets]` in the config
+pub type EnvPresets = BTreeMap<String, Vec<String>>;
+
+/// Split a `NAME=value` line, checking the name is a valid shell variable
+pub fn parse_line(line: &str) -> Result<(&str, &str)> {
+    let (name, value) = line.split_once('=').with_context(|| format!("'{}' is not NAME=value", line))?;
+    let name = name.trim();
+    check_name(name)?;
+    Ok((name, value))
+}
+
+/// Check that `name` can be used as a shell variable
+pub fn check_name(name: &str) -> Result<()> {
+    let valid = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        bail!("'{}' is not a valid variable name", name);
+    }
+    Ok(())
+}
+
+/// Check every line of every preset
+pub fn validate(presets: &EnvPresets) -> Result<()> {
+    for (preset, lines) in presets {
+        for line in lines {
+            parse_line(line).with_context(|| format!("Env preset '{}'", preset))?;
+        }
+    }
+    Ok(())
+}
+
+/// The preset as `export` statements to put before a command. Each variable
+/// is exported on its own, in order, so later values can use earlier ones;
+/// values are double-quoted, so `$VAR` references still expand.
+pub fn export_prefix(presets: &EnvPresets, name: &str) -> Result<String> {
+    let lines = presets.get(name).with_context(|| format!("Unknown env preset '{}'", name))?;
+    let vars = lines.iter().map(|line| parse_line(line)).collect::<Result<Vec<_>>>()?;
+    Ok(export_statements(vars))
+}
+
+/// An entry's own variables as `export` statements, quoted like a preset's
+pub fn export_vars(vars: &[(String, String)]) -> Result<String> {
+    for (name, _) in vars {
+        check_name(name)?;
+    }
+    Ok(export_statements(vars.iter().map(|(name, value)| (name.as_str(), value.as_str()))))
+}
+
+fn export_statements<'a>(vars: impl IntoIterator<Item = (&'a str, &'a str)>) -> String {
+    vars.into_iter()
+        .map(|(name, value)| format!("export {}={}; ", name, double_quote(value)))
+        .collect()
+}
+
+/// Put a value in double quotes, leaving `!secret NAME` placeholders outside
+/// them: secrets resolve to single-quoted words, which the shell then joins
+/// with the quoted parts around them
+fn double_quote(value: &str) -> String {
+    let escaped = value.replace('\\', r"\\").replace('"', "\\\"").replace('`', "\\`");
+    let mut quoted = String::from("\"");
+    let mut rest = escaped.as_str();
+    while let Some(index) = rest.find(secrets::PLACEHOLDER) {
+        let after = &rest[index + secrets::PLACEHOLDER.len()..];
+        let end = after.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(after.len());
+        quoted.push_str(&rest[..index]);
+        quoted.push('"');
+        quoted.push_str(&rest[index..index + secrets::PLACEHOLDER.len() + end]);
+        quoted.push('"');
+        rest = &after[end..];
+    }
+    quoted.push_str(rest);
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_prefix() {
+        let presets = EnvPresets::from([(
+            "python-venv".to_string(),
+            vec!["VIRTUAL_ENV=/opt/venv".to_string(), "PATH=$VIRTUAL_ENV/bin:$PATH".to_string(), "GREETING=say \"hi\"".to_string()],
+        )]);
+        assert_eq!(
+            export_prefix(&presets, "python-venv").unwrap(),
+            r#"export VIRTUAL_ENV="/opt/venv"; export PATH="$VIRTUAL_ENV/bin:$PATH"; export GREETING="say \"hi\""; "#
+        );
+        assert!(export_prefix(&presets, "aws-prod").is_err());
+        assert_eq!(double_quote("Bearer !secret API_TOKEN"), r#""Bearer "!secret API_TOKEN"""#);
+
+        assert!(validate(&presets).is_ok());
+        let broken = EnvPresets::from([("aws-prod".to_string(), vec!["AWS PROFILE=prod".to_string()])]);
+        assert!(validate(&broken).is_err());
+        assert!(parse_line("NO_VALUE").is_err());
+
+        let vars = [("RAILS_ENV".to_string(), "production".to_string())];
+        assert_eq!(export_vars(&vars).unwrap(), r#"export RAILS_ENV="production"; "#);
+        assert!(export_vars(&[("1X".to_string(), String::new())]).is_err());
+    }
+}
@@ -0,0 +1,204 @@
+use crate::cron_entry::CronEntry;
+use crate::cron_parser::CronParser;
+use crate::secrets;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Structured formats entries are exported to and imported from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl Format {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "yaml" | "yml" => Ok(Self::Yaml),
+            "toml" => Ok(Self::Toml),
+            _ => bail!("Unknown format '{}' (use json, yaml or toml)", name),
+        }
+    }
+
+    /// The format a file's extension names, if any
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension().and_then(|ext| Self::parse(&ext.to_string_lossy()).ok())
+    }
+}
+
+/// The document written: a table, since TOML can't hold a bare array
+#[derive(Debug, Serialize, Deserialize)]
+struct Document {
+    entries: Vec<CronEntry>,
+}
+
+/// Every field of the entries in the given format
+pub fn to_string(entries: &[CronEntry], format: Format) -> Result<String> {
+    let document = Document { entries: entries.to_vec() };
+    Ok(match format {
+        Format::Json => serde_json::to_string_pretty(&document)? + "\n",
+        Format::Yaml => serde_yaml::to_string(&document)?,
+        Format::Toml => toml::to_string(&document)?,
+    })
+}
+
+/// What to do with an imported entry whose name is already taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnDuplicate {
+    Skip,
+    Overwrite,
+    /// Add it as `name (imported)`
+    Rename,
+}
+
+impl OnDuplicate {
+    /// Parse the `--on-duplicate` option of `import`
+    pub fn parse(input: &str) -> Result<Self> {
+        Ok(match input {
+            "skip" => Self::Skip,
+            "overwrite" => Self::Overwrite,
+            "rename" => Self::Rename,
+            _ => bail!("Unknown choice '{}' (use skip, overwrite or rename)", input),
+        })
+    }
+}
+
+/// What an import did with the entries it read
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub added: usize,
+    pub overwritten: usize,
+    pub renamed: usize,
+    pub skipped: usize,
+}
+
+/// Entries of an exported document, or of crontab text when no format is
+/// given. Commands resolved on save get their placeholders back, and
+/// generated watchdog entries are left out, as when loading.
+pub fn parse(content: &str, format: Option<Format>) -> Result<Vec<CronEntry>> {
+    let entries = match format {
+        Some(Format::Json) => serde_json::from_str::<Document>(content).context("Invalid JSON export")?.entries,
+        Some(Format::Yaml) => serde_yaml::from_str::<Document>(content).context("Invalid YAML export")?.entries,
+        Some(Format::Toml) => toml::from_str::<Document>(content).context("Invalid TOML export")?.entries,
+        None => CronParser::parse(content)?,
+    };
+    imported(entries)
+}
+
+/// Entries of a crontab in the system format (`/etc/crontab`,
+/// `/etc/cron.d/*`), each with the user it runs as
+pub fn parse_system(content: &str) -> Result<Vec<CronEntry>> {
+    imported(CronParser::parse_system(content)?)
+}
+
+fn imported(mut entries: Vec<CronEntry>) -> Result<Vec<CronEntry>> {
+    entries.retain(|entry| entry.watchdog_for.is_none());
+    entries.iter_mut().for_each(secrets::restore_template);
+    if entries.is_empty() {
+        bail!("No entries found");
+    }
+    Ok(entries)
+}
+
+/// Names of the imported entries that are already taken
+pub fn duplicates<'a>(entries: &[CronEntry], imported: &'a [CronEntry]) -> Vec<&'a str> {
+    imported
+        .iter()
+        .filter(|entry| entries.iter().any(|e| e.name == entry.name))
+        .map(|entry| entry.name.as_str())
+        .collect()
+}
+
+/// Add the imported entries, settling taken names as chosen
+pub fn merge(entries: &mut Vec<CronEntry>, imported: Vec<CronEntry>, on_duplicate: OnDuplicate) -> ImportSummary {
+    let mut summary = ImportSummary::default();
+    for mut entry in imported {
+        let Some(position) = entries.iter().position(|e| e.name == entry.name) else {
+            entries.push(entry);
+            summary.added += 1;
+            continue;
+        };
+        match on_duplicate {
+            OnDuplicate::Skip => summary.skipped += 1,
+            OnDuplicate::Overwrite => {
+                entries[position] = entry;
+                summary.overwritten += 1;
+            }
+            OnDuplicate::Rename => {
+                entry.name = import_name(entries, &entry.name);
+                entries.push(entry);
+                summary.renamed += 1;
+            }
+        }
+    }
+    summary
+}
+
+/// `name (imported)`, or `name (imported 2)` and so on when that is taken
+fn import_name(entries: &[CronEntry], name: &str) -> String {
+    let mut candidate = format!("{} (imported)", name);
+    let mut number = 2;
+    while entries.iter().any(|e| e.name == candidate) {
+        candidate = format!("{} (imported {})", name, number);
+        number += 1;
+    }
+    candidate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_format_keeps_all_fields() {
+        let mut entry = CronEntry::new("Backup".to_string(), "0 2 * * *".to_string(), "/bin/backup".to_string());
+        entry.tags = vec!["daily".to_string()];
+        entry.snoozed_until = chrono::NaiveDate::from_ymd_opt(2026, 1, 2).and_then(|d| d.and_hms_opt(3, 4, 0));
+        entry.env_vars = vec![("LANG".to_string(), "C".to_string())];
+        entry.comments = vec!["nightly".to_string()];
+        let entries = vec![entry, CronEntry::new("Sync".to_string(), "*/5 * * * *".to_string(), "sync".to_string())];
+
+        for format in [Format::Json, Format::Yaml, Format::Toml] {
+            let text = to_string(&entries, format).unwrap();
+            let document: Document = match format {
+                Format::Json => serde_json::from_str(&text).unwrap(),
+                Format::Yaml => serde_yaml::from_str(&text).unwrap(),
+                Format::Toml => toml::from_str(&text).unwrap(),
+            };
+            assert_eq!(document.entries, entries, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn test_import_settles_taken_names() {
+        let entry = |name: &str, command: &str| CronEntry::new(name.to_string(), "0 * * * *".to_string(), command.to_string());
+        let imported = parse("# NAME: Backup\n0 2 * * * /bin/new-backup\n# NAME: Sync\n*/5 * * * * sync\n", None).unwrap();
+        let current = vec![entry("Backup", "/bin/backup"), entry("Backup (imported)", "old")];
+        assert_eq!(duplicates(&current, &imported), vec!["Backup"]);
+
+        let mut entries = current.clone();
+        let summary = merge(&mut entries, imported.clone(), OnDuplicate::Skip);
+        assert_eq!((summary.added, summary.skipped), (1, 1));
+        assert_eq!(entries[0].command, "/bin/backup");
+
+        let mut entries = current.clone();
+        merge(&mut entries, imported.clone(), OnDuplicate::Overwrite);
+        assert_eq!((entries.len(), entries[0].command.as_str()), (3, "/bin/new-backup"));
+
+        let mut entries = current;
+        let summary = merge(&mut entries, imported, OnDuplicate::Rename);
+        assert_eq!(summary, ImportSummary { added: 1, renamed: 1, ..Default::default() });
+        assert_eq!(entries[2].name, "Backup (imported 2)");
+    }
+
+    #[test]
+    fn test_format_from_extension() {
+        assert_eq!(Format::from_path(Path::new("jobs.yml")), Some(Format::Yaml));
+        assert_eq!(Format::from_path(Path::new("jobs.TOML")), Some(Format::Toml));
+        assert_eq!(Format::from_path(Path::new("jobs.txt")), None);
+        assert!(Format::parse("xml").is_err());
+    }
+}
@@ -0,0 +1,185 @@
+use crate::cron_entry::CronEntry;
+use anyhow::{bail, Result};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Selects entries for bulk operations: by tag, or by a name glob where `*`
+/// matches any run of characters and `?` a single one
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntryFilter {
+    Tag(String),
+    Name(String),
+}
+
+impl EntryFilter {
+    /// Parse the TUI form: `#tag` selects by tag, anything else is a name glob
+    pub fn parse(input: &str) -> Result<Self> {
+        let input = input.trim();
+        match input.strip_prefix('#') {
+            Some(tag) if !tag.trim().is_empty() => Ok(Self::Tag(tag.trim().to_string())),
+            Some(_) => bail!("Missing tag after '#'"),
+            None if input.is_empty() => bail!("Enter a tag (#name) or a name pattern"),
+            None => Ok(Self::Name(input.to_string())),
+        }
+    }
+
+    pub fn matches(&self, entry: &CronEntry) -> bool {
+        match self {
+            Self::Tag(tag) => entry.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)),
+            Self::Name(pattern) => glob_match(pattern, &entry.name),
+        }
+    }
+}
+
+impl fmt::Display for EntryFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tag(tag) => write!(f, "#{}", tag),
+            Self::Name(pattern) => write!(f, "'{}'", pattern),
+        }
+    }
+}
+
+/// Result of enabling or disabling the entries matched by a filter
+#[derive(Debug, Default, PartialEq)]
+pub struct BulkChange {
+    /// Names of entries whose state changed
+    pub changed: Vec<String>,
+    /// Matching entries that were already in the requested state
+    pub unchanged: usize,
+}
+
+/// Enable or disable every entry matching `filter`. Snoozes on matched
+/// entries are cleared, as with a manual toggle.
+pub fn set_enabled(entries: &mut [CronEntry], filter: &EntryFilter, enabled: bool) -> BulkChange {
+    let mut change = BulkChange::default();
+    for entry in entries.iter_mut().filter(|e| filter.matches(e)) {
+        entry.snoozed_until = None;
+        if entry.enabled == enabled {
+            change.unchanged += 1;
+        } else {
+            entry.enabled = enabled;
+            change.changed.push(entry.name.clone());
+        }
+    }
+    change
+}
+
+/// A line of the entry table
+#[derive(Debug, Clone, PartialEq)]
+pub enum ListRow {
+    /// Heading of a tag group (`None` for untagged entries) and its size
+    Group(Option<String>, usize),
+    /// Index into the entries
+    Entry(usize),
+}
+
+/// The entry table's rows: the entries with `tag`, or all of them, in order.
+/// Grouped, they go under the heading of their first tag, tags sorted by name
+/// and untagged entries last.
+pub fn list_rows(entries: &[CronEntry], tag: Option<&str>, grouped: bool) -> Vec<ListRow> {
+    let filter = tag.map(|tag| EntryFilter::Tag(tag.to_string()));
+    let shown = (0..entries.len()).filter(|i| filter.as_ref().is_none_or(|f| f.matches(&entries[*i])));
+    if !grouped {
+        return shown.map(ListRow::Entry).collect();
+    }
+
+    // Keyed so that tags differing only in case share a group
+    let mut groups: BTreeMap<(bool, String), (Option<String>, Vec<usize>)> = BTreeMap::new();
+    for i in shown {
+        let first = entries[i].tags.first();
+        let key = (first.is_none(), first.map(|t| t.to_lowercase()).unwrap_or_default());
+        groups.entry(key).or_insert_with(|| (first.cloned(), Vec::new())).1.push(i);
+    }
+    let mut rows = Vec::new();
+    for (tag, members) in groups.into_values() {
+        rows.push(ListRow::Group(tag, members.len()));
+        rows.extend(members.into_iter().map(ListRow::Entry));
+    }
+    rows
+}
+
+/// Case-insensitive glob match of the whole text
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    // Iterative matcher that backtracks to the most recent `*`
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::tagged;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("backup*", "Backup Nightly"));
+        assert!(glob_match("*report?", "weekly reports"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("backup", "backup nightly"));
+        assert!(!glob_match("a*b", "acbd"));
+    }
+
+    #[test]
+    fn test_set_enabled_by_tag_and_name() {
+        let mut entries = vec![
+            tagged("Backup db", &["backups"]),
+            CronEntry { enabled: false, ..tagged("Backup files", &["backups", "nightly"]) },
+            tagged("Report", &["nightly"]),
+        ];
+
+        let change = set_enabled(&mut entries, &EntryFilter::parse("#Backups").unwrap(), false);
+        assert_eq!(change.changed, ["Backup db"]);
+        assert_eq!(change.unchanged, 1);
+        assert!(!entries[0].enabled);
+        assert!(entries[2].enabled);
+
+        let change = set_enabled(&mut entries, &EntryFilter::parse("backup*").unwrap(), true);
+        assert_eq!(change.changed.len(), 2);
+        assert!(EntryFilter::parse("#").is_err());
+    }
+
+    #[test]
+    fn test_list_rows_filter_and_group() {
+        let entries = vec![
+            tagged("Report", &["nightly"]),
+            tagged("Cleanup", &[]),
+            tagged("Backup db", &["backups", "nightly"]),
+            tagged("Backup files", &["Backups"]),
+        ];
+
+        assert_eq!(list_rows(&entries, Some("Nightly"), false), [ListRow::Entry(0), ListRow::Entry(2)]);
+        assert_eq!(
+            list_rows(&entries, None, true),
+            [
+                ListRow::Group(Some("backups".to_string()), 2),
+                ListRow::Entry(2),
+                ListRow::Entry(3),
+                ListRow::Group(Some("nightly".to_string()), 1),
+                ListRow::Entry(0),
+                ListRow::Group(None, 1),
+                ListRow::Entry(1),
+            ]
+        );
+        assert!(list_rows(&entries, Some("weekly"), true).is_empty());
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+/// Most commands kept from the history files
+const MAX_COMMANDS: usize = 5000;
+
+/// Commands from the user's shell history (`$HISTFILE`, `~/.bash_history`,
+/// `~/.zsh_history`), most recent first and without duplicates
+pub fn load_commands() -> Vec<String> {
+    let mut files: Vec<PathBuf> = std::env::var_os("HISTFILE").map(PathBuf::from).into_iter().collect();
+    if let Some(home) = dirs::home_dir() {
+        files.push(home.join(".zsh_history"));
+        files.push(home.join(".bash_history"));
+    }
+
+    let mut seen = HashSet::new();
+    let mut commands = Vec::new();
+    for file in files {
+        // zsh may store bytes that aren't valid UTF-8
+        let Ok(bytes) = fs::read(&file) else {
+            continue;
+        };
+        let content = String::from_utf8_lossy(&bytes);
+        for command in parse_history(&content).into_iter().rev() {
+            if commands.len() >= MAX_COMMANDS {
+                return commands;
+            }
+            if seen.insert(command.clone()) {
+                commands.push(command);
+            }
+        }
+    }
+    commands
+}
+
+/// Commands in file order. Handles zsh's extended format
+/// (`: 1700000000:0;command`) and skips bash timestamp comments.
+fn parse_history(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let command = match line.strip_prefix(": ") {
+                Some(rest) => rest.split_once(';').map_or(line, |(_, command)| command),
+                None => line,
+            };
+            let command = command.trim();
+            (!command.is_empty() && !command.starts_with('#')).then(|| command.to_string())
+        })
+        .collect()
+}
+
+/// Up to `limit` history commands starting with what has been typed
+pub fn suggestions<'a>(history: &'a [String], typed: &str, limit: usize) -> Vec<&'a str> {
+    let typed = typed.trim_start();
+    if typed.is_empty() {
+        return Vec::new();
+    }
+    history
+        .iter()
+        .map(String::as_str)
+        .filter(|command| command.starts_with(typed) && *command != typed)
+        .take(limit)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bash_and_zsh_history() {
+        let zsh = ": 1700000000:0;/usr/local/bin/backup --full\n: 1700000100:0;ls\n";
+        assert_eq!(parse_history(zsh), ["/usr/local/bin/backup --full", "ls"]);
+
+        let bash = "#1700000000\ncd /tmp\n\n/opt/scripts/report.sh\n";
+        assert_eq!(parse_history(bash), ["cd /tmp", "/opt/scripts/report.sh"]);
+    }
+
+    #[test]
+    fn test_suggestions_by_prefix() {
+        let history: Vec<String> = ["/opt/a.sh --x", "/opt/b.sh", "/opt/a.sh", "ls"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(suggestions(&history, "/opt/a", 5), ["/opt/a.sh --x", "/opt/a.sh"]);
+        assert_eq!(suggestions(&history, "/opt/a.sh", 5), ["/opt/a.sh --x"]);
+        assert_eq!(suggestions(&history, "/opt", 1), ["/opt/a.sh --x"]);
+        assert!(suggestions(&history, "", 5).is_empty());
+    }
+}
@@ -0,0 +1,169 @@
+use crate::paths;
+use crate::secrets::shell_quote;
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Excluded dates, one `YYYY-MM-DD label` per line, inside the data directory
+const HOLIDAYS_FILE: &str = "holidays.txt";
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Dates on which entries flagged with "skip on excluded dates" don't run,
+/// such as public holidays
+#[derive(Debug, Clone)]
+pub struct ExclusionCalendar {
+    path: PathBuf,
+}
+
+impl ExclusionCalendar {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn default_path(custom_data_dir: Option<&Path>) -> Option<PathBuf> {
+        paths::data_dir(custom_data_dir).map(|dir| dir.join(HOLIDAYS_FILE))
+    }
+
+    /// Excluded dates with their labels; blank lines and `#` comments are skipped
+    pub fn load(&self) -> Result<BTreeMap<NaiveDate, String>> {
+        if !self.path.exists() {
+            return Ok(BTreeMap::new());
+        }
+        let content = fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read excluded dates: {:?}", self.path))?;
+        let mut dates = BTreeMap::new();
+        for (number, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (date, label) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let date = parse_date(date).with_context(|| format!("{:?}, line {}", self.path, number + 1))?;
+            dates.insert(date, label.trim().to_string());
+        }
+        Ok(dates)
+    }
+
+    fn write(&self, dates: &BTreeMap<NaiveDate, String>) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {:?}", parent))?;
+        }
+        let content: String = dates
+            .iter()
+            .map(|(date, label)| match label.is_empty() {
+                true => format!("{}\n", date.format(DATE_FORMAT)),
+                false => format!("{} {}\n", date.format(DATE_FORMAT), label),
+            })
+            .collect();
+        fs::write(&self.path, content).with_context(|| format!("Failed to write excluded dates: {:?}", self.path))
+    }
+
+    /// Add dates, replacing the labels of dates already excluded; returns
+    /// how many were new
+    pub fn add(&self, dates: impl IntoIterator<Item = (NaiveDate, String)>) -> Result<usize> {
+        let mut existing = self.load()?;
+        let before = existing.len();
+        existing.extend(dates);
+        let added = existing.len() - before;
+        self.write(&existing)?;
+        Ok(added)
+    }
+
+    /// Remove a date; returns whether it was excluded
+    pub fn remove(&self, date: NaiveDate) -> Result<bool> {
+        let mut dates = self.load()?;
+        let removed = dates.remove(&date).is_some();
+        if removed {
+            self.write(&dates)?;
+        }
+        Ok(removed)
+    }
+}
+
+pub fn parse_date(text: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(text, DATE_FORMAT)
+        .with_context(|| format!("Invalid date '{}' (expected YYYY-MM-DD)", text))
+}
+
+/// The all-day events of an iCalendar (`.ics`) file, e.g. a public holiday
+/// calendar, as dates with their summaries. Multi-day events only exclude
+/// their first day.
+pub fn parse_ics(content: &str) -> Vec<(NaiveDate, String)> {
+    // Long lines continue on lines starting with a space or tab
+    let unfolded = content.replace("\r\n", "\n").replace("\n ", "").replace("\n\t", "");
+    let mut events = Vec::new();
+    let (mut date, mut summary) = (None, String::new());
+
+    for line in unfolded.lines() {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        // Parameters follow the property name: `DTSTART;VALUE=DATE:20260101`
+        match name.split(';').next().unwrap_or_default() {
+            "BEGIN" if value == "VEVENT" => (date, summary) = (None, String::new()),
+            "DTSTART" => date = value.get(..8).and_then(|d| NaiveDate::parse_from_str(d, "%Y%m%d").ok()),
+            "SUMMARY" => summary = value.replace("\\,", ",").replace("\\;", ";"),
+            "END" if value == "VEVENT" => {
+                if let Some(date) = date.take() {
+                    events.push((date, std::mem::take(&mut summary)));
+                }
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+/// The command prefixed with a check that ends the job early on excluded dates
+pub fn guard_command(command: &str) -> Result<String> {
+    let exe = std::env::current_exe().context("Failed to locate the cron-manager executable")?;
+    // If the check itself can't run, the job runs as usual
+    Ok(format!("{} holidays check && exit 0; {}", shell_quote(&exe.to_string_lossy()), command))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn date(text: &str) -> NaiveDate {
+        parse_date(text).unwrap()
+    }
+
+    #[test]
+    fn test_add_remove() {
+        let dir = TempDir::new("holidays");
+        let path = dir.join("holidays.txt");
+        let calendar = ExclusionCalendar::new(path.clone());
+
+        let added = calendar
+            .add([(date("2026-12-25"), "Christmas".to_string()), (date("2026-01-01"), String::new())])
+            .unwrap();
+        assert_eq!(added, 2);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "2026-01-01\n2026-12-25 Christmas\n");
+        assert_eq!(calendar.add([(date("2026-01-01"), "New Year".to_string())]).unwrap(), 0);
+
+        assert!(calendar.remove(date("2026-12-25")).unwrap());
+        assert!(!calendar.remove(date("2026-12-25")).unwrap());
+        let dates = calendar.load().unwrap();
+        assert_eq!(dates.len(), 1);
+        assert_eq!(dates[&date("2026-01-01")], "New Year");
+    }
+
+    #[test]
+    fn test_parse_ics() {
+        let ics = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nDTSTART;VALUE=DATE:20260101\r\nSUMMARY:New Year\r\n 's Day\r\nEND:VEVENT\r\n\
+                   BEGIN:VEVENT\r\nDTSTART:20260504T000000Z\r\nSUMMARY:Greenery Day\\, Japan\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+        assert_eq!(
+            parse_ics(ics),
+            [
+                (date("2026-01-01"), "New Year's Day".to_string()),
+                (date("2026-05-04"), "Greenery Day, Japan".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,45 @@
+use crate::cron_entry::CronEntry;
+use crate::retry::RetryPolicy;
+use crate::secrets::shell_quote;
+use anyhow::{Context, Result};
+use std::process::Command;
+
+/// Environment variable holding the main command's exit code in hooks
+/// (empty when it was killed by a signal)
+pub const EXIT_CODE_VAR: &str = "CRON_MANAGER_EXIT_CODE";
+
+/// Environment variable holding the entry's name in hooks
+pub const ENTRY_VAR: &str = "CRON_MANAGER_ENTRY";
+
+/// The command run through `cron-manager exec`, which enforces the entry's
+/// timeout and retries, pings its monitoring check and runs its on-success or
+/// on-failure hook after it. Hooks and the ping URL come resolved.
+pub fn wrap_command(entry: &CronEntry, command: &str, on_success: Option<&str>, on_failure: Option<&str>, ping: Option<&str>) -> Result<String> {
+    let exe = std::env::current_exe().context("Failed to locate the cron-manager executable")?;
+    let mut wrapped = format!("{} exec --name {}", shell_quote(&exe.to_string_lossy()), shell_quote(&entry.name));
+    wrapped.push_str(&RetryPolicy::of(entry).exec_options());
+    for (option, hook) in [("--on-success", on_success), ("--on-failure", on_failure), ("--ping", ping)] {
+        if let Some(hook) = hook {
+            wrapped.push_str(&format!(" {} {}", option, shell_quote(hook)));
+        }
+    }
+    wrapped.push_str(&format!(" -- {}", shell_quote(command)));
+    Ok(wrapped)
+}
+
+/// Run a hook through `sh` with the entry's name and exit code in its
+/// environment. Failures are reported but never change the job's result.
+pub fn run_hook(hook: &str, entry: &str, exit_code: Option<i32>) {
+    let exit_code = exit_code.map(|code| code.to_string()).unwrap_or_default();
+    let result = Command::new("sh")
+        .arg("-c")
+        .arg(hook)
+        .env(ENTRY_VAR, entry)
+        .env(EXIT_CODE_VAR, exit_code)
+        .status();
+    match result {
+        Ok(hook_status) if !hook_status.success() => eprintln!("Hook failed ({}): {}", hook_status, hook),
+        Ok(_) => {}
+        Err(e) => eprintln!("Failed to start hook '{}': {}", hook, e),
+    }
+}
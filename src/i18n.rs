@@ -0,0 +1,476 @@
+use std::fmt::Display;
+
+/// UI language, chosen by the config file's `language` or the locale
+/// environment (`LC_ALL`, `LC_MESSAGES`, `LANG`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    English,
+    Japanese,
+}
+
+impl Language {
+    /// `en`, `ja`, or a locale name such as `ja_JP.UTF-8`
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.trim().to_ascii_lowercase();
+        if name.starts_with("ja") {
+            Some(Self::Japanese)
+        } else if name.starts_with("en") || name == "c" || name == "posix" {
+            Some(Self::English)
+        } else {
+            None
+        }
+    }
+
+    /// The configured language, else the one from the environment, else English
+    pub fn detect(configured: Option<&str>) -> Self {
+        configured
+            .filter(|name| *name != "auto")
+            .and_then(Self::parse)
+            .or_else(|| {
+                ["LC_ALL", "LC_MESSAGES", "LANG"]
+                    .iter()
+                    .filter_map(|var| std::env::var(var).ok())
+                    .find(|value| !value.is_empty())
+                    .and_then(|value| Self::parse(&value))
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn strings(self) -> &'static Strings {
+        match self {
+            Self::English => &EN,
+            Self::Japanese => &JA,
+        }
+    }
+}
+
+/// Fill the `{}` placeholders of a template in order
+pub fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut rest = template;
+    while let Some(index) = rest.find("{}") {
+        output.push_str(&rest[..index]);
+        if let Some(arg) = args.next() {
+            output.push_str(&arg.to_string());
+        }
+        rest = &rest[index + 2..];
+    }
+    output.push_str(rest);
+    output
+}
+
+macro_rules! strings {
+    ($($key:ident: $en:literal, $ja:literal;)*) => {
+        /// Every user-facing string in the TUI, one bundle per language.
+        /// `{}` placeholders are filled with `fill`.
+        pub struct Strings {
+            $(pub $key: &'static str,)*
+        }
+
+        pub const EN: Strings = Strings { $($key: $en,)* };
+        pub const JA: Strings = Strings { $($key: $ja,)* };
+
+        #[cfg(test)]
+        const ALL: &[(&str, &str, &str)] = &[$((stringify!($key), $en, $ja),)*];
+    };
+}
+
+strings! {
+    // Prompts
+    prompt_new_name: "Enter name for new cron entry:", "新しいエントリの名前を入力:";
+    prompt_schedule: "Name: {} | Enter schedule (cron format):", "名前: {} | スケジュールを入力（Cron形式）:";
+    prompt_command: "Name: {} | Schedule: {} | Enter command:", "名前: {} | スケジュール: {} | コマンドを入力:";
+    prompt_edit_name: "Edit name:", "名前を編集:";
+    prompt_edit_schedule: "Edit schedule (cron format):", "スケジュールを編集（Cron形式）:";
+    prompt_edit_command: "Edit command:", "コマンドを編集:";
+    prompt_edit_timezone: "Edit timezone (e.g. Asia/Tokyo, empty for default):", "タイムゾーンを編集（例: Asia/Tokyo、空欄で既定）:";
+    invalid_schedule_enter: "Invalid schedule: {} | Enter schedule:", "不正なスケジュール: {} | スケジュールを入力:";
+    invalid_schedule_edit: "Invalid schedule: {} | Edit schedule:", "不正なスケジュール: {} | スケジュールを編集:";
+    invalid_timezone: "{} | Edit timezone:", "{} | タイムゾーンを編集:";
+    prompt_snooze: "Snooze for (e.g. 30m, 8h, 2d, 1w):", "スヌーズする期間（例: 30m、8h、2d、1w）:";
+    invalid_snooze: "{} | Snooze for:", "{} | スヌーズする期間:";
+    prompt_edit_tags: "Edit tags (comma-separated):", "タグを編集（カンマ区切り）:";
+    prompt_edit_env: "Env preset ({}; empty for none):", "環境変数プリセット（{}、空欄でなし）:";
+    invalid_env_preset: "{} | Env preset:", "{} | 環境変数プリセット:";
+    prompt_edit_shell: "Shell the command is written for (sh, powershell, pwsh or cmd; empty for sh):", "コマンドのシェル（sh・powershell・pwsh・cmd、空欄で sh）:";
+    invalid_shell: "{} | Shell:", "{} | シェル:";
+    prompt_success_hook: "Command to run after a successful run (empty for none):", "成功時に実行するコマンド（空欄でなし）:";
+    prompt_edit_timeout: "Stop the command after (e.g. 90s, 5m; empty for no timeout):", "コマンドを打ち切るまでの時間（例: 90s、5m。空欄でタイムアウトなし）:";
+    prompt_edit_retries: "Retries after a failure, and the wait before each (e.g. 3, 30s; empty for none):", "失敗時の再試行回数と、各再試行までの待ち時間（例: 3, 30s。空欄で再試行なし）:";
+    invalid_timeout: "{} | Stop the command after:", "{} | コマンドを打ち切るまでの時間:";
+    invalid_retries: "{} | Retries after a failure:", "{} | 失敗時の再試行:";
+    prompt_edit_ping: "Monitoring check URL pinged on start and end (https://… or !secret NAME; empty for none):", "開始時と終了時にPingする監視チェックのURL（https://… または !secret 名前。空欄でなし）:";
+    invalid_ping_url: "Use an http(s) URL or !secret NAME", "http(s) のURLか !secret 名前 を入力してください";
+    prompt_failure_hook: "Command to run after a failed run (empty for none):", "失敗時に実行するコマンド（空欄でなし）:";
+    prompt_tag_filter: "Show entries with tag (empty shows all):", "表示するエントリのタグ（空欄ですべて表示）:";
+    prompt_bulk_toggle: "Enable/disable entries matching (#tag or name pattern like backup*):", "一括で有効/無効にするエントリ（#タグ、またはbackup*のような名前のパターン）:";
+    invalid_bulk_filter: "{} | Entries matching:", "{} | 対象のエントリ:";
+    policy_refused: "Refused by policy: {} | Enter command:", "ポリシーにより拒否されました: {} | コマンドを入力:";
+    natural_preview: "\"{}\" => (Enter to confirm):", "「{}」=>（Enterで確定）:";
+    natural_error: "{} | Enter schedule:", "{} | スケジュールを入力:";
+
+    // Messages
+    entry_added: "Entry added successfully", "エントリを追加しました";
+    entry_added_invalid: "Warning: Invalid cron schedule format. Entry still added.", "警告: Cronスケジュールの形式が不正ですが、エントリを追加しました";
+    entry_added_duplicate: "Warning: Entry added, but it duplicates '{}' (same command and schedule)", "警告: エントリを追加しましたが、「{}」と重複しています（同じコマンドとスケジュール）";
+    entry_deleted: "Entry deleted", "エントリを削除しました";
+    hosts_unreachable: "Could not open {}", "開けなかったホスト: {}";
+    save_blocked: "Changes can't be saved: {}", "変更を保存できません: {}";
+    entry_enabled: "Entry {} enabled", "エントリ「{}」を有効にしました";
+    skips_excluded: "{} no longer runs on excluded dates ({} in the calendar)", "「{}」は除外日に実行されなくなりました（カレンダーに{}日）";
+    runs_on_excluded: "{} runs on excluded dates again", "「{}」は除外日にも実行されます";
+    watchdog_on: "Watching {}: missed runs raise an alert", "「{}」を監視します: 実行されなかった回があれば警告します";
+    watchdog_off: "Stopped watching {}", "「{}」の監視を止めました";
+    entry_disabled: "Entry {} disabled", "エントリ「{}」を無効にしました";
+    entry_snoozed: "Entry {} snoozed until {}", "エントリ「{}」を{}までスヌーズしました";
+    backups_disabled: "Backups are disabled in the config", "設定でバックアップが無効になっています";
+    no_backups: "No backups yet; one is taken after every save", "バックアップはまだありません（保存のたびに作成されます）";
+    backups_summary: "{} backups. Enter opens one, Enter on an entry restores it", "バックアップ: {}件。Enterで開き、エントリ上でEnterを押すと復元します";
+    backup_empty: "This backup has no entries", "このバックアップにはエントリがありません";
+    entry_restored: "Restored '{}' from the backup of {}", "「{}」を{}のバックアップから復元しました";
+    confirm_rollback: "Replace every entry with the backup of {}? (y/n)", "すべてのエントリを{}のバックアップに戻しますか？ (y/n)";
+    confirm_recovery: "The entries look damaged ({}). Restore the backup of {}? (y/n)", "エントリが壊れているようです（{}）。{}のバックアップから復元しますか？ (y/n)";
+    backend_emptied: "there are none, but the newest backup has {}", "1件もありませんが、最新のバックアップには{}件あります";
+    damaged_no_backup: "The entries look damaged ({}) and there is no backup to restore; the file is kept as .damaged when saved over", "エントリが壊れているようです（{}）。復元できるバックアップはありません（上書き保存時に元のファイルは .damaged として残ります）";
+    rolled_back: "Rolled back to the backup of {} ({} entries changed)", "{}のバックアップに戻しました（{}件のエントリを変更）";
+    no_recent_changes: "No changes saved in the last {} days", "過去{}日間に保存された変更はありません";
+    changes_summary: "{} changes in the last {} days", "{}件の変更（過去{}日間）";
+    comments_updated: "Updated the comments of {}", "「{}」のコメントを更新しました";
+    comment_ambiguous: "Comment line '{}' would read back as an entry; reword it", "コメント行「{}」はエントリとして読み込まれてしまうため、書き換えてください";
+    entry_trashed: "Moved {} to the trash; T restores it within {} days", "「{}」をゴミ箱に移しました（Tで{}日以内なら戻せます）";
+    trash_disabled: "The trash is turned off in the config", "設定でゴミ箱が無効になっています";
+    trash_empty: "The trash is empty", "ゴミ箱は空です";
+    trash_summary: "{} deleted entries, kept for {} days. Enter restores one", "削除したエントリ: {}件（{}日間保管）。Enterで元に戻します";
+    trash_name_taken: "An entry named {} already exists; rename or delete it first", "「{}」という名前のエントリが既にあります。先に名前を変えるか削除してください";
+    entry_restored_from_trash: "Restored {} from the trash", "「{}」をゴミ箱から戻しました";
+    trash_purged: "Deleted {} for good", "「{}」を完全に削除しました";
+    scripts_unavailable: "No data directory for the script library", "スクリプトライブラリ用のデータディレクトリがありません";
+    scripts_summary: "{} scripts. Entries run one with !script NAME", "スクリプト: {}件。エントリからは !script 名前 で実行できます";
+    prompt_script_name: "New script name:", "新しいスクリプトの名前:";
+    script_created: "Created script '{}'", "スクリプト「{}」を作成しました";
+    script_deleted: "Deleted script '{}'", "スクリプト「{}」を削除しました";
+    script_in_use: "Script '{}' is still run by: {}", "スクリプト「{}」は次のエントリで使われています: {}";
+    script_used: "'{}' now runs script '{}'", "「{}」がスクリプト「{}」を実行するようにしました";
+    save_partial: "{} entries were not registered by the scheduler; {}: {}", "{}件のエントリーがスケジューラに登録されませんでした。{}: {}";
+    snooze_expired: "Snooze ended, re-enabled: {}", "スヌーズが終了し、再び有効にしました: {}";
+    snooze_unsupported: "Snooze is not available with the {} backend (disabled entries are removed)", "{}バックエンドではスヌーズできません（無効なエントリは削除されます）";
+    tags_updated: "Tags updated", "タグを更新しました";
+    tag_filter_set: "Showing entries tagged #{}", "タグ #{} のエントリを表示しています";
+    tag_filter_cleared: "Showing all entries", "すべてのエントリを表示しています";
+    no_tagged_entries: "No entries are tagged #{}", "タグ #{} のエントリはありません";
+    grouped_by_tag: "Grouped by tag", "タグごとにまとめて表示";
+    ungrouped: "Not grouped", "まとめずに表示";
+    untagged_group: "(untagged)", "（タグなし）";
+    entries_title_tagged: " Cron Entries (#{}: {} of {}) ", " Cronエントリ（#{}: {}／{}件） ";
+    env_updated: "Env preset updated", "環境変数プリセットを更新しました";
+    shell_updated: "Shell updated", "シェルを更新しました";
+    hooks_updated: "Hooks updated", "フックを更新しました";
+    ping_updated: "Ping URL updated", "Ping送信先を更新しました";
+    limits_updated: "Timeout and retries updated", "タイムアウトと再試行を更新しました";
+    bulk_edit_failed: "Bulk edit failed: {}", "一括編集に失敗しました: {}";
+    bulk_edit_has_errors: "Fix the errors first (e to edit again)", "先にエラーを修正してください（eで再編集）";
+    bulk_edit_unchanged: "No changes", "変更はありません";
+    bulk_edit_applied: "Applied: {} added, {} removed, {} changed", "適用しました: 追加{}件、削除{}件、変更{}件";
+    bulk_enabled: "Enabled {} entries matching {}: {}", "{}件のエントリを有効にしました（{}）: {}";
+    bulk_disabled: "Disabled {} entries matching {}: {}", "{}件のエントリを無効にしました（{}）: {}";
+    no_match: "No entries match {}", "{}に一致するエントリはありません";
+    name_updated: "Name updated", "名前を更新しました";
+    schedule_updated: "Schedule updated", "スケジュールを更新しました";
+    conversion_prompt: "{} can't run this schedule as written; choose a conversion", "{}ではこのスケジュールをそのまま実行できません。変換方法を選んでください";
+    entry_split: "Entry split into {} entries", "エントリを{}件に分割しました";
+    entries_added_split: "Added {} entries", "エントリを{}件追加しました";
+    schedule_updated_note: "Schedule updated ({})", "スケジュールを更新しました（{}）";
+    reloaded_from_disk: "Reloaded from disk: the entries were changed outside cron-manager", "ディスクから再読み込みしました（cron-manager の外でエントリが変更されました）";
+    entry_moved: "Moved '{}'", "「{}」を移動しました";
+    move_crosses_environment: "'{}' stays put: moving it past {} would change what it runs with", "「{}」は移動しません（{} をまたぐと実行時の環境が変わります）";
+    paste_failed: "Failed to read the clipboard: {}", "クリップボードを読み込めません: {}";
+    crowded_minute: "⚠ {} jobs start together at {} ({})", "⚠ {}件のジョブが {} に同時に開始します（{}）";
+    schedule_updated_invalid: "Schedule updated (Warning: Invalid cron schedule format)", "スケジュールを更新しました（警告: Cronスケジュールの形式が不正です）";
+    command_updated: "Command updated", "コマンドを更新しました";
+    timezone_updated: "Timezone updated", "タイムゾーンを更新しました";
+    converted_expression: "Converted {} expression to '{}'", "{}形式の式を「{}」に変換しました";
+    warning: "Warning: {}", "警告: {}";
+    cancelled: "Cancelled", "キャンセルしました";
+    quartz_expression: "Quartz: {}", "Quartz形式: {}";
+    no_quartz_equivalent: "No Quartz equivalent: {}", "Quartz形式に変換できません: {}";
+    no_log_file: "No log file for this entry (redirect its output to a file)", "このエントリにはログファイルがありません（出力をファイルにリダイレクトしてください）";
+    log_missing: "Log file does not exist yet: {}", "ログファイルがまだありません: {}";
+    log_opened: "Log: {}", "ログ: {}";
+    macro_applied: "Macro '{}' applied to {}", "マクロ「{}」を{}に適用しました";
+    macro_running: "Macro '{}' is running…", "マクロ「{}」を実行中…";
+    macro_ran: "Macro '{}': {}", "マクロ「{}」: {}";
+    macro_failed: "Macro '{}' failed: {}", "マクロ「{}」に失敗しました: {}";
+    prompt_log_search: "Search the log (text, or /regex/; empty to clear):", "ログを検索（文字列または /正規表現/。空欄で解除）:";
+    log_match: "Match {} of {} in the loaded lines (n: older, N: newer)", "一致 {}件目／読み込み済みの行で{}件（n: 前へ、N: 次へ）";
+    log_no_match: "No more matches for '{}'", "これ以上 '{}' に一致する行はありません";
+    simulation_summary: "{} runs in the next {} hours (nothing is executed)", "実行予定: {}回（今後{}時間、実際には実行しません）";
+    ready: "Ready", "準備完了";
+
+    // Screen elements
+    title: "Cron Manager [Backend: {}]", "Cron Manager [バックエンド: {}]";
+    simulated_time: " Simulated time: {} ", " シミュレーション時刻: {} ";
+    unsaved_changes: " (unsaved changes)", "（未保存の変更あり）";
+    writing_changes: "Writing the unsaved changes", "未保存の変更を書き込んでいます";
+    nothing_to_write: "No unsaved changes to write", "書き込む未保存の変更はありません";
+    dry_run_marker: " (dry run: nothing is written)", "（ドライラン: 書き込みません）";
+    dry_run_title: " {} would be written as ", " {} に書き込まれる内容 ";
+    dry_run_off: "Not a dry run; start with --dry-run to preview saves", "ドライランではありません。--dry-run で起動すると保存内容をプレビューできます";
+    dry_run_nothing_saved: "Nothing saved yet in this dry run", "このドライランではまだ何も保存していません";
+    header_status: "Status", "状態";
+    header_name: "Name", "名前";
+    header_schedule: "Schedule", "スケジュール";
+    header_command: "Command", "コマンド";
+    entries_title: " Cron Entries ({}) ", " Cronエントリ（{}件） ";
+    entries_title_window: " Cron Entries ({}-{} of {}) ", " Cronエントリ（{}-{}／{}件） ";
+    more_findings: " (+{} more)", "（他{}件）";
+    details_title: " Details ", " 詳細 ";
+    status_title: " Status ", " ステータス ";
+    saving: "{} Saving… ", "{} 保存中… ";
+    controls_title: " Controls ", " 操作 ";
+    label_schedule: "Schedule: ", "スケジュール: ";
+    label_next: "Next: ", "次回: ";
+    next_run: "{} ({}, in {})", "{}（{}、あと{}）";
+    never: "never", "なし";
+    at_startup: "at startup (when the scheduler starts)", "起動時（スケジューラの起動時）";
+    schedule_invalid: "{} (invalid)", "{}（不正）";
+    label_tags: " | Tags: {}", " | タグ: {}";
+    label_env: " | Env: {}", " | 環境変数: {}";
+    label_vars: " | Vars: {}", " | 変数: {}";
+    label_cwd: " | Dir: {}", " | 作業ディレクトリ: {}";
+    label_user: " | User: {}", " | 実行ユーザー: {}";
+    label_shell: " | Shell: {}", " | シェル: {}";
+    label_stdin: " | Stdin: {}", " | 標準入力: {}";
+    label_skips_excluded: " | Skips excluded dates", " | 除外日は実行しない";
+    label_hooks: " | Hooks: {}", " | フック: {}";
+    label_watchdog: " | Watchdog", " | 監視中";
+    label_ping: " | Pings {}", " | Ping送信先: {}";
+    label_ping_overdue: " | No ping since the run due {}", " | {} の実行からPingがありません";
+    label_timeout: " | Timeout: {}", " | タイムアウト: {}";
+    label_retries: " | Retries: {}", " | 再試行: {}回";
+    label_retries_delay: " | Retries: {} every {}", " | 再試行: {}回（{}間隔）";
+    hook_success: "on success", "成功時";
+    hook_failure: "on failure", "失敗時";
+    snoozed_until: " | Snoozed until {}", " | {}までスヌーズ中";
+    run_stats_title: " 30d: {}% ok, avg {} ", " 30日: 成功{}% 平均{} ";
+    not_registered: " | Not registered: {}", " | 未登録: {}";
+    frequency_summary: "{} runs in the next 24h, {} in 7d", "今後24時間で{}回、7日間で{}回";
+    no_entry_selected: "No entry selected", "エントリが選択されていません";
+    preview_title: " Preview ", " プレビュー ";
+    preview_state: "State: ", "状態: ";
+    preview_enabled: "enabled", "有効";
+    preview_disabled: "disabled", "無効";
+    preview_snoozed: "snoozed until {}", "{}までスヌーズ中";
+    preview_command: "Command:", "コマンド:";
+    preview_next_runs: "Next runs ({}):", "次回以降の実行（{}）:";
+    preview_on: "Preview on", "プレビューを表示";
+    preview_off: "Preview off", "プレビューを非表示";
+    describe_startup: "At startup", "起動時";
+    describe_every_minute: "Every minute", "毎分";
+    describe_every_minutes: "Every {} minutes", "{}分ごと";
+    describe_hourly: "Every hour at minute {}", "毎時{}分";
+    describe_every_hours: "Every {} hours at :{}", "{}時間ごと（{}分）";
+    describe_at: "At {}", "{}";
+    describe_minutes_of_hours: "At minute {} past hour {}", "{}分（{}時台）";
+    describe_weekdays: "on weekdays", "平日";
+    describe_days_of_week: "on {}", "{}曜日";
+    describe_days_of_month: "on day {} of the month", "毎月{}日";
+    describe_either: "{} or {}", "{}または{}";
+    describe_months: "in {}", "{}月";
+    describe_qualified: "{}, {}", "{}（{}）";
+    describe_list_separator: ", ", "、";
+    describe_name_separator: ", ", "・";
+    describe_day_names: "Sun,Mon,Tue,Wed,Thu,Fri,Sat", "日,月,火,水,木,金,土";
+    describe_month_names: "Jan,Feb,Mar,Apr,May,Jun,Jul,Aug,Sep,Oct,Nov,Dec", "1,2,3,4,5,6,7,8,9,10,11,12";
+    simulation_title: " Upcoming runs ({}) ", " 実行予定（{}件） ";
+    simulation_runs: "{} runs", "{}回";
+    calendar_week_title: " Week of {} ", " {}の週 ";
+    calendar_month_title: " {} ", " {} ";
+    calendar_busiest: "Busiest minute {}: {} jobs start together ({})", "最も混み合う時刻 {}: {}件が同時に開始（{}）";
+    calendar_no_clusters: "No two jobs start in the same minute", "同じ分に開始するジョブはありません";
+    scripts_title: " Scripts ({}) ", " スクリプト（{}件） ";
+    no_scripts: "No scripts yet; press n to create one", "スクリプトはまだありません（nで作成）";
+    script_used_by: "used by {}", "使用中: {}";
+    backups_title: " Backups ({}) ", " バックアップ（{}件） ";
+    backup_entries: "Entries (+ missing now, ~ changed since)", "エントリ（+ 現在は無い、~ 変更あり）";
+    changes_title: " Changed in the last {} days ({}) ", " 最近の変更（{}日間、{}件） ";
+    trash_title: " Trash ({}) ", " ゴミ箱（{}件） ";
+    push_title: " Sandbox changes to push to {} ({}) ", " {}に反映するサンドボックスの変更（{}件） ";
+    sandbox_opened: "Cloned {} entries into the sandbox; experiment freely, then press {} to push changes back", "{}件のエントリをサンドボックスに複製しました。自由に試してから、{}で変更を反映できます";
+    sandbox_in_sync: "The sandbox matches {}", "サンドボックスは{}と同じです";
+    push_summary: "{} differences from {}; Space to pick, Enter to push", "差分が{}件あります（{}との比較）。Spaceで選択、Enterで反映";
+    pushed_changes: "Pushed {} changes to {}", "{}件の変更を{}に反映しました";
+    sync_title: " Local file vs {} ({}): + only in the file, - only in the scheduler, ~ scheduler -> file ", " ローカルファイルと{}の差分（{}件）: + ファイルのみ、- スケジューラのみ、~ スケジューラ → ファイル ";
+    sync_unavailable: "Sync works between the local file and the system scheduler", "同期はローカルファイルとシステムスケジューラの間でのみ行えます";
+    sync_in_sync: "The local file and {} are in sync", "ローカルファイルと{}は同じです";
+    sync_summary: "{} differences between the local file and {}; p to push, u to pull, m to merge", "差分が{}件あります（ローカルファイルと{}）。pで反映、uで取り込み、mでマージ";
+    sync_conflicts: "{}; push or pull instead", "{}。反映（p）か取り込み（u）を選んでください";
+    synced_push: "Wrote the local file's {} entries to {}", "ローカルファイルの{}件のエントリを{}に反映しました";
+    synced_pull: "Replaced the local file with the {} entries of {}", "ローカルファイルを{}件のエントリで置き換えました（{}から）";
+    synced_merge: "Merged: {} entries in the local file and {}", "マージしました: {}件のエントリをローカルファイルと{}に保存";
+    push_partial: "{} entries were not registered by {}", "{}件のエントリが{}に登録されませんでした";
+    trash_deleted_at: "deleted {}, kept until {}", "{}に削除、{}まで保管";
+    backup_open_hint: "Press Enter to open the highlighted backup", "Enterで選択中のバックアップを開きます";
+    backup_diff_current: "Changes since this backup (rolling back undoes them)", "このバックアップ以降の変更（戻すと取り消されます）";
+    backup_diff_previous: "Changes made by this save", "この保存での変更";
+    backup_no_changes: "No differences", "差分はありません";
+    simulation_counts: "Runs per entry", "エントリごとの実行回数";
+    presets_title: " Presets (Enter to insert) ", " プリセット（Enterで挿入） ";
+    bulk_edit_title: " Review edit: {} added, {} removed, {} changed ", " 編集内容の確認: 追加{}件、削除{}件、変更{}件 ";
+    bulk_edit_errors: "Errors (fix them before applying):", "エラー（適用前に修正してください）:";
+    conversions_title: " Convert schedule (Enter to choose, Esc to edit) ", " スケジュールの変換（Enterで選択、Escで編集） ";
+    conversion_split: "Split into {} entries (exact)", "{}件のエントリに分割（同等）";
+    conversion_approximate: "Approximate: {} (runs less often)", "近似: {}（実行回数が減ります）";
+    conflicts_title: " Changed elsewhere since loaded (y: yours, t: theirs, m: merged, Esc: don't save) ", " 読み込み後に他で変更されました（y: 自分の変更、t: 相手の変更、m: マージ、Esc: 保存しない） ";
+    conflicts_found: "{} entries were also changed elsewhere; choose which version to keep", "{}件のエントリが他でも変更されています。残す内容を選んでください";
+    conflicts_resolved: "Conflicts resolved and saved", "競合を解決して保存しました";
+    conflicts_help: "O: overwrite everything with yours | R: reload theirs, dropping your changes", "O: すべて自分の内容で上書き | R: 相手の内容を再読み込み（自分の変更は破棄）";
+    conflicts_overwritten: "Saved your version over the changes made elsewhere", "他での変更を自分の内容で上書きして保存しました";
+    conflicts_reloaded: "Reloaded the version changed elsewhere; your unsaved changes were dropped", "他で変更された内容を再読み込みしました（未保存の変更は破棄しました）";
+    changes_merged: "Merged in changes made elsewhere since loading, and saved", "読み込み後に他で行われた変更を取り込んで保存しました";
+    conflicts_not_saved: "Not saved; the next save shows the conflicts again", "保存していません。次の保存時に再び競合を表示します";
+    conflict_deleted: "(deleted)", "（削除）";
+    conflict_yours: "yours", "自分";
+    conflict_theirs: "theirs", "相手";
+    conversion_keep: "Keep as written (won't be registered)", "そのまま保存（登録されません）";
+    log_title: " Log: {} ", " ログ: {} ";
+    log_at_end: "end", "末尾";
+    log_lines_up: "{} lines up", "{}行上";
+    log_more_above: ", more above", "、さらに上あり";
+    runs_title: " Runs of {} ({}) ", " {}の実行履歴（{}件） ";
+    runs_output: " Output (end) ", " 出力（末尾） ";
+    runs_no_output: "(no output recorded)", "（出力の記録なし）";
+    runs_signal: "killed", "強制終了";
+    runs_none: "No runs of {} recorded; set record_runs = true to record every entry", "{}の実行は記録されていません。すべてのエントリを記録するには record_runs = true を設定してください";
+    runs_summary: "{} runs in the last {} days", "{}回実行（過去{}日間）";
+    delete_title: " Delete this entry? ", " このエントリを削除しますか？ ";
+    delete_to_trash: "It stays in the trash for {} days ({})", "ゴミ箱に{}日間残ります（{}）";
+    delete_for_good: "There is no trash; this can't be undone", "ゴミ箱が無効なため、元に戻せません";
+    delete_cancelled: "Not deleted", "削除しませんでした";
+    theme_switched: "Theme: {}", "配色: {}";
+    prompt_import_path: "Import from (JSON/YAML/TOML export or crontab):", "取り込むファイル（JSON・YAML・TOMLのエクスポートまたはcrontab）:";
+    import_failed: "Can't import {}: {}", "{}を取り込めません: {}";
+    import_refused: "Not imported: {}: {}", "取り込みませんでした: {}: {}";
+    import_taken: "{} imported names are already in use", "取り込むエントリのうち{}件の名前が使用済みです";
+    import_title: " Names already in use ", " 使用済みの名前 ";
+    import_more: "and {} more", "ほか{}件";
+    import_done: "Imported {} entries, skipped {}", "{}件のエントリを取り込みました（スキップ {}件）";
+    import_cancelled: "Nothing imported", "取り込みを中止しました";
+    run_title: " Running now: {} ", " 手動実行: {} ";
+    run_running: "running {}s", "実行中 {}秒";
+    run_exit_code: "exit {} after {}s", "終了コード {}（{}秒）";
+    run_signal: "killed after {}s", "強制終了（{}秒）";
+    run_timed_out_after: "timed out after {}s", "タイムアウト（{}秒）";
+    run_retrying: "failed, retrying in {}s", "失敗、{}秒後に再試行";
+    run_attempt: ", retry {}/{}", "、再試行 {}/{}";
+    run_started: "Started {}; its output is shown as it arrives", "{}を実行しました。出力は届き次第表示します";
+    run_exited: "{} exited with code {} after {}s", "{}が終了しました（終了コード {}、{}秒）";
+    run_killed: "{} was killed after {}s", "{}は強制終了されました（{}秒）";
+    run_timed_out: "{} timed out after {}s", "{}はタイムアウトしました（{}秒）";
+    run_stopped: "Stopped {}", "{}を停止しました";
+    run_remote: "Can't run it here: the command belongs to {}", "ここでは実行できません。コマンドは{}のものです";
+
+    // Help bar
+    help_navigate: ": Navigate | ", ": 移動 | ";
+    help_add: ": Add | ", ": 追加 | ";
+    help_delete: ": Delete | ", ": 削除 | ";
+    help_toggle: ": Toggle Enable/Disable", ": 有効/無効の切り替え";
+    help_edit_name: ": Edit Name | ", ": 名前 | ";
+    help_edit_schedule: ": Edit Schedule | ", ": スケジュール | ";
+    help_edit_command: ": Edit Command | ", ": コマンド | ";
+    help_timezone: ": Timezone | ", ": タイムゾーン | ";
+    help_tags: ": Tags | ", ": タグ | ";
+    help_env: ": Env preset | ", ": 環境変数 | ";
+    help_shell: ": Shell | ", ": シェル | ";
+    help_bulk: ": Bulk toggle | ", ": 一括切り替え | ";
+    help_snooze: ": Snooze | ", ": スヌーズ | ";
+    help_quartz: ": Quartz | ", ": Quartz | ";
+    help_pager: ": Log in $PAGER | ", ": $PAGERでログ | ";
+    help_log: ": Log | ", ": ログ | ";
+    help_quit: ": Quit", ": 終了";
+    help_confirm: ": Confirm | ", ": 確定 | ";
+    help_cancel: ": Cancel", ": キャンセル";
+    help_scroll: ": Scroll | ", ": スクロール | ";
+    help_close_view: ": Close", ": 閉じる";
+    help_simulate: ": Next 24h | ", ": 24時間の予定 | ";
+    help_move: ": Move | ", ": 並べ替え | ";
+    help_calendar: ": Calendar | ", ": カレンダー | ";
+    help_calendar_shift: ": Previous/next | ", ": 前/次 | ";
+    help_calendar_span: ": Week/month | ", ": 週/月 | ";
+    help_calendar_today: ": Today | ", ": 今日 | ";
+    help_run_now: ": Run now | ", ": 今すぐ実行 | ";
+    help_yes_delete: ": Delete | ", ": 削除 | ";
+    help_no_keep: ": Keep", ": 削除しない";
+    help_runs: ": Run history | ", ": 実行履歴 | ";
+    help_import: ": Import | ", ": 取り込み | ";
+    help_theme: ": Theme | ", ": 配色 | ";
+    help_tag_filter: ": Filter by tag | ", ": タグで絞り込み | ";
+    help_group_by_tag: ": Group by tag | ", ": タグごとに表示 | ";
+    help_preview: ": Preview | ", ": プレビュー | ";
+    help_skip_taken: ": Skip | ", ": スキップ | ";
+    help_overwrite_taken: ": Overwrite | ", ": 上書き | ";
+    help_rename_taken: ": Rename | ", ": 名前を変えて追加 | ";
+    help_stop_run: ": Close (stops it if running)", ": 閉じる（実行中なら停止）";
+    help_presets: ": Presets | ", ": プリセット | ";
+    help_history: ": From history ", ": 履歴から ";
+    help_scripts: ": Scripts | ", ": スクリプト | ";
+    help_use_script: ": Use in entry | ", ": エントリで使う | ";
+    help_edit: ": Edit | ", ": 編集 | ";
+    help_new: ": New | ", ": 新規 | ";
+    help_backups: ": Backups | ", ": バックアップ | ";
+    help_comments: ": Comments | ", ": コメント | ";
+    help_changes: ": Recent changes | ", ": 最近の変更 | ";
+    help_dry_run: ": Dry-run output | ", ": ドライラン出力 | ";
+    help_write: ": Write now | ", ": 今すぐ保存 | ";
+    help_sync: ": Sync with the local file | ", ": ローカルファイルと同期 | ";
+    help_sync_push: ": Push to the scheduler | ", ": スケジューラに反映 | ";
+    help_sync_pull: ": Pull into the file | ", ": ファイルに取り込み | ";
+    help_sync_merge: ": Merge | ", ": マージ | ";
+    help_trash: ": Trash | ", ": ゴミ箱 | ";
+    help_sandbox: ": Sandbox | ", ": サンドボックス | ";
+    help_pick: ": Pick | ", ": 選択 | ";
+    help_push: ": Push | ", ": 反映 | ";
+    help_delete_forever: ": Delete for good | ", ": 完全に削除 | ";
+    help_skip_excluded: ": Skip excluded dates | ", ": 除外日スキップ | ";
+    help_hooks: ": Hooks | ", ": フック | ";
+    help_limits: ": Timeout/retries | ", ": タイムアウト/再試行 | ";
+    help_ping: ": Ping URL | ", ": Ping送信先 | ";
+    help_watchdog: ": Watchdog | ", ": 監視 | ";
+    help_bulk_edit: ": Edit all in $EDITOR | ", ": $EDITORで一括編集 | ";
+    help_apply: ": Apply | ", ": 適用 | ";
+    help_edit_again: ": Edit again | ", ": 再編集 | ";
+    help_discard: ": Discard", ": 破棄";
+    help_open_backup: ": Open | ", ": 開く | ";
+    help_restore_entry: ": Restore entry | ", ": エントリを復元 | ";
+    help_diff_current: ": Diff with now | ", ": 現在との差分 | ";
+    help_diff_previous: ": Diff with previous | ", ": 前回との差分 | ";
+    help_rollback: ": Roll back | ", ": この版に戻す | ";
+    help_back: ": Back", ": 戻る";
+    help_close_log: ": Close log", ": ログを閉じる";
+    help_search_log: ": Search  ", ": 検索  ";
+    help_next_match: ": Older/newer match  ", ": 前/次の一致  ";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundles_have_matching_placeholders() {
+        for (key, en, ja) in ALL {
+            assert_eq!(en.matches("{}").count(), ja.matches("{}").count(), "placeholders differ for {}", key);
+        }
+    }
+
+    #[test]
+    fn test_parse_and_fill() {
+        assert_eq!(Language::parse("ja_JP.UTF-8"), Some(Language::Japanese));
+        assert_eq!(Language::parse("en_US.UTF-8"), Some(Language::English));
+        assert_eq!(Language::parse("fr_FR"), None);
+        assert_eq!(Language::detect(Some("ja")), Language::Japanese);
+
+        assert_eq!(fill(EN.entry_enabled, &[&"backup"]), "Entry backup enabled");
+        assert_eq!(fill(JA.entries_title_window, &[&1, &20, &100]), " Cronエントリ（1-20／100件） ");
+    }
+}
@@ -0,0 +1,343 @@
+use crate::cron_entry::{self, CronEntry};
+use crate::env_presets;
+use crate::ping;
+use crate::shell::Shell;
+use crate::schedule::{timezone, CronSchedule};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The desired entries, kept in version control and converged onto the
+/// backend with `apply`:
+///
+/// ```toml
+/// [[job]]
+/// name = "Backup"
+/// schedule = "0 2 * * *"
+/// command = "/usr/local/bin/backup.sh"
+/// tags = ["backups"]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Jobfile {
+    #[serde(default, rename = "job")]
+    pub jobs: Vec<Job>,
+}
+
+/// One `[[job]]` table
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Job {
+    pub name: String,
+    pub schedule: String,
+    pub command: String,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub skip_excluded_dates: bool,
+    pub on_success: Option<String>,
+    pub on_failure: Option<String>,
+    #[serde(default)]
+    pub watchdog: bool,
+    /// Env preset from the config
+    pub env: Option<String>,
+    /// The job's own variables as `NAME=value`, set after the preset
+    #[serde(default)]
+    pub vars: Vec<String>,
+    /// Directory the command runs in
+    pub cwd: Option<PathBuf>,
+    /// Interpreter the command is written for: powershell, pwsh or cmd
+    pub shell: Option<Shell>,
+    /// Text fed to the command's standard input
+    pub stdin: Option<String>,
+    /// Comment lines written above the entry; left as they are when not given
+    pub comments: Option<Vec<String>>,
+    /// How long the command may run, like `90s` or `5m`
+    pub timeout: Option<String>,
+    /// Times a failed run is tried again, `retry_delay` apart
+    #[serde(default)]
+    pub retries: u32,
+    pub retry_delay: Option<String>,
+    /// Monitoring check pinged when a run starts and ends (may use `!secret`)
+    pub ping_url: Option<String>,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+impl Jobfile {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).with_context(|| format!("Failed to read jobfile: {:?}", path))?;
+        let jobfile = Self::parse(&content).with_context(|| format!("Invalid jobfile: {:?}", path))?;
+        Ok(jobfile)
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let jobfile: Self = toml::from_str(content)?;
+        let mut names = HashSet::new();
+        for job in &jobfile.jobs {
+            if !names.insert(job.name.as_str()) {
+                bail!("Duplicate job name '{}'", job.name);
+            }
+            CronSchedule::parse(&job.schedule).with_context(|| format!("Job '{}'", job.name))?;
+            if let Some(zone) = &job.timezone {
+                timezone::parse_tz(zone).with_context(|| format!("Job '{}'", job.name))?;
+            }
+            for line in &job.vars {
+                env_presets::parse_line(line).with_context(|| format!("Job '{}'", job.name))?;
+            }
+            for length in [&job.timeout, &job.retry_delay].into_iter().flatten() {
+                cron_entry::parse_seconds(length).with_context(|| format!("Job '{}'", job.name))?;
+            }
+            if let Some(url) = &job.ping_url {
+                if !ping::valid_url(url) {
+                    bail!("Job '{}': ping_url must be an http(s) URL or !secret NAME", job.name);
+                }
+            }
+        }
+        Ok(jobfile)
+    }
+}
+
+impl Job {
+    /// The entry this job describes. A snooze on the current entry is kept,
+    /// so applying the same jobfile again doesn't wake it early, and so are
+    /// its comments unless the job has its own.
+    fn to_entry(&self, current: Option<&CronEntry>) -> CronEntry {
+        let mut entry = CronEntry::new(self.name.clone(), self.schedule.clone(), self.command.clone());
+        entry.enabled = self.enabled;
+        entry.timezone = self.timezone.clone();
+        entry.tags = self.tags.clone();
+        entry.skip_excluded = self.skip_excluded_dates;
+        entry.on_success = self.on_success.clone();
+        entry.on_failure = self.on_failure.clone();
+        entry.watchdog = self.watchdog;
+        entry.env = self.env.clone();
+        // Checked when the jobfile was parsed
+        entry.env_vars = self
+            .vars
+            .iter()
+            .filter_map(|line| env_presets::parse_line(line).ok())
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        entry.cwd = self.cwd.clone();
+        entry.shell = self.shell;
+        entry.stdin = self.stdin.clone();
+        // Checked when the jobfile was parsed
+        entry.timeout_secs = self.timeout.as_deref().and_then(|t| cron_entry::parse_seconds(t).ok());
+        entry.retries = self.retries;
+        entry.ping_url = self.ping_url.clone();
+        entry.retry_delay_secs = self.retry_delay.as_deref().and_then(|d| cron_entry::parse_seconds(d).ok()).unwrap_or(0);
+        entry.comments = match &self.comments {
+            Some(comments) => comments.clone(),
+            None => current.map(|c| c.comments.clone()).unwrap_or_default(),
+        };
+        if let Some(until) = current.and_then(|c| c.snoozed_until).filter(|_| self.enabled) {
+            entry.snooze(until);
+        }
+        entry
+    }
+}
+
+/// One step of a plan; in JSON, an object tagged with `"action"`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+pub enum Change {
+    Create(CronEntry),
+    /// The entry before and after, and the names of the fields that differ
+    Update { before: Box<CronEntry>, after: CronEntry, fields: Vec<&'static str> },
+    Delete(CronEntry),
+}
+
+/// What `apply` would do to bring the entries in line with a jobfile
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Plan {
+    pub changes: Vec<Change>,
+}
+
+impl Plan {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Numbers of entries to create, update and delete
+    pub fn counts(&self) -> (usize, usize, usize) {
+        self.changes.iter().fold((0, 0, 0), |(c, u, d), change| match change {
+            Change::Create(_) => (c + 1, u, d),
+            Change::Update { .. } => (c, u + 1, d),
+            Change::Delete(_) => (c, u, d + 1),
+        })
+    }
+
+    /// The plan for machines, e.g. to post on a pull request before applying:
+    /// `{"summary": {"create": 1, "update": 0, "delete": 0}, "changes": [...]}`
+    pub fn to_json(&self) -> serde_json::Value {
+        let (create, update, delete) = self.counts();
+        serde_json::json!({
+            "summary": { "create": create, "update": update, "delete": delete },
+            "changes": self.changes,
+        })
+    }
+
+    /// Apply the changes: updates in place, creations at the end
+    pub fn apply(&self, entries: &mut Vec<CronEntry>) {
+        for change in &self.changes {
+            match change {
+                Change::Create(entry) => entries.push(entry.clone()),
+                Change::Update { after, .. } => {
+                    if let Some(current) = entries.iter_mut().find(|e| e.name == after.name) {
+                        *current = after.clone();
+                    }
+                }
+                Change::Delete(entry) => entries.retain(|e| e.name != entry.name),
+            }
+        }
+    }
+}
+
+/// Compare the jobfile with the current entries by name. Entries missing
+/// from the jobfile are only deleted with `prune`.
+pub fn plan(jobfile: &Jobfile, current: &[CronEntry], prune: bool) -> Plan {
+    let mut changes = Vec::new();
+    for job in &jobfile.jobs {
+        let existing = current.iter().find(|e| e.name == job.name);
+        let after = job.to_entry(existing);
+        match existing {
+            None => changes.push(Change::Create(after)),
+            Some(before) => {
+                let fields = changed_fields(before, &after);
+                if !fields.is_empty() {
+                    changes.push(Change::Update { before: Box::new(before.clone()), after, fields });
+                }
+            }
+        }
+    }
+    if prune {
+        let names: HashSet<&str> = jobfile.jobs.iter().map(|j| j.name.as_str()).collect();
+        changes.extend(
+            current
+                .iter()
+                .filter(|e| !names.contains(e.name.as_str()))
+                .map(|e| Change::Delete(e.clone())),
+        );
+    }
+    Plan { changes }
+}
+
+fn changed_fields(before: &CronEntry, after: &CronEntry) -> Vec<&'static str> {
+    let checks = [
+        ("schedule", before.schedule != after.schedule),
+        ("command", before.command != after.command),
+        ("enabled", before.enabled != after.enabled),
+        ("timezone", before.timezone != after.timezone),
+        ("tags", before.tags != after.tags),
+        ("skip_excluded_dates", before.skip_excluded != after.skip_excluded),
+        ("on_success", before.on_success != after.on_success),
+        ("on_failure", before.on_failure != after.on_failure),
+        ("watchdog", before.watchdog != after.watchdog),
+        ("env", before.env != after.env),
+        ("vars", before.env_vars != after.env_vars),
+        ("cwd", before.cwd != after.cwd),
+        ("shell", before.shell != after.shell),
+        ("stdin", before.stdin != after.stdin),
+        ("comments", before.comments != after.comments),
+        ("timeout", before.timeout_secs != after.timeout_secs),
+        ("retries", before.retries != after.retries),
+        ("retry_delay", before.retry_delay_secs != after.retry_delay_secs),
+        ("ping_url", before.ping_url != after.ping_url),
+    ];
+    checks.into_iter().filter(|(_, changed)| *changed).map(|(field, _)| field).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JOBS: &str = r#"
+[[job]]
+name = "Backup"
+schedule = "0 2 * * *"
+command = "/bin/backup"
+tags = ["backups"]
+
+[[job]]
+name = "Report"
+schedule = "0 9 * * 1-5"
+command = "/bin/report"
+enabled = false
+"#;
+
+    #[test]
+    fn test_plan_and_apply() {
+        let jobfile = Jobfile::parse(JOBS).unwrap();
+        let mut entries = vec![
+            CronEntry::new("Backup".to_string(), "0 3 * * *".to_string(), "/bin/backup".to_string()),
+            CronEntry::new("Legacy".to_string(), "0 4 * * *".to_string(), "/bin/legacy".to_string()),
+        ];
+
+        let plan = plan(&jobfile, &entries, false);
+        assert_eq!(plan.counts(), (1, 1, 0));
+        let Change::Update { fields, .. } = &plan.changes[0] else { panic!("expected an update") };
+        assert_eq!(fields, &["schedule", "tags"]);
+
+        let json = plan.to_json();
+        assert_eq!(json["summary"]["create"], 1);
+        assert_eq!(json["changes"][0]["action"], "update");
+        assert_eq!(json["changes"][0]["before"]["schedule"], "0 3 * * *");
+        assert_eq!(json["changes"][0]["after"]["schedule"], "0 2 * * *");
+        assert_eq!(json["changes"][1]["action"], "create");
+        assert_eq!(json["changes"][1]["name"], "Report");
+
+        plan.apply(&mut entries);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].schedule, "0 2 * * *");
+        assert!(!entries[2].enabled);
+
+        // Applying again changes nothing; pruning removes the unmanaged entry
+        assert!(super::plan(&jobfile, &entries, false).is_empty());
+        let prune = super::plan(&jobfile, &entries, true);
+        assert_eq!(prune.counts(), (0, 0, 1));
+        prune.apply(&mut entries);
+        assert_eq!(entries.len(), 2);
+    }
+
+    #[test]
+    fn test_invalid_jobfiles() {
+        assert!(Jobfile::parse("[[job]]\nname = \"A\"\nschedule = \"0 25 * * *\"\ncommand = \"x\"\n").is_err());
+        assert!(Jobfile::parse("[[job]]\nname = \"A\"\nschedule = \"0 2 * * *\"\ncommand = \"x\"\ncolour = 1\n").is_err());
+        let twice = format!("{}{}", JOBS, "[[job]]\nname = \"Backup\"\nschedule = \"0 1 * * *\"\ncommand = \"y\"\n");
+        assert!(Jobfile::parse(&twice).is_err());
+        assert!(Jobfile::parse("[[job]]\nname = \"A\"\nschedule = \"0 2 * * *\"\ncommand = \"x\"\nvars = [\"NO VALUE\"]\n").is_err());
+        assert!(Jobfile::parse("[[job]]\nname = \"A\"\nschedule = \"0 2 * * *\"\ncommand = \"x\"\nping_url = \"hc-ping.com/x\"\n").is_err());
+    }
+
+    #[test]
+    fn test_vars_and_cwd() {
+        let jobfile = Jobfile::parse(
+            "[[job]]\nname = \"Deploy\"\nschedule = \"0 3 * * *\"\ncommand = \"./deploy.sh\"\n\
+             vars = [\"RAILS_ENV=production\", \"PATH=/opt/app/bin:$PATH\"]\ncwd = \"/srv/app\"\n",
+        )
+        .unwrap();
+        let entry = jobfile.jobs[0].to_entry(None);
+        assert_eq!(
+            entry.env_vars,
+            [
+                ("RAILS_ENV".to_string(), "production".to_string()),
+                ("PATH".to_string(), "/opt/app/bin:$PATH".to_string())
+            ]
+        );
+        assert_eq!(entry.cwd.as_deref(), Some(Path::new("/srv/app")));
+
+        let mut current = entry.clone();
+        current.cwd = None;
+        let plan = plan(&jobfile, &[current], false);
+        let Change::Update { fields, .. } = &plan.changes[0] else { panic!("expected an update") };
+        assert_eq!(fields, &["cwd"]);
+    }
+}
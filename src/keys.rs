@@ -0,0 +1,180 @@
+use anyhow::{bail, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// What a key does in the entry list, named as in the `[keys]` config section
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    Write,
+    Up,
+    Down,
+    MoveUp,
+    MoveDown,
+    Add,
+    Delete,
+    Toggle,
+    EditName,
+    EditSchedule,
+    EditCommand,
+    EditTimezone,
+    EditTags,
+    EditEnv,
+    EditShell,
+    EditHooks,
+    EditLimits,
+    EditPing,
+    EditComments,
+    Snooze,
+    SkipExcluded,
+    Watchdog,
+    BulkEdit,
+    BulkToggle,
+    Quartz,
+    Log,
+    LogInPager,
+    Upcoming,
+    Calendar,
+    RunNow,
+    Runs,
+    Import,
+    Scripts,
+    Backups,
+    Changes,
+    DryRun,
+    Sync,
+    Trash,
+    Sandbox,
+    Theme,
+    Preview,
+    TagFilter,
+    GroupByTag,
+}
+
+/// The keys every action has unless the config file says otherwise
+const DEFAULT_KEYS: &[(Action, char)] = &[
+    (Action::Quit, 'q'),
+    (Action::Write, 'F'),
+    (Action::Up, 'k'),
+    (Action::Down, 'j'),
+    (Action::MoveUp, '['),
+    (Action::MoveDown, ']'),
+    (Action::Add, 'a'),
+    (Action::Delete, 'd'),
+    (Action::Toggle, ' '),
+    (Action::EditName, 'n'),
+    (Action::EditSchedule, 's'),
+    (Action::EditCommand, 'c'),
+    (Action::EditTimezone, 'z'),
+    (Action::EditTags, 't'),
+    (Action::EditEnv, 'v'),
+    (Action::EditShell, 'P'),
+    (Action::EditHooks, 'h'),
+    (Action::EditLimits, 'o'),
+    (Action::EditPing, 'u'),
+    (Action::EditComments, '#'),
+    (Action::Snooze, 'S'),
+    (Action::SkipExcluded, 'H'),
+    (Action::Watchdog, 'w'),
+    (Action::BulkEdit, 'E'),
+    (Action::BulkToggle, 'b'),
+    (Action::Quartz, 'Q'),
+    (Action::Log, 'l'),
+    (Action::LogInPager, 'L'),
+    (Action::Upcoming, 'r'),
+    (Action::Calendar, 'm'),
+    (Action::RunNow, 'X'),
+    (Action::Runs, 'i'),
+    (Action::Import, 'I'),
+    (Action::Scripts, 'x'),
+    (Action::Backups, 'B'),
+    (Action::Changes, 'R'),
+    (Action::DryRun, 'W'),
+    (Action::Sync, 'U'),
+    (Action::Trash, 'T'),
+    (Action::Sandbox, 'C'),
+    (Action::Theme, 'Y'),
+    (Action::Preview, 'p'),
+    (Action::TagFilter, 'f'),
+    (Action::GroupByTag, 'G'),
+];
+
+/// `[keys]` section: the key of every entry-list action, with the defaults
+/// replaced by the actions the config file rebinds. The arrow keys always
+/// move the selection too.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "BTreeMap<Action, char>")]
+pub struct KeyMap {
+    keys: Vec<(Action, char)>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self { keys: DEFAULT_KEYS.to_vec() }
+    }
+}
+
+impl TryFrom<BTreeMap<Action, char>> for KeyMap {
+    type Error = anyhow::Error;
+
+    fn try_from(overrides: BTreeMap<Action, char>) -> Result<Self> {
+        Self::with(&overrides)
+    }
+}
+
+impl KeyMap {
+    /// The defaults with some actions moved to other keys; two actions can't share one
+    pub fn with(overrides: &BTreeMap<Action, char>) -> Result<Self> {
+        let mut map = Self::default();
+        for (action, key) in &mut map.keys {
+            if let Some(custom) = overrides.get(action) {
+                *key = *custom;
+            }
+        }
+        for (index, (action, key)) in map.keys.iter().enumerate() {
+            if let Some((other, _)) = map.keys[..index].iter().find(|(_, k)| k == key) {
+                bail!("The key '{}' is bound to both {:?} and {:?}", key, other, action);
+            }
+        }
+        Ok(map)
+    }
+
+    /// The action bound to the key, if any
+    pub fn action(&self, key: char) -> Option<Action> {
+        self.keys.iter().find(|(_, k)| *k == key).map(|(action, _)| *action)
+    }
+
+    pub fn key(&self, action: Action) -> char {
+        self.keys.iter().find(|(a, _)| *a == action).map_or(' ', |(_, key)| *key)
+    }
+
+    /// The action's key as shown in the help line
+    pub fn label(&self, action: Action) -> String {
+        match self.key(action) {
+            ' ' => "Space".to_string(),
+            key => key.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rebinding_frees_the_default_key() {
+        let map: KeyMap = toml::from_str::<BTreeMap<Action, char>>("delete = \"D\"\ntoggle = \"e\"\n")
+            .map_err(anyhow::Error::from)
+            .and_then(KeyMap::try_from)
+            .unwrap();
+        assert_eq!(map.action('D'), Some(Action::Delete));
+        assert_eq!(map.action('d'), None);
+        assert_eq!(map.label(Action::Toggle), "e");
+        assert_eq!(KeyMap::default().label(Action::Toggle), "Space");
+
+        let clash = BTreeMap::from([(Action::Add, 'd')]);
+        assert!(KeyMap::with(&clash).is_err());
+        assert!(toml::from_str::<BTreeMap<Action, char>>("launch = \"x\"\n").is_err());
+    }
+}
@@ -1,6 +1,53 @@
+pub mod agent;
+pub mod analysis;
 pub mod app;
+pub mod archive;
+pub mod audit;
+pub mod backup;
+pub mod bulk_edit;
+pub mod cache;
+pub mod cli;
+pub mod clipboard;
+pub mod clock;
+pub mod config;
+pub mod conflict;
 pub mod cron_entry;
 pub mod cron_parser;
+pub mod env_presets;
+pub mod export;
+pub mod filter;
+pub mod history;
+pub mod holidays;
+pub mod hooks;
+pub mod i18n;
+pub mod jobfile;
+pub mod keys;
+pub mod log_capture;
+pub mod log_search;
+pub mod log_tail;
+pub mod logging;
+pub mod macros;
+pub mod mail;
+pub mod notify;
+pub mod paths;
+pub mod ping;
+pub mod policy;
+pub mod privilege;
+pub mod refresh_job;
+pub mod retry;
+pub mod run_history;
+pub mod run_now;
+pub mod save_job;
+pub mod schedule;
 pub mod scheduler;
+pub mod scripts;
+pub mod secrets;
+pub mod shell;
 pub mod storage;
+pub mod sync;
+#[cfg(test)]
+mod test_util;
+pub mod theme;
+pub mod trash;
 pub mod ui;
+pub mod watchdog;
@@ -0,0 +1,134 @@
+use crate::config::LogsConfig;
+use crate::secrets::shell_quote;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Directory inside the data directory that captured output goes to
+pub const LOG_DIR: &str = "logs";
+
+/// The entry's log file in `dir`, named after the entry
+pub fn log_file(dir: &Path, name: &str) -> PathBuf {
+    let mut slug = String::new();
+    for c in name.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    dir.join(format!("{}.log", if slug.is_empty() { "entry" } else { slug }))
+}
+
+/// `command` with its output and errors appended to `log`, after the log
+/// has been rotated; one compound command, so it can follow `cd … &&`
+pub fn capture_command(command: &str, log: &Path) -> Result<String> {
+    let exe = std::env::current_exe().context("Failed to locate the cron-manager executable")?;
+    let log = shell_quote(&log.to_string_lossy());
+    // If the rotation can't run, the job still runs and appends as usual
+    Ok(format!(
+        "{{ {} logs rotate {}; sh -c {} >> {} 2>&1; }}",
+        shell_quote(&exe.to_string_lossy()),
+        log,
+        shell_quote(command),
+        log
+    ))
+}
+
+/// The `n`th rotated copy of the log (`backup.log.1` is the newest)
+fn rotated(log: &Path, n: usize) -> PathBuf {
+    let mut name = log.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// Move the log aside once it has grown past the size limit, keeping the
+/// configured number of older copies, and delete copies past the age limit;
+/// returns whether the log was moved
+pub fn rotate(log: &Path, config: &LogsConfig) -> Result<bool> {
+    let too_old = |path: &Path| {
+        let age = fs::metadata(path).and_then(|meta| meta.modified()).ok().and_then(|time| SystemTime::now().duration_since(time).ok());
+        config.max_age_days > 0 && age.is_some_and(|age| age > Duration::from_secs(u64::from(config.max_age_days) * 86_400))
+    };
+    for n in 1..=config.keep {
+        let copy = rotated(log, n);
+        if copy.exists() && too_old(&copy) {
+            fs::remove_file(&copy).with_context(|| format!("Failed to remove {:?}", copy))?;
+        }
+    }
+
+    let size = match fs::metadata(log) {
+        Ok(meta) => meta.len(),
+        Err(_) => return Ok(false),
+    };
+    if size < config.max_size_kb * 1024 {
+        return Ok(false);
+    }
+    if config.keep == 0 {
+        fs::remove_file(log).with_context(|| format!("Failed to remove {:?}", log))?;
+        return Ok(true);
+    }
+    let _ = fs::remove_file(rotated(log, config.keep));
+    for n in (1..config.keep).rev() {
+        let copy = rotated(log, n);
+        if copy.exists() {
+            fs::rename(&copy, rotated(log, n + 1)).with_context(|| format!("Failed to rotate {:?}", copy))?;
+        }
+    }
+    fs::rename(log, rotated(log, 1)).with_context(|| format!("Failed to rotate {:?}", log))?;
+    Ok(true)
+}
+
+/// Rotate every log in the directory
+pub fn rotate_all(dir: &Path, config: &LogsConfig) -> Result<usize> {
+    let mut rotated = 0;
+    let Ok(files) = fs::read_dir(dir) else {
+        return Ok(0);
+    };
+    for file in files.flatten() {
+        let path = file.path();
+        if path.extension().is_some_and(|extension| extension == "log") && rotate(&path, config)? {
+            rotated += 1;
+        }
+    }
+    Ok(rotated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn test_log_file_and_command() {
+        let dir = Path::new("/data/logs");
+        assert_eq!(log_file(dir, "Nightly build (prod)"), dir.join("nightly-build-prod.log"));
+        assert_eq!(log_file(dir, "夜間 バックアップ"), dir.join("夜間-バックアップ.log"));
+        assert_eq!(log_file(dir, "!!!"), dir.join("entry.log"));
+
+        let command = capture_command("make release", &dir.join("build.log")).unwrap();
+        assert!(command.starts_with("{ "));
+        assert!(command.ends_with(" logs rotate /data/logs/build.log; sh -c 'make release' >> /data/logs/build.log 2>&1; }"));
+    }
+
+    #[test]
+    fn test_rotate_by_size() {
+        let dir = TempDir::new("logs");
+        let log = dir.join("backup.log");
+        let config = LogsConfig { capture: true, max_size_kb: 1, keep: 2, max_age_days: 30 };
+
+        fs::write(&log, "small\n").unwrap();
+        assert!(!rotate(&log, &config).unwrap());
+        for run in ["first", "second", "third"] {
+            fs::write(&log, run.repeat(300)).unwrap();
+            assert!(rotate(&log, &config).unwrap());
+        }
+        assert!(!log.exists());
+        assert!(fs::read_to_string(rotated(&log, 1)).unwrap().starts_with("third"));
+        assert!(fs::read_to_string(rotated(&log, 2)).unwrap().starts_with("second"));
+        assert!(!rotated(&log, 3).exists());
+        assert_eq!(rotate_all(dir.path(), &config).unwrap(), 0);
+    }
+}
@@ -0,0 +1,73 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// Compile a search: `/pattern/` is a regular expression, anything else is
+/// plain text matched without regard to case
+pub fn compile(input: &str) -> Result<Regex> {
+    match input.strip_prefix('/').and_then(|rest| rest.strip_suffix('/')) {
+        Some(pattern) if !pattern.is_empty() => {
+            Regex::new(pattern).with_context(|| format!("Invalid regular expression '{}'", pattern))
+        }
+        _ => Ok(Regex::new(&format!("(?i){}", regex::escape(input)))?),
+    }
+}
+
+/// A matching line of a log file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogMatch {
+    /// 1-based line number
+    pub line: usize,
+    pub text: String,
+}
+
+/// Every matching line of the file, read a line at a time so huge logs
+/// aren't loaded whole
+pub fn search_file(path: &Path, pattern: &Regex) -> Result<Vec<LogMatch>> {
+    let file = File::open(path).with_context(|| format!("Failed to open log file: {:?}", path))?;
+    let mut reader = BufReader::new(file);
+    let mut matches = Vec::new();
+    let mut buffer = Vec::new();
+    let mut line = 0;
+    while reader.read_until(b'\n', &mut buffer)? > 0 {
+        line += 1;
+        let text = String::from_utf8_lossy(&buffer);
+        let text = text.trim_end_matches(['\n', '\r']);
+        if pattern.is_match(text) {
+            matches.push(LogMatch { line, text: text.to_string() });
+        }
+        buffer.clear();
+    }
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn test_search() {
+        let plain = compile("error (disk)").unwrap();
+        assert!(plain.is_match("ERROR (disk) full"));
+        assert!(!plain.is_match("error disk"));
+        let regex = compile("/exit [1-9]/").unwrap();
+        assert!(regex.is_match("exit 2"));
+        assert!(!regex.is_match("exit 0"));
+        assert!(compile("/(/").is_err());
+
+        let dir = TempDir::new("search");
+        let path = dir.join("search.log");
+        std::fs::write(&path, "started\nError: disk full\r\ndone\nerror again").unwrap();
+        let matches = search_file(&path, &compile("error").unwrap()).unwrap();
+        assert_eq!(
+            matches,
+            [
+                LogMatch { line: 2, text: "Error: disk full".to_string() },
+                LogMatch { line: 4, text: "error again".to_string() },
+            ]
+        );
+    }
+}
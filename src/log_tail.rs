@@ -0,0 +1,139 @@
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Bytes read per step when scanning backwards for line breaks
+const CHUNK_SIZE: u64 = 64 * 1024;
+
+/// The last lines of a (possibly huge) log file. Only the loaded window is
+/// kept in memory; earlier lines are read on demand with `load_more`.
+#[derive(Debug)]
+pub struct LogTail {
+    path: PathBuf,
+    /// Loaded lines, oldest first
+    pub lines: Vec<String>,
+    // Byte offset in the file where `lines[0]` starts
+    start: u64,
+}
+
+impl LogTail {
+    /// Load the last `count` lines of the file
+    pub fn open(path: &Path, count: usize) -> Result<Self> {
+        let len = path
+            .metadata()
+            .with_context(|| format!("Failed to read log file: {:?}", path))?
+            .len();
+        let mut tail = Self {
+            path: path.to_path_buf(),
+            lines: Vec::new(),
+            start: len,
+        };
+        tail.load_more(count)?;
+        Ok(tail)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the whole file has been loaded
+    pub fn at_start(&self) -> bool {
+        self.start == 0
+    }
+
+    /// Load up to `count` lines preceding the loaded window; returns how many
+    /// were added
+    pub fn load_more(&mut self, count: usize) -> Result<usize> {
+        if self.at_start() || count == 0 {
+            return Ok(0);
+        }
+
+        let mut file = File::open(&self.path)
+            .with_context(|| format!("Failed to open log file: {:?}", self.path))?;
+
+        // Read backwards until the buffer holds `count` complete lines, plus
+        // the line break before the first of them
+        let mut buffer = Vec::new();
+        let mut pos = self.start;
+        while pos > 0 && buffer.iter().filter(|b| **b == b'\n').count() <= count {
+            let size = CHUNK_SIZE.min(pos);
+            pos -= size;
+            let mut chunk = vec![0; size as usize];
+            file.seek(SeekFrom::Start(pos))?;
+            file.read_exact(&mut chunk)?;
+            chunk.extend_from_slice(&buffer);
+            buffer = chunk;
+        }
+
+        // The break that ends the last line belongs to the window already loaded
+        if buffer.last() == Some(&b'\n') {
+            buffer.pop();
+        }
+        let mut segments: Vec<&[u8]> = buffer.split(|b| *b == b'\n').collect();
+
+        // Keep at most `count` lines; anything before them (including a
+        // partial line cut by the chunk boundary) is left for the next call
+        let keep_from = if pos > 0 {
+            segments.len().saturating_sub(count).max(1)
+        } else {
+            segments.len().saturating_sub(count)
+        };
+        let skipped: usize = segments[..keep_from].iter().map(|s| s.len() + 1).sum();
+        let new_lines: Vec<String> = segments
+            .drain(keep_from..)
+            .map(|s| String::from_utf8_lossy(s).into_owned())
+            .collect();
+
+        self.start = pos + skipped as u64;
+        let added = new_lines.len();
+        self.lines.splice(0..0, new_lines);
+        Ok(added)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn write_log(dir: &TempDir, content: &str) -> PathBuf {
+        let path = dir.join("job.log");
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_tail_and_load_more() {
+        let content: String = (1..=10).map(|i| format!("line {}\n", i)).collect();
+        let dir = TempDir::new("tail");
+        let path = write_log(&dir, &content);
+
+        let mut tail = LogTail::open(&path, 3).unwrap();
+        assert_eq!(tail.lines, ["line 8", "line 9", "line 10"]);
+        assert!(!tail.at_start());
+
+        assert_eq!(tail.load_more(5).unwrap(), 5);
+        assert_eq!(tail.lines.first().unwrap(), "line 3");
+
+        assert_eq!(tail.load_more(100).unwrap(), 2);
+        assert_eq!(tail.lines.len(), 10);
+        assert!(tail.at_start());
+    }
+
+    #[test]
+    fn test_lines_longer_than_a_chunk() {
+        let long = "x".repeat(CHUNK_SIZE as usize + 10);
+        let dir = TempDir::new("long");
+        let path = write_log(&dir, &format!("first\n{}\nlast", long));
+
+        let mut tail = LogTail::open(&path, 2).unwrap();
+        assert_eq!(tail.lines.len(), 2);
+        assert_eq!(tail.lines[0], long);
+        assert_eq!(tail.lines[1], "last");
+
+        tail.load_more(10).unwrap();
+        assert_eq!(tail.lines[0], "first");
+        assert!(tail.at_start());
+    }
+}
@@ -0,0 +1,43 @@
+use crate::paths;
+use anyhow::{Context, Result};
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing_subscriber::EnvFilter;
+
+/// Environment variable holding the log filter, in `RUST_LOG` syntax
+/// (e.g. `debug` or `cron_manager::scheduler=trace`)
+pub const LOG_ENV: &str = "CRON_MANAGER_LOG";
+
+/// Log file name inside the data directory
+const LOG_FILE: &str = "cron-manager.log";
+
+/// Start writing diagnostic logs to a file when `CRON_MANAGER_LOG` is set.
+/// Logs never go to the terminal, so they can't corrupt the TUI. Returns the
+/// log file path when logging was enabled.
+pub fn init(data_dir: Option<&Path>) -> Result<Option<PathBuf>> {
+    let filter = match std::env::var(LOG_ENV) {
+        Ok(filter) if !filter.trim().is_empty() => filter,
+        _ => return Ok(None),
+    };
+    let filter = EnvFilter::try_new(&filter)
+        .with_context(|| format!("Invalid {} filter: {}", LOG_ENV, filter))?;
+
+    let dir = paths::data_dir(data_dir).context("Could not determine the data directory for logs")?;
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create log directory: {:?}", dir))?;
+    let path = dir.join(LOG_FILE);
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open log file: {:?}", path))?;
+
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(Mutex::new(file))
+        .with_ansi(false)
+        .init();
+
+    tracing::info!(version = env!("CARGO_PKG_VERSION"), "logging started");
+    Ok(Some(path))
+}
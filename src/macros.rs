@@ -0,0 +1,258 @@
+use crate::cron_entry::{self, CronEntry};
+use crate::keys::KeyMap;
+use crate::schedule::{self, CronSchedule};
+use crate::shell::Shell;
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Deserialize;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+/// A key bound in `[[macros]]` to either steps applied to the selected entry
+/// or an external command that gets the selected entry as JSON on stdin
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Macro {
+    pub key: char,
+    /// Shown in messages and the help line (defaults to the key)
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub steps: Vec<String>,
+    #[serde(default)]
+    pub command: Option<String>,
+}
+
+impl Macro {
+    pub fn label(&self) -> String {
+        self.name.clone().unwrap_or_else(|| self.key.to_string())
+    }
+}
+
+/// One step of a macro, written as `<action> [argument]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    /// Copy the entry below itself; the following steps change the copy
+    Duplicate,
+    Enable,
+    Disable,
+    Schedule(String),
+    Command(String),
+    Tag(String),
+    Untag(String),
+    /// Env preset name; empty clears it
+    Env(String),
+    /// IANA timezone; empty clears it
+    Timezone(String),
+    /// Interpreter name; empty or `sh` clears it
+    Shell(Option<Shell>),
+}
+
+impl Step {
+    pub fn parse(input: &str) -> Result<Self> {
+        let input = input.trim();
+        let (action, argument) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+        let argument = argument.trim().to_string();
+        let needs_argument = |step: fn(String) -> Step| {
+            if argument.is_empty() {
+                bail!("'{}' needs an argument", action);
+            }
+            Ok(step(argument.clone()))
+        };
+        Ok(match action {
+            "duplicate" => Self::Duplicate,
+            "enable" => Self::Enable,
+            "disable" => Self::Disable,
+            "schedule" => needs_argument(Self::Schedule)?,
+            "command" => needs_argument(Self::Command)?,
+            "tag" => needs_argument(Self::Tag)?,
+            "untag" => needs_argument(Self::Untag)?,
+            "env" => Self::Env(argument),
+            "timezone" => Self::Timezone(argument),
+            "shell" => Self::Shell(Shell::parse(&argument)?),
+            _ => bail!(
+                "Unknown step '{}' (use duplicate, enable, disable, schedule, command, tag, untag, env, timezone or shell)",
+                input
+            ),
+        })
+    }
+}
+
+/// Check keys are free (not bound in the key map) and unique, and each macro
+/// has either valid steps or a command
+pub fn validate(macros: &[Macro], keys: &KeyMap) -> Result<()> {
+    for (index, binding) in macros.iter().enumerate() {
+        let context = || format!("Macro '{}'", binding.label());
+        if keys.action(binding.key).is_some() {
+            bail!("{}: the key '{}' is already used", context(), binding.key);
+        }
+        if macros[..index].iter().any(|m| m.key == binding.key) {
+            bail!("{}: the key '{}' is bound twice", context(), binding.key);
+        }
+        match (binding.steps.is_empty(), &binding.command) {
+            (false, None) => {
+                for step in &binding.steps {
+                    Step::parse(step).with_context(context)?;
+                }
+            }
+            (true, Some(_)) => {}
+            _ => bail!("{}: give either steps or a command", context()),
+        }
+    }
+    Ok(())
+}
+
+/// Apply steps to the entry at `*selected`, following it when `duplicate`
+/// moves on to the copy. Nothing changes if a step fails.
+pub fn apply(steps: &[String], entries: &mut Vec<CronEntry>, selected: &mut usize) -> Result<()> {
+    let mut changed = entries.clone();
+    let mut index = *selected;
+    if index >= changed.len() {
+        bail!("No entry selected");
+    }
+    for step in steps {
+        match Step::parse(step)? {
+            Step::Duplicate => {
+                let mut copy = changed[index].clone();
+                copy.name = copy_name(&changed, &copy.name);
+                copy.watchdog_for = None;
+                changed.insert(index + 1, copy);
+                index += 1;
+            }
+            Step::Enable => {
+                changed[index].enabled = true;
+                changed[index].snoozed_until = None;
+            }
+            Step::Disable => changed[index].enabled = false,
+            Step::Schedule(input) => {
+                let expression = schedule::resolve_input(&input)?.expression;
+                CronSchedule::parse(&expression).with_context(|| format!("Invalid schedule '{}'", input))?;
+                changed[index].schedule = expression;
+            }
+            Step::Command(command) => changed[index].command = command,
+            Step::Tag(tag) => {
+                let tags = std::mem::take(&mut changed[index].tags);
+                changed[index].tags = cron_entry::parse_tags(&format!("{},{}", tags.join(","), tag));
+            }
+            Step::Untag(tag) => changed[index].tags.retain(|t| !t.eq_ignore_ascii_case(&tag)),
+            Step::Env(preset) => changed[index].env = (!preset.is_empty()).then_some(preset),
+            Step::Timezone(zone) => changed[index].timezone = (!zone.is_empty()).then_some(zone),
+            Step::Shell(shell) => changed[index].shell = shell,
+        }
+    }
+    *entries = changed;
+    *selected = index;
+    Ok(())
+}
+
+/// Run an external macro command with `sh -c`, the entry as JSON on its
+/// stdin. Returns the last line it printed, or fails with its stderr.
+pub fn run_command(command: &str, entry: &CronEntry) -> Result<String> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("CRON_MANAGER_ENTRY", &entry.name)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run '{}'", command))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A command that doesn't read its input closes the pipe early
+        let _ = stdin.write_all(serde_json::to_string(entry)?.as_bytes());
+    }
+    let output = child.wait_with_output()?;
+    let last_line = |bytes: &[u8]| {
+        String::from_utf8_lossy(bytes).lines().rev().find(|l| !l.trim().is_empty()).unwrap_or("").to_string()
+    };
+    if !output.status.success() {
+        bail!("{} {}", output.status, last_line(&output.stderr));
+    }
+    Ok(last_line(&output.stdout))
+}
+
+/// A macro's external command running on its own thread, so one that takes
+/// a while doesn't freeze the UI
+#[derive(Debug)]
+pub struct CommandRun {
+    /// The macro's label, for messages
+    pub label: String,
+    result: Receiver<Result<String>>,
+}
+
+impl CommandRun {
+    pub fn start(label: String, command: &str, entry: &CronEntry) -> Self {
+        let (sender, result) = mpsc::channel();
+        let (command, entry) = (command.to_string(), entry.clone());
+        thread::spawn(move || {
+            let _ = sender.send(run_command(&command, &entry));
+        });
+        Self { label, result }
+    }
+
+    /// What `run_command` returned, once the command has finished
+    pub fn poll(&self) -> Option<Result<String>> {
+        match self.result.try_recv() {
+            Ok(output) => Some(output),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(anyhow!("The command stopped unexpectedly"))),
+        }
+    }
+}
+
+/// `name (copy)`, or `name (copy 2)` and so on when that is taken
+fn copy_name(entries: &[CronEntry], name: &str) -> String {
+    let mut candidate = format!("{} (copy)", name);
+    let mut number = 2;
+    while entries.iter().any(|e| e.name == candidate) {
+        candidate = format!("{} (copy {})", name, number);
+        number += 1;
+    }
+    candidate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binding(key: char, steps: &[&str], command: Option<&str>) -> Macro {
+        Macro {
+            key,
+            name: None,
+            steps: steps.iter().map(|s| s.to_string()).collect(),
+            command: command.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_apply_steps() {
+        let mut entries = vec![CronEntry::new("Backup".to_string(), "0 2 * * *".to_string(), "/bin/backup".to_string())];
+        let mut selected = 0;
+        let steps = ["duplicate", "schedule @daily", "tag tmp", "disable"].map(String::from);
+        apply(&steps, &mut entries, &mut selected).unwrap();
+        apply(&steps[..1], &mut entries, &mut selected).unwrap();
+        assert_eq!(selected, 2);
+        assert_eq!(entries[1].name, "Backup (copy)");
+        assert_eq!(entries[1].schedule, "@daily");
+        assert_eq!(entries[1].tags, ["tmp"]);
+        assert!(!entries[1].enabled && entries[0].enabled);
+        assert_eq!(entries[2].name, "Backup (copy) (copy)");
+
+        // A failing step leaves everything as it was
+        let before = entries.clone();
+        assert!(apply(&["tag keep".to_string(), "schedule 99 * * * *".to_string()], &mut entries, &mut selected).is_err());
+        assert_eq!(entries, before);
+        assert!(Step::parse("tag").is_err());
+
+        let keys = KeyMap::default();
+        assert!(validate(&[binding('D', &["duplicate"], None), binding('J', &[], Some("jq ."))], &keys).is_ok());
+        assert!(validate(&[binding('d', &["duplicate"], None)], &keys).is_err());
+        assert!(validate(&[binding('D', &["duplicate"], None), binding('D', &["enable"], None)], &keys).is_err());
+        assert!(validate(&[binding('D', &["rename x"], None)], &keys).is_err());
+        assert!(validate(&[binding('D', &[], None)], &keys).is_err());
+
+        let output = run_command("grep -o '\"name\":\"[^\"]*\"'; echo done", &entries[0]).unwrap();
+        assert_eq!(output, "done");
+        assert!(run_command("echo broken >&2; exit 3", &entries[0]).unwrap_err().to_string().contains("broken"));
+    }
+}
@@ -0,0 +1,175 @@
+use crate::audit;
+use crate::config::{MailConfig, MailWhen, SecretsConfig};
+use crate::secrets::SecretStore;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local};
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Seconds sending may take, so an unreachable server can't hold up the job
+const SEND_TIMEOUT_SECS: u32 = 30;
+
+/// Longest encoded word allowed in a header (RFC 2047)
+const ENCODED_WORD_LIMIT: usize = 75;
+
+/// Whether a run that ended with `exit_code` after printing `output` is mailed
+pub fn should_mail(when: MailWhen, exit_code: Option<i32>, output: &str) -> bool {
+    exit_code != Some(0) || (when == MailWhen::Output && !output.trim().is_empty())
+}
+
+/// The message for a run of the entry, headers and all. Like cron's, the
+/// subject is `Cron <user@host> name` and the body is the run's output.
+pub fn message(
+    from: &str,
+    to: &[String],
+    origin: &str,
+    name: &str,
+    exit_code: Option<i32>,
+    output: &str,
+    date: DateTime<Local>,
+) -> String {
+    let (status, subject_status) = match exit_code {
+        Some(0) => (String::new(), String::new()),
+        Some(code) => (format!("Exit code: {}\n\n", code), format!(" (exit code {})", code)),
+        None => ("Killed by a signal\n\n".to_string(), " (killed by a signal)".to_string()),
+    };
+    let subject = format!("Cron <{}> {}{}", origin, name, subject_status);
+    let output = if output.trim().is_empty() { "(no output)\n" } else { output };
+    format!(
+        "From: {}\nTo: {}\nSubject: {}\nDate: {}\nMIME-Version: 1.0\nContent-Type: text/plain; charset=UTF-8\nContent-Transfer-Encoding: 8bit\n\n{}{}",
+        from,
+        to.join(", "),
+        encode_header(&subject),
+        date.to_rfc2822(),
+        status,
+        output
+    )
+}
+
+/// The header value as it may appear in a message: as it is when it is
+/// ASCII, otherwise as folded UTF-8 encoded words
+fn encode_header(value: &str) -> String {
+    if value.is_ascii() {
+        return value.to_string();
+    }
+    let (prefix, suffix) = ("=?UTF-8?Q?", "?=");
+    let mut words = Vec::new();
+    let mut word = String::new();
+    for c in value.chars() {
+        let mut bytes = [0; 4];
+        let encoded: String = match c {
+            ' ' => "_".to_string(),
+            c if c.is_ascii_alphanumeric() => c.to_string(),
+            c => c.encode_utf8(&mut bytes).bytes().map(|b| format!("={:02X}", b)).collect(),
+        };
+        if prefix.len() + word.len() + encoded.len() + suffix.len() > ENCODED_WORD_LIMIT {
+            words.push(format!("{}{}{}", prefix, word, suffix));
+            word.clear();
+        }
+        word.push_str(&encoded);
+    }
+    words.push(format!("{}{}{}", prefix, word, suffix));
+    words.join("\n ")
+}
+
+/// `user@host` of the machine the job runs on
+pub fn origin() -> String {
+    let host = fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .or_else(|| Command::new("hostname").output().ok().map(|out| String::from_utf8_lossy(&out.stdout).into_owned()))
+        .map(|host| host.trim().to_string())
+        .filter(|host| !host.is_empty())
+        .unwrap_or_else(|| "localhost".to_string());
+    format!("{}@{}", audit::current_user().unwrap_or_else(|| "cron".to_string()), host)
+}
+
+/// Mail the run's output when the config says to, sending it with `curl`
+pub fn mail_run(
+    config: &MailConfig,
+    secrets: &SecretsConfig,
+    name: &str,
+    exit_code: Option<i32>,
+    output: &str,
+) -> Result<()> {
+    if config.to.is_empty() || !should_mail(config.when, exit_code, output) {
+        return Ok(());
+    }
+    let origin = origin();
+    let from = config.from.clone().unwrap_or_else(|| origin.clone());
+    let message = message(&from, &config.to, &origin, name, exit_code, output, Local::now());
+    let password = config.password.as_deref().map(|password| SecretStore::from_config(secrets)?.resolve(password)).transpose()?;
+    send(config, &from, password.as_deref(), &message).context("Failed to mail the run's output")
+}
+
+fn send(config: &MailConfig, from: &str, password: Option<&str>, message: &str) -> Result<()> {
+    let path = std::env::temp_dir().join(format!("cron-manager-mail-{}.eml", std::process::id()));
+    fs::write(&path, message)?;
+    let mut curl = Command::new("curl");
+    curl.args(["-sS", "--crlf", "-m", &SEND_TIMEOUT_SECS.to_string(), "--url", &config.smtp_url])
+        .args(["--mail-from", address(from)]);
+    for to in &config.to {
+        curl.args(["--mail-rcpt", address(to)]);
+    }
+    curl.arg("-T").arg(&path).stdin(Stdio::piped()).stderr(Stdio::piped());
+    if config.username.is_some() {
+        // Never send the password in the clear, and keep it out of `ps`
+        if config.smtp_url.starts_with("smtp://") {
+            curl.arg("--ssl-reqd");
+        }
+        curl.args(["-K", "-"]);
+    }
+    let result = curl.spawn().context("Failed to run curl").and_then(|mut child| {
+        if let (Some(mut stdin), Some(username)) = (child.stdin.take(), &config.username) {
+            let user = format!("{}:{}", username, password.unwrap_or_default());
+            writeln!(stdin, "user = \"{}\"", user.replace('\\', "\\\\").replace('"', "\\\""))?;
+        }
+        Ok(child.wait_with_output()?)
+    });
+    let _ = fs::remove_file(&path);
+    let output = result?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+/// The bare address of `Name <address>`
+fn address(mailbox: &str) -> &str {
+    match (mailbox.find('<'), mailbox.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &mailbox[start + 1..end],
+        _ => mailbox.trim(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_should_mail() {
+        assert!(should_mail(MailWhen::Output, Some(0), "done\n"));
+        assert!(!should_mail(MailWhen::Output, Some(0), "\n"));
+        assert!(!should_mail(MailWhen::Failure, Some(0), "done\n"));
+        assert!(should_mail(MailWhen::Failure, Some(1), ""));
+        assert!(should_mail(MailWhen::Failure, None, ""));
+    }
+
+    #[test]
+    fn test_message() {
+        let date = Local.with_ymd_and_hms(2024, 6, 1, 2, 0, 0).unwrap();
+        let to = vec!["ops@example.com".to_string(), "Me <me@example.com>".to_string()];
+        let text = message("cron@db1", &to, "cron@db1", "Backup", Some(5), "disk full\n", date);
+        assert!(text.starts_with("From: cron@db1\nTo: ops@example.com, Me <me@example.com>\n"));
+        assert!(text.contains("\nSubject: Cron <cron@db1> Backup (exit code 5)\n"));
+        assert!(text.ends_with("\n\nExit code: 5\n\ndisk full\n"));
+        assert_eq!(address(&to[1]), "me@example.com");
+
+        let text = message("cron@db1", &to, "cron@db1", "夜間バックアップ", Some(0), "", date);
+        assert!(text.contains("\nSubject: =?UTF-8?Q?Cron_=3Ccron=40db1=3E_=E5=A4=9C"));
+        let words: Vec<&str> = text.split(['\n', ' ']).filter(|word| word.starts_with("=?")).collect();
+        assert!(words.len() > 1 && words.iter().all(|word| word.len() <= ENCODED_WORD_LIMIT));
+        assert!(text.ends_with("\n\n(no output)\n"));
+    }
+}
@@ -1,40 +1,155 @@
-use anyhow::Result;
-use cron_manager::{app::{App, InputMode}, storage::Storage, ui};
+use anyhow::{anyhow, bail, Context, Result};
+use cron_manager::{analysis::calendar::CalendarSpan, app::{App, BackupDiff, CursorMotion, InputMode, SyncPeer}, cli, conflict::Resolution, cron_entry::CronEntry, export::OnDuplicate, i18n::fill, keys::Action, clock::{self, Clock}, config::{BackendKind, Config}, logging, paths, privilege::{self, Privilege}, storage::Storage, sync::{self, Direction}, ui::{self, HostStatus, HostTab}};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
+    event::{self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
 use std::io;
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, Instant};
 
 fn main() -> Result<()> {
     // Parse command line arguments
-    let args: Vec<String> = std::env::args().collect();
-    let storage = if args.len() > 1 && args[1] == "--local" {
-        Storage::new(None)
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut config = Config::load()?;
+    if args.iter().any(|a| a == "--strict") {
+        config.strict_validation = true;
+    }
+    // `--dry-run` renders what saves would write instead of writing it
+    if args.iter().any(|a| a == "--dry-run") {
+        config.dry_run = true;
+    }
+    logging::init(config.data_dir.as_deref())?;
+
+    // `--local`, `--system` and `--root` override the backend configured for
+    // this OS (by default the system scheduler: cron on Linux, launchd on macOS)
+    let backend = if args.iter().any(|a| a == "--local") {
+        BackendKind::File
+    } else if args.iter().any(|a| a == "--system") {
+        BackendKind::System
+    } else if args.iter().any(|a| a == "--root") {
+        BackendKind::Root
     } else {
-        // Default: use system scheduler (cron on Linux, launchd on macOS)
-        Storage::with_system_scheduler()
+        config.backend.for_current_os()
     };
+    if backend == BackendKind::Root {
+        ensure_privileges()?;
+    }
+    if backend == BackendKind::File {
+        if let Some(file) = paths::storage_file(config.data_dir.as_deref()) {
+            if paths::migrate_legacy_storage(&file)? {
+                eprintln!("Moved ~/.cron-manager-crontab to {}", file.display());
+            }
+        }
+    }
+
+    // `--host <ssh destination>` and `--agent <address>`, both repeatable,
+    // manage remote hosts instead
+    let mut hosts = Vec::new();
+    for (i, option) in args.iter().enumerate().filter(|(_, a)| *a == "--host" || *a == "--agent") {
+        match args.get(i + 1) {
+            Some(host) if !host.starts_with("--") => hosts.push((option.as_str(), host.as_str())),
+            _ if option == "--host" => bail!("--host needs an ssh destination (e.g. deploy@web1)"),
+            _ => bail!("--agent needs an address (host:port or unix:<path>)"),
+        }
+    }
+
+    // `--sandbox` opens the sandbox cloned from the backend (or the one --host)
+    let sandbox = args.iter().any(|a| a == "--sandbox");
+    if sandbox && hosts.len() > 1 {
+        bail!("--sandbox takes a single --host or --agent");
+    }
+
+    // `--simulate-time "YYYY-MM-DD HH:MM"` computes next runs and analyses as
+    // if it were that time
+    let simulated = args.iter().position(|a| a == "--simulate-time").map(|i| args.get(i + 1));
+    let clock = match simulated {
+        Some(Some(time)) => Clock::starting_at(clock::parse_local_time(time).context("Invalid --simulate-time")?),
+        Some(None) => bail!("--simulate-time needs a time (YYYY-MM-DD HH:MM)"),
+        None => Clock::system(),
+    };
+
+    // Subcommands run non-interactively instead of starting the TUI
+    let subcommand = args
+        .iter()
+        .enumerate()
+        .find(|(i, a)| !a.starts_with("--") && (*i == 0 || !cli::GLOBAL_VALUE_OPTIONS.contains(&args[i - 1].as_str())));
+    if let Some((_, command)) = subcommand {
+        let mut storage = match hosts.as_slice() {
+            [] => Storage::for_backend(backend, &config)?,
+            [(option, host)] => open_remote(option, host, &config)?,
+            _ => bail!("Subcommands take a single --host or --agent"),
+        };
+        if sandbox {
+            storage = Storage::sandbox(storage, &config)?;
+        }
+        if config.dry_run {
+            storage = storage.dry_run(true, &config);
+        }
+        tracing::info!(backend = storage.get_backend_name(), "selected backend");
+        return cli::run(command, &args, &storage, &config, clock);
+    }
+
+    // One tab per host; hosts that can't be opened are reported on the first
+    // tab that could
+    let mut open = Vec::new();
+    let mut unreachable = Vec::new();
+    if hosts.is_empty() {
+        let source = Source::Backend(backend);
+        let storage = source.open(&config)?;
+        tracing::info!(backend = storage.get_backend_name(), "selected backend");
+        if sandbox {
+            let storage = dry_run(Storage::sandbox(storage, &config)?, &config);
+            open.push(Host::sandbox(String::new(), App::with_clock(storage, config.clone(), clock)?));
+        } else {
+            let mut app = App::with_clock(storage, config.clone(), clock)?;
+            // A backend that can't be opened here just leaves sync unavailable
+            app.sync_peer = sync::open_peer(backend, &config)
+                .ok()
+                .flatten()
+                .map(|storage| SyncPeer { storage, local: backend == BackendKind::File });
+            open.push(Host::new(String::new(), app, source));
+        }
+    }
+    for (option, host) in &hosts {
+        let source = Source::Remote(option.to_string(), host.to_string());
+        let app = source
+            .open(&config)
+            .and_then(|storage| if sandbox { Storage::sandbox(storage, &config).map(|s| dry_run(s, &config)) } else { Ok(storage) })
+            .and_then(|storage| App::with_clock(storage, config.clone(), clock));
+        match app {
+            Ok(app) if sandbox => open.push(Host::sandbox(host.to_string(), app)),
+            Ok(app) => open.push(Host::new(host.to_string(), app, source)),
+            Err(e) => unreachable.push(format!("{}: {:#}", host, e)),
+        }
+    }
+    if open.is_empty() {
+        bail!("Could not open any host:\n{}", unreachable.join("\n"));
+    }
+    if !unreachable.is_empty() {
+        let first = &mut open[0].app;
+        first.message = Some(fill(first.text.hosts_unreachable, &[&unreachable.join("; ")]));
+    }
 
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture, EnableBracketedPaste)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    // Create app and run
-    let mut app = App::new(storage)?;
-    let res = run_app(&mut terminal, &mut app);
+    let res = run_app(&mut terminal, &mut open, &config, clock);
 
     // Restore terminal
     disable_raw_mode()?;
     execute!(
         terminal.backend_mut(),
         LeaveAlternateScreen,
-        DisableMouseCapture
+        DisableMouseCapture,
+        DisableBracketedPaste
     )?;
     terminal.show_cursor()?;
 
@@ -45,46 +160,561 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// The storage for a `--host` or `--agent` argument
+fn open_remote(option: &str, host: &str, config: &Config) -> Result<Storage> {
+    match option {
+        "--agent" => Storage::for_agent(host, config),
+        _ => Storage::for_host(host, config),
+    }
+}
+
+/// Make sure root's schedule can be reached, offering to re-run under
+/// `sudo` (which asks for the password) when it can't. launchd daemons need
+/// the process itself to be root; root's crontab is also reachable through
+/// cached sudo credentials.
+fn ensure_privileges() -> Result<()> {
+    let enough = match privilege::current() {
+        Privilege::Root => true,
+        Privilege::Sudo => !cfg!(target_os = "macos"),
+        Privilege::Unprivileged => false,
+    };
+    if enough {
+        return Ok(());
+    }
+    if privilege::confirm_rerun("--root manages the system schedule, which needs root privileges")? {
+        std::process::exit(privilege::rerun_with_sudo()?);
+    }
+    bail!("--root needs root privileges; run it with sudo")
+}
+
+/// How a tab's backend was opened, so a sandbox cloned from it can open it
+/// again to push to
+#[derive(Clone)]
+enum Source {
+    Backend(BackendKind),
+    /// `--host` or `--agent`, and its argument
+    Remote(String, String),
+}
+
+impl Source {
+    fn open(&self, config: &Config) -> Result<Storage> {
+        let storage = match self {
+            Self::Backend(kind) => Storage::for_backend(*kind, config)?,
+            Self::Remote(option, host) => open_remote(option, host, config)?,
+        };
+        Ok(dry_run(storage, config))
+    }
+}
+
+/// The storage as a dry run when `--dry-run` was given; the TUI shows the
+/// rendering instead of printing it
+fn dry_run(storage: Storage, config: &Config) -> Storage {
+    if config.dry_run {
+        storage.dry_run(false, config)
+    } else {
+        storage
+    }
+}
+
+/// How often the UI redraws without input, so clocks and countdowns stay current
+const TICK_RATE: Duration = Duration::from_millis(250);
+
+/// How often a running background save is checked on
+const SAVE_POLL_RATE: Duration = Duration::from_millis(100);
+
+/// An open backend; a tab when several hosts are open
+struct Host {
+    /// The ssh destination, empty for the local backend
+    name: String,
+    app: App,
+    /// The last write to the host failed
+    failed: bool,
+    /// How to open the backend again; sandboxes have none
+    source: Option<Source>,
+}
+
+impl Host {
+    fn new(name: String, app: App, source: Source) -> Self {
+        Self { name, app, failed: false, source: Some(source) }
+    }
+
+    /// The tab of the sandbox cloned from the host `origin`
+    fn sandbox(origin: String, app: App) -> Self {
+        Self { name: sandbox_tab(&origin), app, failed: false, source: None }
+    }
+
+    /// Clone the entries into a sandbox of this host's backend
+    fn open_sandbox(&self, entries: &[CronEntry], config: &Config, clock: Clock) -> Result<Host> {
+        let source = self.source.as_ref().context("Already a sandbox")?;
+        let storage = dry_run(Storage::sandbox(source.open(config)?, config)?, config);
+        storage.save(entries)?;
+        let mut app = App::with_clock(storage, config.clone(), clock)?;
+        app.message = Some(fill(app.text.sandbox_opened, &[&entries.len(), &app.config.keys.label(Action::Sandbox)]));
+        Ok(Host::sandbox(self.name.clone(), app))
+    }
+
+    fn status(&self) -> HostStatus {
+        if self.failed || !self.app.save_failures.is_empty() {
+            HostStatus::Failed
+        } else if self.app.has_unsaved_changes() {
+            HostStatus::Unsaved
+        } else {
+            HostStatus::Saved
+        }
+    }
+
+    /// With several hosts open, an error on one (e.g. an unreachable host)
+    /// is shown on its tab instead of ending the whole session
+    fn contain(&mut self, result: Result<()>, isolate: bool) -> Result<()> {
+        match result {
+            Err(e) if isolate => {
+                tracing::error!(host = %self.name, error = %format!("{:#}", e), "host operation failed");
+                self.app.message = Some(format!("{:#}", e));
+                self.failed = true;
+                Ok(())
+            }
+            result => result,
+        }
+    }
+}
+
+/// Tab name of the sandbox cloned from a host's backend
+fn sandbox_tab(origin: &str) -> String {
+    if origin.is_empty() {
+        "sandbox".to_string()
+    } else {
+        format!("sandbox: {}", origin)
+    }
+}
+
 fn run_app<B: ratatui::backend::Backend>(
     terminal: &mut Terminal<B>,
-    app: &mut App,
+    hosts: &mut Vec<Host>,
+    config: &Config,
+    clock: Clock,
 ) -> Result<()> {
+    let mut last_tick = Instant::now();
+    let mut active = 0;
+
     loop {
-        terminal.draw(|f| ui::draw(f, app))?;
-
-        if let Event::Key(key) = event::read()? {
-            if key.kind == KeyEventKind::Press {
-                match app.input_mode {
-                    InputMode::Normal => match key.code {
-                        KeyCode::Char('q') => {
-                            app.quit();
-                            break;
+        // Sandboxes open as tabs, so errors are kept to the tab from then on
+        let isolate = hosts.len() > 1;
+        let tabs: Vec<(String, HostStatus)> = hosts
+            .iter()
+            .map(|h| {
+                let name = if h.name.is_empty() { h.app.storage.get_backend_name().to_string() } else { h.name.clone() };
+                (name, h.status())
+            })
+            .collect();
+        let tabs: Vec<HostTab> = tabs.iter().map(|(host, status)| HostTab { host, status: *status }).collect();
+        let app = &mut hosts[active].app;
+        terminal.draw(|f| ui::draw_tabbed(f, app, &tabs, active))?;
+
+        // Wake up for the next tick, or earlier if a debounced save is due
+        let mut timeout = TICK_RATE.saturating_sub(last_tick.elapsed());
+        if let Some(deadline) = hosts.iter().filter_map(|h| h.app.save_deadline()).min() {
+            timeout = timeout.min(deadline.saturating_duration_since(Instant::now()));
+        }
+        // Turn the spinner and pick up the outcome while a save is running
+        if hosts.iter().any(|h| h.app.save_spinner().is_some()) {
+            timeout = timeout.min(SAVE_POLL_RATE);
+        }
+
+        if event::poll(timeout)? {
+            match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                    KeyCode::Tab if isolate && switches_hosts(&hosts[active].app) => {
+                        active = (active + 1) % hosts.len();
+                    }
+                    KeyCode::BackTab if isolate && switches_hosts(&hosts[active].app) => {
+                        active = (active + hosts.len() - 1) % hosts.len();
+                    }
+                    _ => {
+                        let result = handle_key(&mut hosts[active].app, key);
+                        hosts[active].contain(result, isolate)?;
+                    }
+                },
+                Event::Paste(text) => hosts[active].app.paste(&text),
+                _ => {}
+            }
+        }
+        if let Some(entries) = hosts[active].app.sandbox_request.take() {
+            match hosts[active].open_sandbox(&entries, config, clock) {
+                Ok(sandbox) => {
+                    // Cloning again starts the sandbox over
+                    match hosts.iter().position(|h| h.source.is_none() && h.name == sandbox.name) {
+                        Some(index) => {
+                            hosts[index] = sandbox;
+                            active = index;
+                        }
+                        None => {
+                            hosts.push(sandbox);
+                            active = hosts.len() - 1;
                         }
-                        KeyCode::Up | KeyCode::Char('k') => app.move_selection_up(),
-                        KeyCode::Down | KeyCode::Char('j') => app.move_selection_down(),
-                        KeyCode::Char('a') => app.start_add_entry(),
-                        KeyCode::Char('d') => app.delete_entry()?,
-                        KeyCode::Char('n') => app.start_edit_name(),
-                        KeyCode::Char('s') => app.start_edit_schedule(),
-                        KeyCode::Char('c') => app.start_edit_command(),
-                        KeyCode::Char(' ') => app.toggle_enabled()?,
-                        _ => {}
-                    },
-                    _ => match key.code {
-                        KeyCode::Enter => app.confirm_input()?,
-                        KeyCode::Char(c) => app.handle_input_char(c),
-                        KeyCode::Backspace => app.handle_input_backspace(),
-                        KeyCode::Esc => app.cancel_input(),
-                        _ => {}
-                    },
+                    }
                 }
+                Err(e) => hosts[active].app.message = Some(format!("{:#}", e)),
             }
         }
+        if std::mem::take(&mut hosts[active].app.origin_changed) {
+            // Show what the sandbox pushed in the live backend's tab
+            let sandbox = hosts[active].name.clone();
+            if let Some(host) = hosts.iter_mut().find(|h| h.source.is_some() && sandbox_tab(&h.name) == sandbox) {
+                let result = host.app.reload();
+                host.contain(result, true)?;
+            }
+        }
+        let app = &mut hosts[active].app;
+        if let Some(path) = app.pager_request.take() {
+            if let Err(e) = run_in_terminal(terminal, "PAGER", "less", &path) {
+                app.message = Some(format!("{:#}", e));
+            }
+        }
+        if let Some(path) = app.editor_request.take() {
+            let result = run_in_terminal(terminal, "EDITOR", "vi", &path);
+            let closed = app.editor_closed();
+            if let Err(e) = result {
+                app.message = Some(format!("{:#}", e));
+            }
+            hosts[active].contain(closed, isolate)?;
+        }
+
+        // Every host saves on its own schedule, whichever tab is showing,
+        // on a background thread so slow backends don't hold up input
+        for host in hosts.iter_mut() {
+            if host.app.save_deadline().is_some_and(|deadline| deadline <= Instant::now()) {
+                host.app.start_save();
+            }
+            if let Some(result) = host.app.poll_save() {
+                host.failed = result.is_err();
+                host.contain(result, isolate)?;
+            }
+        }
+        if last_tick.elapsed() >= TICK_RATE {
+            for host in hosts.iter_mut() {
+                let result = host.app.on_tick();
+                host.contain(result, isolate)?;
+            }
+            last_tick = Instant::now();
+        }
 
-        if app.should_quit {
+        if hosts[active].app.should_quit {
             break;
         }
     }
 
+    // Write every host's pending changes, even if one of them fails
+    let mut result = Ok(());
+    for host in hosts.iter_mut() {
+        if let Err(e) = host.app.flush_save() {
+            eprintln!("Failed to save {}: {:#}", host.name, e);
+            result = Err(e);
+        } else if host.app.conflict_resolver.is_some() {
+            eprintln!("Not saved {}: entries were changed there meanwhile; open it again to resolve", host.name);
+            result = Err(anyhow!("Unresolved conflicts"));
+        }
+    }
+    result
+}
+
+/// Whether Tab moves to the next host rather than completing input
+fn switches_hosts(app: &App) -> bool {
+    app.input_mode == InputMode::Normal
+        && app.preset_picker.is_none()
+        && app.conversion.is_none()
+        && app.conflict_resolver.is_none()
+}
+
+/// Hand the terminal to the program in `$var` (or `fallback`) for the file,
+/// then restore the TUI
+fn run_in_terminal<B: ratatui::backend::Backend>(
+    terminal: &mut Terminal<B>,
+    var: &str,
+    fallback: &str,
+    path: &Path,
+) -> Result<()> {
+    let program = std::env::var(var).ok().filter(|p| !p.trim().is_empty()).unwrap_or_else(|| fallback.to_string());
+
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture, DisableBracketedPaste)?;
+    // `$PAGER` and `$EDITOR` may carry arguments (e.g. `less -R`), so let the shell split them
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$1\"", program))
+        .arg("sh")
+        .arg(path)
+        .status();
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture, EnableBracketedPaste)?;
+    terminal.clear()?;
+
+    let status = status.with_context(|| format!("Failed to run ${} '{}'", var, program))?;
+    if !status.success() {
+        bail!("${} '{}' exited with {}", var, program, status);
+    }
+    Ok(())
+}
+
+/// Do what a key bound in the `[keys]` map does in the entry list
+fn run_action(app: &mut App, action: Action) -> Result<()> {
+    match action {
+        Action::Quit => app.quit()?,
+        Action::Write => app.write_now(),
+        Action::Up => app.move_selection_up(),
+        Action::Down => app.move_selection_down(),
+        Action::MoveUp => app.move_entry(false)?,
+        Action::MoveDown => app.move_entry(true)?,
+        Action::Add => app.start_add_entry(),
+        Action::Delete => app.request_delete()?,
+        Action::Toggle => app.toggle_enabled()?,
+        Action::EditName => app.start_edit_name(),
+        Action::EditSchedule => app.start_edit_schedule(),
+        Action::EditCommand => app.start_edit_command(),
+        Action::EditTimezone => app.start_edit_timezone(),
+        Action::EditTags => app.start_edit_tags(),
+        Action::EditEnv => app.start_edit_env(),
+        Action::EditShell => app.start_edit_shell(),
+        Action::EditHooks => app.start_edit_hooks(),
+        Action::EditLimits => app.start_edit_limits(),
+        Action::EditPing => app.start_edit_ping(),
+        Action::EditComments => app.edit_comments(),
+        Action::Snooze => app.start_snooze(),
+        Action::SkipExcluded => app.toggle_skip_excluded()?,
+        Action::Watchdog => app.toggle_watchdog()?,
+        Action::BulkEdit => app.start_bulk_edit(),
+        Action::BulkToggle => app.start_bulk_toggle(),
+        Action::Quartz => app.show_quartz_expression(),
+        Action::Log => app.open_log(),
+        Action::LogInPager => app.open_log_in_pager(),
+        Action::Upcoming => app.open_simulation(),
+        Action::Calendar => app.open_calendar(),
+        Action::RunNow => app.start_manual_run(),
+        Action::Runs => app.open_runs(),
+        Action::Import => app.start_import(),
+        Action::Scripts => app.open_scripts(),
+        Action::Backups => app.open_backups(),
+        Action::Changes => app.open_changes(),
+        Action::DryRun => app.open_dry_run()?,
+        Action::Sync => app.open_sync()?,
+        Action::Trash => app.open_trash(),
+        Action::Sandbox => app.start_sandbox()?,
+        Action::Theme => app.cycle_theme(),
+        Action::Preview => app.toggle_preview(),
+        Action::TagFilter => app.start_tag_filter(),
+        Action::GroupByTag => app.toggle_group_by_tag(),
+    }
+    Ok(())
+}
+
+/// Lines moved by PageUp/PageDown in the log and upcoming-runs views
+const LOG_PAGE: usize = 20;
+
+fn handle_key(app: &mut App, key: KeyEvent) -> Result<()> {
+    // The preset popup takes the keys while it is open
+    if app.preset_picker.is_some() {
+        match key.code {
+            KeyCode::Up => app.move_preset_selection(false),
+            KeyCode::Down => app.move_preset_selection(true),
+            KeyCode::Enter => app.apply_preset(),
+            KeyCode::Esc => app.close_preset_picker(),
+            _ => {}
+        }
+        return Ok(());
+    }
+    // And the conflict resolver
+    if app.conflict_resolver.is_some() {
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => app.move_conflict_selection(false),
+            KeyCode::Down | KeyCode::Char('j') => app.move_conflict_selection(true),
+            KeyCode::Char('y') => app.resolve_conflict(Resolution::Yours)?,
+            KeyCode::Char('t') => app.resolve_conflict(Resolution::Theirs)?,
+            KeyCode::Char('m') => app.resolve_conflict(Resolution::Merged)?,
+            KeyCode::Char('O') => app.overwrite_conflicts()?,
+            KeyCode::Char('R') => app.reload_conflicts(),
+            KeyCode::Esc => app.close_conflicts(),
+            _ => {}
+        }
+        return Ok(());
+    }
+    // So does the conversion wizard
+    if app.conversion.is_some() {
+        match key.code {
+            KeyCode::Up => app.move_conversion_selection(false),
+            KeyCode::Down => app.move_conversion_selection(true),
+            KeyCode::Enter => app.apply_conversion()?,
+            KeyCode::Esc => app.close_conversion(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    match app.input_mode {
+        InputMode::Normal => match key.code {
+            KeyCode::Up if key.modifiers.contains(KeyModifiers::SHIFT) => app.move_entry(false)?,
+            KeyCode::Down if key.modifiers.contains(KeyModifiers::SHIFT) => app.move_entry(true)?,
+            KeyCode::Up => app.move_selection_up(),
+            KeyCode::Down => app.move_selection_down(),
+            KeyCode::PageUp => app.page_selection(false),
+            KeyCode::PageDown => app.page_selection(true),
+            KeyCode::Home => app.select_first(),
+            KeyCode::End => app.select_last(),
+            // With several hosts open, Tab switches hosts before it gets here
+            KeyCode::Tab => app.open_calendar(),
+            KeyCode::Char(c) => match app.config.keys.action(c) {
+                Some(action) => run_action(app, action)?,
+                None => app.run_macro(c)?,
+            },
+            _ => {}
+        },
+        InputMode::ViewingLog => match key.code {
+            KeyCode::Up | KeyCode::Char('k') => app.scroll_log_up(1)?,
+            KeyCode::Down | KeyCode::Char('j') => app.scroll_log_down(1),
+            KeyCode::PageUp => app.scroll_log_up(LOG_PAGE)?,
+            KeyCode::PageDown => app.scroll_log_down(LOG_PAGE),
+            KeyCode::Char('/') => app.start_log_search(),
+            KeyCode::Char('n') => app.next_log_match(true)?,
+            KeyCode::Char('N') => app.next_log_match(false)?,
+            KeyCode::Esc | KeyCode::Char('q') => app.close_log(),
+            _ => {}
+        },
+        InputMode::ViewingCalendar => match key.code {
+            KeyCode::Left | KeyCode::Char('h') => app.shift_calendar(-1),
+            KeyCode::Right | KeyCode::Char('l') => app.shift_calendar(1),
+            KeyCode::Up | KeyCode::Char('k') => app.scroll_calendar_up(1),
+            KeyCode::Down | KeyCode::Char('j') => app.scroll_calendar_down(1),
+            KeyCode::PageUp => app.scroll_calendar_up(LOG_PAGE),
+            KeyCode::PageDown => app.scroll_calendar_down(LOG_PAGE),
+            KeyCode::Char('w') => app.set_calendar_span(CalendarSpan::Week),
+            KeyCode::Char('m') => app.set_calendar_span(CalendarSpan::Month),
+            KeyCode::Char('t') => app.calendar_today(),
+            KeyCode::Tab | KeyCode::Esc | KeyCode::Char('q') => app.close_calendar(),
+            _ => {}
+        },
+        InputMode::ViewingSimulation => match key.code {
+            KeyCode::Up | KeyCode::Char('k') => app.scroll_simulation_up(1),
+            KeyCode::Down | KeyCode::Char('j') => app.scroll_simulation_down(1),
+            KeyCode::PageUp => app.scroll_simulation_up(LOG_PAGE),
+            KeyCode::PageDown => app.scroll_simulation_down(LOG_PAGE),
+            KeyCode::Esc | KeyCode::Char('q') => app.close_simulation(),
+            _ => {}
+        },
+        InputMode::ConfirmDelete => match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => app.confirm_delete(true)?,
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc | KeyCode::Char('q') => app.confirm_delete(false)?,
+            _ => {}
+        },
+        InputMode::ConfirmImport => match key.code {
+            KeyCode::Char('s') => app.finish_import(Some(OnDuplicate::Skip))?,
+            KeyCode::Char('o') => app.finish_import(Some(OnDuplicate::Overwrite))?,
+            KeyCode::Char('r') => app.finish_import(Some(OnDuplicate::Rename))?,
+            KeyCode::Esc | KeyCode::Char('q') => app.finish_import(None)?,
+            _ => {}
+        },
+        InputMode::ViewingRun => match key.code {
+            KeyCode::Up | KeyCode::Char('k') => app.scroll_run_up(1),
+            KeyCode::Down | KeyCode::Char('j') => app.scroll_run_down(1),
+            KeyCode::PageUp => app.scroll_run_up(LOG_PAGE),
+            KeyCode::PageDown => app.scroll_run_down(LOG_PAGE),
+            KeyCode::Esc | KeyCode::Char('q') => app.close_manual_run(),
+            _ => {}
+        },
+        InputMode::BrowsingRuns => match key.code {
+            KeyCode::Up | KeyCode::Char('k') => app.move_runs_selection(false),
+            KeyCode::Down | KeyCode::Char('j') => app.move_runs_selection(true),
+            KeyCode::Esc | KeyCode::Char('q') => app.close_runs(),
+            _ => {}
+        },
+        InputMode::ViewingChanges => match key.code {
+            KeyCode::Up | KeyCode::Char('k') => app.scroll_changes_up(1),
+            KeyCode::Down | KeyCode::Char('j') => app.scroll_changes_down(1),
+            KeyCode::PageUp => app.scroll_changes_up(LOG_PAGE),
+            KeyCode::PageDown => app.scroll_changes_down(LOG_PAGE),
+            KeyCode::Esc | KeyCode::Char('q') => app.close_changes(),
+            _ => {}
+        },
+        InputMode::ViewingDryRun => match key.code {
+            KeyCode::Up | KeyCode::Char('k') => app.scroll_dry_run_up(1),
+            KeyCode::Down | KeyCode::Char('j') => app.scroll_dry_run_down(1),
+            KeyCode::PageUp => app.scroll_dry_run_up(LOG_PAGE),
+            KeyCode::PageDown => app.scroll_dry_run_down(LOG_PAGE),
+            KeyCode::Esc | KeyCode::Char('q') => app.close_dry_run(),
+            _ => {}
+        },
+        InputMode::BrowsingScripts => match key.code {
+            KeyCode::Up | KeyCode::Char('k') => app.move_script_selection(false),
+            KeyCode::Down | KeyCode::Char('j') => app.move_script_selection(true),
+            KeyCode::Enter => app.use_script()?,
+            KeyCode::Char('e') => app.edit_script(),
+            KeyCode::Char('n') => app.start_new_script(),
+            KeyCode::Char('d') => app.delete_script(),
+            KeyCode::Esc | KeyCode::Char('q') => app.close_scripts(),
+            _ => {}
+        },
+        InputMode::ReviewingEdit => match key.code {
+            KeyCode::Enter => app.apply_bulk_edit()?,
+            KeyCode::Char('e') => app.reopen_bulk_edit(),
+            KeyCode::Esc | KeyCode::Char('q') => app.discard_bulk_edit(),
+            _ => {}
+        },
+        InputMode::ReviewingPush => match key.code {
+            KeyCode::Up | KeyCode::Char('k') => app.move_push_selection(false),
+            KeyCode::Down | KeyCode::Char('j') => app.move_push_selection(true),
+            KeyCode::Char(' ') => app.toggle_push_pick(),
+            KeyCode::Enter => app.push_picked()?,
+            KeyCode::Esc | KeyCode::Char('q') => app.close_push_review(),
+            _ => {}
+        },
+        InputMode::ReviewingSync => match key.code {
+            KeyCode::Up | KeyCode::Char('k') => app.move_sync_selection(false),
+            KeyCode::Down | KeyCode::Char('j') => app.move_sync_selection(true),
+            KeyCode::Char('p') => app.sync(Direction::Push)?,
+            KeyCode::Char('u') => app.sync(Direction::Pull)?,
+            KeyCode::Char('m') => app.sync(Direction::Merge)?,
+            KeyCode::Esc | KeyCode::Char('q') => app.close_sync(),
+            _ => {}
+        },
+        InputMode::BrowsingTrash => match key.code {
+            KeyCode::Up | KeyCode::Char('k') => app.move_trash_selection(false),
+            KeyCode::Down | KeyCode::Char('j') => app.move_trash_selection(true),
+            KeyCode::Enter => app.restore_from_trash()?,
+            KeyCode::Char('d') => app.purge_from_trash(),
+            KeyCode::Esc | KeyCode::Char('q') => app.close_trash(),
+            _ => {}
+        },
+        InputMode::BrowsingBackups if app.backup_browser.as_ref().is_some_and(|b| b.confirm_rollback) => {
+            app.confirm_rollback(matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')))?
+        }
+        InputMode::BrowsingBackups => match key.code {
+            KeyCode::Char('d') => app.diff_backup(BackupDiff::Current),
+            KeyCode::Char('D') => app.diff_backup(BackupDiff::Previous),
+            KeyCode::Char('r') => app.request_rollback(),
+            KeyCode::Up | KeyCode::Char('k') => app.move_backup_selection(false),
+            KeyCode::Down | KeyCode::Char('j') => app.move_backup_selection(true),
+            KeyCode::Enter => app.confirm_backup()?,
+            KeyCode::Esc | KeyCode::Char('q') => app.back_from_backups(),
+            _ => {}
+        },
+        _ => match key.code {
+            KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => app.open_preset_picker(),
+            KeyCode::Char('v') if key.modifiers.contains(KeyModifiers::CONTROL) => app.paste_clipboard(),
+            KeyCode::Enter => app.confirm_input()?,
+            KeyCode::Tab => app.complete_input(),
+            KeyCode::Char(c) => app.handle_input_char(c),
+            KeyCode::Backspace => app.handle_input_backspace(),
+            KeyCode::Delete => app.handle_input_delete(),
+            KeyCode::Left if key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => {
+                app.move_input_cursor(CursorMotion::WordLeft)
+            }
+            KeyCode::Right if key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => {
+                app.move_input_cursor(CursorMotion::WordRight)
+            }
+            KeyCode::Left => app.move_input_cursor(CursorMotion::Left),
+            KeyCode::Right => app.move_input_cursor(CursorMotion::Right),
+            KeyCode::Home => app.move_input_cursor(CursorMotion::Home),
+            KeyCode::End => app.move_input_cursor(CursorMotion::End),
+            KeyCode::Esc => app.cancel_input(),
+            _ => {}
+        },
+    }
     Ok(())
 }
@@ -0,0 +1,95 @@
+use crate::config::{NotifyConfig, SecretsConfig};
+use crate::secrets::SecretStore;
+use anyhow::{bail, Context, Result};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Bytes of standard error put in the message: the end, where the error is,
+/// well within Discord's 2000-character limit
+const STDERR_TAIL: usize = 1500;
+
+/// Seconds the post may take, so a slow webhook can't hold up the job
+const POST_TIMEOUT_SECS: u32 = 10;
+
+/// The configured message for a failed run of the entry; `{exit_code}` is
+/// `signal` when a signal killed it
+pub fn message(template: &str, name: &str, exit_code: Option<i32>, stderr: &str) -> String {
+    let exit_code = exit_code.map_or_else(|| "signal".to_string(), |code| code.to_string());
+    template
+        .replace("{name}", name)
+        .replace("{exit_code}", &exit_code)
+        .replace("{stderr}", tail(stderr.trim_end()))
+        .trim_end()
+        .to_string()
+}
+
+/// The last `STDERR_TAIL` bytes of the text, from a character boundary
+fn tail(text: &str) -> &str {
+    let mut start = text.len().saturating_sub(STDERR_TAIL);
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    &text[start..]
+}
+
+/// The JSON body for the webhook: Discord reads the message from `content`,
+/// Slack and most others from `text`
+pub fn payload(url: &str, message: &str) -> String {
+    let field = if url.contains("discord.com/") || url.contains("discordapp.com/") { "content" } else { "text" };
+    serde_json::json!({ field: message }).to_string()
+}
+
+/// Post the body to the webhook with `curl`
+pub fn post(url: &str, body: &str) -> Result<()> {
+    let mut child = Command::new("curl")
+        .args(["-fsS", "-o", "/dev/null", "--retry", "3", "-m", &POST_TIMEOUT_SECS.to_string()])
+        .args(["-H", "Content-Type: application/json", "--data-binary", "@-", url])
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run curl")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(body.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+/// Tell the webhook, if one is configured, that the entry's run failed
+pub fn notify_failure(
+    config: &NotifyConfig,
+    secrets: &SecretsConfig,
+    name: &str,
+    exit_code: Option<i32>,
+    stderr: &str,
+) -> Result<()> {
+    let Some(url) = &config.webhook_url else {
+        return Ok(());
+    };
+    let url = SecretStore::from_config(secrets)?.resolve(url)?;
+    let text = message(&config.message, name, exit_code, stderr);
+    post(&url, &payload(&url, &text)).context("Failed to post the failure to the webhook")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_and_payload() {
+        let template = NotifyConfig::default().message;
+        assert_eq!(
+            message(&template, "Backup", Some(2), "rsync: connection refused\n"),
+            "Cron job \"Backup\" failed with exit code 2\nrsync: connection refused"
+        );
+        assert_eq!(message("{name}: {exit_code}", "Backup", None, ""), "Backup: signal");
+        let long = format!("first\n{}", "é".repeat(STDERR_TAIL));
+        assert!(!message("{stderr}", "Backup", Some(1), &long).contains("first"));
+
+        assert_eq!(payload("https://hooks.slack.com/services/T0/B0/x", "hi"), r#"{"text":"hi"}"#);
+        assert_eq!(payload("https://discord.com/api/webhooks/1/x", "hi"), r#"{"content":"hi"}"#);
+    }
+}
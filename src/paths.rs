@@ -0,0 +1,172 @@
+use anyhow::{Context, Result};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// Directory name used under the config and data base directories
+const APP_DIR: &str = "cron-manager";
+
+/// Local crontab file kept by `--local`, inside the data directory
+const STORAGE_FILE: &str = "crontab";
+
+/// Directory inside the data directory holding sandbox crontabs
+const SANDBOX_DIR: &str = "sandbox";
+
+/// The entries the local file and the system scheduler were left with by
+/// the last `sync`, inside the data directory
+const SYNC_BASE_FILE: &str = "sync-base";
+
+/// Directory inside the data directory for files handed to `$EDITOR` and
+/// the log viewer, which only this user may enter
+const PRIVATE_DIR: &str = "tmp";
+
+/// Where the local crontab lived before the data directory existed
+const LEGACY_STORAGE_FILE: &str = ".cron-manager-crontab";
+
+/// `$XDG_CONFIG_HOME/cron-manager`, falling back to the platform config
+/// directory (`~/.config` on Linux, `~/Library/Application Support` on macOS)
+pub fn config_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME")
+        .or_else(dirs::config_dir)
+        .map(|dir| dir.join(APP_DIR))
+}
+
+/// `$XDG_DATA_HOME/cron-manager`, falling back to the platform data directory
+/// (`~/.local/share` on Linux). `custom` (the config's `data_dir`) wins.
+pub fn data_dir(custom: Option<&Path>) -> Option<PathBuf> {
+    match custom {
+        Some(dir) => Some(expand_home(dir)),
+        None => xdg_dir("XDG_DATA_HOME")
+            .or_else(dirs::data_dir)
+            .map(|dir| dir.join(APP_DIR)),
+    }
+}
+
+/// The local crontab file used by `--local`
+pub fn storage_file(custom_data_dir: Option<&Path>) -> Option<PathBuf> {
+    data_dir(custom_data_dir).map(|dir| dir.join(STORAGE_FILE))
+}
+
+/// The entries both sides had after the last `sync`, the base for merging
+pub fn sync_base_file(custom_data_dir: Option<&Path>) -> Option<PathBuf> {
+    data_dir(custom_data_dir).map(|dir| dir.join(SYNC_BASE_FILE))
+}
+
+/// The sandbox crontab cloned from the local backend, or from a remote host
+pub fn sandbox_file(custom_data_dir: Option<&Path>, host: Option<&str>) -> Option<PathBuf> {
+    let dir = data_dir(custom_data_dir)?.join(SANDBOX_DIR);
+    Some(match host {
+        Some(host) => dir.join("hosts").join(host.replace('/', "_")),
+        None => dir.join(STORAGE_FILE),
+    })
+}
+
+/// Where files that may hold commands and their secrets are written for
+/// other programs to read
+pub fn private_dir(custom_data_dir: Option<&Path>) -> Option<PathBuf> {
+    data_dir(custom_data_dir).map(|dir| dir.join(PRIVATE_DIR))
+}
+
+/// Write a file only this user can read into `dir`, replacing one left from
+/// before. The file is created afresh, so a link planted in its place is
+/// never followed.
+pub fn write_private(dir: &Path, name: &str, content: &str) -> Result<PathBuf> {
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create directory: {:?}", dir))?;
+    let path = dir.join(name);
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o700))
+            .with_context(|| format!("Failed to restrict directory: {:?}", dir))?;
+        options.mode(0o600);
+    }
+    match fs::remove_file(&path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => {
+            return Err(e).with_context(|| format!("Failed to replace {:?}", path));
+        }
+        _ => {}
+    }
+    options
+        .open(&path)
+        .and_then(|mut file| file.write_all(content.as_bytes()))
+        .with_context(|| format!("Failed to write {:?}", path))?;
+    Ok(path)
+}
+
+pub fn legacy_storage_file() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(LEGACY_STORAGE_FILE))
+}
+
+/// Move `~/.cron-manager-crontab` to `target` if only the old file exists.
+/// Returns whether anything was moved.
+pub fn migrate_legacy_storage(target: &Path) -> Result<bool> {
+    let Some(legacy) = legacy_storage_file() else {
+        return Ok(false);
+    };
+    if target.exists() || !legacy.exists() {
+        return Ok(false);
+    }
+
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create data directory: {:?}", parent))?;
+    }
+    // `rename` fails across filesystems; fall back to copy and remove
+    if fs::rename(&legacy, target).is_err() {
+        fs::copy(&legacy, target)
+            .with_context(|| format!("Failed to migrate {:?} to {:?}", legacy, target))?;
+        fs::remove_file(&legacy)
+            .with_context(|| format!("Failed to remove migrated file: {:?}", legacy))?;
+    }
+    Ok(true)
+}
+
+/// An XDG base directory from the environment; relative paths are ignored
+/// as the spec requires
+fn xdg_dir(var: &str) -> Option<PathBuf> {
+    std::env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+}
+
+/// Expand a leading `~` to the home directory
+pub fn expand_home(path: &Path) -> PathBuf {
+    match path.strip_prefix("~") {
+        Ok(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn test_custom_data_dir() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(data_dir(Some(Path::new("~/cron"))), Some(home.join("cron")));
+        assert_eq!(
+            storage_file(Some(Path::new("/srv/cron-manager"))),
+            Some(PathBuf::from("/srv/cron-manager/crontab"))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_private_replaces_links() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = TempDir::new("private");
+        let target = dir.join("target");
+        fs::write(&target, "keep").unwrap();
+        std::os::unix::fs::symlink(&target, dir.join("edit.crontab")).unwrap();
+
+        let path = write_private(dir.path(), "edit.crontab", "secret").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "secret");
+        assert_eq!(fs::read_to_string(&target).unwrap(), "keep");
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        assert_eq!(fs::metadata(dir.path()).unwrap().permissions().mode() & 0o777, 0o700);
+    }
+}
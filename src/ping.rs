@@ -0,0 +1,115 @@
+use crate::schedule::timezone::ScheduleZone;
+use crate::schedule::CronSchedule;
+use crate::secrets;
+use crate::watchdog;
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Seconds one ping may take, so a slow monitoring service can't hold up the job
+const PING_TIMEOUT_SECS: u32 = 10;
+
+/// What a ping tells the monitoring service, in the URL scheme of
+/// healthchecks.io and compatible dead-man's switches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    Start,
+    /// The run ended with this exit code (`None` when a signal killed it)
+    Finish(Option<i32>),
+}
+
+/// Whether the ping URL is one `curl` can request: http(s), or a
+/// `!secret NAME` resolved when the entry is saved
+pub fn valid_url(url: &str) -> bool {
+    url.starts_with("https://") || url.starts_with("http://") || url.starts_with(secrets::PLACEHOLDER)
+}
+
+/// The URL to request for the signal: `<url>/start`, `<url>` on success,
+/// `<url>/<exit code>` on failure, or `<url>/fail` without one
+pub fn signal_url(url: &str, signal: Signal) -> String {
+    let url = url.trim_end_matches('/');
+    match signal {
+        Signal::Start => format!("{}/start", url),
+        Signal::Finish(Some(0)) => url.to_string(),
+        Signal::Finish(Some(code)) => format!("{}/{}", url, code),
+        Signal::Finish(None) => format!("{}/fail", url),
+    }
+}
+
+/// Ping the monitoring service with `curl`, posting `body` (the end of the
+/// run's output) when given. Failures are reported but never change the
+/// job's result.
+pub fn send(url: &str, signal: Signal, body: Option<&str>) {
+    let target = signal_url(url, signal);
+    let mut curl = Command::new("curl");
+    curl.args(["-fsS", "-o", "/dev/null", "--retry", "3", "-m", &PING_TIMEOUT_SECS.to_string()]);
+    if body.is_some() {
+        curl.args(["--data-binary", "@-"]).stdin(Stdio::piped());
+    }
+    let result = curl.arg(&target).spawn().and_then(|mut child| {
+        if let (Some(mut stdin), Some(body)) = (child.stdin.take(), body) {
+            let _ = stdin.write_all(body.as_bytes());
+        }
+        child.wait()
+    });
+    match result {
+        Ok(status) if !status.success() => eprintln!("Ping failed ({}): {}", status, redact(&target)),
+        Ok(_) => {}
+        Err(e) => eprintln!("Failed to run curl for ping {}: {}", redact(&target), e),
+    }
+}
+
+/// The URL without its path, which holds the check's secret id; `!secret NAME`
+/// placeholders are shown as they are
+pub fn redact(url: &str) -> String {
+    if url.starts_with(secrets::PLACEHOLDER) {
+        return url.to_string();
+    }
+    match url.split_once("://") {
+        Some((scheme, rest)) => format!("{}://{}/…", scheme, rest.split('/').next().unwrap_or(rest)),
+        None => "…".to_string(),
+    }
+}
+
+/// When the run that should have pinged after `last_ping` was due, if it is
+/// more than `grace` overdue at `now`. Nothing is known before the first ping.
+pub fn overdue(
+    schedule: &CronSchedule,
+    zone: ScheduleZone,
+    last_ping: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+    grace: Duration,
+) -> Option<DateTime<FixedOffset>> {
+    watchdog::missed_run(schedule, zone, last_ping?, now, grace, |_| false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signal_urls() {
+        let url = "https://hc-ping.com/0f1e2d3c/";
+        assert_eq!(signal_url(url, Signal::Start), "https://hc-ping.com/0f1e2d3c/start");
+        assert_eq!(signal_url(url, Signal::Finish(Some(0))), "https://hc-ping.com/0f1e2d3c");
+        assert_eq!(signal_url(url, Signal::Finish(Some(124))), "https://hc-ping.com/0f1e2d3c/124");
+        assert_eq!(signal_url(url, Signal::Finish(None)), "https://hc-ping.com/0f1e2d3c/fail");
+        assert_eq!(redact(url), "https://hc-ping.com/…");
+        assert_eq!(redact("!secret hc_backup"), "!secret hc_backup");
+        assert!(valid_url("!secret hc_backup") && !valid_url("hc-ping.com/0f1e2d3c"));
+    }
+
+    #[test]
+    fn test_overdue_after_the_grace_period() {
+        let schedule = CronSchedule::parse("0 * * * *").unwrap();
+        let zone = ScheduleZone::Named(chrono_tz::UTC);
+        let at = |time: &str| DateTime::parse_from_rfc3339(time).unwrap().with_timezone(&Utc);
+        let grace = Duration::minutes(15);
+
+        let last = Some(at("2024-06-01T10:00:05Z"));
+        assert_eq!(overdue(&schedule, zone, last, at("2024-06-01T11:14:00Z"), grace), None);
+        let due = overdue(&schedule, zone, last, at("2024-06-01T11:16:00Z"), grace).unwrap();
+        assert_eq!(due.with_timezone(&Utc), at("2024-06-01T11:00:00Z"));
+        assert_eq!(overdue(&schedule, zone, None, at("2024-06-01T11:16:00Z"), grace), None);
+    }
+}
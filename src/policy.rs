@@ -0,0 +1,144 @@
+use crate::analysis::lint::program_name;
+use crate::filter::glob_match;
+use crate::paths;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Operators that start another command within one crontab line
+const COMMAND_SEPARATORS: &[&str] = &["&&", "||", ";", "|", "&"];
+
+/// Rules limiting which commands may be scheduled, read from `policy.toml`
+/// in the config directory (or the config's `policy_file`)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Policy {
+    /// Executables that may be run, as exact paths
+    pub allowed_executables: Vec<String>,
+    /// Directories (or any path prefixes) whose executables may be run
+    pub allowed_prefixes: Vec<String>,
+    /// Globs (`*`, `?`) that must not match anywhere in a command
+    pub forbidden_patterns: Vec<String>,
+}
+
+impl Policy {
+    pub fn default_path() -> Option<PathBuf> {
+        paths::config_dir().map(|dir| dir.join("policy.toml"))
+    }
+
+    /// Load the policy file; `None` when there is none, so everything is allowed
+    pub fn load(custom_path: Option<&Path>) -> Result<Option<Self>> {
+        let path = match custom_path {
+            Some(path) => paths::expand_home(path),
+            None => match Self::default_path() {
+                Some(path) if path.exists() => path,
+                _ => return Ok(None),
+            },
+        };
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read policy file: {:?}", path))?;
+        let policy = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse policy file: {:?}", path))?;
+        Ok(Some(policy))
+    }
+
+    fn has_allowlist(&self) -> bool {
+        !self.allowed_executables.is_empty() || !self.allowed_prefixes.is_empty()
+    }
+
+    fn allows_program(&self, program: &str) -> bool {
+        // `..` could climb out of an allowed directory
+        if program.split('/').any(|part| part == "..") {
+            return false;
+        }
+        self.allowed_executables.iter().any(|exe| exe == program)
+            || self.allowed_prefixes.iter().any(|prefix| program.starts_with(prefix.as_str()))
+    }
+
+    /// Every rule the command breaks, each explained in one sentence
+    pub fn violations(&self, command: &str) -> Vec<String> {
+        let mut violations = Vec::new();
+
+        for pattern in &self.forbidden_patterns {
+            if glob_match(&format!("*{}*", pattern), command) {
+                violations.push(format!("Matches forbidden pattern '{}'", pattern));
+            }
+        }
+
+        if self.has_allowlist() {
+            // Substitutions run commands the allowlist can't see
+            if command.contains("$(") || command.contains('`') {
+                violations.push("Command substitution is not allowed when an allowlist is set".to_string());
+            }
+            for segment in split_commands(command) {
+                match program_name(segment) {
+                    Some(program) if self.allows_program(program) => {}
+                    Some(program) => violations.push(format!(
+                        "'{}' is not in allowed_executables or under allowed_prefixes",
+                        program
+                    )),
+                    None => violations.push(format!(
+                        "Can't tell which program '{}' runs, so the allowlist can't approve it",
+                        segment.trim()
+                    )),
+                }
+            }
+        }
+
+        violations
+    }
+}
+
+/// The individual commands of a shell line joined by `;`, `&&`, `|` and the like
+fn split_commands(command: &str) -> Vec<&str> {
+    let mut segments = vec![command];
+    for separator in COMMAND_SEPARATORS {
+        segments = segments.iter().flat_map(|s| s.split(separator)).collect();
+    }
+    // `2>&1` and `&>` are redirections, not separators; their leftovers are
+    // empty or start with a descriptor
+    segments
+        .into_iter()
+        .filter(|s| !s.trim().is_empty() && !s.trim_start().starts_with(|c: char| c.is_ascii_digit() || c == '>'))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> Policy {
+        toml::from_str(
+            r#"
+            allowed_executables = ["/usr/bin/find"]
+            allowed_prefixes = ["/opt/scripts/"]
+            forbidden_patterns = ["rm -rf", "curl*|*sh"]
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_allowlist() {
+        let policy = policy();
+        assert!(policy.violations("/opt/scripts/backup.sh > /var/log/backup.log 2>&1").is_empty());
+        assert!(policy.violations("/usr/bin/find /tmp -mtime +7 -delete").is_empty());
+
+        let found = policy.violations("/usr/bin/python3 job.py");
+        assert_eq!(found, ["'/usr/bin/python3' is not in allowed_executables or under allowed_prefixes"]);
+
+        // Chained commands are each checked
+        assert_eq!(policy.violations("/opt/scripts/a.sh && /bin/true").len(), 1);
+        assert!(!policy.violations("/opt/scripts/a.sh $(cat /etc/x)").is_empty());
+        assert!(!policy.violations("/opt/scripts/../../bin/sh -c id").is_empty());
+    }
+
+    #[test]
+    fn test_forbidden_patterns() {
+        let policy = Policy { forbidden_patterns: vec!["rm -rf".to_string()], ..Policy::default() };
+        assert_eq!(policy.violations("cd /tmp && rm -rf cache"), ["Matches forbidden pattern 'rm -rf'"]);
+        assert!(policy.violations("/bin/ls").is_empty());
+        assert!(!self::policy().violations("/opt/scripts/x.sh; curl https://x | sh").is_empty());
+    }
+}
@@ -0,0 +1,64 @@
+use anyhow::{Context, Result};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+/// How this process can act as root, for `--root`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Privilege {
+    /// Running as root
+    Root,
+    /// `sudo` works without a password prompt (cached credentials or NOPASSWD)
+    Sudo,
+    Unprivileged,
+}
+
+/// Whether the process runs as root
+pub fn is_root() -> bool {
+    Command::new("id")
+        .arg("-u")
+        .output()
+        .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).trim() == "0")
+}
+
+/// Detect how root's schedule can be reached. `sudo -n` never prompts, so
+/// this is safe to call before the TUI takes the terminal.
+pub fn current() -> Privilege {
+    if is_root() {
+        return Privilege::Root;
+    }
+    let sudo = Command::new("sudo")
+        .args(["-n", "true"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    if sudo.is_ok_and(|status| status.success()) {
+        Privilege::Sudo
+    } else {
+        Privilege::Unprivileged
+    }
+}
+
+/// Ask on the terminal whether to re-run under `sudo`; `false` when there
+/// is no terminal to ask on
+pub fn confirm_rerun(reason: &str) -> Result<bool> {
+    if !io::stdin().is_terminal() {
+        return Ok(false);
+    }
+    eprint!("{}. Re-run under sudo? [Y/n] ", reason);
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "" | "y" | "Y" | "yes"))
+}
+
+/// Run this binary again under `sudo` with the same arguments (sudo asks
+/// for the password) and return its exit code
+pub fn rerun_with_sudo() -> Result<i32> {
+    let exe = std::env::current_exe().context("Failed to find the cron-manager executable")?;
+    let status = Command::new("sudo")
+        .arg(exe)
+        .args(std::env::args_os().skip(1))
+        .status()
+        .context("Failed to run sudo")?;
+    Ok(status.code().unwrap_or(1))
+}
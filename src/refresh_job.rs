@@ -0,0 +1,58 @@
+use crate::analysis::collisions::{self, CollisionReport};
+use crate::analysis::lint::{self, Finding};
+use crate::config::Config;
+use crate::cron_entry::CronEntry;
+use crate::run_history::{self, RunRecord};
+use crate::storage::Storage;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+
+/// The analyses worked out for a new minute
+#[derive(Debug)]
+pub struct Refreshed {
+    /// The entries analysed, so results for a list edited meanwhile can be dropped
+    pub entries: Vec<CronEntry>,
+    pub collisions: CollisionReport,
+    pub lint_findings: Vec<Finding>,
+    /// The run history of the last `STATS_DAYS` days, read at `read_at`
+    pub runs: Result<Vec<RunRecord>>,
+    pub read_at: DateTime<Utc>,
+}
+
+/// The analyses that look ahead from "now", redone on their own thread when
+/// the minute rolls over: they walk every run in the collision horizon and
+/// read the run history, which is too slow for the UI thread
+#[derive(Debug)]
+pub struct RefreshJob {
+    result: Receiver<Refreshed>,
+}
+
+impl RefreshJob {
+    pub fn start(storage: Arc<Storage>, config: Config, entries: Vec<CronEntry>, now: NaiveDateTime) -> Self {
+        let (sender, result) = mpsc::channel();
+        thread::spawn(move || {
+            let read_at = Utc::now();
+            let since = read_at - chrono::Duration::days(run_history::STATS_DAYS as i64);
+            let _ = sender.send(Refreshed {
+                collisions: collisions::analyze(&entries, now, &config.collisions),
+                lint_findings: lint::lint(&entries, &storage, &config, now),
+                runs: storage.runs_since(since, config.data_dir.as_deref()),
+                read_at,
+                entries,
+            });
+        });
+        Self { result }
+    }
+
+    /// The analyses, once they are done
+    pub fn poll(&self) -> Option<Result<Refreshed>> {
+        match self.result.try_recv() {
+            Ok(refreshed) => Some(Ok(refreshed)),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(anyhow!("The refresh stopped unexpectedly"))),
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use crate::cron_entry::CronEntry;
+use crate::secrets::shell_quote;
+use std::process::{Child, Command, ExitStatus};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Exit code of a run stopped at its timeout, the same as `timeout(1)` uses
+pub const TIMEOUT_EXIT_CODE: i32 = 124;
+
+/// How long a stopped command gets to exit on SIGTERM before it is killed
+const KILL_GRACE: Duration = Duration::from_secs(5);
+
+/// How often a running command is checked against its timeout
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long a run may take and how often a failed one is tried again
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub timeout: Option<Duration>,
+    pub retries: u32,
+    pub delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn of(entry: &CronEntry) -> Self {
+        Self {
+            timeout: entry.timeout_secs.map(Duration::from_secs),
+            retries: entry.retries,
+            delay: Duration::from_secs(entry.retry_delay_secs),
+        }
+    }
+
+    /// The `exec` options that enforce the policy, empty when there is nothing to enforce
+    pub fn exec_options(&self) -> String {
+        let mut options = String::new();
+        if let Some(timeout) = self.timeout {
+            options.push_str(&format!(" --timeout {}", timeout.as_secs()));
+        }
+        if self.retries > 0 {
+            options.push_str(&format!(" --retries {}", self.retries));
+            if !self.delay.is_zero() {
+                options.push_str(&format!(" --retry-delay {}", self.delay.as_secs()));
+            }
+        }
+        options
+    }
+}
+
+/// `command` run by `timeout(1)`, which stops it with SIGTERM once it has
+/// run for `seconds`
+pub fn timeout_command(command: &str, seconds: u64) -> String {
+    format!("timeout {} sh -c {}", seconds, shell_quote(command))
+}
+
+/// Start the command in a process group of its own, so that `terminate`
+/// stops whatever it started too
+pub fn own_process_group(command: &mut Command) {
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(command, 0);
+    #[cfg(not(unix))]
+    let _ = command;
+}
+
+/// Wait for the child to exit, stopping it once it has run for `timeout`;
+/// `None` means it was stopped
+pub fn wait_with_timeout(child: &mut Child, timeout: Option<Duration>) -> std::io::Result<Option<ExitStatus>> {
+    let Some(timeout) = timeout else {
+        return child.wait().map(Some);
+    };
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            terminate(child);
+            return Ok(None);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Stop a child started with `own_process_group` and everything it started:
+/// SIGTERM to the group, then SIGKILL if it hasn't exited after a grace period
+pub fn terminate(child: &mut Child) {
+    #[cfg(unix)]
+    {
+        let group = format!("-{}", child.id());
+        let _ = Command::new("kill").args(["-TERM", "--", &group]).status();
+        let deadline = Instant::now() + KILL_GRACE;
+        while Instant::now() < deadline {
+            if !matches!(child.try_wait(), Ok(None)) {
+                return;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+        let _ = Command::new("kill").args(["-KILL", "--", &group]).status();
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exec_options() {
+        let mut entry = CronEntry::new("Sync".to_string(), "0 * * * *".to_string(), "sync".to_string());
+        assert_eq!(RetryPolicy::of(&entry).exec_options(), "");
+        entry.timeout_secs = Some(300);
+        entry.retries = 2;
+        entry.retry_delay_secs = 30;
+        assert_eq!(RetryPolicy::of(&entry).exec_options(), " --timeout 300 --retries 2 --retry-delay 30");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_timeout_stops_the_whole_group() {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg("sleep 30 & sleep 30");
+        own_process_group(&mut command);
+        let mut child = command.spawn().unwrap();
+        let started = Instant::now();
+        assert!(wait_with_timeout(&mut child, Some(Duration::from_millis(200))).unwrap().is_none());
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        let mut child = Command::new("sh").arg("-c").arg("exit 2").spawn().unwrap();
+        let status = wait_with_timeout(&mut child, Some(Duration::from_secs(10))).unwrap();
+        assert_eq!(status.and_then(|s| s.code()), Some(2));
+    }
+}
@@ -0,0 +1,421 @@
+use crate::paths;
+use crate::retry;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// SQLite database of run records inside the data directory
+const HISTORY_FILE: &str = "runs.db";
+
+/// Run records written before the database, imported into it once
+const LEGACY_FILE: &str = "runs.jsonl";
+
+/// Bytes of a run's output kept in its record: the end, where errors are
+const OUTPUT_LIMIT: usize = 4096;
+
+/// Days covered by the per-entry statistics
+pub const STATS_DAYS: usize = 30;
+
+/// One finished run of an entry's command, recorded by `cron-manager exec`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub entry: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    /// `None` when the command was killed by a signal
+    pub exit_code: Option<i32>,
+    /// The end of what the command printed, stdout and stderr together
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub output: String,
+}
+
+impl RunRecord {
+    pub fn succeeded(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Run records kept in a SQLite database, so concurrent runs can add theirs
+/// without clobbering each other
+#[derive(Debug, Clone)]
+pub struct RunHistory {
+    path: PathBuf,
+}
+
+impl RunHistory {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn default_path(custom_data_dir: Option<&Path>) -> Option<PathBuf> {
+        paths::data_dir(custom_data_dir).map(|dir| dir.join(HISTORY_FILE))
+    }
+
+    /// Open the database, creating it (and importing the old JSON lines
+    /// file next to it) the first time
+    fn open(&self) -> Result<Connection> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {:?}", parent))?;
+        }
+        let mut connection = Connection::open(&self.path)
+            .with_context(|| format!("Failed to open run history: {:?}", self.path))?;
+        // Runs finishing at the same moment wait for each other's writes
+        connection.busy_timeout(std::time::Duration::from_secs(5))?;
+        let transaction = connection.transaction()?;
+        transaction.execute(
+            "CREATE TABLE IF NOT EXISTS runs (
+                entry TEXT NOT NULL,
+                started_at TEXT NOT NULL,
+                duration_ms INTEGER NOT NULL,
+                exit_code INTEGER,
+                output TEXT NOT NULL DEFAULT ''
+            )",
+            [],
+        )?;
+        transaction.execute("CREATE INDEX IF NOT EXISTS runs_started_at ON runs (started_at)", [])?;
+        let legacy = self.path.with_file_name(LEGACY_FILE);
+        if legacy.exists() && Self::count(&transaction)? == 0 {
+            let content = fs::read_to_string(&legacy)
+                .with_context(|| format!("Failed to read run history: {:?}", legacy))?;
+            for record in content.lines().filter_map(|line| serde_json::from_str::<RunRecord>(line).ok()) {
+                Self::insert(&transaction, &record)?;
+            }
+            fs::rename(&legacy, legacy.with_extension("jsonl.imported"))
+                .with_context(|| format!("Failed to rename imported run history: {:?}", legacy))?;
+        }
+        transaction.commit()?;
+        Ok(connection)
+    }
+
+    fn count(connection: &Connection) -> Result<i64> {
+        Ok(connection.query_row("SELECT COUNT(*) FROM runs", [], |row| row.get(0))?)
+    }
+
+    fn insert(connection: &Connection, record: &RunRecord) -> Result<()> {
+        connection.execute(
+            "INSERT INTO runs (entry, started_at, duration_ms, exit_code, output) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                record.entry,
+                timestamp(record.started_at),
+                record.duration_ms as i64,
+                record.exit_code,
+                record.output
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn append(&self, record: &RunRecord) -> Result<()> {
+        Self::insert(&self.open()?, record)
+            .with_context(|| format!("Failed to write run history: {:?}", self.path))
+    }
+
+    /// Records of runs started at or after `since`, oldest first
+    pub fn load_since(&self, since: DateTime<Utc>) -> Result<Vec<RunRecord>> {
+        if !self.path.exists() && !self.path.with_file_name(LEGACY_FILE).exists() {
+            return Ok(Vec::new());
+        }
+        let connection = self.open()?;
+        let mut statement = connection.prepare(
+            "SELECT entry, started_at, duration_ms, exit_code, output FROM runs
+             WHERE started_at >= ?1 ORDER BY started_at",
+        )?;
+        let rows = statement.query_map([timestamp(since)], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?, row.get(3)?, row.get(4)?))
+        })?;
+        let mut records = Vec::new();
+        for row in rows {
+            let (entry, started_at, duration_ms, exit_code, output) =
+                row.with_context(|| format!("Failed to read run history: {:?}", self.path))?;
+            // Rows from elsewhere with an unreadable time are skipped
+            let Ok(started_at) = DateTime::parse_from_rfc3339(&started_at) else {
+                continue;
+            };
+            records.push(RunRecord {
+                entry,
+                started_at: started_at.with_timezone(&Utc),
+                duration_ms: duration_ms as u64,
+                exit_code,
+                output,
+            });
+        }
+        Ok(records)
+    }
+}
+
+/// A fixed-width UTC time, so the database orders times as text correctly
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Nanos, true)
+}
+
+/// How a command run by `run_captured` ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedRun {
+    /// `None` when a signal killed it, `TIMEOUT_EXIT_CODE` when it was
+    /// stopped at its timeout
+    pub exit_code: Option<i32>,
+    /// The last `OUTPUT_LIMIT` bytes of its output, both streams together
+    pub output: String,
+    /// The last `OUTPUT_LIMIT` bytes of its standard error alone
+    pub stderr: String,
+}
+
+/// Run `command` through `sh`, passing its output on to ours while keeping
+/// the end of it, stopping it at `timeout`
+pub fn run_captured(command: &str, timeout: Option<std::time::Duration>) -> Result<CapturedRun> {
+    let mut process = Command::new("sh");
+    process.arg("-c").arg(command).stdout(Stdio::piped()).stderr(Stdio::piped());
+    retry::own_process_group(&mut process);
+    let mut child = process.spawn().context("Failed to start the command")?;
+    let output = Arc::new(Mutex::new(Vec::new()));
+    let stderr = Arc::new(Mutex::new(Vec::new()));
+    let readers = [
+        child.stdout.take().map(|pipe| tee(pipe, std::io::stdout(), vec![Arc::clone(&output)])),
+        child.stderr.take().map(|pipe| tee(pipe, std::io::stderr(), vec![Arc::clone(&output), Arc::clone(&stderr)])),
+    ];
+    let status = retry::wait_with_timeout(&mut child, timeout).context("Failed to wait for the command")?;
+    for reader in readers.into_iter().flatten() {
+        let _ = reader.join();
+    }
+    let text = |captured: &Mutex<Vec<u8>>| {
+        let bytes = captured.lock().map(|bytes| bytes.clone()).unwrap_or_default();
+        String::from_utf8_lossy(&bytes).into_owned()
+    };
+    let mut run = CapturedRun { exit_code: status.and_then(|status| status.code()), output: text(&output), stderr: text(&stderr) };
+    if status.is_none() {
+        let note = format!("Timed out after {}s\n", timeout.unwrap_or_default().as_secs());
+        eprint!("{}", note);
+        run.output.push_str(&note);
+        run.stderr.push_str(&note);
+        run.exit_code = Some(retry::TIMEOUT_EXIT_CODE);
+    }
+    Ok(run)
+}
+
+/// Copy a pipe to `out` on a thread of its own, adding what passes through
+/// to the end of each of `captured`
+fn tee(
+    mut pipe: impl Read + Send + 'static,
+    mut out: impl Write + Send + 'static,
+    captured: Vec<Arc<Mutex<Vec<u8>>>>,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let mut buffer = [0; 8192];
+        while let Ok(n) = pipe.read(&mut buffer) {
+            if n == 0 {
+                break;
+            }
+            let _ = out.write_all(&buffer[..n]);
+            let _ = out.flush();
+            for captured in &captured {
+                if let Ok(mut captured) = captured.lock() {
+                    captured.extend_from_slice(&buffer[..n]);
+                    let excess = captured.len().saturating_sub(OUTPUT_LIMIT);
+                    captured.drain(..excess);
+                }
+            }
+        }
+    })
+}
+
+/// Success rate and timing of an entry's recent runs
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunStats {
+    pub runs: usize,
+    pub successes: usize,
+    pub average_duration: Duration,
+    /// Percentage of successful runs per day, oldest first; `None` for days
+    /// without runs
+    pub daily_success: Vec<Option<u64>>,
+}
+
+impl RunStats {
+    /// Successful runs as a percentage
+    pub fn success_rate(&self) -> u64 {
+        (self.successes * 100 / self.runs.max(1)) as u64
+    }
+}
+
+/// Running totals for one entry while its records are counted
+#[derive(Default)]
+struct Totals {
+    runs: usize,
+    successes: usize,
+    duration_ms: u64,
+    /// Runs and successful runs per day, oldest first
+    days: Vec<(u64, u64)>,
+}
+
+/// Statistics per entry name over the `STATS_DAYS` days before `now`
+pub fn stats(records: &[RunRecord], now: DateTime<Utc>) -> HashMap<String, RunStats> {
+    let mut totals: HashMap<&str, Totals> = HashMap::new();
+    for record in records {
+        let age = now.signed_duration_since(record.started_at).num_days();
+        if !(0..STATS_DAYS as i64).contains(&age) {
+            continue;
+        }
+        let total = totals.entry(record.entry.as_str()).or_insert_with(|| Totals {
+            days: vec![(0, 0); STATS_DAYS],
+            ..Totals::default()
+        });
+        let day = &mut total.days[STATS_DAYS - 1 - age as usize];
+        total.runs += 1;
+        total.duration_ms += record.duration_ms;
+        day.0 += 1;
+        if record.succeeded() {
+            total.successes += 1;
+            day.1 += 1;
+        }
+    }
+
+    totals
+        .into_iter()
+        .map(|(entry, total)| {
+            let stats = RunStats {
+                runs: total.runs,
+                successes: total.successes,
+                average_duration: Duration::milliseconds((total.duration_ms / total.runs as u64) as i64),
+                daily_success: total
+                    .days
+                    .into_iter()
+                    .map(|(runs, successes)| (runs > 0).then(|| successes * 100 / runs))
+                    .collect(),
+            };
+            (entry.to_string(), stats)
+        })
+        .collect()
+}
+
+/// Records as CSV with a header row; `exit_code` is empty for runs killed
+/// by a signal
+pub fn to_csv(records: &[RunRecord]) -> String {
+    let mut csv = String::from("entry,started_at,duration_ms,exit_code,succeeded\n");
+    for record in records {
+        let exit_code = record.exit_code.map(|code| code.to_string()).unwrap_or_default();
+        csv.push_str(&format!(
+            "{},{},{},{},{}\n",
+            csv_field(&record.entry),
+            record.started_at.to_rfc3339(),
+            record.duration_ms,
+            exit_code,
+            record.succeeded()
+        ));
+    }
+    csv
+}
+
+/// Quote a field containing separators, quotes or line breaks
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn record(entry: &str, hours_ago: i64, duration_ms: u64, exit_code: i32, now: DateTime<Utc>) -> RunRecord {
+        RunRecord {
+            entry: entry.to_string(),
+            started_at: now - Duration::hours(hours_ago),
+            duration_ms,
+            exit_code: Some(exit_code),
+            output: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_stats_per_entry() {
+        let now = Utc::now();
+        let records = vec![
+            record("Backup", 1, 1000, 0, now),
+            record("Backup", 2, 3000, 1, now),
+            record("Backup", 50, 2000, 0, now),
+            record("Backup", 24 * 40, 9000, 1, now),
+            record("Report", 3, 500, 0, now),
+        ];
+
+        let stats = stats(&records, now);
+        let backup = &stats["Backup"];
+        assert_eq!(backup.runs, 3);
+        assert_eq!(backup.success_rate(), 66);
+        assert_eq!(backup.average_duration, Duration::seconds(2));
+        assert_eq!(backup.daily_success.len(), STATS_DAYS);
+        assert_eq!(backup.daily_success[STATS_DAYS - 1], Some(50));
+        assert_eq!(backup.daily_success[STATS_DAYS - 3], Some(100));
+        assert_eq!(backup.daily_success[0], None);
+        assert_eq!(stats["Report"].success_rate(), 100);
+    }
+
+    #[test]
+    fn test_append_and_load() {
+        let dir = TempDir::new("runs");
+        let now = Utc::now();
+        // Records from before the database are imported the first time it opens
+        let legacy = record("Legacy", 2, 10, 0, now);
+        fs::write(dir.join(LEGACY_FILE), format!("{}\n", serde_json::to_string(&legacy).unwrap())).unwrap();
+        let history = RunHistory::new(dir.join(HISTORY_FILE));
+
+        history.append(&record("Old", 24 * 60, 10, 0, now)).unwrap();
+        let new = RunRecord { output: "failed: disk full\n".to_string(), ..record("New", 1, 10, 2, now) };
+        history.append(&new).unwrap();
+        let loaded = history.load_since(now - Duration::days(30)).unwrap();
+        assert_eq!(loaded, [legacy, new]);
+        assert!(!loaded[1].succeeded());
+        assert!(!dir.join(LEGACY_FILE).exists());
+    }
+
+    #[test]
+    fn test_run_captured_keeps_the_end() {
+        let run = run_captured("echo first; head -c 5000 /dev/zero | tr '\\0' x; echo", None).unwrap();
+        assert_eq!(run.exit_code, Some(0));
+        assert_eq!(run.output.len(), OUTPUT_LIMIT);
+        assert!(run.output.ends_with("xxx\n"));
+        assert!(!run.output.contains("first"));
+        assert_eq!(run.stderr, "");
+
+        // The two streams are read separately, so only check stderr on its own
+        let run = run_captured("echo oops >&2; exit 4", None).unwrap();
+        assert_eq!(run.exit_code, Some(4));
+        assert_eq!(run.output, "oops\n");
+        assert_eq!(run.stderr, "oops\n");
+
+        let run = run_captured("sleep 30", Some(std::time::Duration::from_secs(1))).unwrap();
+        assert_eq!(run.exit_code, Some(crate::retry::TIMEOUT_EXIT_CODE));
+        assert_eq!(run.output, "Timed out after 1s\n");
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let started_at = DateTime::parse_from_rfc3339("2024-06-01T02:00:00Z").unwrap().with_timezone(&Utc);
+        let records = vec![
+            RunRecord { entry: "Backup".to_string(), started_at, duration_ms: 1500, exit_code: Some(0), output: String::new() },
+            RunRecord {
+                entry: "Report, \"daily\"".to_string(),
+                started_at,
+                duration_ms: 20,
+                exit_code: None,
+                output: String::new(),
+            },
+        ];
+        assert_eq!(
+            to_csv(&records),
+            "entry,started_at,duration_ms,exit_code,succeeded\n\
+             Backup,2024-06-01T02:00:00+00:00,1500,0,true\n\
+             \"Report, \"\"daily\"\"\",2024-06-01T02:00:00+00:00,20,,false\n"
+        );
+    }
+}
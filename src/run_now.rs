@@ -0,0 +1,236 @@
+use crate::retry::{self, RetryPolicy, TIMEOUT_EXIT_CODE};
+use anyhow::{Context, Result};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// One line the command printed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputLine {
+    pub text: String,
+    pub stderr: bool,
+}
+
+/// An entry's command started by hand, with its output collected as it
+/// arrives, stopped at the entry's timeout and started again while it fails
+/// and retries are left. Dropping it kills a command that is still running.
+#[derive(Debug)]
+pub struct ManualRun {
+    /// Name of the entry the command belongs to
+    pub name: String,
+    /// Output so far, in the order it was read
+    pub lines: Vec<OutputLine>,
+    command: String,
+    stdin: Option<String>,
+    policy: RetryPolicy,
+    child: Child,
+    sender: Sender<OutputLine>,
+    output: Receiver<OutputLine>,
+    started: Instant,
+    /// Retries started so far, and when the current try started
+    attempt: u32,
+    attempt_started: Instant,
+    /// When the next try starts, while waiting out the retry delay
+    retry_at: Option<Instant>,
+    timed_out: bool,
+    /// The exit code (`None` for a signal) and how long it took, once the
+    /// last try has ended
+    finished: Option<(Option<i32>, Duration)>,
+}
+
+impl ManualRun {
+    /// Start `command` with `sh -c` in the home directory, like cron does,
+    /// feeding it `stdin` when given
+    pub fn start(name: &str, command: &str, stdin: Option<&str>, policy: RetryPolicy) -> Result<Self> {
+        let (sender, output) = mpsc::channel();
+        let child = spawn(command, stdin, &sender)?;
+        Ok(Self {
+            name: name.to_string(),
+            lines: Vec::new(),
+            command: command.to_string(),
+            stdin: stdin.map(str::to_string),
+            policy,
+            child,
+            sender,
+            output,
+            started: Instant::now(),
+            attempt: 0,
+            attempt_started: Instant::now(),
+            retry_at: None,
+            timed_out: false,
+            finished: None,
+        })
+    }
+
+    /// Take in the output printed since the last call, stop the command at
+    /// its timeout, start the next try when it is due, and check whether the
+    /// command has exited; returns whether the last try finished just now
+    pub fn poll(&mut self) -> bool {
+        self.lines.extend(self.output.try_iter());
+        if self.finished.is_some() {
+            return false;
+        }
+        if let Some(at) = self.retry_at {
+            if Instant::now() >= at {
+                self.retry_at = None;
+                self.timed_out = false;
+                self.attempt_started = Instant::now();
+                match spawn(&self.command, self.stdin.as_deref(), &self.sender) {
+                    Ok(child) => self.child = child,
+                    Err(e) => {
+                        self.lines.push(OutputLine { text: format!("{:#}", e), stderr: true });
+                        self.finished = Some((None, self.started.elapsed()));
+                        return true;
+                    }
+                }
+            }
+            return false;
+        }
+        let exit_code = match self.child.try_wait() {
+            Ok(Some(status)) => status.code(),
+            Ok(None) if self.policy.timeout.is_some_and(|timeout| self.attempt_started.elapsed() >= timeout) => {
+                retry::terminate(&mut self.child);
+                self.timed_out = true;
+                Some(TIMEOUT_EXIT_CODE)
+            }
+            Ok(None) => return false,
+            Err(e) => {
+                tracing::warn!(entry = %self.name, error = %e, "failed to check the manual run");
+                return false;
+            }
+        };
+        // The pipes close when it exits; pick up what's left in them
+        while let Ok(line) = self.output.recv_timeout(Duration::from_millis(100)) {
+            self.lines.push(line);
+        }
+        if exit_code != Some(0) && self.attempt < self.policy.retries {
+            self.attempt += 1;
+            self.retry_at = Some(Instant::now() + self.policy.delay);
+            return false;
+        }
+        self.finished = Some((exit_code, self.started.elapsed()));
+        true
+    }
+
+    /// The exit code (`None` when a signal killed it) and duration, once the
+    /// last try has ended
+    pub fn finished(&self) -> Option<(Option<i32>, Duration)> {
+        self.finished
+    }
+
+    /// Retries started so far, out of how many the entry allows
+    pub fn attempt(&self) -> (u32, u32) {
+        (self.attempt, self.policy.retries)
+    }
+
+    /// Time left before the next try, while waiting out the retry delay
+    pub fn retry_in(&self) -> Option<Duration> {
+        self.retry_at.map(|at| at.saturating_duration_since(Instant::now()))
+    }
+
+    /// Whether the last try was stopped at the entry's timeout
+    pub fn timed_out(&self) -> bool {
+        self.timed_out
+    }
+
+    /// How long the command has been running, or ran
+    pub fn elapsed(&self) -> Duration {
+        self.finished.map_or_else(|| self.started.elapsed(), |(_, duration)| duration)
+    }
+
+    /// Kill the command if it is still running, and give up on retrying it
+    pub fn stop(&mut self) {
+        if self.finished.is_none() {
+            retry::terminate(&mut self.child);
+            self.retry_at = None;
+        }
+    }
+}
+
+impl Drop for ManualRun {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Start one try of the command, its output going to `sender`
+fn spawn(command: &str, stdin: Option<&str>, sender: &Sender<OutputLine>) -> Result<Child> {
+    let mut process = Command::new("sh");
+    process
+        .arg("-c")
+        .arg(command)
+        .stdin(if stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(home) = dirs::home_dir() {
+        process.current_dir(home);
+    }
+    retry::own_process_group(&mut process);
+    let mut child = process.spawn().context("Failed to start the command")?;
+
+    if let (Some(mut pipe), Some(input)) = (child.stdin.take(), stdin) {
+        // Cron ends the text with a newline; a command that exits without
+        // reading it all just closes the pipe
+        let input = format!("{}\n", input);
+        thread::spawn(move || pipe.write_all(input.as_bytes()));
+    }
+    if let Some(pipe) = child.stdout.take() {
+        forward(pipe, false, sender.clone());
+    }
+    if let Some(pipe) = child.stderr.take() {
+        forward(pipe, true, sender.clone());
+    }
+    Ok(child)
+}
+
+/// Read lines from a pipe on a thread of their own and send them on
+fn forward(pipe: impl Read + Send + 'static, stderr: bool, sender: Sender<OutputLine>) {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(pipe);
+        let mut buffer = Vec::new();
+        while matches!(reader.read_until(b'\n', &mut buffer), Ok(n) if n > 0) {
+            let text = String::from_utf8_lossy(&buffer).trim_end_matches(['\n', '\r']).to_string();
+            if sender.send(OutputLine { text, stderr }).is_err() {
+                break;
+            }
+            buffer.clear();
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collects_output_and_status() {
+        let mut run = ManualRun::start("Test", "cat; echo oops >&2; exit 3", Some("first\nsecond"), RetryPolicy::default()).unwrap();
+        wait(&mut run);
+        assert_eq!(run.finished().unwrap().0, Some(3));
+
+        let stdout: Vec<&str> = run.lines.iter().filter(|l| !l.stderr).map(|l| l.text.as_str()).collect();
+        assert_eq!(stdout, ["first", "second"]);
+        assert!(run.lines.contains(&OutputLine { text: "oops".to_string(), stderr: true }));
+    }
+
+    #[test]
+    fn test_retries_until_the_timeout_ends_the_last_try() {
+        let policy = RetryPolicy { timeout: Some(Duration::from_millis(300)), retries: 2, delay: Duration::ZERO };
+        let mut run = ManualRun::start("Test", "echo try; sleep 30", None, policy).unwrap();
+        wait(&mut run);
+        assert_eq!(run.finished().unwrap().0, Some(TIMEOUT_EXIT_CODE));
+        assert!(run.timed_out());
+        assert_eq!(run.attempt(), (2, 2));
+        assert_eq!(run.lines.iter().filter(|l| l.text == "try").count(), 3);
+    }
+
+    fn wait(run: &mut ManualRun) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !run.poll() {
+            assert!(Instant::now() < deadline, "command didn't finish");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use crate::cron_entry::CronEntry;
+use crate::storage::Storage;
+use anyhow::{anyhow, Result};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+/// Frames of the spinner shown while a save runs
+const SPINNER: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// How a background save ended
+#[derive(Debug)]
+pub enum Written {
+    /// Saved; the entries the backend refused, by name since the list may
+    /// have changed meanwhile, with the reason, and the entries as read back
+    Saved(Vec<(String, String)>, Vec<CronEntry>),
+    /// Not saved: the backend changed since the entries were loaded, and
+    /// has these now; they are merged before saving again
+    Changed(Vec<CronEntry>),
+}
+
+/// A save running on its own thread, so backends that shell out for every
+/// entry (launchctl, ssh) don't freeze the UI while they write
+#[derive(Debug)]
+pub struct SaveJob {
+    started: Instant,
+    result: Receiver<Result<Written>>,
+}
+
+impl SaveJob {
+    /// Save `entries` unless the backend no longer has `loaded`
+    pub fn start(storage: Arc<Storage>, entries: Vec<CronEntry>, loaded: Vec<CronEntry>) -> Self {
+        let (sender, result) = mpsc::channel();
+        thread::spawn(move || {
+            let written = (|| {
+                let theirs = storage.load()?;
+                if theirs != loaded {
+                    return Ok(Written::Changed(theirs));
+                }
+                let report = storage.save_changes(&loaded, &entries)?;
+                let refused = report.failed.into_iter().map(|(index, reason)| (entries[index].name.clone(), reason)).collect();
+                // As read back, since some backends don't keep every field
+                Ok(Written::Saved(refused, storage.load()?))
+            })();
+            let _ = sender.send(written);
+        });
+        Self { started: Instant::now(), result }
+    }
+
+    /// The outcome, once the save has finished
+    pub fn poll(&self) -> Option<Result<Written>> {
+        match self.result.try_recv() {
+            Ok(written) => Some(written),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(anyhow!("The save stopped unexpectedly"))),
+        }
+    }
+
+    /// Block until the save has finished
+    pub fn wait(self) -> Result<Written> {
+        self.result.recv().map_err(|_| anyhow!("The save stopped unexpectedly"))?
+    }
+
+    /// The spinner frame for how long the save has been running
+    pub fn spinner(&self) -> char {
+        let frame = (self.started.elapsed().as_millis() / 100) as usize;
+        SPINNER[frame % SPINNER.len()]
+    }
+}
@@ -0,0 +1,107 @@
+use super::{compress_values, CronSchedule, FieldSet};
+use crate::i18n::{fill, Strings};
+
+/// Longest list of times of day spelled out before falling back to fields
+const MAX_TIMES: usize = 6;
+
+/// The schedule in words, e.g. "At 09:00, on weekdays"
+pub fn describe(schedule: &CronSchedule, text: &Strings) -> String {
+    if schedule.reboot {
+        return text.describe_startup.to_string();
+    }
+    let time = describe_time(schedule, text);
+
+    let day_names: Vec<&str> = text.describe_day_names.split(',').collect();
+    let month_names: Vec<&str> = text.describe_month_names.split(',').collect();
+    let names = |set: &FieldSet, names: &[&str]| {
+        set.values().iter().map(|v| names[*v as usize % names.len()]).collect::<Vec<_>>().join(text.describe_name_separator)
+    };
+    let weekdays = || {
+        if schedule.days_of_week.values() == [1, 2, 3, 4, 5] {
+            text.describe_weekdays.to_string()
+        } else {
+            fill(text.describe_days_of_week, &[&names(&schedule.days_of_week, &day_names)])
+        }
+    };
+    let month_days = || fill(text.describe_days_of_month, &[&compress_values(&schedule.days_of_month.values())]);
+
+    let mut qualifiers = Vec::new();
+    match (schedule.days_of_month.len() < 31, schedule.days_of_week.len() < 7) {
+        (false, false) => {}
+        (true, false) => qualifiers.push(month_days()),
+        (false, true) => qualifiers.push(weekdays()),
+        // Like cron, either one when neither field is starred, otherwise both
+        (true, true) if !schedule.days_of_month.is_star() && !schedule.days_of_week.is_star() => {
+            qualifiers.push(fill(text.describe_either, &[&month_days(), &weekdays()]))
+        }
+        (true, true) => qualifiers.extend([month_days(), weekdays()]),
+    }
+    if schedule.months.len() < 12 {
+        // Month names are 1-based
+        let months: Vec<&str> = schedule.months.values().iter().map(|m| month_names[(*m as usize - 1) % 12]).collect();
+        qualifiers.push(fill(text.describe_months, &[&months.join(text.describe_name_separator)]));
+    }
+    if qualifiers.is_empty() {
+        time
+    } else {
+        fill(text.describe_qualified, &[&time, &qualifiers.join(text.describe_list_separator)])
+    }
+}
+
+fn describe_time(schedule: &CronSchedule, text: &Strings) -> String {
+    let (minutes, hours) = (schedule.minutes.values(), schedule.hours.values());
+    if hours.len() == 24 {
+        return match step(&minutes, 60) {
+            _ if minutes.len() == 60 => text.describe_every_minute.to_string(),
+            Some(step) => fill(text.describe_every_minutes, &[&step]),
+            None => fill(text.describe_hourly, &[&compress_values(&minutes)]),
+        };
+    }
+    if let (Some(step), [minute]) = (step(&hours, 24), minutes.as_slice()) {
+        return fill(text.describe_every_hours, &[&step, &format!("{:02}", minute)]);
+    }
+    if hours.len() * minutes.len() <= MAX_TIMES {
+        let times: Vec<String> = hours
+            .iter()
+            .flat_map(|hour| minutes.iter().map(move |minute| format!("{:02}:{:02}", hour, minute)))
+            .collect();
+        return fill(text.describe_at, &[&times.join(text.describe_list_separator)]);
+    }
+    fill(text.describe_minutes_of_hours, &[&compress_values(&minutes), &compress_values(&hours)])
+}
+
+/// The step of values spread evenly from 0 across a field of `size` values
+/// (`*/5` and the like), if they are
+fn step(values: &[u32], size: u32) -> Option<u32> {
+    let step = *values.get(1)?;
+    let evenly = values.iter().enumerate().all(|(i, v)| *v == i as u32 * step);
+    // `*/7` leaves a shorter gap at the end of the hour, so it has one value too many
+    (evenly && values.len() as u32 == size / step).then_some(step)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::Language;
+
+    fn en(expr: &str) -> String {
+        describe(&CronSchedule::parse(expr).unwrap(), Language::English.strings())
+    }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(en("* * * * *"), "Every minute");
+        assert_eq!(en("*/15 * * * *"), "Every 15 minutes");
+        assert_eq!(en("5,35 * * * *"), "Every hour at minute 5,35");
+        assert_eq!(en("30 */6 * * *"), "Every 6 hours at :30");
+        assert_eq!(en("0 9 * * 1-5"), "At 09:00, on weekdays");
+        assert_eq!(en("0 9,18 1,15 * *"), "At 09:00, 18:00, on day 1,15 of the month");
+        assert_eq!(en("0 0 1 1 *"), "At 00:00, on day 1 of the month, in Jan");
+        assert_eq!(en("0 2 13 * 5"), "At 02:00, on day 13 of the month or on Fri");
+        assert_eq!(en("0,30 9-17 * * *"), "At minute 0,30 past hour 9-17");
+        assert_eq!(en("0 0 */2 * 1"), "At 00:00, on day 1,3,5,7,9,11,13,15,17,19,21,23,25,27,29,31 of the month, on Mon");
+        assert_eq!(en("@reboot"), "At startup");
+        let ja = describe(&CronSchedule::parse("0 9 * * 0,6").unwrap(), Language::Japanese.strings());
+        assert_eq!(ja, "09:00（日・土曜日）");
+    }
+}
@@ -0,0 +1,497 @@
+use anyhow::{bail, Context, Result};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike};
+use std::str::FromStr;
+
+/// How many years ahead `next_after` searches before concluding that a
+/// schedule never fires (e.g. `0 0 30 2 *`)
+const SEARCH_YEARS: i32 = 8;
+
+/// Month names accepted in the month field, January first
+pub const MONTH_NAMES: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+/// Day names accepted in the day-of-week field, Sunday first
+pub const DAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// The five fields of a standard cron expression
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Minute,
+    Hour,
+    DayOfMonth,
+    Month,
+    DayOfWeek,
+}
+
+impl FieldKind {
+    pub const ALL: [FieldKind; 5] = [
+        FieldKind::Minute,
+        FieldKind::Hour,
+        FieldKind::DayOfMonth,
+        FieldKind::Month,
+        FieldKind::DayOfWeek,
+    ];
+
+    /// Human-readable field name used in error messages
+    pub fn name(self) -> &'static str {
+        match self {
+            FieldKind::Minute => "minute",
+            FieldKind::Hour => "hour",
+            FieldKind::DayOfMonth => "day",
+            FieldKind::Month => "month",
+            FieldKind::DayOfWeek => "weekday",
+        }
+    }
+
+    /// Inclusive range of values accepted in expressions.
+    /// Day-of-week accepts 7 as an alias for Sunday (0).
+    pub fn bounds(self) -> (u32, u32) {
+        match self {
+            FieldKind::Minute => (0, 59),
+            FieldKind::Hour => (0, 23),
+            FieldKind::DayOfMonth => (1, 31),
+            FieldKind::Month => (1, 12),
+            FieldKind::DayOfWeek => (0, 7),
+        }
+    }
+
+    /// Symbolic names accepted in this field, starting from its lowest value
+    pub fn names(self) -> &'static [&'static str] {
+        match self {
+            FieldKind::Month => &MONTH_NAMES,
+            FieldKind::DayOfWeek => &DAY_NAMES,
+            _ => &[],
+        }
+    }
+
+    /// The value a (case-insensitive) name stands for, e.g. `MON` => 1
+    pub fn name_value(self, name: &str) -> Option<u32> {
+        let (min, _) = self.bounds();
+        self.names()
+            .iter()
+            .position(|n| n.eq_ignore_ascii_case(name))
+            .map(|index| min + index as u32)
+    }
+}
+
+/// The set of values matched by a single cron field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldSet {
+    bits: u64,
+    // Whether the field was written starting with `*`. Cron only ORs the
+    // day-of-month and day-of-week fields when neither of them is starred.
+    star: bool,
+}
+
+impl FieldSet {
+    pub fn parse(kind: FieldKind, text: &str) -> Result<Self> {
+        let (min, max) = kind.bounds();
+        let mut bits = 0u64;
+
+        if text.is_empty() {
+            bail!("Empty {} field", kind.name());
+        }
+
+        for item in text.split(',') {
+            let (base, step) = match item.split_once('/') {
+                Some((base, step)) => {
+                    let step: u32 = step
+                        .parse()
+                        .with_context(|| format!("Invalid step '{}' in {} field", step, kind.name()))?;
+                    if step == 0 {
+                        bail!("Step value must be greater than zero in {} field", kind.name());
+                    }
+                    (base, Some(step))
+                }
+                None => (item, None),
+            };
+
+            let (start, end) = if base == "*" {
+                (min, max)
+            } else if let Some((a, b)) = base.split_once('-') {
+                (parse_value(kind, a)?, parse_value(kind, b)?)
+            } else {
+                let value = parse_value(kind, base)?;
+                // `a/n` means "from a to the end of the range, every n"
+                if step.is_some() { (value, max) } else { (value, value) }
+            };
+
+            if start > end {
+                bail!("Invalid range '{}' in {} field", base, kind.name());
+            }
+
+            let step = step.unwrap_or(1) as usize;
+            for value in (start..=end).step_by(step) {
+                let value = if kind == FieldKind::DayOfWeek && value == 7 { 0 } else { value };
+                bits |= 1 << value;
+            }
+        }
+
+        Ok(Self {
+            bits,
+            star: text.starts_with('*'),
+        })
+    }
+
+    pub fn contains(&self, value: u32) -> bool {
+        value < 64 && self.bits & (1 << value) != 0
+    }
+
+    /// Whether the field was written as `*` or `*/n`
+    pub fn is_star(&self) -> bool {
+        self.star
+    }
+
+    /// All matched values in ascending order
+    pub fn values(&self) -> Vec<u32> {
+        (0..64).filter(|v| self.contains(*v)).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+}
+
+fn parse_value(kind: FieldKind, text: &str) -> Result<u32> {
+    let (min, max) = kind.bounds();
+    if let Some(value) = kind.name_value(text) {
+        return Ok(value);
+    }
+    let value: u32 = text
+        .parse()
+        .with_context(|| format!("Invalid value '{}' in {} field", text, kind.name()))?;
+    if value < min || value > max {
+        bail!(
+            "Value {} out of range in {} field (expected {}-{})",
+            value, kind.name(), min, max
+        );
+    }
+    Ok(value)
+}
+
+/// Replace month and day names in a field with their numbers, leaving
+/// everything else untouched: `MON-FRI` => `1-5`, `jan,jul` => `1,7`
+pub fn replace_names(kind: FieldKind, text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut word = String::new();
+    let flush = |word: &mut String, output: &mut String| {
+        match kind.name_value(word) {
+            Some(value) => output.push_str(&value.to_string()),
+            None => output.push_str(word),
+        }
+        word.clear();
+    };
+
+    for c in text.chars() {
+        if c.is_ascii_alphabetic() {
+            word.push(c);
+        } else {
+            flush(&mut word, &mut output);
+            output.push(c);
+        }
+    }
+    flush(&mut word, &mut output);
+    output
+}
+
+/// A parsed standard five-field cron expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    pub minutes: FieldSet,
+    pub hours: FieldSet,
+    pub days_of_month: FieldSet,
+    pub months: FieldSet,
+    pub days_of_week: FieldSet,
+    /// `@reboot`: runs when cron starts, so never at a time of day and with
+    /// every field empty
+    pub reboot: bool,
+}
+
+impl CronSchedule {
+    /// Parse five fields, or one of cron's `@daily`-style nicknames
+    pub fn parse(expr: &str) -> Result<Self> {
+        if is_reboot(expr) {
+            let none = FieldSet { bits: 0, star: false };
+            return Ok(Self {
+                minutes: none,
+                hours: none,
+                days_of_month: none,
+                months: none,
+                days_of_week: none,
+                reboot: true,
+            });
+        }
+        let expr = expand_nickname(expr);
+        if expr.starts_with('@') {
+            bail!("Unknown nickname '{}' (use @reboot, @yearly, @monthly, @weekly, @daily or @hourly)", expr.trim());
+        }
+        let parts: Vec<&str> = expr.split_whitespace().collect();
+        if parts.len() != 5 {
+            bail!(
+                "Expected 5 fields (minute hour day month weekday), found {}",
+                parts.len()
+            );
+        }
+
+        Ok(Self {
+            minutes: FieldSet::parse(FieldKind::Minute, parts[0])?,
+            hours: FieldSet::parse(FieldKind::Hour, parts[1])?,
+            days_of_month: FieldSet::parse(FieldKind::DayOfMonth, parts[2])?,
+            months: FieldSet::parse(FieldKind::Month, parts[3])?,
+            days_of_week: FieldSet::parse(FieldKind::DayOfWeek, parts[4])?,
+            reboot: false,
+        })
+    }
+
+    pub fn field(&self, kind: FieldKind) -> &FieldSet {
+        match kind {
+            FieldKind::Minute => &self.minutes,
+            FieldKind::Hour => &self.hours,
+            FieldKind::DayOfMonth => &self.days_of_month,
+            FieldKind::Month => &self.months,
+            FieldKind::DayOfWeek => &self.days_of_week,
+        }
+    }
+
+    /// Whether the schedule fires on the given date (ignoring the time of day)
+    pub fn matches_date(&self, date: NaiveDate) -> bool {
+        if !self.months.contains(date.month()) {
+            return false;
+        }
+
+        let dom = self.days_of_month.contains(date.day());
+        let dow = self.days_of_week.contains(date.weekday().num_days_from_sunday());
+
+        if self.days_of_month.is_star() || self.days_of_week.is_star() {
+            dom && dow
+        } else {
+            dom || dow
+        }
+    }
+
+    pub fn matches(&self, time: &NaiveDateTime) -> bool {
+        self.matches_date(time.date())
+            && self.hours.contains(time.hour())
+            && self.minutes.contains(time.minute())
+    }
+
+    /// The first firing time strictly after `after`, or `None` if the
+    /// schedule never fires within the search horizon
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        if self.reboot {
+            return None;
+        }
+        let mut time = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let last_year = time.year() + SEARCH_YEARS;
+
+        while time.year() <= last_year {
+            if !self.matches_date(time.date()) {
+                time = time.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !self.hours.contains(time.hour()) {
+                time = time.with_minute(0)? + Duration::hours(1);
+                continue;
+            }
+            if !self.minutes.contains(time.minute()) {
+                time += Duration::minutes(1);
+                continue;
+            }
+            return Some(time);
+        }
+
+        None
+    }
+
+    /// Iterate over firing times strictly after `after`
+    pub fn upcoming(&self, after: NaiveDateTime) -> impl Iterator<Item = NaiveDateTime> + '_ {
+        std::iter::successors(self.next_after(after), move |t| self.next_after(*t))
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+/// Render a sorted list of values compactly, collapsing consecutive runs
+/// into ranges (e.g. `[1, 2, 3, 5]` becomes `1-3,5`)
+pub fn compress_values(values: &[u32]) -> String {
+    let mut parts = Vec::new();
+    let mut i = 0;
+
+    while i < values.len() {
+        let start = values[i];
+        let mut end = start;
+        while i + 1 < values.len() && values[i + 1] == end + 1 {
+            end = values[i + 1];
+            i += 1;
+        }
+        match end - start {
+            0 => parts.push(start.to_string()),
+            1 => parts.push(format!("{},{}", start, end)),
+            _ => parts.push(format!("{}-{}", start, end)),
+        }
+        i += 1;
+    }
+
+    parts.join(",")
+}
+
+/// A schedule typed by the user, resolved to a standard cron expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedSchedule {
+    pub expression: String,
+    /// The notation the input was converted from, if it wasn't standard cron
+    pub converted_from: Option<&'static str>,
+}
+
+/// Resolve schedule prompt input into a standard cron expression,
+/// converting alternative notations where possible
+pub fn resolve_input(input: &str) -> Result<ResolvedSchedule> {
+    let input = input.trim();
+
+    if natural::looks_like_natural(input) {
+        return Ok(ResolvedSchedule {
+            expression: natural::parse(input)?,
+            converted_from: Some("natural language"),
+        });
+    }
+
+    // Nicknames are kept as typed, but cron only knows them in lowercase
+    if input.starts_with('@') {
+        return Ok(ResolvedSchedule {
+            expression: input.to_ascii_lowercase(),
+            converted_from: None,
+        });
+    }
+
+    if quartz::looks_like_quartz(input) {
+        let expression = quartz::QuartzExpression::parse(input)?.to_standard()?;
+        return Ok(ResolvedSchedule {
+            expression,
+            converted_from: Some("Quartz"),
+        });
+    }
+
+    Ok(ResolvedSchedule {
+        expression: input.to_string(),
+        converted_from: None,
+    })
+}
+
+/// The five fields a `@daily`-style nickname stands for; other
+/// expressions (and `@reboot`, which has none) come back unchanged
+pub fn expand_nickname(expr: &str) -> &str {
+    nickname(expr.trim()).unwrap_or(expr)
+}
+
+/// Whether the expression is `@reboot`
+pub fn is_reboot(expr: &str) -> bool {
+    expr.trim().eq_ignore_ascii_case("@reboot")
+}
+
+/// The expression for cron's `@daily`-style nicknames (`@reboot` has none)
+fn nickname(input: &str) -> Option<&'static str> {
+    Some(match input.to_ascii_lowercase().as_str() {
+        "@yearly" | "@annually" => "0 0 1 1 *",
+        "@monthly" => "0 0 1 * *",
+        "@weekly" => "0 0 * * 0",
+        "@daily" | "@midnight" => "0 0 * * *",
+        "@hourly" => "0 * * * *",
+        _ => return None,
+    })
+}
+
+pub mod describe;
+pub mod natural;
+pub mod normalize;
+pub mod quartz;
+pub mod split;
+pub mod suggest;
+pub mod timezone;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_parse_fields() {
+        let schedule = CronSchedule::parse("*/15 9-17 * * 1-5").unwrap();
+        assert_eq!(schedule.minutes.values(), vec![0, 15, 30, 45]);
+        assert_eq!(schedule.hours.len(), 9);
+        assert_eq!(schedule.days_of_week.values(), vec![1, 2, 3, 4, 5]);
+
+        let sunday = CronSchedule::parse("0 0 * * 7").unwrap();
+        assert_eq!(sunday.days_of_week.values(), vec![0]);
+
+        assert!(CronSchedule::parse("60 * * * *").is_err());
+        assert!(CronSchedule::parse("* * * *").is_err());
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+    }
+
+    #[test]
+    fn test_next_after() {
+        let schedule = CronSchedule::parse("30 2 * * *").unwrap();
+        assert_eq!(schedule.next_after(at("2024-01-01 00:00")), Some(at("2024-01-01 02:30")));
+        assert_eq!(schedule.next_after(at("2024-01-01 02:30")), Some(at("2024-01-02 02:30")));
+
+        let never = CronSchedule::parse("0 0 30 2 *").unwrap();
+        assert_eq!(never.next_after(at("2024-01-01 00:00")), None);
+    }
+
+    #[test]
+    fn test_day_of_month_or_day_of_week() {
+        // Both restricted: fires on the 1st OR on Mondays
+        let schedule = CronSchedule::parse("0 0 1 * 1").unwrap();
+        assert!(schedule.matches(&at("2024-01-01 00:00")));
+        assert!(schedule.matches(&at("2024-01-08 00:00")));
+        assert!(!schedule.matches(&at("2024-01-09 00:00")));
+    }
+
+    #[test]
+    fn test_day_and_month_names() {
+        let schedule = CronSchedule::parse("0 9 * jan,Jul MON-FRI").unwrap();
+        assert_eq!(schedule.months.values(), vec![1, 7]);
+        assert_eq!(schedule.days_of_week.values(), vec![1, 2, 3, 4, 5]);
+        assert_eq!(
+            schedule,
+            CronSchedule::parse("0 9 * 1,7 1-5").unwrap()
+        );
+        assert!(CronSchedule::parse("0 9 MON * *").is_err());
+        assert!(CronSchedule::parse("0 9 * * FUNDAY").is_err());
+
+        assert_eq!(replace_names(FieldKind::DayOfWeek, "MON-FRI"), "1-5");
+        assert_eq!(replace_names(FieldKind::Month, "*/2,dec"), "*/2,12");
+    }
+
+    #[test]
+    fn test_compress_values() {
+        assert_eq!(compress_values(&[1, 2, 3, 5]), "1-3,5");
+        assert_eq!(compress_values(&[0, 1]), "0,1");
+        assert_eq!(compress_values(&[]), "");
+    }
+
+    #[test]
+    fn test_nicknames() {
+        assert_eq!(resolve_input("@Weekly").unwrap(), ResolvedSchedule { expression: "@weekly".to_string(), converted_from: None });
+        assert_eq!(CronSchedule::parse("@daily").unwrap(), CronSchedule::parse("0 0 * * *").unwrap());
+        assert_eq!(CronSchedule::parse("@annually").unwrap(), CronSchedule::parse("0 0 1 1 *").unwrap());
+        assert!(CronSchedule::parse("@fortnightly").is_err());
+
+        let reboot = CronSchedule::parse("@reboot").unwrap();
+        assert!(reboot.reboot);
+        assert_eq!(reboot.next_after(at("2024-01-01 00:00")), None);
+    }
+}
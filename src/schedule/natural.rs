@@ -0,0 +1,247 @@
+use crate::schedule::{compress_values, CronSchedule};
+use anyhow::{bail, Result};
+
+const DAY_NAMES: [&str; 7] = [
+    "sunday", "monday", "tuesday", "wednesday", "thursday", "friday", "saturday",
+];
+const MONTH_NAMES: [&str; 12] = [
+    "january", "february", "march", "april", "may", "june", "july", "august", "september",
+    "october", "november", "december",
+];
+
+/// Plain-English schedules start with a word, cron expressions never do
+pub fn looks_like_natural(input: &str) -> bool {
+    input.starts_with(|c: char| c.is_ascii_alphabetic())
+}
+
+/// Convert a plain-English schedule such as "every weekday at 9am" or
+/// "every 15 minutes" into a standard cron expression
+pub fn parse(input: &str) -> Result<String> {
+    let normalized = input.to_lowercase().replace(',', " ");
+    let tokens: Vec<&str> = normalized.split_whitespace().collect();
+
+    let mut minute_step: Option<u32> = None;
+    let mut hour_step: Option<u32> = None;
+    let mut times: Vec<(u32, u32)> = Vec::new();
+    let mut days_of_week: Vec<u32> = Vec::new();
+    let mut days_of_month: Vec<u32> = Vec::new();
+    let mut months: Vec<u32> = Vec::new();
+    let mut hourly = false;
+    let mut weekly = false;
+    let mut monthly = false;
+    let mut yearly = false;
+
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i];
+        let next = tokens.get(i + 1).copied();
+
+        match token {
+            "every" | "each" | "at" | "on" | "and" | "in" | "the" | "of" | "day" | "days"
+            | "daily" => {}
+            "minute" => minute_step = Some(1),
+            "hour" | "hourly" => hourly = true,
+            "weekday" | "weekdays" => days_of_week.extend(1..=5),
+            "weekend" | "weekends" => days_of_week.extend([0, 6]),
+            "week" | "weekly" => weekly = true,
+            "month" | "monthly" => monthly = true,
+            "year" | "yearly" | "annually" => yearly = true,
+            "noon" | "midday" => times.push((12, 0)),
+            "midnight" => times.push((0, 0)),
+            _ => {
+                if let Some(n) = token.parse::<u32>().ok().filter(|_| {
+                    matches!(next, Some("minute" | "minutes" | "min" | "mins" | "hour" | "hours"))
+                }) {
+                    if n == 0 {
+                        bail!("Interval must be greater than zero");
+                    }
+                    match next {
+                        Some("hour" | "hours") => hour_step = Some(n),
+                        _ => minute_step = Some(n),
+                    }
+                    i += 2;
+                    continue;
+                }
+
+                if let Some(day) = parse_ordinal(token) {
+                    days_of_month.push(day);
+                } else if let Some(day) = lookup(token, &DAY_NAMES) {
+                    days_of_week.push(day);
+                } else if let Some(month) = lookup(token, &MONTH_NAMES) {
+                    months.push(month + 1);
+                } else {
+                    let meridiem = next.filter(|n| matches!(*n, "am" | "pm" | "a.m." | "p.m."));
+                    match parse_time(token, meridiem) {
+                        Some(time) => {
+                            times.push(time);
+                            if meridiem.is_some() {
+                                i += 1;
+                            }
+                        }
+                        None => bail!("Could not understand '{}' in \"{}\"", token, input),
+                    }
+                }
+            }
+        }
+        i += 1;
+    }
+
+    // "weekly"/"monthly"/"yearly" only pick a default day when none was named
+    if weekly && days_of_week.is_empty() {
+        days_of_week.push(0);
+    }
+    if (monthly || yearly) && days_of_month.is_empty() && days_of_week.is_empty() {
+        days_of_month.push(1);
+    }
+    if yearly && months.is_empty() {
+        months.push(1);
+    }
+
+    let (minute, hour) = if let Some(step) = minute_step {
+        if !times.is_empty() || hour_step.is_some() {
+            bail!("A minute interval cannot be combined with a time of day");
+        }
+        (step_field(step, 60, "minute", "an hour")?, "*".to_string())
+    } else if let Some(step) = hour_step {
+        if !times.is_empty() {
+            bail!("An hour interval cannot be combined with a time of day");
+        }
+        ("0".to_string(), step_field(step, 24, "hour", "a day")?)
+    } else if hourly {
+        ("0".to_string(), "*".to_string())
+    } else if times.is_empty() {
+        ("0".to_string(), "0".to_string())
+    } else {
+        let minute = times[0].1;
+        if times.iter().any(|(_, m)| *m != minute) {
+            bail!("Multiple times must share the same minute to fit one cron expression");
+        }
+        let mut hours: Vec<u32> = times.iter().map(|(h, _)| *h).collect();
+        hours.sort_unstable();
+        hours.dedup();
+        (minute.to_string(), compress_values(&hours))
+    };
+
+    let expression = format!(
+        "{} {} {} {} {}",
+        minute,
+        hour,
+        list_field(days_of_month),
+        list_field(months),
+        list_field(days_of_week),
+    );
+
+    // Guard against combinations we produced but cron would reject
+    CronSchedule::parse(&expression)?;
+    Ok(expression)
+}
+
+/// `*/step` starts over at 0 every `cycle`, so a step that doesn't divide
+/// it would leave a short gap, e.g. `*/7` minutes runs at :56 and then :00
+fn step_field(step: u32, cycle: u32, unit: &str, period: &str) -> Result<String> {
+    if !cycle.is_multiple_of(step) {
+        bail!(
+            "Every {} {}s doesn't divide {} evenly, and cron starts over each time; use a step that divides {}",
+            step,
+            unit,
+            period,
+            cycle
+        );
+    }
+    Ok(if step == 1 { "*".to_string() } else { format!("*/{}", step) })
+}
+
+fn list_field(mut values: Vec<u32>) -> String {
+    if values.is_empty() {
+        return "*".to_string();
+    }
+    values.sort_unstable();
+    values.dedup();
+    compress_values(&values)
+}
+
+/// Match full names, plurals ("mondays") and three-letter abbreviations
+fn lookup(token: &str, names: &[&str]) -> Option<u32> {
+    let token = token.strip_suffix('s').filter(|t| t.len() > 3).unwrap_or(token);
+    names
+        .iter()
+        .position(|name| *name == token || (token.len() == 3 && name.starts_with(token)))
+        .map(|i| i as u32)
+}
+
+/// "1st", "2nd", "15th", "31st"
+fn parse_ordinal(token: &str) -> Option<u32> {
+    let digits = token
+        .strip_suffix("st")
+        .or_else(|| token.strip_suffix("nd"))
+        .or_else(|| token.strip_suffix("rd"))
+        .or_else(|| token.strip_suffix("th"))?;
+    digits.parse().ok().filter(|d| (1..=31).contains(d))
+}
+
+/// "9am", "9:30pm", "14:30", or "9" followed by a separate "am"/"pm" token
+fn parse_time(token: &str, meridiem: Option<&str>) -> Option<(u32, u32)> {
+    let (clock, suffix) = if let Some(clock) = token.strip_suffix("am") {
+        (clock, Some("am"))
+    } else if let Some(clock) = token.strip_suffix("pm") {
+        (clock, Some("pm"))
+    } else {
+        (token, meridiem.map(|m| if m.starts_with('a') { "am" } else { "pm" }))
+    };
+
+    let (hour, minute) = match clock.split_once(':') {
+        Some((h, m)) => (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?),
+        // A bare number is only a time when it has an am/pm suffix
+        None if suffix.is_some() => (clock.parse::<u32>().ok()?, 0),
+        None => return None,
+    };
+    if minute > 59 {
+        return None;
+    }
+
+    let hour = match suffix {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some("am") => hour % 12,
+        Some(_) => hour % 12 + 12,
+        None if hour <= 23 => hour,
+        None => return None,
+    };
+
+    Some((hour, minute))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intervals() {
+        assert_eq!(parse("every minute").unwrap(), "* * * * *");
+        assert_eq!(parse("every 15 minutes").unwrap(), "*/15 * * * *");
+        assert_eq!(parse("every hour").unwrap(), "0 * * * *");
+        assert_eq!(parse("every 2 hours").unwrap(), "0 */2 * * *");
+    }
+
+    #[test]
+    fn test_times_and_days() {
+        assert_eq!(parse("every weekday at 9am").unwrap(), "0 9 * * 1-5");
+        assert_eq!(parse("every day at 14:30").unwrap(), "30 14 * * *");
+        assert_eq!(parse("daily at 9:30 pm").unwrap(), "30 21 * * *");
+        assert_eq!(parse("every monday and friday at noon").unwrap(), "0 12 * * 1,5");
+        assert_eq!(parse("on the 1st of every month at midnight").unwrap(), "0 0 1 * *");
+        assert_eq!(parse("every day at 9am and 5pm").unwrap(), "0 9,17 * * *");
+        assert_eq!(parse("every month").unwrap(), "0 0 1 * *");
+        assert_eq!(parse("every week on wednesday at 8:15am").unwrap(), "15 8 * * 3");
+    }
+
+    #[test]
+    fn test_rejects_nonsense() {
+        assert!(parse("whenever you feel like it").is_err());
+        assert!(parse("every 5 minutes at 9am").is_err());
+        assert!(parse("every day at 25:00").is_err());
+        // Steps that don't divide the hour or the day would run unevenly
+        assert!(parse("every 7 minutes").unwrap_err().to_string().contains("divides 60"));
+        assert!(parse("every 90 minutes").is_err());
+        assert!(parse("every 5 hours").unwrap_err().to_string().contains("divides 24"));
+    }
+}
@@ -0,0 +1,104 @@
+use crate::schedule::{compress_values, CronSchedule, FieldKind, FieldSet};
+use anyhow::Result;
+
+/// Rewrite a cron expression into a canonical form so that equivalent
+/// schedules compare equal: `*/1` becomes `*`, `7` becomes `0` in the
+/// weekday field, nicknames such as `@daily` are spelled out, lists are sorted and deduplicated, and runs of values
+/// collapse into ranges.
+///
+/// Star-ness of the day fields is preserved where it matters, because cron
+/// ORs day-of-month and day-of-week only when neither is written with `*`.
+pub fn normalize(expr: &str) -> Result<String> {
+    let schedule = CronSchedule::parse(expr)?;
+    if schedule.reboot {
+        return Ok("@reboot".to_string());
+    }
+
+    let fields: Vec<String> = FieldKind::ALL
+        .iter()
+        .map(|kind| {
+            let field = schedule.field(*kind);
+            let other_day_star = match kind {
+                FieldKind::DayOfMonth => schedule.days_of_week.is_star(),
+                FieldKind::DayOfWeek => schedule.days_of_month.is_star(),
+                _ => true,
+            };
+            render_field(*kind, field, field.is_star() || other_day_star)
+        })
+        .collect();
+
+    Ok(fields.join(" "))
+}
+
+/// Whether two expressions describe the same schedule. Unparseable
+/// expressions are only equivalent when textually identical.
+pub fn equivalent(a: &str, b: &str) -> bool {
+    match (normalize(a), normalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a.split_whitespace().eq(b.split_whitespace()),
+    }
+}
+
+fn render_field(kind: FieldKind, field: &FieldSet, may_use_star: bool) -> String {
+    let (min, max) = match kind {
+        // Sunday is stored as 0, so the effective weekday range is 0-6
+        FieldKind::DayOfWeek => (0, 6),
+        _ => kind.bounds(),
+    };
+    let values = field.values();
+
+    if may_use_star {
+        if values.len() as u32 == max - min + 1 {
+            return "*".to_string();
+        }
+        if let Some(step) = star_step(&values, min, max) {
+            return format!("*/{}", step);
+        }
+        // e.g. `*/7` in the weekday field matches only Sunday, but must stay starred
+        if field.is_star() && values.len() == 1 {
+            return format!("*/{}", max - min + 1);
+        }
+    }
+
+    compress_values(&values)
+}
+
+/// Detect values that `*/n` would produce: starting at the field minimum
+/// and continuing every `n` up to the maximum
+fn star_step(values: &[u32], min: u32, max: u32) -> Option<u32> {
+    let step = match values {
+        [first, second, ..] if *first == min => second - first,
+        _ => return None,
+    };
+    let expected: Vec<u32> = (min..=max).step_by(step as usize).collect();
+    (expected == values).then_some(step)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("0 0 * * 7").unwrap(), "0 0 * * 0");
+        assert_eq!(normalize("*/1 * * * *").unwrap(), "* * * * *");
+        assert_eq!(normalize("5,1,3,2 * * * *").unwrap(), "1-3,5 * * * *");
+        assert_eq!(normalize("0,15,30,45 * * * *").unwrap(), "*/15 * * * *");
+        assert_eq!(normalize("0 0 * * 1,2,3,4,5").unwrap(), "0 0 * * 1-5");
+        assert_eq!(normalize("0  2 *  * *").unwrap(), "0 2 * * *");
+    }
+
+    #[test]
+    fn test_normalize_preserves_day_semantics() {
+        // With day-of-month restricted, `0-6` (every day, ORed) differs from `*`
+        assert_eq!(normalize("0 0 1 * 0-6").unwrap(), "0 0 1 * 0-6");
+        assert_eq!(normalize("0 0 1 * *").unwrap(), "0 0 1 * *");
+    }
+
+    #[test]
+    fn test_equivalent() {
+        assert!(equivalent("0 0 * * 7", "0 0 * * 0"));
+        assert!(equivalent("*/1 * * * *", "* * * * *"));
+        assert!(!equivalent("0 1 * * *", "0 2 * * *"));
+    }
+}
@@ -0,0 +1,336 @@
+use crate::schedule::{compress_values, expand_nickname, is_reboot, CronSchedule, DAY_NAMES, MONTH_NAMES};
+use anyhow::{bail, Context, Result};
+use std::fmt;
+
+/// A Quartz-style cron expression as used by Java schedulers:
+/// `seconds minutes hours day-of-month month day-of-week [year]`
+///
+/// Quartz numbers days of the week 1-7 starting from Sunday, requires `?`
+/// in exactly one of the two day fields, and supports `L`, `W` and `#`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuartzExpression {
+    pub seconds: String,
+    pub minutes: String,
+    pub hours: String,
+    pub day_of_month: String,
+    pub month: String,
+    pub day_of_week: String,
+    pub year: Option<String>,
+}
+
+/// Quartz expressions have 6 or 7 fields, standard cron has 5
+pub fn looks_like_quartz(expr: &str) -> bool {
+    matches!(expr.split_whitespace().count(), 6 | 7)
+}
+
+impl QuartzExpression {
+    pub fn parse(expr: &str) -> Result<Self> {
+        let parts: Vec<String> = expr
+            .split_whitespace()
+            .map(|p| p.to_ascii_uppercase())
+            .collect();
+        if parts.len() != 6 && parts.len() != 7 {
+            bail!(
+                "Quartz expressions have 6 or 7 fields (seconds minutes hours day month weekday [year]), found {}",
+                parts.len()
+            );
+        }
+
+        let quartz = Self {
+            seconds: parts[0].clone(),
+            minutes: parts[1].clone(),
+            hours: parts[2].clone(),
+            day_of_month: parts[3].clone(),
+            month: parts[4].clone(),
+            day_of_week: parts[5].clone(),
+            year: parts.get(6).cloned(),
+        };
+        quartz.validate()?;
+        Ok(quartz)
+    }
+
+    fn validate(&self) -> Result<()> {
+        check_field(&self.seconds, "seconds", 0, 59, &[], |_| false)?;
+        check_field(&self.minutes, "minutes", 0, 59, &[], |_| false)?;
+        check_field(&self.hours, "hours", 0, 23, &[], |_| false)?;
+        check_field(&self.day_of_month, "day-of-month", 1, 31, &[], is_day_of_month_special)?;
+        check_field(&self.month, "month", 1, 12, &MONTH_NAMES, |_| false)?;
+        check_field(&self.day_of_week, "day-of-week", 1, 7, &DAY_NAMES, is_day_of_week_special)?;
+        if let Some(year) = &self.year {
+            check_field(year, "year", 1970, 2099, &[], |_| false)?;
+        }
+
+        let dom_any = self.day_of_month == "?";
+        let dow_any = self.day_of_week == "?";
+        if dom_any == dow_any {
+            bail!("Quartz expressions must use '?' in exactly one of the day-of-month and day-of-week fields");
+        }
+
+        Ok(())
+    }
+
+    /// Convert to a standard five-field cron expression.
+    ///
+    /// Fails for constructs cron cannot express: non-zero seconds, specific
+    /// years, and the `L`, `W` and `#` day modifiers.
+    pub fn to_standard(&self) -> Result<String> {
+        if self.seconds != "0" {
+            bail!(
+                "Seconds field '{}' cannot be represented in standard cron (minute resolution only)",
+                self.seconds
+            );
+        }
+        if let Some(year) = self.year.as_deref().filter(|y| *y != "*") {
+            bail!("Year field '{}' cannot be represented in standard cron", year);
+        }
+        if self.day_of_month.contains('L') || self.day_of_month.contains('W') {
+            bail!(
+                "Day-of-month '{}' (last day / nearest weekday) has no standard cron equivalent",
+                self.day_of_month
+            );
+        }
+        if self.day_of_week.contains('L') || self.day_of_week.contains('#') {
+            bail!(
+                "Day-of-week '{}' (last / nth weekday of month) has no standard cron equivalent",
+                self.day_of_week
+            );
+        }
+
+        let day_of_month = if self.day_of_month == "?" {
+            "*".to_string()
+        } else {
+            standard_field(&self.day_of_month, 1, 31, &[], 0)?
+        };
+        let day_of_week = if self.day_of_week == "?" {
+            "*".to_string()
+        } else {
+            standard_field(&self.day_of_week, 1, 7, &DAY_NAMES, 1)?
+        };
+
+        let expression = format!(
+            "{} {} {} {} {}",
+            standard_field(&self.minutes, 0, 59, &[], 0)?,
+            standard_field(&self.hours, 0, 23, &[], 0)?,
+            day_of_month,
+            standard_field(&self.month, 1, 12, &MONTH_NAMES, 0)?,
+            day_of_week,
+        );
+
+        CronSchedule::parse(&expression)
+            .with_context(|| format!("Converted expression '{}' is not valid cron", expression))?;
+        Ok(expression)
+    }
+
+    /// Build the Quartz equivalent of a standard five-field cron expression
+    pub fn from_standard(expr: &str) -> Result<Self> {
+        if is_reboot(expr) {
+            bail!("@reboot has no Quartz equivalent");
+        }
+        let expr = expand_nickname(expr);
+        let schedule = CronSchedule::parse(expr)?;
+        let parts: Vec<&str> = expr.split_whitespace().collect();
+
+        let (day_of_month, day_of_week) = if parts[4] == "*" {
+            (parts[2].to_string(), "?".to_string())
+        } else if parts[2] == "*" {
+            let day_of_week = match parts[4].strip_prefix('*') {
+                // `*/n` starts on Sunday in both notations
+                Some(step) => format!("1{}", step),
+                None => {
+                    let days: Vec<u32> = schedule.days_of_week.values().iter().map(|d| d + 1).collect();
+                    compress_values(&days)
+                }
+            };
+            ("?".to_string(), day_of_week)
+        } else {
+            bail!(
+                "Quartz cannot combine day-of-month and day-of-week restrictions; \
+                 leave one of the two day fields as *"
+            );
+        };
+
+        Ok(Self {
+            seconds: "0".to_string(),
+            minutes: parts[0].to_string(),
+            hours: parts[1].to_string(),
+            day_of_month,
+            month: parts[3].to_string(),
+            day_of_week,
+            year: None,
+        })
+    }
+}
+
+impl fmt::Display for QuartzExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {} {}",
+            self.seconds, self.minutes, self.hours, self.day_of_month, self.month, self.day_of_week
+        )?;
+        if let Some(year) = &self.year {
+            write!(f, " {}", year)?;
+        }
+        Ok(())
+    }
+}
+
+fn is_day_of_month_special(item: &str) -> bool {
+    // L, L-3, LW, 15W
+    item == "L"
+        || item == "LW"
+        || item
+            .strip_prefix("L-")
+            .is_some_and(|n| n.parse::<u32>().is_ok_and(|n| n <= 30))
+        || item
+            .strip_suffix('W')
+            .is_some_and(|n| n.parse::<u32>().is_ok_and(|n| (1..=31).contains(&n)))
+}
+
+fn is_day_of_week_special(item: &str) -> bool {
+    // L, 6L, FRIL, 6#3, FRI#3
+    let is_day = |d: &str| resolve_value(d, 1, 7, &DAY_NAMES).is_ok();
+    item == "L"
+        || item.strip_suffix('L').is_some_and(is_day)
+        || item.split_once('#').is_some_and(|(d, n)| {
+            is_day(d) && n.parse::<u32>().is_ok_and(|n| (1..=5).contains(&n))
+        })
+}
+
+fn check_field(
+    text: &str,
+    name: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+    is_special: fn(&str) -> bool,
+) -> Result<()> {
+    if text == "?" && (name == "day-of-month" || name == "day-of-week") {
+        return Ok(());
+    }
+
+    for item in text.split(',') {
+        if is_special(item) {
+            continue;
+        }
+
+        let (base, step) = match item.split_once('/') {
+            Some((base, step)) => (base, Some(step)),
+            None => (item, None),
+        };
+        if let Some(step) = step {
+            match step.parse::<u32>() {
+                Ok(n) if n > 0 => {}
+                _ => bail!("Invalid step '{}' in Quartz {} field", step, name),
+            }
+        }
+
+        if base == "*" {
+            continue;
+        }
+        let (a, b) = base.split_once('-').unwrap_or((base, base));
+        let start = resolve_value(a, min, max, names)
+            .with_context(|| format!("Invalid Quartz {} field '{}'", name, text))?;
+        let end = resolve_value(b, min, max, names)
+            .with_context(|| format!("Invalid Quartz {} field '{}'", name, text))?;
+        if start > end {
+            bail!("Invalid range '{}' in Quartz {} field", base, name);
+        }
+    }
+
+    Ok(())
+}
+
+/// Resolve a numeric value or symbolic name (`JAN`, `MON`) within a field
+fn resolve_value(text: &str, min: u32, max: u32, names: &[&str]) -> Result<u32> {
+    if let Some(index) = names.iter().position(|n| n.eq_ignore_ascii_case(text)) {
+        return Ok(min + index as u32);
+    }
+    match text.parse::<u32>() {
+        Ok(v) if (min..=max).contains(&v) => Ok(v),
+        _ => bail!("Invalid value '{}' (expected {}-{})", text, min, max),
+    }
+}
+
+/// Translate a Quartz field into standard cron syntax: names become numbers,
+/// values are shifted down by `offset`, and `a/n` becomes `a-max/n`
+fn standard_field(text: &str, min: u32, max: u32, names: &[&str], offset: u32) -> Result<String> {
+    let convert = |v: &str| -> Result<u32> { Ok(resolve_value(v, min, max, names)? - offset) };
+
+    let items = text
+        .split(',')
+        .map(|item| {
+            let (base, step) = match item.split_once('/') {
+                Some((base, step)) => (base, Some(step)),
+                None => (item, None),
+            };
+
+            let base = if base == "*" {
+                "*".to_string()
+            } else if let Some((a, b)) = base.split_once('-') {
+                format!("{}-{}", convert(a)?, convert(b)?)
+            } else if step.is_some() {
+                format!("{}-{}", convert(base)?, max - offset)
+            } else {
+                convert(base)?.to_string()
+            };
+
+            Ok(match step {
+                Some(step) => format!("{}/{}", base, step),
+                None => base,
+            })
+        })
+        .collect::<Result<Vec<String>>>()?;
+
+    Ok(items.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quartz_to_standard() {
+        let quartz = QuartzExpression::parse("0 0 2 * * ?").unwrap();
+        assert_eq!(quartz.to_standard().unwrap(), "0 2 * * *");
+
+        let weekdays = QuartzExpression::parse("0 30 9 ? * MON-FRI").unwrap();
+        assert_eq!(weekdays.to_standard().unwrap(), "30 9 * * 1-5");
+
+        let sunday = QuartzExpression::parse("0 0 12 ? * 1").unwrap();
+        assert_eq!(sunday.to_standard().unwrap(), "0 12 * * 0");
+
+        let stepped = QuartzExpression::parse("0 0/15 * * * ?").unwrap();
+        assert_eq!(stepped.to_standard().unwrap(), "0-59/15 * * * *");
+    }
+
+    #[test]
+    fn test_quartz_unrepresentable() {
+        assert!(QuartzExpression::parse("30 0 2 * * ?").unwrap().to_standard().is_err());
+        assert!(QuartzExpression::parse("0 0 2 L * ?").unwrap().to_standard().is_err());
+        assert!(QuartzExpression::parse("0 0 2 15W * ?").unwrap().to_standard().is_err());
+        assert!(QuartzExpression::parse("0 0 2 ? * 6#3").unwrap().to_standard().is_err());
+        assert!(QuartzExpression::parse("0 0 2 ? * 6L").unwrap().to_standard().is_err());
+        assert!(QuartzExpression::parse("0 0 2 * * ? 2030").unwrap().to_standard().is_err());
+    }
+
+    #[test]
+    fn test_quartz_validation() {
+        assert!(QuartzExpression::parse("0 0 2 * * *").is_err());
+        assert!(QuartzExpression::parse("0 0 2 ? * ?").is_err());
+        assert!(QuartzExpression::parse("0 0 25 * * ?").is_err());
+        assert!(QuartzExpression::parse("0 0 2 ? * 8").is_err());
+    }
+
+    #[test]
+    fn test_standard_to_quartz() {
+        let quartz = QuartzExpression::from_standard("0 2 * * *").unwrap();
+        assert_eq!(quartz.to_string(), "0 0 2 * * ?");
+
+        let weekdays = QuartzExpression::from_standard("30 9 * * 1-5").unwrap();
+        assert_eq!(weekdays.to_string(), "0 30 9 ? * 2-6");
+        assert_eq!(weekdays.to_standard().unwrap(), "30 9 * * 1-5");
+
+        assert!(QuartzExpression::from_standard("0 0 1 * 1").is_err());
+    }
+}
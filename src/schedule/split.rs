@@ -0,0 +1,141 @@
+use crate::schedule::{CronSchedule, FieldKind, FieldSet};
+
+/// Most entries an exact split may produce before it stops being practical
+pub const MAX_SPLIT: usize = 24;
+
+/// A way to run a schedule on a backend that can't represent it as written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Conversion {
+    /// Several expressions that together fire exactly when the original does
+    Split(Vec<String>),
+    /// A single expression that fires on a subset of the original times
+    Approximate(String),
+}
+
+/// Conversions of `expr` into expressions the backend accepts, best first.
+/// Backends such as launchd only take a single value or `*` per field, so
+/// ranges, lists and steps are expanded into one entry per combination, or
+/// narrowed down to their first value.
+pub fn conversions(expr: &str, supported: impl Fn(&str) -> bool) -> Vec<Conversion> {
+    // `@reboot` has no fields to split
+    let Some(schedule) = CronSchedule::parse(expr).ok().filter(|schedule| !schedule.reboot) else {
+        return Vec::new();
+    };
+    let mut options = Vec::new();
+
+    if let Some(split) = split(&schedule).filter(|split| split.iter().all(|e| supported(e))) {
+        options.push(Conversion::Split(split));
+    }
+    let approximation = approximate(&schedule);
+    if supported(&approximation) {
+        options.push(Conversion::Approximate(approximation));
+    }
+    options
+}
+
+/// The values a field has to be expanded into, or `None` if it can stay `*`
+fn field_values(kind: FieldKind, field: &FieldSet, other_day_star: bool) -> Option<Vec<u32>> {
+    let (min, max) = kind.bounds();
+    let full = match kind {
+        FieldKind::DayOfWeek => 7,
+        _ => (max - min + 1) as usize,
+    };
+    // A full day field only means "every day" when cron ANDs the day fields
+    (field.len() != full || !(field.is_star() || other_day_star)).then(|| field.values())
+}
+
+/// Expand every multi-valued field into single values, one expression per
+/// combination. `None` when the day fields would end up both restricted,
+/// which cron ORs instead of ANDs, or when there would be too many entries.
+fn split(schedule: &CronSchedule) -> Option<Vec<String>> {
+    let mut expressions = vec![String::new()];
+    let mut restricted_days = 0;
+
+    for kind in FieldKind::ALL {
+        let values = field_values(kind, schedule.field(kind), other_day_star(schedule, kind));
+        let parts: Vec<String> = match values {
+            Some(values) => {
+                if matches!(kind, FieldKind::DayOfMonth | FieldKind::DayOfWeek) {
+                    restricted_days += 1;
+                }
+                values.iter().map(u32::to_string).collect()
+            }
+            None => vec!["*".to_string()],
+        };
+        if expressions.len() * parts.len() > MAX_SPLIT {
+            return None;
+        }
+        expressions = expressions
+            .iter()
+            .flat_map(|prefix| parts.iter().map(move |part| format!("{}{} ", prefix, part)))
+            .collect();
+    }
+
+    if restricted_days == 2 {
+        return None;
+    }
+    Some(expressions.into_iter().map(|e| e.trim_end().to_string()).collect())
+}
+
+/// Narrow every multi-valued field to its first value. A starred day field
+/// becomes `*` instead when narrowing it would switch cron to ORing the days.
+fn approximate(schedule: &CronSchedule) -> String {
+    let fields: Vec<String> = FieldKind::ALL
+        .iter()
+        .map(|kind| {
+            let field = schedule.field(*kind);
+            let other_day_star = other_day_star(schedule, *kind);
+            match field_values(*kind, field, other_day_star) {
+                None => "*".to_string(),
+                Some(_) if field.is_star() && !other_day_star => "*".to_string(),
+                Some(values) => values[0].to_string(),
+            }
+        })
+        .collect();
+    fields.join(" ")
+}
+
+fn other_day_star(schedule: &CronSchedule, kind: FieldKind) -> bool {
+    match kind {
+        FieldKind::DayOfMonth => schedule.days_of_week.is_star(),
+        FieldKind::DayOfWeek => schedule.days_of_month.is_star(),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Single values and `*` only, like launchd
+    fn simple(expr: &str) -> bool {
+        expr.split_whitespace().all(|f| f == "*" || f.parse::<u32>().is_ok())
+    }
+
+    #[test]
+    fn test_split_ranges_and_steps() {
+        assert_eq!(
+            conversions("0 9 1-3 * *", simple),
+            [
+                Conversion::Split(vec!["0 9 1 * *".into(), "0 9 2 * *".into(), "0 9 3 * *".into()]),
+                Conversion::Approximate("0 9 1 * *".into()),
+            ]
+        );
+        let options = conversions("*/15 * * * MON-FRI", simple);
+        let Conversion::Split(split) = &options[0] else { panic!("expected a split") };
+        assert_eq!(split.len(), 20);
+        assert_eq!(split[0], "0 * * * 1");
+        assert_eq!(split[19], "45 * * * 5");
+    }
+
+    #[test]
+    fn test_no_exact_split() {
+        // Cron ORs restricted day fields: splitting would run twice on overlaps
+        assert_eq!(conversions("0 9 1,15 * 1", simple), [Conversion::Approximate("0 9 1 * 1".into())]);
+        // Too many entries
+        assert_eq!(conversions("*/5 9-17 * * *", simple), [Conversion::Approximate("0 9 * * *".into())]);
+        // Narrowing `*/2` would turn "even days that are Mondays" into an OR
+        assert_eq!(conversions("0 9 */2 * 1", simple), [Conversion::Approximate("0 9 * * 1".into())]);
+        assert!(conversions("not cron", simple).is_empty());
+    }
+}
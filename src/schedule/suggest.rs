@@ -0,0 +1,159 @@
+use crate::schedule::FieldKind;
+
+/// A value offered for the field being typed, with a short explanation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Suggestion {
+    pub value: &'static str,
+    pub description: &'static str,
+}
+
+const fn s(value: &'static str, description: &'static str) -> Suggestion {
+    Suggestion { value, description }
+}
+
+const MINUTE: &[Suggestion] = &[
+    s("*", "every minute"),
+    s("0", "on the hour"),
+    s("*/5", "every 5 minutes"),
+    s("*/10", "every 10 minutes"),
+    s("*/15", "every 15 minutes"),
+    s("*/30", "every 30 minutes"),
+    s("0,30", "on the hour and half hour"),
+];
+
+const HOUR: &[Suggestion] = &[
+    s("*", "every hour"),
+    s("0", "midnight"),
+    s("9", "9am"),
+    s("12", "noon"),
+    s("*/2", "every 2 hours"),
+    s("*/6", "every 6 hours"),
+    s("9-17", "business hours"),
+];
+
+const DAY_OF_MONTH: &[Suggestion] = &[
+    s("*", "every day"),
+    s("1", "first of the month"),
+    s("15", "15th of the month"),
+    s("1,15", "1st and 15th"),
+    s("*/2", "every other day"),
+];
+
+const MONTH: &[Suggestion] = &[
+    s("*", "every month"),
+    s("1", "January"),
+    s("JAN", "January"),
+    s("*/3", "quarterly (Jan, Apr, Jul, Oct)"),
+    s("1,7", "January and July"),
+    s("6-8", "June to August"),
+    s("12", "December"),
+];
+
+const DAY_OF_WEEK: &[Suggestion] = &[
+    s("*", "every day of the week"),
+    s("1-5", "Monday to Friday"),
+    s("MON-FRI", "Monday to Friday"),
+    s("0,6", "weekends (Sunday, Saturday)"),
+    s("SAT,SUN", "weekends"),
+    s("0", "Sunday"),
+    s("1", "Monday"),
+    s("2", "Tuesday"),
+    s("3", "Wednesday"),
+    s("4", "Thursday"),
+    s("5", "Friday"),
+    s("6", "Saturday"),
+];
+
+/// Whole expressions for routine schedules, offered by the preset picker
+pub const PRESETS: &[Suggestion] = &[
+    s("*/5 * * * *", "every 5 minutes"),
+    s("*/15 * * * *", "every 15 minutes"),
+    s("0 * * * *", "hourly"),
+    s("0 0 * * *", "daily at midnight"),
+    s("0 2 * * *", "daily at 02:00"),
+    s("0 9 * * 1-5", "weekdays at 9:00"),
+    s("0 9 * * 1", "Mondays at 9:00"),
+    s("0 0 1 * *", "first of the month"),
+    s("0 0 1 1 *", "yearly on January 1st"),
+];
+
+impl FieldKind {
+    /// Common values for this field, in the order Tab cycles through them
+    pub fn suggestions(self) -> &'static [Suggestion] {
+        match self {
+            FieldKind::Minute => MINUTE,
+            FieldKind::Hour => HOUR,
+            FieldKind::DayOfMonth => DAY_OF_MONTH,
+            FieldKind::Month => MONTH,
+            FieldKind::DayOfWeek => DAY_OF_WEEK,
+        }
+    }
+}
+
+/// The field the cursor (end of `input`) is in, and the part of it typed so far.
+/// `None` once all five fields are complete.
+pub fn current_field(input: &str) -> Option<(FieldKind, &str)> {
+    let partial = input.rsplit(char::is_whitespace).next().unwrap_or("");
+    let complete = input.split_whitespace().count() - usize::from(!partial.is_empty());
+    FieldKind::ALL.get(complete).map(|kind| (*kind, partial))
+}
+
+/// Suggestions for the current field that start with what has been typed,
+/// or all of them when nothing matches
+pub fn suggestions_for(input: &str) -> Option<(FieldKind, Vec<Suggestion>)> {
+    let (kind, partial) = current_field(input)?;
+    let all = kind.suggestions();
+    let partial = partial.to_ascii_uppercase();
+    let matching: Vec<Suggestion> = all
+        .iter()
+        .filter(|s| s.value.starts_with(&partial) && s.value != partial)
+        .copied()
+        .collect();
+    Some((kind, if matching.is_empty() { all.to_vec() } else { matching }))
+}
+
+/// Replace the partially typed current field in `input` with `value`
+pub fn complete(input: &str, value: &str) -> String {
+    let partial = input.rsplit(char::is_whitespace).next().unwrap_or("");
+    format!("{}{}", &input[..input.len() - partial.len()], value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_current_field() {
+        assert_eq!(current_field(""), Some((FieldKind::Minute, "")));
+        assert_eq!(current_field("*/"), Some((FieldKind::Minute, "*/")));
+        assert_eq!(current_field("0 "), Some((FieldKind::Hour, "")));
+        assert_eq!(current_field("0 9 * * 1-"), Some((FieldKind::DayOfWeek, "1-")));
+        assert_eq!(current_field("0 9 * * 1 "), None);
+    }
+
+    #[test]
+    fn test_suggestions_filter_by_prefix() {
+        let (kind, found) = suggestions_for("*/1").unwrap();
+        assert_eq!(kind, FieldKind::Minute);
+        let values: Vec<_> = found.iter().map(|s| s.value).collect();
+        assert_eq!(values, ["*/10", "*/15"]);
+
+        // Nothing starts with "7" in the day-of-week list: offer everything
+        let (_, found) = suggestions_for("0 9 * * 7").unwrap();
+        assert_eq!(found.len(), DAY_OF_WEEK.len());
+    }
+
+    #[test]
+    fn test_presets_are_valid() {
+        for preset in PRESETS {
+            assert!(crate::schedule::CronSchedule::parse(preset.value).is_ok(), "{}", preset.value);
+        }
+    }
+
+    #[test]
+    fn test_complete() {
+        assert_eq!(complete("0 9 * * 1-", "1-5"), "0 9 * * 1-5");
+        assert_eq!(complete("0 ", "*/2"), "0 */2");
+        assert_eq!(complete("", "*"), "*");
+    }
+}
@@ -0,0 +1,167 @@
+use crate::schedule::CronSchedule;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, FixedOffset, LocalResult, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::fmt;
+
+/// The timezone a schedule is evaluated in: the system's local zone, or an
+/// IANA zone set in the config file or on the entry itself
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScheduleZone {
+    Local,
+    Named(Tz),
+}
+
+impl ScheduleZone {
+    /// Pick the entry's own timezone if set, else the configured one, else local
+    pub fn resolve(entry_tz: Option<&str>, configured_tz: Option<&str>) -> Result<Self> {
+        match entry_tz.or(configured_tz) {
+            Some(name) => Ok(Self::Named(parse_tz(name)?)),
+            None => Ok(Self::Local),
+        }
+    }
+
+    /// The next `count` firing times after `after`, as wall-clock times in this zone
+    pub fn next_runs(&self, schedule: &CronSchedule, after: DateTime<Utc>, count: usize) -> Vec<DateTime<FixedOffset>> {
+        let mut runs = Vec::with_capacity(count);
+        if count == 0 {
+            return runs;
+        }
+        self.walk_runs(schedule, after, |time| {
+            runs.push(time);
+            runs.len() < count
+        });
+        runs
+    }
+
+    /// Every firing time after `after` and before `until`
+    pub fn runs_between(&self, schedule: &CronSchedule, after: DateTime<Utc>, until: DateTime<Utc>) -> Vec<DateTime<FixedOffset>> {
+        let mut runs = Vec::new();
+        self.walk_runs(schedule, after, |time| {
+            if time >= until {
+                return false;
+            }
+            runs.push(time);
+            true
+        });
+        runs
+    }
+
+    fn walk_runs(&self, schedule: &CronSchedule, after: DateTime<Utc>, visit: impl FnMut(DateTime<FixedOffset>) -> bool) {
+        match self {
+            Self::Local => walk_runs_in(schedule, &after.with_timezone(&chrono::Local), visit),
+            Self::Named(tz) => walk_runs_in(schedule, &after.with_timezone(tz), visit),
+        }
+    }
+
+    /// Whether a wall-clock time occurs once, twice or never in this zone
+    pub fn classify(&self, time: NaiveDateTime) -> WallClock {
+        match self {
+            Self::Local => local_result_kind(chrono::Local.from_local_datetime(&time)),
+            Self::Named(tz) => local_result_kind(tz.from_local_datetime(&time)),
+        }
+    }
+
+    /// UTC offset in effect at the given wall-clock time, if it exists
+    pub fn offset_at(&self, time: NaiveDateTime) -> Option<FixedOffset> {
+        match self {
+            Self::Local => chrono::Local.from_local_datetime(&time).earliest().map(|t| t.offset().fix()),
+            Self::Named(tz) => tz.from_local_datetime(&time).earliest().map(|t| t.offset().fix()),
+        }
+    }
+}
+
+/// How a wall-clock time maps onto real time in a zone
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WallClock {
+    /// Occurs exactly once
+    Unique,
+    /// Occurs twice (clocks were turned back)
+    Repeated,
+    /// Never occurs (clocks jumped forward over it)
+    Skipped,
+}
+
+fn local_result_kind<T>(result: LocalResult<T>) -> WallClock {
+    match result {
+        LocalResult::Single(_) => WallClock::Unique,
+        LocalResult::Ambiguous(_, _) => WallClock::Repeated,
+        LocalResult::None => WallClock::Skipped,
+    }
+}
+
+impl fmt::Display for ScheduleZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Local => write!(f, "local"),
+            Self::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
+
+pub fn parse_tz(name: &str) -> Result<Tz> {
+    name.trim()
+        .parse::<Tz>()
+        .map_err(|_| anyhow!("Unknown timezone '{}'", name))
+}
+
+/// Walk the schedule in wall-clock time of `after`'s zone, passing each run
+/// to `visit` until it returns false. Times skipped by a DST jump are passed
+/// over, and repeated times count once (at their first occurrence).
+fn walk_runs_in<Z: TimeZone>(
+    schedule: &CronSchedule,
+    after: &DateTime<Z>,
+    mut visit: impl FnMut(DateTime<FixedOffset>) -> bool,
+) {
+    let zone = after.timezone();
+    let mut cursor = after.naive_local();
+
+    while let Some(next) = schedule.next_after(cursor) {
+        cursor = next;
+
+        let time = match zone.from_local_datetime(&next) {
+            LocalResult::Single(time) => time,
+            LocalResult::Ambiguous(earliest, _) => earliest,
+            LocalResult::None => continue,
+        };
+        if time > *after && !visit(time.fixed_offset()) {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_next_runs_in_named_zone() {
+        let schedule = CronSchedule::parse("0 9 * * *").unwrap();
+        let tokyo = ScheduleZone::resolve(Some("Asia/Tokyo"), None).unwrap();
+
+        // 2024-01-01 00:00 UTC is 09:00 JST, so the next 09:00 JST is the following day
+        let runs = tokyo.next_runs(&schedule, utc("2024-01-01T00:00:00Z"), 2);
+        assert_eq!(runs[0].to_rfc3339(), "2024-01-02T09:00:00+09:00");
+        assert_eq!(runs[1].to_rfc3339(), "2024-01-03T09:00:00+09:00");
+    }
+
+    #[test]
+    fn test_entry_zone_overrides_config() {
+        let zone = ScheduleZone::resolve(Some("UTC"), Some("Asia/Tokyo")).unwrap();
+        assert_eq!(zone.to_string(), "UTC");
+        assert!(ScheduleZone::resolve(Some("Mars/Olympus"), None).is_err());
+    }
+
+    #[test]
+    fn test_skips_nonexistent_dst_times() {
+        // 2024-03-10 02:30 does not exist in New York
+        let schedule = CronSchedule::parse("30 2 * * *").unwrap();
+        let new_york = ScheduleZone::resolve(Some("America/New_York"), None).unwrap();
+        let runs = new_york.next_runs(&schedule, utc("2024-03-09T12:00:00Z"), 2);
+        assert_eq!(runs[0].to_rfc3339(), "2024-03-11T02:30:00-04:00");
+    }
+}
@@ -0,0 +1,154 @@
+use crate::agent::{self, AgentInfo, Call, Reply, Request};
+use crate::cron_entry::CronEntry;
+use crate::paths;
+use crate::run_history::RunRecord;
+use crate::scheduler::{SaveReport, Scheduler};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How long to wait for the agent to accept a connection
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long to wait for a reply; saves on launchd can take a while
+const REPLY_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Lines of an entry's log fetched from the agent for the log viewer
+const LOG_LINES: usize = 5000;
+
+/// Entries managed through `cron-manager agent` on another host, which
+/// applies them to its own backend and serves its run history and logs
+pub struct AgentScheduler {
+    /// `host:port`, or `unix:<path>` for a socket (e.g. forwarded over ssh)
+    address: String,
+    token: String,
+    info: AgentInfo,
+    /// Answers to schedule checks, asked for on every analysis
+    checked: Mutex<HashMap<String, Result<(), String>>>,
+    /// Where fetched logs are written for the log viewer
+    private_dir: Option<PathBuf>,
+}
+
+impl AgentScheduler {
+    /// Connect to the agent, checking the address and token up front
+    pub fn connect(address: &str, token: String) -> Result<Self> {
+        let mut scheduler = Self {
+            address: address.to_string(),
+            token,
+            info: AgentInfo { backend: String::new(), keeps_disabled_entries: true },
+            checked: Mutex::new(HashMap::new()),
+            private_dir: None,
+        };
+        scheduler.info = scheduler.call(Call::Info)?;
+        Ok(scheduler)
+    }
+
+    /// Where to keep the logs fetched from the agent; without one, logs can't be viewed
+    pub fn private_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.private_dir = dir;
+        self
+    }
+
+    fn call<T: DeserializeOwned>(&self, call: Call) -> Result<T> {
+        let request = Request { token: self.token.clone(), call };
+        let reply = match self.address.strip_prefix(agent::UNIX_PREFIX) {
+            #[cfg(unix)]
+            Some(path) => {
+                let stream = std::os::unix::net::UnixStream::connect(path)
+                    .with_context(|| format!("Failed to connect to the agent at {}", path))?;
+                stream.set_read_timeout(Some(REPLY_TIMEOUT))?;
+                exchange(stream, &request)?
+            }
+            #[cfg(not(unix))]
+            Some(_) => bail!("Unix sockets aren't supported on this platform"),
+            None => {
+                let address = self
+                    .address
+                    .to_socket_addrs()
+                    .with_context(|| format!("Invalid agent address '{}'", self.address))?
+                    .next()
+                    .with_context(|| format!("No address found for '{}'", self.address))?;
+                let stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)
+                    .with_context(|| format!("Failed to connect to the agent at {}", self.address))?;
+                stream.set_read_timeout(Some(REPLY_TIMEOUT))?;
+                exchange(stream, &request)?
+            }
+        };
+        match reply {
+            Reply::Ok(value) => serde_json::from_value(value).context("Unexpected reply from the agent"),
+            Reply::Error(e) => bail!("Agent at {}: {}", self.address, e),
+        }
+    }
+}
+
+/// Send one request and wait for its reply
+fn exchange(mut stream: impl Read + Write, request: &Request) -> Result<Reply> {
+    agent::write_message(&mut stream, request)?;
+    agent::read_message(stream)
+}
+
+impl Scheduler for AgentScheduler {
+    fn load(&self) -> Result<Vec<CronEntry>> {
+        self.call(Call::Load)
+    }
+
+    fn save(&self, entries: &[CronEntry]) -> Result<SaveReport> {
+        self.call(Call::Save { entries: entries.to_vec() })
+    }
+
+    fn backend_name(&self) -> &'static str {
+        "Agent"
+    }
+
+    fn check_schedule(&self, schedule: &str) -> Result<()> {
+        let mut checked = self.checked.lock().unwrap_or_else(|e| e.into_inner());
+        if !checked.contains_key(schedule) {
+            let result = self
+                .call::<serde_json::Value>(Call::CheckSchedule { schedule: schedule.to_string() })
+                .map(|_| ())
+                .map_err(|e| format!("{:#}", e));
+            checked.insert(schedule.to_string(), result);
+        }
+        match &checked[schedule] {
+            Ok(()) => Ok(()),
+            Err(e) => bail!("{}", e),
+        }
+    }
+
+    /// The tail of the log on the agent's host, copied to a local file
+    fn log_path(&self, entry: &CronEntry) -> Option<PathBuf> {
+        let lines: Vec<String> = match self.call(Call::Log { entry: entry.name.clone(), lines: LOG_LINES }) {
+            Ok(lines) => lines,
+            Err(e) => {
+                tracing::warn!(error = %format!("{:#}", e), entry = %entry.name, "failed to fetch log");
+                return None;
+            }
+        };
+        let name: String = format!("{}-{}", self.address, entry.name)
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let content: String = lines.iter().map(|line| format!("{}\n", line)).collect();
+        let written = paths::write_private(self.private_dir.as_ref()?, &format!("agent-{}.log", name), &content);
+        written.inspect_err(|e| tracing::warn!(error = %format!("{:#}", e), "failed to keep fetched log")).ok()
+    }
+
+    fn keeps_disabled_entries(&self) -> bool {
+        self.info.keeps_disabled_entries
+    }
+
+    /// The agent resolves commands with its host's secrets, scripts and paths
+    fn resolves_commands(&self) -> bool {
+        true
+    }
+
+    fn remote_runs(&self, since: DateTime<Utc>) -> Option<Result<Vec<RunRecord>>> {
+        Some(self.call(Call::Runs { since }))
+    }
+}
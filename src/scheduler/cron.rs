@@ -1,46 +1,105 @@
 use crate::cron_entry::CronEntry;
 use crate::cron_parser::CronParser;
-use crate::scheduler::Scheduler;
-use anyhow::{Context, Result};
+use crate::scheduler::{run_command, run_command_with_input, SaveReport, Scheduler};
+use anyhow::{bail, Context, Result};
+use std::collections::hash_map::DefaultHasher;
 use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::ErrorKind;
 use std::path::PathBuf;
-use std::process::Command;
+use std::process::{Command, Output};
+
+/// Exit status of `ssh` itself failing, as opposed to the remote command
+const SSH_ERROR_STATUS: i32 = 255;
+
+/// Files listing the users allowed to, or kept from, using `crontab`
+#[cfg(target_os = "macos")]
+const ACCESS_FILES: (&str, &str) = ("/usr/lib/cron/cron.allow", "/usr/lib/cron/cron.deny");
+#[cfg(not(target_os = "macos"))]
+const ACCESS_FILES: (&str, &str) = ("/etc/cron.allow", "/etc/cron.deny");
 
 /// Cron-based scheduler for Linux and other Unix systems
 pub struct CronScheduler {
-    temp_file: PathBuf,
+    /// `ssh` destination (e.g. `deploy@web1`) whose crontab is managed
+    /// instead of the local one
+    host: Option<String>,
+    /// Manage root's crontab (`crontab -u root`)
+    root: bool,
+    /// Run `crontab` through `sudo -n`, for root's crontab when not root
+    sudo: bool,
 }
 
 impl CronScheduler {
     pub fn new() -> Self {
         Self {
-            temp_file: PathBuf::from("/tmp/crontab-temp"),
+            host: None,
+            root: false,
+            sudo: false,
+        }
+    }
+
+    /// Manage root's crontab, through `sudo` unless already root
+    pub fn root(sudo: bool) -> Self {
+        Self { root: true, sudo, ..Self::new() }
+    }
+
+    /// Manage the crontab of a remote host over `ssh`. Authentication must
+    /// not need a prompt (keys or an agent), as the TUI owns the terminal.
+    pub fn remote(host: String) -> Self {
+        Self { host: Some(host), ..Self::new() }
+    }
+
+    /// `crontab`, run on the remote host when there is one
+    fn crontab(&self) -> Command {
+        match &self.host {
+            Some(host) => {
+                let mut command = Command::new("ssh");
+                command.args(["-o", "BatchMode=yes", host, "crontab"]);
+                command
+            }
+            None if self.sudo => {
+                let mut command = Command::new("sudo");
+                command.args(["-n", "crontab", "-u", "root"]);
+                command
+            }
+            None if self.root => {
+                let mut command = Command::new("crontab");
+                command.args(["-u", "root"]);
+                command
+            }
+            None => Command::new("crontab"),
         }
     }
 
     fn load_from_crontab(&self) -> Result<String> {
-        let output = Command::new("crontab")
-            .arg("-l")
-            .output()
+        let output = run_command(self.crontab().arg("-l"))
             .context("Failed to execute crontab -l")?;
+        self.listing(&output)
+    }
 
+    /// The crontab `crontab -l` printed. Only its own "no crontab for"
+    /// means there is none yet: reading any other failure as an empty
+    /// crontab would have the next save install one holding nothing but
+    /// the cron-manager block.
+    fn listing(&self, output: &Output) -> Result<String> {
+        let error = String::from_utf8_lossy(&output.stderr);
         if output.status.success() {
             Ok(String::from_utf8_lossy(&output.stdout).to_string())
-        } else {
-            // No crontab exists yet
+        } else if self.sudo && error.contains("password") {
+            anyhow::bail!("sudo needs a password again; restart with --root to be asked for it");
+        } else if self.host.is_some() && output.status.code() == Some(SSH_ERROR_STATUS) {
+            anyhow::bail!("Failed to connect to {}: {}", self.host.as_deref().unwrap_or_default(), error.trim());
+        } else if error.contains("no crontab for") {
             Ok(String::new())
+        } else {
+            anyhow::bail!("Failed to read the crontab: {}", error.trim());
         }
     }
 
+    /// Install the crontab from stdin, so it is never written to a file
+    /// where other users could read its secrets or swap in a link
     fn save_to_crontab(&self, content: &str) -> Result<()> {
-        // Write to temporary file first
-        fs::write(&self.temp_file, content)
-            .with_context(|| format!("Failed to write temp file: {:?}", self.temp_file))?;
-
-        // Load the temporary file into crontab
-        let output = Command::new("crontab")
-            .arg(&self.temp_file)
-            .output()
+        let output = run_command_with_input(self.crontab().arg("-"), content.as_bytes())
             .context("Failed to execute crontab command")?;
 
         if !output.status.success() {
@@ -52,18 +111,163 @@ impl CronScheduler {
     }
 }
 
+/// Read an access file; one this user can't read is treated as absent, as
+/// `crontab` will have the final word anyway
+fn read_access_file(path: &str) -> Option<String> {
+    match fs::read_to_string(path) {
+        Ok(content) => Some(content),
+        Err(e) if e.kind() == ErrorKind::NotFound => None,
+        Err(e) => {
+            tracing::debug!(path, error = %e, "can't read cron access file");
+            None
+        }
+    }
+}
+
+/// Whether `user` may use `crontab`, going by `cron.allow` (which, when
+/// present, wins) and `cron.deny`. root is never kept out.
+fn check_access(user: &str, allow: Option<&str>, deny: Option<&str>) -> Result<()> {
+    let listed = |content: &str| content.lines().any(|line| line.trim() == user);
+    if user == "root" {
+        return Ok(());
+    }
+    match (allow, deny) {
+        (Some(allow), _) if !listed(allow) => bail!(
+            "Your user '{}' isn't listed in {}, so crontab refuses changes; ask an administrator to add it",
+            user,
+            ACCESS_FILES.0
+        ),
+        (None, Some(deny)) if listed(deny) => bail!(
+            "Your user '{}' is listed in {}, so crontab refuses changes; ask an administrator to remove it",
+            user,
+            ACCESS_FILES.1
+        ),
+        _ => Ok(()),
+    }
+}
+
+impl Default for CronScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Scheduler for CronScheduler {
     fn load(&self) -> Result<Vec<CronEntry>> {
         let content = self.load_from_crontab()?;
-        CronParser::parse(&content)
+        CronParser::parse_block(&content)
     }
 
-    fn save(&self, entries: &[CronEntry]) -> Result<()> {
-        let content = CronParser::serialize(entries);
-        self.save_to_crontab(&content)
+    fn save(&self, entries: &[CronEntry]) -> Result<SaveReport> {
+        let current = self.load_from_crontab()?;
+        let content = CronParser::serialize_block(&current, entries);
+        // Skip no-op saves so cron isn't reloaded when nothing changed
+        if current == content {
+            tracing::debug!("crontab unchanged, skipping install");
+        } else {
+            self.save_to_crontab(&content)?;
+        }
+        // crontab installs the whole table or nothing
+        Ok(SaveReport::all(entries))
+    }
+
+    /// Only the cron-manager block is rewritten; lines outside it, such as
+    /// jobs other tools installed, stay as they are
+    fn render(&self, entries: &[CronEntry]) -> Result<String> {
+        Ok(CronParser::serialize_block(&self.load_from_crontab()?, entries))
+    }
+
+    /// A hash of the crontab's text, which skips parsing it
+    fn fingerprint(&self) -> Option<u64> {
+        let mut hasher = DefaultHasher::new();
+        self.load_from_crontab().ok()?.hash(&mut hasher);
+        Some(hasher.finish())
+    }
+
+    fn environment_between(&self, first: &CronEntry, second: &CronEntry) -> Option<String> {
+        CronParser::environment_between(&self.load_from_crontab().ok()?, &first.name, &second.name)
+    }
+
+    /// A remote host's access files are left to its own `crontab`, and
+    /// root is never kept out
+    fn preflight(&self) -> Result<()> {
+        if self.host.is_some() || self.root {
+            return Ok(());
+        }
+        if let Ok(user) = std::env::var("USER").or_else(|_| std::env::var("LOGNAME")) {
+            let allow = read_access_file(ACCESS_FILES.0);
+            let deny = read_access_file(ACCESS_FILES.1);
+            check_access(&user, allow.as_deref(), deny.as_deref())?;
+        }
+        Ok(())
     }
 
     fn backend_name(&self) -> &'static str {
-        "Cron"
+        match self.host {
+            Some(_) => "SSH",
+            None if self.root => "Cron (root)",
+            None => "Cron",
+        }
+    }
+
+    /// Output redirected on a remote host can't be read locally
+    fn log_path(&self, entry: &CronEntry) -> Option<PathBuf> {
+        match self.host {
+            Some(_) => None,
+            None => entry.output_path(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_access() {
+        assert!(check_access("alice", None, None).is_ok());
+        assert!(check_access("alice", None, Some("bob\n")).is_ok());
+        let denied = check_access("alice", None, Some("bob\nalice\n")).unwrap_err();
+        assert!(denied.to_string().contains("is listed in"));
+        // cron.allow wins over cron.deny
+        assert!(check_access("alice", Some("alice\n"), Some("alice\n")).is_ok());
+        assert!(check_access("alice", Some("bob\n"), None).unwrap_err().to_string().contains("isn't listed in"));
+        assert!(check_access("root", Some(""), Some("root\n")).is_ok());
+    }
+
+    #[test]
+    fn test_root_crontab_command() {
+        let args = |scheduler: CronScheduler| {
+            let command = scheduler.crontab();
+            std::iter::once(command.get_program()).chain(command.get_args()).map(|a| a.to_string_lossy().into_owned()).collect::<Vec<_>>()
+        };
+        assert_eq!(args(CronScheduler::new()), ["crontab"]);
+        assert_eq!(args(CronScheduler::root(false)), ["crontab", "-u", "root"]);
+        assert_eq!(args(CronScheduler::root(true)), ["sudo", "-n", "crontab", "-u", "root"]);
+        assert_eq!(CronScheduler::root(true).backend_name(), "Cron (root)");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_only_a_missing_crontab_reads_as_empty() {
+        use std::os::unix::process::ExitStatusExt;
+        use std::process::ExitStatus;
+        let output = |code: i32, stdout: &str, stderr: &str| Output {
+            status: ExitStatus::from_raw(code << 8),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: stderr.as_bytes().to_vec(),
+        };
+        let scheduler = CronScheduler::new();
+        assert_eq!(scheduler.listing(&output(0, "MAILTO=me\n", "")).unwrap(), "MAILTO=me\n");
+        assert_eq!(scheduler.listing(&output(1, "", "no crontab for alice\n")).unwrap(), "");
+        assert_eq!(scheduler.listing(&output(1, "", "crontab: no crontab for alice\n")).unwrap(), "");
+        let denied = scheduler.listing(&output(1, "", "crontab: Permission denied\n")).unwrap_err();
+        assert_eq!(denied.to_string(), "Failed to read the crontab: crontab: Permission denied");
+        assert!(scheduler.listing(&output(1, "", "crontab: PAM authentication error\n")).is_err());
+        assert!(CronScheduler::root(true).listing(&output(1, "", "sudo: a terminal is required\n")).is_err());
+
+        let remote = CronScheduler::remote("deploy@web1".to_string());
+        assert!(remote.listing(&output(255, "", "Permission denied (publickey).\n")).unwrap_err().to_string().contains("Failed to connect"));
+        assert!(remote.listing(&output(1, "", "bash: crontab: Permission denied\n")).is_err());
     }
 }
@@ -0,0 +1,142 @@
+use crate::cron_entry::CronEntry;
+use crate::run_history::RunRecord;
+use crate::scheduler::{SaveReport, Scheduler};
+use crate::secrets;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+
+/// A backend whose saves only render what would be written (`--dry-run`).
+/// The entries last "saved" are kept in memory and loaded back, so a
+/// session sees its own changes while the backend stays untouched.
+pub struct DryRunScheduler {
+    inner: Box<dyn Scheduler>,
+    state: Mutex<DryRunState>,
+    /// Print each rendering to standard output, for subcommands
+    print: bool,
+    /// Mask credentials in the rendering (`[secrets] redact`)
+    redact: bool,
+}
+
+#[derive(Default)]
+struct DryRunState {
+    saved: Option<Vec<CronEntry>>,
+    rendered: String,
+}
+
+impl DryRunScheduler {
+    pub fn new(inner: Box<dyn Scheduler>, print: bool, redact: bool) -> Self {
+        Self { inner, state: Mutex::default(), print, redact }
+    }
+
+    fn state(&self) -> MutexGuard<'_, DryRunState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Scheduler for DryRunScheduler {
+    fn load(&self) -> Result<Vec<CronEntry>> {
+        match &self.state().saved {
+            Some(entries) => Ok(entries.clone()),
+            None => self.inner.load(),
+        }
+    }
+
+    fn save(&self, entries: &[CronEntry]) -> Result<SaveReport> {
+        let mut rendered = self.inner.render(entries)?;
+        if self.redact {
+            rendered = rendered.lines().map(|line| secrets::redact(line) + "\n").collect();
+        }
+        if self.print {
+            eprintln!("Dry run: {} would be written as follows; nothing was changed", self.inner.backend_name());
+            print!("{}", rendered);
+        }
+        let mut state = self.state();
+        state.saved = Some(entries.to_vec());
+        state.rendered = rendered;
+        Ok(SaveReport::all(entries))
+    }
+
+    fn render(&self, entries: &[CronEntry]) -> Result<String> {
+        self.inner.render(entries)
+    }
+
+    fn damage(&self) -> Option<String> {
+        self.inner.damage()
+    }
+
+    fn fingerprint(&self) -> Option<u64> {
+        self.inner.fingerprint()
+    }
+
+    fn environment_between(&self, first: &CronEntry, second: &CronEntry) -> Option<String> {
+        self.inner.environment_between(first, second)
+    }
+
+    fn dry_run_output(&self) -> Option<String> {
+        Some(self.state().rendered.clone())
+    }
+
+    fn backend_name(&self) -> &'static str {
+        self.inner.backend_name()
+    }
+
+    // No preflight: its write probe would touch the backend's directory
+
+    fn check_schedule(&self, schedule: &str) -> Result<()> {
+        self.inner.check_schedule(schedule)
+    }
+
+    fn log_path(&self, entry: &CronEntry) -> Option<PathBuf> {
+        self.inner.log_path(entry)
+    }
+
+    fn keeps_disabled_entries(&self) -> bool {
+        self.inner.keeps_disabled_entries()
+    }
+
+    fn sets_environment(&self) -> bool {
+        self.inner.sets_environment()
+    }
+
+    fn keeps_output(&self) -> bool {
+        self.inner.keeps_output()
+    }
+
+    fn has_timeout_command(&self) -> bool {
+        self.inner.has_timeout_command()
+    }
+
+    fn resolves_commands(&self) -> bool {
+        self.inner.resolves_commands()
+    }
+
+    fn remote_runs(&self, since: DateTime<Utc>) -> Option<Result<Vec<RunRecord>>> {
+        self.inner.remote_runs(since)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::mock::MockScheduler;
+
+    #[test]
+    fn test_saves_render_without_writing() {
+        let mock = MockScheduler::with_entries(vec![CronEntry::new("A".into(), "0 * * * *".into(), "/bin/a".into())]);
+        let scheduler = DryRunScheduler::new(Box::new(mock.clone()), false, true);
+        assert_eq!(scheduler.dry_run_output().as_deref(), Some(""));
+
+        let entries = vec![CronEntry::new("B".into(), "0 2 * * *".into(), "/bin/b".into())];
+        scheduler.save(&entries).unwrap();
+        assert!(mock.saves().is_empty());
+        assert_eq!(scheduler.load().unwrap(), entries);
+        assert!(scheduler.dry_run_output().unwrap().contains("# NAME: B\n0 2 * * * /bin/b\n"));
+        assert_eq!(mock.load().unwrap()[0].name, "A");
+
+        let entries = vec![CronEntry::new("C".into(), "0 3 * * *".into(), "curl -H 'Authorization: Bearer abc123' x".into())];
+        scheduler.save(&entries).unwrap();
+        assert!(!scheduler.dry_run_output().unwrap().contains("abc123"));
+    }
+}
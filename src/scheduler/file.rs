@@ -1,73 +1,319 @@
 use crate::cron_entry::CronEntry;
 use crate::cron_parser::CronParser;
-use crate::scheduler::Scheduler;
+use crate::paths;
+use crate::scheduler::{check_writable, SaveReport, Scheduler};
 use anyhow::{Context, Result};
-use std::fs;
-use std::path::PathBuf;
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{self, File, OpenOptions};
+use std::hash::{Hash, Hasher};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
 
 /// File-based scheduler for local testing/development
 pub struct FileScheduler {
     file_path: PathBuf,
+    import_from_system: bool,
 }
 
 impl FileScheduler {
     pub fn new(custom_path: Option<PathBuf>) -> Self {
-        let file_path = custom_path.unwrap_or_else(|| {
-            dirs::home_dir()
-                .unwrap_or_else(|| PathBuf::from("."))
-                .join(".cron-manager-crontab")
-        });
+        let file_path = custom_path
+            .or_else(|| paths::storage_file(None))
+            .unwrap_or_else(|| PathBuf::from("cron-manager-crontab"));
 
-        Self { file_path }
+        Self {
+            file_path,
+            import_from_system: true,
+        }
+    }
+
+    /// Whether to import the system scheduler's entries when the file doesn't exist yet
+    pub fn import_from_system(mut self, enabled: bool) -> Self {
+        self.import_from_system = enabled;
+        self
+    }
+
+    fn ensure_parent_dir(&self) -> Result<()> {
+        if let Some(parent) = self.file_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {:?}", parent))?;
+        }
+        Ok(())
+    }
+
+    /// Hold the lock next to the file until the guard is dropped, so two
+    /// cron-managers never write at once. Reads need no lock since the file
+    /// is only ever replaced whole. The lock is advisory and goes away with
+    /// the process.
+    fn lock(&self) -> Result<File> {
+        self.ensure_parent_dir()?;
+        let path = sibling(&self.file_path, "lock");
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open lock file: {:?}", path))?;
+        file.lock().with_context(|| format!("Failed to lock {:?}", path))?;
+        Ok(file)
+    }
+
+    /// Write the whole file or nothing: the content goes to a temporary file
+    /// that then replaces it, so a crash mid-write leaves the old file intact.
+    /// The temporary file takes the old one's permissions before any content
+    /// goes in, so a file kept private stays private.
+    fn write(&self, content: &str) -> Result<()> {
+        let temp = sibling(&self.file_path, "tmp");
+        let permissions = fs::metadata(&self.file_path).map(|metadata| metadata.permissions()).ok();
+        let written = File::create(&temp)
+            .and_then(|mut file| {
+                if let Some(permissions) = permissions {
+                    file.set_permissions(permissions)?;
+                }
+                file.write_all(content.as_bytes())?;
+                file.sync_all()
+            })
+            .and_then(|()| fs::rename(&temp, &self.file_path));
+        if written.is_err() {
+            let _ = fs::remove_file(&temp);
+        }
+        written.with_context(|| format!("Failed to write file: {:?}", self.file_path))
+    }
+
+    /// The file's content, with bytes that aren't UTF-8 replaced so a
+    /// damaged file still shows what can be read of it
+    fn read(&self) -> Result<String> {
+        Ok(String::from_utf8_lossy(&self.read_bytes()?).into_owned())
+    }
+
+    fn read_bytes(&self) -> Result<Vec<u8>> {
+        match fs::read(&self.file_path) {
+            Ok(content) => Ok(content),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read file: {:?}", self.file_path)),
+        }
+    }
+
+    fn load_system_entries(&self) -> Result<Vec<CronEntry>> {
+        #[cfg(not(target_os = "macos"))]
+        {
+            crate::scheduler::cron::CronScheduler::new().load()
+        }
+        #[cfg(target_os = "macos")]
+        {
+            // On macOS, import from launchd
+            crate::scheduler::launchd::LaunchdScheduler::new().load()
+        }
     }
 }
 
 impl Scheduler for FileScheduler {
     fn load(&self) -> Result<Vec<CronEntry>> {
-        let content = if !self.file_path.exists() {
-            // Try to import from system on first run
-            #[cfg(not(target_os = "macos"))]
-            {
-                match crate::scheduler::cron::CronScheduler::new().load() {
-                    Ok(entries) if !entries.is_empty() => {
-                        // Save imported entries
-                        let content = CronParser::serialize(&entries);
-                        fs::write(&self.file_path, &content)
-                            .with_context(|| format!("Failed to create initial file: {:?}", self.file_path))?;
-                        content
-                    }
-                    _ => String::new(),
-                }
-            }
-            #[cfg(target_os = "macos")]
-            {
-                // On macOS, try to import from launchd
-                match crate::scheduler::launchd::LaunchdScheduler::new().load() {
-                    Ok(entries) if !entries.is_empty() => {
-                        let content = CronParser::serialize(&entries);
-                        fs::write(&self.file_path, &content)
-                            .with_context(|| format!("Failed to create initial file: {:?}", self.file_path))?;
-                        content
-                    }
-                    _ => String::new(),
-                }
+        let content = if self.file_path.exists() {
+            self.read()?
+        } else if self.import_from_system {
+            // Import from the system on first run. The file is written even
+            // when there is nothing to import, so later starts skip this; a
+            // failed import writes nothing, so the next start tries again.
+            let _lock = self.lock()?;
+            if self.file_path.exists() {
+                // Another instance imported first
+                self.read()?
+            } else {
+                let entries = self.load_system_entries().context(
+                    "Failed to import the system scheduler's entries; set import_from_system = false to start with an empty file",
+                )?;
+                let content = CronParser::serialize(&entries);
+                self.write(&content).context("Failed to create initial file")?;
+                content
             }
         } else {
-            fs::read_to_string(&self.file_path)
-                .with_context(|| format!("Failed to read file: {:?}", self.file_path))?
+            String::new()
         };
 
         CronParser::parse(&content)
     }
 
-    fn save(&self, entries: &[CronEntry]) -> Result<()> {
-        let content = CronParser::serialize(entries);
-        fs::write(&self.file_path, content)
-            .with_context(|| format!("Failed to write file: {:?}", self.file_path))?;
-        Ok(())
+    fn save(&self, entries: &[CronEntry]) -> Result<SaveReport> {
+        // Locked from reading the lines kept as they are until the new file is in place
+        let _lock = self.lock()?;
+        let current = self.read_bytes()?;
+        let mut existing = String::from_utf8_lossy(&current).into_owned();
+        if damage(&current).is_some() {
+            // Written without the damaged lines, with the whole damaged file kept next to it
+            let copy = sibling(&self.file_path, "damaged");
+            fs::write(&copy, &current).with_context(|| format!("Failed to keep damaged file: {:?}", copy))?;
+            tracing::warn!(path = %copy.display(), "kept a copy of the damaged file");
+            existing = CronParser::without_damage(&existing);
+        }
+        let content = CronParser::serialize_into(&existing, entries);
+        self.write(&content)?;
+        Ok(SaveReport::all(entries))
+    }
+
+    /// Lines that aren't entries, like `MAILTO=`, stay as they are
+    fn render(&self, entries: &[CronEntry]) -> Result<String> {
+        Ok(CronParser::serialize_into(&self.read()?, entries))
+    }
+
+    fn preflight(&self) -> Result<()> {
+        check_writable(&self.file_path)
+    }
+
+    fn damage(&self) -> Option<String> {
+        damage(&self.read_bytes().ok()?)
+    }
+
+    fn environment_between(&self, first: &CronEntry, second: &CronEntry) -> Option<String> {
+        CronParser::environment_between(&self.read().ok()?, &first.name, &second.name)
+    }
+
+    /// The file's modification time and size; saves replace the file, so
+    /// both change with every write
+    fn fingerprint(&self) -> Option<u64> {
+        let mut hasher = DefaultHasher::new();
+        match fs::metadata(&self.file_path) {
+            Ok(metadata) => (metadata.modified().ok()?, metadata.len()).hash(&mut hasher),
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(_) => return None,
+        }
+        Some(hasher.finish())
     }
 
     fn backend_name(&self) -> &'static str {
         "File"
     }
 }
+
+/// What gives away a file cut off or garbled mid-write: saves always write
+/// text, and every `# NAME:` header is followed by its job. A missing final
+/// newline alone is no sign, since hand-edited files often end that way.
+fn damage(content: &[u8]) -> Option<String> {
+    if content.contains(&0) {
+        return Some("it contains NUL bytes".to_string());
+    }
+    let Ok(text) = std::str::from_utf8(content) else {
+        return Some("it isn't valid UTF-8 text".to_string());
+    };
+    let headers = text.lines().filter(|line| line.trim().starts_with("# NAME:")).count();
+    let unread = headers.saturating_sub(CronParser::parse(text).ok()?.len());
+    (unread > 0).then(|| format!("{} entries can't be read", unread))
+}
+
+/// `crontab` → `crontab.lock`, next to the file so it is on the same filesystem
+fn sibling(path: &Path, extension: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(extension);
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{command_entry, entry, TempDir};
+
+    #[test]
+    fn test_damage() {
+        assert_eq!(damage(b""), None);
+        assert_eq!(damage(b"MAILTO=me\n# NAME: A\n0 * * * * /bin/a\n"), None);
+        assert!(damage(b"# NAME: A\n0 * * * * /bin/a\n\0\0\0").is_some());
+        assert!(damage(b"# NAME: A\n0 * * * * /bin/\xff\n").is_some());
+        assert!(damage(b"# NAME: A\n0 * * * * /bin/a\n# NAME: B\n0 2 *").is_some());
+        assert_eq!(damage(b"MAILTO=me\n# NAME: A\n0 * * * * /bin/a"), None);
+        assert_eq!(damage(b"# NAME: A\n0 * * * * /bin/a\n# NAME: B\n").as_deref(), Some("1 entries can't be read"));
+    }
+
+    #[test]
+    fn test_saving_over_a_damaged_file_keeps_a_copy() {
+        let dir = TempDir::new("damaged");
+        let path = dir.join("crontab");
+        fs::write(&path, b"MAILTO=me@example.com\n# NAME: A\n0 * * * * /bin/a\n\n# Nightly\n# NAME: B\n# TAGS: db\n0 2 \xff").unwrap();
+
+        let scheduler = FileScheduler::new(Some(path.clone()));
+        let entries = scheduler.load().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert!(scheduler.damage().is_some());
+
+        scheduler.save(&entries).unwrap();
+        assert_eq!(scheduler.damage(), None);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "MAILTO=me@example.com\n# NAME: A\n0 * * * * /bin/a\n\n# Nightly\n");
+        assert!(fs::read(sibling(&path, "damaged")).unwrap().ends_with(b"0 2 \xff"));
+    }
+
+    #[test]
+    fn test_a_file_without_a_final_newline_is_saved_over_in_place() {
+        let dir = TempDir::new("newline");
+        let path = dir.join("crontab");
+        fs::write(&path, "MAILTO=me@example.com\n# keep me\n\n# NAME: A\n0 * * * * /bin/a").unwrap();
+
+        let scheduler = FileScheduler::new(Some(path.clone()));
+        assert_eq!(scheduler.damage(), None);
+        let mut entries = scheduler.load().unwrap();
+        entries[0].command = "/bin/b".to_string();
+        scheduler.save(&entries).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "MAILTO=me@example.com\n# keep me\n\n# NAME: A\n0 * * * * /bin/b\n");
+        assert!(!sibling(&path, "damaged").exists());
+    }
+
+    #[test]
+    fn test_concurrent_saves_replace_the_file_whole() {
+        let dir = TempDir::new("file");
+        let path = dir.join("crontab");
+        fs::write(&path, "MAILTO=me@example.com\n").unwrap();
+
+        let writers: Vec<_> = ["A", "B"]
+            .into_iter()
+            .map(|name| {
+                let scheduler = FileScheduler::new(Some(path.clone())).import_from_system(false);
+                std::thread::spawn(move || {
+                    let entries: Vec<_> = (0..20)
+                        .map(|i| entry(&format!("{} {}", name, i), "0 * * * *"))
+                        .collect();
+                    for _ in 0..20 {
+                        scheduler.save(&entries).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let scheduler = FileScheduler::new(Some(path.clone()));
+        let entries = scheduler.load().unwrap();
+        assert_eq!(entries.len(), 20);
+        assert!(entries.iter().all(|entry| entry.name.starts_with(&entries[0].name[..1])));
+        assert!(fs::read_to_string(&path).unwrap().starts_with("MAILTO=me@example.com\n"));
+        assert!(!sibling(&path, "tmp").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_saves_keep_the_file_mode() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = TempDir::new("mode");
+        let path = dir.join("crontab");
+        fs::write(&path, "").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+
+        let scheduler = FileScheduler::new(Some(path.clone()));
+        scheduler.save(&[command_entry("A", "0 * * * *", "/bin/a")]).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+
+    #[test]
+    fn test_fingerprint_follows_writes() {
+        let dir = TempDir::new("fingerprint");
+        let scheduler = FileScheduler::new(Some(dir.join("crontab"))).import_from_system(false);
+        let missing = scheduler.fingerprint();
+        assert!(missing.is_some());
+
+        scheduler.save(&[command_entry("A", "0 * * * *", "/bin/a")]).unwrap();
+        let saved = scheduler.fingerprint();
+        assert_ne!(saved, missing);
+        assert_eq!(scheduler.fingerprint(), saved);
+        fs::write(dir.join("crontab"), "# NAME: B\n0 * * * * /bin/b\n").unwrap();
+        assert_ne!(scheduler.fingerprint(), saved);
+    }
+}
@@ -1,20 +1,42 @@
-use crate::cron_entry::CronEntry;
-use crate::scheduler::Scheduler;
+use crate::cron_entry::{parse_tags, CronEntry};
+use crate::schedule::{self, CronSchedule, FieldKind};
+use crate::shell::Shell;
+use crate::scheduler::{check_writable, run_command, Rejected, SaveReport, Scheduler};
+use crate::secrets::shell_quote;
 use anyhow::{Context, Result};
 use std::collections::hash_map::DefaultHasher;
 use std::fs;
 use std::hash::{Hash, Hasher};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 // Constants
 const LABEL_PREFIX: &str = "com.cronmanager";
 const STDOUT_PATH_PREFIX: &str = "/tmp";
 const STDERR_PATH_PREFIX: &str = "/tmp";
+/// Here-document delimiter for an entry's stdin text
+const STDIN_DELIMITER: &str = "CRON_MANAGER_STDIN";
+/// Most `StartCalendarInterval` dicts one agent gets, e.g. `*/5 9-17 * * 1-5`
+/// needs 540
+const MAX_CALENDAR_INTERVALS: usize = 1440;
+/// Suffix an adopted agent's plist is renamed with, so launchd no longer
+/// loads it at login
+const ADOPTED_SUFFIX: &str = "adopted";
+
+/// A LaunchAgent CronManager didn't write, e.g. one made by hand or by
+/// another tool
+pub struct ForeignAgent {
+    pub label: String,
+    pub path: PathBuf,
+    /// The agent as an entry, or why it can't be one
+    pub entry: std::result::Result<CronEntry, String>,
+}
 
 /// Launchd-based scheduler for macOS
 pub struct LaunchdScheduler {
     launch_agents_dir: PathBuf,
+    /// LaunchDaemons run as root in the system domain (`--root`)
+    system: bool,
 }
 
 impl LaunchdScheduler {
@@ -23,7 +45,13 @@ impl LaunchdScheduler {
         let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
         let launch_agents_dir = home.join("Library/LaunchAgents");
 
-        Self { launch_agents_dir }
+        Self { launch_agents_dir, system: false }
+    }
+
+    /// Manage LaunchDaemons in /Library/LaunchDaemons, which run as root
+    /// whether or not anyone is logged in; needs root
+    pub fn system() -> Self {
+        Self { launch_agents_dir: PathBuf::from("/Library/LaunchDaemons"), system: true }
     }
 
     fn ensure_launch_agents_dir(&self) -> Result<()> {
@@ -71,11 +99,18 @@ impl LaunchdScheduler {
             .replace("&quot;", "\"")
     }
 
+    /// The launchd domain agents are loaded into: the user's GUI session,
+    /// or the system domain for daemons
+    fn domain(&self) -> Result<String> {
+        if self.system {
+            return Ok("system".to_string());
+        }
+        Ok(format!("gui/{}", self.get_uid()?))
+    }
+
     fn get_uid(&self) -> Result<String> {
         // Get the current user's UID using the id command
-        let output = Command::new("id")
-            .arg("-u")
-            .output()
+        let output = run_command(Command::new("id").arg("-u"))
             .context("Failed to get user ID")?;
 
         if !output.status.success() {
@@ -89,104 +124,204 @@ impl LaunchdScheduler {
         Ok(uid)
     }
 
-    fn cron_to_calendar_interval(&self, schedule: &str) -> Result<String> {
-        // Parse cron expression: minute hour day month weekday
-        let parts: Vec<&str> = schedule.split_whitespace().collect();
-        if parts.len() != 5 {
-            anyhow::bail!("Invalid cron expression: {}", schedule);
-        }
-
-        let minute = parts[0];
-        let hour = parts[1];
-        let day = parts[2];
-        let month = parts[3];
-        let weekday = parts[4];
-
-        // Validate that cron expressions are supported (simple values only)
-        // launchd doesn't support ranges (1-5), lists (1,3,5), or step values (*/15)
-        for (i, part) in parts.iter().enumerate() {
-            let field_name = match i {
-                0 => "minute",
-                1 => "hour",
-                2 => "day",
-                3 => "month",
-                4 => "weekday",
-                _ => unreachable!(),
-            };
+    /// The `StartCalendarInterval` dicts (their keys) that together fire
+    /// when the cron schedule does. launchd takes one value per key, so
+    /// ranges, lists and steps become one dict per combination of values;
+    /// keys that match every value are left out, which launchd reads as `*`.
+    fn cron_to_calendar_intervals(&self, schedule: &str) -> Result<Vec<String>> {
+        // Nicknames such as @daily stand for plain values (@reboot is
+        // RunAtLoad instead, see create_plist)
+        let expr = schedule::expand_nickname(schedule);
+        let cron = CronSchedule::parse(expr).with_context(|| format!("Invalid cron expression: {}", schedule))?;
+        if cron.reboot {
+            anyhow::bail!("@reboot has no calendar interval");
+        }
 
-            if part.contains('-') {
-                anyhow::bail!(
-                    "Cron expression contains unsupported range '{}' in {} field. \
-                     launchd only supports simple values or * wildcard.",
-                    part, field_name
-                );
-            }
-            if part.contains(',') {
-                anyhow::bail!(
-                    "Cron expression contains unsupported list '{}' in {} field. \
-                     launchd only supports simple values or * wildcard.",
-                    part, field_name
-                );
+        let values = |kind: FieldKind| -> Vec<Option<u32>> {
+            let field = cron.field(kind);
+            let (min, max) = kind.bounds();
+            // Day of week 7 is read as Sunday (0), so all days are 7 values
+            let all = if kind == FieldKind::DayOfWeek { 7 } else { (max - min + 1) as usize };
+            if field.len() == all {
+                vec![None]
+            } else {
+                field.values().into_iter().map(Some).collect()
             }
-            if part.contains('/') {
+        };
+        let (days, weekdays) = (values(FieldKind::DayOfMonth), values(FieldKind::DayOfWeek));
+        let restricted = |values: &[Option<u32>]| values != [None];
+
+        // launchd, like cron, runs on either the Day or the Weekday when a
+        // dict has both, so days that must match both can't be expressed
+        let day_pairs: Vec<(Option<u32>, Option<u32>)> = if cron.days_of_month.is_star() || cron.days_of_week.is_star() {
+            if restricted(&days) && restricted(&weekdays) {
                 anyhow::bail!(
-                    "Cron expression contains unsupported step value '{}' in {} field. \
-                     launchd only supports simple values or * wildcard.",
-                    part, field_name
+                    "'{}' runs only on days matching both the day and weekday fields, \
+                     which launchd can't express; restrict one of them with a plain value or list",
+                    schedule
                 );
             }
-            if part.starts_with('@') {
-                anyhow::bail!(
-                    "Cron expression contains unsupported special syntax '{}'. \
-                     Please use explicit minute/hour/day values.",
-                    part
-                );
+            days.iter().flat_map(|day| weekdays.iter().map(move |weekday| (*day, *weekday))).collect()
+        } else if !restricted(&days) || !restricted(&weekdays) {
+            // Either field matching every day makes every day match
+            vec![(None, None)]
+        } else {
+            days.iter().map(|day| (*day, None)).chain(weekdays.iter().map(|weekday| (None, *weekday))).collect()
+        };
+
+        let (months, hours, minutes) = (values(FieldKind::Month), values(FieldKind::Hour), values(FieldKind::Minute));
+        let count = months.len() * day_pairs.len() * hours.len() * minutes.len();
+        if count > MAX_CALENDAR_INTERVALS {
+            anyhow::bail!(
+                "'{}' needs {} calendar intervals, more than the {} launchd agents here are allowed",
+                schedule,
+                count,
+                MAX_CALENDAR_INTERVALS
+            );
+        }
+
+        let mut intervals = Vec::with_capacity(count);
+        for month in &months {
+            for (day, weekday) in &day_pairs {
+                for hour in &hours {
+                    for minute in &minutes {
+                        let keys = [("Month", month), ("Day", day), ("Weekday", weekday), ("Hour", hour), ("Minute", minute)];
+                        intervals.push(
+                            keys.iter()
+                                .filter_map(|(key, value)| value.map(|value| (key, value)))
+                                .map(|(key, value)| format!("<key>{}</key>\n<integer>{}</integer>\n", key, value))
+                                .collect(),
+                        );
+                    }
+                }
             }
+        }
+        Ok(intervals)
+    }
 
-            // Validate it's either * or a number
-            if *part != "*" && part.parse::<u32>().is_err() {
-                anyhow::bail!(
-                    "Invalid value '{}' in {} field. Must be a number or *.",
-                    part, field_name
-                );
+    /// The `StartCalendarInterval` key: a dict, or an array of them
+    fn calendar_interval_plist(&self, intervals: &[String]) -> String {
+        let indent = |keys: &str, depth: usize| -> String {
+            keys.lines().map(|line| format!("{}{}\n", " ".repeat(depth), line)).collect()
+        };
+        match intervals {
+            [interval] => format!(
+                "    <key>StartCalendarInterval</key>\n    <dict>\n{}    </dict>\n",
+                indent(interval, 8)
+            ),
+            _ => {
+                let dicts: String = intervals
+                    .iter()
+                    .map(|interval| format!("        <dict>\n{}        </dict>\n", indent(interval, 12)))
+                    .collect();
+                format!("    <key>StartCalendarInterval</key>\n    <array>\n{}    </array>\n", dicts)
             }
         }
+    }
 
-        // Convert to launchd calendar format
-        let mut calendar_dict = String::new();
+    fn create_plist(&self, entry: &CronEntry) -> Result<String> {
+        let label = self.entry_to_label(entry);
+        // @reboot runs when the agent is loaded, i.e. at login
+        let trigger = if schedule::is_reboot(&entry.schedule) {
+            "    <key>RunAtLoad</key>\n    <true/>\n".to_string()
+        } else {
+            self.calendar_interval_plist(&self.cron_to_calendar_intervals(&entry.schedule)?)
+        };
 
-        // Month (1-12)
-        if month != "*" {
-            calendar_dict.push_str(&format!("        <key>Month</key>\n        <integer>{}</integer>\n", month));
+        // Entry metadata that launchd itself ignores, kept for round-tripping.
+        // The schedule as written, since the intervals may not read back as it.
+        let mut metadata = format!(
+            "    <key>CronManagerSchedule</key>\n    <string>{}</string>\n",
+            self.escape_xml(&entry.schedule)
+        );
+        if let Some(tz) = &entry.timezone {
+            metadata.push_str(&format!(
+                "    <key>CronManagerTimezone</key>\n    <string>{}</string>\n",
+                self.escape_xml(tz)
+            ));
         }
-
-        // Day (1-31)
-        if day != "*" {
-            calendar_dict.push_str(&format!("        <key>Day</key>\n        <integer>{}</integer>\n", day));
+        if let Some(template) = &entry.command_template {
+            metadata.push_str(&format!(
+                "    <key>CronManagerCommand</key>\n    <string>{}</string>\n",
+                self.escape_xml(template)
+            ));
         }
-
-        // Weekday (0-7, where 0 and 7 are Sunday)
-        if weekday != "*" {
-            let wd = if weekday == "7" { "0" } else { weekday };
-            calendar_dict.push_str(&format!("        <key>Weekday</key>\n        <integer>{}</integer>\n", wd));
+        if !entry.tags.is_empty() {
+            metadata.push_str(&format!(
+                "    <key>CronManagerTags</key>\n    <string>{}</string>\n",
+                self.escape_xml(&entry.tags.join(", "))
+            ));
         }
 
-        // Hour (0-23)
-        if hour != "*" {
-            calendar_dict.push_str(&format!("        <key>Hour</key>\n        <integer>{}</integer>\n", hour));
+        if let Some(preset) = &entry.env {
+            metadata.push_str(&format!(
+                "    <key>CronManagerEnv</key>\n    <string>{}</string>\n",
+                self.escape_xml(preset)
+            ));
+        }
+        if let Some(shell) = entry.shell {
+            metadata.push_str(&format!(
+                "    <key>CronManagerShell</key>\n    <string>{}</string>\n",
+                shell.name()
+            ));
+        }
+        if !entry.comments.is_empty() {
+            metadata.push_str(&format!(
+                "    <key>CronManagerComments</key>\n    <string>{}</string>\n",
+                self.escape_xml(&entry.comments.join("\n"))
+            ));
         }
 
-        // Minute (0-59)
-        if minute != "*" {
-            calendar_dict.push_str(&format!("        <key>Minute</key>\n        <integer>{}</integer>\n", minute));
+        if entry.watchdog {
+            metadata.push_str("    <key>CronManagerWatchdog</key>\n    <true/>\n");
+        }
+        if let Some(watched) = &entry.watchdog_for {
+            metadata.push_str(&format!(
+                "    <key>CronManagerWatchdogFor</key>\n    <string>{}</string>\n",
+                self.escape_xml(watched)
+            ));
         }
 
-        Ok(calendar_dict)
-    }
+        // The entry's own variables and directory, set by launchd itself.
+        // launchd doesn't expand `~`, so keep the path as written alongside.
+        let mut environment = String::new();
+        if !entry.env_vars.is_empty() {
+            let vars: String = entry
+                .env_vars
+                .iter()
+                .map(|(name, value)| {
+                    format!(
+                        "        <key>{}</key>\n        <string>{}</string>\n",
+                        self.escape_xml(name),
+                        self.escape_xml(value)
+                    )
+                })
+                .collect();
+            environment.push_str(&format!("    <key>EnvironmentVariables</key>\n    <dict>\n{}    </dict>\n", vars));
+        }
+        if let Some(cwd) = &entry.cwd {
+            let written = cwd.to_string_lossy();
+            let expanded = match written.strip_prefix('~') {
+                Some(rest) => format!("{}{}", dirs::home_dir().unwrap_or_default().display(), rest),
+                None => written.to_string(),
+            };
+            environment.push_str(&format!(
+                "    <key>WorkingDirectory</key>\n    <string>{}</string>\n",
+                self.escape_xml(&expanded)
+            ));
+            if expanded != written {
+                metadata.push_str(&format!(
+                    "    <key>CronManagerWorkingDirectory</key>\n    <string>{}</string>\n",
+                    self.escape_xml(&written)
+                ));
+            }
+        }
 
-    fn create_plist(&self, entry: &CronEntry) -> Result<String> {
-        let label = self.entry_to_label(entry);
-        let calendar = self.cron_to_calendar_interval(&entry.schedule)?;
+        // launchd has no inline stdin, so feed it through a here-document
+        let command = match &entry.stdin {
+            Some(input) => format!("{{ {}\n}} <<'{}'\n{}\n{}", entry.command, STDIN_DELIMITER, input, STDIN_DELIMITER),
+            None => entry.command.clone(),
+        };
 
         let plist = format!(
             r#"<?xml version="1.0" encoding="UTF-8"?>
@@ -197,16 +332,13 @@ impl LaunchdScheduler {
     <string>{}</string>
     <key>CronManagerTaskName</key>
     <string>{}</string>
-    <key>ProgramArguments</key>
+{}    <key>ProgramArguments</key>
     <array>
         <string>/bin/sh</string>
         <string>-c</string>
         <string>{}</string>
     </array>
-    <key>StartCalendarInterval</key>
-    <dict>
-{}    </dict>
-    <key>StandardOutPath</key>
+{}{}    <key>StandardOutPath</key>
     <string>{}/{}.stdout</string>
     <key>StandardErrorPath</key>
     <string>{}/{}.stderr</string>
@@ -215,8 +347,10 @@ impl LaunchdScheduler {
 "#,
             label,
             self.escape_xml(&entry.name),
-            self.escape_xml(&entry.command),
-            calendar,
+            metadata,
+            self.escape_xml(&command),
+            trigger,
+            environment,
             STDOUT_PATH_PREFIX,
             label,
             STDERR_PATH_PREFIX,
@@ -231,15 +365,15 @@ impl LaunchdScheduler {
 
         // Use modern bootstrap command (macOS 10.11+)
         // Format: launchctl bootstrap gui/<uid> <plist_path>
-        let uid = self.get_uid()?;
-        let domain = format!("gui/{}", uid);
+        let domain = self.domain()?;
 
-        let output = Command::new("launchctl")
-            .arg("bootstrap")
-            .arg(&domain)
-            .arg(&plist_path)
-            .output()
-            .context("Failed to execute launchctl bootstrap")?;
+        let output = run_command(
+            Command::new("launchctl")
+                .arg("bootstrap")
+                .arg(&domain)
+                .arg(&plist_path),
+        )
+        .context("Failed to execute launchctl bootstrap")?;
 
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
@@ -252,6 +386,44 @@ impl LaunchdScheduler {
         Ok(())
     }
 
+    /// Make the entry's agent match it: left alone when its plist is
+    /// already as generated and loaded, loaded when only that is missing (an
+    /// earlier bootstrap failed, or it was booted out by hand), otherwise
+    /// unloaded, rewritten and loaded again
+    fn sync_agent(&self, entry: &CronEntry) -> Result<()> {
+        let plist_content = self.create_plist(entry)?;
+        let label = self.entry_to_label(entry);
+        let plist_path = self.plist_path(&label);
+        if fs::read_to_string(&plist_path).is_ok_and(|current| current == plist_content) {
+            if self.is_loaded(&label)? {
+                return Ok(());
+            }
+            return self.load_agent(&label);
+        }
+        self.unload_agent(&label)?;
+        fs::write(&plist_path, plist_content)
+            .with_context(|| format!("Failed to write plist: {:?}", plist_path))?;
+        self.load_agent(&label)
+    }
+
+    /// Unload the agent and delete its plist
+    fn remove_agent(&self, label: &str) -> Result<()> {
+        self.unload_agent(label)?;
+        let plist_path = self.plist_path(label);
+        if plist_path.exists() {
+            fs::remove_file(&plist_path).with_context(|| format!("Failed to remove plist: {:?}", plist_path))?;
+        }
+        Ok(())
+    }
+
+    /// Whether launchd has the agent loaded in our domain
+    fn is_loaded(&self, label: &str) -> Result<bool> {
+        let service_target = format!("{}/{}", self.domain()?, label);
+        let output = run_command(Command::new("launchctl").arg("print").arg(&service_target))
+            .context("Failed to execute launchctl print")?;
+        Ok(output.status.success())
+    }
+
     fn unload_agent(&self, label: &str) -> Result<()> {
         let plist_path = self.plist_path(label);
 
@@ -261,13 +433,9 @@ impl LaunchdScheduler {
 
         // Use modern bootout command (macOS 10.11+)
         // Format: launchctl bootout gui/<uid>/<label>
-        let uid = self.get_uid()?;
-        let service_target = format!("gui/{}/{}", uid, label);
+        let service_target = format!("{}/{}", self.domain()?, label);
 
-        let _output = Command::new("launchctl")
-            .arg("bootout")
-            .arg(&service_target)
-            .output()
+        let _output = run_command(Command::new("launchctl").arg("bootout").arg(&service_target))
             .context("Failed to execute launchctl bootout")?;
 
         // Ignore errors on bootout (agent might not be loaded)
@@ -298,6 +466,167 @@ impl LaunchdScheduler {
         Ok(labels)
     }
 
+    /// The user's LaunchAgents that CronManager didn't write, each read
+    /// into an entry where its trigger has a cron equivalent
+    pub fn foreign_agents(&self) -> Result<Vec<ForeignAgent>> {
+        let mut agents = Vec::new();
+        if !self.launch_agents_dir.exists() {
+            return Ok(agents);
+        }
+
+        for entry in fs::read_dir(&self.launch_agents_dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("plist") {
+                continue;
+            }
+            let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            if stem.starts_with(LABEL_PREFIX) {
+                continue;
+            }
+            let content = match self.read_plist_xml(&path) {
+                Ok(content) => content,
+                Err(e) => {
+                    agents.push(ForeignAgent { label: stem.to_string(), path, entry: Err(format!("{:#}", e)) });
+                    continue;
+                }
+            };
+            let label = self
+                .extract_xml_value(&content, "Label")
+                .map(|label| self.unescape_xml(&label))
+                .unwrap_or_else(|| stem.to_string());
+            let entry = self.parse_foreign_plist(&label, &content);
+            agents.push(ForeignAgent { label, path, entry });
+        }
+
+        agents.sort_by(|a, b| a.label.cmp(&b.label));
+        Ok(agents)
+    }
+
+    /// Unload an adopted agent and rename its plist so it isn't loaded
+    /// again, leaving it next to the others to restore by hand. Returns the
+    /// new path.
+    pub fn retire_agent(&self, agent: &ForeignAgent) -> Result<PathBuf> {
+        // Not loaded is fine: the plist is what would bring it back
+        run_command(Command::new("launchctl").arg("bootout").arg(format!("{}/{}", self.domain()?, agent.label)))
+            .context("Failed to execute launchctl bootout")?;
+
+        let mut retired = agent.path.clone().into_os_string();
+        retired.push(format!(".{}", ADOPTED_SUFFIX));
+        let retired = PathBuf::from(retired);
+        fs::rename(&agent.path, &retired)
+            .with_context(|| format!("Failed to rename {:?} to {:?}", agent.path, retired))?;
+        Ok(retired)
+    }
+
+    /// A plist as XML: agents written by other tools may be binary, which
+    /// `plutil` converts
+    fn read_plist_xml(&self, path: &Path) -> Result<String> {
+        let output = run_command(Command::new("plutil").args(["-convert", "xml1", "-o", "-"]).arg(path));
+        match output {
+            Ok(output) if output.status.success() => Ok(String::from_utf8_lossy(&output.stdout).into_owned()),
+            _ => fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path)),
+        }
+    }
+
+    /// An agent written elsewhere as an entry named after its label, or
+    /// why its trigger or program has no crontab equivalent
+    fn parse_foreign_plist(&self, label: &str, content: &str) -> std::result::Result<CronEntry, String> {
+        let command = self.extract_foreign_command(content).ok_or("no Program or ProgramArguments")?;
+
+        let schedule = if content.contains("<key>StartCalendarInterval</key>") {
+            let intervals = self.extract_calendar_dicts(content);
+            calendar_dicts_to_cron(&intervals).ok_or_else(|| {
+                format!("{} calendar intervals that don't make one cron schedule", intervals.len())
+            })?
+        } else if let Some(seconds) = self.extract_calendar_value(content, "StartInterval") {
+            let seconds: u64 = seconds.trim().parse().map_err(|_| format!("invalid StartInterval '{}'", seconds))?;
+            interval_to_cron(seconds)
+                .ok_or_else(|| format!("StartInterval of {} seconds has no cron equivalent", seconds))?
+        } else if self.has_true(content, "RunAtLoad") {
+            "@reboot".to_string()
+        } else {
+            return Err("no StartCalendarInterval, StartInterval or RunAtLoad".to_string());
+        };
+        self.check_schedule(&schedule).map_err(|e| format!("{:#}", e))?;
+
+        let mut entry = CronEntry::new(label.to_string(), schedule, command);
+        entry.env_vars = self.extract_environment(content);
+        entry.cwd = self
+            .extract_xml_value(content, "WorkingDirectory")
+            .map(|cwd| PathBuf::from(self.unescape_xml(&cwd)));
+        entry.enabled = !self.has_true(content, "Disabled");
+        Ok(entry)
+    }
+
+    /// Whether a boolean key is `<true/>`
+    fn has_true(&self, content: &str, key: &str) -> bool {
+        let key_pattern = format!("<key>{}</key>", key);
+        content
+            .find(&key_pattern)
+            .is_some_and(|pos| content[pos + key_pattern.len()..].trim_start().starts_with("<true/>"))
+    }
+
+    /// The command line of any agent: a `sh -c` script as is, other
+    /// argument lists quoted for the shell
+    fn extract_foreign_command(&self, content: &str) -> Option<String> {
+        let mut args = Vec::new();
+        if let Some(start) = content.find("<key>ProgramArguments</key>") {
+            let after = &content[start..];
+            let array = after.find("</array>").map_or(after, |end| &after[..end]);
+            let mut rest = array;
+            while let Some(string_start) = rest.find("<string>") {
+                let after_string = &rest[string_start + 8..];
+                let Some(string_end) = after_string.find("</string>") else {
+                    break;
+                };
+                args.push(self.unescape_xml(&after_string[..string_end]));
+                rest = &after_string[string_end..];
+            }
+        }
+        // Program, when given, is run with ProgramArguments as its argv
+        if let Some(program) = self.extract_xml_value(content, "Program") {
+            let program = self.unescape_xml(&program);
+            match args.first_mut() {
+                Some(first) => *first = program,
+                None => args.push(program),
+            }
+        }
+
+        match args.as_slice() {
+            [] => None,
+            [shell, flag, script] if shell.ends_with("sh") && flag == "-c" => Some(script.clone()),
+            _ => Some(args.iter().map(|arg| shell_quote(arg)).collect::<Vec<_>>().join(" ")),
+        }
+    }
+
+    /// The `StartCalendarInterval` dicts as cron fields (minute, hour, day,
+    /// month, weekday), `None` where a key is left out
+    fn extract_calendar_dicts(&self, content: &str) -> Vec<[Option<String>; 5]> {
+        let Some(start) = content.find("<key>StartCalendarInterval</key>") else {
+            return Vec::new();
+        };
+        let after = content[start + "<key>StartCalendarInterval</key>".len()..].trim_start();
+        let body = if after.starts_with("<array>") {
+            after.find("</array>").map_or(after, |end| &after[..end])
+        } else {
+            after.find("</dict>").map_or(after, |end| &after[..end + 7])
+        };
+
+        let mut dicts = Vec::new();
+        let mut rest = body;
+        while let Some(open) = rest.find("<dict>") {
+            let Some(close) = rest[open..].find("</dict>") else {
+                break;
+            };
+            let dict = &rest[open..open + close];
+            dicts.push(["Minute", "Hour", "Day", "Month", "Weekday"].map(|key| self.extract_calendar_value(dict, key)));
+            rest = &rest[open + close + 7..];
+        }
+        dicts
+    }
+
     fn parse_plist(&self, path: &PathBuf) -> Result<CronEntry> {
         let content = fs::read_to_string(path)?;
 
@@ -319,13 +648,86 @@ impl LaunchdScheduler {
 
         // Extract command from ProgramArguments (it's the third string, after /bin/sh and -c)
         let command = self.extract_command(&content)
-            .unwrap_or_else(|| "".to_string());
+            .unwrap_or_default();
+
+        // The schedule as written; plists from before it was kept have a
+        // single calendar interval to convert back to cron
+        let schedule = match self.extract_xml_value(&content, "CronManagerSchedule") {
+            Some(schedule) => self.unescape_xml(&schedule),
+            None if !content.contains("<key>StartCalendarInterval</key>") && content.contains("<key>RunAtLoad</key>") => {
+                "@reboot".to_string()
+            }
+            None => self.extract_calendar_to_cron(&content).unwrap_or_else(|| "0 0 * * *".to_string()),
+        };
 
-        // Extract calendar interval and convert back to cron
-        let schedule = self.extract_calendar_to_cron(&content)
-            .unwrap_or_else(|| "0 0 * * *".to_string());
+        let mut entry = CronEntry::new(name, schedule, command);
+        if let Some((command, input)) = self.split_stdin(&entry.command) {
+            entry.command = command;
+            entry.stdin = Some(input);
+        }
+        entry.timezone = self.extract_xml_value(&content, "CronManagerTimezone")
+            .map(|tz| self.unescape_xml(&tz));
+        entry.command_template = self.extract_xml_value(&content, "CronManagerCommand")
+            .map(|template| self.unescape_xml(&template));
+        entry.tags = self.extract_xml_value(&content, "CronManagerTags")
+            .map(|tags| parse_tags(&self.unescape_xml(&tags)))
+            .unwrap_or_default();
+        entry.env = self.extract_xml_value(&content, "CronManagerEnv")
+            .map(|preset| self.unescape_xml(&preset));
+        entry.shell = self.extract_xml_value(&content, "CronManagerShell")
+            .and_then(|shell| Shell::parse(&shell).ok().flatten());
+        entry.comments = self.extract_xml_value(&content, "CronManagerComments")
+            .map(|comments| self.unescape_xml(&comments).lines().map(str::to_string).collect())
+            .unwrap_or_default();
+        entry.env_vars = self.extract_environment(&content);
+        entry.cwd = self.extract_xml_value(&content, "CronManagerWorkingDirectory")
+            .or_else(|| self.extract_xml_value(&content, "WorkingDirectory"))
+            .map(|cwd| PathBuf::from(self.unescape_xml(&cwd)));
+        entry.watchdog = content.contains("<key>CronManagerWatchdog</key>");
+        entry.watchdog_for = self.extract_xml_value(&content, "CronManagerWatchdogFor")
+            .map(|name| self.unescape_xml(&name));
+
+        Ok(entry)
+    }
 
-        Ok(CronEntry::new(name, schedule, command))
+    /// The `EnvironmentVariables` dict, in the order written
+    fn extract_environment(&self, content: &str) -> Vec<(String, String)> {
+        let Some(start) = content.find("<key>EnvironmentVariables</key>") else {
+            return Vec::new();
+        };
+        let after = &content[start..];
+        let Some(dict) = after.find("<dict>").and_then(|open| {
+            after[open..].find("</dict>").map(|close| &after[open + 6..open + close])
+        }) else {
+            return Vec::new();
+        };
+        let mut vars = Vec::new();
+        let mut rest = dict;
+        while let Some(key_start) = rest.find("<key>") {
+            let after_key = &rest[key_start + 5..];
+            let Some(key_end) = after_key.find("</key>") else {
+                break;
+            };
+            let name = self.unescape_xml(&after_key[..key_end]);
+            let after_name = &after_key[key_end + 6..];
+            let value = after_name
+                .trim_start()
+                .strip_prefix("<string>")
+                .and_then(|value| value.find("</string>").map(|end| self.unescape_xml(&value[..end])));
+            if let Some(value) = value {
+                vars.push((name, value));
+            }
+            rest = after_name;
+        }
+        vars
+    }
+
+    /// The command and stdin text of a command written with a here-document
+    fn split_stdin(&self, command: &str) -> Option<(String, String)> {
+        let wrapped = command.strip_prefix("{ ")?;
+        let (command, rest) = wrapped.split_once(&format!("\n}} <<'{}'\n", STDIN_DELIMITER))?;
+        let input = rest.strip_suffix(&format!("\n{}", STDIN_DELIMITER))?;
+        Some((command.to_string(), input.to_string()))
     }
 
     fn extract_xml_value(&self, content: &str, key: &str) -> Option<String> {
@@ -393,55 +795,368 @@ impl LaunchdScheduler {
     }
 }
 
+/// One cron schedule for calendar intervals that differ in at most one key,
+/// e.g. `Hour` 9 and 17 become `0 9,17 * * *`
+fn calendar_dicts_to_cron(dicts: &[[Option<String>; 5]]) -> Option<String> {
+    let first = dicts.first()?;
+    let differing: Vec<usize> = (0..5).filter(|&i| dicts.iter().any(|dict| dict[i] != first[i])).collect();
+    let fields: Vec<String> = (0..5)
+        .map(|i| {
+            if !differing.contains(&i) {
+                return Some(first[i].clone().unwrap_or_else(|| "*".to_string()));
+            }
+            // A key left out in one dict matches every value
+            let mut values: Vec<u32> = dicts
+                .iter()
+                .map(|dict| dict[i].as_deref().and_then(|value| value.trim().parse().ok()))
+                .collect::<Option<_>>()?;
+            values.sort_unstable();
+            values.dedup();
+            Some(values.iter().map(u32::to_string).collect::<Vec<_>>().join(","))
+        })
+        .collect::<Option<_>>()?;
+    (differing.len() <= 1).then(|| fields.join(" "))
+}
+
+/// The cron schedule for a `StartInterval`, where the seconds divide evenly
+/// into an hour or a day. launchd counts from when the agent was loaded
+/// rather than the clock, so runs may shift to the top of the interval.
+fn interval_to_cron(seconds: u64) -> Option<String> {
+    if seconds == 0 || !seconds.is_multiple_of(60) {
+        return None;
+    }
+    let minutes = seconds / 60;
+    match minutes {
+        1 => Some("* * * * *".to_string()),
+        2..=59 if 60 % minutes == 0 => Some(format!("*/{} * * * *", minutes)),
+        60 => Some("0 * * * *".to_string()),
+        1440 => Some("0 0 * * *".to_string()),
+        10080 => Some("0 0 * * 0".to_string()),
+        _ if minutes.is_multiple_of(60) && 24 % (minutes / 60) == 0 => Some(format!("0 */{} * * *", minutes / 60)),
+        _ => None,
+    }
+}
+
+impl Default for LaunchdScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Scheduler for LaunchdScheduler {
     fn load(&self) -> Result<Vec<CronEntry>> {
-        let mut entries = Vec::new();
-
         let labels = self.list_agents()?;
-
-        for label in labels {
-            let plist_path = self.plist_path(&label);
-            if let Ok(entry) = self.parse_plist(&plist_path) {
-                entries.push(entry);
-            }
+        if labels.is_empty() {
+            return Ok(Vec::new());
         }
 
+        // Parse plists on scoped worker threads, one contiguous chunk of
+        // labels each, so entries come back in the same order as `labels`
+        let workers = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk_size = labels.len().div_ceil(workers);
+
+        let entries = std::thread::scope(|scope| {
+            let handles: Vec<_> = labels
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .filter_map(|label| self.parse_plist(&self.plist_path(label)).ok())
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap_or_default())
+                .collect()
+        });
+
         Ok(entries)
     }
 
-    fn save(&self, entries: &[CronEntry]) -> Result<()> {
+    /// The save removes and rewrites plists one by one, so a directory it
+    /// can't write would leave the agents half replaced
+    fn preflight(&self) -> Result<()> {
+        check_writable(&self.launch_agents_dir)
+    }
+
+    /// Only the agents whose plist changed are unloaded and loaded again;
+    /// the others keep running undisturbed
+    fn save(&self, entries: &[CronEntry]) -> Result<SaveReport> {
         self.ensure_launch_agents_dir()?;
 
-        // Get list of existing agents managed by us
-        let existing_labels = self.list_agents()?;
+        // Remove the agents of entries that are gone or disabled
+        let wanted: Vec<String> = entries.iter().filter(|e| e.enabled).map(|e| self.entry_to_label(e)).collect();
+        for label in self.list_agents()? {
+            if !wanted.contains(&label) {
+                self.remove_agent(&label)?;
+            }
+        }
 
-        // Unload and remove all existing agents
-        for label in existing_labels {
-            self.unload_agent(&label)?;
-            let plist_path = self.plist_path(&label);
-            if plist_path.exists() {
-                fs::remove_file(&plist_path)?;
+        // Write and load the agents that are new or changed. One agent
+        // failing doesn't stop the rest from being registered.
+        let mut report = SaveReport::default();
+        for (index, entry) in entries.iter().enumerate() {
+            if !entry.enabled {
+                report.applied.push(index);
+                continue;
+            }
+            match self.sync_agent(entry) {
+                Ok(()) => report.applied.push(index),
+                Err(e) => {
+                    tracing::warn!(entry = %entry.name, error = %format!("{:#}", e), "agent not registered");
+                    report.failed.push((index, format!("{:#}", e)));
+                }
             }
         }
 
-        // Create and load new agents for enabled entries
-        for entry in entries {
-            if entry.enabled {
-                let plist_content = self.create_plist(entry)?;
-                let label = self.entry_to_label(entry);
-                let plist_path = self.plist_path(&label);
+        Ok(report)
+    }
+
+    fn add(&self, entry: &CronEntry) -> Result<()> {
+        if !entry.enabled {
+            return Ok(());
+        }
+        self.ensure_launch_agents_dir()?;
+        // As in a full save, an agent launchd won't load is the entry's failure
+        self.sync_agent(entry)
+            .map_err(|e| Rejected { name: entry.name.clone(), reason: format!("{:#}", e) }.into())
+    }
+
+    fn update(&self, old: &CronEntry, new: &CronEntry) -> Result<()> {
+        let label = self.entry_to_label(old);
+        if !new.enabled || label != self.entry_to_label(new) {
+            self.remove_agent(&label)?;
+        }
+        self.add(new)
+    }
+
+    fn remove(&self, entry: &CronEntry) -> Result<()> {
+        self.remove_agent(&self.entry_to_label(entry))
+    }
 
-                fs::write(&plist_path, plist_content)
-                    .with_context(|| format!("Failed to write plist: {:?}", plist_path))?;
+    /// The name, modification time and size of every agent's plist, read
+    /// without asking launchctl or parsing any of them
+    fn fingerprint(&self) -> Option<u64> {
+        let mut labels = self.list_agents().ok()?;
+        labels.sort();
+        let mut hasher = DefaultHasher::new();
+        for label in labels {
+            let metadata = fs::metadata(self.plist_path(&label)).ok()?;
+            (label, metadata.modified().ok()?, metadata.len()).hash(&mut hasher);
+        }
+        Some(hasher.finish())
+    }
 
-                self.load_agent(&label)?;
+    /// The plist of every enabled entry; agents of other entries are removed
+    fn render(&self, entries: &[CronEntry]) -> Result<String> {
+        let mut output = String::new();
+        for entry in entries.iter().filter(|entry| entry.enabled) {
+            let path = self.plist_path(&self.entry_to_label(entry));
+            match self.create_plist(entry) {
+                Ok(plist) => output.push_str(&format!("==> {} <==\n{}\n", path.display(), plist)),
+                Err(e) => output.push_str(&format!("==> {} <==\n(not written: {:#})\n\n", path.display(), e)),
             }
         }
+        Ok(output)
+    }
 
-        Ok(())
+    fn log_path(&self, entry: &CronEntry) -> Option<PathBuf> {
+        // Commands can still redirect output themselves; otherwise launchd
+        // writes it to StandardOutPath
+        let label = self.entry_to_label(entry);
+        entry.output_path().or_else(|| {
+            // Prefer what the installed agent actually uses
+            let installed = fs::read_to_string(self.plist_path(&label))
+                .ok()
+                .and_then(|content| self.extract_xml_value(&content, "StandardOutPath"))
+                .map(|path| PathBuf::from(self.unescape_xml(&path)));
+            installed.or_else(|| Some(PathBuf::from(format!("{}/{}.stdout", STDOUT_PATH_PREFIX, label))))
+        })
     }
 
     fn backend_name(&self) -> &'static str {
-        "Launchd"
+        if self.system {
+            "Launchd (root)"
+        } else {
+            "Launchd"
+        }
+    }
+
+    fn keeps_disabled_entries(&self) -> bool {
+        // Disabled entries have no agent, so there is nothing to re-enable
+        false
+    }
+
+    fn keeps_output(&self) -> bool {
+        // StandardOutPath in the plist
+        true
+    }
+
+    fn has_timeout_command(&self) -> bool {
+        // Not part of macOS
+        false
+    }
+
+    fn sets_environment(&self) -> bool {
+        // EnvironmentVariables and WorkingDirectory in the plist
+        true
+    }
+
+    fn check_schedule(&self, schedule: &str) -> Result<()> {
+        if schedule::is_reboot(schedule) {
+            return Ok(());
+        }
+        self.cron_to_calendar_intervals(schedule).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheduler() -> LaunchdScheduler {
+        LaunchdScheduler { launch_agents_dir: PathBuf::from("/nonexistent"), system: false }
+    }
+
+    /// Each dict's keys as `Key=value`, e.g. `Hour=9 Minute=0`
+    fn intervals(schedule: &str) -> Result<Vec<String>> {
+        let dicts = scheduler().cron_to_calendar_intervals(schedule)?;
+        Ok(dicts
+            .iter()
+            .map(|dict| {
+                let lines: Vec<&str> = dict.lines().collect();
+                let keys: Vec<String> = lines
+                    .chunks(2)
+                    .map(|pair| {
+                        let key = pair[0].trim_start_matches("<key>").trim_end_matches("</key>");
+                        let value = pair[1].trim_start_matches("<integer>").trim_end_matches("</integer>");
+                        format!("{}={}", key, value)
+                    })
+                    .collect();
+                keys.join(" ")
+            })
+            .collect())
+    }
+
+    #[test]
+    fn test_calendar_intervals() {
+        assert_eq!(intervals("*/15 * * * *").unwrap(), ["Minute=0", "Minute=15", "Minute=30", "Minute=45"]);
+        assert_eq!(intervals("@daily").unwrap(), ["Hour=0 Minute=0"]);
+        // Every minute: one dict with no keys
+        assert_eq!(intervals("* * * * *").unwrap(), [""]);
+
+        let weekdays = intervals("0 9-17/4 * * 1-5").unwrap();
+        assert_eq!(weekdays.len(), 15);
+        assert_eq!(weekdays[..3], ["Weekday=1 Hour=9 Minute=0", "Weekday=1 Hour=13 Minute=0", "Weekday=1 Hour=17 Minute=0"]);
+        assert_eq!(weekdays[14], "Weekday=5 Hour=17 Minute=0");
+
+        // Weekday 7 is Sunday, and 0-7 is every day
+        assert_eq!(intervals("30 8 * * 7").unwrap(), ["Weekday=0 Hour=8 Minute=30"]);
+        assert_eq!(intervals("30 8 * * 0-7").unwrap(), ["Hour=8 Minute=30"]);
+    }
+
+    #[test]
+    fn test_calendar_intervals_for_days_and_weekdays() {
+        // Both fields restricted: either one matching runs the job
+        assert_eq!(
+            intervals("0 6 1,15 * 1").unwrap(),
+            ["Day=1 Hour=6 Minute=0", "Day=15 Hour=6 Minute=0", "Weekday=1 Hour=6 Minute=0"]
+        );
+        // A field matching every day makes every day match
+        assert_eq!(intervals("0 6 1-31 * 1").unwrap(), ["Hour=6 Minute=0"]);
+        // With a `*` field, days must match both, which launchd can't express
+        let error = intervals("0 6 */2 * 1").unwrap_err();
+        assert!(error.to_string().contains("matching both the day and weekday fields"));
+        assert_eq!(intervals("0 6 * * 1").unwrap(), ["Weekday=1 Hour=6 Minute=0"]);
+    }
+
+    #[test]
+    fn test_calendar_intervals_are_limited() {
+        // Every minute of an hour leaves the minute out
+        assert_eq!(intervals("* 0-11 * * *").unwrap().len(), 12);
+        assert_eq!(intervals("*/2 */2 * * 1-4").unwrap().len(), MAX_CALENDAR_INTERVALS);
+        let error = intervals("*/2 */2 * * 1-5").unwrap_err();
+        assert!(error.to_string().contains("needs 1800 calendar intervals"));
+    }
+
+    #[test]
+    fn test_calendar_interval_plist() {
+        let scheduler = scheduler();
+        let single = scheduler.calendar_interval_plist(&scheduler.cron_to_calendar_intervals("30 9 * * *").unwrap());
+        assert_eq!(
+            single,
+            "    <key>StartCalendarInterval</key>\n    <dict>\n        <key>Hour</key>\n        <integer>9</integer>\n        \
+             <key>Minute</key>\n        <integer>30</integer>\n    </dict>\n"
+        );
+        let several = scheduler.calendar_interval_plist(&scheduler.cron_to_calendar_intervals("0 9,17 * * *").unwrap());
+        assert_eq!(
+            several,
+            "    <key>StartCalendarInterval</key>\n    <array>\n        <dict>\n            <key>Hour</key>\n            \
+             <integer>9</integer>\n            <key>Minute</key>\n            <integer>0</integer>\n        </dict>\n        \
+             <dict>\n            <key>Hour</key>\n            <integer>17</integer>\n            <key>Minute</key>\n            \
+             <integer>0</integer>\n        </dict>\n    </array>\n"
+        );
+    }
+
+    #[test]
+    fn test_interval_to_cron() {
+        assert_eq!(interval_to_cron(300).as_deref(), Some("*/5 * * * *"));
+        assert_eq!(interval_to_cron(3600).as_deref(), Some("0 * * * *"));
+        assert_eq!(interval_to_cron(21600).as_deref(), Some("0 */6 * * *"));
+        assert_eq!(interval_to_cron(86400).as_deref(), Some("0 0 * * *"));
+        assert_eq!(interval_to_cron(90), None);
+        assert_eq!(interval_to_cron(420), None);
+    }
+
+    #[test]
+    fn test_parse_foreign_plist() {
+        let scheduler = scheduler();
+        let plist = r#"<dict>
+    <key>Label</key>
+    <string>org.example.sync</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>/usr/local/bin/sync</string>
+        <string>--to</string>
+        <string>my backups</string>
+    </array>
+    <key>StartCalendarInterval</key>
+    <array>
+        <dict>
+            <key>Hour</key>
+            <integer>17</integer>
+            <key>Minute</key>
+            <integer>30</integer>
+        </dict>
+        <dict>
+            <key>Hour</key>
+            <integer>9</integer>
+            <key>Minute</key>
+            <integer>30</integer>
+        </dict>
+    </array>
+    <key>WorkingDirectory</key>
+    <string>/tmp</string>
+</dict>"#;
+        let entry = scheduler.parse_foreign_plist("org.example.sync", plist).unwrap();
+        assert_eq!(entry.schedule, "30 9,17 * * *");
+        assert_eq!(entry.command, "/usr/local/bin/sync --to 'my backups'");
+        assert_eq!(entry.cwd, Some(PathBuf::from("/tmp")));
+
+        let shell = plist.replace("<string>/usr/local/bin/sync</string>", "<string>/bin/sh</string>")
+            .replace("<string>--to</string>", "<string>-c</string>");
+        assert_eq!(scheduler.parse_foreign_plist("x", &shell).unwrap().command, "my backups");
+
+        // Differing in both the hour and the minute isn't one schedule
+        let uneven = plist.replace("<integer>30</integer>\n        </dict>\n    </array>", "<integer>0</integer>\n        </dict>\n    </array>");
+        assert!(scheduler.parse_foreign_plist("x", &uneven).is_err());
+
+        let watch = "<dict><key>ProgramArguments</key><array><string>true</string></array>\
+                     <key>WatchPaths</key><array><string>/tmp</string></array></dict>";
+        assert!(scheduler.parse_foreign_plist("x", watch).is_err());
     }
 }
@@ -0,0 +1,104 @@
+use crate::cron_entry::CronEntry;
+use crate::scheduler::{SaveReport, Scheduler};
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// In-memory scheduler for tests: serves injected entries, records every
+/// save and fails on request. Clones share their state, so a test can keep
+/// one while `Storage` owns another.
+#[derive(Debug, Clone, Default)]
+pub struct MockScheduler {
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    entries: Vec<CronEntry>,
+    saves: Vec<Vec<CronEntry>>,
+    load_error: Option<String>,
+    save_error: Option<String>,
+    /// Entry names the mock refuses to register, with the reason
+    rejected: HashMap<String, String>,
+}
+
+impl MockScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A mock whose `load` returns these entries
+    pub fn with_entries(entries: Vec<CronEntry>) -> Self {
+        let mock = Self::default();
+        mock.state().entries = entries;
+        mock
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        // A panicking test may poison the lock; the data is still usable
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Replace the entries behind the app's back, like another user editing
+    /// the backend
+    pub fn set_entries(&self, entries: Vec<CronEntry>) {
+        self.state().entries = entries;
+    }
+
+    /// Make every `load` fail with this message
+    pub fn fail_load(&self, message: &str) {
+        self.state().load_error = Some(message.to_string());
+    }
+
+    /// Make every `save` fail with this message, leaving the entries untouched
+    pub fn fail_save(&self, message: &str) {
+        self.state().save_error = Some(message.to_string());
+    }
+
+    /// Report the named entry as not registered on every save, like a
+    /// launchd agent that fails to load
+    pub fn reject_entry(&self, name: &str, reason: &str) {
+        self.state().rejected.insert(name.to_string(), reason.to_string());
+    }
+
+    /// Entries as of the last successful save (or as injected)
+    pub fn entries(&self) -> Vec<CronEntry> {
+        self.state().entries.clone()
+    }
+
+    /// The entries passed to each successful save, oldest first
+    pub fn saves(&self) -> Vec<Vec<CronEntry>> {
+        self.state().saves.clone()
+    }
+}
+
+impl Scheduler for MockScheduler {
+    fn load(&self) -> Result<Vec<CronEntry>> {
+        let state = self.state();
+        if let Some(message) = &state.load_error {
+            bail!("{}", message);
+        }
+        Ok(state.entries.clone())
+    }
+
+    fn save(&self, entries: &[CronEntry]) -> Result<SaveReport> {
+        let mut state = self.state();
+        if let Some(message) = &state.save_error {
+            bail!("{}", message);
+        }
+        let mut report = SaveReport::default();
+        for (index, entry) in entries.iter().enumerate() {
+            match state.rejected.get(&entry.name) {
+                Some(reason) if entry.enabled => report.failed.push((index, reason.clone())),
+                _ => report.applied.push(index),
+            }
+        }
+        state.entries = entries.to_vec();
+        state.saves.push(entries.to_vec());
+        Ok(report)
+    }
+
+    fn backend_name(&self) -> &'static str {
+        "Mock"
+    }
+}
@@ -1,16 +1,260 @@
 use crate::cron_entry::CronEntry;
-use anyhow::Result;
+use crate::cron_parser::CronParser;
+use crate::run_history::RunRecord;
+use chrono::{DateTime, Utc};
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
 
-/// Trait for different scheduler backends (cron, launchd, etc.)
+/// Trait for different scheduler backends (cron, launchd, systemd, etc.)
 pub trait Scheduler: Send + Sync {
     /// Load all scheduled entries from the scheduler
     fn load(&self) -> Result<Vec<CronEntry>>;
 
-    /// Save all scheduled entries to the scheduler
-    fn save(&self, entries: &[CronEntry]) -> Result<()>;
+    /// Save all scheduled entries to the scheduler. Errors mean nothing
+    /// could be saved; entries the backend rejected one by one are listed in
+    /// the report instead.
+    fn save(&self, entries: &[CronEntry]) -> Result<SaveReport>;
 
     /// Get a human-readable name for this scheduler backend
     fn backend_name(&self) -> &'static str;
+
+    /// Add one entry after the others. By default the whole schedule is
+    /// saved again; backends that register entries one by one override it
+    /// to leave the rest alone. An entry the backend refuses is a
+    /// [`Rejected`] error.
+    fn add(&self, entry: &CronEntry) -> Result<()> {
+        let mut entries = self.load()?;
+        entries.push(entry.clone());
+        let report = self.save(&entries)?;
+        report.check(&entries, entries.len() - 1)
+    }
+
+    /// Replace the entry named like `old` with `new`, in place
+    fn update(&self, old: &CronEntry, new: &CronEntry) -> Result<()> {
+        let mut entries = self.load()?;
+        let index = entries
+            .iter()
+            .position(|e| e.name == old.name)
+            .ok_or_else(|| anyhow!("No entry named '{}'", old.name))?;
+        entries[index] = new.clone();
+        let report = self.save(&entries)?;
+        report.check(&entries, index)
+    }
+
+    /// Remove the entry named like `entry`
+    fn remove(&self, entry: &CronEntry) -> Result<()> {
+        let mut entries = self.load()?;
+        let count = entries.len();
+        entries.retain(|e| e.name != entry.name);
+        if entries.len() == count {
+            return Err(anyhow!("No entry named '{}'", entry.name));
+        }
+        self.save(&entries).map(|_| ())
+    }
+
+    /// Check that a save can go through, e.g. that this user may write the
+    /// files involved, so it fails with a clear message before anything changes
+    fn preflight(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Why what the backend holds looks damaged, e.g. cut off by a crash
+    /// mid-write, so a backup can be offered before it is saved over
+    fn damage(&self) -> Option<String> {
+        None
+    }
+
+    /// A value that changes whenever what the backend holds does, such as a
+    /// file's modification time, so outside changes can be noticed without
+    /// loading every entry; `None` when there is nothing cheaper than `load`
+    fn fingerprint(&self) -> Option<u64> {
+        None
+    }
+
+    /// The line that sets a variable, like `MAILTO=""`, between two entries
+    /// in what the backend holds, so swapping them would change the
+    /// environment each runs with
+    fn environment_between(&self, _first: &CronEntry, _second: &CronEntry) -> Option<String> {
+        None
+    }
+
+    /// Check whether this backend can represent the given cron schedule
+    fn check_schedule(&self, _schedule: &str) -> Result<()> {
+        Ok(())
+    }
+
+    /// Where the entry's output ends up, for the log viewer
+    fn log_path(&self, entry: &CronEntry) -> Option<PathBuf> {
+        entry.output_path()
+    }
+
+    /// Whether disabled entries survive a save (and so can be snoozed)
+    fn keeps_disabled_entries(&self) -> bool {
+        true
+    }
+
+    /// Whether the backend sets an entry's own variables and working directory
+    /// itself, rather than having them put before the command
+    fn sets_environment(&self) -> bool {
+        false
+    }
+
+    /// Whether the backend writes commands' output to a log file of its own,
+    /// so it needn't be captured
+    fn keeps_output(&self) -> bool {
+        false
+    }
+
+    /// Whether the host has `timeout(1)` to stop commands that run too long;
+    /// otherwise `cron-manager exec` enforces entries' timeouts
+    fn has_timeout_command(&self) -> bool {
+        true
+    }
+
+    /// Whether the backend takes entries as edited and resolves placeholders,
+    /// hooks and watchdogs itself, like an agent on another host
+    fn resolves_commands(&self) -> bool {
+        false
+    }
+
+    /// Run records kept on the backend's host, when that isn't this machine
+    fn remote_runs(&self, _since: DateTime<Utc>) -> Option<Result<Vec<RunRecord>>> {
+        None
+    }
+
+    /// What saving these entries would write, for `--dry-run`: the crontab
+    /// text, or each generated file under a `==> path <==` heading
+    fn render(&self, entries: &[CronEntry]) -> Result<String> {
+        Ok(CronParser::serialize(entries))
+    }
+
+    /// What the last save would have written, when saves are a dry run
+    fn dry_run_output(&self) -> Option<String> {
+        None
+    }
+}
+
+/// What a save actually registered with the backend
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaveReport {
+    /// Indices of entries saved as requested
+    pub applied: Vec<usize>,
+    /// Indices of entries the backend did not accept, with the reason
+    pub failed: Vec<(usize, String)>,
+}
+
+impl SaveReport {
+    /// Every entry saved as requested
+    pub fn all(entries: &[CronEntry]) -> Self {
+        Self { applied: (0..entries.len()).collect(), failed: Vec::new() }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// An error when the backend rejected the entry at `index`
+    fn check(&self, entries: &[CronEntry], index: usize) -> Result<()> {
+        match self.failed.iter().find(|(i, _)| *i == index) {
+            Some((_, reason)) => Err(Rejected { name: entries[index].name.clone(), reason: reason.clone() }.into()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// An entry the backend refused to register while the rest of the save went
+/// through, so callers can report it like a `SaveReport` failure
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rejected {
+    pub name: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for Rejected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "'{}' was not registered: {}", self.name, self.reason)
+    }
+}
+
+impl std::error::Error for Rejected {}
+
+/// Fail with a clear message if this user can't write `path`: the file when
+/// it exists, otherwise the directory it would be created in
+pub fn check_writable(path: &Path) -> Result<()> {
+    let (target, result) = if path.is_file() {
+        (path, OpenOptions::new().write(true).open(path).map(|_| ()))
+    } else {
+        // New files go in the nearest directory that exists
+        let dir = if path.is_dir() {
+            path
+        } else {
+            path.ancestors().skip(1).find(|p| p.is_dir()).unwrap_or(Path::new("."))
+        };
+        let probe = dir.join(format!(".cron-manager-probe-{}", std::process::id()));
+        let result = OpenOptions::new().write(true).create_new(true).open(&probe).and_then(|_| fs::remove_file(&probe));
+        (dir, result)
+    };
+    match result {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == ErrorKind::PermissionDenied => Err(anyhow!(
+            "Your user can't write {}; if it was created with sudo, `sudo chown -R $USER {}` gives it back",
+            target.display(),
+            target.display()
+        )),
+        Err(e) => Err(e).with_context(|| format!("Failed to check access to {}", target.display())),
+    }
+}
+
+/// Run an external command to completion, logging the command line, exit
+/// status and output at debug level
+pub fn run_command(command: &mut Command) -> std::io::Result<Output> {
+    let line = format!("{:?}", command);
+    let output = command.output();
+    log_output(&line, &output);
+    output
+}
+
+/// Run an external command with `input` on its stdin, so the content never
+/// has to sit in a file other users might read or replace
+pub fn run_command_with_input(command: &mut Command, input: &[u8]) -> std::io::Result<Output> {
+    let line = format!("{:?}", command);
+    let output = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .and_then(|mut child| {
+            // Written from another thread, so a command that answers before
+            // reading everything can't leave both sides waiting
+            let mut stdin = child.stdin.take().expect("stdin is piped");
+            let input = input.to_vec();
+            let writer = std::thread::spawn(move || stdin.write_all(&input));
+            let output = child.wait_with_output()?;
+            match writer.join() {
+                // The command may exit without reading it all; its status says why
+                Ok(Err(e)) if e.kind() != ErrorKind::BrokenPipe => Err(e),
+                _ => Ok(output),
+            }
+        });
+    log_output(&line, &output);
+    output
+}
+
+fn log_output(line: &str, output: &std::io::Result<Output>) {
+    match output {
+        Ok(output) => tracing::debug!(
+            command = %line,
+            status = %output.status,
+            stdout = %String::from_utf8_lossy(&output.stdout),
+            stderr = %String::from_utf8_lossy(&output.stderr),
+            "ran external command"
+        ),
+        Err(e) => tracing::warn!(command = %line, error = %e, "failed to run external command"),
+    }
 }
 
 /// Auto-detect and create the appropriate scheduler for the current OS
@@ -34,8 +278,59 @@ pub fn create_scheduler(use_system: bool) -> Box<dyn Scheduler> {
     }
 }
 
+/// The scheduler for root's schedule (`--root`): root's crontab, through
+/// `sudo` when credentials are cached, or LaunchDaemons on macOS, which
+/// needs the process itself to run as root
+pub fn create_root_scheduler() -> Result<Box<dyn Scheduler>> {
+    use crate::privilege::{self, Privilege};
+
+    #[cfg(target_os = "macos")]
+    {
+        if !privilege::is_root() {
+            anyhow::bail!("Managing /Library/LaunchDaemons needs root; run cron-manager --root with sudo");
+        }
+        Ok(Box::new(crate::scheduler::launchd::LaunchdScheduler::system()))
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        match privilege::current() {
+            Privilege::Root => Ok(Box::new(crate::scheduler::cron::CronScheduler::root(false))),
+            Privilege::Sudo => Ok(Box::new(crate::scheduler::cron::CronScheduler::root(true))),
+            Privilege::Unprivileged => {
+                anyhow::bail!("Managing root's crontab needs root or sudo; run cron-manager --root with sudo")
+            }
+        }
+    }
+}
+
+pub mod agent;
+pub mod dry_run;
 pub mod file;
 pub mod cron;
 
+#[cfg(any(test, feature = "test-util"))]
+pub mod mock;
+
 #[cfg(target_os = "macos")]
 pub mod launchd;
+
+#[cfg(target_os = "linux")]
+pub mod systemd;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_command_with_input() {
+        // More than a pipe buffer both ways
+        let input = "0 * * * * /bin/true\n".repeat(10_000);
+        let output = run_command_with_input(&mut Command::new("cat"), input.as_bytes()).unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, input.as_bytes());
+
+        let output = run_command_with_input(&mut Command::new("true"), input.as_bytes()).unwrap();
+        assert!(output.status.success());
+    }
+}
@@ -0,0 +1,488 @@
+use crate::cron_entry::{CronEntry, METADATA_KEYS};
+use crate::schedule::{compress_values, CronSchedule, FieldKind, FieldSet};
+use crate::scheduler::{check_writable, run_command, SaveReport, Scheduler};
+use anyhow::{bail, Context, Result};
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// Constants
+const UNIT_PREFIX: &str = "cron-manager-";
+const LOG_PATH_PREFIX: &str = "/tmp";
+/// Here-document delimiter for an entry's stdin text
+const STDIN_DELIMITER: &str = "CRON_MANAGER_STDIN";
+/// Prefix of the `ExecStart=` line of a generated service
+const EXEC_PREFIX: &str = "ExecStart=/bin/sh -c ";
+/// Weekday names as `OnCalendar=` writes them, Sunday first like cron
+const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+/// systemd user timers: a `.timer`/`.service` unit pair per entry
+pub struct SystemdScheduler {
+    unit_dir: PathBuf,
+}
+
+impl SystemdScheduler {
+    pub fn new() -> Self {
+        // User units live in $XDG_CONFIG_HOME/systemd/user (~/.config/systemd/user)
+        let config_dir = dirs::config_dir()
+            .unwrap_or_else(|| dirs::home_dir().unwrap_or_else(|| PathBuf::from(".")).join(".config"));
+        Self { unit_dir: config_dir.join("systemd/user") }
+    }
+
+    fn ensure_unit_dir(&self) -> Result<()> {
+        if !self.unit_dir.exists() {
+            fs::create_dir_all(&self.unit_dir)
+                .with_context(|| format!("Failed to create systemd unit directory: {:?}", self.unit_dir))?;
+        }
+        Ok(())
+    }
+
+    fn entry_to_unit(&self, entry: &CronEntry) -> String {
+        // A hash of the name keeps "My Task" and "My/Task" apart
+        let mut hasher = DefaultHasher::new();
+        entry.name.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        // Unit names only allow a few characters, so keep a readable slug
+        let safe_name: String = entry.name
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+            .take(32)
+            .collect::<String>()
+            .to_ascii_lowercase();
+
+        format!("{}{}-{:x}", UNIT_PREFIX, safe_name, hash)
+    }
+
+    fn unit_path(&self, unit: &str, suffix: &str) -> PathBuf {
+        self.unit_dir.join(format!("{}.{}", unit, suffix))
+    }
+
+    fn log_file(&self, unit: &str) -> PathBuf {
+        PathBuf::from(format!("{}/{}.log", LOG_PATH_PREFIX, unit))
+    }
+
+    fn create_timer(&self, entry: &CronEntry, unit: &str) -> Result<String> {
+        let calendars = on_calendar(&entry.schedule, entry.timezone.as_deref())?;
+
+        // The entry in `X-` keys, which systemd ignores: the same metadata
+        // a crontab keeps in comments, under `X-CronManager-<KEY>`
+        let mut metadata = format!(
+            "X-CronManager-NAME={}\nX-CronManager-SCHEDULE={}\n",
+            quote(&entry.name),
+            quote(&entry.schedule)
+        );
+        if !entry.enabled {
+            metadata.push_str("X-CronManager-ENABLED=no\n");
+        }
+        for (key, value) in entry.metadata() {
+            metadata.push_str(&format!("X-CronManager-{}={}\n", key, quote(&value)));
+        }
+        if !entry.comments.is_empty() {
+            metadata.push_str(&format!("X-CronManager-COMMENTS={}\n", quote(&entry.comments.join("\n"))));
+        }
+
+        let mut calendars: String = calendars.iter().map(|c| format!("OnCalendar={}\n", c)).collect();
+        if calendars.is_empty() {
+            calendars.push_str("OnStartupSec=0\n");
+        }
+
+        Ok(format!(
+            "# Generated by cron-manager; changes are overwritten on the next save\n\
+             [Unit]\n\
+             Description=cron-manager: {}\n\
+             {}\n\
+             [Timer]\n\
+             {}\
+             Unit={}.service\n\
+             \n\
+             [Install]\n\
+             WantedBy=timers.target\n",
+            entry.name.replace('%', "%%"),
+            metadata,
+            calendars,
+            unit
+        ))
+    }
+
+    fn create_service(&self, entry: &CronEntry, unit: &str) -> String {
+        // Like launchd, stdin text goes to the command through a here-document
+        let command = match &entry.stdin {
+            Some(input) => format!("{{ {}\n}} <<'{}'\n{}\n{}", entry.command, STDIN_DELIMITER, input, STDIN_DELIMITER),
+            None => entry.command.clone(),
+        };
+        let log = self.log_file(unit);
+
+        format!(
+            "# Generated by cron-manager; changes are overwritten on the next save\n\
+             [Unit]\n\
+             Description=cron-manager: {}\n\
+             \n\
+             [Service]\n\
+             Type=oneshot\n\
+             {}{}\n\
+             StandardOutput=append:{}\n\
+             StandardError=append:{}\n",
+            entry.name.replace('%', "%%"),
+            EXEC_PREFIX,
+            quote(&command),
+            log.display(),
+            log.display()
+        )
+    }
+
+    /// Run `systemctl --user` with the given arguments
+    fn systemctl(&self, args: &[&str]) -> Result<()> {
+        let output = run_command(Command::new("systemctl").arg("--user").args(args))
+            .context("Failed to execute systemctl")?;
+
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            bail!("systemctl --user {} failed: {}", args.join(" "), error.trim());
+        }
+        Ok(())
+    }
+
+    /// Managed unit names (without suffix), from their timer files
+    fn list_units(&self) -> Result<Vec<String>> {
+        let mut units = Vec::new();
+
+        if !self.unit_dir.exists() {
+            return Ok(units);
+        }
+
+        for entry in fs::read_dir(&self.unit_dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|s| s.to_str()) == Some("timer") {
+                if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                    if stem.starts_with(UNIT_PREFIX) {
+                        units.push(stem.to_string());
+                    }
+                }
+            }
+        }
+
+        units.sort();
+        Ok(units)
+    }
+
+    fn parse_units(&self, unit: &str) -> Result<CronEntry> {
+        let timer = fs::read_to_string(self.unit_path(unit, "timer"))?;
+        let service = fs::read_to_string(self.unit_path(unit, "service"))?;
+
+        let name = unit_value(&timer, "X-CronManager-NAME").unwrap_or_else(|| unit.to_string());
+        let schedule = unit_value(&timer, "X-CronManager-SCHEDULE").unwrap_or_else(|| "0 0 * * *".to_string());
+        let command = service
+            .lines()
+            .find_map(|line| line.strip_prefix(EXEC_PREFIX))
+            .map(unquote)
+            .unwrap_or_default();
+
+        let mut entry = CronEntry::new(name, schedule, command);
+        if let Some((command, input)) = split_stdin(&entry.command) {
+            entry.command = command;
+            entry.stdin = Some(input);
+        }
+        entry.enabled = unit_value(&timer, "X-CronManager-ENABLED").as_deref() != Some("no");
+        for key in METADATA_KEYS {
+            if let Some(value) = unit_value(&timer, &format!("X-CronManager-{}", key)) {
+                entry.set_metadata(key, &value);
+            }
+        }
+        entry.comments = unit_value(&timer, "X-CronManager-COMMENTS")
+            .map(|comments| comments.lines().map(str::to_string).collect())
+            .unwrap_or_default();
+
+        Ok(entry)
+    }
+
+    /// Write the entry's timer and service files
+    fn write_units(&self, entry: &CronEntry, unit: &str) -> Result<()> {
+        let timer = self.create_timer(entry, unit)?;
+        write_unit(&self.unit_path(unit, "service"), &self.create_service(entry, unit))?;
+        write_unit(&self.unit_path(unit, "timer"), &timer)
+    }
+}
+
+impl Default for SystemdScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scheduler for SystemdScheduler {
+    fn load(&self) -> Result<Vec<CronEntry>> {
+        Ok(self
+            .list_units()?
+            .iter()
+            .filter_map(|unit| self.parse_units(unit).ok())
+            .collect())
+    }
+
+    /// The save removes every managed unit before writing new ones, so a
+    /// directory it can't write would leave the timers half replaced
+    fn preflight(&self) -> Result<()> {
+        check_writable(&self.unit_dir)
+    }
+
+    fn save(&self, entries: &[CronEntry]) -> Result<SaveReport> {
+        self.ensure_unit_dir()?;
+
+        // Stop the existing timers before their files go, so no enable
+        // symlinks are left pointing at removed units
+        let existing = self.list_units()?;
+        if !existing.is_empty() {
+            let timers: Vec<String> = existing.iter().map(|unit| format!("{}.timer", unit)).collect();
+            let mut args = vec!["disable", "--now"];
+            args.extend(timers.iter().map(String::as_str));
+            // Timers that were never enabled or loaded are fine to skip
+            if let Err(e) = self.systemctl(&args) {
+                tracing::debug!(error = %format!("{:#}", e), "timers not disabled");
+            }
+        }
+        for unit in &existing {
+            for suffix in ["timer", "service"] {
+                let path = self.unit_path(unit, suffix);
+                if path.exists() {
+                    fs::remove_file(&path).with_context(|| format!("Failed to remove unit: {:?}", path))?;
+                }
+            }
+        }
+
+        // Disabled entries keep their units, so they can be enabled again.
+        // One entry failing doesn't stop the rest from being registered.
+        let mut report = SaveReport::default();
+        let mut written = Vec::new();
+        for (index, entry) in entries.iter().enumerate() {
+            let unit = self.entry_to_unit(entry);
+            match self.write_units(entry, &unit) {
+                Ok(()) => written.push((index, unit)),
+                Err(e) => report.failed.push((index, format!("{:#}", e))),
+            }
+        }
+
+        let reloaded = self.systemctl(&["daemon-reload"]);
+        for (index, unit) in written {
+            let entry = &entries[index];
+            let result = match &reloaded {
+                Ok(()) if entry.enabled => self.systemctl(&["enable", "--now", &format!("{}.timer", unit)]),
+                Ok(()) => Ok(()),
+                Err(e) => Err(anyhow::anyhow!("{:#}", e)),
+            };
+            match result {
+                Ok(()) => report.applied.push(index),
+                Err(e) => {
+                    tracing::warn!(entry = %entry.name, error = %format!("{:#}", e), "timer not registered");
+                    report.failed.push((index, format!("{:#}", e)));
+                }
+            }
+        }
+        report.failed.sort_by_key(|(index, _)| *index);
+
+        Ok(report)
+    }
+
+    /// Every entry's service and timer; the existing units are replaced
+    fn render(&self, entries: &[CronEntry]) -> Result<String> {
+        let mut output = String::new();
+        for entry in entries {
+            let unit = self.entry_to_unit(entry);
+            let (service, timer) = (self.unit_path(&unit, "service"), self.unit_path(&unit, "timer"));
+            output.push_str(&format!("==> {} <==\n{}\n", service.display(), self.create_service(entry, &unit)));
+            match self.create_timer(entry, &unit) {
+                Ok(content) => output.push_str(&format!("==> {} <==\n{}\n", timer.display(), content)),
+                Err(e) => output.push_str(&format!("==> {} <==\n(not written: {:#})\n\n", timer.display(), e)),
+            }
+        }
+        Ok(output)
+    }
+
+    fn log_path(&self, entry: &CronEntry) -> Option<PathBuf> {
+        // Commands can still redirect output themselves; otherwise the
+        // service appends it to its log file
+        entry.output_path().or_else(|| Some(self.log_file(&self.entry_to_unit(entry))))
+    }
+
+    fn backend_name(&self) -> &'static str {
+        "Systemd"
+    }
+
+    fn keeps_output(&self) -> bool {
+        // StandardOutput=append: in the service
+        true
+    }
+
+    fn check_schedule(&self, schedule: &str) -> Result<()> {
+        on_calendar(schedule, None).map(|_| ())
+    }
+}
+
+/// The `OnCalendar=` expressions for a cron schedule. Cron runs a job on
+/// either the day of month or the weekday when both are restricted, while
+/// one expression needs both, so that case takes two. `@reboot` has none;
+/// its timer fires when the user's service manager starts instead.
+pub fn on_calendar(schedule: &str, timezone: Option<&str>) -> Result<Vec<String>> {
+    let cron = CronSchedule::parse(schedule)
+        .with_context(|| format!("Invalid cron expression: {}", schedule))?;
+    if cron.reboot {
+        return Ok(Vec::new());
+    }
+
+    let time = format!(
+        "{}:{}:00",
+        calendar_values(&cron.hours, FieldKind::Hour),
+        calendar_values(&cron.minutes, FieldKind::Minute)
+    );
+    let months = calendar_values(&cron.months, FieldKind::Month);
+    let days = calendar_values(&cron.days_of_month, FieldKind::DayOfMonth);
+    let weekdays = calendar_values(&cron.days_of_week, FieldKind::DayOfWeek);
+    let zone = timezone.map(|tz| format!(" {}", tz)).unwrap_or_default();
+
+    let calendar = |weekdays: &str, days: &str| {
+        let weekdays = if weekdays == "*" { String::new() } else { format!("{} ", weekdays) };
+        format!("{}*-{}-{} {}{}", weekdays, months, days, time, zone)
+    };
+
+    if cron.days_of_month.is_star() || cron.days_of_week.is_star() {
+        Ok(vec![calendar(&weekdays, &days)])
+    } else {
+        Ok(vec![calendar("*", &days), calendar(&weekdays, "*")])
+    }
+}
+
+/// A cron field in `OnCalendar=` syntax: `*`, or values and `a..b` ranges
+fn calendar_values(field: &FieldSet, kind: FieldKind) -> String {
+    let (min, max) = kind.bounds();
+    // Cron allows 7 for Sunday too; the parsed set only has 0-6
+    let max = if kind == FieldKind::DayOfWeek { 6 } else { max };
+    if field.len() as u32 == max - min + 1 {
+        return "*".to_string();
+    }
+
+    let value = |text: &str| {
+        let value: usize = text.parse().unwrap_or_default();
+        match kind {
+            FieldKind::DayOfWeek => WEEKDAYS[value % 7].to_string(),
+            _ => format!("{:02}", value),
+        }
+    };
+    compress_values(&field.values())
+        .split(',')
+        .map(|part| part.split('-').map(value).collect::<Vec<_>>().join(".."))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Quote a value for a unit file: a double-quoted string on one line, with
+/// `%` specifiers and `$` variables kept literal
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '%' => quoted.push_str("%%"),
+            '$' => quoted.push_str("$$"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Undo [`quote`]
+fn unquote(value: &str) -> String {
+    let inner = value.trim().strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(value);
+    let mut output = String::with_capacity(inner.len());
+    let mut chars = inner.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => output.push('\n'),
+                Some(other) => output.push(other),
+                None => output.push('\\'),
+            },
+            '%' | '$' if chars.peek() == Some(&c) => {
+                chars.next();
+                output.push(c);
+            }
+            c => output.push(c),
+        }
+    }
+    output
+}
+
+/// The unquoted value of a `Key=` line
+fn unit_value(content: &str, key: &str) -> Option<String> {
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+        .map(unquote)
+}
+
+/// The command and stdin text of a command written with a here-document
+fn split_stdin(command: &str) -> Option<(String, String)> {
+    let wrapped = command.strip_prefix("{ ")?;
+    let (command, rest) = wrapped.split_once(&format!("\n}} <<'{}'\n", STDIN_DELIMITER))?;
+    let input = rest.strip_suffix(&format!("\n{}", STDIN_DELIMITER))?;
+    Some((command.to_string(), input.to_string()))
+}
+
+fn write_unit(path: &Path, content: &str) -> Result<()> {
+    fs::write(path, content).with_context(|| format!("Failed to write unit: {:?}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shell::Shell;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn test_on_calendar() {
+        assert_eq!(on_calendar("0 3 * * *", None).unwrap(), ["*-*-* 03:00:00"]);
+        assert_eq!(
+            on_calendar("*/15 9-17 * * MON-FRI", Some("Asia/Tokyo")).unwrap(),
+            ["Mon..Fri *-*-* 09..17:00,15,30,45:00 Asia/Tokyo"]
+        );
+        assert_eq!(on_calendar("30 6 1,15 JAN,JUL *", None).unwrap(), ["*-01,07-01,15 06:30:00"]);
+        assert_eq!(on_calendar("0 0 * * 0,7", None).unwrap(), ["Sun *-*-* 00:00:00"]);
+        // Cron ORs a restricted day of month and weekday
+        assert_eq!(
+            on_calendar("0 12 1 * 5", None).unwrap(),
+            ["*-*-01 12:00:00", "Fri *-*-* 12:00:00"]
+        );
+        assert_eq!(on_calendar("@hourly", None).unwrap(), ["*-*-* *:00:00"]);
+        assert!(on_calendar("@reboot", None).unwrap().is_empty());
+        assert!(on_calendar("@never", None).is_err());
+    }
+
+    #[test]
+    fn test_units_round_trip() {
+        let dir = TempDir::new("systemd");
+        let scheduler = SystemdScheduler { unit_dir: dir.path().to_path_buf() };
+
+        let mut entry = CronEntry::new(
+            "Backup 100%".to_string(),
+            "0 3 * * *".to_string(),
+            r#"tar czf "$HOME/b-$(date +%F).tgz" ~/docs \ && echo done"#.to_string(),
+        );
+        entry.enabled = false;
+        entry.stdin = Some("line one\nline two".to_string());
+        entry.tags = vec!["backup".to_string(), "nightly".to_string()];
+        entry.comments = vec!["Keep a week".to_string(), "of archives".to_string()];
+        entry.shell = Some(Shell::Pwsh);
+        entry.on_failure = Some("notify-send failed".to_string());
+        entry.skip_excluded = true;
+        let unit = scheduler.entry_to_unit(&entry);
+        scheduler.write_units(&entry, &unit).unwrap();
+
+        let service = fs::read_to_string(scheduler.unit_path(&unit, "service")).unwrap();
+        assert!(service.contains("$$HOME") && service.contains("%%F"));
+        assert_eq!(scheduler.load().unwrap(), vec![entry]);
+    }
+}
@@ -0,0 +1,177 @@
+use crate::secrets::shell_quote;
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Placeholder in commands replaced by a managed script's path when saving
+pub const PLACEHOLDER: &str = "!script ";
+
+/// Directory holding the scripts, inside the data directory
+const SCRIPTS_DIR: &str = "scripts";
+
+/// Content of a newly created script
+const TEMPLATE: &str = "#!/bin/sh\nset -eu\n\n";
+
+/// Small scripts kept by cron-manager, so entries can run them by name from
+/// a stable path instead of scattering them around `$HOME`
+#[derive(Debug, Clone)]
+pub struct ScriptLibrary {
+    dir: PathBuf,
+}
+
+impl ScriptLibrary {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// `scripts` under the data directory
+    pub fn default_dir(data_dir: &Path) -> PathBuf {
+        data_dir.join(SCRIPTS_DIR)
+    }
+
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+
+    /// Names of all scripts, sorted
+    pub fn list(&self) -> Result<Vec<String>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut names: Vec<String> = fs::read_dir(&self.dir)
+            .with_context(|| format!("Failed to read script directory: {:?}", self.dir))?
+            .filter_map(|item| {
+                let item = item.ok()?;
+                let name = item.file_name().into_string().ok()?;
+                (item.path().is_file() && validate_name(&name).is_ok()).then_some(name)
+            })
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    /// Create an executable script from the template and return its path
+    pub fn create(&self, name: &str) -> Result<PathBuf> {
+        validate_name(name)?;
+        let path = self.path(name);
+        if path.exists() {
+            bail!("Script '{}' already exists", name);
+        }
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create script directory: {:?}", self.dir))?;
+        fs::write(&path, TEMPLATE).with_context(|| format!("Failed to create script: {:?}", path))?;
+        make_executable(&path)?;
+        Ok(path)
+    }
+
+    pub fn read(&self, name: &str) -> Result<String> {
+        let path = self.path(name);
+        fs::read_to_string(&path).with_context(|| format!("Failed to read script: {:?}", path))
+    }
+
+    /// Write an executable script with the given content, replacing one of
+    /// the same name (used by `restore`)
+    pub fn write(&self, name: &str, content: &str) -> Result<PathBuf> {
+        validate_name(name)?;
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create script directory: {:?}", self.dir))?;
+        let path = self.path(name);
+        fs::write(&path, content).with_context(|| format!("Failed to write script: {:?}", path))?;
+        make_executable(&path)?;
+        Ok(path)
+    }
+
+    pub fn delete(&self, name: &str) -> Result<()> {
+        let path = self.path(name);
+        fs::remove_file(&path).with_context(|| format!("Failed to delete script: {:?}", path))
+    }
+
+    /// Replace every `!script NAME` in the command with the script's path
+    pub fn resolve(&self, command: &str) -> Result<String> {
+        let mut output = String::with_capacity(command.len());
+        let mut rest = command;
+        while let Some(index) = rest.find(PLACEHOLDER) {
+            output.push_str(&rest[..index]);
+            let after = &rest[index + PLACEHOLDER.len()..];
+            let end = after.find(|c: char| !is_name_char(c)).unwrap_or(after.len());
+            let name = &after[..end];
+            if name.is_empty() {
+                bail!("Missing script name after '{}'", PLACEHOLDER.trim());
+            }
+            let path = self.path(name);
+            if !path.is_file() {
+                bail!("Script '{}' not found in {}", name, self.dir.display());
+            }
+            output.push_str(&shell_quote(&path.to_string_lossy()));
+            rest = &after[end..];
+        }
+        output.push_str(rest);
+        Ok(output)
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')
+}
+
+/// Script names are plain file names: letters, digits, `_`, `-` and `.`,
+/// not starting with a dot
+pub fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.starts_with('.') || !name.chars().all(is_name_char) {
+        bail!("Script names may only use letters, digits, '_', '-' and '.', and can't start with '.'");
+    }
+    Ok(())
+}
+
+/// Whether the command runs the named script
+pub fn references(command: &str, name: &str) -> bool {
+    command.match_indices(PLACEHOLDER).any(|(index, _)| {
+        let after = &command[index + PLACEHOLDER.len()..];
+        after.strip_prefix(name).is_some_and(|rest| !rest.starts_with(is_name_char))
+    })
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+        .with_context(|| format!("Failed to make script executable: {:?}", path))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn test_create_resolve_delete() {
+        let dir = TempDir::new("scripts");
+        let library = ScriptLibrary::new(dir.join("scripts"));
+
+        let path = library.create("rotate-logs.sh").unwrap();
+        assert!(library.create("rotate-logs.sh").is_err());
+        assert!(library.create("../escape").is_err());
+        assert_eq!(library.list().unwrap(), ["rotate-logs.sh"]);
+
+        assert_eq!(
+            library.resolve("!script rotate-logs.sh --days 7 > /dev/null").unwrap(),
+            format!("{} --days 7 > /dev/null", path.display())
+        );
+        assert!(library.resolve("!script missing").is_err());
+
+        library.delete("rotate-logs.sh").unwrap();
+        assert!(library.list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_references() {
+        assert!(references("!script backup --full", "backup"));
+        assert!(!references("!script backup.sh", "backup"));
+        assert!(!references("/bin/backup", "backup"));
+    }
+}
@@ -0,0 +1,153 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Extensions that end the program path of a Windows command
+const WINDOWS_EXTENSIONS: &[&str] = &[".exe", ".bat", ".cmd", ".ps1", ".com"];
+
+/// The interpreter an entry's command is written for. Entries without one
+/// are POSIX shell commands, which is what cron and launchd run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Shell {
+    /// Windows PowerShell (`powershell.exe`, e.g. from WSL)
+    PowerShell,
+    /// PowerShell 7 (`pwsh`), on any OS
+    Pwsh,
+    /// The Windows command prompt (`cmd.exe`, e.g. from WSL)
+    Cmd,
+}
+
+impl Shell {
+    /// Parse the shell prompt, `# SHELL:` metadata or a macro step; `sh` and
+    /// an empty value mean the default POSIX shell
+    pub fn parse(input: &str) -> Result<Option<Self>> {
+        Ok(match input.trim().to_ascii_lowercase().as_str() {
+            "" | "sh" => None,
+            "powershell" => Some(Self::PowerShell),
+            "pwsh" => Some(Self::Pwsh),
+            "cmd" => Some(Self::Cmd),
+            _ => bail!("Unknown shell '{}' (use sh, powershell, pwsh or cmd)", input.trim()),
+        })
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::PowerShell => "powershell",
+            Self::Pwsh => "pwsh",
+            Self::Cmd => "cmd",
+        }
+    }
+
+    /// Whether commands use Windows paths (`C:\...`)
+    pub fn is_windows(self) -> bool {
+        self != Self::Pwsh
+    }
+
+    /// The command line the POSIX shell of the scheduler runs: the
+    /// interpreter, with the command as a single argument
+    pub fn wrap(self, command: &str) -> String {
+        let command = sh_quote(command);
+        match self {
+            Self::PowerShell => format!("powershell.exe -NoProfile -NonInteractive -Command {}", command),
+            Self::Pwsh => format!("pwsh -NoProfile -NonInteractive -Command {}", command),
+            Self::Cmd => format!("cmd.exe /C {}", command),
+        }
+    }
+
+    /// A program path quoted so this shell runs it, spaces and all
+    pub fn quote_path(self, path: &str) -> String {
+        match self {
+            // A quoted string alone is just a value in PowerShell; `&` runs it
+            Self::PowerShell | Self::Pwsh => format!("& '{}'", path.replace('\'', "''")),
+            Self::Cmd => format!("\"{}\"", path),
+        }
+    }
+
+    /// The program the command runs: a quoted path (after `&` in
+    /// PowerShell), or the first word
+    pub fn program(self, command: &str) -> Option<&str> {
+        let command = command.trim_start();
+        let command = match self {
+            Self::PowerShell | Self::Pwsh => command.strip_prefix('&').map_or(command, str::trim_start),
+            Self::Cmd => command,
+        };
+        match command.chars().next()? {
+            quote @ ('"' | '\'') => command[1..].split(quote).next(),
+            _ => command.split_whitespace().next(),
+        }
+    }
+
+    /// An unquoted Windows program path containing spaces, which the shell
+    /// would split at the first space: `C:\Program Files\App\app.exe -v`
+    pub fn unquoted_path_with_spaces(self, command: &str) -> Option<&str> {
+        let command = command.trim_start();
+        if !self.is_windows() || !is_windows_absolute(command) {
+            return None;
+        }
+        let lower = command.to_ascii_lowercase();
+        let end = WINDOWS_EXTENSIONS
+            .iter()
+            .filter_map(|ext| lower.find(ext).map(|i| i + ext.len()))
+            .filter(|end| lower[*end..].chars().next().filter(|c| !c.is_whitespace()).is_none())
+            .min()?;
+        command[..end].contains(' ').then(|| &command[..end])
+    }
+}
+
+/// A drive-letter (`C:\`, `C:/`) or UNC (`\\server\share`) path
+pub fn is_windows_absolute(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    path.starts_with(r"\\")
+        || (bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && matches!(bytes[2], b'\\' | b'/'))
+}
+
+/// Where WSL mounts a drive-letter path (`C:\Tools\x.exe` is
+/// `/mnt/c/Tools/x.exe`), when that drive is mounted here
+pub fn wsl_path(path: &str) -> Option<PathBuf> {
+    if !is_windows_absolute(path) || path.starts_with(r"\\") {
+        return None;
+    }
+    let drive = PathBuf::from("/mnt").join(path[..1].to_ascii_lowercase());
+    if !drive.is_dir() {
+        return None;
+    }
+    Some(path[3..].split(['\\', '/']).filter(|part| !part.is_empty()).fold(drive, |dir, part| dir.join(part)))
+}
+
+/// Single-quote a word for a POSIX shell
+fn sh_quote(word: &str) -> String {
+    format!("'{}'", word.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_windows_commands() {
+        assert_eq!(Shell::parse("PowerShell").unwrap(), Some(Shell::PowerShell));
+        assert_eq!(Shell::parse("sh").unwrap(), None);
+        assert!(Shell::parse("zsh").is_err());
+
+        assert_eq!(
+            Shell::Cmd.wrap(r#""C:\Program Files\App\run.bat" it's"#),
+            r#"cmd.exe /C '"C:\Program Files\App\run.bat" it'\''s'"#
+        );
+        assert_eq!(Shell::PowerShell.quote_path(r"C:\Tom's Tools\a.exe"), r"& 'C:\Tom''s Tools\a.exe'");
+
+        assert_eq!(Shell::PowerShell.program(r"& 'C:\Program Files\a.exe' -v"), Some(r"C:\Program Files\a.exe"));
+        assert_eq!(Shell::Cmd.program(r#""C:\My Tools\b.bat" x"#), Some(r"C:\My Tools\b.bat"));
+        assert_eq!(Shell::Cmd.program("dir C:\\"), Some("dir"));
+
+        assert_eq!(
+            Shell::Cmd.unquoted_path_with_spaces(r"C:\Program Files\App\run.exe --all"),
+            Some(r"C:\Program Files\App\run.exe")
+        );
+        assert_eq!(Shell::Cmd.unquoted_path_with_spaces(r"C:\Tools\run.exe --all files"), None);
+        assert_eq!(Shell::Pwsh.unquoted_path_with_spaces(r"C:\Program Files\a.exe"), None);
+
+        assert!(is_windows_absolute(r"D:/backups") && is_windows_absolute(r"\\nas\share"));
+        assert!(!is_windows_absolute(r"backups\daily.bat"));
+    }
+}
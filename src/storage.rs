@@ -1,37 +1,617 @@
+use crate::agent;
+use crate::backup::BackupStore;
 use crate::cron_entry::CronEntry;
-use crate::scheduler::{create_scheduler, Scheduler};
-use anyhow::Result;
+use crate::run_history::{RunHistory, RunRecord};
+use crate::scheduler::agent::AgentScheduler;
+use crate::scheduler::dry_run::DryRunScheduler;
+use crate::scheduler::{create_root_scheduler, create_scheduler, Rejected, SaveReport, Scheduler};
+use crate::config::{BackendKind, Config, SecretsConfig, WatchdogConfig};
+use crate::env_presets::{self, EnvPresets};
+use crate::export;
+use crate::holidays;
+use crate::hooks;
+use crate::log_capture;
+use crate::paths;
+use crate::retry;
+use crate::scripts::{self, ScriptLibrary};
+use crate::secrets::{self, SecretStore};
+use crate::trash::Trash;
+use crate::watchdog;
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+
+/// Directory under the backups holding one directory of snapshots per remote host
+const HOST_BACKUP_DIR: &str = "hosts";
 
 pub struct Storage {
     scheduler: Box<dyn Scheduler>,
+    secrets: SecretStore,
+    backups: Option<BackupStore>,
+    trash: Option<Trash>,
+    scripts: Option<ScriptLibrary>,
+    /// When the generated watchdog entries run
+    watchdog_schedule: String,
+    env_presets: EnvPresets,
+    /// Wrap every entry in `exec` so all runs are recorded and failures
+    /// notified, not only watched ones
+    record_runs: bool,
+    /// Where the output of entries that don't redirect it is captured, when
+    /// `[logs] capture` is on and the backend keeps no log of its own
+    log_dir: Option<PathBuf>,
+    /// The `ssh` destination or agent address of a backend on another host
+    host: Option<String>,
+    /// The live backend a sandbox was cloned from, which its changes are pushed to
+    origin: Option<Box<Storage>>,
 }
 
 impl Storage {
     /// Create a new Storage instance with a local file backend
     pub fn new(custom_path: Option<std::path::PathBuf>) -> Self {
-        let scheduler = Box::new(crate::scheduler::file::FileScheduler::new(custom_path));
-        Self { scheduler }
+        Self::local(custom_path, true)
+    }
+
+    /// Create a local file backend, optionally importing the system
+    /// scheduler's entries when the file doesn't exist yet
+    pub fn local(custom_path: Option<std::path::PathBuf>, import_from_system: bool) -> Self {
+        let scheduler = crate::scheduler::file::FileScheduler::new(custom_path)
+            .import_from_system(import_from_system);
+        Self {
+            scheduler: Box::new(scheduler),
+            secrets: SecretStore::from_config(&SecretsConfig::default()).unwrap_or_default(),
+            backups: None,
+            trash: None,
+            scripts: Self::script_library(None, None),
+            watchdog_schedule: WatchdogConfig::default().schedule,
+            env_presets: EnvPresets::new(),
+            record_runs: false,
+            log_dir: None,
+            host: None,
+            origin: None,
+        }
+    }
+
+    /// Wrap any scheduler (e.g. a `MockScheduler` in tests) without secrets,
+    /// backups or the script library
+    pub fn with_scheduler(scheduler: Box<dyn Scheduler>) -> Self {
+        Self {
+            scheduler,
+            secrets: SecretStore::default(),
+            backups: None,
+            trash: None,
+            scripts: None,
+            watchdog_schedule: WatchdogConfig::default().schedule,
+            env_presets: EnvPresets::new(),
+            record_runs: false,
+            log_dir: None,
+            host: None,
+            origin: None,
+        }
+    }
+
+    /// Create a Storage instance for a backend selected in the config
+    pub fn for_backend(kind: BackendKind, config: &Config) -> Result<Self> {
+        let scheduler: Box<dyn Scheduler> = match kind {
+            BackendKind::File => {
+                let mut storage = Self::local(
+                    paths::storage_file(config.data_dir.as_deref()),
+                    config.import_from_system,
+                );
+                storage.secrets = SecretStore::from_config(&config.secrets)?;
+                storage.backups = Self::backup_store(config);
+                storage.trash = Self::trash_store(config, None);
+                storage.scripts = Self::script_library(config.scripts_dir.as_deref(), config.data_dir.as_deref());
+                storage.watchdog_schedule = config.watchdog.schedule.clone();
+                storage.env_presets = config.env_presets.clone();
+                storage.record_runs = config.exec_every_entry();
+                storage.log_dir = Self::capture_dir(config, storage.scheduler.as_ref());
+                return Ok(storage);
+            }
+            BackendKind::System => create_scheduler(true),
+            BackendKind::Root => create_root_scheduler()?,
+            BackendKind::Cron => Box::new(crate::scheduler::cron::CronScheduler::new()),
+            #[cfg(target_os = "macos")]
+            BackendKind::Launchd => Box::new(crate::scheduler::launchd::LaunchdScheduler::new()),
+            #[cfg(not(target_os = "macos"))]
+            BackendKind::Launchd => bail!("The launchd backend is only available on macOS"),
+            #[cfg(target_os = "linux")]
+            BackendKind::Systemd => Box::new(crate::scheduler::systemd::SystemdScheduler::new()),
+            #[cfg(not(target_os = "linux"))]
+            BackendKind::Systemd => bail!("The systemd backend is only available on Linux"),
+        };
+        Ok(Self {
+            log_dir: Self::capture_dir(config, scheduler.as_ref()),
+            scheduler,
+            secrets: SecretStore::from_config(&config.secrets)?,
+            backups: Self::backup_store(config),
+            trash: Self::trash_store(config, None),
+            scripts: Self::script_library(config.scripts_dir.as_deref(), config.data_dir.as_deref()),
+            watchdog_schedule: config.watchdog.schedule.clone(),
+            env_presets: config.env_presets.clone(),
+            record_runs: config.exec_every_entry(),
+            host: None,
+            origin: None,
+        })
+    }
+
+    /// Create a Storage instance for the crontab of a remote host reached
+    /// over `ssh`, with its own backups
+    pub fn for_host(host: &str, config: &Config) -> Result<Self> {
+        let backups = Self::backup_store(config).and_then(|_| {
+            let dir = BackupStore::default_dir(&paths::data_dir(config.data_dir.as_deref())?);
+            Some(BackupStore::new(dir.join(HOST_BACKUP_DIR).join(host.replace('/', "_")), config.backups.keep))
+        });
+        Ok(Self {
+            scheduler: Box::new(crate::scheduler::cron::CronScheduler::remote(host.to_string())),
+            secrets: SecretStore::from_config(&config.secrets)?,
+            backups,
+            trash: Self::trash_store(config, Some(host)),
+            scripts: Self::script_library(config.scripts_dir.as_deref(), config.data_dir.as_deref()),
+            watchdog_schedule: config.watchdog.schedule.clone(),
+            env_presets: config.env_presets.clone(),
+            record_runs: config.exec_every_entry(),
+            log_dir: None,
+            host: Some(host.to_string()),
+            origin: None,
+        })
+    }
+
+    /// Create a file-backed sandbox to experiment on a copy of `origin`'s
+    /// entries, without backups or a trash
+    pub fn sandbox(origin: Storage, config: &Config) -> Result<Self> {
+        let file = paths::sandbox_file(config.data_dir.as_deref(), origin.host()).context("No data directory for the sandbox")?;
+        let mut storage = Self::local(Some(file), false);
+        storage.secrets = SecretStore::from_config(&config.secrets)?;
+        storage.scripts = Self::script_library(config.scripts_dir.as_deref(), config.data_dir.as_deref());
+        storage.watchdog_schedule = config.watchdog.schedule.clone();
+        storage.env_presets = config.env_presets.clone();
+        storage.record_runs = config.exec_every_entry();
+        storage.origin = Some(Box::new(origin));
+        Ok(storage)
+    }
+
+    /// The live backend, when this is a sandbox
+    pub fn origin(&self) -> Option<&Storage> {
+        self.origin.as_deref()
+    }
+
+    /// Only render what saves would write (`--dry-run`), printing it when
+    /// `print` is set; backups, the trash and log directories are left alone
+    /// too, and a sandbox's pushes to its origin are rendered the same way
+    pub fn dry_run(self, print: bool, config: &Config) -> Self {
+        if self.dry_run_output().is_some() {
+            return self;
+        }
+        Self {
+            scheduler: Box::new(DryRunScheduler::new(self.scheduler, print, config.secrets.redact)),
+            backups: None,
+            trash: None,
+            origin: self.origin.map(|origin| Box::new(origin.dry_run(print, config))),
+            ..self
+        }
+    }
+
+    /// What the last save would have written, when this is a dry run
+    pub fn dry_run_output(&self) -> Option<String> {
+        self.scheduler.dry_run_output()
+    }
+
+    /// Create a Storage instance for the backend served by `cron-manager
+    /// agent` at `address`, which keeps its own secrets, scripts and backups
+    pub fn for_agent(address: &str, config: &Config) -> Result<Self> {
+        let token = agent::token(config.agent.token.as_deref())?;
+        Ok(Self {
+            scheduler: Box::new(
+                AgentScheduler::connect(address, token)?.private_dir(paths::private_dir(config.data_dir.as_deref())),
+            ),
+            secrets: SecretStore::default(),
+            backups: None,
+            trash: Self::trash_store(config, Some(address)),
+            scripts: None,
+            watchdog_schedule: config.watchdog.schedule.clone(),
+            env_presets: EnvPresets::new(),
+            record_runs: false,
+            log_dir: None,
+            host: Some(address.to_string()),
+            origin: None,
+        })
+    }
+
+    /// The remote host, for backends reached over `ssh` or an agent
+    pub fn host(&self) -> Option<&str> {
+        self.host.as_deref()
+    }
+
+    fn script_library(custom_dir: Option<&Path>, custom_data_dir: Option<&Path>) -> Option<ScriptLibrary> {
+        let dir = match custom_dir {
+            Some(dir) => paths::expand_home(dir),
+            None => ScriptLibrary::default_dir(&paths::data_dir(custom_data_dir)?),
+        };
+        Some(ScriptLibrary::new(dir))
+    }
+
+    /// The managed script library, when there is a data directory for it
+    pub fn scripts(&self) -> Option<&ScriptLibrary> {
+        self.scripts.as_ref()
+    }
+
+    /// Fail if a `!script` or `!secret` placeholder in the command can't be
+    /// resolved. Backends that resolve commands themselves report it on save.
+    pub fn check_placeholders(&self, command: &str) -> Result<()> {
+        if self.scheduler.resolves_commands() {
+            return Ok(());
+        }
+        self.secrets.resolve(&self.resolve_scripts(command)?).map(|_| ())
+    }
+
+    /// Fail if the env preset isn't defined. Backends that resolve commands
+    /// themselves use their host's presets and report it on save.
+    pub fn check_env_preset(&self, name: &str) -> Result<()> {
+        if self.scheduler.resolves_commands() {
+            return Ok(());
+        }
+        env_presets::export_prefix(&self.env_presets, name).map(|_| ())
+    }
+
+    /// Names of the env presets in the config, for the preset prompt
+    pub fn env_preset_names(&self) -> Vec<&str> {
+        self.env_presets.keys().map(String::as_str).collect()
+    }
+
+    /// The command with `!script NAME` placeholders replaced by script paths
+    pub fn resolve_scripts(&self, command: &str) -> Result<String> {
+        match &self.scripts {
+            Some(library) => library.resolve(command),
+            None if command.contains(scripts::PLACEHOLDER) => bail!("No data directory for the script library"),
+            None => Ok(command.to_string()),
+        }
+    }
+
+    /// The directory output is captured in, if the config asks for it and
+    /// the backend keeps no log of its own
+    fn capture_dir(config: &Config, scheduler: &dyn Scheduler) -> Option<PathBuf> {
+        if !config.logs.capture || scheduler.keeps_output() {
+            return None;
+        }
+        Some(paths::data_dir(config.data_dir.as_deref())?.join(log_capture::LOG_DIR))
+    }
+
+    /// The file the entry's output is captured in, for entries that don't
+    /// redirect it themselves
+    fn captured_log(&self, entry: &CronEntry) -> Option<PathBuf> {
+        let dir = self.log_dir.as_ref()?;
+        entry.output_path().is_none().then(|| log_capture::log_file(dir, &entry.name))
+    }
+
+    fn backup_store(config: &Config) -> Option<BackupStore> {
+        if !config.backups.enabled {
+            return None;
+        }
+        let data_dir = paths::data_dir(config.data_dir.as_deref())?;
+        Some(BackupStore::new(BackupStore::default_dir(&data_dir), config.backups.keep))
+    }
+
+    /// Take snapshots after saves into `backups`, e.g. for a scheduler
+    /// wrapped with `with_scheduler`
+    pub fn with_backups(mut self, backups: BackupStore) -> Self {
+        self.backups = Some(backups);
+        self
+    }
+
+    /// Snapshots taken after saves, when backups are enabled
+    pub fn backups(&self) -> Option<&BackupStore> {
+        self.backups.as_ref()
+    }
+
+    fn trash_store(config: &Config, host: Option<&str>) -> Option<Trash> {
+        if config.trash.retention_days == 0 {
+            return None;
+        }
+        let data_dir = paths::data_dir(config.data_dir.as_deref())?;
+        Some(Trash::new(Trash::default_path(&data_dir, host), config.trash.retention_days))
+    }
+
+    /// Entries deleted in the TUI, kept on this machine even for remote backends
+    pub fn trash(&self) -> Option<&Trash> {
+        self.trash.as_ref()
     }
 
     /// Create a Storage instance with the system scheduler backend
     /// (cron on Linux, launchd on macOS)
     pub fn with_system_scheduler() -> Self {
         let scheduler = create_scheduler(true);
-        Self { scheduler }
+        Self {
+            scheduler,
+            secrets: SecretStore::from_config(&SecretsConfig::default()).unwrap_or_default(),
+            backups: None,
+            trash: None,
+            scripts: Self::script_library(None, None),
+            watchdog_schedule: WatchdogConfig::default().schedule,
+            env_presets: EnvPresets::new(),
+            record_runs: false,
+            log_dir: None,
+            host: None,
+            origin: None,
+        }
     }
 
-    /// Load all cron entries from the scheduler
+    /// Load all cron entries from the scheduler, with `!script` and `!secret` placeholders
+    /// put back into commands that were resolved on save. Generated watchdog
+    /// entries are left out; saving creates them again.
     pub fn load(&self) -> Result<Vec<CronEntry>> {
-        self.scheduler.load()
+        let mut entries = self.scheduler.load()?;
+        if self.scheduler.resolves_commands() {
+            return Ok(entries);
+        }
+        entries.retain(|entry| entry.watchdog_for.is_none());
+        entries.iter_mut().for_each(secrets::restore_template);
+        Ok(entries)
+    }
+
+    /// All entries as a structured document, with placeholders left in commands
+    pub fn export(&self, format: export::Format) -> Result<String> {
+        export::to_string(&self.load()?, format)
+    }
+
+    /// Save all cron entries to the scheduler, resolving `!script` and
+    /// `!secret` placeholders and env presets, wrapping commands that have
+    /// hooks, watchdogs or skip excluded dates, and adding an entry per watchdog
+    pub fn save(&self, entries: &[CronEntry]) -> Result<SaveReport> {
+        self.scheduler.preflight()?;
+        if self.scheduler.resolves_commands() {
+            return self.scheduler.save(entries);
+        }
+        self.create_log_dir()?;
+        let mut resolved = entries.iter().map(|entry| self.resolve(entry)).collect::<Result<Vec<_>>>()?;
+        let watched: Vec<usize> = (0..entries.len()).filter(|i| entries[*i].watchdog).collect();
+        for index in &watched {
+            resolved.push(watchdog::companion(&entries[*index], &self.watchdog_schedule)?);
+        }
+
+        let mut report = self.scheduler.save(&resolved)?;
+        // A watchdog the backend rejected is reported on the entry it watches
+        report.applied.retain(|index| *index < entries.len());
+        for (index, reason) in &mut report.failed {
+            if *index >= entries.len() {
+                *index = watched[*index - entries.len()];
+                *reason = format!("watchdog: {}", reason);
+            }
+        }
+        self.record_backup(entries);
+        Ok(report)
+    }
+
+    /// Save `entries` over `saved`, what the backend has now. When a single
+    /// entry was added at the end, changed or removed, only that entry goes
+    /// to the scheduler, so backends that register entries one by one leave
+    /// the rest alone, and the report covers only that entry; anything else
+    /// is a full `save`.
+    pub fn save_changes(&self, saved: &[CronEntry], entries: &[CronEntry]) -> Result<SaveReport> {
+        let Some(change) = EntryChange::between(saved, entries) else {
+            return self.save(entries);
+        };
+        // Watchdogs bring entries of their own, and an agent resolves commands itself
+        if change.touches_watchdog(saved, entries) || self.scheduler.resolves_commands() {
+            return self.save(entries);
+        }
+        self.scheduler.preflight()?;
+        self.create_log_dir()?;
+        let (index, applied) = match change {
+            EntryChange::Added(index) => (index, self.scheduler.add(&self.resolve(&entries[index])?)),
+            EntryChange::Updated(index) => {
+                (index, self.scheduler.update(&saved[index], &self.resolve(&entries[index])?))
+            }
+            EntryChange::Removed(index) => {
+                self.scheduler.remove(&saved[index])?;
+                self.record_backup(entries);
+                return Ok(SaveReport::default());
+            }
+        };
+        self.record_backup(entries);
+        // Like a full save, an entry the backend refused is reported rather than failing the save
+        match applied {
+            Ok(()) => Ok(SaveReport { applied: vec![index], failed: Vec::new() }),
+            Err(e) => match e.downcast::<Rejected>() {
+                Ok(rejected) => Ok(SaveReport { applied: Vec::new(), failed: vec![(index, rejected.reason)] }),
+                Err(e) => Err(e),
+            },
+        }
+    }
+
+    /// The entry as the scheduler gets it, with its command resolved
+    fn resolve(&self, entry: &CronEntry) -> Result<CronEntry> {
+        let mut resolved = entry.clone();
+        resolved.command = self.runtime_command(entry)?;
+        if resolved.command != entry.command {
+            resolved.command_template = Some(entry.command.clone());
+        }
+        Ok(resolved)
+    }
+
+    fn create_log_dir(&self) -> Result<()> {
+        if let Some(dir) = self.log_dir.as_ref().filter(|_| self.dry_run_output().is_none()) {
+            std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+        }
+        Ok(())
+    }
+
+    /// Snapshots keep the placeholders, never the secrets
+    fn record_backup(&self, entries: &[CronEntry]) {
+        if let Some(backups) = &self.backups {
+            if let Err(e) = backups.record(entries) {
+                tracing::warn!(error = %format!("{:#}", e), "failed to write backup");
+            }
+        }
+    }
+
+    /// The command line the scheduler runs for the entry
+    fn runtime_command(&self, entry: &CronEntry) -> Result<String> {
+        let resolve = |command: &str| self.secrets.resolve(&self.resolve_scripts(command)?);
+        let mut command = resolve(&entry.command)?;
+        // Innermost, so hooks, env presets and the guard stay POSIX shell
+        if let Some(shell) = entry.shell {
+            command = shell.wrap(&command);
+        }
+        // Watched and pinging entries go through `exec` too, so their runs are
+        // recorded, and so do retries and timeouts `timeout(1)` isn't there for
+        let timeout_by_exec = entry.timeout_secs.is_some() && !self.scheduler.has_timeout_command();
+        if self.record_runs || entry.on_success.is_some() || entry.on_failure.is_some() || entry.watchdog
+            || entry.ping_url.is_some() || entry.retries > 0 || timeout_by_exec
+        {
+            let on_success = entry.on_success.as_deref().map(resolve).transpose()?;
+            let on_failure = entry.on_failure.as_deref().map(resolve).transpose()?;
+            let ping = entry.ping_url.as_deref().map(|url| self.secrets.resolve(url)).transpose()?;
+            command = hooks::wrap_command(entry, &command, on_success.as_deref(), on_failure.as_deref(), ping.as_deref())?;
+        } else if let Some(seconds) = entry.timeout_secs {
+            command = retry::timeout_command(&command, seconds);
+        }
+        // Around the `exec` wrap, so what it and the hooks print is kept too
+        if let Some(log) = self.captured_log(entry) {
+            command = log_capture::capture_command(&command, &log)?;
+        }
+        // Exported before the `exec` wrap, so hooks inherit it
+        command = format!("{}{}", self.env_prefix(entry, self.scheduler.sets_environment())?, command);
+        // Outermost, so excluded dates skip the hooks too
+        if entry.skip_excluded {
+            command = holidays::guard_command(&command)?;
+        }
+        Ok(command)
+    }
+
+    /// The entry's command as a run started by hand executes it: resolved and
+    /// in its shell and env preset, but without the hooks, run recording and
+    /// exclusion-date guard a scheduled run goes through
+    pub fn manual_command(&self, entry: &CronEntry) -> Result<String> {
+        let mut command = self.secrets.resolve(&self.resolve_scripts(&entry.command)?)?;
+        if let Some(shell) = entry.shell {
+            command = shell.wrap(&command);
+        }
+        Ok(format!("{}{}", self.env_prefix(entry, false)?, command))
+    }
+
+    /// The `export ...;` prefix of the entry's env preset, if it has one;
+    /// values may use `!secret`
+    fn env_prefix(&self, entry: &CronEntry, native_environment: bool) -> Result<String> {
+        let mut prefix = match &entry.env {
+            Some(preset) => env_presets::export_prefix(&self.env_presets, preset)
+                .with_context(|| format!("Entry '{}'", entry.name))?,
+            None => String::new(),
+        };
+        // After the preset, so the entry's own values win
+        if !native_environment {
+            prefix.push_str(&env_presets::export_vars(&entry.env_vars).with_context(|| format!("Entry '{}'", entry.name))?);
+            if let Some(cwd) = &entry.cwd {
+                prefix.push_str(&format!("cd {} && ", cwd_word(cwd)));
+            }
+        }
+        self.secrets.resolve(&prefix)
+    }
+
+    /// Check that a save can go through, before the user makes any changes
+    pub fn preflight(&self) -> Result<()> {
+        self.scheduler.preflight()
+    }
+
+    /// Why the backend's entries look damaged, if they do
+    pub fn damage(&self) -> Option<String> {
+        self.scheduler.damage()
+    }
+
+    /// What changes with the backend's entries, when there is something cheaper than loading them
+    pub fn fingerprint(&self) -> Option<u64> {
+        self.scheduler.fingerprint()
+    }
+
+    /// The variable line between two entries that moving one past the other would cross
+    pub fn environment_between(&self, first: &CronEntry, second: &CronEntry) -> Option<String> {
+        self.scheduler.environment_between(first, second)
+    }
+
+    /// Check whether the backend can represent the given schedule
+    pub fn check_schedule(&self, schedule: &str) -> Result<()> {
+        self.scheduler.check_schedule(schedule)
+    }
+
+    /// The log file the entry's output is written to, if known
+    pub fn log_path(&self, entry: &CronEntry) -> Option<PathBuf> {
+        self.scheduler.log_path(entry).or_else(|| self.captured_log(entry))
     }
 
-    /// Save all cron entries to the scheduler
-    pub fn save(&self, entries: &[CronEntry]) -> Result<()> {
-        self.scheduler.save(entries)
+    /// Runs recorded since `since`: on the backend's host when that isn't
+    /// this machine, otherwise in the local data directory
+    pub fn runs_since(&self, since: chrono::DateTime<chrono::Utc>, data_dir: Option<&Path>) -> Result<Vec<RunRecord>> {
+        match self.remote_runs(since) {
+            Some(records) => records,
+            None => match RunHistory::default_path(data_dir) {
+                Some(path) => RunHistory::new(path).load_since(since),
+                None => Ok(Vec::new()),
+            },
+        }
+    }
+
+    /// Run records kept on the backend's host, when that isn't this machine
+    pub fn remote_runs(&self, since: chrono::DateTime<chrono::Utc>) -> Option<Result<Vec<RunRecord>>> {
+        self.scheduler.remote_runs(since)
+    }
+
+    /// Whether disabled entries are kept by the backend
+    pub fn keeps_disabled_entries(&self) -> bool {
+        self.scheduler.keeps_disabled_entries()
     }
 
     /// Get the backend name for display purposes
     pub fn get_backend_name(&self) -> &'static str {
+        if self.origin.is_some() {
+            return "Sandbox";
+        }
         self.scheduler.backend_name()
     }
 }
+
+/// A working directory as one shell word, with a leading `~` left to the
+/// shell so it means the home directory of whoever runs the job
+fn cwd_word(cwd: &Path) -> String {
+    let cwd = cwd.to_string_lossy();
+    match cwd.strip_prefix("~/") {
+        Some(rest) => format!("\"$HOME\"/{}", secrets::shell_quote(rest)),
+        None if cwd == "~" => "\"$HOME\"".to_string(),
+        None => secrets::shell_quote(&cwd),
+    }
+}
+
+/// The one entry that differs between what was saved and what is saved now,
+/// by its index in the list it is in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EntryChange {
+    Added(usize),
+    Updated(usize),
+    Removed(usize),
+}
+
+impl EntryChange {
+    fn between(saved: &[CronEntry], entries: &[CronEntry]) -> Option<Self> {
+        let index = saved.iter().zip(entries).take_while(|(a, b)| a == b).count();
+        let change = match entries.len().checked_sub(saved.len()) {
+            Some(0) if index < saved.len() && saved[index + 1..] == entries[index + 1..] => Self::Updated(index),
+            // Backends add after the others, so only an entry added last keeps the order
+            Some(1) if index == saved.len() => Self::Added(index),
+            None if saved.len() == entries.len() + 1 && saved[index + 1..] == entries[index..] => Self::Removed(index),
+            _ => return None,
+        };
+        // Backends find the entry by name, which must not be ambiguous
+        let unique = |list: &[CronEntry], name: &str| list.iter().filter(|e| e.name == name).count() <= 1;
+        let unambiguous = match change {
+            Self::Added(index) => unique(entries, &entries[index].name),
+            Self::Updated(index) => unique(saved, &saved[index].name) && unique(entries, &entries[index].name),
+            Self::Removed(index) => unique(saved, &saved[index].name),
+        };
+        unambiguous.then_some(change)
+    }
+
+    fn touches_watchdog(self, saved: &[CronEntry], entries: &[CronEntry]) -> bool {
+        match self {
+            Self::Added(index) => entries[index].watchdog,
+            Self::Updated(index) => saved[index].watchdog || entries[index].watchdog,
+            Self::Removed(index) => saved[index].watchdog,
+        }
+    }
+}
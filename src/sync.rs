@@ -0,0 +1,175 @@
+use crate::audit::{self, AuditRecord};
+use crate::config::{BackendKind, Config};
+use crate::conflict::{self, Merge};
+use crate::cron_entry::CronEntry;
+use crate::cron_parser::CronParser;
+use crate::paths;
+use crate::scheduler::SaveReport;
+use crate::storage::Storage;
+use anyhow::{bail, Context, Result};
+use std::path::PathBuf;
+
+/// Which way `sync` copies entries between the local file and the system
+/// scheduler
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Make the system scheduler match the local file
+    Push,
+    /// Make the local file match the system scheduler
+    Pull,
+    /// Take the changes made on each side since the last sync into both
+    Merge,
+}
+
+/// The backend the local file syncs with: the configured system scheduler,
+/// or the OS default when the local file is the configured backend
+pub fn system_kind(config: &Config) -> BackendKind {
+    match config.backend.for_current_os() {
+        BackendKind::File | BackendKind::Root => BackendKind::System,
+        kind => kind,
+    }
+}
+
+/// The other side of a backend opened with `kind`; root's schedule and
+/// remote hosts have none
+pub fn open_peer(kind: BackendKind, config: &Config) -> Result<Option<Storage>> {
+    let peer = match kind {
+        BackendKind::Root => return Ok(None),
+        BackendKind::File => Storage::for_backend(system_kind(config), config)?,
+        _ => Storage::for_backend(BackendKind::File, config)?,
+    };
+    Ok(Some(if config.dry_run { peer.dry_run(false, config) } else { peer }))
+}
+
+/// The local file and the system scheduler, and what each looked like after
+/// the last sync
+pub struct Sync<'a> {
+    pub local: &'a Storage,
+    pub system: &'a Storage,
+    base_file: Option<PathBuf>,
+}
+
+impl<'a> Sync<'a> {
+    pub fn new(local: &'a Storage, system: &'a Storage, config: &Config) -> Self {
+        Self { local, system, base_file: paths::sync_base_file(config.data_dir.as_deref()) }
+    }
+
+    /// The entries both sides were left with by the last sync; none before
+    /// the first
+    fn base(&self) -> Result<Vec<CronEntry>> {
+        match &self.base_file {
+            Some(path) if path.exists() => {
+                let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+                CronParser::parse(&content)
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// What a push would change in the system scheduler
+    pub fn changes(&self) -> Result<Vec<AuditRecord>> {
+        Ok(audit::diff(&self.system.load()?, &self.local.load()?, chrono::Utc::now(), None))
+    }
+
+    /// The entries a merge would leave on both sides. Entries changed on
+    /// both sides since the last sync (or that differ, before the first)
+    /// are conflicts, kept as the local file has them until resolved.
+    pub fn merge(&self) -> Result<Merge> {
+        Ok(conflict::merge(&self.base()?, &self.local.load()?, &self.system.load()?))
+    }
+
+    /// Write `entries` to the sides the direction changes, and remember
+    /// them as the base of the next merge. The system scheduler's report is
+    /// returned, or the local file's on a pull. When it rejected entries the
+    /// base stays as the last sync left it, since a base holding entries the
+    /// system never got would have the next merge read their absence there
+    /// as a deletion.
+    pub fn apply(&self, direction: Direction, entries: &[CronEntry]) -> Result<SaveReport> {
+        let report = match direction {
+            Direction::Push => self.system.save(entries)?,
+            Direction::Pull => self.local.save(entries)?,
+            Direction::Merge => {
+                self.local.save(entries)?;
+                self.system.save(entries)?
+            }
+        };
+        if self.local.dry_run_output().is_none() && report.is_complete() {
+            if let Some(path) = &self.base_file {
+                if let Some(dir) = path.parent() {
+                    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+                }
+                std::fs::write(path, CronParser::serialize(entries)).with_context(|| format!("Failed to write {:?}", path))?;
+            }
+        }
+        Ok(report)
+    }
+
+    /// The entries the direction leaves on both sides; a merge with
+    /// unresolved conflicts fails, naming them
+    pub fn entries(&self, direction: Direction) -> Result<Vec<CronEntry>> {
+        match direction {
+            Direction::Push => self.local.load(),
+            Direction::Pull => self.system.load(),
+            Direction::Merge => {
+                let merge = self.merge()?;
+                if !merge.conflicts.is_empty() {
+                    let names: Vec<&str> = merge.conflicts.iter().map(|c| c.name.as_str()).collect();
+                    bail!("Changed on both sides since the last sync: {}", names.join(", "));
+                }
+                Ok(merge.entries)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::mock::MockScheduler;
+    use crate::test_util::{command_entry, TempDir};
+
+    #[test]
+    fn test_merge_uses_last_sync_as_base() {
+        let dir = TempDir::new("sync");
+        let config = Config { data_dir: Some(dir.path().to_path_buf()), ..Config::default() };
+        let local = Storage::with_scheduler(Box::new(MockScheduler::with_entries(vec![command_entry("A", "0 * * * *", "/bin/a"), command_entry("B", "0 * * * *", "/bin/b")])));
+        let system = Storage::with_scheduler(Box::new(MockScheduler::with_entries(vec![command_entry("A", "0 * * * *", "/bin/a2")])));
+        let sync = Sync::new(&local, &system, &config);
+
+        // Before the first sync, entries that differ conflict
+        assert_eq!(sync.changes().unwrap().len(), 2);
+        assert!(sync.entries(Direction::Merge).is_err());
+        sync.apply(Direction::Push, &sync.entries(Direction::Push).unwrap()).unwrap();
+        assert_eq!(system.load().unwrap(), local.load().unwrap());
+        assert!(sync.changes().unwrap().is_empty());
+
+        // B deleted locally, C added on the system: both taken in
+        local.save(&[command_entry("A", "0 * * * *", "/bin/a")]).unwrap();
+        system.save(&[command_entry("A", "0 * * * *", "/bin/a"), command_entry("B", "0 * * * *", "/bin/b"), command_entry("C", "0 * * * *", "/bin/c")]).unwrap();
+        let merged = sync.entries(Direction::Merge).unwrap();
+        let names: Vec<&str> = merged.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["A", "C"]);
+        sync.apply(Direction::Merge, &merged).unwrap();
+        assert_eq!(local.load().unwrap(), merged);
+        assert_eq!(system.load().unwrap(), merged);
+    }
+
+    #[test]
+    fn test_entries_the_system_rejects_stay_out_of_the_base() {
+        let dir = TempDir::new("sync-rejected");
+        let config = Config { data_dir: Some(dir.path().to_path_buf()), ..Config::default() };
+        let entries = vec![command_entry("A", "0 * * * *", "/bin/a"), command_entry("B", "0 * * * *", "/bin/b")];
+        let local = Storage::with_scheduler(Box::new(MockScheduler::with_entries(entries.clone())));
+        let mock = MockScheduler::new();
+        mock.reject_entry("B", "failed to load");
+        let system = Storage::with_scheduler(Box::new(mock.clone()));
+        let sync = Sync::new(&local, &system, &config);
+
+        assert!(!sync.apply(Direction::Push, &entries).unwrap().is_complete());
+        assert!(sync.base().unwrap().is_empty());
+
+        // B is pushed again rather than deleted from the local file
+        mock.set_entries(vec![entries[0].clone()]);
+        assert_eq!(sync.entries(Direction::Merge).unwrap(), entries);
+    }
+}
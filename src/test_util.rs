@@ -0,0 +1,51 @@
+use crate::cron_entry::CronEntry;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// An entry running `/bin/true` on `schedule`
+pub fn entry(name: &str, schedule: &str) -> CronEntry {
+    command_entry(name, schedule, "/bin/true")
+}
+
+/// An entry running `command` on `schedule`
+pub fn command_entry(name: &str, schedule: &str, command: &str) -> CronEntry {
+    CronEntry::new(name.to_string(), schedule.to_string(), command.to_string())
+}
+
+/// An hourly `/bin/true` entry carrying `tags`
+pub fn tagged(name: &str, tags: &[&str]) -> CronEntry {
+    CronEntry { tags: tags.iter().map(|tag| tag.to_string()).collect(), ..entry(name, "0 * * * *") }
+}
+
+/// An empty directory of its own for a test, removed with everything in it
+/// when dropped, so a failing assertion doesn't leave it behind
+#[derive(Debug)]
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// `label` says which test a directory left by a crash belongs to; the
+    /// process id and a counter keep tests running at once apart
+    pub fn new(label: &str) -> Self {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let name = format!("cron-manager-{}-{}-{}", label, std::process::id(), COUNT.fetch_add(1, Ordering::Relaxed));
+        let path = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn join(&self, name: &str) -> PathBuf {
+        self.0.join(name)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
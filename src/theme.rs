@@ -0,0 +1,160 @@
+use ratatui::style::Color;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+
+/// Built-in color sets, switched between at runtime with the `theme` key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    #[default]
+    Dark,
+    Light,
+    Solarized,
+}
+
+impl Preset {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Dark => "dark",
+            Self::Light => "light",
+            Self::Solarized => "solarized",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Self::Dark => Self::Light,
+            Self::Light => Self::Solarized,
+            Self::Solarized => Self::Dark,
+        }
+    }
+}
+
+/// `[theme]` section: a preset and the colors that replace its own. Colors
+/// are names (`red`, `lightblue`), `#rrggbb` or a 256-color index.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    pub preset: Preset,
+    #[serde(deserialize_with = "color")]
+    pub title: Option<Color>,
+    #[serde(deserialize_with = "color")]
+    pub header: Option<Color>,
+    #[serde(deserialize_with = "color")]
+    pub selected_fg: Option<Color>,
+    #[serde(deserialize_with = "color")]
+    pub selected_bg: Option<Color>,
+    #[serde(deserialize_with = "color")]
+    pub enabled: Option<Color>,
+    #[serde(deserialize_with = "color")]
+    pub disabled: Option<Color>,
+    #[serde(deserialize_with = "color")]
+    pub snoozed: Option<Color>,
+    #[serde(deserialize_with = "color")]
+    pub status: Option<Color>,
+    #[serde(deserialize_with = "color")]
+    pub prompt: Option<Color>,
+}
+
+fn color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Color>, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    name.parse()
+        .map(Some)
+        .map_err(|_| D::Error::custom(format!("Unknown color '{}' (use a name like red, #rrggbb or 0-255)", name)))
+}
+
+/// Colors of the title, the table and the status bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub preset: Preset,
+    pub title: Color,
+    /// Column headers of the entry table
+    pub header: Color,
+    pub selected_fg: Color,
+    pub selected_bg: Color,
+    /// Status symbols of enabled, disabled and snoozed entries
+    pub enabled: Color,
+    pub disabled: Color,
+    pub snoozed: Color,
+    /// Messages in the status bar, and the prompt while typing
+    pub status: Color,
+    pub prompt: Color,
+}
+
+impl Theme {
+    /// The preset's colors with the config's replacements
+    pub fn new(config: &ThemeConfig, preset: Preset) -> Self {
+        let base = Self::preset(preset);
+        Self {
+            preset,
+            title: config.title.unwrap_or(base.title),
+            header: config.header.unwrap_or(base.header),
+            selected_fg: config.selected_fg.unwrap_or(base.selected_fg),
+            selected_bg: config.selected_bg.unwrap_or(base.selected_bg),
+            enabled: config.enabled.unwrap_or(base.enabled),
+            disabled: config.disabled.unwrap_or(base.disabled),
+            snoozed: config.snoozed.unwrap_or(base.snoozed),
+            status: config.status.unwrap_or(base.status),
+            prompt: config.prompt.unwrap_or(base.prompt),
+        }
+    }
+
+    fn preset(preset: Preset) -> Self {
+        match preset {
+            Preset::Dark => Self {
+                preset,
+                title: Color::Cyan,
+                header: Color::Yellow,
+                selected_fg: Color::Reset,
+                selected_bg: Color::DarkGray,
+                enabled: Color::Green,
+                disabled: Color::Red,
+                snoozed: Color::Yellow,
+                status: Color::Green,
+                prompt: Color::Yellow,
+            },
+            // For terminals with a light background, where yellow and cyan fade
+            Preset::Light => Self {
+                preset,
+                title: Color::Blue,
+                header: Color::Magenta,
+                selected_fg: Color::Black,
+                selected_bg: Color::Gray,
+                enabled: Color::Green,
+                disabled: Color::Red,
+                snoozed: Color::Magenta,
+                status: Color::Blue,
+                prompt: Color::Magenta,
+            },
+            Preset::Solarized => Self {
+                preset,
+                title: Color::Rgb(0x26, 0x8b, 0xd2),
+                header: Color::Rgb(0xb5, 0x89, 0x00),
+                selected_fg: Color::Rgb(0x93, 0xa1, 0xa1),
+                selected_bg: Color::Rgb(0x07, 0x36, 0x42),
+                enabled: Color::Rgb(0x85, 0x99, 0x00),
+                disabled: Color::Rgb(0xdc, 0x32, 0x2f),
+                snoozed: Color::Rgb(0xcb, 0x4b, 0x16),
+                status: Color::Rgb(0x2a, 0xa1, 0x98),
+                prompt: Color::Rgb(0x6c, 0x71, 0xc4),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_colors_replace_the_preset() {
+        let config: ThemeConfig = toml::from_str("preset = \"light\"\nheader = \"#102030\"\nselected_bg = \"8\"\n").unwrap();
+        let theme = Theme::new(&config, config.preset);
+        assert_eq!(theme.header, Color::Rgb(0x10, 0x20, 0x30));
+        assert_eq!(theme.selected_bg, Color::Indexed(8));
+        assert_eq!(theme.title, Color::Blue);
+        // Replacements stay when switching presets
+        assert_eq!(Theme::new(&config, Preset::Solarized).header, Color::Rgb(0x10, 0x20, 0x30));
+        assert!(toml::from_str::<ThemeConfig>("title = \"mauve-ish\"\n").is_err());
+    }
+}
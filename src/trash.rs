@@ -0,0 +1,122 @@
+use crate::cron_entry::CronEntry;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory inside the data directory holding the trash
+const TRASH_DIR: &str = "trash";
+
+/// Trash of the local backend; remote hosts get one file each under `hosts/`
+const LOCAL_TRASH: &str = "local.json";
+
+/// Deleted entries, kept for a number of days so they can be put back
+#[derive(Debug, Clone)]
+pub struct Trash {
+    path: PathBuf,
+    retention_days: u32,
+}
+
+/// An entry in the trash and when it was deleted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrashedEntry {
+    pub entry: CronEntry,
+    pub deleted_at: DateTime<Utc>,
+}
+
+impl Trash {
+    pub fn new(path: PathBuf, retention_days: u32) -> Self {
+        Self { path, retention_days }
+    }
+
+    /// The trash file for the local backend, or for a remote host
+    pub fn default_path(data_dir: &Path, host: Option<&str>) -> PathBuf {
+        let dir = data_dir.join(TRASH_DIR);
+        match host {
+            Some(host) => dir.join("hosts").join(format!("{}.json", host.replace('/', "_"))),
+            None => dir.join(LOCAL_TRASH),
+        }
+    }
+
+    pub fn retention_days(&self) -> u32 {
+        self.retention_days
+    }
+
+    /// When an entry deleted at `deleted_at` is dropped for good
+    pub fn expires_at(&self, deleted_at: DateTime<Utc>) -> DateTime<Utc> {
+        deleted_at + Duration::days(self.retention_days as i64)
+    }
+
+    /// Entries still kept at `now`, most recently deleted first
+    pub fn list(&self, now: DateTime<Utc>) -> Result<Vec<TrashedEntry>> {
+        let mut items = self.read()?;
+        items.retain(|item| self.expires_at(item.deleted_at) > now);
+        items.sort_by_key(|item| std::cmp::Reverse(item.deleted_at));
+        Ok(items)
+    }
+
+    /// Put a deleted entry in the trash, dropping the ones that expired
+    pub fn add(&self, entry: CronEntry, now: DateTime<Utc>) -> Result<()> {
+        let mut items = self.list(now)?;
+        items.insert(0, TrashedEntry { entry, deleted_at: now });
+        self.write(&items)
+    }
+
+    /// Take an entry out of the trash, to restore it or drop it for good.
+    /// Returns it if it was still there.
+    pub fn remove(&self, item: &TrashedEntry, now: DateTime<Utc>) -> Result<Option<CronEntry>> {
+        let mut items = self.list(now)?;
+        let Some(index) = items.iter().position(|i| i == item) else {
+            return Ok(None);
+        };
+        let removed = items.remove(index);
+        self.write(&items)?;
+        Ok(Some(removed.entry))
+    }
+
+    fn read(&self) -> Result<Vec<TrashedEntry>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let content =
+            fs::read_to_string(&self.path).with_context(|| format!("Failed to read trash: {:?}", self.path))?;
+        serde_json::from_str(&content).with_context(|| format!("Invalid trash file: {:?}", self.path))
+    }
+
+    fn write(&self, items: &[TrashedEntry]) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("Failed to create trash directory: {:?}", parent))?;
+        }
+        let content = serde_json::to_string_pretty(items)?;
+        fs::write(&self.path, content).with_context(|| format!("Failed to write trash: {:?}", self.path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{entry, TempDir};
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_add_restore_and_expire() {
+        let dir = TempDir::new("trash");
+        let trash = Trash::new(Trash::default_path(dir.path(), None), 30);
+        let day = |d: u32| Utc.with_ymd_and_hms(2026, 3, d, 12, 0, 0).unwrap();
+
+        trash.add(entry("Backup", "0 2 * * *"), day(1)).unwrap();
+        trash.add(entry("Report", "0 2 * * *"), day(10)).unwrap();
+        let items = trash.list(day(10)).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].entry.name, "Report");
+
+        let restored = trash.remove(&items[0], day(10)).unwrap();
+        assert_eq!(restored.map(|e| e.name).as_deref(), Some("Report"));
+        assert_eq!(trash.remove(&items[0], day(10)).unwrap(), None);
+
+        // 30 days after deletion the entry is gone
+        assert_eq!(trash.list(day(30)).unwrap().len(), 1);
+        assert!(trash.list(day(31)).unwrap().is_empty());
+    }
+}
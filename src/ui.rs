@@ -1,61 +1,317 @@
-use crate::app::{App, InputMode};
+use crate::analysis::calendar::CalendarSpan;
+use crate::analysis::frequency::Density;
+use crate::analysis::lint::Severity;
+use crate::audit::{AuditRecord, ChangeKind};
+use crate::app::{App, BackupDiff, ConflictResolver, ConversionWizard, InputMode};
+use crate::cron_entry::{self, CronEntry};
+use crate::export;
+use crate::filter::ListRow;
+use crate::keys::Action;
+use crate::ping;
+use crate::i18n::fill;
+use crate::run_history;
+use crate::schedule::split::Conversion;
+use crate::schedule::{self, suggest};
+use crate::scripts;
+use crate::secrets;
+use chrono::Datelike;
+use regex::Regex;
+use std::collections::BTreeMap;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Sparkline, Table, TableState, Tabs, Wrap},
     Frame,
 };
 
-pub fn draw(f: &mut Frame, app: &App) {
+/// How a host's backend is doing, shown on its tab
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HostStatus {
+    Saved,
+    Unsaved,
+    /// The last operation failed or some entries weren't registered
+    Failed,
+}
+
+/// One host's tab when several hosts are open
+pub struct HostTab<'a> {
+    pub host: &'a str,
+    pub status: HostStatus,
+}
+
+pub fn draw(f: &mut Frame, app: &mut App) {
+    draw_tabbed(f, app, &[], 0);
+}
+
+/// Comment lines of the selected entry shown below its details
+const MAX_COMMENT_LINES: usize = 3;
+
+/// Draw the active host's app, with a tab per host in the title bar when
+/// there is more than one
+pub fn draw_tabbed(f: &mut Frame, app: &mut App, tabs: &[HostTab], active: usize) {
+    // The details grow by the selected entry's comment lines, up to a few
+    let comment_lines = app.entries.get(app.selected_index).map_or(0, |e| e.comments.len().min(MAX_COMMENT_LINES));
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(3),  // Title
             Constraint::Min(10),    // Table
+            Constraint::Length(3 + comment_lines as u16),  // Details
             Constraint::Length(3),  // Input/Message
             Constraint::Length(4),  // Help
         ])
         .split(f.area());
 
-    draw_title(f, chunks[0], app);
-    draw_table(f, app, chunks[1]);
-    draw_input_or_message(f, app, chunks[2]);
-    draw_help(f, app, chunks[3]);
+    if tabs.len() > 1 {
+        draw_host_tabs(f, chunks[0], app, tabs, active);
+    } else {
+        draw_title(f, chunks[0], app);
+    }
+    match app.input_mode {
+        InputMode::ViewingLog | InputMode::SearchingLog => draw_log(f, app, chunks[1]),
+        InputMode::ViewingSimulation => draw_simulation(f, app, chunks[1]),
+        InputMode::ViewingCalendar => draw_calendar(f, app, chunks[1]),
+        InputMode::ViewingRun => draw_manual_run(f, app, chunks[1]),
+        InputMode::ViewingChanges => draw_changes(f, app, chunks[1]),
+        InputMode::ViewingDryRun => draw_dry_run(f, app, chunks[1]),
+        InputMode::BrowsingBackups => draw_backups(f, app, chunks[1]),
+        InputMode::BrowsingTrash => draw_trash(f, app, chunks[1]),
+        InputMode::BrowsingRuns => draw_runs(f, app, chunks[1]),
+        InputMode::ReviewingPush => draw_push_review(f, app, chunks[1]),
+        InputMode::ReviewingSync => draw_sync_review(f, app, chunks[1]),
+        InputMode::ReviewingEdit => draw_bulk_edit(f, app, chunks[1]),
+        InputMode::BrowsingScripts | InputMode::AddingScript => draw_scripts(f, app, chunks[1]),
+        _ if app.show_preview && chunks[1].width >= PREVIEW_MIN_WIDTH => {
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+                .split(chunks[1]);
+            draw_table(f, app, columns[0]);
+            draw_preview(f, app, columns[1]);
+        }
+        _ => draw_table(f, app, chunks[1]),
+    }
+    draw_details(f, app, chunks[2]);
+    draw_input_or_message(f, app, chunks[3]);
+    draw_help(f, app, chunks[4]);
+    if let Some(selected) = app.preset_picker {
+        draw_preset_picker(f, app, chunks[1], selected);
+    }
+    if let Some(resolver) = &app.conflict_resolver {
+        draw_conflicts(f, app, chunks[1], resolver);
+    }
+    if let Some(wizard) = &app.conversion {
+        draw_conversion(f, app, chunks[1], wizard);
+    }
+    if app.input_mode == InputMode::ConfirmDelete {
+        draw_delete_confirmation(f, app, chunks[1]);
+    }
+    if app.input_mode == InputMode::ConfirmImport {
+        draw_import_confirmation(f, app, chunks[1]);
+    }
+}
+
+/// Narrowest table area split to show the preview pane beside it
+const PREVIEW_MIN_WIDTH: u16 = 100;
+
+/// Upcoming runs listed in the preview pane
+const PREVIEW_RUNS: usize = 5;
+
+/// The selected entry in full: name, schedule in words, state, the whole
+/// command wrapped, and its next runs
+fn draw_preview(f: &mut Frame, app: &mut App, area: Rect) {
+    let text = app.text;
+    let block = Block::default().borders(Borders::ALL).title(text.preview_title);
+    let Some(entry) = app.entries.get(app.selected_index).cloned() else {
+        f.render_widget(Paragraph::new(text.no_entry_selected).block(block), area);
+        return;
+    };
+    let label = Style::default().fg(Color::Cyan);
+    let (state, color) = match (entry.enabled, entry.snoozed_until) {
+        (true, _) => (text.preview_enabled.to_string(), app.theme.enabled),
+        (false, Some(until)) => (fill(text.preview_snoozed, &[&until.format("%Y-%m-%d %H:%M")]), app.theme.snoozed),
+        (false, None) => (text.preview_disabled.to_string(), app.theme.disabled),
+    };
+    let command = if app.config.secrets.redact { secrets::redact(&entry.command) } else { entry.command.clone() };
+
+    let mut lines = vec![
+        Line::styled(entry.name.clone(), Style::default().add_modifier(Modifier::BOLD)),
+        Line::from(vec![Span::styled(text.label_schedule, label), Span::raw(entry.schedule.clone())]),
+    ];
+    let now = app.clock.now();
+    match app.selected_schedule_info().map(|info| (info.schedule.clone(), info.zone.clone())) {
+        Some((Ok(schedule), zone)) => {
+            lines.push(Line::raw(format!("  {}", schedule::describe::describe(&schedule, text))));
+            lines.push(Line::from(vec![Span::styled(text.preview_state, label), Span::styled(state, Style::default().fg(color))]));
+            if let (Ok(zone), false) = (zone, schedule.reboot) {
+                lines.push(Line::styled(fill(text.preview_next_runs, &[&zone]), label));
+                for time in zone.next_runs(&schedule, now, PREVIEW_RUNS) {
+                    lines.push(Line::raw(format!("  {}", time.format("%Y-%m-%d (%a) %H:%M"))));
+                }
+            }
+        }
+        Some((Err(e), _)) => {
+            lines.push(Line::styled(format!("  {}", e), Style::default().fg(Color::Red)));
+            lines.push(Line::from(vec![Span::styled(text.preview_state, label), Span::styled(state, Style::default().fg(color))]));
+        }
+        None => {}
+    }
+    lines.push(Line::styled(text.preview_command, label));
+    lines.extend(command.lines().map(|line| Line::raw(format!("  {}", line))));
+    f.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }).block(block), area);
 }
 
 fn draw_title(f: &mut Frame, area: Rect, app: &App) {
     let backend = app.storage.get_backend_name();
-    let title = Paragraph::new(format!("Cron Manager [Backend: {}]", backend))
-        .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
-        .block(Block::default().borders(Borders::ALL));
+    let unsaved = if app.has_unsaved_changes() { app.text.unsaved_changes } else { "" };
+    let dry_run = if app.config.dry_run { app.text.dry_run_marker } else { "" };
+    let title = Paragraph::new(format!("{}{}{}", fill(app.text.title, &[&backend]), dry_run, unsaved))
+        .style(Style::default().fg(app.theme.title).add_modifier(Modifier::BOLD))
+        .block(Block::default().borders(Borders::ALL).title(clock_line(app).right_aligned()));
     f.render_widget(title, area);
 }
 
-fn draw_table(f: &mut Frame, app: &App, area: Rect) {
-    let header_cells = ["Status", "Name", "Schedule", "Command"]
-        .iter()
-        .map(|h| Cell::from(*h).style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+/// The current time, highlighted when it is simulated
+fn clock_line(app: &App) -> Line<'static> {
+    if app.clock.is_simulated() {
+        Line::styled(
+            fill(app.text.simulated_time, &[&app.clock.now_local().format("%Y-%m-%d %H:%M:%S")]),
+            Style::default().fg(Color::Black).bg(Color::Yellow),
+        )
+    } else {
+        Line::from(app.clock.now_local().format(" %H:%M:%S ").to_string())
+    }
+}
+
+fn draw_host_tabs(f: &mut Frame, area: Rect, app: &App, tabs: &[HostTab], active: usize) {
+    let titles = tabs.iter().map(|tab| {
+        let marker = match tab.status {
+            HostStatus::Saved => Span::raw(""),
+            HostStatus::Unsaved => Span::styled(" *", Style::default().fg(Color::Yellow)),
+            HostStatus::Failed => Span::styled(" ✗", Style::default().fg(Color::Red)),
+        };
+        Line::from(vec![Span::raw(tab.host), marker])
+    });
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(fill(app.text.title, &[&app.storage.get_backend_name()]))
+        .title(clock_line(app).right_aligned());
+    let tabs = Tabs::new(titles)
+        .select(active)
+        .style(Style::default().fg(app.theme.title))
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED))
+        .block(block);
+    f.render_widget(tabs, area);
+}
+
+fn draw_table(f: &mut Frame, app: &mut App, area: Rect) {
+    let text = app.text;
+    let header_cells = [text.header_status, text.header_name, text.header_schedule, text.header_command]
+        .into_iter()
+        .map(|h| Cell::from(h).style(Style::default().fg(app.theme.header).add_modifier(Modifier::BOLD)));
     let header = Row::new(header_cells).height(1).bottom_margin(1);
 
-    let rows: Vec<Row> = app.entries.iter().enumerate().map(|(i, entry)| {
-        let status_symbol = if entry.enabled { "✓" } else { "✗" };
-        let status_color = if entry.enabled { Color::Green } else { Color::Red };
+    let redact = app.config.secrets.redact;
+    let theme = app.theme;
+    let selected_style = Style::default().fg(theme.selected_fg).bg(theme.selected_bg).add_modifier(Modifier::BOLD);
+    let flagged = app.collisions.flagged();
+    let mut worst: BTreeMap<usize, Severity> = BTreeMap::new();
+    for finding in &app.lint_findings {
+        let severity = worst.entry(finding.entry).or_insert(finding.severity);
+        *severity = (*severity).max(finding.severity);
+    }
+
+    app.keep_selection_shown();
+    let list_rows = app.list_rows();
+    let shown = list_rows.iter().filter(|row| matches!(row, ListRow::Entry(_))).count();
+    // The selected entry's row, or its group's heading when it is the first
+    let selected_row = list_rows.iter().position(|row| *row == ListRow::Entry(app.selected_index));
+    let first_row = selected_row.map(|row| match row.checked_sub(1).map(|above| &list_rows[above]) {
+        Some(ListRow::Group(..)) => row - 1,
+        _ => row,
+    });
+
+    // Only build rows for the visible window (borders, header and its margin
+    // take four lines), scrolling just enough to keep the selection in view
+    let visible = (area.height as usize).saturating_sub(4).max(1);
+    let mut offset = app.table_offset.min(list_rows.len().saturating_sub(visible));
+    if let (Some(first), Some(selected)) = (first_row, selected_row) {
+        if first < offset {
+            offset = first;
+        } else if selected >= offset + visible {
+            offset = selected + 1 - visible;
+        }
+    }
+    app.table_offset = offset;
+    app.table_height = visible;
+    let end = (offset + visible).min(list_rows.len());
+
+    // Schedules are colored by how often they fire; disabled ones don't run,
+    // and @reboot ones run once per startup
+    let densities: Vec<Option<Density>> = list_rows[offset..end]
+        .iter()
+        .map(|row| {
+            let ListRow::Entry(i) = *row else {
+                return None;
+            };
+            let enabled = app.entries[i].enabled;
+            app.schedule_info(i)
+                .filter(|info| enabled && info.schedule.as_ref().is_ok_and(|schedule| !schedule.reboot))
+                .map(|info| info.frequency.density())
+        })
+        .collect();
+
+    let group_style = Style::default().fg(theme.header).add_modifier(Modifier::BOLD);
+    let rows: Vec<Row> = list_rows[offset..end].iter().enumerate().map(|(row, list_row)| {
+        let i = match list_row {
+            ListRow::Entry(i) => *i,
+            ListRow::Group(tag, size) => {
+                let heading = match tag {
+                    Some(tag) => format!("#{} ({})", tag, size),
+                    None => format!("{} ({})", text.untagged_group, size),
+                };
+                return Row::new(vec![Cell::from(""), Cell::from(heading)]).style(group_style).height(1);
+            }
+        };
+        let entry = &app.entries[i];
+        let (status_symbol, status_color) = match (entry.enabled, entry.snoozed_until) {
+            (true, _) => ("✓", theme.enabled),
+            (false, Some(_)) => ("z", theme.snoozed),
+            (false, None) => ("✗", theme.disabled),
+        };
+
+        let mut status_spans = vec![Span::styled(status_symbol, Style::default().fg(status_color))];
+        if app.save_failures.contains_key(&i) {
+            // The backend refused it, so the job won't run
+            status_spans.push(Span::styled(
+                " ✗",
+                Style::default().fg(Color::White).bg(Color::Red).add_modifier(Modifier::BOLD),
+            ));
+        } else if worst.get(&i) == Some(&Severity::Error) {
+            status_spans.push(Span::styled(" !", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)));
+        } else if flagged.contains(&i) || worst.contains_key(&i) {
+            // Collides with other jobs or has lint warnings
+            status_spans.push(Span::styled(" ⚠", Style::default().fg(Color::Yellow)));
+        }
+        if app.overdue_pings.contains_key(&entry.name) {
+            status_spans.push(Span::styled(" ⏱", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)));
+        }
 
         let cells = vec![
-            Cell::from(status_symbol).style(Style::default().fg(status_color)),
+            Cell::from(Line::from(status_spans)),
             Cell::from(entry.name.clone()),
-            Cell::from(entry.schedule.clone()),
-            Cell::from(entry.command.clone()),
+            Cell::from(entry.schedule.clone()).style(match densities[row] {
+                Some(Density::Minutely) => Style::default().fg(Color::Red),
+                Some(Density::Hourly) => Style::default().fg(Color::Yellow),
+                Some(Density::Daily) => Style::default().fg(Color::Green),
+                None if entry.enabled && schedule::is_reboot(&entry.schedule) => Style::default().fg(Color::Cyan),
+                None => Style::default(),
+            }),
+            Cell::from(if redact { secrets::redact(&entry.command) } else { entry.command.clone() }),
         ];
 
-        let style = if i == app.selected_index {
-            Style::default()
-                .bg(Color::DarkGray)
-                .add_modifier(Modifier::BOLD)
-        } else {
-            Style::default()
-        };
+        let style = if i == app.selected_index { selected_style } else { Style::default() };
 
         Row::new(cells).style(style).height(1)
     }).collect();
@@ -67,80 +323,1376 @@ fn draw_table(f: &mut Frame, app: &App, area: Rect) {
         Constraint::Percentage(50),
     ];
 
+    let title = if let Some(tag) = &app.tag_filter {
+        fill(text.entries_title_tagged, &[tag, &shown, &app.entries.len()])
+    } else if end - offset < list_rows.len() {
+        fill(text.entries_title_window, &[&(offset + 1), &end, &list_rows.len()])
+    } else {
+        fill(text.entries_title, &[&app.entries.len()])
+    };
+    let mut block = Block::default().borders(Borders::ALL).title(title);
+
+    // Summarize lint findings for the highlighted entry on the bottom border
+    let findings: Vec<_> = app.selected_findings().collect();
+    if let Some(first) = findings.iter().max_by_key(|f| f.severity) {
+        let color = if first.severity == Severity::Error { Color::Red } else { Color::Yellow };
+        let more = if findings.len() > 1 {
+            fill(text.more_findings, &[&(findings.len() - 1)])
+        } else {
+            String::new()
+        };
+        block = block.title_bottom(Line::styled(
+            format!(" {}: {}{} ", first.severity.label(), first.message, more),
+            Style::default().fg(color),
+        ));
+    }
+
     let table = Table::new(rows, widths)
         .header(header)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(format!(" Cron Entries ({}) ", app.entries.len()))
-        )
-        .row_highlight_style(
+        .block(block)
+        .row_highlight_style(selected_style);
+
+    let mut state = TableState::default().with_selected(selected_row.map(|row| row - offset));
+    f.render_stateful_widget(table, area, &mut state);
+}
+
+fn draw_log(f: &mut Frame, app: &App, area: Rect) {
+    let Some(log) = &app.log else {
+        return;
+    };
+
+    let visible = (area.height as usize).saturating_sub(2);
+    let end = log.lines.len().saturating_sub(app.log_scroll);
+    let start = end.saturating_sub(visible);
+    let lines: Vec<Line> = log.lines[start..end]
+        .iter()
+        .map(|l| match &app.log_search {
+            Some((_, pattern)) => highlight(l, pattern),
+            None => Line::raw(l.as_str()),
+        })
+        .collect();
+
+    let position = if app.log_scroll == 0 {
+        app.text.log_at_end.to_string()
+    } else {
+        fill(app.text.log_lines_up, &[&app.log_scroll])
+    };
+    let more = if log.at_start() { "" } else { app.text.log_more_above };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(fill(app.text.log_title, &[&log.path().display()]))
+        .title_bottom(Line::from(format!(" {}{} ", position, more)).right_aligned());
+
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+fn draw_manual_run(f: &mut Frame, app: &App, area: Rect) {
+    let Some(run) = &app.manual_run else {
+        return;
+    };
+
+    let visible = (area.height as usize).saturating_sub(2);
+    let end = run.lines.len().saturating_sub(app.run_scroll);
+    let start = end.saturating_sub(visible);
+    let lines: Vec<Line> = run.lines[start..end]
+        .iter()
+        .map(|l| {
+            let style = if l.stderr { Style::default().fg(Color::Red) } else { Style::default() };
+            Line::styled(l.text.as_str(), style)
+        })
+        .collect();
+
+    let seconds = format!("{:.1}", run.elapsed().as_secs_f64());
+    let mut state = match run.finished().map(|(exit_code, _)| exit_code) {
+        None => match run.retry_in() {
+            Some(wait) => fill(app.text.run_retrying, &[&wait.as_secs()]),
+            None => fill(app.text.run_running, &[&seconds]),
+        },
+        Some(_) if run.timed_out() => fill(app.text.run_timed_out_after, &[&seconds]),
+        Some(Some(code)) => fill(app.text.run_exit_code, &[&code, &seconds]),
+        Some(None) => fill(app.text.run_signal, &[&seconds]),
+    };
+    if let (attempt @ 1.., retries) = run.attempt() {
+        state.push_str(&fill(app.text.run_attempt, &[&attempt, &retries]));
+    }
+    let position = if app.run_scroll == 0 {
+        app.text.log_at_end.to_string()
+    } else {
+        fill(app.text.log_lines_up, &[&app.run_scroll])
+    };
+    let color = match run.finished().map(|(exit_code, _)| exit_code == Some(0)) {
+        None => Color::Yellow,
+        Some(true) => Color::Green,
+        Some(false) => Color::Red,
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(fill(app.text.run_title, &[&run.name]))
+        .title_bottom(Line::styled(format!(" {} ", state), Style::default().fg(color)))
+        .title_bottom(Line::from(format!(" {} ", position)).right_aligned());
+
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// A log line with the search matches highlighted
+fn highlight<'a>(line: &'a str, pattern: &Regex) -> Line<'a> {
+    let mut spans = Vec::new();
+    let mut last = 0;
+    for found in pattern.find_iter(line).filter(|m| !m.is_empty()) {
+        spans.push(Span::raw(&line[last..found.start()]));
+        spans.push(Span::styled(found.as_str(), Style::default().fg(Color::Black).bg(Color::Yellow)));
+        last = found.end();
+    }
+    spans.push(Span::raw(&line[last..]));
+    Line::from(spans)
+}
+
+fn draw_simulation(f: &mut Frame, app: &App, area: Rect) {
+    let Some(simulation) = &app.simulation else {
+        return;
+    };
+    let text = app.text;
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+        .split(area);
+
+    let visible = (columns[0].height as usize).saturating_sub(2);
+    let lines: Vec<Line> = simulation
+        .runs
+        .iter()
+        .skip(app.simulation_scroll)
+        .take(visible)
+        .map(|run| {
+            let time = run.time.with_timezone(&chrono::Local).format("%m-%d %H:%M  ").to_string();
+            Line::from(vec![
+                Span::styled(time, Style::default().fg(Color::Cyan)),
+                Span::raw(app.entries[run.entry].name.as_str()),
+            ])
+        })
+        .collect();
+    let runs = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(fill(text.simulation_title, &[&simulation.runs.len()])),
+    );
+    f.render_widget(runs, columns[0]);
+
+    let counts: Vec<Line> = simulation
+        .counts
+        .iter()
+        .map(|(index, count)| {
+            Line::from(vec![
+                Span::styled(format!("{:>5}  ", fill(text.simulation_runs, &[count])), Style::default().fg(Color::Yellow)),
+                Span::raw(app.entries[*index].name.as_str()),
+            ])
+        })
+        .collect();
+    let counts = Paragraph::new(counts)
+        .block(Block::default().borders(Borders::ALL).title(format!(" {} ", text.simulation_counts)));
+    f.render_widget(counts, columns[1]);
+}
+
+/// Style of a cell of the calendar by the most runs starting in one of its
+/// minutes: clusters stand out in red
+fn calendar_style(app: &App, peak: usize) -> Style {
+    match peak {
+        0 => Style::default().fg(Color::DarkGray),
+        1 => Style::default(),
+        peak if peak < app.config.collisions.cluster_size => Style::default().fg(Color::Yellow),
+        _ => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+    }
+}
+
+/// Upcoming runs of the week, hour by hour, or of the month, day by day
+fn draw_calendar(f: &mut Frame, app: &App, area: Rect) {
+    let Some(calendar) = &app.calendar else {
+        return;
+    };
+    let Some(first) = calendar.days.first() else {
+        return;
+    };
+    let today = app.clock.now().with_timezone(&chrono::Local).date_naive();
+    let day_names: Vec<&str> = app.text.describe_day_names.split(',').collect();
+    let day_name = |date: chrono::NaiveDate| day_names[date.weekday().num_days_from_sunday() as usize];
+    let heading = |date: chrono::NaiveDate| {
+        let style = Style::default().fg(Color::Cyan);
+        if date == today { style.add_modifier(Modifier::REVERSED) } else { style }
+    };
+    const COLUMN: usize = 10;
+
+    let mut lines = Vec::new();
+    let title = match calendar.span {
+        CalendarSpan::Week => {
+            let mut header = vec![Span::raw("       ")];
+            for day in &calendar.days {
+                let label = format!("{} {}", day_name(day.date), day.date.format("%m-%d"));
+                header.push(Span::styled(format!("{:>width$}", label, width = COLUMN), heading(day.date)));
+            }
+            lines.push(Line::from(header));
+            let visible = (area.height as usize).saturating_sub(3);
+            for hour in (0..24).skip(app.calendar_scroll.min(24usize.saturating_sub(visible))).take(visible) {
+                let mut row = vec![Span::styled(format!("{:02}:00  ", hour), Style::default().fg(Color::Cyan))];
+                for day in &calendar.days {
+                    let runs = day.hours[hour];
+                    let cell = if runs == 0 { "·".to_string() } else { runs.to_string() };
+                    row.push(Span::styled(format!("{:>width$}", cell, width = COLUMN), calendar_style(app, day.hour_peaks[hour])));
+                }
+                lines.push(Line::from(row));
+            }
+            fill(app.text.calendar_week_title, &[&first.date.format("%Y-%m-%d")])
+        }
+        CalendarSpan::Month => {
+            let mut header = Vec::new();
+            // Weeks start on Monday, like the week view
+            for offset in 1..=7 {
+                header.push(Span::styled(format!("{:>width$}", day_names[offset % 7], width = COLUMN), Style::default().fg(Color::Cyan)));
+            }
+            lines.push(Line::from(header));
+            let mut row = vec![Span::raw(" ".repeat(COLUMN)); first.date.weekday().num_days_from_monday() as usize];
+            for day in &calendar.days {
+                row.push(Span::styled(format!("{:>width$}", day.date.day(), width = 3), heading(day.date)));
+                let runs = if day.runs() == 0 { "·".to_string() } else { day.runs().to_string() };
+                row.push(Span::styled(format!("{:>width$}", runs, width = COLUMN - 3), calendar_style(app, day.peak())));
+                if day.date.weekday() == chrono::Weekday::Sun {
+                    lines.push(Line::from(std::mem::take(&mut row)));
+                    lines.push(Line::raw(""));
+                }
+            }
+            if !row.is_empty() {
+                lines.push(Line::from(row));
+            }
+            fill(app.text.calendar_month_title, &[&first.date.format("%Y-%m")])
+        }
+    };
+    let grid = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(grid, area);
+}
+
+/// Changes from the audit log, each followed by its changed fields
+fn draw_changes(f: &mut Frame, app: &App, area: Rect) {
+    let Some(records) = &app.recent_changes else {
+        return;
+    };
+    let redact = app.config.secrets.redact;
+    let mut lines = Vec::new();
+    for record in records {
+        let (marker, color) = match record.kind {
+            ChangeKind::Added => ("+", Color::Green),
+            ChangeKind::Removed => ("-", Color::Red),
+            ChangeKind::Changed => ("~", Color::Yellow),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(
+                record.at.with_timezone(&chrono::Local).format("%m-%d %H:%M  ").to_string(),
+                Style::default().fg(Color::Cyan),
+            ),
+            Span::styled(format!("{:<10} ", record.user.as_deref().unwrap_or("?")), Style::default().fg(Color::DarkGray)),
+            Span::styled(format!("{} ", marker), Style::default().fg(color)),
+            Span::raw(record.entry.as_str()),
+        ]));
+        for change in &record.fields {
+            let show = |value: &str| if redact { secrets::redact(value) } else { value.to_string() };
+            lines.push(Line::styled(
+                format!("      {}: {} -> {}", change.field, show(&change.before), show(&change.after)),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+    }
+    let visible = (area.height as usize).saturating_sub(2);
+    let lines: Vec<Line> = lines.into_iter().skip(app.changes_scroll).take(visible).collect();
+    let title = fill(app.text.changes_title, &[&app.config.backups.recent_days, &records.len()]);
+    let paragraph = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(paragraph, area);
+}
+
+/// What the last save would have written in a `--dry-run`; credentials are
+/// already masked when `[secrets] redact` is on
+fn draw_dry_run(f: &mut Frame, app: &App, area: Rect) {
+    let Some(view) = &app.dry_run_view else {
+        return;
+    };
+    let visible = (area.height as usize).saturating_sub(2);
+    let lines: Vec<Line> = view
+        .lines()
+        .skip(app.dry_run_scroll)
+        .take(visible)
+        .map(|line| {
+            if line.starts_with("==> ") {
+                Line::styled(line, Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+            } else if line.starts_with('#') {
+                Line::styled(line, Style::default().fg(Color::DarkGray))
+            } else {
+                Line::raw(line)
+            }
+        })
+        .collect();
+    let title = fill(app.text.dry_run_title, &[&app.storage.get_backend_name()]);
+    let paragraph = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(paragraph, area);
+}
+
+/// Managed scripts and the entries that run them
+fn draw_scripts(f: &mut Frame, app: &App, area: Rect) {
+    let Some(browser) = &app.script_browser else {
+        return;
+    };
+    let text = app.text;
+    let visible = (area.height as usize).saturating_sub(2).max(1);
+    let lines: Vec<Line> = if browser.names.is_empty() {
+        vec![Line::styled(text.no_scripts, Style::default().fg(Color::DarkGray))]
+    } else {
+        browser
+            .names
+            .iter()
+            .enumerate()
+            .skip((browser.selected + 1).saturating_sub(visible))
+            .take(visible)
+            .map(|(i, name)| {
+                let style = if i == browser.selected {
+                    Style::default().fg(Color::Black).bg(Color::Cyan)
+                } else {
+                    Style::default()
+                };
+                let users: Vec<&str> = app
+                    .entries
+                    .iter()
+                    .filter(|e| scripts::references(&e.command, name))
+                    .map(|e| e.name.as_str())
+                    .collect();
+                let mut spans = vec![Span::styled(name.as_str(), style)];
+                if !users.is_empty() {
+                    spans.push(Span::styled(
+                        format!("  {}", fill(text.script_used_by, &[&users.join(", ")])),
+                        Style::default().fg(Color::DarkGray),
+                    ));
+                }
+                Line::from(spans)
+            })
+            .collect()
+    };
+    let list = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(fill(text.scripts_title, &[&browser.names.len()])),
+    );
+    f.render_widget(list, area);
+}
+
+/// Snapshots on the left; the opened snapshot's entries on the right, marked
+/// `+` when missing from the current set and `~` when they differ from it
+fn draw_backups(f: &mut Frame, app: &App, area: Rect) {
+    let Some(browser) = &app.backup_browser else {
+        return;
+    };
+    let text = app.text;
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
+        .split(area);
+    let highlight = Style::default().fg(Color::Black).bg(Color::Cyan);
+    let visible = (columns[0].height as usize).saturating_sub(2).max(1);
+    // Scroll just enough to keep the highlighted line in view
+    let offset = |selected: usize| (selected + 1).saturating_sub(visible);
+
+    let snapshot_lines: Vec<Line> = browser
+        .snapshots
+        .iter()
+        .enumerate()
+        .skip(offset(browser.selected))
+        .take(visible)
+        .map(|(i, snapshot)| {
+            let style = if i == browser.selected { highlight } else { Style::default() };
+            Line::styled(snapshot.taken_at.format("%Y-%m-%d %H:%M:%S").to_string(), style)
+        })
+        .collect();
+    let snapshots = Paragraph::new(snapshot_lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(fill(text.backups_title, &[&browser.snapshots.len()])),
+    );
+    f.render_widget(snapshots, columns[0]);
+
+    if let (None, Some((against, records))) = (&browser.opened, &browser.diff) {
+        draw_backup_diff(f, app, *against, records, columns[1]);
+        return;
+    }
+
+    let entry_lines: Vec<Line> = match &browser.opened {
+        Some((entries, selected)) => entries
+            .iter()
+            .enumerate()
+            .skip(offset(*selected))
+            .take(visible)
+            .map(|(i, entry)| {
+                let (marker, color) = match app.entries.iter().find(|e| e.name == entry.name) {
+                    None => ("+", Color::Green),
+                    Some(current) if current != entry => ("~", Color::Yellow),
+                    Some(_) => (" ", Color::Reset),
+                };
+                let style = if i == *selected { highlight } else { Style::default() };
+                Line::from(vec![
+                    Span::styled(format!("{} ", marker), Style::default().fg(color)),
+                    Span::styled(format!("{}  {}  {}", entry.name, entry.schedule, shown_command(app, &entry.command)), style),
+                ])
+            })
+            .collect(),
+        None => vec![Line::styled(text.backup_open_hint, Style::default().fg(Color::DarkGray))],
+    };
+    let entries = Paragraph::new(entry_lines)
+        .block(Block::default().borders(Borders::ALL).title(format!(" {} ", text.backup_entries)));
+    f.render_widget(entries, columns[1]);
+}
+
+/// The changes between a snapshot and the current entries or the snapshot
+/// before it, field by field
+fn draw_backup_diff(f: &mut Frame, app: &App, against: BackupDiff, records: &[AuditRecord], area: Rect) {
+    let redact = app.config.secrets.redact;
+    let mut lines = Vec::new();
+    for record in records {
+        let (marker, color) = match record.kind {
+            ChangeKind::Added => ("+", Color::Green),
+            ChangeKind::Removed => ("-", Color::Red),
+            ChangeKind::Changed => ("~", Color::Yellow),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{} ", marker), Style::default().fg(color)),
+            Span::raw(record.entry.as_str()),
+        ]));
+        for change in &record.fields {
+            let show = |value: &str| if redact { secrets::redact(value) } else { value.to_string() };
+            lines.push(Line::styled(
+                format!("    {}: {} -> {}", change.field, show(&change.before), show(&change.after)),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+    }
+    if lines.is_empty() {
+        lines.push(Line::styled(app.text.backup_no_changes, Style::default().fg(Color::DarkGray)));
+    }
+    let title = match against {
+        BackupDiff::Current => app.text.backup_diff_current,
+        BackupDiff::Previous => app.text.backup_diff_previous,
+    };
+    let visible = (area.height as usize).saturating_sub(2);
+    let lines: Vec<Line> = lines.into_iter().take(visible).collect();
+    let paragraph = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(format!(" {} ", title)));
+    f.render_widget(paragraph, area);
+}
+
+/// Deleted entries, most recent first, with when they drop out of the trash
+fn draw_trash(f: &mut Frame, app: &App, area: Rect) {
+    let (Some(browser), Some(trash)) = (&app.trash_browser, app.storage.trash()) else {
+        return;
+    };
+    let text = app.text;
+    let highlight = Style::default().fg(Color::Black).bg(Color::Cyan);
+    let visible = (area.height as usize).saturating_sub(2).max(1);
+    let offset = (browser.selected + 1).saturating_sub(visible);
+    let format = |time: chrono::DateTime<chrono::Utc>| time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string();
+
+    let lines: Vec<Line> = browser
+        .items
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .take(visible)
+        .map(|(i, item)| {
+            let style = if i == browser.selected { highlight } else { Style::default() };
+            let entry = &item.entry;
+            Line::from(vec![
+                Span::styled(format!("{}  {}  {}", entry.name, entry.schedule, shown_command(app, &entry.command)), style),
+                Span::styled(
+                    format!(
+                        "  {}",
+                        fill(text.trash_deleted_at, &[&format(item.deleted_at), &format(trash.expires_at(item.deleted_at))])
+                    ),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ])
+        })
+        .collect();
+    let list = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(fill(text.trash_title, &[&browser.items.len()])));
+    f.render_widget(list, area);
+}
+
+/// An entry's recorded runs on the left, the selected run's output on the right
+fn draw_runs(f: &mut Frame, app: &App, area: Rect) {
+    let Some(browser) = &app.run_browser else {
+        return;
+    };
+    let text = app.text;
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(44), Constraint::Min(0)])
+        .split(area);
+    let highlight = Style::default().fg(Color::Black).bg(Color::Cyan);
+    let visible = (columns[0].height as usize).saturating_sub(2).max(1);
+    let offset = (browser.selected + 1).saturating_sub(visible);
+
+    let lines: Vec<Line> = browser
+        .runs
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .take(visible)
+        .map(|(i, run)| {
+            let started = run.started_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S");
+            let result = match run.exit_code {
+                Some(code) => code.to_string(),
+                None => text.runs_signal.to_string(),
+            };
+            let color = if run.succeeded() { Color::Green } else { Color::Red };
+            let style = if i == browser.selected { highlight } else { Style::default() };
+            Line::from(vec![
+                Span::styled(format!("{}  {:>7.1}s  ", started, run.duration_ms as f64 / 1000.0), style),
+                Span::styled(result, if i == browser.selected { highlight } else { Style::default().fg(color) }),
+            ])
+        })
+        .collect();
+    let list = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(fill(text.runs_title, &[&browser.entry, &browser.runs.len()])),
+    );
+    f.render_widget(list, columns[0]);
+
+    let output = browser.runs.get(browser.selected).map_or("", |run| run.output.as_str());
+    let visible = (columns[1].height as usize).saturating_sub(2);
+    let output_lines: Vec<Line> = if output.is_empty() {
+        vec![Line::styled(text.runs_no_output, Style::default().fg(Color::DarkGray))]
+    } else {
+        let all: Vec<&str> = output.lines().collect();
+        all[all.len().saturating_sub(visible)..].iter().map(|l| Line::raw(*l)).collect()
+    };
+    let output = Paragraph::new(output_lines).block(Block::default().borders(Borders::ALL).title(text.runs_output));
+    f.render_widget(output, columns[1]);
+}
+
+/// How the sandbox differs from the live backend, with the picked changes checked
+fn draw_push_review(f: &mut Frame, app: &App, area: Rect) {
+    let (Some(review), Some(origin)) = (&app.push_review, app.storage.origin()) else {
+        return;
+    };
+    let redact = app.config.secrets.redact;
+    let mut lines = Vec::new();
+    let mut selected_line = 0;
+    for (i, (record, picked)) in review.changes.iter().zip(&review.picked).enumerate() {
+        let (marker, color) = match record.kind {
+            ChangeKind::Added => ("+", Color::Green),
+            ChangeKind::Removed => ("-", Color::Red),
+            ChangeKind::Changed => ("~", Color::Yellow),
+        };
+        let style = if i == review.selected { Style::default().fg(Color::Black).bg(Color::Cyan) } else { Style::default() };
+        if i == review.selected {
+            selected_line = lines.len();
+        }
+        lines.push(Line::from(vec![
+            Span::raw(if *picked { "[x] " } else { "[ ] " }),
+            Span::styled(format!("{} ", marker), Style::default().fg(color)),
+            Span::styled(record.entry.as_str(), style),
+        ]));
+        for change in &record.fields {
+            let show = |value: &str| if redact { secrets::redact(value) } else { value.to_string() };
+            lines.push(Line::styled(
+                format!("      {}: {} -> {}", change.field, show(&change.before), show(&change.after)),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+    }
+    let visible = (area.height as usize).saturating_sub(2).max(1);
+    let lines: Vec<Line> = lines.into_iter().skip((selected_line + 1).saturating_sub(visible)).take(visible).collect();
+    let title = fill(app.text.push_title, &[&origin.get_backend_name(), &review.changes.len()]);
+    f.render_widget(Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title)), area);
+}
+
+/// How the local file differs from the system scheduler: what a push
+/// would change there
+fn draw_sync_review(f: &mut Frame, app: &App, area: Rect) {
+    let (Some(review), Some(peer)) = (&app.sync_review, &app.sync_peer) else {
+        return;
+    };
+    let redact = app.config.secrets.redact;
+    let mut lines = Vec::new();
+    let mut selected_line = 0;
+    for (i, record) in review.changes.iter().enumerate() {
+        let (marker, color) = match record.kind {
+            ChangeKind::Added => ("+", Color::Green),
+            ChangeKind::Removed => ("-", Color::Red),
+            ChangeKind::Changed => ("~", Color::Yellow),
+        };
+        let style = if i == review.selected { Style::default().fg(Color::Black).bg(Color::Cyan) } else { Style::default() };
+        if i == review.selected {
+            selected_line = lines.len();
+        }
+        lines.push(Line::from(vec![
+            Span::styled(format!("{} ", marker), Style::default().fg(color)),
+            Span::styled(record.entry.as_str(), style),
+        ]));
+        for change in &record.fields {
+            let show = |value: &str| if redact { secrets::redact(value) } else { value.to_string() };
+            lines.push(Line::styled(
+                format!("      {}: {} -> {}", change.field, show(&change.before), show(&change.after)),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+    }
+    let visible = (area.height as usize).saturating_sub(2).max(1);
+    let lines: Vec<Line> = lines.into_iter().skip((selected_line + 1).saturating_sub(visible)).take(visible).collect();
+    let system = if peer.local { peer.storage.get_backend_name() } else { app.storage.get_backend_name() };
+    let title = fill(app.text.sync_title, &[&system, &review.changes.len()]);
+    f.render_widget(Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title)), area);
+}
+
+/// Changes made in `$EDITOR`, and the errors that keep them from being applied
+fn draw_bulk_edit(f: &mut Frame, app: &App, area: Rect) {
+    let Some(edit) = &app.bulk_edit else {
+        return;
+    };
+    let text = app.text;
+    let describe = |entry: &CronEntry| format!("{}  {}  {}", entry.name, entry.schedule, shown_command(app, &entry.command));
+    let mut lines = Vec::new();
+
+    if !edit.errors.is_empty() {
+        lines.push(Line::styled(text.bulk_edit_errors, Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)));
+        for error in &edit.errors {
+            lines.push(Line::styled(format!("  {}", error), Style::default().fg(Color::Red)));
+        }
+        lines.push(Line::raw(""));
+    }
+    for entry in &edit.entries {
+        let previous = app.entries.iter().find(|e| e.name == entry.name);
+        if edit.diff.added.contains(&entry.name) {
+            lines.push(Line::styled(format!("+ {}", describe(entry)), Style::default().fg(Color::Green)));
+        } else if edit.diff.changed.contains(&entry.name) {
+            lines.push(Line::styled(format!("~ {}", describe(entry)), Style::default().fg(Color::Yellow)));
+            if let Some(previous) = previous {
+                lines.push(Line::styled(format!("  {}", describe(previous)), Style::default().fg(Color::DarkGray)));
+            }
+        }
+    }
+    for entry in app.entries.iter().filter(|e| edit.diff.removed.contains(&e.name)) {
+        lines.push(Line::styled(format!("- {}", describe(entry)), Style::default().fg(Color::Red)));
+    }
+    if edit.diff.is_empty() {
+        lines.push(Line::styled(text.bulk_edit_unchanged, Style::default().fg(Color::DarkGray)));
+    }
+
+    let title = fill(text.bulk_edit_title, &[&edit.diff.added.len(), &edit.diff.removed.len(), &edit.diff.changed.len()]);
+    f.render_widget(Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title)), area);
+}
+
+/// Popup listing schedule presets, centered over `area`
+fn draw_preset_picker(f: &mut Frame, app: &App, area: Rect, selected: usize) {
+    let lines: Vec<Line> = suggest::PRESETS
+        .iter()
+        .enumerate()
+        .map(|(i, preset)| {
+            let style = if i == selected {
+                Style::default().fg(Color::Black).bg(Color::Cyan)
+            } else {
+                Style::default()
+            };
+            Line::styled(format!(" {:<14} {} ", preset.value, preset.description), style)
+        })
+        .collect();
+
+    draw_popup(f, area, lines, app.text.presets_title);
+}
+
+/// Schedules of a split shown in the conversion wizard before eliding the rest
+const SPLIT_PREVIEW: usize = 4;
+
+fn draw_conversion(f: &mut Frame, app: &App, area: Rect, wizard: &ConversionWizard) {
+    let text = app.text;
+    let highlight = |i: usize| {
+        if i == wizard.selected {
+            Style::default().fg(Color::Black).bg(Color::Cyan)
+        } else {
             Style::default()
-                .bg(Color::DarkGray)
-                .add_modifier(Modifier::BOLD)
+        }
+    };
+
+    let mut lines = vec![Line::styled(format!(" {} ", wizard.schedule), Style::default().fg(Color::Yellow))];
+    for (i, option) in wizard.options.iter().enumerate() {
+        match option {
+            Conversion::Split(schedules) => {
+                lines.push(Line::styled(format!(" {} ", fill(text.conversion_split, &[&schedules.len()])), highlight(i)));
+                let mut preview: Vec<&str> = schedules.iter().take(SPLIT_PREVIEW).map(String::as_str).collect();
+                if schedules.len() > SPLIT_PREVIEW {
+                    preview.push("…");
+                }
+                lines.push(Line::styled(format!("   {} ", preview.join(" | ")), Style::default().fg(Color::DarkGray)));
+            }
+            Conversion::Approximate(schedule) => {
+                lines.push(Line::styled(format!(" {} ", fill(text.conversion_approximate, &[schedule])), highlight(i)));
+            }
+        }
+    }
+    if wizard.can_keep {
+        lines.push(Line::styled(format!(" {} ", text.conversion_keep), highlight(wizard.options.len())));
+    }
+    draw_popup(f, area, lines, text.conversions_title);
+}
+
+/// Entries changed on both sides, with the fields that differ for the highlighted one
+fn draw_conflicts(f: &mut Frame, app: &App, area: Rect, resolver: &ConflictResolver) {
+    let text = app.text;
+    let mut lines = Vec::new();
+    for (i, conflict) in resolver.merge.conflicts.iter().enumerate() {
+        let style = if i == resolver.selected { Style::default().fg(Color::Black).bg(Color::Cyan) } else { Style::default() };
+        lines.push(Line::styled(format!(" {} ", conflict.name), style));
+        if i != resolver.selected {
+            continue;
+        }
+        let side = |label: &str, entry: &Option<CronEntry>| match entry {
+            Some(entry) => format!("   {}: {}  {}", label, entry.schedule, shown_command(app, &entry.command)),
+            None => format!("   {}: {}", label, text.conflict_deleted),
+        };
+        lines.push(Line::styled(side(text.conflict_yours, &conflict.yours), Style::default().fg(Color::Green)));
+        lines.push(Line::styled(side(text.conflict_theirs, &conflict.theirs), Style::default().fg(Color::Yellow)));
+        let fields = conflict.changed_fields();
+        if !fields.is_empty() {
+            lines.push(Line::styled(format!("   ≠ {} ", fields.join(", ")), Style::default().fg(Color::DarkGray)));
+        }
+    }
+    lines.push(Line::styled(format!(" {} ", text.conflicts_help), Style::default().fg(Color::DarkGray)));
+    draw_popup(f, area, lines, text.conflicts_title);
+}
+
+/// The entry about to be deleted, and whether it can be brought back
+fn draw_delete_confirmation(f: &mut Frame, app: &App, area: Rect) {
+    let Some(entry) = app.entries.get(app.selected_index) else {
+        return;
+    };
+    let text = app.text;
+    let command = if app.config.secrets.redact { secrets::redact(&entry.command) } else { entry.command.clone() };
+    let undo = match app.storage.trash() {
+        Some(trash) => fill(text.delete_to_trash, &[&trash.retention_days(), &app.config.keys.label(Action::Trash)]),
+        None => text.delete_for_good.to_string(),
+    };
+    let lines = vec![
+        Line::styled(format!(" {} ", entry.name), Style::default().add_modifier(Modifier::BOLD)),
+        Line::raw(format!(" {}  {} ", entry.schedule, command)),
+        Line::styled(format!(" {} ", undo), Style::default().fg(Color::DarkGray)),
+        Line::from(vec![
+            Span::styled(" y", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+            Span::raw(text.help_yes_delete),
+            Span::styled("n", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::raw(format!("{} ", text.help_no_keep)),
+        ]),
+    ];
+    draw_popup(f, area, lines, text.delete_title);
+}
+
+/// Taken names listed in the import popup before eliding the rest
+const IMPORT_NAMES_SHOWN: usize = 8;
+
+/// Imported names that are already in use, and what can be done with them
+fn draw_import_confirmation(f: &mut Frame, app: &App, area: Rect) {
+    let Some(imported) = &app.pending_import else {
+        return;
+    };
+    let text = app.text;
+    let taken = export::duplicates(&app.entries, imported);
+    let mut lines: Vec<Line> = taken.iter().take(IMPORT_NAMES_SHOWN).map(|name| Line::raw(format!(" {} ", name))).collect();
+    if taken.len() > IMPORT_NAMES_SHOWN {
+        let more = fill(text.import_more, &[&(taken.len() - IMPORT_NAMES_SHOWN)]);
+        lines.push(Line::styled(format!(" {} ", more), Style::default().fg(Color::DarkGray)));
+    }
+    lines.push(Line::from(vec![
+        Span::styled(" s", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+        Span::raw(text.help_skip_taken),
+        Span::styled("o", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+        Span::raw(text.help_overwrite_taken),
+        Span::styled("r", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+        Span::raw(text.help_rename_taken),
+        Span::styled("Esc", Style::default().fg(Color::Red)),
+        Span::raw(format!("{} ", text.help_cancel)),
+    ]));
+    draw_popup(f, area, lines, text.import_title);
+}
+
+/// Draw lines in a bordered box centered in `area`
+/// The command as shown, masked when `[secrets] redact` is on
+fn shown_command(app: &App, command: &str) -> String {
+    if app.config.secrets.redact { secrets::redact(command) } else { command.to_string() }
+}
+
+fn draw_popup(f: &mut Frame, area: Rect, lines: Vec<Line>, title: &str) {
+    let width = lines
+        .iter()
+        .map(|l| l.width() as u16 + 2)
+        .chain([Line::from(title).width() as u16 + 2])
+        .max()
+        .unwrap_or(0)
+        .min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    f.render_widget(Clear, popup);
+    f.render_widget(Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title)), popup);
+}
+
+fn draw_details(f: &mut Frame, app: &mut App, area: Rect) {
+    let warn_runs_per_day = app.config.frequency.warn_runs_per_day;
+    let text = app.text;
+    let (schedule_text, snoozed_until, tags, env, shell, stdin, skip_excluded, watchdog) = match app.entries.get(app.selected_index) {
+        Some(entry) => (
+            // Nicknames show the fields they stand for
+            match schedule::expand_nickname(&entry.schedule) {
+                expanded if expanded != entry.schedule => format!("{} ({})", entry.schedule, expanded),
+                _ => entry.schedule.clone(),
+            },
+            entry.snoozed_until,
+            entry.tags.join(", "),
+            entry.env.clone(),
+            entry.shell,
+            entry.stdin.as_ref().map(|input| input.replace('\n', " / ")),
+            entry.skip_excluded,
+            entry.watchdog,
+        ),
+        None => (String::new(), None, String::new(), None, None, None, false, false),
+    };
+    // Variable names only, since values may hold credentials
+    let (vars, cwd, user) = match app.entries.get(app.selected_index) {
+        Some(entry) => (
+            entry.env_vars.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>().join(", "),
+            entry.cwd.as_ref().map(|cwd| cwd.display().to_string()),
+            entry.user.clone(),
+        ),
+        None => (String::new(), None, None),
+    };
+    let hooks: Vec<&str> = match app.entries.get(app.selected_index) {
+        Some(entry) => [(&entry.on_success, text.hook_success), (&entry.on_failure, text.hook_failure)]
+            .into_iter()
+            .filter_map(|(hook, label)| hook.as_ref().map(|_| label))
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let now = app.clock.now();
+    let mut line = match app.selected_schedule_info() {
+        Some(info) if info.schedule.as_ref().is_ok_and(|schedule| schedule.reboot) => Line::from(vec![
+            Span::styled(text.label_schedule, Style::default().fg(Color::Cyan)),
+            Span::raw(format!("{} | ", schedule_text)),
+            Span::styled(text.label_next, Style::default().fg(Color::Cyan)),
+            Span::raw(text.at_startup),
+        ]),
+        Some(info) if info.schedule.is_ok() => {
+            let color = if info.frequency.peak_per_day > warn_runs_per_day {
+                Color::Yellow
+            } else {
+                Color::Reset
+            };
+            let next_run = match (&info.zone, info.next_run) {
+                (Ok(zone), Some(time)) => fill(
+                    text.next_run,
+                    &[
+                        &time.format("%Y-%m-%d %H:%M"),
+                        zone,
+                        &format_countdown(time.signed_duration_since(now)),
+                    ],
+                ),
+                (Ok(_), None) => text.never.to_string(),
+                (Err(e), _) => e.clone(),
+            };
+            Line::from(vec![
+                Span::styled(text.label_schedule, Style::default().fg(Color::Cyan)),
+                Span::raw(format!("{} | ", schedule_text)),
+                Span::styled(text.label_next, Style::default().fg(Color::Cyan)),
+                Span::raw(format!("{} | ", next_run)),
+                Span::styled(
+                    fill(text.frequency_summary, &[&info.frequency.next_day, &info.frequency.next_week]),
+                    Style::default().fg(color),
+                ),
+            ])
+        }
+        Some(_) => Line::from(vec![
+            Span::styled(text.label_schedule, Style::default().fg(Color::Cyan)),
+            Span::styled(fill(text.schedule_invalid, &[&schedule_text]), Style::default().fg(Color::Red)),
+        ]),
+        None => Line::from(text.no_entry_selected),
+    };
+    if !tags.is_empty() {
+        line.spans.push(Span::styled(fill(text.label_tags, &[&tags]), Style::default().fg(Color::Magenta)));
+    }
+    if let Some(env) = env {
+        line.spans.push(Span::styled(fill(text.label_env, &[&env]), Style::default().fg(Color::Magenta)));
+    }
+    if !vars.is_empty() {
+        line.spans.push(Span::styled(fill(text.label_vars, &[&vars]), Style::default().fg(Color::Magenta)));
+    }
+    if let Some(cwd) = cwd {
+        line.spans.push(Span::styled(fill(text.label_cwd, &[&cwd]), Style::default().fg(Color::Magenta)));
+    }
+    if let Some(user) = user {
+        line.spans.push(Span::styled(fill(text.label_user, &[&user]), Style::default().fg(Color::Magenta)));
+    }
+    if let Some(shell) = shell {
+        line.spans.push(Span::styled(fill(text.label_shell, &[&shell.name()]), Style::default().fg(Color::Magenta)));
+    }
+    if let Some(stdin) = stdin {
+        line.spans.push(Span::styled(fill(text.label_stdin, &[&stdin]), Style::default().fg(Color::Magenta)));
+    }
+    if !hooks.is_empty() {
+        line.spans.push(Span::styled(fill(text.label_hooks, &[&hooks.join(", ")]), Style::default().fg(Color::Magenta)));
+    }
+    if let Some(entry) = app.entries.get(app.selected_index) {
+        if let Some(timeout) = entry.timeout_secs {
+            line.spans.push(Span::styled(
+                fill(text.label_timeout, &[&cron_entry::format_seconds(timeout)]),
+                Style::default().fg(Color::Magenta),
+            ));
+        }
+        let retries = match (entry.retries, entry.retry_delay_secs) {
+            (0, _) => None,
+            (retries, 0) => Some(fill(text.label_retries, &[&retries])),
+            (retries, delay) => Some(fill(text.label_retries_delay, &[&retries, &cron_entry::format_seconds(delay)])),
+        };
+        if let Some(retries) = retries {
+            line.spans.push(Span::styled(retries, Style::default().fg(Color::Magenta)));
+        }
+        match (app.overdue_pings.get(&entry.name), &entry.ping_url) {
+            (Some(due), _) => line.spans.push(Span::styled(
+                fill(text.label_ping_overdue, &[&due.format("%Y-%m-%d %H:%M")]),
+                Style::default().fg(Color::Red),
+            )),
+            (None, Some(url)) => {
+                line.spans.push(Span::styled(fill(text.label_ping, &[&ping::redact(url)]), Style::default().fg(Color::Magenta)))
+            }
+            (None, None) => {}
+        }
+    }
+    if skip_excluded {
+        line.spans.push(Span::styled(text.label_skips_excluded, Style::default().fg(Color::Magenta)));
+    }
+    if watchdog {
+        line.spans.push(Span::styled(text.label_watchdog, Style::default().fg(Color::Magenta)));
+    }
+    if let Some(until) = snoozed_until {
+        line.spans.push(Span::styled(
+            fill(text.snoozed_until, &[&until.format("%Y-%m-%d %H:%M")]),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    if let Some(reason) = app.save_failures.get(&app.selected_index) {
+        line.spans.push(Span::styled(fill(text.not_registered, &[reason]), Style::default().fg(Color::Red)));
+    }
+
+    let mut lines = vec![line];
+    if let Some(entry) = app.entries.get(app.selected_index) {
+        lines.extend(
+            entry
+                .comments
+                .iter()
+                .map(|comment| Line::styled(format!("# {}", comment), Style::default().fg(Color::DarkGray))),
         );
+    }
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(text.details_title));
+    let stats = app.entries.get(app.selected_index).and_then(|entry| app.run_stats.get(&entry.name));
+    let Some(stats) = stats else {
+        f.render_widget(paragraph, area);
+        return;
+    };
 
-    f.render_widget(table, area);
+    // Daily success rate of recent runs, next to the details
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Length(run_history::STATS_DAYS as u16 + 2)])
+        .split(area);
+    f.render_widget(paragraph, columns[0]);
+    let color = match stats.success_rate() {
+        90.. => Color::Green,
+        50.. => Color::Yellow,
+        _ => Color::Red,
+    };
+    let title = fill(text.run_stats_title, &[&stats.success_rate(), &format_countdown(stats.average_duration)]);
+    let sparkline = Sparkline::default()
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .data(stats.daily_success.iter().copied())
+        .max(100)
+        .style(Style::default().fg(color));
+    f.render_widget(sparkline, columns[1]);
+}
+
+/// Compact time-until string: "2d 3h", "3h 05m", "4m 30s"
+fn format_countdown(remaining: chrono::TimeDelta) -> String {
+    let secs = remaining.num_seconds().max(0);
+    let (days, hours, minutes, seconds) = (secs / 86400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {:02}m", hours, minutes)
+    } else {
+        format!("{}m {:02}s", minutes, seconds)
+    }
 }
 
 fn draw_input_or_message(f: &mut Frame, app: &App, area: Rect) {
+    let mut scroll = 0;
     let text = if app.input_mode != InputMode::Normal {
-        let prompt = app.message.as_ref().map(|s| s.as_str()).unwrap_or("");
+        let prompt = app.message.as_deref().unwrap_or("");
+        let typed: String = app.input_buffer.chars().take(app.input_cursor()).collect();
+        // Scroll sideways to keep the cursor in view in long input
+        let column = Line::raw(format!("{} {}", prompt, typed)).width() as u16;
+        let width = area.width.saturating_sub(2);
+        if width > 0 && app.input_mode.takes_text() && app.preset_picker.is_none() && app.conversion.is_none() {
+            scroll = column.saturating_sub(width - 1);
+            f.set_cursor_position((area.x + 1 + column - scroll, area.y + 1));
+        }
         format!("{} {}", prompt, app.input_buffer)
     } else if let Some(msg) = &app.message {
         msg.clone()
     } else {
-        "Ready".to_string()
+        app.text.ready.to_string()
     };
 
     let style = if app.input_mode != InputMode::Normal {
-        Style::default().fg(Color::Yellow)
+        Style::default().fg(app.theme.prompt)
     } else {
-        Style::default().fg(Color::Green)
+        Style::default().fg(app.theme.status)
     };
 
     let paragraph = Paragraph::new(text)
         .style(style)
-        .block(Block::default().borders(Borders::ALL).title(" Status "));
+        .scroll((0, scroll))
+        .block(Block::default().borders(Borders::ALL).title(status_title(app)));
     f.render_widget(paragraph, area);
 }
 
+/// The status title, with a spinner while a save runs in the background
+fn status_title(app: &App) -> Line<'static> {
+    match app.save_spinner() {
+        Some(frame) => Line::from(vec![
+            Span::raw(app.text.status_title),
+            Span::styled(fill(app.text.saving, &[&frame]), Style::default().fg(Color::Yellow)),
+        ]),
+        None => Line::raw(app.text.status_title),
+    }
+}
+
 fn draw_help(f: &mut Frame, app: &App, area: Rect) {
-    let help_text = if app.input_mode != InputMode::Normal {
+    let text = app.text;
+    let help_text = if matches!(
+        app.input_mode,
+        InputMode::ViewingLog
+            | InputMode::ViewingSimulation
+            | InputMode::ViewingChanges
+            | InputMode::ViewingDryRun
+            | InputMode::ViewingRun
+    ) {
+        let close = match app.input_mode {
+            InputMode::ViewingLog => text.help_close_log,
+            InputMode::ViewingRun => text.help_stop_run,
+            _ => text.help_close_view,
+        };
+        let mut spans = vec![
+            Span::styled("↑/↓ PgUp/PgDn", Style::default().fg(Color::Cyan)),
+            Span::raw(text.help_scroll),
+        ];
+        if app.input_mode == InputMode::ViewingLog {
+            spans.extend([
+                Span::styled("/", Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_search_log),
+                Span::styled("n/N", Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_next_match),
+            ]);
+        }
+        spans.extend([
+            Span::styled("Esc", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+            Span::raw(close),
+        ]);
+        vec![Line::from(spans)]
+    } else if app.input_mode == InputMode::ViewingCalendar {
+        vec![Line::from(vec![
+            Span::styled("←/→", Style::default().fg(Color::Cyan)),
+            Span::raw(text.help_calendar_shift),
+            Span::styled("w/m", Style::default().fg(Color::Cyan)),
+            Span::raw(text.help_calendar_span),
+            Span::styled("t", Style::default().fg(Color::Cyan)),
+            Span::raw(text.help_calendar_today),
+            Span::styled("↑/↓", Style::default().fg(Color::Cyan)),
+            Span::raw(text.help_scroll),
+            Span::styled("Tab/Esc", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+            Span::raw(text.help_close_view),
+        ])]
+    } else if app.input_mode == InputMode::BrowsingScripts {
         vec![
             Line::from(vec![
+                Span::styled("↑/↓", Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_navigate),
                 Span::styled("Enter", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
-                Span::raw(": Confirm | "),
+                Span::raw(text.help_use_script),
+                Span::styled("e", Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_edit),
+                Span::styled("n", Style::default().fg(Color::Green)),
+                Span::raw(text.help_new),
+                Span::styled("d", Style::default().fg(Color::Red)),
+                Span::raw(text.help_delete),
                 Span::styled("Esc", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
-                Span::raw(": Cancel"),
+                Span::raw(text.help_close_view),
             ])
         ]
-    } else {
+    } else if app.input_mode == InputMode::BrowsingBackups {
+        let opened = app.backup_browser.as_ref().is_some_and(|b| b.opened.is_some());
+        let mut spans = vec![
+            Span::styled("↑/↓", Style::default().fg(Color::Cyan)),
+            Span::raw(text.help_navigate),
+            Span::styled("Enter", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::raw(if opened { text.help_restore_entry } else { text.help_open_backup }),
+        ];
+        if !opened {
+            spans.extend([
+                Span::styled("d", Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_diff_current),
+                Span::styled("D", Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_diff_previous),
+                Span::styled("r", Style::default().fg(Color::Yellow)),
+                Span::raw(text.help_rollback),
+            ]);
+        }
+        spans.extend([
+            Span::styled("Esc", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+            Span::raw(if opened { text.help_back } else { text.help_close_view }),
+        ]);
+        vec![Line::from(spans)]
+    } else if app.input_mode == InputMode::BrowsingTrash {
         vec![
             Line::from(vec![
                 Span::styled("↑/↓", Style::default().fg(Color::Cyan)),
-                Span::raw(": Navigate | "),
-                Span::styled("a", Style::default().fg(Color::Green)),
-                Span::raw(": Add | "),
+                Span::raw(text.help_navigate),
+                Span::styled("Enter", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+                Span::raw(text.help_restore_entry),
                 Span::styled("d", Style::default().fg(Color::Red)),
-                Span::raw(": Delete | "),
+                Span::raw(text.help_delete_forever),
+                Span::styled("Esc", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+                Span::raw(text.help_close_view),
+            ])
+        ]
+    } else if app.input_mode == InputMode::ConfirmDelete {
+        vec![
+            Line::from(vec![
+                Span::styled("y", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+                Span::raw(text.help_yes_delete),
+                Span::styled("n/Esc", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+                Span::raw(text.help_no_keep),
+            ])
+        ]
+    } else if app.input_mode == InputMode::ConfirmImport {
+        vec![
+            Line::from(vec![
+                Span::styled("s", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+                Span::raw(text.help_skip_taken),
+                Span::styled("o", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+                Span::raw(text.help_overwrite_taken),
+                Span::styled("r", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+                Span::raw(text.help_rename_taken),
+                Span::styled("Esc", Style::default().fg(Color::Red)),
+                Span::raw(text.help_cancel),
+            ])
+        ]
+    } else if app.input_mode == InputMode::BrowsingRuns {
+        vec![
+            Line::from(vec![
+                Span::styled("↑/↓", Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_navigate),
+                Span::styled("Esc", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+                Span::raw(text.help_close_view),
+            ])
+        ]
+    } else if app.input_mode == InputMode::ReviewingPush {
+        vec![
+            Line::from(vec![
+                Span::styled("↑/↓", Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_navigate),
                 Span::styled("Space", Style::default().fg(Color::Yellow)),
-                Span::raw(": Toggle Enable/Disable"),
-            ]),
+                Span::raw(text.help_pick),
+                Span::styled("Enter", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+                Span::raw(text.help_push),
+                Span::styled("Esc", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+                Span::raw(text.help_close_view),
+            ])
+        ]
+    } else if app.input_mode == InputMode::ReviewingSync {
+        vec![
+            Line::from(vec![
+                Span::styled("↑/↓", Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_navigate),
+                Span::styled("p", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+                Span::raw(text.help_sync_push),
+                Span::styled("u", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+                Span::raw(text.help_sync_pull),
+                Span::styled("m", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+                Span::raw(text.help_sync_merge),
+                Span::styled("Esc", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+                Span::raw(text.help_close_view),
+            ])
+        ]
+    } else if app.input_mode == InputMode::ReviewingEdit {
+        vec![
+            Line::from(vec![
+                Span::styled("Enter", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+                Span::raw(text.help_apply),
+                Span::styled("e", Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_edit_again),
+                Span::styled("Esc", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+                Span::raw(text.help_discard),
+            ])
+        ]
+    } else if app.input_mode != InputMode::Normal {
+        let mut first = vec![
+            Span::styled("Enter", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::raw(text.help_confirm),
+        ];
+        if matches!(app.input_mode, InputMode::AddingSchedule | InputMode::EditingSchedule) {
+            first.push(Span::styled("Ctrl+P", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)));
+            first.push(Span::raw(text.help_presets));
+        }
+        first.push(Span::styled("Esc", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)));
+        first.push(Span::raw(text.help_cancel));
+        let mut lines = vec![Line::from(first)];
+        if let Some((suggestions, selected)) = app.command_suggestions() {
+            let mut spans = vec![
+                Span::styled("Tab", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+                Span::raw(text.help_history),
+            ];
+            for (i, command) in suggestions.iter().enumerate() {
+                let style = if selected == Some(i) {
+                    Style::default().fg(Color::Black).bg(Color::Cyan)
+                } else {
+                    Style::default().fg(Color::Cyan)
+                };
+                spans.push(Span::styled(*command, style));
+                spans.push(Span::raw("  "));
+            }
+            lines.push(Line::from(spans));
+        }
+        if let Some((kind, suggestions, selected)) = app.schedule_suggestions() {
+            let (min, max) = kind.bounds();
+            let mut spans = vec![
+                Span::styled("Tab", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+                Span::raw(format!(": {} ({}-{}) ", kind.name(), min, max)),
+            ];
+            for (i, suggestion) in suggestions.iter().enumerate() {
+                let style = if selected == Some(i) {
+                    Style::default().fg(Color::Black).bg(Color::Cyan)
+                } else {
+                    Style::default().fg(Color::Cyan)
+                };
+                spans.push(Span::styled(suggestion.value, style));
+                spans.push(Span::raw(format!(" {}  ", suggestion.description)));
+            }
+            lines.push(Line::from(spans));
+        }
+        lines
+    } else {
+        let keys = &app.config.keys;
+        let mut first = vec![
+            Span::styled("↑/↓", Style::default().fg(Color::Cyan)),
+            Span::raw(text.help_navigate),
+            Span::styled(format!("{}/{}", keys.label(Action::MoveUp), keys.label(Action::MoveDown)), Style::default().fg(Color::Cyan)),
+            Span::raw(text.help_move),
+            Span::styled(keys.label(Action::Add), Style::default().fg(Color::Green)),
+            Span::raw(text.help_add),
+            Span::styled(keys.label(Action::Delete), Style::default().fg(Color::Red)),
+            Span::raw(text.help_delete),
+            Span::styled(keys.label(Action::BulkToggle), Style::default().fg(Color::Yellow)),
+            Span::raw(text.help_bulk),
+            Span::styled(keys.label(Action::Toggle), Style::default().fg(Color::Yellow)),
+            Span::raw(text.help_toggle),
+        ];
+        // User-defined macros follow the built-in keys
+        for binding in &app.config.macros {
+            first.push(Span::styled(binding.key.to_string(), Style::default().fg(Color::Magenta)));
+            first.push(Span::raw(format!(": {}  ", binding.label())));
+        }
+        vec![
+            Line::from(first),
             Line::from(vec![
-                Span::styled("n", Style::default().fg(Color::Cyan)),
-                Span::raw(": Edit Name | "),
-                Span::styled("s", Style::default().fg(Color::Cyan)),
-                Span::raw(": Edit Schedule | "),
-                Span::styled("c", Style::default().fg(Color::Cyan)),
-                Span::raw(": Edit Command | "),
-                Span::styled("q", Style::default().fg(Color::Red)),
-                Span::raw(": Quit"),
+                Span::styled(keys.label(Action::EditName), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_edit_name),
+                Span::styled(keys.label(Action::EditSchedule), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_edit_schedule),
+                Span::styled(keys.label(Action::EditCommand), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_edit_command),
+                Span::styled(keys.label(Action::EditTimezone), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_timezone),
+                Span::styled(keys.label(Action::EditTags), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_tags),
+                Span::styled(keys.label(Action::EditEnv), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_env),
+                Span::styled(keys.label(Action::EditShell), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_shell),
+                Span::styled(keys.label(Action::Snooze), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_snooze),
+                Span::styled(keys.label(Action::SkipExcluded), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_skip_excluded),
+                Span::styled(keys.label(Action::EditHooks), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_hooks),
+                Span::styled(keys.label(Action::EditLimits), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_limits),
+                Span::styled(keys.label(Action::EditPing), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_ping),
+                Span::styled(keys.label(Action::Watchdog), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_watchdog),
+                Span::styled(keys.label(Action::BulkEdit), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_bulk_edit),
+                Span::styled(keys.label(Action::Quartz), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_quartz),
+                Span::styled(keys.label(Action::Log), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_log),
+                Span::styled(keys.label(Action::LogInPager), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_pager),
+                Span::styled(keys.label(Action::Upcoming), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_simulate),
+                Span::styled(keys.label(Action::Calendar), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_calendar),
+                Span::styled(keys.label(Action::RunNow), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_run_now),
+                Span::styled(keys.label(Action::Runs), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_runs),
+                Span::styled(keys.label(Action::Import), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_import),
+                Span::styled(keys.label(Action::Scripts), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_scripts),
+                Span::styled(keys.label(Action::Backups), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_backups),
+                Span::styled(keys.label(Action::EditComments), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_comments),
+                Span::styled(keys.label(Action::Changes), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_changes),
+                Span::styled(keys.label(Action::DryRun), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_dry_run),
+                Span::styled(keys.label(Action::Sync), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_sync),
+                Span::styled(keys.label(Action::Trash), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_trash),
+                Span::styled(keys.label(Action::Sandbox), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_sandbox),
+                Span::styled(keys.label(Action::TagFilter), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_tag_filter),
+                Span::styled(keys.label(Action::GroupByTag), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_group_by_tag),
+                Span::styled(keys.label(Action::Preview), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_preview),
+                Span::styled(keys.label(Action::Theme), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_theme),
+                Span::styled(keys.label(Action::Write), Style::default().fg(Color::Cyan)),
+                Span::raw(text.help_write),
+                Span::styled(keys.label(Action::Quit), Style::default().fg(Color::Red)),
+                Span::raw(text.help_quit),
             ]),
         ]
     };
 
     let paragraph = Paragraph::new(help_text)
-        .block(Block::default().borders(Borders::ALL).title(" Controls "));
+        .block(Block::default().borders(Borders::ALL).title(text.controls_title));
     f.render_widget(paragraph, area);
 }
@@ -0,0 +1,123 @@
+use crate::cron_entry::CronEntry;
+use crate::hooks::ENTRY_VAR;
+use crate::schedule::timezone::ScheduleZone;
+use crate::schedule::CronSchedule;
+use crate::secrets::shell_quote;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use std::process::Command;
+
+/// Runs recorded longer ago than this aren't looked at
+pub const LOOKBACK_DAYS: i64 = 400;
+
+/// Environment variable holding the time the missed run was due in alerts
+pub const DUE_AT_VAR: &str = "CRON_MANAGER_DUE_AT";
+
+/// The generated entry that checks, on `schedule`, that `entry` keeps running.
+/// It carries everything the check needs, so it never has to load the
+/// backend the entries live in.
+pub fn companion(entry: &CronEntry, schedule: &str) -> Result<CronEntry> {
+    let exe = std::env::current_exe().context("Failed to locate the cron-manager executable")?;
+    let mut command = format!(
+        "{} watchdog --name {} --schedule {}",
+        shell_quote(&exe.to_string_lossy()),
+        shell_quote(&entry.name),
+        shell_quote(&entry.schedule)
+    );
+    if let Some(tz) = &entry.timezone {
+        command.push_str(&format!(" --tz {}", shell_quote(tz)));
+    }
+    if entry.skip_excluded {
+        command.push_str(" --skip-excluded");
+    }
+
+    let mut companion = CronEntry::new(format!("{} (watchdog)", entry.name), schedule.to_string(), command);
+    companion.enabled = entry.enabled;
+    companion.watchdog_for = Some(entry.name.clone());
+    Ok(companion)
+}
+
+/// The first run after `last_run` that is more than `grace` overdue at `now`,
+/// if any. Runs for which `skipped` holds, e.g. on excluded dates, aren't
+/// expected to happen.
+pub fn missed_run(
+    schedule: &CronSchedule,
+    zone: ScheduleZone,
+    last_run: DateTime<Utc>,
+    now: DateTime<Utc>,
+    grace: Duration,
+    skipped: impl Fn(DateTime<Utc>) -> bool,
+) -> Option<DateTime<FixedOffset>> {
+    let mut after = last_run;
+    loop {
+        let due = *zone.next_runs(schedule, after, 1).first()?;
+        if due + grace > now {
+            return None;
+        }
+        after = due.with_timezone(&Utc);
+        if !skipped(after) {
+            return Some(due);
+        }
+    }
+}
+
+/// Run the configured alert command through `sh`. Failures are reported but
+/// don't hide the alert itself.
+pub fn run_alert(command: &str, entry: &str, due_at: DateTime<FixedOffset>) {
+    let result = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env(ENTRY_VAR, entry)
+        .env(DUE_AT_VAR, due_at.to_rfc3339())
+        .status();
+    match result {
+        Ok(status) if !status.success() => eprintln!("Alert command failed ({}): {}", status, command),
+        Ok(_) => {}
+        Err(e) => eprintln!("Failed to start alert command '{}': {}", command, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, TimeZone};
+
+    fn at(hour: u32, minute: u32, day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, day, hour, minute, 5).unwrap()
+    }
+
+    #[test]
+    fn test_missed_run() {
+        let schedule = CronSchedule::parse("0 2 * * *").unwrap();
+        let zone = ScheduleZone::Named(chrono_tz::UTC);
+        let grace = Duration::minutes(15);
+        let check = |now, skipped: &dyn Fn(DateTime<Utc>) -> bool| {
+            missed_run(&schedule, zone, at(2, 0, 10), now, grace, skipped)
+        };
+
+        // Still within the grace period of the next run
+        assert_eq!(check(at(2, 10, 11), &|_| false), None);
+        let missed = check(at(2, 20, 11), &|_| false).unwrap();
+        assert_eq!(missed.with_timezone(&Utc), Utc.with_ymd_and_hms(2026, 3, 11, 2, 0, 0).unwrap());
+
+        // A run skipped on an excluded date isn't missed, the one after is
+        let excluded = |time: DateTime<Utc>| time.day() == 11;
+        assert_eq!(check(at(2, 20, 11), &excluded), None);
+        assert_eq!(check(at(3, 0, 12), &excluded).unwrap().day(), 12);
+    }
+
+    #[test]
+    fn test_companion() {
+        let mut entry = CronEntry::new("Nightly backup".to_string(), "0 2 * * *".to_string(), "/bin/backup".to_string());
+        entry.timezone = Some("Asia/Tokyo".to_string());
+        entry.enabled = false;
+
+        let companion = companion(&entry, "7 * * * *").unwrap();
+        assert_eq!(companion.name, "Nightly backup (watchdog)");
+        assert_eq!(companion.watchdog_for.as_deref(), Some("Nightly backup"));
+        assert!(!companion.enabled);
+        assert!(companion
+            .command
+            .ends_with(" watchdog --name 'Nightly backup' --schedule '0 2 * * *' --tz Asia/Tokyo"));
+    }
+}
@@ -0,0 +1,524 @@
+use cron_manager::app::{App, BackupDiff, InputMode};
+use cron_manager::audit::ChangeKind;
+use cron_manager::backup::BackupStore;
+use cron_manager::clock::Clock;
+use cron_manager::config::{BackendKind, Config};
+use cron_manager::conflict::Resolution;
+use cron_manager::cron_entry::CronEntry;
+use cron_manager::macros::Macro;
+use cron_manager::paths;
+use cron_manager::run_history::{RunHistory, RunRecord};
+use cron_manager::scheduler::mock::MockScheduler;
+use cron_manager::scheduler::Scheduler;
+use cron_manager::storage::Storage;
+
+fn entry(name: &str, enabled: bool) -> CronEntry {
+    let mut entry = CronEntry::new(name.to_string(), "0 * * * *".to_string(), "/bin/true > /dev/null".to_string());
+    entry.enabled = enabled;
+    entry
+}
+
+/// An app over the mock that writes every change immediately
+fn app(mock: &MockScheduler) -> App {
+    let config = Config { save_debounce_ms: 0, ..Config::default() };
+    App::new(Storage::with_scheduler(Box::new(mock.clone())), config).unwrap()
+}
+
+#[test]
+fn test_storage_loads_and_records_saves() {
+    let mock = MockScheduler::with_entries(vec![entry("Backup", true)]);
+    let storage = Storage::with_scheduler(Box::new(mock.clone()));
+
+    let mut entries = storage.load().unwrap();
+    assert_eq!(entries.len(), 1);
+    entries.push(entry("Report", false));
+    let report = storage.save(&entries).unwrap();
+
+    assert!(report.is_complete());
+    assert_eq!(report.applied, [0, 1]);
+    assert_eq!(mock.saves().len(), 1);
+    assert_eq!(mock.entries().len(), 2);
+}
+
+#[test]
+fn test_single_entry_operations_default_to_saves() {
+    let mock = MockScheduler::with_entries(vec![entry("Backup", true)]);
+
+    mock.add(&entry("Report", true)).unwrap();
+    mock.update(&entry("Backup", true), &entry("Backup", false)).unwrap();
+    assert!(mock.update(&entry("Missing", true), &entry("Missing", false)).is_err());
+    let names: Vec<(String, bool)> = mock.entries().into_iter().map(|e| (e.name, e.enabled)).collect();
+    assert_eq!(names, [("Backup".to_string(), false), ("Report".to_string(), true)]);
+
+    mock.reject_entry("Cleanup", "no agent");
+    assert!(mock.add(&entry("Cleanup", true)).unwrap_err().to_string().contains("no agent"));
+    mock.remove(&entry("Backup", false)).unwrap();
+    assert!(mock.remove(&entry("Backup", false)).is_err());
+    assert_eq!(mock.saves().len(), 4);
+}
+
+#[test]
+fn test_app_changes_reach_the_scheduler() {
+    let mock = MockScheduler::with_entries(vec![entry("Backup", true)]);
+    let mut app = app(&mock);
+
+    app.toggle_enabled().unwrap();
+    assert!(!mock.entries()[0].enabled);
+    app.delete_entry().unwrap();
+    assert!(mock.entries().is_empty());
+    assert_eq!(mock.saves().len(), 2);
+}
+
+#[test]
+fn test_delete_asks_first() {
+    let mock = MockScheduler::with_entries(vec![entry("Backup", true)]);
+    let mut app = app(&mock);
+
+    app.request_delete().unwrap();
+    assert_eq!(app.input_mode, InputMode::ConfirmDelete);
+    app.confirm_delete(false).unwrap();
+    assert_eq!(app.input_mode, InputMode::Normal);
+    assert_eq!(mock.entries().len(), 1);
+
+    app.request_delete().unwrap();
+    app.confirm_delete(true).unwrap();
+    assert!(mock.entries().is_empty());
+
+    // With the confirmation turned off, `d` deletes at once
+    let mock = MockScheduler::with_entries(vec![entry("Backup", true)]);
+    let config = Config { save_debounce_ms: 0, confirm_delete: false, ..Config::default() };
+    let mut app = App::new(Storage::with_scheduler(Box::new(mock.clone())), config).unwrap();
+    app.request_delete().unwrap();
+    assert_eq!(app.input_mode, InputMode::Normal);
+    assert!(mock.entries().is_empty());
+}
+
+#[test]
+fn test_rejected_entries_are_marked_in_the_app() {
+    let mock = MockScheduler::with_entries(vec![entry("Backup", false)]);
+    mock.reject_entry("Backup", "Load failed: 5: Input/output error");
+    let mut app = app(&mock);
+
+    app.toggle_enabled().unwrap();
+    assert_eq!(app.save_failures.get(&0).map(String::as_str), Some("Load failed: 5: Input/output error"));
+}
+
+#[test]
+fn test_scheduler_failures_are_errors() {
+    let mock = MockScheduler::with_entries(vec![entry("Backup", true)]);
+    let mut app = app(&mock);
+    mock.fail_save("crontab: permission denied");
+    assert!(app.toggle_enabled().is_err());
+    assert!(mock.saves().is_empty());
+
+    mock.fail_load("crontab: not installed");
+    let config = Config { save_debounce_ms: 0, ..Config::default() };
+    assert!(App::new(Storage::with_scheduler(Box::new(mock)), config).is_err());
+}
+
+#[test]
+fn test_debounced_saves_run_in_the_background() {
+    let mock = MockScheduler::with_entries(vec![entry("Backup", true)]);
+    let config = Config { save_debounce_ms: 60_000, ..Config::default() };
+    let mut app = App::new(Storage::with_scheduler(Box::new(mock.clone())), config).unwrap();
+
+    app.toggle_enabled().unwrap();
+    assert!(mock.saves().is_empty());
+    assert!(app.save_deadline().is_some());
+
+    app.start_save();
+    assert!(app.save_deadline().is_none());
+    assert!(app.has_unsaved_changes());
+    let result = loop {
+        if let Some(result) = app.poll_save() {
+            break result;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    };
+    result.unwrap();
+    assert!(app.save_spinner().is_none());
+    assert!(!app.has_unsaved_changes());
+    assert_eq!(mock.saves().len(), 1);
+    assert!(!mock.entries()[0].enabled);
+}
+
+#[test]
+fn test_background_save_failures_follow_the_entries() {
+    let mock = MockScheduler::with_entries(vec![entry("Backup", true), entry("Report", false)]);
+    mock.reject_entry("Report", "launchctl: bootstrap failed");
+    let config = Config { save_debounce_ms: 60_000, confirm_delete: false, ..Config::default() };
+    let mut app = App::new(Storage::with_scheduler(Box::new(mock.clone())), config).unwrap();
+
+    app.move_selection_down();
+    app.toggle_enabled().unwrap();
+    app.start_save();
+    // Backup is deleted while the save runs, so Report moves up
+    app.move_selection_up();
+    app.request_delete().unwrap();
+    while app.poll_save().is_none() {
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    assert_eq!(app.entries[0].name, "Report");
+    assert_eq!(app.save_failures.keys().collect::<Vec<_>>(), [&0]);
+}
+
+#[test]
+fn test_a_single_edit_reports_only_that_entry() {
+    let mock = MockScheduler::with_entries(vec![entry("Backup", true), entry("Report", true)]);
+    mock.reject_entry("Report", "launchctl: bootstrap failed");
+    let mut app = app(&mock);
+
+    // Only Backup goes to the backend, so Report's refusal isn't news
+    app.toggle_enabled().unwrap();
+    assert!(app.save_failures.is_empty());
+    assert!(!mock.entries()[0].enabled);
+
+    // Report's refusal shows once it is saved again
+    app.move_selection_down();
+    app.toggle_enabled().unwrap();
+    app.toggle_enabled().unwrap();
+    assert_eq!(app.save_failures.get(&1).map(String::as_str), Some("launchctl: bootstrap failed"));
+}
+
+#[test]
+fn test_a_burst_of_edits_is_written_once() {
+    let entries = (0..5).map(|i| entry(&format!("Job {}", i), true)).collect();
+    let mock = MockScheduler::with_entries(entries);
+    let config = Config { save_debounce_ms: 60_000, ..Config::default() };
+    let mut app = App::new(Storage::with_scheduler(Box::new(mock.clone())), config).unwrap();
+
+    app.write_now();
+    assert!(!app.has_unsaved_changes());
+    for _ in 0..5 {
+        app.toggle_enabled().unwrap();
+        app.move_selection_down();
+    }
+    assert!(mock.saves().is_empty());
+
+    app.write_now();
+    while app.poll_save().is_none() {
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    assert_eq!(mock.saves().len(), 1);
+    assert!(mock.entries().iter().all(|entry| !entry.enabled));
+}
+
+#[test]
+fn test_bulk_edit_is_reviewed_before_applying() {
+    let mock = MockScheduler::with_entries(vec![entry("Backup", true), entry("Report", true)]);
+    let mut app = app(&mock);
+
+    app.start_bulk_edit();
+    let path = app.editor_request.take().unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    std::fs::write(&path, text.replace("0 * * * *", "0 99 * * *")).unwrap();
+    app.editor_closed().unwrap();
+    assert_eq!(app.input_mode, InputMode::ReviewingEdit);
+    assert_eq!(app.bulk_edit.as_ref().unwrap().errors.len(), 2);
+    app.apply_bulk_edit().unwrap();
+    assert!(mock.saves().is_empty());
+
+    std::fs::write(&path, text.replace("# NAME: Report\n0 * * * * /bin/true > /dev/null\n", "")).unwrap();
+    app.editor_closed().unwrap();
+    assert_eq!(app.bulk_edit.as_ref().unwrap().diff.removed, ["Report"]);
+    app.apply_bulk_edit().unwrap();
+    assert_eq!(app.input_mode, InputMode::Normal);
+    assert_eq!(mock.entries().len(), 1);
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_comments_are_edited_in_editor() {
+    let mock = MockScheduler::with_entries(vec![entry("Backup", true)]);
+    let mut app = app(&mock);
+
+    app.edit_comments();
+    let path = app.editor_request.take().unwrap();
+    std::fs::write(&path, "\nKeep in sync with the DB maintenance window\n\n").unwrap();
+    app.editor_closed().unwrap();
+    assert_eq!(mock.entries()[0].comments, ["Keep in sync with the DB maintenance window"]);
+    assert!(!path.exists());
+
+    // A line that would read back as a job is refused
+    app.edit_comments();
+    let path = app.editor_request.take().unwrap();
+    std::fs::write(&path, "0 2 * * * /bin/old-backup\n").unwrap();
+    app.editor_closed().unwrap();
+    assert_eq!(mock.entries()[0].comments.len(), 1);
+}
+
+#[test]
+fn test_concurrent_changes_are_merged_or_resolved() {
+    let mock = MockScheduler::with_entries(vec![entry("Backup", true), entry("Report", true)]);
+    let mut app = app(&mock);
+
+    // Someone else disables Report and adds Sync; our change to Backup merges with theirs
+    mock.set_entries(vec![entry("Backup", true), entry("Report", false), entry("Sync", true)]);
+    app.toggle_enabled().unwrap();
+    let saved = mock.entries();
+    assert_eq!(saved.iter().map(|e| (e.name.as_str(), e.enabled)).collect::<Vec<_>>(), [("Backup", false), ("Report", false), ("Sync", true)]);
+    assert!(app.conflict_resolver.is_none());
+
+    // Both sides change Backup: nothing is written until it is resolved
+    let mut theirs = mock.entries();
+    theirs[0].command = "/bin/backup --fast".to_string();
+    mock.set_entries(theirs);
+    app.selected_index = 0;
+    app.toggle_enabled().unwrap();
+    assert_eq!(app.conflict_resolver.as_ref().map(|r| r.merge.conflicts.len()), Some(1));
+    assert!(!mock.entries()[0].enabled);
+
+    app.resolve_conflict(Resolution::Merged).unwrap();
+    assert!(app.conflict_resolver.is_none());
+    assert!(mock.entries()[0].enabled);
+    assert_eq!(mock.entries()[0].command, "/bin/backup --fast");
+}
+
+#[test]
+fn test_sandbox_pushes_picked_changes() {
+    let data_dir = std::env::temp_dir().join(format!("cron-manager-sandbox-{}", std::process::id()));
+    let config = Config { save_debounce_ms: 0, data_dir: Some(data_dir.clone()), ..Config::default() };
+    let mock = MockScheduler::with_entries(vec![entry("Backup", true), entry("Report", true)]);
+    let sandbox = Storage::sandbox(Storage::with_scheduler(Box::new(mock.clone())), &config).unwrap();
+    sandbox.save(&mock.entries()).unwrap();
+    let mut app = App::new(sandbox, config).unwrap();
+
+    // Changes in the sandbox never reach the live backend on their own
+    app.toggle_enabled().unwrap();
+    app.selected_index = 1;
+    app.delete_entry().unwrap();
+    assert!(mock.saves().is_empty());
+
+    app.start_sandbox().unwrap();
+    assert_eq!(app.input_mode, InputMode::ReviewingPush);
+    assert_eq!(app.push_review.as_ref().map(|r| r.changes.len()), Some(2));
+
+    // Push only the first change; the other stays listed
+    app.toggle_push_pick();
+    app.push_picked().unwrap();
+    assert!(app.origin_changed);
+    assert_eq!(mock.entries().iter().map(|e| (e.name.as_str(), e.enabled)).collect::<Vec<_>>(), [("Backup", false), ("Report", true)]);
+    assert_eq!(app.push_review.as_ref().map(|r| r.changes.len()), Some(1));
+
+    app.push_picked().unwrap();
+    assert_eq!(mock.entries().len(), 1);
+    assert!(app.push_review.is_none());
+    std::fs::remove_dir_all(data_dir).ok();
+}
+
+#[test]
+fn test_timeouts_and_retries_are_encoded_in_the_command() {
+    let mock = MockScheduler::new();
+    let storage = Storage::with_scheduler(Box::new(mock.clone()));
+    let mut timed = entry("Sync", true);
+    timed.timeout_secs = Some(300);
+    let mut retried = entry("Fetch", true);
+    retried.timeout_secs = Some(60);
+    retried.retries = 2;
+    retried.retry_delay_secs = 30;
+    storage.save(&[timed, retried]).unwrap();
+
+    let saved = mock.entries();
+    assert_eq!(saved[0].command, "timeout 300 sh -c '/bin/true > /dev/null'");
+    assert_eq!(saved[0].command_template.as_deref(), Some("/bin/true > /dev/null"));
+    // Retries need `exec`, which enforces the timeout itself
+    assert!(saved[1].command.contains(" exec --name Fetch --timeout 60 --retries 2 --retry-delay 30 -- "));
+    assert!(!saved[1].command.contains("timeout 60 sh"));
+}
+
+#[test]
+fn test_ping_url_wraps_the_command_in_exec() {
+    let mock = MockScheduler::new();
+    let storage = Storage::with_scheduler(Box::new(mock.clone()));
+    let mut pinged = entry("Backup", true);
+    pinged.ping_url = Some("https://hc-ping.com/0f1e2d3c".to_string());
+    storage.save(&[pinged]).unwrap();
+
+    let saved = mock.entries();
+    assert!(saved[0].command.contains(" exec --name Backup --ping https://hc-ping.com/0f1e2d3c -- "));
+    assert_eq!(saved[0].ping_url.as_deref(), Some("https://hc-ping.com/0f1e2d3c"));
+}
+
+#[test]
+fn test_a_new_minute_refreshes_the_run_stats_in_the_background() {
+    let dir = std::env::temp_dir().join(format!("cron-manager-refresh-{}", std::process::id()));
+    let mock = MockScheduler::with_entries(vec![entry("Backup", true)]);
+    let config = Config { save_debounce_ms: 0, data_dir: Some(dir.clone()), ..Config::default() };
+    // The minute rolls over right after the app starts
+    let next_minute = chrono::Utc::now().timestamp() / 60 * 60 + 60;
+    let start = chrono::DateTime::from_timestamp(next_minute, 0).unwrap() - chrono::Duration::milliseconds(200);
+    let mut app = App::with_clock(Storage::with_scheduler(Box::new(mock)), config, Clock::starting_at(start)).unwrap();
+    assert!(app.run_stats.is_empty());
+
+    let record = RunRecord {
+        entry: "Backup".to_string(),
+        started_at: chrono::Utc::now(),
+        duration_ms: 10,
+        exit_code: Some(0),
+        output: String::new(),
+    };
+    RunHistory::new(RunHistory::default_path(Some(&dir)).unwrap()).append(&record).unwrap();
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    while !app.run_stats.contains_key("Backup") && std::time::Instant::now() < deadline {
+        app.on_tick().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+    assert!(app.run_stats.contains_key("Backup"));
+}
+
+#[test]
+fn test_macro_commands_run_in_the_background() {
+    let mock = MockScheduler::with_entries(vec![entry("Backup", true)]);
+    let binding = Macro { key: 'x', name: Some("Ship".to_string()), steps: Vec::new(), command: Some("cat > /dev/null; sleep 0.3; echo shipped $CRON_MANAGER_ENTRY".to_string()) };
+    let config = Config { save_debounce_ms: 0, macros: vec![binding], ..Config::default() };
+    let mut app = App::new(Storage::with_scheduler(Box::new(mock)), config).unwrap();
+
+    let started = std::time::Instant::now();
+    app.run_macro('x').unwrap();
+    assert!(started.elapsed() < std::time::Duration::from_millis(300));
+    assert!(app.macro_run.is_some());
+    while app.macro_run.is_some() {
+        app.on_tick().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    assert_eq!(app.message.as_deref(), Some("Macro 'Ship': shipped Backup"));
+}
+
+#[test]
+fn test_bulk_edits_are_written_where_only_the_user_can_read_them() {
+    let dir = std::env::temp_dir().join(format!("cron-manager-bulk-edit-{}", std::process::id()));
+    let mock = MockScheduler::with_entries(vec![entry("Backup", true)]);
+    let config = Config { save_debounce_ms: 0, data_dir: Some(dir.clone()), ..Config::default() };
+    let mut app = App::new(Storage::with_scheduler(Box::new(mock)), config).unwrap();
+
+    app.start_bulk_edit();
+    let path = app.editor_request.clone().unwrap();
+    assert!(path.starts_with(&dir));
+    assert!(std::fs::read_to_string(&path).unwrap().contains("/bin/true"));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_a_damaged_local_file_offers_the_newest_backup() {
+    let dir = std::env::temp_dir().join(format!("cron-manager-recovery-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let config = Config { save_debounce_ms: 0, import_from_system: false, data_dir: Some(dir.clone()), ..Config::default() };
+    let storage = || Storage::for_backend(BackendKind::File, &config).unwrap();
+    storage().save(&[entry("Backup", true), entry("Report", true)]).unwrap();
+
+    // Cut off mid-write: the last entry lost its job line
+    let path = paths::storage_file(Some(&dir)).unwrap();
+    let saved = std::fs::read_to_string(&path).unwrap();
+    std::fs::write(&path, &saved[..saved.rfind("0 * * * *").unwrap()]).unwrap();
+    let mut app = App::new(storage(), config.clone()).unwrap();
+    assert_eq!(app.input_mode, InputMode::BrowsingBackups);
+    assert_eq!(app.entries.len(), 1);
+    app.confirm_rollback(true).unwrap();
+    assert_eq!(storage().load().unwrap(), [entry("Backup", true), entry("Report", true)]);
+    assert_eq!(storage().damage(), None);
+
+    // A hand-edited file without a final newline is read as it is
+    std::fs::write(&path, "MAILTO=me@example.com\n# NAME: Backup\n0 * * * * /bin/true > /dev/null").unwrap();
+    let mut app = App::new(storage(), config).unwrap();
+    assert_eq!(app.input_mode, InputMode::Normal);
+    app.toggle_enabled().unwrap();
+    assert!(std::fs::read_to_string(&path).unwrap().starts_with("MAILTO=me@example.com\n"));
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_moves_keep_hidden_entries_in_place_and_stop_at_environment_lines() {
+    let tagged = |name: &str| CronEntry { tags: vec!["nightly".to_string()], ..entry(name, true) };
+    let mock = MockScheduler::with_entries(vec![tagged("A"), entry("B", true), entry("C", true), tagged("D"), entry("E", true)]);
+    let mut app = app(&mock);
+    app.tag_filter = Some("nightly".to_string());
+
+    // D is the next row shown: A goes right after it, B and C stay in order
+    app.move_entry(true).unwrap();
+    let names = |entries: &[CronEntry]| entries.iter().map(|e| e.name.clone()).collect::<Vec<_>>();
+    assert_eq!(names(&mock.entries()), ["B", "C", "D", "A", "E"]);
+    assert_eq!(app.entries[app.selected_index].name, "A");
+    app.move_entry(false).unwrap();
+    assert_eq!(names(&mock.entries()), ["B", "C", "A", "D", "E"]);
+
+    let dir = std::env::temp_dir().join(format!("cron-manager-move-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let config = Config { save_debounce_ms: 0, import_from_system: false, data_dir: Some(dir.clone()), ..Config::default() };
+    let path = paths::storage_file(Some(&dir)).unwrap();
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(&path, "# NAME: A\n0 * * * * /bin/a\nMAILTO=ops@example.com\n# NAME: B\n0 * * * * /bin/b\n").unwrap();
+    let mut app = App::new(Storage::for_backend(BackendKind::File, &config).unwrap(), config).unwrap();
+    app.move_entry(true).unwrap();
+    assert_eq!(names(&app.entries), ["A", "B"]);
+    assert!(app.message.as_deref().unwrap_or_default().contains("MAILTO=ops@example.com"));
+    assert!(std::fs::read_to_string(&path).unwrap().starts_with("# NAME: A\n"));
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_rolling_back_to_an_older_backup() {
+    let dir = std::env::temp_dir().join(format!("cron-manager-rollback-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let mock = MockScheduler::with_entries(vec![entry("Backup", true)]);
+    let storage = Storage::with_scheduler(Box::new(mock.clone())).with_backups(BackupStore::new(dir.clone(), 10));
+    let mut app = App::new(storage, Config { save_debounce_ms: 0, ..Config::default() }).unwrap();
+    app.toggle_enabled().unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    app.entries.push(entry("Report", true));
+    app.save().unwrap();
+
+    app.open_backups();
+    assert_eq!(app.input_mode, InputMode::BrowsingBackups);
+    app.move_backup_selection(true);
+    let changes = |app: &App| {
+        let (_, records) = app.backup_browser.as_ref().unwrap().diff.clone().unwrap();
+        records.into_iter().map(|r| (r.entry, r.kind)).collect::<Vec<_>>()
+    };
+    app.diff_backup(BackupDiff::Current);
+    assert_eq!(changes(&app), [("Report".to_string(), ChangeKind::Added)]);
+    // The oldest snapshot is compared with nothing
+    app.diff_backup(BackupDiff::Previous);
+    assert_eq!(changes(&app), [("Backup".to_string(), ChangeKind::Added)]);
+
+    app.request_rollback();
+    app.confirm_rollback(true).unwrap();
+    assert_eq!(app.input_mode, InputMode::Normal);
+    assert_eq!(mock.saves().last().unwrap(), &[entry("Backup", false)]);
+    assert_eq!(mock.entries(), [entry("Backup", false)]);
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_strict_validation_keeps_invalid_schedules_out() {
+    let mock = MockScheduler::new();
+    let config = Config { save_debounce_ms: 0, strict_validation: true, ..Config::default() };
+    let mut strict = App::new(Storage::with_scheduler(Box::new(mock.clone())), config).unwrap();
+    let add = |app: &mut App, name: &str, schedule: &str| {
+        app.start_add_entry();
+        app.set_input(name);
+        app.confirm_input().unwrap();
+        app.set_input(schedule);
+        app.confirm_input().unwrap();
+    };
+
+    add(&mut strict, "Report", "61 * * * *");
+    assert_eq!(strict.input_mode, InputMode::AddingSchedule);
+    assert_eq!(strict.input_buffer, "61 * * * *");
+    assert!(strict.message.as_deref().unwrap_or_default().starts_with("Invalid schedule:"));
+    assert!(mock.saves().is_empty());
+
+    // Without strict mode the entry is added, and shown as invalid
+    let mut app = app(&mock);
+    add(&mut app, "Report", "61 * * * *");
+    assert_eq!(app.input_mode, InputMode::AddingCommand);
+    app.set_input("/bin/report > /dev/null");
+    app.confirm_input().unwrap();
+    assert_eq!(mock.entries().len(), 1);
+    assert_eq!(mock.entries()[0].schedule, "61 * * * *");
+}